@@ -0,0 +1,282 @@
+use crate::entity_extractor::{EntityType, ExtractionResult};
+use crate::error::{GraphError, Result};
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// File layout for [`StandoffAnnotations`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationFormat {
+    /// brat `.ann`: `T` lines for mentions, `R` lines for relations; the text goes in a
+    /// `.txt` file of the same name
+    Brat,
+    /// One JSON document holding the text, mentions and relations
+    Json,
+}
+
+impl AnnotationFormat {
+    /// `.ann` is brat, `.json` is JSON
+    pub fn from_path(path: &str) -> Result<Self> {
+        match Path::new(path).extension().and_then(|ext| ext.to_str()).map(str::to_lowercase).as_deref() {
+            Some("ann") => Ok(Self::Brat),
+            Some("json") => Ok(Self::Json),
+            _ => Err(GraphError::Export(format!(
+                "Annotation file {} should end in .ann (brat) or .json",
+                path
+            ))),
+        }
+    }
+}
+
+/// One entity mention: a span of the original text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MentionAnnotation {
+    /// `T1`, `T2`, ... in text order
+    pub id: String,
+    /// Id of the extracted entity
+    pub entity_id: String,
+    /// Entity type, e.g. `Person`
+    pub label: String,
+    /// Character (not byte) offsets, end exclusive
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+/// A relationship between two mentions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelationAnnotation {
+    /// `R1`, `R2`, ...
+    pub id: String,
+    pub label: String,
+    /// Mention id of the source entity
+    pub arg1: String,
+    /// Mention id of the target entity
+    pub arg2: String,
+}
+
+/// An extraction as standoff annotations over the original text, for loading into
+/// annotation tools and as training data for NER models.
+///
+/// Extraction positions refer to cleaned sentences, so mentions are found again in the
+/// original text: every whole-word, case-insensitive occurrence of an entity's name,
+/// longer names first, without overlaps. A relationship links the closest pair of its
+/// entities' mentions, preferring pairs in the same sentence. Concepts are not annotated.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StandoffAnnotations {
+    pub text: String,
+    pub mentions: Vec<MentionAnnotation>,
+    pub relations: Vec<RelationAnnotation>,
+}
+
+impl StandoffAnnotations {
+    pub fn from_extraction(text: &str, extraction: &ExtractionResult) -> Self {
+        let mut entities: Vec<_> = extraction.entities.iter().collect();
+        entities.sort_by_key(|entity| std::cmp::Reverse(entity.name.len()));
+
+        // Byte spans first, converted to characters once the spans are final
+        let mut spans: Vec<(usize, usize, &str, String)> = Vec::new();
+        for entity in entities {
+            let Some(pattern) = mention_pattern(&entity.name) else {
+                continue;
+            };
+            let Ok(regex) = RegexBuilder::new(&pattern).case_insensitive(true).build() else {
+                continue;
+            };
+            for found in regex.find_iter(text) {
+                let overlaps = spans.iter().any(|(start, end, _, _)| found.start() < *end && *start < found.end());
+                if !overlaps {
+                    spans.push((found.start(), found.end(), entity.id.as_str(), type_label(&entity.entity_type)));
+                }
+            }
+        }
+        spans.sort_by_key(|(start, _, _, _)| *start);
+
+        let mentions: Vec<MentionAnnotation> = spans
+            .into_iter()
+            .enumerate()
+            .map(|(index, (start, end, entity_id, label))| MentionAnnotation {
+                id: format!("T{}", index + 1),
+                entity_id: entity_id.to_string(),
+                label,
+                start: text[..start].chars().count(),
+                end: text[..end].chars().count(),
+                text: text[start..end].to_string(),
+            })
+            .collect();
+
+        let chars: Vec<char> = text.chars().collect();
+        let mut by_entity: HashMap<&str, Vec<&MentionAnnotation>> = HashMap::new();
+        for mention in &mentions {
+            by_entity.entry(mention.entity_id.as_str()).or_default().push(mention);
+        }
+        let mut seen = HashSet::new();
+        let mut relations = Vec::new();
+        for relationship in &extraction.relationships {
+            let (Some(sources), Some(targets)) = (
+                by_entity.get(relationship.source_entity_id.as_str()),
+                by_entity.get(relationship.target_entity_id.as_str()),
+            ) else {
+                continue;
+            };
+            let closest = sources
+                .iter()
+                .flat_map(|source| targets.iter().map(move |target| (*source, *target)))
+                .min_by_key(|(source, target)| {
+                    let (first, second) = if source.start <= target.start { (source, target) } else { (target, source) };
+                    let between = &chars[first.end.min(second.start)..second.start];
+                    let crosses_sentence = between.iter().any(|c| matches!(c, '.' | '!' | '?' | '\n'));
+                    (crosses_sentence, second.start - first.start)
+                });
+            let Some((source, target)) = closest else {
+                continue;
+            };
+            let label = brat_name(&relationship.label);
+            if source.id != target.id && seen.insert((label.clone(), source.id.clone(), target.id.clone())) {
+                relations.push(RelationAnnotation {
+                    id: format!("R{}", relations.len() + 1),
+                    label,
+                    arg1: source.id.clone(),
+                    arg2: target.id.clone(),
+                });
+            }
+        }
+
+        Self { text: text.to_string(), mentions, relations }
+    }
+
+    /// The brat `.ann` file; offsets refer to `text` saved as the matching `.txt`
+    pub fn to_brat(&self) -> String {
+        let mut ann = String::new();
+        for mention in &self.mentions {
+            ann.push_str(&format!(
+                "{}\t{} {} {}\t{}\n",
+                mention.id,
+                brat_name(&mention.label),
+                mention.start,
+                mention.end,
+                mention.text
+            ));
+        }
+        for relation in &self.relations {
+            ann.push_str(&format!("{}\t{} Arg1:{} Arg2:{}\n", relation.id, relation.label, relation.arg1, relation.arg2));
+        }
+        ann
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Write in the format `path` implies. For brat the text is written next to the
+    /// `.ann` file as a `.txt` of the same name, which brat expects.
+    pub fn write(&self, path: &str) -> Result<()> {
+        match AnnotationFormat::from_path(path)? {
+            AnnotationFormat::Brat => {
+                std::fs::write(path, self.to_brat())?;
+                std::fs::write(Path::new(path).with_extension("txt"), &self.text)?;
+            }
+            AnnotationFormat::Json => std::fs::write(path, self.to_json()?)?,
+        }
+        Ok(())
+    }
+}
+
+/// Whole-word, whitespace- and punctuation-tolerant pattern for `name`, so the cleaned
+/// "Diana s" still finds "Diana's"; mentions never span a line break
+fn mention_pattern(name: &str) -> Option<String> {
+    let words: Vec<String> = name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(regex::escape)
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+    Some(format!(r"\b{}\b", words.join(r"[\W_&&[^\n]]+")))
+}
+
+/// `Other("product")` is `product`, the rest their variant name
+fn type_label(entity_type: &EntityType) -> String {
+    match entity_type {
+        EntityType::Other(name) => name.clone(),
+        other => format!("{:?}", other),
+    }
+}
+
+/// brat type names cannot contain spaces: "works at" becomes `works_at`
+fn brat_name(label: &str) -> String {
+    let name: String = label
+        .trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    if name.is_empty() { "related".to_string() } else { name }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::Pipeline;
+
+    #[tokio::test]
+    async fn test_mentions_use_character_offsets_into_the_original_text() {
+        let text = "Alice Smith is connected to Diana.\n\nZoë met Diana's friend. Alice Smith left.";
+        let output = Pipeline::new().with_text(text).run_detailed().await.unwrap();
+        let annotations = StandoffAnnotations::from_extraction(text, &output.extraction);
+
+        let chars: Vec<char> = text.chars().collect();
+        for mention in &annotations.mentions {
+            let span: String = chars[mention.start..mention.end].iter().collect();
+            assert_eq!(span, mention.text);
+        }
+        let texts: Vec<&str> = annotations.mentions.iter().map(|mention| mention.text.as_str()).collect();
+        assert_eq!(texts.iter().filter(|text| **text == "Alice Smith").count(), 2);
+        assert_eq!(texts.iter().filter(|text| text.starts_with("Diana")).count(), 2, "{:?}", texts);
+        assert!(!texts.iter().any(|text| text.contains('\n')));
+
+        // The relationship links the pair of mentions in the same sentence, not the closest
+        let relation = annotations.relations.iter().find(|relation| relation.label == "is_connected_to").unwrap();
+        let arg = |id: &str| annotations.mentions.iter().find(|mention| mention.id == id).unwrap();
+        assert_eq!(arg(&relation.arg1).text, "Alice Smith");
+        assert_eq!((arg(&relation.arg2).text.as_str(), arg(&relation.arg1).start), ("Diana", 0));
+    }
+
+    #[test]
+    fn test_brat_lines() {
+        let annotations = StandoffAnnotations {
+            text: "Alice works at Initech".to_string(),
+            mentions: vec![
+                MentionAnnotation {
+                    id: "T1".to_string(),
+                    entity_id: "a".to_string(),
+                    label: "Person".to_string(),
+                    start: 0,
+                    end: 5,
+                    text: "Alice".to_string(),
+                },
+                MentionAnnotation {
+                    id: "T2".to_string(),
+                    entity_id: "b".to_string(),
+                    label: "software company".to_string(),
+                    start: 15,
+                    end: 22,
+                    text: "Initech".to_string(),
+                },
+            ],
+            relations: vec![RelationAnnotation {
+                id: "R1".to_string(),
+                label: brat_name("works at"),
+                arg1: "T1".to_string(),
+                arg2: "T2".to_string(),
+            }],
+        };
+        assert_eq!(
+            annotations.to_brat(),
+            "T1\tPerson 0 5\tAlice\nT2\tsoftware_company 15 22\tInitech\nR1\tworks_at Arg1:T1 Arg2:T2\n"
+        );
+        assert_eq!(AnnotationFormat::from_path("out/doc.ANN").unwrap(), AnnotationFormat::Brat);
+        assert!(AnnotationFormat::from_path("doc.txt").is_err());
+    }
+}
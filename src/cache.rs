@@ -0,0 +1,198 @@
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Settings for the on-disk LLM response cache
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmCacheConfig {
+    pub enabled: bool,
+    /// Directory holding one JSON file per cached response
+    pub directory: String,
+    /// Entries older than this many seconds are ignored and refetched; `None` keeps them forever
+    pub ttl_seconds: Option<u64>,
+}
+
+impl Default for LlmCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            directory: ".msg_net_cache/llm".to_string(),
+            ttl_seconds: None,
+        }
+    }
+}
+
+/// What an LLM answer depends on, and so what a cached answer is keyed by
+#[derive(Debug, Clone, Serialize)]
+pub struct LlmRequest<'a> {
+    pub endpoint: &'a str,
+    pub model: &'a str,
+    pub prompt: &'a str,
+    /// The other request fields shaping the answer, such as the output schema (`format`)
+    /// and the context window (`options`)
+    pub parameters: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    model: String,
+    created_at: u64,
+    response: String,
+}
+
+/// On-disk cache of LLM responses keyed by a hash of the request: endpoint, model,
+/// prompt and the parameters shaping the answer.
+///
+/// Deep analysis sends the same prompts on every run over unchanged text, so caching
+/// the responses makes re-running for styling or export changes nearly instant.
+/// Read and write failures are never fatal to extraction: a corrupt entry is a miss.
+#[derive(Debug, Clone)]
+pub struct LlmCache {
+    directory: PathBuf,
+    ttl: Option<Duration>,
+}
+
+impl LlmCache {
+    pub fn new(directory: impl Into<PathBuf>, ttl: Option<Duration>) -> Self {
+        Self {
+            directory: directory.into(),
+            ttl,
+        }
+    }
+
+    /// Build a cache from config, or `None` when caching is disabled
+    pub fn from_config(config: &LlmCacheConfig) -> Option<Self> {
+        config
+            .enabled
+            .then(|| Self::new(&config.directory, config.ttl_seconds.map(Duration::from_secs)))
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Cache key for a request (hex-encoded SHA-256 of its JSON, with object keys in
+    /// order so equal parameters always hash alike)
+    pub fn key(request: &LlmRequest) -> String {
+        let json = serde_json::to_value(request).map(|value| value.to_string()).unwrap_or_default();
+        Sha256::digest(json.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    fn entry_path(&self, request: &LlmRequest) -> PathBuf {
+        self.directory.join(format!("{}.json", Self::key(request)))
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0)
+    }
+
+    /// Look up a cached response, ignoring entries past their TTL
+    pub fn get(&self, request: &LlmRequest) -> Option<String> {
+        let content = fs::read_to_string(self.entry_path(request)).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&content).ok()?;
+
+        if let Some(ttl) = self.ttl {
+            if Self::now().saturating_sub(entry.created_at) >= ttl.as_secs() {
+                return None;
+            }
+        }
+
+        Some(entry.response)
+    }
+
+    /// Store a response, creating the cache directory if needed
+    pub fn put(&self, request: &LlmRequest, response: &str) -> Result<()> {
+        fs::create_dir_all(&self.directory)?;
+
+        let entry = CacheEntry {
+            model: request.model.to_string(),
+            created_at: Self::now(),
+            response: response.to_string(),
+        };
+        fs::write(self.entry_path(request), serde_json::to_string(&entry)?)?;
+        Ok(())
+    }
+
+    /// Delete every cached response, returning how many were removed
+    pub fn clear(&self) -> Result<usize> {
+        if !self.directory.exists() {
+            return Ok(0);
+        }
+
+        let mut removed = 0;
+        for entry in fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                fs::remove_file(&path)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn request<'a>(model: &'a str, prompt: &'a str) -> LlmRequest<'a> {
+        LlmRequest {
+            endpoint: "http://localhost:11434/api/generate",
+            model,
+            prompt,
+            parameters: serde_json::json!({ "format": "json", "options": { "num_ctx": 8192 } }),
+        }
+    }
+
+    #[test]
+    fn test_cache_round_trip_is_keyed_by_the_whole_request() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let cache = LlmCache::new(temp_dir.path(), None);
+
+        assert_eq!(cache.get(&request("llama3.2", "prompt")), None);
+        cache.put(&request("llama3.2", "prompt"), "response").expect("Failed to write cache");
+
+        assert_eq!(cache.get(&request("llama3.2", "prompt")).as_deref(), Some("response"));
+        assert_eq!(cache.get(&request("mistral", "prompt")), None);
+        assert_eq!(cache.get(&request("llama3.2", "other prompt")), None);
+
+        // Any other schema, context window or server may answer differently
+        let schema = serde_json::json!({ "type": "array", "items": { "type": "object" } });
+        let changed = [
+            LlmRequest { parameters: serde_json::json!({ "format": schema, "options": { "num_ctx": 8192 } }), ..request("llama3.2", "prompt") },
+            LlmRequest { parameters: serde_json::json!({ "format": "json", "options": { "num_ctx": 4096 } }), ..request("llama3.2", "prompt") },
+            LlmRequest { endpoint: "http://gpu-box:11434/api/generate", ..request("llama3.2", "prompt") },
+        ];
+        for changed in &changed {
+            assert_eq!(cache.get(changed), None, "{:?}", changed);
+        }
+        // Parameters are compared by content, not by the order their keys were written in
+        let reordered: serde_json::Value = serde_json::from_str(r#"{"options": {"num_ctx": 8192}, "format": "json"}"#).unwrap();
+        assert_eq!(
+            cache.get(&LlmRequest { parameters: reordered, ..request("llama3.2", "prompt") }).as_deref(),
+            Some("response")
+        );
+
+        assert_eq!(cache.clear().expect("Failed to clear cache"), 1);
+        assert_eq!(cache.get(&request("llama3.2", "prompt")), None);
+    }
+
+    #[test]
+    fn test_expired_entries_are_misses() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let cache = LlmCache::new(temp_dir.path(), Some(Duration::from_secs(0)));
+
+        cache.put(&request("llama3.2", "prompt"), "response").expect("Failed to write cache");
+        assert_eq!(cache.get(&request("llama3.2", "prompt")), None);
+
+        let no_ttl = LlmCache::new(temp_dir.path(), None);
+        assert_eq!(no_ttl.get(&request("llama3.2", "prompt")).as_deref(), Some("response"));
+    }
+}
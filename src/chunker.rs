@@ -0,0 +1,292 @@
+use serde::{Deserialize, Serialize};
+
+/// How pattern extraction (no LLM) finds entities
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityDetector {
+    /// `entity_patterns` regexes (by default, runs of capitalized words)
+    #[default]
+    Patterns,
+    /// Noun phrases from a part-of-speech tagger and chunker, whatever their capitalization
+    NounPhrases,
+    /// Spans tagged by a local ONNX token-classification model, see [`crate::ner_model`]
+    NerModel,
+}
+
+/// Coarse part-of-speech tags
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PosTag {
+    Determiner,
+    Pronoun,
+    Preposition,
+    Conjunction,
+    /// Forms of "be", "have", "do" and modals
+    Auxiliary,
+    Verb,
+    Adverb,
+    Adjective,
+    Noun,
+    ProperNoun,
+    Number,
+    Punctuation,
+}
+
+/// A word or punctuation mark with its byte range in the sentence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaggedToken<'a> {
+    pub text: &'a str,
+    pub tag: PosTag,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A chunked noun phrase without its determiner ("the analytics module" -> "analytics module")
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NounPhrase<'a> {
+    pub text: &'a str,
+    pub start: usize,
+    pub end: usize,
+    /// Whether the head is a proper noun ("Acme Corp")
+    pub proper: bool,
+}
+
+/// Longest noun phrase kept, in words; longer runs keep their last words, the head being last
+pub const MAX_PHRASE_WORDS: usize = 4;
+
+const DETERMINERS: &[&str] = &[
+    "the", "a", "an", "this", "that", "these", "those", "my", "your", "his", "her", "its", "our", "their", "some",
+    "any", "each", "every", "all", "no", "another", "both", "either", "neither", "such", "which", "what", "whose",
+];
+const PRONOUNS: &[&str] = &[
+    "i", "you", "he", "she", "it", "we", "they", "me", "him", "us", "them", "who", "whom", "myself", "itself",
+    "themselves", "something", "anything", "nothing", "everything", "someone", "anyone", "everyone", "there",
+    "here", "one",
+];
+const PREPOSITIONS: &[&str] = &[
+    "of", "in", "on", "at", "by", "for", "with", "from", "to", "into", "onto", "about", "over", "under", "between",
+    "through", "during", "before", "after", "against", "without", "within", "across", "behind", "via", "per",
+    "among", "around", "toward", "towards", "upon", "like", "than", "since", "until", "as",
+];
+const CONJUNCTIONS: &[&str] = &[
+    "and", "or", "but", "nor", "so", "yet", "if", "because", "while", "when", "where", "although", "though",
+    "whether", "unless", "then", "also",
+];
+const AUXILIARIES: &[&str] = &[
+    "is", "are", "was", "were", "be", "been", "being", "am", "has", "have", "had", "having", "do", "does", "did",
+    "will", "would", "shall", "should", "can", "could", "may", "might", "must", "not",
+];
+/// Base forms of verbs common enough in technical and business prose to tag by lexicon;
+/// their "-s", "-ed" and "-ing" forms are recognized too
+const VERBS: &[&str] = &[
+    "use", "manage", "send", "receive", "call", "store", "read", "write", "create", "build", "run", "handle",
+    "process", "depend", "own", "lead", "report", "work", "provide", "require", "need", "contain", "include",
+    "connect", "support", "cause", "trigger", "generate", "return", "update", "validate", "make", "take",
+    "give", "get", "find", "show", "help", "keep", "move", "join", "leave", "start", "stop", "pay", "buy", "sell",
+    "fail", "feed", "load", "save", "parse", "render", "export", "import", "fetch", "emit", "publish",
+    "subscribe", "notify", "check", "track", "monitor", "schedule", "deploy", "host", "serve", "implement",
+    "replace", "allow", "enable", "prevent", "block", "affect", "produce", "consume", "hire", "found", "acquire",
+    "meet", "talk", "speak", "tell", "say", "know", "think", "see", "want", "like", "live", "visit", "become",
+    "seem", "go", "come", "drive", "aggregate", "compute", "collect", "forward", "route", "retry", "cache",
+];
+/// Irregular verb forms not derived from `VERBS` by suffix
+const IRREGULAR_VERBS: &[&str] = &[
+    "sent", "ran", "built", "wrote", "written", "led", "made", "took", "taken", "gave", "given", "got", "found",
+    "kept", "left", "paid", "bought", "sold", "fed", "met", "spoke", "told", "said", "knew", "thought", "saw",
+    "seen", "became", "went", "gone", "came", "drove", "driven",
+];
+const ADJECTIVE_SUFFIXES: &[&str] = &["ous", "ful", "ive", "able", "ible", "less", "ish", "ary"];
+
+/// Tag a sentence's words with a lexicon of closed-class words and common verbs,
+/// suffix rules and a few context rules. Good enough to find noun phrases; not a
+/// general-purpose tagger.
+pub fn tag(sentence: &str) -> Vec<TaggedToken<'_>> {
+    let mut tokens: Vec<TaggedToken> = tokenize(sentence)
+        .into_iter()
+        .enumerate()
+        .map(|(index, (start, end))| {
+            let text = &sentence[start..end];
+            TaggedToken { text, tag: lexical_tag(text, index == 0), start, end }
+        })
+        .collect();
+
+    // Context: after a determiner, adjective or preposition a "verb" is a noun ("the
+    // report", "of use") and an "-ly" word an adjective ("the weekly report"); an unknown
+    // word right after a pronoun, modal or "to" is a verb ("they ingest", "will ingest",
+    // "to ingest"), and so is "-ed" right after a noun
+    for index in 1..tokens.len() {
+        let previous = &tokens[index - 1];
+        let lower = tokens[index].text.to_lowercase();
+        if matches!(previous.tag, PosTag::Determiner | PosTag::Adjective | PosTag::Preposition) {
+            match tokens[index].tag {
+                PosTag::Verb => tokens[index].tag = PosTag::Noun,
+                PosTag::Adverb if previous.tag != PosTag::Preposition => tokens[index].tag = PosTag::Adjective,
+                _ => {}
+            }
+            continue;
+        }
+        let after_verb_slot = previous.tag == PosTag::Pronoun
+            || (previous.tag == PosTag::Auxiliary && !is_be_or_have(&previous.text.to_lowercase()))
+            || previous.text.eq_ignore_ascii_case("to");
+        let past_after_noun = matches!(previous.tag, PosTag::Noun | PosTag::ProperNoun) && lower.ends_with("ed");
+        if tokens[index].tag == PosTag::Noun && (after_verb_slot || past_after_noun) {
+            tokens[index].tag = PosTag::Verb;
+        }
+    }
+    tokens
+}
+
+/// Maximal runs of adjectives and nouns ending in a noun, after any determiner
+pub fn noun_phrases(sentence: &str) -> Vec<NounPhrase<'_>> {
+    let tokens = tag(sentence);
+    let mut phrases = Vec::new();
+    let mut index = 0;
+    while index < tokens.len() {
+        if !matches!(tokens[index].tag, PosTag::Adjective | PosTag::Noun | PosTag::ProperNoun) {
+            index += 1;
+            continue;
+        }
+        let run_start = index;
+        while index < tokens.len() && matches!(tokens[index].tag, PosTag::Adjective | PosTag::Noun | PosTag::ProperNoun) {
+            index += 1;
+        }
+        // Trailing adjectives are predicates ("the queue is full"), not part of the phrase
+        let Some(head) = (run_start..index).rev().find(|&i| tokens[i].tag != PosTag::Adjective) else {
+            continue;
+        };
+        let first = run_start.max((head + 1).saturating_sub(MAX_PHRASE_WORDS));
+        phrases.push(NounPhrase {
+            text: &sentence[tokens[first].start..tokens[head].end],
+            start: tokens[first].start,
+            end: tokens[head].end,
+            proper: tokens[head].tag == PosTag::ProperNoun,
+        });
+    }
+    phrases
+}
+
+/// Byte ranges of words (letters, digits, hyphens, apostrophes) and single punctuation marks
+fn tokenize(sentence: &str) -> Vec<(usize, usize)> {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '-' || c == '\'' || c == '_';
+    let mut tokens = Vec::new();
+    let mut word_start = None;
+    for (position, c) in sentence.char_indices() {
+        if is_word_char(c) {
+            word_start.get_or_insert(position);
+            continue;
+        }
+        if let Some(start) = word_start.take() {
+            tokens.push((start, position));
+        }
+        if !c.is_whitespace() {
+            tokens.push((position, position + c.len_utf8()));
+        }
+    }
+    if let Some(start) = word_start {
+        tokens.push((start, sentence.len()));
+    }
+    tokens
+}
+
+fn lexical_tag(word: &str, sentence_start: bool) -> PosTag {
+    let lower = word.to_lowercase();
+    let first = word.chars().next().unwrap_or(' ');
+    if !first.is_alphanumeric() {
+        return PosTag::Punctuation;
+    }
+    if word.chars().all(|c| c.is_ascii_digit() || c == '.' || c == ',') {
+        return PosTag::Number;
+    }
+    let closed_classes = [
+        (DETERMINERS, PosTag::Determiner),
+        (PRONOUNS, PosTag::Pronoun),
+        (PREPOSITIONS, PosTag::Preposition),
+        (CONJUNCTIONS, PosTag::Conjunction),
+        (AUXILIARIES, PosTag::Auxiliary),
+    ];
+    if let Some((_, tag)) = closed_classes.iter().find(|(words, _)| words.contains(&lower.as_str())) {
+        return *tag;
+    }
+    // A single letter left by text cleaning ("Diana's" -> "Diana s") ends the phrase
+    if lower.chars().count() == 1 && first.is_lowercase() {
+        return PosTag::Conjunction;
+    }
+    if first.is_uppercase() && !sentence_start {
+        return PosTag::ProperNoun;
+    }
+    if is_known_verb(&lower) {
+        return PosTag::Verb;
+    }
+    if lower.ends_with("ly") && lower.len() > 4 {
+        return PosTag::Adverb;
+    }
+    if ADJECTIVE_SUFFIXES.iter().any(|suffix| lower.ends_with(suffix) && lower.len() > suffix.len() + 2) {
+        return PosTag::Adjective;
+    }
+    PosTag::Noun
+}
+
+fn is_known_verb(lower: &str) -> bool {
+    if IRREGULAR_VERBS.contains(&lower) || VERBS.contains(&lower) {
+        return true;
+    }
+    let stems = [
+        lower.strip_suffix("ies").map(|stem| format!("{}y", stem)),
+        lower.strip_suffix("es").map(str::to_string),
+        lower.strip_suffix('s').map(str::to_string),
+        lower.strip_suffix("ed").map(str::to_string),
+        lower.strip_suffix('d').map(str::to_string),
+        lower.strip_suffix("ied").map(|stem| format!("{}y", stem)),
+        lower.strip_suffix("ing").map(str::to_string),
+        lower.strip_suffix("ing").map(|stem| format!("{}e", stem)),
+    ];
+    stems.into_iter().flatten().any(|stem| {
+        VERBS.contains(&stem.as_str())
+            // "stopped", "running": doubled final consonant
+            || (stem.len() > 2 && stem.as_bytes()[stem.len() - 1] == stem.as_bytes()[stem.len() - 2]
+                && VERBS.contains(&&stem[..stem.len() - 1]))
+    })
+}
+
+fn is_be_or_have(lower: &str) -> bool {
+    matches!(lower, "is" | "are" | "was" | "were" | "be" | "been" | "being" | "am" | "has" | "have" | "had" | "having")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts<'a>(phrases: &[NounPhrase<'a>]) -> Vec<&'a str> {
+        phrases.iter().map(|phrase| phrase.text).collect()
+    }
+
+    #[test]
+    fn test_lowercase_noun_phrases_are_chunked() {
+        let phrases = noun_phrases("The analytics module sends the weekly report to the billing service.");
+        assert_eq!(texts(&phrases), vec!["analytics module", "weekly report", "billing service"]);
+        assert!(phrases.iter().all(|phrase| !phrase.proper));
+
+        let phrases = noun_phrases("Yesterday Alice deployed the new payment gateway for Acme Corp.");
+        assert_eq!(texts(&phrases), vec!["Yesterday Alice", "new payment gateway", "Acme Corp"]);
+        assert!(phrases[2].proper);
+    }
+
+    #[test]
+    fn test_tagger_uses_lexicon_suffixes_and_context() {
+        let tags: Vec<PosTag> = tag("They ingest logs and the queue is full").iter().map(|token| token.tag).collect();
+        assert_eq!(
+            tags,
+            vec![
+                PosTag::Pronoun,
+                PosTag::Verb,
+                PosTag::Noun,
+                PosTag::Conjunction,
+                PosTag::Determiner,
+                PosTag::Noun,
+                PosTag::Auxiliary,
+                PosTag::Noun,
+            ]
+        );
+        // A trailing adjective never heads a phrase
+        assert_eq!(texts(&noun_phrases("the queue is reliable")), vec!["queue"]);
+    }
+}
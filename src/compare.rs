@@ -0,0 +1,373 @@
+//! Pattern and LLM extraction of the same text, side by side.
+//!
+//! The `compare-methods` command runs both methods and merges their results into one
+//! graph colored by agreement, with counts of what each method found on its own, so a
+//! user can judge whether the LLM finds enough more to be worth its latency.
+
+use crate::config::GraphConfig;
+use crate::entity_extractor::{Entity, ExtractionMetadata, ExtractionResult, Relationship};
+use crate::error::Result;
+use crate::graph_builder::{GraphBuilder, InteractiveGraph};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Overlay color of what both methods found
+pub const AGREEMENT_COLOR: &str = "#2E7D32";
+/// Overlay color of what only the LLM found
+pub const LLM_ONLY_COLOR: &str = "#1565C0";
+/// Overlay color of what only the patterns found
+pub const PATTERNS_ONLY_COLOR: &str = "#EF6C00";
+
+/// Which extraction method found an entity or relationship
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FoundBy {
+    Both,
+    LlmOnly,
+    PatternsOnly,
+}
+
+impl FoundBy {
+    fn new(by_patterns: bool, by_llm: bool) -> Self {
+        match (by_patterns, by_llm) {
+            (true, true) => FoundBy::Both,
+            (false, _) => FoundBy::LlmOnly,
+            (true, false) => FoundBy::PatternsOnly,
+        }
+    }
+
+    pub fn color(&self) -> &'static str {
+        match self {
+            FoundBy::Both => AGREEMENT_COLOR,
+            FoundBy::LlmOnly => LLM_ONLY_COLOR,
+            FoundBy::PatternsOnly => PATTERNS_ONLY_COLOR,
+        }
+    }
+
+    /// Value of the `found_by` attribute set on overlay nodes
+    pub fn label(&self) -> &'static str {
+        match self {
+            FoundBy::Both => "both",
+            FoundBy::LlmOnly => "LLM only",
+            FoundBy::PatternsOnly => "patterns only",
+        }
+    }
+}
+
+/// How many entities (or relationships) each method found, split by agreement
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MethodCounts {
+    pub both: usize,
+    pub llm_only: usize,
+    pub patterns_only: usize,
+}
+
+impl MethodCounts {
+    fn add(&mut self, found_by: FoundBy) {
+        match found_by {
+            FoundBy::Both => self.both += 1,
+            FoundBy::LlmOnly => self.llm_only += 1,
+            FoundBy::PatternsOnly => self.patterns_only += 1,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.both + self.llm_only + self.patterns_only
+    }
+
+    /// Share of everything found that both methods found (Jaccard index); 1 when neither
+    /// found anything
+    pub fn agreement(&self) -> f64 {
+        match self.total() {
+            0 => 1.0,
+            total => self.both as f64 / total as f64,
+        }
+    }
+}
+
+impl fmt::Display for MethodCounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} both, {} LLM only, {} patterns only ({:.0}% agreement)",
+            self.both,
+            self.llm_only,
+            self.patterns_only,
+            self.agreement() * 100.0
+        )
+    }
+}
+
+/// Agreement statistics of a pattern run and an LLM run on the same text
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MethodComparison {
+    pub entities: MethodCounts,
+    pub relationships: MethodCounts,
+    pub pattern_method: String,
+    pub llm_method: String,
+    pub pattern_time_ms: u64,
+    pub llm_time_ms: u64,
+}
+
+impl fmt::Display for MethodComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Entities: {}", self.entities)?;
+        writeln!(f, "Relationships: {}", self.relationships)?;
+        write!(
+            f,
+            "Time: {} ms for {}, {} ms for {}",
+            self.pattern_time_ms, self.pattern_method, self.llm_time_ms, self.llm_method
+        )
+    }
+}
+
+/// Pattern and LLM extractions of one text merged into a single extraction, with each
+/// entity and relationship tagged by the method(s) that found it.
+///
+/// Entities match by name, ignoring case; relationships match by their endpoints in
+/// either direction, whatever their labels. Attributes and concepts are left out so the
+/// overlay shows only what the two methods are compared on.
+#[derive(Debug, Clone)]
+pub struct ComparisonOverlay {
+    pub extraction: ExtractionResult,
+    pub comparison: MethodComparison,
+    /// Who found each entity and relationship, by id in `extraction`
+    pub found_by: HashMap<String, FoundBy>,
+}
+
+impl ComparisonOverlay {
+    pub fn new(patterns: &ExtractionResult, llm: &ExtractionResult) -> Self {
+        let mut entities: Vec<Entity> = Vec::new();
+        let mut found_by = HashMap::new();
+        let mut entity_counts = MethodCounts::default();
+        for entity in patterns.entities.iter().chain(&llm.entities) {
+            if entities.iter().any(|kept| kept.name.eq_ignore_ascii_case(&entity.name)) {
+                continue;
+            }
+            let by_patterns = patterns.entities.iter().any(|other| other.name.eq_ignore_ascii_case(&entity.name));
+            let by_llm = llm.entities.iter().any(|other| other.name.eq_ignore_ascii_case(&entity.name));
+            let tag = FoundBy::new(by_patterns, by_llm);
+            entity_counts.add(tag);
+            found_by.insert(entity.id.clone(), tag);
+            entities.push(Entity {
+                attributes: Vec::new(),
+                ..entity.clone()
+            });
+        }
+
+        let pattern_pairs = endpoint_pairs(patterns);
+        let llm_pairs = endpoint_pairs(llm);
+        let entity_id = |name: &str| {
+            entities
+                .iter()
+                .find(|entity| entity.name.eq_ignore_ascii_case(name))
+                .map(|entity| entity.id.clone())
+        };
+        let mut relationships: Vec<Relationship> = Vec::new();
+        let mut seen: Vec<(String, String)> = Vec::new();
+        let mut relationship_counts = MethodCounts::default();
+        for (pair, relationship, source, target) in pattern_pairs.iter().chain(&llm_pairs) {
+            if seen.contains(pair) {
+                continue;
+            }
+            let (Some(source_id), Some(target_id)) = (entity_id(source), entity_id(target)) else {
+                continue;
+            };
+            seen.push(pair.clone());
+            let pattern_label = pattern_pairs.iter().find(|(other, ..)| other == pair).map(|(_, found, ..)| &found.label);
+            let llm_label = llm_pairs.iter().find(|(other, ..)| other == pair).map(|(_, found, ..)| &found.label);
+            let tag = FoundBy::new(pattern_label.is_some(), llm_label.is_some());
+            relationship_counts.add(tag);
+            found_by.insert(relationship.id.clone(), tag);
+            let label = match (pattern_label, llm_label) {
+                (Some(pattern), Some(llm)) if !pattern.eq_ignore_ascii_case(llm) => format!("{} / {}", pattern, llm),
+                _ => relationship.label.clone(),
+            };
+            relationships.push(Relationship {
+                source_entity_id: source_id,
+                target_entity_id: target_id,
+                label,
+                ..(*relationship).clone()
+            });
+        }
+
+        let comparison = MethodComparison {
+            entities: entity_counts,
+            relationships: relationship_counts,
+            pattern_method: patterns.metadata.extraction_method.clone(),
+            llm_method: llm.metadata.extraction_method.clone(),
+            pattern_time_ms: patterns.metadata.processing_time_ms,
+            llm_time_ms: llm.metadata.processing_time_ms,
+        };
+        let extraction = ExtractionResult {
+            metadata: ExtractionMetadata {
+                total_entities: entities.len(),
+                total_relationships: relationships.len(),
+                total_concepts: 0,
+                processing_time_ms: comparison.pattern_time_ms + comparison.llm_time_ms,
+                confidence_threshold: llm.metadata.confidence_threshold,
+                extraction_method: format!("Comparison of {} and {}", comparison.pattern_method, comparison.llm_method),
+            },
+            entities,
+            relationships,
+            concepts: Vec::new(),
+        };
+
+        Self {
+            extraction,
+            comparison,
+            found_by,
+        }
+    }
+
+    /// Build and lay out the overlay graph, coloring every node and edge by who found it.
+    /// The statistics are added to the provenance shown in the viewer's About section.
+    pub fn build_graph(&self, config: &GraphConfig, source_text: &str) -> Result<InteractiveGraph> {
+        let graph_builder = GraphBuilder::new(config.clone());
+        let mut graph = graph_builder.build_graph(&self.extraction, source_text)?;
+        graph_builder.apply_layout(&mut graph)?;
+
+        for node in &mut graph.nodes {
+            if let Some(found_by) = self.found_by.get(&node.id) {
+                node.color = found_by.color().to_string();
+                node.metadata.attributes.insert("found_by".to_string(), found_by.label().to_string());
+            }
+        }
+        for edge in &mut graph.edges {
+            if let Some(found_by) = self.found_by.get(&edge.id) {
+                edge.color = found_by.color().to_string();
+            }
+        }
+
+        let summary = &mut graph.metadata.provenance.config_summary;
+        summary.insert("comparison.entities".to_string(), self.comparison.entities.to_string());
+        summary.insert("comparison.relationships".to_string(), self.comparison.relationships.to_string());
+        summary.insert(
+            "comparison.time_ms".to_string(),
+            format!("{} patterns, {} LLM", self.comparison.pattern_time_ms, self.comparison.llm_time_ms),
+        );
+        summary.insert(
+            "comparison.colors".to_string(),
+            format!("{} both, {} LLM only, {} patterns only", AGREEMENT_COLOR, LLM_ONLY_COLOR, PATTERNS_ONLY_COLOR),
+        );
+        Ok(graph)
+    }
+}
+
+/// Each relationship with its endpoint names, keyed by the lowercased names in sorted
+/// order; the first relationship between a pair stands for it
+fn endpoint_pairs(result: &ExtractionResult) -> Vec<((String, String), &Relationship, String, String)> {
+    let names: HashMap<&str, &str> = result
+        .entities
+        .iter()
+        .map(|entity| (entity.id.as_str(), entity.name.as_str()))
+        .collect();
+    let mut pairs: Vec<((String, String), &Relationship, String, String)> = Vec::new();
+    for relationship in &result.relationships {
+        let (Some(source), Some(target)) = (
+            names.get(relationship.source_entity_id.as_str()),
+            names.get(relationship.target_entity_id.as_str()),
+        ) else {
+            continue;
+        };
+        let (a, b) = (source.to_lowercase(), target.to_lowercase());
+        let pair = if a <= b { (a, b) } else { (b, a) };
+        if !pairs.iter().any(|(existing, ..)| *existing == pair) {
+            pairs.push((pair, relationship, source.to_string(), target.to_string()));
+        }
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity_extractor::{EntityType, RelationshipType};
+
+    fn entity(id: &str, name: &str) -> Entity {
+        Entity {
+            id: id.to_string(),
+            name: name.to_string(),
+            entity_type: EntityType::Person,
+            attributes: Vec::new(),
+            confidence: 0.8,
+            position: None,
+            mentions: 1,
+            disambiguation: None,
+        }
+    }
+
+    fn relationship(id: &str, source: &str, target: &str, label: &str) -> Relationship {
+        Relationship {
+            id: id.to_string(),
+            source_entity_id: source.to_string(),
+            target_entity_id: target.to_string(),
+            relationship_type: RelationshipType::RelatedTo,
+            label: label.to_string(),
+            confidence: 0.8,
+            position: None,
+            bidirectional: false,
+            category: None,
+            context: None,
+        }
+    }
+
+    fn extraction(method: &str, entities: Vec<Entity>, relationships: Vec<Relationship>) -> ExtractionResult {
+        ExtractionResult {
+            metadata: ExtractionMetadata {
+                total_entities: entities.len(),
+                total_relationships: relationships.len(),
+                total_concepts: 0,
+                processing_time_ms: 5,
+                confidence_threshold: 0.5,
+                extraction_method: method.to_string(),
+            },
+            entities,
+            relationships,
+            concepts: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_overlay_tags_agreement_and_colors_the_graph() {
+        let patterns = extraction(
+            "Pattern-based",
+            vec![entity("p1", "Alice"), entity("p2", "TechCorp"), entity("p3", "Monday")],
+            vec![relationship("pr1", "p1", "p2", "works at"), relationship("pr2", "p1", "p3", "met on")],
+        );
+        let llm = extraction(
+            "LLM-stub",
+            vec![entity("l1", "alice"), entity("l2", "TechCorp"), entity("l3", "Bob")],
+            vec![relationship("lr1", "l2", "l1", "employs"), relationship("lr2", "l3", "l2", "manages")],
+        );
+
+        let overlay = ComparisonOverlay::new(&patterns, &llm);
+        let counts = |both, llm_only, patterns_only| MethodCounts { both, llm_only, patterns_only };
+        assert_eq!(overlay.comparison.entities, counts(2, 1, 1));
+        assert_eq!(overlay.comparison.relationships, counts(1, 1, 1));
+        assert!((overlay.comparison.entities.agreement() - 0.5).abs() < 1e-9);
+        assert_eq!(
+            overlay.comparison.entities.to_string(),
+            "2 both, 1 LLM only, 1 patterns only (50% agreement)"
+        );
+
+        // Matched relationships keep both labels and point at the merged entities
+        let works_at = overlay.extraction.relationships.iter().find(|r| r.id == "pr1").unwrap();
+        assert_eq!(works_at.label, "works at / employs");
+        let manages = overlay.extraction.relationships.iter().find(|r| r.id == "lr2").unwrap();
+        assert_eq!((manages.source_entity_id.as_str(), manages.target_entity_id.as_str()), ("l3", "p2"));
+
+        let graph = overlay.build_graph(&GraphConfig::default(), "Alice works at TechCorp.").unwrap();
+        let node = |id: &str| graph.nodes.iter().find(|node| node.id == id).unwrap();
+        assert_eq!(node("p1").color, AGREEMENT_COLOR);
+        assert_eq!(node("l3").color, LLM_ONLY_COLOR);
+        assert_eq!(node("p3").color, PATTERNS_ONLY_COLOR);
+        assert_eq!(node("p3").metadata.attributes["found_by"], "patterns only");
+        let edge = |id: &str| graph.edges.iter().find(|edge| edge.id == id).unwrap();
+        assert_eq!(edge("pr2").color, PATTERNS_ONLY_COLOR);
+        assert_eq!(edge("lr2").color, LLM_ONLY_COLOR);
+        assert_eq!(graph.metadata.provenance.extraction_method, "Comparison of Pattern-based and LLM-stub");
+        assert!(graph.metadata.provenance.config_summary["comparison.relationships"].starts_with("1 both"));
+    }
+}
@@ -0,0 +1,152 @@
+//! `field op value` conditions, shared by filter expressions ([`crate::query`]) and
+//! style rules ([`crate::style_rules`]).
+//!
+//! The operators are `=` (or `:`), `!=`, `>`, `>=`, `<`, `<=` and `~` for a regex match.
+//! Values that both parse as numbers are compared as numbers; any other value only
+//! supports `=` and `!=`, ignoring case. A value may be quoted (`"Alice Smith"`) or, for
+//! `~`, written between slashes (`/^A|B/`).
+
+use regex::{Regex, RegexBuilder};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Comparison {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+/// What a condition checks its field's values with
+#[derive(Debug, Clone)]
+pub(crate) enum Test {
+    Compare(Comparison, String),
+    Matches(Regex),
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Condition {
+    /// Field name, lowercased
+    pub field: String,
+    pub test: Test,
+}
+
+// Two-character operators first so `>=` isn't read as `>`
+const OPERATORS: [(&str, Option<Comparison>); 8] = [
+    (">=", Some(Comparison::Ge)),
+    ("<=", Some(Comparison::Le)),
+    ("!=", Some(Comparison::Ne)),
+    ("=", Some(Comparison::Eq)),
+    (":", Some(Comparison::Eq)),
+    (">", Some(Comparison::Gt)),
+    ("<", Some(Comparison::Lt)),
+    // Regex match
+    ("~", None),
+];
+
+/// Characters that end a field name, after which a quoted or /regex/ value may start
+pub(crate) fn is_operator_char(c: char) -> bool {
+    matches!(c, ':' | '~' | '=' | '>' | '<')
+}
+
+/// The value without its quotes or slashes
+fn unquote(value: &str) -> String {
+    for delimiter in ['"', '/'] {
+        if value.len() >= 2 && value.starts_with(delimiter) && value.ends_with(delimiter) {
+            let inner = &value[1..value.len() - 1];
+            return inner.replace(&format!("\\{}", delimiter), &delimiter.to_string());
+        }
+    }
+    value.to_string()
+}
+
+fn compare(candidate: &str, comparison: Comparison, value: &str) -> bool {
+    match (candidate.parse::<f64>(), value.parse::<f64>()) {
+        (Ok(actual), Ok(expected)) => match comparison {
+            Comparison::Eq => actual == expected,
+            Comparison::Ne => actual != expected,
+            Comparison::Gt => actual > expected,
+            Comparison::Ge => actual >= expected,
+            Comparison::Lt => actual < expected,
+            Comparison::Le => actual <= expected,
+        },
+        // Text only supports equality, compared case-insensitively
+        _ => match comparison {
+            Comparison::Eq => candidate.to_lowercase() == value.to_lowercase(),
+            Comparison::Ne => candidate.to_lowercase() != value.to_lowercase(),
+            _ => false,
+        },
+    }
+}
+
+impl Condition {
+    /// Parse one `field op value` condition, split at its leftmost operator. The error
+    /// says what is wrong with `text`, for the caller to wrap.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (position, operator, comparison) = OPERATORS
+            .iter()
+            .filter_map(|(operator, comparison)| text.find(operator).map(|pos| (pos, *operator, *comparison)))
+            .min_by_key(|(pos, _, _)| *pos)
+            .ok_or_else(|| format!("'{}' is not a condition like type:Person or degree>=3", text))?;
+        let field = text[..position].trim().to_lowercase();
+        let value = unquote(text[position + operator.len()..].trim());
+        if field.is_empty() || value.is_empty() {
+            return Err(format!("'{}' needs a field and a value", text));
+        }
+        let test = match comparison {
+            Some(comparison) => Test::Compare(comparison, value),
+            None => Test::Matches(
+                RegexBuilder::new(&value)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|e| format!("bad regex in '{}': {}", text, e))?,
+            ),
+        };
+        Ok(Self { field, test })
+    }
+
+    /// A field can have several values (a node's `type` is both e.g. `entity` and `Person`):
+    /// `!=` holds when none of them is equal, every other test when any of them holds.
+    /// A missing field never matches.
+    pub fn matches(&self, candidates: &[String]) -> bool {
+        match &self.test {
+            _ if candidates.is_empty() => false,
+            Test::Compare(Comparison::Ne, value) => !candidates.iter().any(|c| compare(c, Comparison::Eq, value)),
+            Test::Compare(comparison, value) => candidates.iter().any(|c| compare(c, *comparison, value)),
+            Test::Matches(regex) => candidates.iter().any(|c| regex.is_match(c)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_conditions_compare_numbers_text_and_regexes() {
+        let person = Condition::parse("Type:Person").unwrap();
+        assert_eq!(person.field, "type");
+        assert!(person.matches(&values(&["entity", "person"])));
+        assert!(!person.matches(&[]));
+        assert!(!Condition::parse("type!=Person").unwrap().matches(&values(&["entity", "Person"])));
+
+        // `>=` is one operator and 10 > 9 numerically, not as text
+        let degree = Condition::parse("degree>=9").unwrap();
+        assert!(matches!(degree.test, Test::Compare(Comparison::Ge, _)));
+        assert!(degree.matches(&values(&["10"])));
+        assert!(!Condition::parse("label>b").unwrap().matches(&values(&["c"])));
+
+        assert!(Condition::parse("label:\"Zoë Ångström\"").unwrap().matches(&values(&["ZOË ÅNGSTRÖM"])));
+        let regex = Condition::parse("label~/^(al|bo)/").unwrap();
+        assert!(regex.matches(&values(&["Bob"])) && !regex.matches(&values(&["Carol"])));
+
+        for invalid in ["degree 5", "=Person", "label:", "label~("] {
+            assert!(Condition::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
@@ -1,4 +1,27 @@
+use crate::cache::LlmCacheConfig;
+use crate::cooccurrence::{CooccurrenceConfig, ExtractionMode};
+use crate::estimate::LlmCostConfig;
+use crate::gazetteer::GazetteerFile;
+use crate::graph_builder::sorted_map;
+use crate::keywords::KeywordConfig;
+use crate::llm_output::LLM_ENTITY_TYPES;
+use crate::chunker::EntityDetector;
+use crate::ner_model::NerModelConfig;
+use crate::disambiguation::DisambiguationConfig;
+use crate::error::{GraphError, Result};
+use crate::extractors::{ChunkMergePolicy, ExtractorKind, ExtractorStep};
+use crate::pattern_library::PatternCategory;
+use crate::relationship_validation::RelationshipValidationConfig;
+use crate::prompt_budget::PromptBudgetConfig;
+use crate::languages::{default_languages, LanguagePatterns};
+use crate::sentiment::SentimentConfig;
+use crate::timeline::TimelineConfig;
+use crate::streaming::StreamingConfig;
+use crate::style_rules::StyleRule;
+use crate::timestamps::TimestampConfig;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GraphConfig {
@@ -8,6 +31,504 @@ pub struct GraphConfig {
     pub physics: PhysicsConfig,
     pub extraction: ExtractionConfig,
     pub text_processing: TextProcessingConfig,
+    /// Formatting of timestamps in metadata, exports and the HTML header
+    #[serde(default)]
+    pub timestamps: TimestampConfig,
+    /// Data-driven node size and color rules
+    #[serde(default)]
+    pub styling: StylingConfig,
+    /// Ordered cleanup recipe run after the graph is built, e.g. `["dedupe", "min_confidence 0.6"]`
+    #[serde(default)]
+    pub transforms: Vec<String>,
+    /// Remove nodes without edges once the graph is built (after `transforms` and `min_degree`)
+    #[serde(default)]
+    pub prune_isolated: bool,
+    /// Remove nodes with fewer edges than this once the graph is built; 0 keeps every node
+    #[serde(default)]
+    pub min_degree: usize,
+    /// Keep at most this many nodes, the best connected (then the most confident) ones,
+    /// after the cleanup above; a warning says what was dropped
+    #[serde(default)]
+    pub max_nodes: Option<usize>,
+    /// Keep at most this many edges, the most confident ones, after `max_nodes`
+    #[serde(default)]
+    pub max_edges: Option<usize>,
+    /// Where the HTML viewer gets vis-network from
+    #[serde(default)]
+    pub assets: AssetConfig,
+    /// Palette of HTML exports: `light`, `dark`, `auto` or a custom CSS file
+    #[serde(default)]
+    pub theme: Theme,
+    /// Node styles and scale for TikZ exports
+    #[serde(default)]
+    pub tikz: TikzConfig,
+    /// Direction, node cap and label length for Mermaid exports
+    #[serde(default)]
+    pub mermaid: MermaidConfig,
+    /// Table of contents and section count for Markdown and HTML report exports
+    #[serde(default)]
+    pub report: ReportConfig,
+    /// Viewer page and length limit for share-URL exports
+    #[serde(default)]
+    pub share: ShareConfig,
+    /// Chunk size and layout for chunked HTML exports of large graphs
+    #[serde(default)]
+    pub chunks: ChunkConfig,
+    /// File layout and byte order mark of CSV exports
+    #[serde(default)]
+    pub csv: CsvConfig,
+    /// Chunk size of `generate --bounded-memory`, which writes JSON Lines as chunks finish
+    #[serde(default)]
+    pub streaming: StreamingConfig,
+    /// Zoom levels at which the HTML viewer folds large graphs into clusters
+    #[serde(default)]
+    pub lod: LodConfig,
+    /// Lexicon-based sentiment scores on entities and relationship edges
+    #[serde(default)]
+    pub sentiment: SentimentConfig,
+    /// Dates mentioned alongside entities, drawn on the viewer's timeline
+    #[serde(default)]
+    pub timeline: TimelineConfig,
+    /// Draw several edges between the same two nodes curved apart, or merge them into one
+    #[serde(default)]
+    pub parallel_edges: ParallelEdges,
+    /// Content-hashed ids, sorted extraction results and a fixed timestamp, so the same
+    /// text and config always give byte-identical output (`--deterministic`)
+    #[serde(default)]
+    pub deterministic: bool,
+    /// Named overrides selected with `--profile`, e.g. `{"deep": {"extraction": {"use_llm": true}}}`.
+    /// Each profile is merged over the rest of the file, object by object.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, serde_json::Value>,
+}
+
+impl GraphConfig {
+    /// Stable identifier of this configuration: the first 16 hex digits of the SHA-256
+    /// of its JSON form, with map keys in order. Graphs record it instead of carrying the
+    /// whole config.
+    pub fn config_id(&self) -> String {
+        let json = serde_json::to_string(self).unwrap_or_default();
+        let digest = Sha256::digest(json.as_bytes());
+        digest.iter().take(8).map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    /// The layout graphs are drawn with: `layout`, except that hierarchy mode always uses
+    /// the `tree` layout so the org chart reads top-down
+    pub fn effective_layout(&self) -> LayoutConfig {
+        let mut layout = self.layout.clone();
+        if self.extraction.mode == ExtractionMode::Hierarchy {
+            layout.algorithm = "tree".to_string();
+            layout.hierarchical = true;
+        }
+        layout
+    }
+
+    /// Merge the profile `name` over this config and drop the profiles.
+    ///
+    /// Objects merge key by key, so a profile only lists what it changes
+    /// (`{"layout": {"spacing": 300}}` keeps the other layout settings); any other value,
+    /// arrays included, replaces the base value.
+    pub fn apply_profile(&mut self, name: &str) -> Result<()> {
+        let profiles = std::mem::take(&mut self.profiles);
+        let Some(profile) = profiles.get(name) else {
+            let available = if profiles.is_empty() {
+                "the config defines none".to_string()
+            } else {
+                format!("available: {}", profiles.keys().cloned().collect::<Vec<_>>().join(", "))
+            };
+            return Err(GraphError::Configuration(format!("Unknown profile '{}' ({})", name, available)));
+        };
+        if !profile.is_object() {
+            return Err(GraphError::Configuration(format!("Profile '{}' must be a JSON object", name)));
+        }
+        if profile.get("profiles").is_some() {
+            return Err(GraphError::Configuration(format!("Profile '{}' cannot define profiles", name)));
+        }
+
+        let mut merged = serde_json::to_value(&*self)?;
+        merge_json(&mut merged, profile);
+        *self = serde_json::from_value(merged)
+            .map_err(|e| GraphError::Configuration(format!("Profile '{}' is not a valid config: {}", name, e)))?;
+        Ok(())
+    }
+}
+
+/// Merge `overlay` into `base`: objects key by key, anything else replaced
+fn merge_json(base: &mut serde_json::Value, overlay: &serde_json::Value) {
+    match (base, overlay) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay.clone(),
+    }
+}
+
+/// The part of a [`GraphConfig`] a built graph still needs for rendering and exporting,
+/// plus the id of the full config it was built with.
+///
+/// Node colors, shapes and styling are already applied to the nodes and edges, and the
+/// extraction settings only matter while building, so they are not repeated in every
+/// export. Old exports that embedded the whole config still load: the extra keys are
+/// dropped and `config_id` is derived from them (see [`RenderConfig::deserialize`]).
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderConfig {
+    pub config_id: String,
+    pub layout: LayoutConfig,
+    pub physics: PhysicsConfig,
+    pub timestamps: TimestampConfig,
+    pub assets: AssetConfig,
+    pub theme: Theme,
+    pub tikz: TikzConfig,
+    pub mermaid: MermaidConfig,
+    pub report: ReportConfig,
+    pub share: ShareConfig,
+    pub chunks: ChunkConfig,
+    pub csv: CsvConfig,
+    pub lod: LodConfig,
+}
+
+impl From<&GraphConfig> for RenderConfig {
+    fn from(config: &GraphConfig) -> Self {
+        Self {
+            config_id: config.config_id(),
+            layout: config.effective_layout(),
+            physics: config.physics.clone(),
+            timestamps: config.timestamps.clone(),
+            assets: config.assets.clone(),
+            theme: config.theme.clone(),
+            tikz: config.tikz.clone(),
+            mermaid: config.mermaid.clone(),
+            report: config.report.clone(),
+            share: config.share.clone(),
+            chunks: config.chunks.clone(),
+            csv: config.csv.clone(),
+            lod: config.lod.clone(),
+        }
+    }
+}
+
+impl Default for RenderConfig {
+    fn default() -> Self {
+        Self::from(&GraphConfig::default())
+    }
+}
+
+/// On-disk form of [`RenderConfig`], accepting both the current layout and the full
+/// `GraphConfig` that exports before `config_id` embedded
+#[derive(Deserialize)]
+struct StoredRenderConfig {
+    #[serde(default)]
+    config_id: Option<String>,
+    #[serde(flatten)]
+    fields: serde_json::Map<String, serde_json::Value>,
+}
+
+/// `name` from a stored config object, or the default when it is missing or null
+fn stored_field<T, E>(fields: &serde_json::Map<String, serde_json::Value>, name: &str) -> std::result::Result<T, E>
+where
+    T: serde::de::DeserializeOwned + Default,
+    E: serde::de::Error,
+{
+    match fields.get(name) {
+        Some(value) if !value.is_null() => serde_json::from_value(value.clone()).map_err(E::custom),
+        _ => Ok(T::default()),
+    }
+}
+
+impl<'de> Deserialize<'de> for RenderConfig {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let StoredRenderConfig { config_id, fields } = StoredRenderConfig::deserialize(deserializer)?;
+        let config_id = match config_id {
+            Some(id) => id,
+            // Legacy export: the embedded config is a full GraphConfig, so hash it the same
+            // way a new build would. Anything else gets no id rather than failing to load.
+            None => serde_json::from_value::<GraphConfig>(serde_json::Value::Object(fields.clone()))
+                .map(|config| config.config_id())
+                .unwrap_or_else(|_| "unknown".to_string()),
+        };
+        let required = |name: &str| fields.get(name).cloned().ok_or_else(|| D::Error::custom(format!("missing field `{}`", name)));
+
+        Ok(Self {
+            config_id,
+            layout: serde_json::from_value(required("layout")?).map_err(D::Error::custom)?,
+            physics: serde_json::from_value(required("physics")?).map_err(D::Error::custom)?,
+            timestamps: stored_field(&fields, "timestamps")?,
+            assets: stored_field(&fields, "assets")?,
+            theme: stored_field(&fields, "theme")?,
+            tikz: stored_field(&fields, "tikz")?,
+            mermaid: stored_field(&fields, "mermaid")?,
+            report: stored_field(&fields, "report")?,
+            share: stored_field(&fields, "share")?,
+            chunks: stored_field(&fields, "chunks")?,
+            csv: stored_field(&fields, "csv")?,
+            lod: stored_field(&fields, "lod")?,
+        })
+    }
+}
+
+/// TikZ export settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TikzConfig {
+    /// Centimetres per layout unit (vis.js pixel)
+    pub scale: f64,
+    /// Wrap the picture in a `standalone` document that compiles on its own
+    pub standalone: bool,
+    /// TikZ node options per node type; the node's color is added as `fill`
+    pub node_styles: TikzNodeStyles,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TikzNodeStyles {
+    pub entity: String,
+    pub relationship: String,
+    pub concept: String,
+    pub attribute: String,
+}
+
+impl Default for TikzConfig {
+    fn default() -> Self {
+        Self {
+            scale: 0.02,
+            standalone: true,
+            node_styles: TikzNodeStyles::default(),
+        }
+    }
+}
+
+impl Default for TikzNodeStyles {
+    fn default() -> Self {
+        Self {
+            entity: "ellipse, draw".to_string(),
+            relationship: "rectangle, draw".to_string(),
+            concept: "circle, draw, inner sep=1pt".to_string(),
+            attribute: "diamond, draw, inner sep=1pt".to_string(),
+        }
+    }
+}
+
+/// Mermaid export settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MermaidConfig {
+    /// Flowchart direction: `TD`, `LR`, `BT` or `RL`
+    pub direction: String,
+    /// Keep only this many nodes, the most connected first; renderers in READMEs and
+    /// note apps become unreadable with hundreds of nodes
+    pub max_nodes: Option<usize>,
+    /// Longer node and edge labels are cut and end in "…"; `0` keeps them whole
+    pub max_label_length: usize,
+}
+
+impl Default for MermaidConfig {
+    fn default() -> Self {
+        Self {
+            direction: "TD".to_string(),
+            max_nodes: None,
+            max_label_length: 40,
+        }
+    }
+}
+
+/// Markdown and HTML report export settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportConfig {
+    /// Entities listed in the ranked table of contents, most central first
+    pub toc_size: usize,
+    /// Write sections for only this many entities, the most central first
+    pub max_sections: Option<usize>,
+    /// Relationships listed per entity, the most confident first
+    pub max_relationships: usize,
+    /// Sentences quoted per entity when the source text is known
+    pub max_sentences: usize,
+}
+
+impl Default for ReportConfig {
+    fn default() -> Self {
+        Self {
+            toc_size: 20,
+            max_sections: None,
+            max_relationships: 10,
+            max_sentences: 3,
+        }
+    }
+}
+
+/// Share-URL export settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShareConfig {
+    /// Viewer page the link opens. A relative path gets a copy of the data-less viewer
+    /// written next to the link; a hosted copy of that page works for anyone
+    pub viewer_url: String,
+    /// Longest link written; larger graphs should be shared as files. Chat apps and
+    /// mail clients start cutting links somewhere past 8000 characters
+    pub max_url_length: usize,
+}
+
+impl Default for ShareConfig {
+    fn default() -> Self {
+        Self {
+            viewer_url: "msg_net_viewer.html".to_string(),
+            max_url_length: 8000,
+        }
+    }
+}
+
+/// Chunked HTML export settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChunkConfig {
+    /// Most nodes per chunk file; communities larger than this are split
+    pub max_chunk_nodes: usize,
+    /// Distance between neighbouring nodes in the precomputed layout
+    pub node_spacing: f64,
+    /// On-screen radius, in pixels, at which a chunk's nodes are loaded in place of its
+    /// summary node. Chunks are unloaded again below half of it
+    pub load_radius_px: f64,
+}
+
+impl Default for ChunkConfig {
+    fn default() -> Self {
+        Self {
+            max_chunk_nodes: 2000,
+            node_spacing: 40.0,
+            load_radius_px: 150.0,
+        }
+    }
+}
+
+/// CSV export settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CsvConfig {
+    pub layout: CsvLayout,
+    /// Start each file with a UTF-8 byte order mark, so Excel reads non-ASCII labels
+    /// correctly
+    pub bom: bool,
+}
+
+/// How CSV exports lay out nodes and edges
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvLayout {
+    /// One file with a `# NODES` and an `# EDGES` section
+    #[default]
+    Sectioned,
+    /// `<name>_nodes.csv` (`Id,Label,...`) and `<name>_edges.csv` (`Source,Target,Type,Weight,...`)
+    /// as Gephi's spreadsheet import and Neo4j's `LOAD CSV` expect them. Rendered to a
+    /// string or writer, the sectioned layout is produced instead
+    Tables,
+}
+
+/// Level-of-detail settings for the HTML viewer. Zoomed out, each community is drawn as
+/// one cluster node joined by its heavier links; members appear as the view zooms in,
+/// attribute nodes last
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LodConfig {
+    pub enabled: bool,
+    /// Graphs with fewer nodes are always drawn in full
+    pub min_nodes: usize,
+    /// Zoom scale below which communities are drawn as cluster nodes
+    pub cluster_scale: f64,
+    /// Zoom scale below which attribute nodes stay hidden
+    pub detail_scale: f64,
+    /// Fewest edges between two clusters for their link to be drawn
+    pub min_link_edges: usize,
+}
+
+impl Default for LodConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_nodes: 300,
+            cluster_scale: 0.3,
+            detail_scale: 0.7,
+            min_link_edges: 2,
+        }
+    }
+}
+
+/// Script assets used by the HTML viewer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AssetConfig {
+    /// URL the page loads vis-network from when assets are not embedded
+    pub vis_network_url: String,
+    /// Local copy of `vis-network.min.js` to inline for offline (`--offline`) exports
+    pub vis_network_path: Option<String>,
+    /// URL the page loads vis-timeline from when the graph has dated entities
+    pub vis_timeline_url: String,
+    /// Local copy of `vis-timeline-graph2d.min.js` to inline for offline exports; without it
+    /// offline HTML pages leave the timeline out
+    pub vis_timeline_path: Option<String>,
+}
+
+/// Palette of the HTML viewer. Serialized as `light`, `dark`, `auto` (follow the
+/// browser's color scheme) or, for anything else, the path of a CSS file inlined after the
+/// built-in stylesheet on top of the light palette.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum Theme {
+    #[default]
+    Light,
+    Dark,
+    Auto,
+    Custom(String),
+}
+
+impl Theme {
+    /// Built-in palette the page starts with, the `data-theme` of its root element
+    pub fn palette(&self) -> &'static str {
+        match self {
+            Theme::Light | Theme::Custom(_) => "light",
+            Theme::Dark => "dark",
+            Theme::Auto => "auto",
+        }
+    }
+}
+
+impl From<String> for Theme {
+    fn from(value: String) -> Self {
+        match value.to_lowercase().as_str() {
+            "light" | "" => Theme::Light,
+            "dark" => Theme::Dark,
+            "auto" => Theme::Auto,
+            _ => Theme::Custom(value),
+        }
+    }
+}
+
+impl From<Theme> for String {
+    fn from(theme: Theme) -> Self {
+        match theme {
+            Theme::Custom(path) => path,
+            theme => theme.palette().to_string(),
+        }
+    }
+}
+
+impl Default for AssetConfig {
+    fn default() -> Self {
+        Self {
+            vis_network_url: crate::web_interface::DEFAULT_VIS_NETWORK_URL.to_string(),
+            vis_network_path: None,
+            vis_timeline_url: crate::web_interface::DEFAULT_VIS_TIMELINE_URL.to_string(),
+            vis_timeline_path: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,6 +552,13 @@ pub struct LayoutConfig {
     pub algorithm: String,
     pub spacing: f64,
     pub hierarchical: bool,
+    /// Rounds of the `force` and `kamada-kawai` layouts
+    #[serde(default = "default_layout_iterations")]
+    pub iterations: usize,
+}
+
+fn default_layout_iterations() -> usize {
+    200
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,6 +568,180 @@ pub struct PhysicsConfig {
     pub repulsion: f64,
     pub spring_length: f64,
     pub spring_constant: f64,
+    /// Pull of every node towards the center
+    #[serde(default = "default_central_gravity")]
+    pub central_gravity: f64,
+}
+
+fn default_central_gravity() -> f64 {
+    0.1
+}
+
+/// Per-node measures that styling rules can map onto size or color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeMetric {
+    /// Extraction confidence
+    Confidence,
+    /// Number of edges touching the node
+    Degree,
+    /// How often the node's label occurs in the source text
+    MentionCount,
+}
+
+/// What decides a node's color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorMode {
+    /// `node_colors` by node type (entity, concept, ...)
+    NodeType,
+    /// Entity type (Person, Organization, ...) via `entity_type_colors`, else `palette`
+    EntityType,
+    /// Detected community (label propagation), colored from `palette`
+    Community,
+    /// Gradient from `gradient_start` to `gradient_end` over `gradient_metric`
+    Gradient,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StylingConfig {
+    /// Metric mapped linearly onto node size; `None` keeps the built-in sizing
+    pub size_by: Option<NodeMetric>,
+    pub min_size: f64,
+    pub max_size: f64,
+    pub color_by: ColorMode,
+    pub gradient_metric: NodeMetric,
+    pub gradient_start: String,
+    pub gradient_end: String,
+    /// Colors for specific entity types when `color_by` is `entity_type`
+    #[serde(serialize_with = "sorted_map")]
+    pub entity_type_colors: HashMap<String, String>,
+    /// Colors cycled through for communities and unlisted entity types
+    pub palette: Vec<String>,
+    /// Conditional overrides, applied in order after the rules above
+    pub rules: Vec<StyleRule>,
+    /// Entity-to-attribute edge colors by attribute type (`Description`, `Location`, ...),
+    /// overriding [`DEFAULT_ATTRIBUTE_EDGE_COLORS`]
+    #[serde(serialize_with = "sorted_map")]
+    pub attribute_edge_colors: HashMap<String, String>,
+    /// Arrowheads, dashes and opacity by edge type (`relationship`, `entity_attribute`,
+    /// `concept_entity`, `concept_concept`, `hierarchy`) or relationship type (`WorksFor`,
+    /// `co-occurs`, ...), on top of [`default_edge_styles`]
+    #[serde(serialize_with = "sorted_map")]
+    pub edge_styles: HashMap<String, EdgeStyle>,
+}
+
+/// How edges between the same two nodes are exported
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParallelEdges {
+    /// Keep every edge and bend them apart so each label stays readable
+    #[default]
+    Curve,
+    /// Fold them into one edge labeled with every relationship
+    Merge,
+}
+
+/// Arrowheads drawn on an edge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArrowStyle {
+    To,
+    From,
+    Both,
+    None,
+}
+
+impl ArrowStyle {
+    /// The vis.js `arrows` value stored on graph edges
+    pub fn vis_arrows(self) -> &'static str {
+        match self {
+            ArrowStyle::To => "to",
+            ArrowStyle::From => "from",
+            ArrowStyle::Both => "to, from",
+            ArrowStyle::None => "",
+        }
+    }
+}
+
+/// Look of the edges of one edge or relationship type; unset fields keep the look the
+/// edge was built with
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EdgeStyle {
+    /// Edge color, e.g. `#E74C3C`; relationship edges otherwise use `node_colors.relationship`
+    pub color: Option<String>,
+    /// Line width in pixels; relationship edges otherwise scale with confidence
+    pub width: Option<f64>,
+    pub arrows: Option<ArrowStyle>,
+    /// Dash and gap lengths in pixels, e.g. `[6, 4]`; `[]` draws the edge solid
+    pub dashes: Option<Vec<f64>>,
+    /// 0 (invisible) to 1 (opaque)
+    pub opacity: Option<f64>,
+}
+
+impl EdgeStyle {
+    /// `self` with the fields `other` sets replaced
+    fn overlaid(mut self, other: &EdgeStyle) -> EdgeStyle {
+        self.color = other.color.clone().or(self.color);
+        self.width = other.width.or(self.width);
+        self.arrows = other.arrows.or(self.arrows);
+        self.dashes = other.dashes.clone().or(self.dashes);
+        self.opacity = other.opacity.or(self.opacity);
+        self
+    }
+}
+
+/// Built-in edge styles, setting attribute and concept links apart from relationships
+pub fn default_edge_styles() -> HashMap<String, EdgeStyle> {
+    HashMap::from([
+        ("entity_attribute".to_string(), EdgeStyle { dashes: Some(vec![6.0, 4.0]), ..EdgeStyle::default() }),
+        (
+            "concept_entity".to_string(),
+            EdgeStyle { dashes: Some(vec![2.0, 4.0]), opacity: Some(0.6), ..EdgeStyle::default() },
+        ),
+    ])
+}
+
+/// Attribute edge colors by attribute type; unlisted types are gray
+pub const DEFAULT_ATTRIBUTE_EDGE_COLORS: &[(&str, &str)] = &[
+    ("Description", "#8E7CC3"),
+    ("Location", "#6AA84F"),
+    ("Date", "#E69138"),
+    ("Number", "#3D85C6"),
+    ("Category", "#C27BA0"),
+    ("Property", "#45818E"),
+    ("Role", "#CC4125"),
+    ("Affiliation", "#3C78D8"),
+];
+
+impl StylingConfig {
+    /// Color of an edge to an attribute of type `attribute_type`
+    pub fn attribute_edge_color(&self, attribute_type: &str) -> String {
+        self.attribute_edge_colors
+            .get(attribute_type)
+            .map(String::as_str)
+            .or_else(|| DEFAULT_ATTRIBUTE_EDGE_COLORS.iter().find(|(t, _)| *t == attribute_type).map(|(_, color)| *color))
+            .unwrap_or("#888888")
+            .to_string()
+    }
+
+    /// Style for an edge of `edge_type` (see `EdgeType::metadata_key`) and
+    /// `relationship_type`: the built-in style for the edge type, then the configured one,
+    /// then the one for the relationship type, each overriding the fields it sets
+    pub fn edge_style(&self, edge_type: &str, relationship_type: &str) -> EdgeStyle {
+        let defaults = default_edge_styles();
+        // Custom relationship types are stored as `Other("co-occurs")`
+        let relationship_type = relationship_type
+            .strip_prefix("Other(\"")
+            .and_then(|name| name.strip_suffix("\")"))
+            .unwrap_or(relationship_type);
+        [defaults.get(edge_type), self.edge_styles.get(edge_type), self.edge_styles.get(relationship_type)]
+            .into_iter()
+            .flatten()
+            .fold(EdgeStyle::default(), |style, layer| style.overlaid(layer))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,6 +752,144 @@ pub struct ExtractionConfig {
     pub entity_patterns: Vec<String>,
     pub relationship_patterns: Vec<String>,
     pub concept_patterns: Vec<String>,
+    /// On-disk cache for LLM responses
+    #[serde(default)]
+    pub llm_cache: LlmCacheConfig,
+    /// Ask Ollama to stream its response, reporting progress as tokens arrive
+    #[serde(default = "default_llm_stream")]
+    pub llm_stream: bool,
+    /// How Ollama is told to shape its answer: `schema` (default), `json` or `text`
+    #[serde(default)]
+    pub llm_output_format: LlmOutputFormat,
+    /// Token prices and the budget `--confirm` checks LLM runs against
+    #[serde(default)]
+    pub llm_cost: LlmCostConfig,
+    /// When set, keep only entities named in this list and the relationships between them
+    #[serde(default)]
+    pub entity_whitelist: Option<Vec<String>>,
+    /// `entities` (default) or `cooccurrence`
+    #[serde(default)]
+    pub mode: ExtractionMode,
+    #[serde(default)]
+    pub cooccurrence: CooccurrenceConfig,
+    /// TF-IDF/RAKE keyword concepts for pattern extraction
+    #[serde(default)]
+    pub keywords: KeywordConfig,
+    /// WebAssembly extractor plugins (`.wasm` paths) run after the built-in extraction;
+    /// requires a build with `--features plugins`
+    #[serde(default)]
+    pub plugins: Vec<String>,
+    /// Known-entity lists matched with high confidence alongside patterns and the LLM
+    #[serde(default)]
+    pub gazetteer_files: Vec<GazetteerFile>,
+    /// Relationship rules with typed subject/object slots, e.g.
+    /// `{subject:person} works at {object:organization} -> works_at`
+    #[serde(default)]
+    pub relationship_rules: Vec<String>,
+    /// Built-in relationship rule categories applied after `relationship_rules`;
+    /// `[]` turns the library off
+    #[serde(default = "default_pattern_library")]
+    pub pattern_library: Vec<PatternCategory>,
+    /// How pattern extraction finds entities: `patterns` (`entity_patterns`),
+    /// `noun_phrases` (part-of-speech chunking, any capitalization) or `ner_model`
+    /// (the ONNX model in `ner_model`)
+    #[serde(default)]
+    pub entity_detector: EntityDetector,
+    /// Model files and labels for `entity_detector = "ner_model"`
+    #[serde(default)]
+    pub ner_model: NerModelConfig,
+    /// Extraction strategies to chain, in order, each with an `enabled` flag; empty
+    /// means the LLM or the patterns (per `use_llm`), then any gazetteers
+    #[serde(default)]
+    pub extractors: Vec<ExtractorStep>,
+    /// Custom entity types ("Medication", "Gene", "LegalCase", ...) with the keywords that
+    /// classify an entity as one and how their nodes are drawn
+    #[serde(default)]
+    pub entity_types: Vec<EntityTypeDefinition>,
+    /// Plausibility check dropping generic pattern relationships whose entities are far
+    /// apart, split by a sentence boundary or only part of a longer word
+    #[serde(default)]
+    pub relationship_validation: RelationshipValidationConfig,
+    /// Context window per model; texts too long for one prompt are split into segments
+    #[serde(default)]
+    pub prompt_budget: PromptBudgetConfig,
+    /// Patterns by language, used for texts detected as that language (`german`,
+    /// `spanish`) or forced to it with `language`; listing any replaces the built-in ones
+    #[serde(default = "default_languages")]
+    pub languages: BTreeMap<String, LanguagePatterns>,
+    /// Language whose `languages` entry to use whatever the detection says, e.g. `german`
+    #[serde(default)]
+    pub language: Option<String>,
+    /// How an entity found in several prompt segments of a long text gets its type and
+    /// confidence
+    #[serde(default)]
+    pub chunk_merge: ChunkMergePolicy,
+    /// Split entities whose same-named mentions mean different things into one entity
+    /// per sense
+    #[serde(default)]
+    pub disambiguation: DisambiguationConfig,
+}
+
+/// A custom entity type from `extraction.entity_types`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityTypeDefinition {
+    /// Type name, e.g. `Medication`; LLM answers, gazetteer types and filters match it
+    /// ignoring case
+    pub name: String,
+    /// An entity whose name contains one of these, ignoring case, gets this type
+    /// ("cillin", "aspirin"); checked before the built-in heuristics
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// Node color; `node_colors.entity` when unset
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Node shape (`ellipse`, `box`, `diamond`, ...); `node_shapes.entity` when unset
+    #[serde(default)]
+    pub shape: Option<String>,
+}
+
+impl ExtractionConfig {
+    /// The custom type named `name`, ignoring case
+    pub fn entity_type_definition(&self, name: &str) -> Option<&EntityTypeDefinition> {
+        self.entity_types.iter().find(|definition| definition.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Type names the LLM is asked to choose from: the built-in ones, the custom ones, then `Other`
+    pub fn llm_entity_types(&self) -> Vec<String> {
+        let mut names: Vec<String> = LLM_ENTITY_TYPES.iter().map(|name| name.to_string()).collect();
+        for definition in &self.entity_types {
+            if !names.iter().any(|name| name.eq_ignore_ascii_case(&definition.name)) {
+                names.push(definition.name.clone());
+            }
+        }
+        names.push("Other".to_string());
+        names
+    }
+
+    /// The enabled built-in strategies, in the order they run
+    pub fn extractor_chain(&self) -> Vec<ExtractorKind> {
+        if !self.extractors.is_empty() {
+            return self.extractors.iter().filter(|step| step.enabled).map(|step| step.kind).collect();
+        }
+        let mut chain = vec![if self.use_llm { ExtractorKind::Llm } else { ExtractorKind::Patterns }];
+        if !self.gazetteer_files.is_empty() {
+            chain.push(ExtractorKind::Gazetteer);
+        }
+        chain
+    }
+
+    /// Whether the chain calls the LLM
+    pub fn uses_llm(&self) -> bool {
+        self.extractor_chain().contains(&ExtractorKind::Llm)
+    }
+}
+
+fn default_llm_stream() -> bool {
+    true
+}
+
+fn default_pattern_library() -> Vec<PatternCategory> {
+    PatternCategory::ALL.to_vec()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -57,6 +897,62 @@ pub struct TextProcessingConfig {
     pub remove_stopwords: bool,
     pub stopwords_file: Option<String>,
     pub custom_stopwords: Option<Vec<String>>,
+    /// Also remove stopwords from the text the LLM and the enhanced relationship patterns
+    /// read, keeping those inside entity names ("Bank of America"). Off, stopwords only
+    /// leave the word statistics: word counts, keywords and co-occurrence
+    #[serde(default)]
+    pub strip_prompt_stopwords: bool,
+    /// Reduce words to their stems ("manages", "managed" -> "manag") for
+    /// relationship classification and concept dedup; labels keep the surface form
+    #[serde(default)]
+    pub stem_words: bool,
+    #[serde(default)]
+    pub sentence_splitting: SentenceSplittingConfig,
+}
+
+/// How text is split into sentences, which decides `sentence_index` provenance and
+/// which entities pattern extraction pairs up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SentenceSplittingConfig {
+    pub method: SentenceSplitMethod,
+    /// Include the built-in English abbreviations ("Dr.", "Mr.", "U.S.", "e.g.", ...)
+    pub default_abbreviations: bool,
+    /// Further abbreviations that never end a sentence ("Corp.", "approx")
+    pub abbreviations: Vec<String>,
+}
+
+impl Default for SentenceSplittingConfig {
+    fn default() -> Self {
+        Self {
+            method: SentenceSplitMethod::Unicode,
+            default_abbreviations: true,
+            abbreviations: Vec::new(),
+        }
+    }
+}
+
+/// The `format` Ollama constrains its output to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LlmOutputFormat {
+    /// A JSON schema per call, so the model can only produce the expected fields
+    /// (Ollama 0.5 and later)
+    #[default]
+    Schema,
+    /// Any valid JSON (`format: "json"`), for older Ollama versions
+    Json,
+    /// No constraint; the prompt alone asks for JSON
+    Text,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SentenceSplitMethod {
+    /// Unicode sentence boundaries (UAX #29) that respect abbreviations and initials
+    Unicode,
+    /// Split after every run of `.`, `!` or `?`, as older versions did
+    Punctuation,
 }
 
 impl Default for GraphConfig {
@@ -78,6 +974,7 @@ impl Default for GraphConfig {
                 algorithm: "hierarchical".to_string(),
                 spacing: 200.0,
                 hierarchical: true,
+                iterations: default_layout_iterations(),
             },
             physics: PhysicsConfig {
                 enabled: true,
@@ -85,9 +982,32 @@ impl Default for GraphConfig {
                 repulsion: 200.0,
                 spring_length: 150.0,
                 spring_constant: 0.04,
+                central_gravity: default_central_gravity(),
             },
             extraction: ExtractionConfig::default(),
             text_processing: TextProcessingConfig::default(),
+            timestamps: TimestampConfig::default(),
+            styling: StylingConfig::default(),
+            transforms: Vec::new(),
+            prune_isolated: false,
+            min_degree: 0,
+            max_nodes: None,
+            max_edges: None,
+            assets: AssetConfig::default(),
+            theme: Theme::default(),
+            tikz: TikzConfig::default(),
+            mermaid: MermaidConfig::default(),
+            report: ReportConfig::default(),
+            share: ShareConfig::default(),
+            chunks: ChunkConfig::default(),
+            csv: CsvConfig::default(),
+            streaming: StreamingConfig::default(),
+            lod: LodConfig::default(),
+            sentiment: SentimentConfig::default(),
+            timeline: TimelineConfig::default(),
+            parallel_edges: ParallelEdges::default(),
+            deterministic: false,
+            profiles: BTreeMap::new(),
         }
     }
 }
@@ -110,6 +1030,56 @@ impl Default for ExtractionConfig {
                 r"\b(?:concept|idea|principle|theory|method|approach|strategy)\b".to_string(),
                 r"\b(?:system|process|workflow|procedure|protocol)\b".to_string(),
             ],
+            llm_cache: LlmCacheConfig::default(),
+            llm_stream: true,
+            llm_output_format: LlmOutputFormat::default(),
+            llm_cost: LlmCostConfig::default(),
+            entity_whitelist: None,
+            mode: ExtractionMode::default(),
+            cooccurrence: CooccurrenceConfig::default(),
+            keywords: KeywordConfig::default(),
+            plugins: Vec::new(),
+            gazetteer_files: Vec::new(),
+            relationship_rules: Vec::new(),
+            pattern_library: default_pattern_library(),
+            entity_detector: EntityDetector::default(),
+            ner_model: NerModelConfig::default(),
+            extractors: Vec::new(),
+            entity_types: Vec::new(),
+            relationship_validation: RelationshipValidationConfig::default(),
+            prompt_budget: PromptBudgetConfig::default(),
+            languages: default_languages(),
+            language: None,
+            chunk_merge: ChunkMergePolicy::default(),
+            disambiguation: DisambiguationConfig::default(),
+        }
+    }
+}
+
+impl Default for StylingConfig {
+    fn default() -> Self {
+        Self {
+            size_by: None,
+            min_size: 15.0,
+            max_size: 60.0,
+            color_by: ColorMode::NodeType,
+            gradient_metric: NodeMetric::Confidence,
+            gradient_start: "#D6EAF8".to_string(),
+            gradient_end: "#1B4F72".to_string(),
+            entity_type_colors: HashMap::new(),
+            palette: vec![
+                "#FF6B6B".to_string(),
+                "#4ECDC4".to_string(),
+                "#45B7D1".to_string(),
+                "#FFA07A".to_string(),
+                "#98D8C8".to_string(),
+                "#F7DC6F".to_string(),
+                "#BB8FCE".to_string(),
+                "#85C1E9".to_string(),
+            ],
+            rules: Vec::new(),
+            attribute_edge_colors: HashMap::new(),
+            edge_styles: HashMap::new(),
         }
     }
 }
@@ -120,6 +1090,87 @@ impl Default for TextProcessingConfig {
             remove_stopwords: true,
             stopwords_file: None,
             custom_stopwords: None,
+            strip_prompt_stopwords: false,
+            stem_words: false,
+            sentence_splitting: SentenceSplittingConfig::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_merges_over_base_config() {
+        let mut base = GraphConfig::default();
+        base.layout.algorithm = "force".to_string();
+        base.node_colors.entity = "#FF0000".to_string();
+        base.profiles.insert(
+            "deep".to_string(),
+            serde_json::json!({"extraction": {"use_llm": true}, "layout": {"spacing": 300.0}}),
+        );
+        base.profiles.insert("quick".to_string(), serde_json::json!({"extraction": {"relationship_patterns": []}}));
+
+        let mut deep = base.clone();
+        deep.apply_profile("deep").unwrap();
+        assert!(deep.extraction.use_llm);
+        assert_eq!(deep.extraction.llm_model, "llama3.2");
+        assert_eq!((deep.layout.algorithm.as_str(), deep.layout.spacing), ("force", 300.0));
+        assert_eq!(deep.node_colors.entity, "#FF0000");
+        assert!(deep.profiles.is_empty());
+
+        let mut quick = base.clone();
+        quick.apply_profile("quick").unwrap();
+        assert!(quick.extraction.relationship_patterns.is_empty());
+        assert!(!quick.extraction.use_llm);
+
+        let error = base.clone().apply_profile("slow").unwrap_err().to_string();
+        assert!(error.contains("available: deep, quick"), "{}", error);
+    }
+
+    #[test]
+    fn test_edge_style_matches_custom_relationship_names() {
+        let styling: StylingConfig = serde_json::from_value(serde_json::json!({
+            "edge_styles": {
+                "relationship": {"color": "#444444", "width": 2},
+                "co-occurs": {"arrows": "none", "opacity": 0.4, "color": "#E74C3C"}
+            }
+        }))
+        .unwrap();
+        let style = styling.edge_style("relationship", "Other(\"co-occurs\")");
+        assert_eq!((style.arrows, style.opacity), (Some(ArrowStyle::None), Some(0.4)));
+        assert_eq!((style.color.as_deref(), style.width), (Some("#E74C3C"), Some(2.0)));
+        let style = styling.edge_style("relationship", "IsA");
+        assert_eq!((style.color.as_deref(), style.arrows), (Some("#444444"), None));
+    }
+
+    #[test]
+    fn test_config_id_ignores_map_order() {
+        // Each HashMap hashes with its own random seed, so building the maps in opposite
+        // orders, let alone in another run, would change the id if key order leaked into it
+        let config = |reversed: bool| {
+            let mut entries = vec![
+                ("Person", "#E74C3C", "Location"),
+                ("Organization", "#3498DB", "Description"),
+                ("Medication", "#2ECC71", "Date"),
+                ("Place", "#F39C12", "Role"),
+            ];
+            if reversed {
+                entries.reverse();
+            }
+            let mut config = GraphConfig::default();
+            for (entity_type, color, attribute) in entries {
+                config.styling.entity_type_colors.insert(entity_type.to_string(), color.to_string());
+                config.styling.attribute_edge_colors.insert(attribute.to_string(), color.to_string());
+                config.styling.edge_styles.insert(
+                    entity_type.to_string(),
+                    EdgeStyle { color: Some(color.to_string()), ..EdgeStyle::default() },
+                );
+            }
+            config
+        };
+        assert_eq!(config(false).config_id(), config(true).config_id());
+        assert_eq!(config(false).config_id(), "6722e716d28dc5c0");
+    }
+}
@@ -0,0 +1,198 @@
+use crate::entity_extractor::{Entity, EntityType, ExtractionMetadata, ExtractionResult, Relationship, RelationshipType};
+use crate::error::Result;
+use crate::text_processor::{ProcessedText, TextProcessor};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// How the extraction stage turns text into nodes and edges
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtractionMode {
+    /// Entities, relationships and concepts from patterns or the LLM
+    #[default]
+    Entities,
+    /// Term co-occurrence network from sliding context windows; no patterns or LLM
+    Cooccurrence,
+    /// Org chart: the entity extraction cut down to manages / reports to / part of links,
+    /// drawn as a tree, see [`crate::hierarchy`]
+    Hierarchy,
+}
+
+/// Settings for [`ExtractionMode::Cooccurrence`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CooccurrenceConfig {
+    /// Words per context window, centred on each term
+    pub window_size: usize,
+    /// Minimum occurrences for a term, and minimum co-occurrences for an edge
+    pub min_count: usize,
+    /// Keep at most this many of the most frequent terms
+    pub max_terms: usize,
+    /// Drop edges whose pointwise mutual information is not above this
+    pub min_pmi: f64,
+    /// Ignore words shorter than this
+    pub min_word_length: usize,
+}
+
+impl Default for CooccurrenceConfig {
+    fn default() -> Self {
+        Self {
+            window_size: 5,
+            min_count: 2,
+            max_terms: 100,
+            min_pmi: 0.0,
+            min_word_length: 3,
+        }
+    }
+}
+
+/// Build a term co-occurrence network: one entity per frequent term and one symmetric
+/// relationship per pair of terms that share context windows, weighted by PMI.
+///
+/// Edge confidence is the normalized PMI (clamped to 0..1) so stronger associations are
+/// drawn wider; the raw PMI and count go into the edge label.
+pub fn extract_cooccurrence(
+    processed_text: &ProcessedText,
+    processor: &TextProcessor,
+    config: &CooccurrenceConfig,
+) -> Result<ExtractionResult> {
+    let start_time = web_time::Instant::now();
+
+    let terms: Vec<String> = processed_text
+        .words()
+        .map(|word| word.into_owned())
+        .filter(|word| word.chars().count() >= config.min_word_length && word.chars().all(char::is_alphabetic))
+        .collect();
+
+    let mut term_counts: HashMap<&str, usize> = HashMap::new();
+    for term in &terms {
+        *term_counts.entry(term.as_str()).or_insert(0) += 1;
+    }
+
+    // Most frequent terms first; ties alphabetically so output is stable
+    let mut vocabulary: Vec<(&str, usize)> =
+        term_counts.iter().filter(|(_, &count)| count >= config.min_count).map(|(t, c)| (*t, *c)).collect();
+    vocabulary.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    vocabulary.truncate(config.max_terms);
+    let max_count = vocabulary.first().map(|(_, count)| *count).unwrap_or(1);
+
+    let entities: Vec<Entity> = vocabulary
+        .iter()
+        .map(|(term, count)| Entity {
+            id: Uuid::new_v4().to_string(),
+            name: term.to_string(),
+            entity_type: EntityType::Other("term".to_string()),
+            attributes: Vec::new(),
+            confidence: 0.5 + 0.5 * (*count as f64 / max_count as f64),
+            position: None,
+            mentions: 1,
+            disambiguation: None,
+        })
+        .collect();
+    let ids: HashMap<&str, &str> = entities.iter().map(|e| (e.name.as_str(), e.id.as_str())).collect();
+
+    // Window i is centred on terms[i]; count each pair once per window centre
+    let windows = processor.extract_context_windows(&terms.join(" "), config.window_size)?;
+    let half = config.window_size / 2;
+    let mut pair_counts: HashMap<(&str, &str), usize> = HashMap::new();
+    let mut total_pairs = 0usize;
+    for (index, window) in windows.iter().enumerate() {
+        let center = terms[index].as_str();
+        let center_offset = index.min(half);
+        for (offset, other) in window.split_whitespace().enumerate() {
+            if offset == center_offset {
+                continue;
+            }
+            total_pairs += 1;
+            if center != other && ids.contains_key(center) && ids.contains_key(other) {
+                let key = if center < other { (center, other) } else { (other, center) };
+                *pair_counts.entry(key).or_insert(0) += 1;
+            }
+        }
+    }
+
+    let total_terms = terms.len().max(1) as f64;
+    let total_pairs = total_pairs.max(1) as f64;
+    let mut pairs: Vec<((&str, &str), usize)> = pair_counts.into_iter().collect();
+    pairs.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let relationships: Vec<Relationship> = pairs
+        .into_iter()
+        // Each co-occurrence is seen from both window centres
+        .map(|(pair, count)| (pair, count / 2 + count % 2))
+        .filter(|(_, count)| *count >= config.min_count)
+        .filter_map(|((first, second), count)| {
+            let p_pair = count as f64 * 2.0 / total_pairs;
+            let p_first = term_counts[first] as f64 / total_terms;
+            let p_second = term_counts[second] as f64 / total_terms;
+            let pmi = (p_pair / (p_first * p_second)).ln();
+            if pmi <= config.min_pmi {
+                return None;
+            }
+            let npmi = if p_pair < 1.0 { pmi / -p_pair.ln() } else { 1.0 };
+            Some(Relationship {
+                id: Uuid::new_v4().to_string(),
+                source_entity_id: ids[first].to_string(),
+                target_entity_id: ids[second].to_string(),
+                relationship_type: RelationshipType::Other("co-occurs".to_string()),
+                label: format!("PMI {:.2} ({}x)", pmi, count),
+                confidence: npmi.clamp(0.05, 1.0),
+                position: None,
+                bidirectional: true,
+                category: None,
+                context: None,
+            })
+        })
+        .collect();
+
+    let metadata = ExtractionMetadata {
+        total_entities: entities.len(),
+        total_relationships: relationships.len(),
+        total_concepts: 0,
+        processing_time_ms: start_time.elapsed().as_millis() as u64,
+        confidence_threshold: config.min_pmi,
+        extraction_method: "Co-occurrence".to_string(),
+    };
+
+    Ok(ExtractionResult {
+        entities,
+        relationships,
+        concepts: Vec::new(),
+        metadata,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text_processor::SourceType;
+
+    #[test]
+    fn test_cooccurring_terms_are_linked() {
+        let text = "The solar panel charges the battery. A solar panel feeds the battery bank. \
+                    Rain falls on the garden. The garden needs rain. Solar panel output varies.";
+        let processor = TextProcessor::new().unwrap();
+        let processed = processor.process_text(text, SourceType::Document).unwrap();
+        let result = extract_cooccurrence(&processed, &processor, &CooccurrenceConfig::default()).unwrap();
+
+        let names: Vec<&str> = result.entities.iter().map(|e| e.name.as_str()).collect();
+        assert!(names.contains(&"solar") && names.contains(&"panel"));
+        let id = |name: &str| result.entities.iter().find(|e| e.name == name).unwrap().id.clone();
+        let (solar, panel) = (id("solar"), id("panel"));
+        assert!(result.relationships.iter().any(|r| {
+            (r.source_entity_id == solar && r.target_entity_id == panel)
+                || (r.source_entity_id == panel && r.target_entity_id == solar)
+        }));
+        assert!(result.relationships.iter().all(|r| r.bidirectional && r.confidence > 0.0 && r.confidence <= 1.0));
+    }
+
+    #[test]
+    fn test_rare_terms_are_dropped() {
+        let processor = TextProcessor::new().unwrap();
+        let processed = processor.process_text("Alpha beta gamma delta.", SourceType::Document).unwrap();
+        let result = extract_cooccurrence(&processed, &processor, &CooccurrenceConfig::default()).unwrap();
+        assert!(result.entities.is_empty());
+        assert!(result.relationships.is_empty());
+    }
+}
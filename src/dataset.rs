@@ -0,0 +1,221 @@
+use crate::annotations::StandoffAnnotations;
+use crate::error::{GraphError, Result};
+use crate::sentence_splitter::SentenceSplitter;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// An entity span inside a [`DatasetRecord`] sentence
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatasetEntity {
+    /// Character offsets into the record's `text`, end exclusive
+    pub start: usize,
+    pub end: usize,
+    /// Entity type, e.g. `Person`
+    pub label: String,
+    pub text: String,
+}
+
+/// A relation between two entities of the same sentence
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatasetRelation {
+    /// Index into the record's `entities`
+    pub head: usize,
+    pub tail: usize,
+    pub label: String,
+}
+
+/// One line of a bootstrapped training set: a sentence with labeled spans and relations,
+/// the shape most NER/relation-extraction fine-tuning scripts expect.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatasetRecord {
+    /// Input file the sentence came from
+    pub source: String,
+    /// Position of the sentence in that file
+    pub sentence: usize,
+    pub text: String,
+    pub entities: Vec<DatasetEntity>,
+    pub relations: Vec<DatasetRelation>,
+}
+
+impl DatasetRecord {
+    /// Split `annotations` into one record per sentence. Mentions crossing a sentence
+    /// boundary are dropped, as are relations whose mentions are in different sentences.
+    /// Sentences without entities are kept: they are the negative examples.
+    pub fn from_annotations(source: &str, annotations: &StandoffAnnotations, splitter: &SentenceSplitter) -> Vec<Self> {
+        let text = annotations.text.as_str();
+        let mut records = Vec::new();
+        // Mention id -> (record, entity index), to resolve relations afterwards
+        let mut located: HashMap<&str, (usize, usize)> = HashMap::new();
+
+        for (index, (start, end)) in splitter.split_spans(text).into_iter().enumerate() {
+            let char_start = text[..start].chars().count();
+            let char_end = char_start + text[start..end].chars().count();
+            let mut entities = Vec::new();
+            for mention in &annotations.mentions {
+                if mention.start >= char_start && mention.end <= char_end {
+                    located.insert(mention.id.as_str(), (records.len(), entities.len()));
+                    entities.push(DatasetEntity {
+                        start: mention.start - char_start,
+                        end: mention.end - char_start,
+                        label: mention.label.clone(),
+                        text: mention.text.clone(),
+                    });
+                }
+            }
+            records.push(Self {
+                source: source.to_string(),
+                sentence: index,
+                text: text[start..end].to_string(),
+                entities,
+                relations: Vec::new(),
+            });
+        }
+
+        for relation in &annotations.relations {
+            let (Some(&(record, head)), Some(&(tail_record, tail))) =
+                (located.get(relation.arg1.as_str()), located.get(relation.arg2.as_str()))
+            else {
+                continue;
+            };
+            if record == tail_record {
+                records[record].relations.push(DatasetRelation { head, tail, label: relation.label.clone() });
+            }
+        }
+        records
+    }
+
+    /// Whether this record belongs in the review split, which holds out about
+    /// `fraction` of the records for manual checking. The choice hashes the source and
+    /// sentence text, so reruns over the same input put the same sentences up for review.
+    pub fn in_review_split(&self, fraction: f64) -> bool {
+        if fraction <= 0.0 {
+            return false;
+        }
+        let mut hasher = Sha256::new();
+        hasher.update(self.source.as_bytes());
+        hasher.update([0]);
+        hasher.update(self.text.as_bytes());
+        let digest = hasher.finalize();
+        let bucket = u64::from_be_bytes(digest[..8].try_into().expect("digest is 32 bytes"));
+        (bucket as f64 / u64::MAX as f64) < fraction
+    }
+}
+
+/// The `.txt` and `.md` files to bootstrap from: `path` itself when it is a file,
+/// otherwise the files under it (recursively, skipping hidden folders) in path order
+pub fn dataset_inputs(path: &str) -> Result<Vec<PathBuf>> {
+    let path = Path::new(path);
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    if !path.is_dir() {
+        return Err(GraphError::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("{} is neither a file nor a directory", path.display()),
+        )));
+    }
+    let mut files = Vec::new();
+    collect_text_files(path, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+fn collect_text_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let hidden = path.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.starts_with('.'));
+        if hidden {
+            continue;
+        }
+        if path.is_dir() {
+            collect_text_files(&path, files)?;
+        } else if matches!(path.extension().and_then(|ext| ext.to_str()), Some("txt" | "md")) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Write `records` as JSON Lines, one record per line
+pub fn write_dataset<'a>(path: &str, records: impl IntoIterator<Item = &'a DatasetRecord>) -> Result<usize> {
+    let mut writer = BufWriter::new(fs::File::create(path)?);
+    let mut written = 0;
+    for record in records {
+        serde_json::to_writer(&mut writer, record)?;
+        writer.write_all(b"\n")?;
+        written += 1;
+    }
+    writer.flush()?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::annotations::{MentionAnnotation, RelationAnnotation};
+
+    fn mention(id: &str, start: usize, end: usize, text: &str) -> MentionAnnotation {
+        MentionAnnotation {
+            id: id.to_string(),
+            entity_id: text.to_lowercase(),
+            label: "Person".to_string(),
+            start,
+            end,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_records_use_sentence_relative_offsets_and_keep_relations_within_a_sentence() {
+        let annotations = StandoffAnnotations {
+            text: "Zoë met Alice. Nothing here.\nAlice called Bob.".to_string(),
+            mentions: vec![
+                mention("T1", 0, 3, "Zoë"),
+                mention("T2", 8, 13, "Alice"),
+                mention("T3", 29, 34, "Alice"),
+                mention("T4", 42, 45, "Bob"),
+            ],
+            relations: vec![
+                RelationAnnotation { id: "R1".to_string(), label: "met".to_string(), arg1: "T1".to_string(), arg2: "T2".to_string() },
+                RelationAnnotation { id: "R2".to_string(), label: "knows".to_string(), arg1: "T1".to_string(), arg2: "T4".to_string() },
+                RelationAnnotation { id: "R3".to_string(), label: "called".to_string(), arg1: "T3".to_string(), arg2: "T4".to_string() },
+            ],
+        };
+        let records = DatasetRecord::from_annotations("notes.txt", &annotations, &SentenceSplitter::default());
+
+        let texts: Vec<&str> = records.iter().map(|record| record.text.as_str()).collect();
+        assert_eq!(texts, vec!["Zoë met Alice.", "Nothing here.", "Alice called Bob."]);
+        assert!(records[1].entities.is_empty());
+
+        for record in &records {
+            let chars: Vec<char> = record.text.chars().collect();
+            for entity in &record.entities {
+                assert_eq!(chars[entity.start..entity.end].iter().collect::<String>(), entity.text);
+            }
+        }
+        assert_eq!(records[0].relations, vec![DatasetRelation { head: 0, tail: 1, label: "met".to_string() }]);
+        assert_eq!(records[2].relations, vec![DatasetRelation { head: 0, tail: 1, label: "called".to_string() }]);
+    }
+
+    #[test]
+    fn test_review_split_is_deterministic_and_roughly_sized() {
+        let records: Vec<DatasetRecord> = (0..1000)
+            .map(|index| DatasetRecord {
+                source: "a.txt".to_string(),
+                sentence: index,
+                text: format!("Sentence number {}.", index),
+                entities: Vec::new(),
+                relations: Vec::new(),
+            })
+            .collect();
+        let review = records.iter().filter(|record| record.in_review_split(0.2)).count();
+        assert!((150..250).contains(&review), "{}", review);
+        assert_eq!(review, records.iter().filter(|record| record.in_review_split(0.2)).count());
+        assert!(!records.iter().any(|record| record.in_review_split(0.0)));
+        assert!(records.iter().all(|record| record.in_review_split(1.0)));
+    }
+}
@@ -0,0 +1,106 @@
+use crate::config::GraphConfig;
+use crate::chunker::EntityDetector;
+use crate::cooccurrence::ExtractionMode;
+use crate::entity_extractor::ExtractionResult;
+use crate::graph_builder::InteractiveGraph;
+use crate::transforms::config_transforms;
+
+/// Graphs with fewer nodes than this are reported as empty or nearly so
+pub const SPARSE_GRAPH_NODES: usize = 2;
+
+/// Whether a graph is too small to be worth looking at
+pub fn is_sparse(graph: &InteractiveGraph) -> bool {
+    graph.nodes.len() < SPARSE_GRAPH_NODES
+}
+
+/// What to try when a run found little or nothing, most specific first: filters and
+/// transforms that removed what was found, then ways to find more
+pub fn empty_result_hints(config: &GraphConfig, extraction: &ExtractionResult, graph: &InteractiveGraph) -> Vec<String> {
+    let extraction_config = &config.extraction;
+    let mut hints = Vec::new();
+
+    if extraction_config.entity_whitelist.is_some() {
+        hints.push("Only entities listed with --only/--only-file are kept; check their spelling or drop the filter".to_string());
+    }
+    let extracted = extraction.entities.len() + extraction.concepts.len();
+    let transforms = config_transforms(config).unwrap_or_default();
+    if !transforms.is_empty() && extracted > graph.nodes.len() {
+        hints.push(format!(
+            "The transforms ({}) removed {} of {} extracted items; relax or remove them",
+            transforms.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "),
+            extracted - graph.nodes.len(),
+            extracted
+        ));
+    }
+
+    if extraction_config.mode == ExtractionMode::Cooccurrence {
+        let cooccurrence = &extraction_config.cooccurrence;
+        hints.push(format!(
+            "Lower cooccurrence.min_count (now {}) or min_word_length (now {}); short texts rarely repeat terms",
+            cooccurrence.min_count, cooccurrence.min_word_length
+        ));
+        return hints;
+    }
+    if extraction_config.mode == ExtractionMode::Hierarchy && extraction.relationships.is_empty() {
+        hints.push(
+            "Hierarchy mode only keeps manages, reports to and part of links; phrase them as \"Alice manages Bob\", \
+             \"Bob reports to Alice\" or \"Sales is part of Acme Corp\""
+                .to_string(),
+        );
+    }
+
+    if config.text_processing.remove_stopwords {
+        hints.push("Keep stopwords with --no-remove-stopwords; removing them can break up the phrases patterns match".to_string());
+    }
+    if extraction_config.entity_detector == EntityDetector::Patterns {
+        hints.push(
+            "Loosen extraction.entity_patterns: the default only matches Capitalized Words, so lowercase and camel-case \
+             names (\"TechCorp\") are missed. Set extraction.entity_detector to \"noun_phrases\" to find lowercase \
+             phrases such as \"the analytics module\", or list known names in extraction.gazetteer_files"
+                .to_string(),
+        );
+    } else {
+        hints.push("List known names in extraction.gazetteer_files".to_string());
+    }
+    if extraction_config.uses_llm() {
+        hints.push(format!(
+            "Check that the LLM at {} is running and model {} is pulled; rerun with -v to see its responses",
+            extraction_config.llm_endpoint, extraction_config.llm_model
+        ));
+    } else {
+        hints.push("Extract with an LLM: --use-llm (needs a running Ollama server)".to_string());
+    }
+    hints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::{Pipeline, PipelineOutput};
+
+    #[tokio::test]
+    async fn test_hints_point_at_what_removed_or_missed_entities() {
+        let mut config = GraphConfig {
+            transforms: vec!["min_confidence 0.99".to_string()],
+            ..GraphConfig::default()
+        };
+        let PipelineOutput { graph, extraction } = Pipeline::new()
+            .with_config(config.clone())
+            .with_text("Alice is connected to Bob.")
+            .run_detailed()
+            .await
+            .expect("Pipeline failed");
+
+        assert!(is_sparse(&graph));
+        let hints = empty_result_hints(&config, &extraction, &graph);
+        assert!(hints[0].starts_with("The transforms (min_confidence 0.99) removed"));
+        assert!(hints.iter().any(|hint| hint.contains("--no-remove-stopwords")));
+        assert!(hints.iter().any(|hint| hint.contains("--use-llm")));
+
+        config.extraction.mode = ExtractionMode::Cooccurrence;
+        config.transforms.clear();
+        let hints = empty_result_hints(&config, &extraction, &graph);
+        assert_eq!(hints.len(), 1);
+        assert!(hints[0].contains("cooccurrence.min_count"));
+    }
+}
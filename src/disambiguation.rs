@@ -0,0 +1,384 @@
+//! Splitting an entity whose name refers to different things ("Mercury" the planet and
+//! "Mercury" the company) into one entity per sense.
+//!
+//! Each sentence mentioning the name votes for the type whose context cues it contains
+//! most. Mentions without a clear vote join the nearest voting mention, and senses with
+//! too few mentions fold into the largest one. When more than one sense remains, the
+//! entity is split and each part carries its [`Disambiguation`].
+
+use crate::entity_extractor::{entity_type_from_name, Entity, EntityType, Relationship, TextPosition};
+use crate::llm_output::repair_json;
+use crate::text_processor::ProcessedText;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// Settings for splitting same-named entities by context (`extraction.disambiguation`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisambiguationConfig {
+    /// Split entities whose mentions point to different types
+    pub enabled: bool,
+    /// Mentions a sense needs to become an entity of its own; smaller ones join the
+    /// largest sense
+    pub min_mentions: usize,
+    /// Words hinting at each type (`place`, `organization`, custom type names, ...),
+    /// matched as whole words in the sentences mentioning the entity
+    pub context_cues: BTreeMap<String, Vec<String>>,
+    /// Let the LLM settle names whose mentions vote for different types (needs the LLM
+    /// extractor)
+    pub use_llm: bool,
+}
+
+impl Default for DisambiguationConfig {
+    fn default() -> Self {
+        let cues = |words: &[&str]| words.iter().map(|word| word.to_string()).collect();
+        Self {
+            enabled: false,
+            min_mentions: 2,
+            context_cues: BTreeMap::from([
+                ("person".to_string(), cues(&["he", "she", "born", "married", "wife", "husband", "son", "daughter", "said", "actor", "singer", "author"])),
+                ("place".to_string(), cues(&["planet", "orbit", "orbits", "sun", "moon", "city", "town", "river", "mountain", "country", "located", "visited", "capital"])),
+                ("organization".to_string(), cues(&["company", "shares", "stock", "ceo", "founded", "acquired", "revenue", "firm", "startup", "employees", "headquarters", "inc"])),
+                ("event".to_string(), cues(&["held", "festival", "conference", "war", "battle", "election", "tournament"])),
+                ("product".to_string(), cues(&["released", "version", "launched", "model", "app", "device", "price"])),
+            ]),
+            use_llm: false,
+        }
+    }
+}
+
+/// How an entity split off from a same-named one was told apart from the others
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Disambiguation {
+    /// Name of the sense, e.g. `place` or the LLM's `planet`; shown after the node label
+    pub sense: String,
+    /// Number of senses the name was split into
+    pub senses: usize,
+    /// Indices of the sentences mentioning this sense
+    pub sentences: Vec<usize>,
+    /// Context cues that voted for the sense
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cues: Vec<String>,
+    /// `context` or `llm`
+    pub method: String,
+}
+
+/// One meaning of a name: what it is and the sentences using it that way
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sense {
+    pub label: String,
+    pub entity_type: EntityType,
+    pub sentences: Vec<usize>,
+    pub cues: Vec<String>,
+}
+
+/// Indices of the sentences mentioning `name` as a whole word
+pub fn mention_sentences(processed_text: &ProcessedText, name: &str) -> Vec<usize> {
+    processed_text
+        .sentences()
+        .enumerate()
+        .filter(|(_, sentence)| find_whole_word(sentence, name).is_some())
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Byte range of the first whole-word occurrence of `name` in `sentence`
+fn find_whole_word(sentence: &str, name: &str) -> Option<(usize, usize)> {
+    if name.is_empty() {
+        return None;
+    }
+    sentence.match_indices(name).map(|(start, _)| (start, start + name.len())).find(|(start, end)| {
+        !sentence[..*start].chars().next_back().is_some_and(char::is_alphanumeric)
+            && !sentence[*end..].chars().next().is_some_and(char::is_alphanumeric)
+    })
+}
+
+/// Senses of `name` by the context cues of the sentences mentioning it, in order of
+/// first mention. A single sense means there is nothing to split.
+pub fn context_senses(processed_text: &ProcessedText, name: &str, config: &DisambiguationConfig) -> Vec<Sense> {
+    let mentions = mention_sentences(processed_text, name);
+    let own_words = padded_words(name);
+    let votes: Vec<Option<(&str, Vec<String>)>> = mentions
+        .iter()
+        .map(|&index| {
+            let words = padded_words(processed_text.sentence(index).unwrap_or_default());
+            let mut tally: Vec<(&str, Vec<String>)> = config
+                .context_cues
+                .iter()
+                .map(|(sense, cues)| {
+                    let found = cues
+                        .iter()
+                        .map(|cue| cue.to_lowercase())
+                        .filter(|cue| {
+                            let cue = format!(" {} ", cue.trim());
+                            words.contains(&cue) && !own_words.contains(&cue)
+                        })
+                        .collect();
+                    (sense.as_str(), found)
+                })
+                .collect();
+            tally.sort_by_key(|(_, found)| std::cmp::Reverse(found.len()));
+            match tally.as_slice() {
+                [(_, first), ..] if first.is_empty() => None,
+                [(_, first), (_, second), ..] if first.len() == second.len() => None,
+                [_, ..] => Some(tally.swap_remove(0)),
+                [] => None,
+            }
+        })
+        .collect();
+
+    // Mentions without a clear vote join the nearest voting mention, the earlier on a tie
+    let voted: Vec<usize> = (0..mentions.len()).filter(|&i| votes[i].is_some()).collect();
+    if voted.is_empty() {
+        return Vec::new();
+    }
+    let mut senses: Vec<Sense> = Vec::new();
+    for (i, &sentence) in mentions.iter().enumerate() {
+        let voter = *voted
+            .iter()
+            .min_by_key(|&&v| mentions[v].abs_diff(sentence))
+            .expect("at least one mention voted");
+        let (label, cues) = votes[voter].as_ref().expect("voters have a vote");
+        let index = match senses.iter().position(|sense| sense.label == *label) {
+            Some(index) => index,
+            None => {
+                senses.push(Sense {
+                    label: label.to_string(),
+                    entity_type: sense_type(label),
+                    sentences: Vec::new(),
+                    cues: Vec::new(),
+                });
+                senses.len() - 1
+            }
+        };
+        let sense = &mut senses[index];
+        sense.sentences.push(sentence);
+        if i == voter {
+            for cue in cues {
+                if !sense.cues.contains(cue) {
+                    sense.cues.push(cue.clone());
+                }
+            }
+        }
+    }
+    fold_small_senses(senses, config.min_mentions)
+}
+
+/// Merge senses with fewer than `min_mentions` sentences into the largest sense
+fn fold_small_senses(mut senses: Vec<Sense>, min_mentions: usize) -> Vec<Sense> {
+    let Some(largest) = senses
+        .iter()
+        .enumerate()
+        .max_by(|(a_index, a), (b_index, b)| a.sentences.len().cmp(&b.sentences.len()).then(b_index.cmp(a_index)))
+        .map(|(index, _)| index)
+    else {
+        return senses;
+    };
+    let mut folded = Vec::new();
+    for (index, sense) in senses.iter_mut().enumerate() {
+        if index != largest && sense.sentences.len() < min_mentions {
+            folded.append(&mut sense.sentences);
+        }
+    }
+    senses[largest].sentences.append(&mut folded);
+    senses[largest].sentences.sort_unstable();
+    senses.retain(|sense| !sense.sentences.is_empty());
+    senses
+}
+
+/// Entity type for a sense name: the built-in types by name, anything else custom
+pub fn sense_type(name: &str) -> EntityType {
+    match name.to_lowercase().as_str() {
+        "event" => EntityType::Event,
+        "product" => EntityType::Product,
+        _ => entity_type_from_name(name),
+    }
+}
+
+fn padded_words(text: &str) -> String {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    format!(" {} ", words.join(" "))
+}
+
+/// One entity per sense. The sense mentioned in the entity's own sentence keeps its id
+/// and attributes; the others get new ids and a position at their first mention.
+pub fn split_entity(entity: &Entity, senses: &[Sense], method: &str, processed_text: &ProcessedText) -> Vec<Entity> {
+    let own_sentence = entity.position.as_ref().map(|position| position.sentence_index);
+    let keeper = senses
+        .iter()
+        .position(|sense| own_sentence.is_some_and(|index| sense.sentences.contains(&index)))
+        .unwrap_or(0);
+    senses
+        .iter()
+        .enumerate()
+        .map(|(index, sense)| {
+            let mut split = entity.clone();
+            split.entity_type = sense.entity_type.clone();
+            if index != keeper {
+                split.id = Uuid::new_v4().to_string();
+                split.attributes.clear();
+                split.position = sense.sentences.first().and_then(|&sentence_index| {
+                    let (start, end) = find_whole_word(processed_text.sentence(sentence_index)?, &entity.name)?;
+                    Some(TextPosition { start, end, sentence_index })
+                });
+            }
+            split.disambiguation = Some(Disambiguation {
+                sense: sense.label.clone(),
+                senses: senses.len(),
+                sentences: sense.sentences.clone(),
+                cues: sense.cues.clone(),
+                method: method.to_string(),
+            });
+            split
+        })
+        .collect()
+}
+
+/// Point relationship endpoints at the sense of a split entity that is mentioned in the
+/// relationship's sentence, for relationships found by name before the split mattered
+pub fn assign_sense_relationships(entities: &[Entity], relationships: &mut [Relationship]) {
+    let names: HashMap<&str, &str> = entities
+        .iter()
+        .filter(|entity| entity.disambiguation.is_some())
+        .map(|entity| (entity.id.as_str(), entity.name.as_str()))
+        .collect();
+    if names.is_empty() {
+        return;
+    }
+    for relationship in relationships {
+        let Some(sentence_index) = relationship.position.as_ref().map(|position| position.sentence_index) else {
+            continue;
+        };
+        for endpoint in [&mut relationship.source_entity_id, &mut relationship.target_entity_id] {
+            let Some(name) = names.get(endpoint.as_str()) else {
+                continue;
+            };
+            let sense = entities.iter().find(|entity| {
+                entity.name == *name
+                    && entity.disambiguation.as_ref().is_some_and(|found| found.sentences.contains(&sentence_index))
+            });
+            if let Some(sense) = sense {
+                *endpoint = sense.id.clone();
+            }
+        }
+    }
+}
+
+/// Prompt asking the LLM to group the numbered sentences mentioning `name` by meaning
+pub fn adjudication_prompt(name: &str, sentences: &[&str]) -> String {
+    let numbered: Vec<String> = sentences.iter().enumerate().map(|(i, sentence)| format!("{}. {}", i + 1, sentence.trim())).collect();
+    format!(
+        "The name \"{}\" appears in the numbered sentences below. Decide whether it refers to one thing or to several different things, and group the sentences by the thing they mean.\n\n{}\n\nAnswer with JSON only, in this shape:\n{{\"senses\": [{{\"label\": \"short description\", \"type\": \"person|place|organization|event|product|concept\", \"sentences\": [1, 2]}}]}}",
+        name,
+        numbered.join("\n")
+    )
+}
+
+#[derive(Debug, Deserialize)]
+struct AdjudicationAnswer {
+    senses: Vec<AdjudicatedSense>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AdjudicatedSense {
+    label: String,
+    #[serde(rename = "type", default)]
+    entity_type: String,
+    #[serde(default)]
+    sentences: Vec<usize>,
+}
+
+/// Senses from an adjudication answer about the sentences at `mentions` (numbered from
+/// 1 in the prompt). Sentences the answer leaves out join the sense nearest to them.
+pub fn parse_adjudication(response: &str, mentions: &[usize]) -> Option<Vec<Sense>> {
+    let answer: AdjudicationAnswer = serde_json::from_str(&repair_json(response)).ok()?;
+    let mut senses: Vec<Sense> = answer
+        .senses
+        .into_iter()
+        .map(|sense| Sense {
+            entity_type: sense_type(if sense.entity_type.trim().is_empty() { &sense.label } else { sense.entity_type.trim() }),
+            label: sense.label.trim().to_string(),
+            sentences: sense.sentences.iter().filter_map(|number| mentions.get(number.checked_sub(1)?).copied()).collect(),
+            cues: Vec::new(),
+        })
+        .filter(|sense| !sense.label.is_empty() && !sense.sentences.is_empty())
+        .collect();
+    if senses.is_empty() {
+        return None;
+    }
+    for &sentence in mentions {
+        if senses.iter().any(|sense| sense.sentences.contains(&sentence)) {
+            continue;
+        }
+        let nearest = senses
+            .iter_mut()
+            .min_by_key(|sense| sense.sentences.iter().map(|&other| other.abs_diff(sentence)).min().unwrap_or(usize::MAX))?;
+        nearest.sentences.push(sentence);
+    }
+    for sense in &mut senses {
+        sense.sentences.sort_unstable();
+        sense.sentences.dedup();
+    }
+    Some(senses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ExtractionConfig;
+    use crate::entity_extractor::EntityExtractor;
+    use crate::text_processor::{SourceType, TextProcessor};
+
+    const TEXT: &str = "Mercury is the closest planet to the Sun. Mercury completes an orbit in 88 days. \
+                        Mercury is a company founded by Alice. Investors bought shares of Mercury from Bob.";
+
+    fn processed() -> ProcessedText {
+        TextProcessor::new().unwrap().process_text(TEXT, SourceType::Document).unwrap()
+    }
+
+    #[test]
+    fn test_context_cues_separate_senses() {
+        let senses = context_senses(&processed(), "Mercury", &DisambiguationConfig::default());
+        assert_eq!(senses.len(), 2);
+        assert_eq!((senses[0].label.as_str(), senses[0].sentences.as_slice()), ("place", &[0, 1][..]));
+        assert_eq!((senses[1].label.as_str(), senses[1].sentences.as_slice()), ("organization", &[2, 3][..]));
+        assert_eq!(senses[1].entity_type, EntityType::Organization);
+        assert!(senses[0].cues.contains(&"planet".to_string()));
+
+        // A lone mention of another sense folds into the larger one
+        let config = DisambiguationConfig { min_mentions: 3, ..DisambiguationConfig::default() };
+        assert_eq!(context_senses(&processed(), "Mercury", &config).len(), 1);
+
+        let answer = r#"Sure: {"senses": [{"label": "planet", "type": "place", "sentences": [1, 2]}, {"label": "company", "type": "organization", "sentences": [3]}]}"#;
+        let senses = parse_adjudication(answer, &[0, 1, 2, 3]).unwrap();
+        assert_eq!(senses[1].sentences, vec![2, 3]);
+        assert_eq!(senses[0].entity_type, EntityType::Place);
+    }
+
+    #[tokio::test]
+    async fn test_same_name_becomes_one_entity_per_sense() {
+        let mut config = ExtractionConfig::default();
+        config.disambiguation.enabled = true;
+        let result = EntityExtractor::new(config).unwrap().extract_from_text(&processed()).await.unwrap();
+
+        let mercuries: Vec<&Entity> = result.entities.iter().filter(|entity| entity.name == "Mercury").collect();
+        assert_eq!(mercuries.len(), 2);
+        assert_ne!(mercuries[0].id, mercuries[1].id);
+        let company = mercuries.iter().find(|entity| entity.entity_type == EntityType::Organization).unwrap();
+        let disambiguation = company.disambiguation.as_ref().unwrap();
+        assert_eq!((disambiguation.sense.as_str(), disambiguation.senses), ("organization", 2));
+        assert_eq!(disambiguation.method, "context");
+
+        // Relationships in the company's sentences attach to the company
+        let alice = result.entities.iter().find(|entity| entity.name == "Alice").unwrap();
+        assert!(result.relationships.iter().any(|relationship| {
+            [&relationship.source_entity_id, &relationship.target_entity_id] == [&company.id, &alice.id]
+                || [&relationship.source_entity_id, &relationship.target_entity_id] == [&alice.id, &company.id]
+        }));
+    }
+}
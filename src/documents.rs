@@ -0,0 +1,74 @@
+use crate::graph_builder::InteractiveGraph;
+
+/// Separator placed between documents combined into one input text. A blank line ends
+/// a sentence and a paragraph in every source format, so no sentence spans two files.
+pub const DOCUMENT_SEPARATOR: &str = "\n\n";
+
+/// One input file of a multi-file run
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceDocument {
+    /// Name recorded in node `sources`, usually the path as given
+    pub name: String,
+    pub text: String,
+}
+
+impl SourceDocument {
+    pub fn new(name: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            text: text.into(),
+        }
+    }
+}
+
+/// The documents' texts as one input, in order, separated by [`DOCUMENT_SEPARATOR`]; a
+/// single document's text is returned unchanged
+pub fn combine_documents(documents: &[SourceDocument]) -> String {
+    documents
+        .iter()
+        .map(|document| document.text.as_str())
+        .collect::<Vec<_>>()
+        .join(DOCUMENT_SEPARATOR)
+}
+
+/// Set every node's `metadata.sources` to the documents mentioning its label or original
+/// text, ignoring case, and list the documents under `inputs` in the provenance
+pub fn record_sources(graph: &mut InteractiveGraph, documents: &[SourceDocument]) {
+    let texts: Vec<String> = documents.iter().map(|document| document.text.to_lowercase()).collect();
+    for node in &mut graph.nodes {
+        let names = [node.label.to_lowercase(), node.metadata.original_text.to_lowercase()];
+        node.metadata.sources = documents
+            .iter()
+            .zip(&texts)
+            .filter(|(_, text)| names.iter().any(|name| !name.is_empty() && text.contains(name.as_str())))
+            .map(|(document, _)| document.name.clone())
+            .collect();
+    }
+    let names: Vec<&str> = documents.iter().map(|document| document.name.as_str()).collect();
+    graph.metadata.provenance.config_summary.insert("inputs".to_string(), names.join(", "));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::Pipeline;
+
+    #[tokio::test]
+    async fn test_combined_documents_keep_per_file_sources() {
+        let documents = [
+            SourceDocument::new("a.txt", "Alice works at Initech.\n"),
+            SourceDocument::new("b.txt", "Bob met Alice in Berlin."),
+        ];
+        let text = combine_documents(&documents);
+        assert_eq!(text, "Alice works at Initech.\n\n\nBob met Alice in Berlin.");
+        assert_eq!(combine_documents(&documents[..1]), documents[0].text);
+
+        let mut graph = Pipeline::new().with_text(text).run().await.unwrap();
+        record_sources(&mut graph, &documents);
+        let sources = |label: &str| graph.nodes.iter().find(|node| node.label == label).expect(label).metadata.sources.clone();
+        assert_eq!(sources("Alice"), ["a.txt", "b.txt"]);
+        assert_eq!(sources("Initech"), ["a.txt"]);
+        assert_eq!(sources("Bob"), ["b.txt"]);
+        assert_eq!(graph.metadata.provenance.config_summary["inputs"], "a.txt, b.txt");
+    }
+}
@@ -1,11 +1,37 @@
+use crate::cache::{LlmCache, LlmRequest};
+use crate::chunker::{noun_phrases, EntityDetector};
+use crate::ner_model::NerModel;
 use crate::config::ExtractionConfig;
+use crate::disambiguation::{
+    adjudication_prompt, assign_sense_relationships, context_senses, mention_sentences, parse_adjudication, split_entity,
+    Disambiguation,
+};
 use crate::error::{GraphError, Result};
-use crate::text_processor::ProcessedText;
-use regex::Regex;
+use crate::extractors::{
+    merge_concepts, merge_entities, merge_relationships, reduce_chunk_entities, Extractor, ExtractorKind,
+    GazetteerExtractor, LlmExtractor, PatternExtractor,
+};
+use crate::estimate::estimate_tokens;
+use crate::gazetteer::{Gazetteer, GAZETTEER_CONFIDENCE};
+use crate::keywords::extract_keywords;
+use crate::languages::LanguagePatterns;
+use crate::llm_output::{
+    parse_llm_items, LlmConcept, LlmEntity, LlmItemKind, LlmRelationship, DEEP_RELATIONSHIP_CATEGORIES,
+};
+use crate::prompt_budget::{names_within_budget, PromptPlan};
+use crate::relationship_rules::{RelationshipRule, RULE_CONFIDENCE};
+use crate::relationship_validation::RelationshipValidator;
+use crate::sentence_splitter::SentenceSplitter;
+use crate::text_processor::{stem_word, ProcessedText, TextProcessor};
+use regex::{Regex, RegexSet};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::sync::Arc;
+use tracing::{debug, info, warn};
 use uuid::Uuid;
-use reqwest;
 use serde_json;
 
 // Ollama API request/response structures
@@ -14,6 +40,16 @@ struct OllamaRequest {
     model: String,
     prompt: String,
     stream: bool,
+    /// `"json"` or a JSON schema the response must follow
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<serde_json::Value>,
+    options: OllamaOptions,
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaOptions {
+    /// Context window, so Ollama does not truncate prompts the budget lets through
+    num_ctx: usize,
 }
 
 #[derive(Debug, Deserialize)]
@@ -25,6 +61,31 @@ struct OllamaResponse {
     done: bool,
 }
 
+/// One line of a streamed Ollama response
+#[cfg(feature = "native")]
+#[derive(Debug, Deserialize)]
+struct OllamaStreamChunk {
+    #[serde(default)]
+    response: String,
+    #[serde(default)]
+    done: bool,
+    error: Option<String>,
+}
+
+/// Progress of a streamed LLM response, reported to the
+/// [`on_llm_progress`](EntityExtractor::on_llm_progress) callback as chunks arrive
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LlmProgress {
+    /// Chunks received so far; Ollama sends about one token per chunk
+    pub tokens: usize,
+    /// Characters of response text received so far
+    pub characters: usize,
+    pub done: bool,
+}
+
+type LlmProgressCallback = Arc<dyn Fn(&LlmProgress) + Send + Sync>;
+type WarningCallback = Arc<dyn Fn(&str) + Send + Sync>;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Entity {
     pub id: String,
@@ -33,6 +94,17 @@ pub struct Entity {
     pub attributes: Vec<Attribute>,
     pub confidence: f64,
     pub position: Option<TextPosition>,
+    /// Number of chunks (prompt segments) the entity was found in; 1 when the text was
+    /// extracted in one piece
+    #[serde(default = "default_mentions")]
+    pub mentions: usize,
+    /// Which sense of its name the entity is, when same-named mentions were split apart
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disambiguation: Option<Disambiguation>,
+}
+
+fn default_mentions() -> usize {
+    1
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +116,17 @@ pub struct Relationship {
     pub label: String,
     pub confidence: f64,
     pub position: Option<TextPosition>,
+    /// True for symmetric relations ("connected to", "collaborates with") where
+    /// source and target are interchangeable
+    #[serde(default)]
+    pub bidirectional: bool,
+    /// Deep-analysis category (`implicit`, `temporal`, `hierarchical`, `functional` or
+    /// `dependency`) of a relationship the LLM found in that pass
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Text the LLM cited as evidence for the relationship
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,7 +148,7 @@ pub struct Attribute {
     pub confidence: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum EntityType {
     Person,
     Place,
@@ -100,6 +183,10 @@ pub enum AttributeType {
     Number,
     Category,
     Property,
+    /// Job title or role, e.g. "CTO" from "Alice, CTO of TechCorp,"
+    Role,
+    /// Organization an entity belongs to, e.g. "TechCorp" from "Alice, CTO of TechCorp,"
+    Affiliation,
     Other(String),
 }
 
@@ -110,6 +197,18 @@ pub struct TextPosition {
     pub sentence_index: usize,
 }
 
+/// One LLM request an extraction would make, see [`EntityExtractor::planned_llm_calls`]
+#[derive(Debug, Clone)]
+pub struct PlannedLlmCall {
+    /// `entities`, `relationships` or `concepts`
+    pub stage: &'static str,
+    pub prompt: String,
+    /// Answered from the LLM response cache, so no request would be sent
+    pub cached: bool,
+    /// Items the pattern extractor finds for this stage, a guide to the response size
+    pub expected_items: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtractionResult {
     pub entities: Vec<Entity>,
@@ -118,6 +217,101 @@ pub struct ExtractionResult {
     pub metadata: ExtractionMetadata,
 }
 
+impl ExtractionResult {
+    /// Keep only entities matching one of `names`, the relationships between kept entities,
+    /// and no concepts. A name matches an entity whose name equals it or contains it as
+    /// whole words, ignoring case ("alice" matches "Alice Smith").
+    pub fn retain_entities(&mut self, names: &[String]) {
+        let wanted: Vec<Vec<String>> = names
+            .iter()
+            .map(|name| name.split_whitespace().map(str::to_lowercase).collect::<Vec<_>>())
+            .filter(|words| !words.is_empty())
+            .collect();
+        let matches = |entity: &Entity| {
+            let words: Vec<String> = entity.name.split_whitespace().map(str::to_lowercase).collect();
+            wanted.iter().any(|name| words.windows(name.len()).any(|window| window == name.as_slice()))
+        };
+
+        self.entities.retain(|entity| matches(entity));
+        let kept: HashSet<&str> = self.entities.iter().map(|entity| entity.id.as_str()).collect();
+        self.relationships.retain(|relationship| {
+            kept.contains(relationship.source_entity_id.as_str()) && kept.contains(relationship.target_entity_id.as_str())
+        });
+        self.concepts.clear();
+
+        self.metadata.total_entities = self.entities.len();
+        self.metadata.total_relationships = self.relationships.len();
+        self.metadata.total_concepts = 0;
+    }
+
+    /// Replace the random ids with ones hashed from content and sort everything by name,
+    /// so the same text always gives the same ids in the same order (`deterministic`).
+    ///
+    /// Entities are keyed by name (ignoring case) and type, their attributes by entity,
+    /// name and value, relationships by endpoints, type and label, and concepts by name.
+    /// An id another item already took gets a `-2`, `-3`, ... suffix.
+    pub fn assign_stable_ids(&mut self) {
+        let mut taken = HashSet::new();
+        let mut stable_id = |kind: &str, key: String| {
+            let digest = Sha256::digest(format!("{}\0{}", kind, key).as_bytes());
+            let hex: String = digest.iter().take(6).map(|byte| format!("{:02x}", byte)).collect();
+            let base = format!("{}-{}", kind, hex);
+            let mut id = base.clone();
+            let mut suffix = 1;
+            while !taken.insert(id.clone()) {
+                suffix += 1;
+                id = format!("{}-{}", base, suffix);
+            }
+            id
+        };
+
+        let entity_key = |entity: &Entity| (entity.name.to_lowercase(), format!("{:?}", entity.entity_type), entity.name.clone());
+        self.entities.sort_by_key(|entity| entity_key(entity));
+        let mut entity_ids = HashMap::new();
+        for entity in &mut self.entities {
+            let (name, entity_type, _) = entity_key(entity);
+            let id = stable_id("entity", format!("{}\0{}", name, entity_type));
+            entity_ids.insert(std::mem::replace(&mut entity.id, id.clone()), id);
+            entity.attributes.sort_by(|a, b| (&a.name, &a.value).cmp(&(&b.name, &b.value)));
+            for attribute in &mut entity.attributes {
+                attribute.id = stable_id("attribute", format!("{}\0{}\0{}", entity.id, attribute.name, attribute.value));
+            }
+        }
+
+        for relationship in &mut self.relationships {
+            for endpoint in [&mut relationship.source_entity_id, &mut relationship.target_entity_id] {
+                if let Some(id) = entity_ids.get(endpoint.as_str()) {
+                    *endpoint = id.clone();
+                }
+            }
+        }
+        let relationship_key = |relationship: &Relationship| {
+            (
+                relationship.source_entity_id.clone(),
+                relationship.target_entity_id.clone(),
+                format!("{:?}", relationship.relationship_type),
+                relationship.label.clone(),
+            )
+        };
+        self.relationships.sort_by_key(|relationship| relationship_key(relationship));
+        for relationship in &mut self.relationships {
+            let (source, target, relationship_type, label) = relationship_key(relationship);
+            relationship.id = stable_id("relationship", format!("{}\0{}\0{}\0{}", source, target, relationship_type, label));
+        }
+
+        self.concepts.sort_by_key(|concept| (concept.name.to_lowercase(), concept.name.clone()));
+        for concept in &mut self.concepts {
+            concept.id = stable_id("concept", concept.name.to_lowercase());
+            for related in &mut concept.related_entities {
+                if let Some(id) = entity_ids.get(related.as_str()) {
+                    *related = id.clone();
+                }
+            }
+            concept.related_entities.sort();
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtractionMetadata {
     pub total_entities: usize,
@@ -128,56 +322,501 @@ pub struct ExtractionMetadata {
     pub extraction_method: String,
 }
 
+/// Phrases describing relations that hold equally in both directions
+const SYMMETRIC_PHRASES: &[&str] = &[
+    "connected to",
+    "linked to",
+    "related to",
+    "relates to",
+    "associated with",
+    "collaborates with",
+    "collaborated with",
+    "communicates with",
+    "works with",
+    "worked with",
+    "partners with",
+    "partnered with",
+    "married to",
+    "friends with",
+];
+
+/// Whether a relation phrase (or the text spanning it) describes a symmetric relation
+pub(crate) fn is_symmetric_relation(text: &str) -> bool {
+    let normalized = text.to_lowercase().replace('_', " ");
+    SYMMETRIC_PHRASES.iter().any(|phrase| normalized.contains(phrase))
+}
+
+/// Role and affiliation from an appositive right after `entity` in `context`:
+/// "Alice, CTO of TechCorp," gives `("CTO", Some("TechCorp"))`, as do "Alice, the CTO at
+/// TechCorp." and "Alice, a senior engineer for TechCorp,". The organization must be
+/// capitalized, and without an article so must the role, which keeps "Alice, who works at
+/// TechCorp," out. A bare title from [`BARE_TITLES`] ("Alice, CTO, said") gives just the role.
+fn find_appositive(entity: &str, context: &str) -> Option<(String, Option<String>)> {
+    let entity = regex::escape(entity);
+    let with_affiliation = Regex::new(&format!(
+        r"{entity},\s+(?P<article>(?:the|a|an)\s+)?(?P<role>[\w-]+(?:\s+[\w-]+){{0,3}}?)\s+(?:of|at|for|with)\s+(?:the\s+)?(?P<org>[A-Z][\w&-]*(?:\s+[A-Z][\w&-]*)*)\s*(?:[,.;)]|$)"
+    ))
+    .ok()?;
+    if let Some(caps) = with_affiliation.captures(context) {
+        let role = &caps["role"];
+        if caps.name("article").is_some() || role.starts_with(|c: char| c.is_uppercase()) {
+            return Some((role.to_string(), Some(caps["org"].to_string())));
+        }
+    }
+
+    let bare_title = Regex::new(&format!(r"{entity},\s+(?P<role>[\w-]+)\s*[,.;)]")).ok()?;
+    let caps = bare_title.captures(context)?;
+    let role = &caps["role"];
+    BARE_TITLES.contains(&role.to_lowercase().as_str()).then(|| (role.to_string(), None))
+}
+
+/// The type name in the Debug form graph nodes store their entity type in:
+/// `Other("Medication")` -> `Medication`, `Person` -> `Person`
+pub fn entity_type_name(stored: &str) -> &str {
+    stored
+        .strip_prefix("Other(\"")
+        .and_then(|rest| rest.strip_suffix("\")"))
+        .unwrap_or(stored)
+}
+
+/// Entity type for a type name reported by an LLM or plugin ("person", "organization", ...)
+pub(crate) fn entity_type_from_name(name: &str) -> EntityType {
+    match name.to_lowercase().as_str() {
+        "person" => EntityType::Person,
+        "place" => EntityType::Place,
+        "organization" => EntityType::Organization,
+        "system" => EntityType::Other("System".to_string()),
+        "process" => EntityType::Other("Process".to_string()),
+        "concept" => EntityType::Other("Concept".to_string()),
+        _ => EntityType::Other(name.to_string()),
+    }
+}
+
+/// Configured patterns compiled both individually and as a [`RegexSet`], so a text is
+/// scanned once to find which patterns can match before running only those
+struct PatternSet {
+    set: RegexSet,
+    patterns: Vec<Regex>,
+}
+
+impl PatternSet {
+    fn new(patterns: &[String]) -> Result<Self> {
+        Ok(Self {
+            set: RegexSet::new(patterns)?,
+            patterns: patterns
+                .iter()
+                .map(|pattern| Regex::new(pattern).map_err(GraphError::from))
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    /// Patterns that match somewhere in `text`, in configuration order
+    fn matching<'a>(&'a self, text: &str) -> impl Iterator<Item = &'a Regex> + 'a {
+        self.set.matches(text).into_iter().map(move |index| &self.patterns[index])
+    }
+}
+
+/// Compiled patterns of one `extraction.languages` entry, falling back to the base
+/// patterns where the entry lists none
+struct LanguageRules {
+    entity_patterns: PatternSet,
+    relationship_patterns: PatternSet,
+    concept_patterns: PatternSet,
+    settings: LanguagePatterns,
+}
+
+impl LanguageRules {
+    fn new(settings: &LanguagePatterns, config: &ExtractionConfig) -> Result<Self> {
+        let or_base = |own: &Vec<String>, base: &Vec<String>| PatternSet::new(if own.is_empty() { base } else { own });
+        Ok(Self {
+            entity_patterns: or_base(&settings.entity_patterns, &config.entity_patterns)?,
+            relationship_patterns: or_base(&settings.relationship_patterns, &config.relationship_patterns)?,
+            concept_patterns: or_base(&settings.concept_patterns, &config.concept_patterns)?,
+            settings: settings.clone(),
+        })
+    }
+
+    /// `name` without its leading articles and titles ("El Banco de España" -> "Banco de
+    /// España", "Frau Schmidt" -> "Schmidt"); empty when it is nothing else
+    fn strip_leading_words<'a>(&self, mut name: &'a str) -> &'a str {
+        let leading = self.settings.leading_words.iter().chain(&self.settings.person_titles);
+        while let Some(rest) = leading.clone().find_map(|word| {
+            name.strip_prefix(word.as_str()).filter(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+        }) {
+            name = rest.trim_start();
+        }
+        name
+    }
+
+    /// A person right after one of the language's titles, an organization when a word of
+    /// the name is one of its organization keywords
+    fn classify(&self, name: &str, preceding: &str) -> Option<EntityType> {
+        let titled = preceding
+            .split_whitespace()
+            .next_back()
+            .is_some_and(|word| self.settings.person_titles.iter().any(|title| title == word));
+        if titled {
+            return Some(EntityType::Person);
+        }
+        name.split_whitespace()
+            .any(|word| self.settings.organization_keywords.iter().any(|keyword| keyword.eq_ignore_ascii_case(word)))
+            .then_some(EntityType::Organization)
+    }
+}
+
+/// Confidence of a common-noun phrase found by the chunker; proper-noun phrases get the
+/// usual 0.7 of pattern extraction
+const NOUN_PHRASE_CONFIDENCE: f64 = 0.6;
+
+/// Confidence of a role, affiliation or organization read from an appositive
+/// ("Alice, CTO of TechCorp,")
+const APPOSITIVE_CONFIDENCE: f64 = 0.8;
+
+/// Titles accepted as an appositive without an organization ("Alice, CTO, said ...");
+/// anything else between commas is more likely the next item of a list
+const BARE_TITLES: [&str; 14] = [
+    "ceo", "cto", "cfo", "coo", "cio", "cmo", "ciso", "vp", "president", "chairman", "chair", "director", "founder",
+    "co-founder",
+];
+
+/// Longest connecting phrase used as an edge label; longer gaps get a generated label
+const MAX_VERB_PHRASE_WORDS: usize = 5;
+
+/// Relationship patterns used by deep analysis on top of the configured ones
+const ENHANCED_RELATIONSHIP_PATTERNS: [(&str, &str); 7] = [
+    (r"(\w+)\s+manages?\s+(\w+)", "manages"),
+    (r"(\w+)\s+depends?\s+on\s+(\w+)", "depends_on"),
+    (r"(\w+)\s+implements?\s+(\w+)", "implements"),
+    (r"(\w+)\s+inherits?\s+from\s+(\w+)", "inherits_from"),
+    (r"(\w+)\s+communicates?\s+with\s+(\w+)", "communicates_with"),
+    (r"(\w+)\s+provides?\s+(\w+)", "provides"),
+    (r"(\w+)\s+requires?\s+(\w+)", "requires"),
+];
+
 pub struct EntityExtractor {
     config: ExtractionConfig,
-    entity_patterns: Vec<Regex>,
-    relationship_patterns: Vec<Regex>,
-    concept_patterns: Vec<Regex>,
+    entity_patterns: PatternSet,
+    relationship_patterns: PatternSet,
+    concept_patterns: PatternSet,
+    /// `extraction.languages` by lowercase name
+    languages: HashMap<String, LanguageRules>,
+    enhanced_relationship_patterns: Vec<(Regex, RelationshipType)>,
+    passive_voice: Regex,
+    cache: Option<LlmCache>,
+    /// Leading stopwords are stripped from concept names ("The analytics module"), and a
+    /// name made only of stopwords is not kept in a stopword-free prompt text
+    stopwords: HashSet<String>,
+    /// Where a text too long for one prompt may be split
+    sentence_splitter: SentenceSplitter,
+    gazetteers: Vec<Gazetteer>,
+    relationship_rules: Vec<RelationshipRule>,
+    /// Drops generic pattern matches whose mentions are too far apart; `None` when
+    /// `relationship_validation` is off
+    relationship_validator: Option<RelationshipValidator>,
+    /// Loaded when `entity_detector` is `ner_model`
+    ner_model: Option<NerModel>,
+    /// Strategies added with [`add_extractor`](Self::add_extractor), run after the built-in ones
+    custom_extractors: Vec<Box<dyn Extractor>>,
+    llm_progress: Option<LlmProgressCallback>,
+    warning: Option<WarningCallback>,
 }
 
 impl EntityExtractor {
     pub fn new(config: ExtractionConfig) -> Result<Self> {
-        let entity_patterns = Self::compile_patterns(&config.entity_patterns)?;
-        let relationship_patterns = Self::compile_patterns(&config.relationship_patterns)?;
-        let concept_patterns = Self::compile_patterns(&config.concept_patterns)?;
+        let entity_patterns = PatternSet::new(&config.entity_patterns)?;
+        let relationship_patterns = PatternSet::new(&config.relationship_patterns)?;
+        let concept_patterns = PatternSet::new(&config.concept_patterns)?;
+        let languages: HashMap<String, LanguageRules> = config
+            .languages
+            .iter()
+            .map(|(name, settings)| Ok((name.to_lowercase(), LanguageRules::new(settings, &config)?)))
+            .collect::<Result<_>>()?;
+        if let Some(language) = &config.language {
+            if !language.eq_ignore_ascii_case("english") && !languages.contains_key(&language.to_lowercase()) {
+                return Err(GraphError::Configuration(format!(
+                    "extraction.language is '{}', but extraction.languages has no such entry",
+                    language
+                )));
+            }
+        }
+        let enhanced_relationship_patterns = ENHANCED_RELATIONSHIP_PATTERNS
+            .iter()
+            .map(|(pattern, label)| Ok((Regex::new(pattern)?, RelationshipType::Other(label.to_string()))))
+            .collect::<Result<_>>()?;
+        let gazetteers = config.gazetteer_files.iter().map(Gazetteer::load).collect::<Result<_>>()?;
+        let mut relationship_rules: Vec<RelationshipRule> =
+            config.relationship_rules.iter().map(|rule| rule.parse()).collect::<Result<_>>()?;
+        for category in &config.pattern_library {
+            relationship_rules.extend_from_slice(category.compiled_rules());
+        }
 
         Ok(Self {
+            cache: LlmCache::from_config(&config.llm_cache),
+            gazetteers,
+            relationship_rules,
+            relationship_validator: config
+                .relationship_validation
+                .enabled
+                .then(|| RelationshipValidator::new(&config.relationship_validation)),
+            ner_model: (config.entity_detector == EntityDetector::NerModel)
+                .then(|| NerModel::load(&config.ner_model))
+                .transpose()?,
+            custom_extractors: Vec::new(),
+            llm_progress: None,
+            warning: None,
             config,
             entity_patterns,
             relationship_patterns,
             concept_patterns,
+            languages,
+            enhanced_relationship_patterns,
+            // "is managed by", "was written by", "were quietly taken by"
+            passive_voice: Regex::new(r"(?i)\b(?:is|are|was|were|be|been|being)\s+(?:\w+ly\s+)?(?P<verb>\w+(?:ed|en|wn|ne|t))\s+by\b")?,
+            stopwords: TextProcessor::default_english_stopwords(),
+            sentence_splitter: SentenceSplitter::default(),
         })
     }
 
-    fn compile_patterns(patterns: &[String]) -> Result<Vec<Regex>> {
-        patterns
+    /// Use the same stopwords as the text processor when normalizing concept names
+    pub fn set_stopwords(&mut self, stopwords: HashSet<String>) {
+        self.stopwords = stopwords;
+    }
+
+    /// Use the same sentence splitting as the text processor when a text is split
+    /// across prompts
+    pub fn set_sentence_splitter(&mut self, splitter: SentenceSplitter) {
+        self.sentence_splitter = splitter;
+    }
+
+    /// Append a strategy to the extraction chain, after the ones `extractors` configures
+    pub fn add_extractor(&mut self, extractor: Box<dyn Extractor>) {
+        self.custom_extractors.push(extractor);
+    }
+
+    /// Call `callback` as streamed LLM responses arrive (`llm_stream`), e.g. to drive a
+    /// progress bar during long generations
+    pub fn on_llm_progress(&mut self, callback: impl Fn(&LlmProgress) + Send + Sync + 'static) {
+        self.llm_progress = Some(Arc::new(callback));
+    }
+
+    /// Call `callback` with each problem extraction recovers from, such as an LLM call
+    /// falling back to patterns; the same messages are also logged as warnings
+    pub fn on_warning(&mut self, callback: impl Fn(&str) + Send + Sync + 'static) {
+        self.warning = Some(Arc::new(callback));
+    }
+
+    fn warn(&self, message: String) {
+        warn!("⚠️  {}", message);
+        if let Some(callback) = &self.warning {
+            callback(&message);
+        }
+    }
+
+    pub fn config(&self) -> &ExtractionConfig {
+        &self.config
+    }
+
+    /// The `extraction.languages` entry for the forced or detected language of the text;
+    /// `None` keeps the base (English) patterns
+    fn language_rules(&self, processed_text: &ProcessedText) -> Option<&LanguageRules> {
+        let language = self.config.language.as_deref().unwrap_or(&processed_text.metadata.language);
+        self.languages.get(&language.to_lowercase())
+    }
+
+    /// Surface form and dedup key for a concept name: leading stopwords are stripped from
+    /// both, the key is lowercased (and stemmed when the text was). `None` if nothing is left.
+    fn normalize_concept_name(&self, name: &str, stemmed: bool) -> Option<(String, String)> {
+        let words: Vec<&str> = name.split_whitespace().collect();
+        let first_content = words
+            .iter()
+            .position(|word| !self.stopwords.contains(&word.to_lowercase()))?;
+        let surface = words[first_content..].join(" ");
+        let key = words[first_content..]
             .iter()
-            .map(|pattern| Regex::new(pattern).map_err(GraphError::from))
+            .map(|word| {
+                let lower = word.to_lowercase();
+                if stemmed { stem_word(&lower) } else { lower }
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        Some((surface, key))
+    }
+
+    /// Rename concepts to their normalized surface form and merge ones with the same key,
+    /// keeping the first occurrence and the highest confidence
+    fn normalize_concepts(&self, concepts: Vec<Concept>, stemmed: bool) -> Vec<Concept> {
+        let mut normalized: Vec<Concept> = Vec::new();
+        let mut index_by_key: HashMap<String, usize> = HashMap::new();
+
+        for mut concept in concepts {
+            let Some((surface, key)) = self.normalize_concept_name(&concept.name, stemmed) else {
+                continue;
+            };
+            if surface.len() < 3 {
+                continue;
+            }
+            match index_by_key.get(&key) {
+                Some(&index) => {
+                    let existing = &mut normalized[index];
+                    existing.confidence = existing.confidence.max(concept.confidence);
+                }
+                None => {
+                    concept.name = surface;
+                    index_by_key.insert(key, normalized.len());
+                    normalized.push(concept);
+                }
+            }
+        }
+        normalized
+    }
+
+    /// Split entities whose mentions mean different things into one entity per sense
+    /// (`extraction.disambiguation`). Context cues decide, and with `use_llm` the LLM
+    /// settles names the cues split; a failed call keeps the cues' answer.
+    async fn disambiguate_entities(&self, processed_text: &ProcessedText, entities: &mut Vec<Entity>) {
+        let config = &self.config.disambiguation;
+        if !config.enabled {
+            return;
+        }
+        let mut disambiguated = Vec::with_capacity(entities.len());
+        for entity in entities.drain(..) {
+            let mut senses = context_senses(processed_text, &entity.name, config);
+            let mut method = "context";
+            if senses.len() > 1 && config.use_llm && self.config.uses_llm() {
+                let mentions = mention_sentences(processed_text, &entity.name);
+                let sentences: Vec<&str> = mentions.iter().filter_map(|&index| processed_text.sentence(index)).collect();
+                match self.call_ollama(&adjudication_prompt(&entity.name, &sentences)).await {
+                    Ok(response) => match parse_adjudication(&response, &mentions) {
+                        Some(adjudicated) => {
+                            senses = adjudicated;
+                            method = "llm";
+                        }
+                        None => self.warn(format!("Could not parse the LLM's senses of '{}', keeping the context cues' answer", entity.name)),
+                    },
+                    Err(e) => self.warn(format!("LLM disambiguation of '{}' failed: {}, keeping the context cues' answer", entity.name, e)),
+                }
+            }
+            if senses.len() > 1 {
+                info!("🔀 Split '{}' into {} senses", entity.name, senses.len());
+                disambiguated.extend(split_entity(&entity, &senses, method, processed_text));
+            } else {
+                disambiguated.push(entity);
+            }
+        }
+        *entities = disambiguated;
+    }
+
+    /// The text prompts and the enhanced relationship patterns read. When stopwords are
+    /// stripped from it, those inside names the entity patterns or gazetteers match are kept
+    fn prompt_text<'a>(&self, processed_text: &'a ProcessedText) -> Cow<'a, str> {
+        if !processed_text.strips_prompt_stopwords() {
+            return processed_text.cleaned_text();
+        }
+        let text = processed_text.text();
+        let entity_patterns = self
+            .language_rules(processed_text)
+            .map_or(&self.entity_patterns, |rules| &rules.entity_patterns);
+        let mut protected: Vec<Range<usize>> = entity_patterns
+            .matching(text)
+            .flat_map(|pattern| pattern.find_iter(text).map(|found| found.range()))
+            .collect();
+        for gazetteer in &self.gazetteers {
+            protected.extend(gazetteer.find_iter(text).map(|found| found.start..found.end));
+        }
+        // A capitalized stopword alone ("The") is not a name worth keeping
+        protected.retain(|range| {
+            text[range.clone()].split(|c: char| !c.is_alphanumeric()).any(|word| !word.is_empty() && !self.stopwords.contains(&word.to_lowercase()))
+        });
+        processed_text.cleaned_text_protecting(&protected)
+    }
+
+    /// Restrict the result to `entity_whitelist`, if one is configured
+    fn apply_whitelist(&self, result: &mut ExtractionResult) {
+        if let Some(names) = &self.config.entity_whitelist {
+            let before = result.entities.len();
+            result.retain_entities(names);
+            info!("🎯 Whitelist kept {} of {} entities", result.entities.len(), before);
+        }
+    }
+
+    /// The first mention of each gazetteer name in the text, typed by its gazetteer
+    pub(crate) fn extract_entities_with_gazetteers(&self, processed_text: &ProcessedText) -> Vec<Entity> {
+        let language = self.language_rules(processed_text);
+        let mut entities: Vec<Entity> = Vec::new();
+        for gazetteer in &self.gazetteers {
+            for (sentence_idx, sentence) in processed_text.sentences().enumerate() {
+                for found in gazetteer.find_iter(sentence) {
+                    if entities.iter().any(|e| e.name.eq_ignore_ascii_case(found.name)) {
+                        continue;
+                    }
+                    entities.push(Entity {
+                        id: Uuid::new_v4().to_string(),
+                        name: found.name.to_string(),
+                        entity_type: gazetteer
+                            .entity_type()
+                            .cloned()
+                            .unwrap_or_else(|| self.classify_entity_type(found.name, language, &sentence[..found.start])),
+                        attributes: self.extract_entity_attributes(found.name, sentence),
+                        confidence: GAZETTEER_CONFIDENCE,
+                        position: Some(TextPosition {
+                            start: found.start,
+                            end: found.end,
+                            sentence_index: sentence_idx,
+                        }),
+                        mentions: 1,
+                        disambiguation: None,
+                    });
+                }
+            }
+        }
+        entities
+    }
+
+    /// The built-in strategies `extractors` enables, in order
+    fn builtin_extractors(&self) -> Vec<Box<dyn Extractor + '_>> {
+        self.config
+            .extractor_chain()
+            .into_iter()
+            .map(|kind| -> Box<dyn Extractor + '_> {
+                match kind {
+                    ExtractorKind::Patterns => Box::new(PatternExtractor(self)),
+                    ExtractorKind::Llm => Box::new(LlmExtractor(self)),
+                    ExtractorKind::Gazetteer => Box::new(GazetteerExtractor(self)),
+                }
+            })
             .collect()
     }
 
+    /// Run the extraction chain: entities from every strategy, merged, then each
+    /// strategy's relationships among them, then concepts
     pub async fn extract_from_text(&self, processed_text: &ProcessedText) -> Result<ExtractionResult> {
-        let start_time = std::time::Instant::now();
+        let start_time = web_time::Instant::now();
+        let builtin = self.builtin_extractors();
+        let chain: Vec<&dyn Extractor> = builtin
+            .iter()
+            .map(|extractor| extractor.as_ref())
+            .chain(self.custom_extractors.iter().map(|extractor| extractor.as_ref()))
+            .collect();
 
-        let entities = if self.config.use_llm {
-            self.extract_entities_with_llm(processed_text).await?
-        } else {
-            self.extract_entities_with_patterns(processed_text)?
-        };
+        let mut entities = Vec::new();
+        for extractor in &chain {
+            merge_entities(&mut entities, extractor.entities(processed_text).await?);
+        }
+        self.disambiguate_entities(processed_text, &mut entities).await;
 
-        let relationships = if self.config.use_llm {
-            self.extract_relationships_with_llm(processed_text, &entities).await?
-        } else {
-            self.extract_relationships_with_patterns(processed_text, &entities)?
-        };
+        let mut relationships = Vec::new();
+        for extractor in &chain {
+            merge_relationships(&mut relationships, extractor.relationships(processed_text, &entities).await?);
+        }
+        assign_sense_relationships(&entities, &mut relationships);
 
-        let concepts = if self.config.use_llm {
-            self.extract_concepts_with_llm(processed_text).await?
-        } else {
-            self.extract_concepts_with_patterns(processed_text)?
-        };
+        let mut concepts = Vec::new();
+        for extractor in &chain {
+            merge_concepts(&mut concepts, extractor.concepts(processed_text).await?);
+        }
 
         let processing_time = start_time.elapsed().as_millis() as u64;
+        let names: Vec<String> = chain.iter().map(|extractor| extractor.name()).collect();
 
         let metadata = ExtractionMetadata {
             total_entities: entities.len(),
@@ -185,29 +824,48 @@ impl EntityExtractor {
             total_concepts: concepts.len(),
             processing_time_ms: processing_time,
             confidence_threshold: 0.5,
-            extraction_method: if self.config.use_llm {
-                format!("LLM-{}", self.config.llm_model)
-            } else {
-                "Pattern-based".to_string()
-            },
+            extraction_method: if names.is_empty() { "None".to_string() } else { names.join(" + ") },
         };
 
-        Ok(ExtractionResult {
+        let mut result = ExtractionResult {
             entities,
             relationships,
             concepts,
             metadata,
-        })
+        };
+        self.apply_whitelist(&mut result);
+        Ok(result)
     }
 
-    fn extract_entities_with_patterns(&self, processed_text: &ProcessedText) -> Result<Vec<Entity>> {
+    pub(crate) fn extract_entities_with_patterns(&self, processed_text: &ProcessedText) -> Result<Vec<Entity>> {
+        let mut entities = match self.config.entity_detector {
+            EntityDetector::Patterns => self.extract_entities_with_regexes(processed_text),
+            EntityDetector::NounPhrases => self.extract_entities_with_chunker(processed_text),
+            EntityDetector::NerModel => self.extract_entities_with_ner_model(processed_text)?,
+        };
+        self.add_affiliation_entities(processed_text, &mut entities);
+        Ok(entities)
+    }
+
+    /// Matches of `entity_patterns` (or the text language's), each text once; a named
+    /// group `entity` narrows a match to the name
+    fn extract_entities_with_regexes(&self, processed_text: &ProcessedText) -> Vec<Entity> {
         let mut entities = Vec::new();
         let mut seen_entities = HashSet::new();
-
-        for (sentence_idx, sentence) in processed_text.sentences.iter().enumerate() {
-            for pattern in &self.entity_patterns {
-                for mat in pattern.find_iter(sentence) {
-                    let entity_text = mat.as_str().trim();
+        let language = self.language_rules(processed_text);
+        let entity_patterns = language.map_or(&self.entity_patterns, |rules| &rules.entity_patterns);
+
+        for (sentence_idx, sentence) in processed_text.sentences().enumerate() {
+            for pattern in entity_patterns.matching(sentence) {
+                for captures in pattern.captures_iter(sentence) {
+                    let Some(mat) = captures.name("entity").or_else(|| captures.get(0)) else { continue };
+                    let matched = mat.as_str();
+                    let entity_text = language.map_or(matched.trim(), |rules| rules.strip_leading_words(matched.trim()));
+                    // "Der Vertrag" loses its article and is no longer a name
+                    if entity_text != matched.trim() && !pattern.is_match(entity_text) {
+                        continue;
+                    }
+                    let start = mat.start() + matched.find(entity_text).unwrap_or(0);
                     
                     if entity_text.len() < 2 || seen_entities.contains(entity_text) {
                         continue;
@@ -215,7 +873,7 @@ impl EntityExtractor {
                     
                     seen_entities.insert(entity_text.to_string());
                     
-                    let entity_type = self.classify_entity_type(entity_text);
+                    let entity_type = self.classify_entity_type(entity_text, language, &sentence[..start]);
                     let attributes = self.extract_entity_attributes(entity_text, sentence);
                     
                     let entity = Entity {
@@ -225,10 +883,12 @@ impl EntityExtractor {
                         attributes,
                         confidence: 0.7, // Default confidence for pattern-based extraction
                         position: Some(TextPosition {
-                            start: mat.start(),
-                            end: mat.end(),
+                            start,
+                            end: start + entity_text.len(),
                             sentence_index: sentence_idx,
                         }),
+                        mentions: 1,
+                        disambiguation: None,
                     };
                     
                     entities.push(entity);
@@ -236,17 +896,131 @@ impl EntityExtractor {
             }
         }
 
+        entities
+    }
+
+    /// Organizations named only in an appositive ("Alice, CTO of TechCorp,") become entities
+    /// too, since the default patterns miss names such as "TechCorp"
+    fn add_affiliation_entities(&self, processed_text: &ProcessedText, entities: &mut Vec<Entity>) {
+        let sentences: Vec<&str> = processed_text.sentences().collect();
+        let mut index = 0;
+        while index < entities.len() {
+            let sentence_idx = entities[index].position.as_ref().map(|pos| pos.sentence_index);
+            let affiliations: Vec<String> = entities[index]
+                .attributes
+                .iter()
+                .filter(|attr| matches!(attr.attribute_type, AttributeType::Affiliation))
+                .map(|attr| attr.value.clone())
+                .collect();
+            index += 1;
+
+            let Some((sentence_idx, sentence)) = sentence_idx.and_then(|i| sentences.get(i).map(|s| (i, *s))) else {
+                continue;
+            };
+            for name in affiliations {
+                if entities.iter().any(|e| e.name.eq_ignore_ascii_case(&name)) {
+                    continue;
+                }
+                let Some(start) = sentence.find(&name) else { continue };
+                entities.push(Entity {
+                    id: Uuid::new_v4().to_string(),
+                    entity_type: EntityType::Organization,
+                    attributes: self.extract_entity_attributes(&name, sentence),
+                    confidence: APPOSITIVE_CONFIDENCE,
+                    position: Some(TextPosition {
+                        start,
+                        end: start + name.len(),
+                        sentence_index: sentence_idx,
+                    }),
+                    name,
+                    mentions: 1,
+                    disambiguation: None,
+                });
+            }
+        }
+    }
+
+    /// Spans tagged by the NER model as entities, typed and scored by the model, each
+    /// name once ignoring case
+    fn extract_entities_with_ner_model(&self, processed_text: &ProcessedText) -> Result<Vec<Entity>> {
+        let Some(model) = &self.ner_model else {
+            return Ok(Vec::new());
+        };
+        let mut entities = Vec::new();
+        let mut seen_entities = HashSet::new();
+
+        for (sentence_idx, sentence) in processed_text.sentences().enumerate() {
+            for span in model.spans(sentence)? {
+                let name = sentence[span.start..span.end].trim();
+                if name.len() < 2 || !seen_entities.insert(name.to_lowercase()) {
+                    continue;
+                }
+
+                entities.push(Entity {
+                    id: Uuid::new_v4().to_string(),
+                    name: name.to_string(),
+                    entity_type: span.entity_type,
+                    attributes: self.extract_entity_attributes(name, sentence),
+                    confidence: span.confidence,
+                    position: Some(TextPosition {
+                        start: span.start,
+                        end: span.start + name.len(),
+                        sentence_index: sentence_idx,
+                    }),
+                    mentions: 1,
+                    disambiguation: None,
+                });
+            }
+        }
+
         Ok(entities)
     }
 
-    fn extract_relationships_with_patterns(
+    /// Noun phrases as entities, skipping phrases made only of stopwords and repeats
+    /// that differ only in case
+    fn extract_entities_with_chunker(&self, processed_text: &ProcessedText) -> Vec<Entity> {
+        let mut entities = Vec::new();
+        let mut seen_entities = HashSet::new();
+        let language = self.language_rules(processed_text);
+
+        for (sentence_idx, sentence) in processed_text.sentences().enumerate() {
+            for phrase in noun_phrases(sentence) {
+                let only_stopwords = phrase.text.split_whitespace().all(|word| self.stopwords.contains(&word.to_lowercase()));
+                if phrase.text.len() < 3 || only_stopwords || !seen_entities.insert(phrase.text.to_lowercase()) {
+                    continue;
+                }
+
+                entities.push(Entity {
+                    id: Uuid::new_v4().to_string(),
+                    name: phrase.text.to_string(),
+                    entity_type: self.classify_entity_type(phrase.text, language, &sentence[..phrase.start]),
+                    attributes: self.extract_entity_attributes(phrase.text, sentence),
+                    confidence: if phrase.proper { 0.7 } else { NOUN_PHRASE_CONFIDENCE },
+                    position: Some(TextPosition {
+                        start: phrase.start,
+                        end: phrase.end,
+                        sentence_index: sentence_idx,
+                    }),
+                    mentions: 1,
+                    disambiguation: None,
+                });
+            }
+        }
+
+        entities
+    }
+
+    pub(crate) fn extract_relationships_with_patterns(
         &self,
         processed_text: &ProcessedText,
         entities: &[Entity],
     ) -> Result<Vec<Relationship>> {
         let mut relationships = Vec::new();
+        let relationship_patterns = self
+            .language_rules(processed_text)
+            .map_or(&self.relationship_patterns, |rules| &rules.relationship_patterns);
         
-        for (sentence_idx, sentence) in processed_text.sentences.iter().enumerate() {
+        for (sentence_idx, sentence) in processed_text.sentences().enumerate() {
             // Find entities in this sentence
             let sentence_entities: Vec<&Entity> = entities
                 .iter()
@@ -259,17 +1033,54 @@ impl EntityExtractor {
                 })
                 .collect();
 
+            // Rules bind typed participants; pairs they cover skip the generic patterns
+            let mut ruled_pairs = HashSet::new();
+            let rules = if sentence_entities.len() < 2 { &[][..] } else { &self.relationship_rules[..] };
+            for rule in rules {
+                for found in rule.find(sentence, &sentence_entities) {
+                    let mut pair = [found.subject.id.as_str(), found.object.id.as_str()];
+                    pair.sort();
+                    if !ruled_pairs.insert(pair) {
+                        continue;
+                    }
+                    let label = rule.label();
+                    relationships.push(Relationship {
+                        id: Uuid::new_v4().to_string(),
+                        source_entity_id: found.subject.id.clone(),
+                        target_entity_id: found.object.id.clone(),
+                        relationship_type: rule.relationship_type(),
+                        bidirectional: is_symmetric_relation(&label),
+                        label,
+                        confidence: RULE_CONFIDENCE,
+                        position: Some(TextPosition {
+                            start: found.start,
+                            end: found.end,
+                            sentence_index: sentence_idx,
+                        }),
+                        category: None,
+                        context: None,
+                    });
+                }
+            }
+
             // Look for relationship patterns between entities
             for i in 0..sentence_entities.len() {
                 for j in i + 1..sentence_entities.len() {
                     let entity1 = sentence_entities[i];
                     let entity2 = sentence_entities[j];
+                    let mut pair = [entity1.id.as_str(), entity2.id.as_str()];
+                    pair.sort();
+                    if ruled_pairs.contains(&pair) {
+                        continue;
+                    }
                     
                     if let Some(relationship) = self.find_relationship_between_entities(
                         entity1,
                         entity2,
                         sentence,
                         sentence_idx,
+                        processed_text.metadata.stemmed,
+                        relationship_patterns,
                     )? {
                         relationships.push(relationship);
                     }
@@ -277,25 +1088,65 @@ impl EntityExtractor {
             }
         }
 
+        self.add_affiliation_relationships(entities, &mut relationships);
         Ok(relationships)
     }
 
-    fn extract_concepts_with_patterns(&self, processed_text: &ProcessedText) -> Result<Vec<Concept>> {
+    /// An `affiliated_with` edge from each entity to the organization its appositive names,
+    /// unless the two are already related ("Alice, CTO of TechCorp," also matches the
+    /// pattern library's more specific `works_at`)
+    fn add_affiliation_relationships(&self, entities: &[Entity], relationships: &mut Vec<Relationship>) {
+        for entity in entities {
+            let affiliations = entity
+                .attributes
+                .iter()
+                .filter(|attr| matches!(attr.attribute_type, AttributeType::Affiliation));
+            for affiliation in affiliations {
+                let Some(organization) = entities
+                    .iter()
+                    .find(|e| e.id != entity.id && e.name.eq_ignore_ascii_case(&affiliation.value))
+                else {
+                    continue;
+                };
+                let related = relationships.iter().any(|r| {
+                    (r.source_entity_id == entity.id && r.target_entity_id == organization.id)
+                        || (r.source_entity_id == organization.id && r.target_entity_id == entity.id)
+                });
+                if related {
+                    continue;
+                }
+                relationships.push(Relationship {
+                    id: Uuid::new_v4().to_string(),
+                    source_entity_id: entity.id.clone(),
+                    target_entity_id: organization.id.clone(),
+                    relationship_type: RelationshipType::Other("affiliated_with".to_string()),
+                    label: "affiliated with".to_string(),
+                    confidence: APPOSITIVE_CONFIDENCE,
+                    position: entity.position.clone(),
+                    bidirectional: false,
+                    category: None,
+                    context: None,
+                });
+            }
+        }
+    }
+
+    pub(crate) fn extract_concepts_with_patterns(&self, processed_text: &ProcessedText) -> Result<Vec<Concept>> {
         let mut concepts = Vec::new();
-        let mut seen_concepts = HashSet::new();
+        let keywords = &self.config.keywords;
+        let sentences: Vec<&str> = if keywords.enabled && keywords.replace_patterns {
+            Vec::new()
+        } else {
+            processed_text.sentences().collect()
+        };
 
-        for (sentence_idx, sentence) in processed_text.sentences.iter().enumerate() {
-            for pattern in &self.concept_patterns {
+        let concept_patterns =
+            self.language_rules(processed_text).map_or(&self.concept_patterns, |rules| &rules.concept_patterns);
+        for (sentence_idx, sentence) in sentences.iter().enumerate() {
+            for pattern in concept_patterns.matching(sentence) {
                 for mat in pattern.find_iter(sentence) {
                     let concept_text = mat.as_str().trim();
-                    
-                    if concept_text.len() < 3 || seen_concepts.contains(concept_text) {
-                        continue;
-                    }
-                    
-                    seen_concepts.insert(concept_text.to_string());
-                    
-                    let concept = Concept {
+                    concepts.push(Concept {
                         id: Uuid::new_v4().to_string(),
                         name: concept_text.to_string(),
                         description: self.generate_concept_description(concept_text, sentence),
@@ -306,165 +1157,374 @@ impl EntityExtractor {
                             end: mat.end(),
                             sentence_index: sentence_idx,
                         }),
-                    };
-                    
-                    concepts.push(concept);
+                    });
                 }
             }
         }
 
-        Ok(concepts)
+        if keywords.enabled {
+            let all_sentences: Vec<&str> = processed_text.sentences().collect();
+            let ranked = extract_keywords(&all_sentences, &self.stopwords, keywords);
+            let top_score = ranked.first().map(|keyword| keyword.score).unwrap_or(1.0);
+            for keyword in ranked {
+                let sentence = all_sentences[keyword.sentence_index];
+                concepts.push(Concept {
+                    id: Uuid::new_v4().to_string(),
+                    description: format!(
+                        "{} (keyword, {:?} score {:.3})",
+                        self.generate_concept_description(&keyword.phrase, sentence),
+                        keywords.method,
+                        keyword.score
+                    ),
+                    name: keyword.phrase,
+                    related_entities: Vec::new(),
+                    // Best keyword gets 0.9, the rest scale down with their score
+                    confidence: (0.9 * keyword.score / top_score).clamp(0.3, 0.9),
+                    position: Some(TextPosition {
+                        start: keyword.start,
+                        end: keyword.end,
+                        sentence_index: keyword.sentence_index,
+                    }),
+                });
+            }
+        }
+
+        // Case, leading articles and (with stemming) inflection don't make a new concept
+        Ok(self.normalize_concepts(concepts, processed_text.metadata.stemmed))
+    }
+
+    /// Entity prompt for `text`; `known` lists names found in earlier segments of a text
+    /// too long for one prompt, so the model reuses them for the same entities
+    fn entity_prompt(&self, text: &str, known: &[&str]) -> String {
+        let carryover = if known.is_empty() {
+            String::new()
+        } else {
+            format!("\nEntities already found in earlier parts of the text (reuse these names): {:?}\n", known)
+        };
+        format!(
+            r#"Analyze the following text and extract entities (people, places, organizations, concepts, systems, processes).
+{}
+Text: "{}"
+
+Please respond with a JSON object in this exact format:
+{{
+  "entities": [
+    {{
+      "name": "entity_name",
+      "type": "{}",
+      "confidence": 0.8
+    }}
+  ]
+}}
+
+Only return the JSON object, no other text."#,
+            carryover,
+            text,
+            self.config.llm_entity_types().join("|")
+        )
+    }
+
+    fn relationship_prompt(&self, entity_names: &[&str], text: &str) -> String {
+        format!(
+            r#"Analyze the following text and identify relationships between these entities: {:?}
+
+Text: "{}"
+
+Please respond with a JSON object in this exact format:
+{{
+  "relationships": [
+    {{
+      "from": "entity1_name",
+      "to": "entity2_name",
+      "relationship": "relationship_type",
+      "confidence": 0.8
+    }}
+  ]
+}}
+
+Only return the JSON object, no other text."#,
+            entity_names,
+            text
+        )
+    }
+
+    fn deep_relationship_prompt(&self, entity_names: &[&str], text: &str) -> String {
+        format!(
+            r#"Analyze the following text for sophisticated relationships between these entities: {:?}
+
+Text: "{}"
+
+Please identify:
+1. Implicit relationships (not directly stated but implied)
+2. Temporal relationships (sequence, causation)
+3. Hierarchical relationships (parent-child, part-whole)
+4. Functional relationships (roles, responsibilities)
+5. Dependency relationships (requires, depends on)
+
+Please respond with a JSON object in this exact format:
+{{
+  "relationships": [
+    {{
+      "from": "entity1",
+      "to": "entity2",
+      "relationship": "relationship_type",
+      "category": "{}",
+      "confidence": 0.8,
+      "context": "supporting text quoted from the passage"
+    }}
+  ]
+}}
+
+Only return the JSON object, no other text."#,
+            entity_names,
+            text,
+            DEEP_RELATIONSHIP_CATEGORIES.join("|")
+        )
+    }
+
+    fn concept_prompt(&self, text: &str) -> String {
+        format!(
+            r#"Analyze the following text and extract key concepts, ideas, systems, processes, and methods.
+
+Text: "{}"
+
+Please respond with a JSON object in this exact format:
+{{
+  "concepts": [
+    {{
+      "name": "concept_name",
+      "description": "brief description of the concept",
+      "confidence": 0.8
+    }}
+  ]
+}}
+
+Only return the JSON object, no other text."#,
+            text
+        )
+    }
+
+    /// How `text` is split across prompts built from `template` (the prompt with no text)
+    /// to fit the model's context window
+    fn prompt_plan<'a>(&self, text: &'a str, template: &str) -> PromptPlan<'a> {
+        let plan = self.config.prompt_budget.plan(&self.config.llm_model, text, estimate_tokens(template), &self.sentence_splitter);
+        if plan.is_split() {
+            debug!(
+                "✂️  Text is too long for one {} prompt, sending it in {} segments",
+                self.config.llm_model,
+                plan.segments.len()
+            );
+        }
+        plan
+    }
+
+    /// Relationship prompts for `text`. A split text gets one prompt per segment listing
+    /// the entities that segment mentions, and none for segments mentioning fewer than two.
+    fn relationship_prompts(&self, entities: &[Entity], text: &str) -> Vec<String> {
+        self.prompts_per_segment(entities, text, |names, text| self.relationship_prompt(names, text))
+    }
+
+    fn deep_relationship_prompts(&self, entities: &[Entity], text: &str) -> Vec<String> {
+        self.prompts_per_segment(entities, text, |names, text| self.deep_relationship_prompt(names, text))
+    }
+
+    /// One `prompt` over the whole text, or one per segment naming the entities it
+    /// mentions when the text is too long; segments mentioning fewer than two are skipped
+    fn prompts_per_segment(&self, entities: &[Entity], text: &str, prompt: impl Fn(&[&str], &str) -> String) -> Vec<String> {
+        let names: Vec<&str> = entities.iter().map(|e| e.name.as_str()).collect();
+        let plan = self.prompt_plan(text, &prompt(&[], ""));
+        if !plan.is_split() {
+            return vec![prompt(&names, text)];
+        }
+        plan.segments
+            .iter()
+            .filter_map(|segment| {
+                let lower = segment.to_lowercase();
+                let mentioned: Vec<&str> =
+                    names.iter().copied().filter(|name| lower.contains(&name.to_lowercase())).collect();
+                let mentioned = names_within_budget(&mentioned, plan.carryover_tokens);
+                (mentioned.len() >= 2).then(|| prompt(&mentioned, segment))
+            })
+            .collect()
+    }
+
+    fn concept_prompts(&self, text: &str) -> Vec<String> {
+        let plan = self.prompt_plan(text, &self.concept_prompt(""));
+        plan.segments.iter().map(|segment| self.concept_prompt(segment)).collect()
     }
 
-    async fn extract_entities_with_llm(&self, processed_text: &ProcessedText) -> Result<Vec<Entity>> {
-        if !self.config.use_llm {
+    /// The LLM calls [`extract_from_text`](Self::extract_from_text) would make, without
+    /// making them. The relationship prompt lists the pattern-matched entities where the
+    /// real run would list the LLM's, as do the carried-over names of a split entity
+    /// prompt, so their size is an estimate.
+    pub fn planned_llm_calls(&self, processed_text: &ProcessedText) -> Result<Vec<PlannedLlmCall>> {
+        if !self.config.uses_llm() {
             return Ok(Vec::new());
         }
+        let mut entities = self.extract_entities_with_patterns(processed_text)?;
+        merge_entities(&mut entities, self.extract_entities_with_gazetteers(processed_text));
+        let relationships = self.extract_relationships_with_patterns(processed_text, &entities)?;
+        let concepts = self.extract_concepts_with_patterns(processed_text)?;
+        let text = &self.prompt_text(processed_text);
+
+        let plan = self.prompt_plan(text, &self.entity_prompt("", &[]));
+        let segments = plan.segments.len();
+        let mut calls = Vec::new();
+        for (index, segment) in plan.segments.iter().enumerate() {
+            let earlier = plan.segments[..index].join(" ").to_lowercase();
+            let names: Vec<&str> = entities
+                .iter()
+                .map(|e| e.name.as_str())
+                .filter(|name| earlier.contains(&name.to_lowercase()))
+                .collect();
+            let prompt = self.entity_prompt(segment, &names_within_budget(&names, plan.carryover_tokens));
+            calls.push(("entities", LlmItemKind::Entities, prompt, entities.len().div_ceil(segments)));
+        }
+        // No relationship call is made when the LLM finds no entities
+        if !entities.is_empty() {
+            let prompts = self.relationship_prompts(&entities, text);
+            let expected = relationships.len().div_ceil(prompts.len().max(1));
+            calls.extend(prompts.into_iter().map(|prompt| ("relationships", LlmItemKind::Relationships, prompt, expected)));
+        }
+        let prompts = self.concept_prompts(text);
+        let expected = concepts.len().div_ceil(prompts.len());
+        calls.extend(prompts.into_iter().map(|prompt| ("concepts", LlmItemKind::Concepts, prompt, expected)));
+
+        Ok(calls
+            .into_iter()
+            .map(|(stage, kind, prompt, expected_items)| PlannedLlmCall {
+                stage,
+                cached: self.cache.as_ref().is_some_and(|cache| {
+                    let request = self.ollama_request(&prompt, self.ollama_format(kind));
+                    cache.get(&self.cache_request(&request)).is_some()
+                }),
+                prompt,
+                expected_items,
+            })
+            .collect())
+    }
 
-        println!("🤖 Extracting entities using LLM: {}", self.config.llm_model);
-        
-        let prompt = format!(
-            r#"Analyze the following text and extract entities (people, places, organizations, concepts, systems, processes).
+    pub(crate) async fn extract_entities_with_llm(&self, processed_text: &ProcessedText) -> Result<Vec<Entity>> {
+        if !self.config.uses_llm() {
+            return Ok(Vec::new());
+        }
 
-Text: "{}"
+        info!("🤖 Extracting entities using LLM: {}", self.config.llm_model);
 
-Please respond with a JSON array of entities in this exact format:
-[
-  {{
-    "name": "entity_name",
-    "type": "Person|Place|Organization|System|Process|Concept|Other",
-    "confidence": 0.8
-  }}
-]
-
-Only return the JSON array, no other text."#,
-            processed_text.cleaned_text
-        );
+        let text = self.prompt_text(processed_text);
+        let plan = self.prompt_plan(&text, &self.entity_prompt("", &[]));
+        let mut chunks: Vec<Vec<Entity>> = Vec::new();
+        for segment in &plan.segments {
+            let mut names: Vec<&str> = Vec::new();
+            for entity in chunks.iter().flatten() {
+                if !names.iter().any(|name| name.eq_ignore_ascii_case(&entity.name)) {
+                    names.push(&entity.name);
+                }
+            }
+            let prompt = self.entity_prompt(segment, &names_within_budget(&names, plan.carryover_tokens));
 
-        match self.call_ollama(&prompt).await {
-            Ok(response) => {
-                match self.parse_entities_from_llm_response(&response) {
-                    Ok(entities) => {
-                        println!("✅ LLM extracted {} entities", entities.len());
-                        Ok(entities)
-                    }
+            match self.call_ollama_for(&prompt, LlmItemKind::Entities).await {
+                Ok(response) => match self.parse_entities_from_llm_response(&response) {
+                    Ok(found) => chunks.push(found),
                     Err(e) => {
-                        println!("⚠️  LLM response parsing failed: {}, falling back to patterns", e);
-                        self.extract_entities_with_patterns(processed_text)
+                        self.warn(format!("LLM response parsing failed: {}, falling back to patterns", e));
+                        return self.extract_entities_with_patterns(processed_text);
                     }
+                },
+                Err(e) => {
+                    self.warn(format!("LLM call failed: {}, falling back to patterns", e));
+                    return self.extract_entities_with_patterns(processed_text);
                 }
             }
-            Err(e) => {
-                println!("⚠️  LLM call failed: {}, falling back to patterns", e);
-                self.extract_entities_with_patterns(processed_text)
-            }
         }
+        // Later segments name entities the same way, so reducing by name joins them
+        let entities = reduce_chunk_entities(chunks, self.config.chunk_merge);
+        info!("✅ LLM extracted {} entities", entities.len());
+        Ok(entities)
     }
 
-    async fn extract_relationships_with_llm(
+    pub(crate) async fn extract_relationships_with_llm(
         &self,
         processed_text: &ProcessedText,
         entities: &[Entity],
     ) -> Result<Vec<Relationship>> {
-        if !self.config.use_llm || entities.is_empty() {
+        if !self.config.uses_llm() || entities.is_empty() {
             return self.extract_relationships_with_patterns(processed_text, entities);
         }
 
-        println!("🤖 Extracting relationships using LLM: {}", self.config.llm_model);
-        
-        let entity_names: Vec<&str> = entities.iter().map(|e| e.name.as_str()).collect();
-        let prompt = format!(
-            r#"Analyze the following text and identify relationships between these entities: {:?}
-
-Text: "{}"
-
-Please respond with a JSON array of relationships in this exact format:
-[
-  {{
-    "from": "entity1_name",
-    "to": "entity2_name", 
-    "relationship": "relationship_type",
-    "confidence": 0.8
-  }}
-]
-
-Only return the JSON array, no other text."#,
-            entity_names,
-            processed_text.cleaned_text
-        );
+        info!("🤖 Extracting relationships using LLM: {}", self.config.llm_model);
 
-        match self.call_ollama(&prompt).await {
-            Ok(response) => {
-                match self.parse_relationships_from_llm_response(&response, entities) {
-                    Ok(relationships) => {
-                        println!("✅ LLM extracted {} relationships", relationships.len());
-                        Ok(relationships)
-                    }
+        let mut relationships = Vec::new();
+        for prompt in self.relationship_prompts(entities, &self.prompt_text(processed_text)) {
+            match self.call_ollama_for(&prompt, LlmItemKind::Relationships).await {
+                Ok(response) => match self.parse_relationships_from_llm_response(&response, entities) {
+                    Ok(found) => merge_relationships(&mut relationships, found),
                     Err(e) => {
-                        println!("⚠️  LLM response parsing failed: {}, falling back to patterns", e);
-                        self.extract_relationships_with_patterns(processed_text, entities)
+                        self.warn(format!("LLM response parsing failed: {}, falling back to patterns", e));
+                        return self.extract_relationships_with_patterns(processed_text, entities);
                     }
+                },
+                Err(e) => {
+                    self.warn(format!("LLM call failed: {}, falling back to patterns", e));
+                    return self.extract_relationships_with_patterns(processed_text, entities);
                 }
             }
-            Err(e) => {
-                println!("⚠️  LLM call failed: {}, falling back to patterns", e);
-                self.extract_relationships_with_patterns(processed_text, entities)
-            }
         }
+        info!("✅ LLM extracted {} relationships", relationships.len());
+        Ok(relationships)
     }
 
-    async fn extract_concepts_with_llm(&self, processed_text: &ProcessedText) -> Result<Vec<Concept>> {
-        if !self.config.use_llm {
+    pub(crate) async fn extract_concepts_with_llm(&self, processed_text: &ProcessedText) -> Result<Vec<Concept>> {
+        if !self.config.uses_llm() {
             return self.extract_concepts_with_patterns(processed_text);
         }
 
-        println!("🤖 Extracting concepts using LLM: {}", self.config.llm_model);
-        
-        let prompt = format!(
-            r#"Analyze the following text and extract key concepts, ideas, systems, processes, and methods.
-
-Text: "{}"
-
-Please respond with a JSON array of concepts in this exact format:
-[
-  {{
-    "name": "concept_name",
-    "description": "brief description of the concept",
-    "confidence": 0.8
-  }}
-]
-
-Only return the JSON array, no other text."#,
-            processed_text.cleaned_text
-        );
+        info!("🤖 Extracting concepts using LLM: {}", self.config.llm_model);
 
-        match self.call_ollama(&prompt).await {
-            Ok(response) => {
-                match self.parse_concepts_from_llm_response(&response) {
-                    Ok(concepts) => {
-                        println!("✅ LLM extracted {} concepts", concepts.len());
-                        Ok(concepts)
-                    }
+        let mut concepts = Vec::new();
+        for prompt in self.concept_prompts(&self.prompt_text(processed_text)) {
+            match self.call_ollama_for(&prompt, LlmItemKind::Concepts).await {
+                Ok(response) => match self.parse_concepts_from_llm_response(&response) {
+                    Ok(found) => concepts.extend(found),
                     Err(e) => {
-                        println!("⚠️  LLM response parsing failed: {}, falling back to patterns", e);
-                        self.extract_concepts_with_patterns(processed_text)
+                        self.warn(format!("LLM response parsing failed: {}, falling back to patterns", e));
+                        return self.extract_concepts_with_patterns(processed_text);
                     }
+                },
+                Err(e) => {
+                    self.warn(format!("LLM call failed: {}, falling back to patterns", e));
+                    return self.extract_concepts_with_patterns(processed_text);
                 }
             }
-            Err(e) => {
-                println!("⚠️  LLM call failed: {}, falling back to patterns", e);
-                self.extract_concepts_with_patterns(processed_text)
-            }
         }
+        // Normalizing also joins concepts that several segments found
+        let concepts = self.normalize_concepts(concepts, processed_text.metadata.stemmed);
+        info!("✅ LLM extracted {} concepts", concepts.len());
+        Ok(concepts)
     }
 
-    fn classify_entity_type(&self, entity_text: &str) -> EntityType {
+    /// Type of `entity_text`, found right after `preceding` in its sentence
+    fn classify_entity_type(&self, entity_text: &str, language: Option<&LanguageRules>, preceding: &str) -> EntityType {
         let lower_text = entity_text.to_lowercase();
-        
+        let custom = self.config.entity_types.iter().find(|definition| {
+            definition.keywords.iter().any(|keyword| !keyword.is_empty() && lower_text.contains(&keyword.to_lowercase()))
+        });
+        if let Some(definition) = custom {
+            return EntityType::Other(definition.name.clone());
+        }
+        if let Some(entity_type) = language.and_then(|rules| rules.classify(entity_text, preceding)) {
+            return entity_type;
+        }
+        // German capitalizes every noun, so capitals say nothing about persons there
+        let capitalized_names = language.is_none_or(|rules| rules.settings.capitalized_names);
+
         // Simple heuristics for entity classification
         if lower_text.contains("corp") || lower_text.contains("inc") || lower_text.contains("company") {
             EntityType::Organization
-        } else if entity_text.chars().next().unwrap_or(' ').is_uppercase() && entity_text.split_whitespace().count() <= 3 {
+        } else if capitalized_names
+            && entity_text.chars().next().unwrap_or(' ').is_uppercase()
+            && entity_text.split_whitespace().count() <= 3
+        {
             // Likely a proper noun (person or place)
             EntityType::Person
         } else if lower_text.contains("system") || lower_text.contains("process") || lower_text.contains("method") {
@@ -486,8 +1546,25 @@ Only return the JSON array, no other text."#,
             confidence: 1.0,
         });
 
-        // Look for descriptive attributes in context
-        if let Some(description) = self.extract_description_from_context(entity_text, context) {
+        // An appositive names a role and affiliation; otherwise look for a description
+        if let Some((role, affiliation)) = find_appositive(entity_text, context) {
+            attributes.push(Attribute {
+                id: Uuid::new_v4().to_string(),
+                name: "role".to_string(),
+                value: role,
+                attribute_type: AttributeType::Role,
+                confidence: APPOSITIVE_CONFIDENCE,
+            });
+            if let Some(affiliation) = affiliation {
+                attributes.push(Attribute {
+                    id: Uuid::new_v4().to_string(),
+                    name: "affiliation".to_string(),
+                    value: affiliation,
+                    attribute_type: AttributeType::Affiliation,
+                    confidence: APPOSITIVE_CONFIDENCE,
+                });
+            }
+        } else if let Some(description) = self.extract_description_from_context(entity_text, context) {
             attributes.push(Attribute {
                 id: Uuid::new_v4().to_string(),
                 name: "description".to_string(),
@@ -526,41 +1603,133 @@ Only return the JSON array, no other text."#,
         entity2: &Entity,
         sentence: &str,
         sentence_idx: usize,
+        stemmed: bool,
+        relationship_patterns: &PatternSet,
     ) -> Result<Option<Relationship>> {
+        let (Some(pos1), Some(pos2)) = (sentence.find(&entity1.name), sentence.find(&entity2.name)) else {
+            return Ok(None);
+        };
+        if let Some(validator) = &self.relationship_validator {
+            let score = validator.score(sentence, pos1..pos1 + entity1.name.len(), pos2..pos2 + entity2.name.len());
+            if !validator.accepts(score) {
+                return Ok(None);
+            }
+        }
+        let start = std::cmp::min(pos1, pos2);
+        let end = std::cmp::max(pos1 + entity1.name.len(), pos2 + entity2.name.len());
+        let substring = &sentence[start..end];
+
         // Look for relationship patterns between entities
-        for pattern in &self.relationship_patterns {
-            let entity1_pos = sentence.find(&entity1.name);
-            let entity2_pos = sentence.find(&entity2.name);
-            
-            if let (Some(pos1), Some(pos2)) = (entity1_pos, entity2_pos) {
-                let start = std::cmp::min(pos1, pos2);
-                let end = std::cmp::max(pos1 + entity1.name.len(), pos2 + entity2.name.len());
-                let substring = &sentence[start..end];
-                
-                if pattern.is_match(substring) {
-                    let relationship_type = self.classify_relationship_type(substring);
-                    let label = self.generate_relationship_label(&relationship_type, &entity1.name, &entity2.name);
-                    
-                    return Ok(Some(Relationship {
-                        id: Uuid::new_v4().to_string(),
-                        source_entity_id: entity1.id.clone(),
-                        target_entity_id: entity2.id.clone(),
-                        relationship_type,
-                        label,
-                        confidence: 0.6,
-                        position: Some(TextPosition {
-                            start,
-                            end,
-                            sentence_index: sentence_idx,
-                        }),
-                    }));
-                }
+        for pattern in relationship_patterns.matching(substring) {
+            if let Some(captures) = pattern.captures(substring) {
+                let relationship_type = if stemmed {
+                    self.classify_relationship_type_by_stems(substring)
+                } else {
+                    self.classify_relationship_type(substring)
+                };
+                let (source, target) =
+                    self.infer_direction((entity1, pos1), (entity2, pos2), sentence, start, &captures);
+                let (first_end, second_start) = if pos1 <= pos2 {
+                    (pos1 + entity1.name.len(), pos2)
+                } else {
+                    (pos2 + entity2.name.len(), pos1)
+                };
+                let reversed = std::ptr::eq(source, entity1) != (pos1 <= pos2);
+                let label = sentence
+                    .get(first_end..second_start)
+                    .and_then(|between| self.connecting_phrase(between, reversed))
+                    .unwrap_or_else(|| self.generate_relationship_label(&relationship_type, &source.name, &target.name));
+
+                return Ok(Some(Relationship {
+                    id: Uuid::new_v4().to_string(),
+                    source_entity_id: source.id.clone(),
+                    target_entity_id: target.id.clone(),
+                    relationship_type,
+                    label,
+                    confidence: 0.6,
+                    position: Some(TextPosition {
+                        start,
+                        end,
+                        sentence_index: sentence_idx,
+                    }),
+                    bidirectional: is_symmetric_relation(substring),
+                    category: None,
+                    context: None,
+                }));
             }
         }
         
         Ok(None)
     }
 
+    /// Decide which of two entities in a sentence is the source of a relationship.
+    ///
+    /// A relationship pattern with a named `source` group wins: the entity whose mention
+    /// overlaps that group is the source. Otherwise the entity mentioned first is the
+    /// source, unless the words between the mentions are in the passive voice
+    /// ("the database is managed by Bob"), which flips the direction.
+    fn infer_direction<'a>(
+        &self,
+        (entity1, pos1): (&'a Entity, usize),
+        (entity2, pos2): (&'a Entity, usize),
+        sentence: &str,
+        match_offset: usize,
+        captures: &regex::Captures,
+    ) -> (&'a Entity, &'a Entity) {
+        let overlaps = |range: &std::ops::Range<usize>, pos: usize, len: usize| range.start < pos + len && pos < range.end;
+
+        if let Some(source) = captures.name("source") {
+            let range = (source.start() + match_offset)..(source.end() + match_offset);
+            if overlaps(&range, pos1, entity1.name.len()) {
+                return (entity1, entity2);
+            }
+            if overlaps(&range, pos2, entity2.name.len()) {
+                return (entity2, entity1);
+            }
+        }
+
+        let ((first, first_pos), (second, second_pos)) = if pos1 <= pos2 {
+            ((entity1, pos1), (entity2, pos2))
+        } else {
+            ((entity2, pos2), (entity1, pos1))
+        };
+        let between_start = first_pos + first.name.len();
+        let between = if between_start <= second_pos { &sentence[between_start..second_pos] } else { "" };
+
+        if self.passive_voice.is_match(between) {
+            (second, first)
+        } else {
+            (first, second)
+        }
+    }
+
+    /// The words linking two entity mentions, used as the edge label ("works at",
+    /// "acquired"). `reversed` means the edge points from the second mention to the first:
+    /// a passive phrase is then turned active ("was hired by" -> "hired"), anything else
+    /// would read backwards and is rejected. `None` if the mentions are adjacent or the
+    /// words between them are too many to be a verb phrase.
+    fn connecting_phrase(&self, between: &str, reversed: bool) -> Option<String> {
+        let words: Vec<&str> = between
+            .split_whitespace()
+            .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric() && c != '\''))
+            .filter(|word| !word.is_empty())
+            .collect();
+        let is_article = |word: &&str| matches!(word.to_lowercase().as_str(), "the" | "a" | "an");
+        let start = words.iter().position(|word| !is_article(word))?;
+        let end = words.iter().rposition(|word| !is_article(word))? + 1;
+        let phrase = &words[start..end];
+        if phrase.len() > MAX_VERB_PHRASE_WORDS || between.contains([',', ';', ':']) {
+            return None;
+        }
+        let phrase = phrase.join(" ");
+
+        match (reversed, self.passive_voice.captures(&phrase)) {
+            (false, _) => Some(phrase),
+            (true, Some(passive)) => Some(passive["verb"].to_string()),
+            (true, None) => None,
+        }
+    }
+
     fn classify_relationship_type(&self, text: &str) -> RelationshipType {
         let lower_text = text.to_lowercase();
         
@@ -583,6 +1752,34 @@ Only return the JSON array, no other text."#,
         }
     }
 
+    /// Stem-aware variant of `classify_relationship_type`, so inflections like
+    /// "used"/"using" or "created" classify the same as "uses"/"creates"
+    fn classify_relationship_type_by_stems(&self, text: &str) -> RelationshipType {
+        let stems: Vec<String> = text
+            .split_whitespace()
+            .map(|word| stem_word(word.trim_matches(|c: char| !c.is_alphanumeric())))
+            .collect();
+        let has_any = |keywords: &[&str]| keywords.iter().any(|k| stems.contains(&stem_word(k)));
+
+        if has_any(&["has", "have", "owns"]) {
+            RelationshipType::Has
+        } else if has_any(&["is", "are", "was", "were"]) {
+            RelationshipType::IsA
+        } else if text.to_lowercase().contains("part of") || has_any(&["belongs"]) {
+            RelationshipType::PartOf
+        } else if has_any(&["connected", "linked"]) {
+            RelationshipType::ConnectedTo
+        } else if has_any(&["uses", "utilizes"]) {
+            RelationshipType::Uses
+        } else if has_any(&["creates", "generates"]) {
+            RelationshipType::Creates
+        } else if has_any(&["influences", "affects"]) {
+            RelationshipType::Influences
+        } else {
+            RelationshipType::RelatedTo
+        }
+    }
+
     fn generate_relationship_label(&self, rel_type: &RelationshipType, entity1: &str, entity2: &str) -> String {
         match rel_type {
             RelationshipType::Has => format!("{} has {}", entity1, entity2),
@@ -609,18 +1806,71 @@ Only return the JSON array, no other text."#,
                 })
     }
 
-    /// Call Ollama API with a prompt
-    async fn call_ollama(&self, prompt: &str) -> Result<String> {
-        let client = reqwest::Client::new();
-        let request = OllamaRequest {
+    /// Call Ollama API with a prompt, for a free-text answer
+    pub(crate) async fn call_ollama(&self, prompt: &str) -> Result<String> {
+        self.request_ollama(prompt, None).await
+    }
+
+    /// Call Ollama for the items of one extraction step, constraining the answer to
+    /// JSON as `llm_output_format` says
+    async fn call_ollama_for(&self, prompt: &str, kind: LlmItemKind) -> Result<String> {
+        self.request_ollama(prompt, self.ollama_format(kind)).await
+    }
+
+    /// The `format` an extraction step's answer is constrained to
+    fn ollama_format(&self, kind: LlmItemKind) -> Option<serde_json::Value> {
+        kind.ollama_format(self.config.llm_output_format, &self.config.llm_entity_types())
+    }
+
+    fn ollama_request(&self, prompt: &str, format: Option<serde_json::Value>) -> OllamaRequest {
+        OllamaRequest {
             model: self.config.llm_model.clone(),
             prompt: prompt.to_string(),
-            stream: false,
-        };
+            stream: self.config.llm_stream,
+            format,
+            options: OllamaOptions {
+                num_ctx: self.config.prompt_budget.context_tokens_for(&self.config.llm_model),
+            },
+        }
+    }
+
+    /// What `request`'s answer is cached under: everything but `stream`, which only
+    /// changes how the same answer arrives
+    fn cache_request<'a>(&'a self, request: &'a OllamaRequest) -> LlmRequest<'a> {
+        LlmRequest {
+            endpoint: &self.config.llm_endpoint,
+            model: &request.model,
+            prompt: &request.prompt,
+            parameters: serde_json::json!({ "format": request.format, "options": request.options }),
+        }
+    }
+
+    #[tracing::instrument(name = "llm", skip_all, fields(model = %self.config.llm_model))]
+    async fn request_ollama(&self, prompt: &str, format: Option<serde_json::Value>) -> Result<String> {
+        let request = self.ollama_request(prompt, format);
+        if let Some(response) = self.cache.as_ref().and_then(|cache| cache.get(&self.cache_request(&request))) {
+            debug!("💾 Using cached LLM response");
+            return Ok(response);
+        }
+
+        let text = self.send_ollama(&request).await?;
+
+        if let Some(cache) = &self.cache {
+            if let Err(e) = cache.put(&self.cache_request(&request), &text) {
+                self.warn(format!("Failed to cache LLM response: {}", e));
+            }
+        }
+
+        Ok(text)
+    }
 
+    /// Send one request to the Ollama endpoint and return the text of its answer
+    #[cfg(feature = "native")]
+    async fn send_ollama(&self, request: &OllamaRequest) -> Result<String> {
+        let client = reqwest::Client::new();
         let response = client
             .post(&self.config.llm_endpoint)
-            .json(&request)
+            .json(request)
             .send()
             .await
             .map_err(|e| GraphError::EntityExtraction(format!("Ollama request failed: {}", e)))?;
@@ -632,43 +1882,82 @@ Only return the JSON array, no other text."#,
             )));
         }
 
-        let ollama_response: OllamaResponse = response
-            .json()
-            .await
-            .map_err(|e| GraphError::EntityExtraction(format!("Failed to parse Ollama response: {}", e)))?;
+        if self.config.llm_stream {
+            self.read_streamed_response(response).await
+        } else {
+            let ollama_response: OllamaResponse = response
+                .json()
+                .await
+                .map_err(|e| GraphError::EntityExtraction(format!("Failed to parse Ollama response: {}", e)))?;
+            Ok(ollama_response.response)
+        }
+    }
 
-        Ok(ollama_response.response)
+    #[cfg(not(feature = "native"))]
+    async fn send_ollama(&self, _request: &OllamaRequest) -> Result<String> {
+        Err(GraphError::EntityExtraction(format!(
+            "Cannot call {}: LLM extraction needs a build with the native feature",
+            self.config.llm_endpoint
+        )))
     }
 
-    /// Parse entities from LLM JSON response
-    fn parse_entities_from_llm_response(&self, response: &str) -> Result<Vec<Entity>> {
-        #[derive(Deserialize)]
-        struct LlmEntity {
-            name: String,
-            #[serde(rename = "type")]
-            entity_type: String,
-            confidence: f64,
+    /// Accumulate a streamed response: one JSON object per line, each carrying the next
+    /// piece of text, the last one marked `done`. A stream ending before that is an error.
+    #[cfg(feature = "native")]
+    async fn read_streamed_response(&self, mut response: reqwest::Response) -> Result<String> {
+        let read_error = |e: reqwest::Error| GraphError::EntityExtraction(format!("Ollama stream failed: {}", e));
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut text = String::new();
+        let mut progress = LlmProgress { tokens: 0, characters: 0, done: false };
+
+        loop {
+            let chunk = response.chunk().await.map_err(read_error)?;
+            let finished = chunk.is_none();
+            if let Some(bytes) = chunk {
+                buffer.extend_from_slice(&bytes);
+            }
+            // Complete lines only, unless the stream has ended
+            let end = if finished { buffer.len() } else { buffer.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1) };
+            for line in buffer.drain(..end).collect::<Vec<u8>>().split(|b| *b == b'\n') {
+                let line = String::from_utf8_lossy(line);
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let piece: OllamaStreamChunk = serde_json::from_str(&line)
+                    .map_err(|e| GraphError::EntityExtraction(format!("Failed to parse Ollama stream: {}", e)))?;
+                if let Some(error) = piece.error {
+                    return Err(GraphError::EntityExtraction(format!("Ollama stream error: {}", error)));
+                }
+                text.push_str(&piece.response);
+                progress.tokens += usize::from(!piece.response.is_empty());
+                progress.characters += piece.response.chars().count();
+                progress.done = piece.done;
+                if let Some(callback) = &self.llm_progress {
+                    callback(&progress);
+                }
+            }
+            if finished || progress.done {
+                break;
+            }
         }
 
-        // Try to extract JSON from the response (LLM might include extra text)
-        let json_start = response.find('[').unwrap_or(0);
-        let json_end = response.rfind(']').map(|i| i + 1).unwrap_or(response.len());
-        let json_str = &response[json_start..json_end];
+        // A cut-off answer would be parsed and cached as if it were whole
+        if !progress.done {
+            return Err(GraphError::EntityExtraction(format!(
+                "Ollama stream ended without a final chunk after {} characters",
+                progress.characters
+            )));
+        }
+        Ok(text)
+    }
 
-        let llm_entities: Vec<LlmEntity> = serde_json::from_str(json_str)
-            .map_err(|e| GraphError::EntityExtraction(format!("Failed to parse LLM entities: {}", e)))?;
+    /// Parse entities from LLM JSON response
+    fn parse_entities_from_llm_response(&self, response: &str) -> Result<Vec<Entity>> {
+        let llm_entities: Vec<LlmEntity> = parse_llm_items(response, LlmItemKind::Entities)?;
 
         let mut entities = Vec::new();
         for llm_entity in llm_entities {
-            let entity_type = match llm_entity.entity_type.to_lowercase().as_str() {
-                "person" => EntityType::Person,
-                "place" => EntityType::Place,
-                "organization" => EntityType::Organization,
-                "system" => EntityType::Other("System".to_string()),
-                "process" => EntityType::Other("Process".to_string()),
-                "concept" => EntityType::Other("Concept".to_string()),
-                _ => EntityType::Other(llm_entity.entity_type),
-            };
+            let entity_type = entity_type_from_name(&llm_entity.entity_type);
 
             entities.push(Entity {
                 id: Uuid::new_v4().to_string(),
@@ -685,6 +1974,8 @@ Only return the JSON array, no other text."#,
                 ],
                 confidence: llm_entity.confidence,
                 position: None,
+                mentions: 1,
+                disambiguation: None,
             });
         }
 
@@ -693,21 +1984,7 @@ Only return the JSON array, no other text."#,
 
     /// Parse relationships from LLM JSON response
     fn parse_relationships_from_llm_response(&self, response: &str, entities: &[Entity]) -> Result<Vec<Relationship>> {
-        #[derive(Deserialize)]
-        struct LlmRelationship {
-            from: String,
-            to: String,
-            relationship: String,
-            confidence: f64,
-        }
-
-        // Try to extract JSON from the response
-        let json_start = response.find('[').unwrap_or(0);
-        let json_end = response.rfind(']').map(|i| i + 1).unwrap_or(response.len());
-        let json_str = &response[json_start..json_end];
-
-        let llm_relationships: Vec<LlmRelationship> = serde_json::from_str(json_str)
-            .map_err(|e| GraphError::EntityExtraction(format!("Failed to parse LLM relationships: {}", e)))?;
+        let llm_relationships: Vec<LlmRelationship> = parse_llm_items(response, LlmItemKind::Relationships)?;
 
         // Create a mapping from entity names to IDs
         let entity_map: std::collections::HashMap<String, &Entity> = entities
@@ -726,9 +2003,12 @@ Only return the JSON array, no other text."#,
                     source_entity_id: from_entity.id.clone(),
                     target_entity_id: to_entity.id.clone(),
                     relationship_type: RelationshipType::Other(llm_rel.relationship.clone()),
+                    bidirectional: is_symmetric_relation(&llm_rel.relationship),
                     label: llm_rel.relationship,
                     confidence: llm_rel.confidence,
                     position: None,
+                    category: llm_rel.category.map(|category| category.trim().to_lowercase()).filter(|c| !c.is_empty()),
+                    context: llm_rel.context.map(|context| context.trim().to_string()).filter(|c| !c.is_empty()),
                 });
             }
         }
@@ -738,20 +2018,7 @@ Only return the JSON array, no other text."#,
 
     /// Parse concepts from LLM JSON response
     fn parse_concepts_from_llm_response(&self, response: &str) -> Result<Vec<Concept>> {
-        #[derive(Deserialize)]
-        struct LlmConcept {
-            name: String,
-            description: String,
-            confidence: f64,
-        }
-
-        // Try to extract JSON from the response
-        let json_start = response.find('[').unwrap_or(0);
-        let json_end = response.rfind(']').map(|i| i + 1).unwrap_or(response.len());
-        let json_str = &response[json_start..json_end];
-
-        let llm_concepts: Vec<LlmConcept> = serde_json::from_str(json_str)
-            .map_err(|e| GraphError::EntityExtraction(format!("Failed to parse LLM concepts: {}", e)))?;
+        let llm_concepts: Vec<LlmConcept> = parse_llm_items(response, LlmItemKind::Concepts)?;
 
         let mut concepts = Vec::new();
         for llm_concept in llm_concepts {
@@ -770,36 +2037,39 @@ Only return the JSON array, no other text."#,
 
     /// Perform deep analysis using LLM for comprehensive relationship extraction
     pub async fn extract_with_deep_analysis(&self, processed_text: &ProcessedText) -> Result<ExtractionResult> {
-        if !self.config.use_llm {
+        if !self.config.uses_llm() {
             return Err(GraphError::EntityExtraction(
                 "Deep analysis requires LLM to be enabled. Use --use-llm flag.".to_string()
             ));
         }
 
-        println!("🔬 Starting deep analysis with LLM for comprehensive extraction...");
-        let start_time = std::time::Instant::now();
+        info!("🔬 Starting deep analysis with LLM for comprehensive extraction...");
+        let start_time = web_time::Instant::now();
 
         // Phase 1: Basic extraction
         let mut entities = self.extract_entities_with_llm(processed_text).await?;
+        merge_entities(&mut entities, self.extract_entities_with_gazetteers(processed_text));
+        self.disambiguate_entities(processed_text, &mut entities).await;
         let mut relationships = self.extract_relationships_with_llm(processed_text, &entities).await?;
         let concepts = self.extract_concepts_with_llm(processed_text).await?;
 
-        println!("📊 Initial extraction: {} entities, {} relationships, {} concepts", 
+        info!("📊 Initial extraction: {} entities, {} relationships, {} concepts", 
                 entities.len(), relationships.len(), concepts.len());
 
         // Phase 2: Deep relationship analysis
-        println!("🔍 Performing deep relationship analysis...");
+        info!("🔍 Performing deep relationship analysis...");
         let deep_relationships = self.extract_deep_relationships_with_llm(processed_text, &entities).await?;
-        relationships.extend(deep_relationships);
+        merge_relationships(&mut relationships, deep_relationships);
 
         // Phase 3: Contextual entity enhancement
-        println!("✨ Enhancing entities with contextual information...");
+        info!("✨ Enhancing entities with contextual information...");
         entities = self.enhance_entities_with_context(processed_text, entities).await?;
 
         // Phase 4: Advanced concept mapping
-        println!("🧩 Mapping advanced concept relationships...");
+        info!("🧩 Mapping advanced concept relationships...");
         let concept_relationships = self.extract_concept_relationships(processed_text, &concepts, &entities).await?;
         relationships.extend(concept_relationships);
+        assign_sense_relationships(&entities, &mut relationships);
 
         let processing_time = start_time.elapsed().as_millis() as u64;
 
@@ -812,43 +2082,45 @@ Only return the JSON array, no other text."#,
             extraction_method: format!("Deep-Analysis-LLM-{}", self.config.llm_model),
         };
 
-        println!("🎯 Deep analysis complete: {} entities, {} relationships, {} concepts", 
+        info!("🎯 Deep analysis complete: {} entities, {} relationships, {} concepts", 
                 entities.len(), relationships.len(), concepts.len());
 
-        Ok(ExtractionResult {
+        let mut result = ExtractionResult {
             entities,
             relationships,
             concepts,
             metadata,
-        })
+        };
+        self.apply_whitelist(&mut result);
+        Ok(result)
     }
 
-    /// Extract sophisticated relationships using advanced LLM prompting
+    /// Implicit, temporal, hierarchical, functional and dependency relationships from the
+    /// LLM, each with its category and the text supporting it. Falls back to the enhanced
+    /// relationship patterns when the call or its parsing fails.
     async fn extract_deep_relationships_with_llm(&self, processed_text: &ProcessedText, entities: &[Entity]) -> Result<Vec<Relationship>> {
-        let entity_names: Vec<&str> = entities.iter().map(|e| e.name.as_str()).collect();
-        
-        let _prompt = format!(
-            r#"Analyze the following text for sophisticated relationships between entities. 
-            
-Text: "{}"
-
-Known entities: {:?}
-
-Please identify:
-1. Implicit relationships (not directly stated but implied)
-2. Temporal relationships (sequence, causation)
-3. Hierarchical relationships (parent-child, part-whole)
-4. Functional relationships (roles, responsibilities)
-5. Dependency relationships (requires, depends on)
-
-Return relationships in JSON format:
-[{{"from": "entity1", "to": "entity2", "type": "relationship_type", "confidence": 0.8, "context": "supporting_text"}}]"#,
-            processed_text.cleaned_text,
-            entity_names
-        );
+        if entities.is_empty() {
+            return Ok(Vec::new());
+        }
 
-        // This would call the LLM - for now, return enhanced pattern-based relationships
-        self.extract_relationships_with_enhanced_patterns(processed_text, entities)
+        let mut relationships = Vec::new();
+        for prompt in self.deep_relationship_prompts(entities, &self.prompt_text(processed_text)) {
+            match self.call_ollama_for(&prompt, LlmItemKind::DeepRelationships).await {
+                Ok(response) => match self.parse_relationships_from_llm_response(&response, entities) {
+                    Ok(found) => merge_relationships(&mut relationships, found),
+                    Err(e) => {
+                        self.warn(format!("Deep analysis response parsing failed: {}, falling back to patterns", e));
+                        return self.extract_relationships_with_enhanced_patterns(processed_text, entities);
+                    }
+                },
+                Err(e) => {
+                    self.warn(format!("Deep analysis LLM call failed: {}, falling back to patterns", e));
+                    return self.extract_relationships_with_enhanced_patterns(processed_text, entities);
+                }
+            }
+        }
+        info!("✅ Deep analysis found {} relationships", relationships.len());
+        Ok(relationships)
     }
 
     /// Enhance entities with additional contextual information
@@ -903,6 +2175,9 @@ Return relationships in JSON format:
                         label: "relates to".to_string(),
                         confidence: 0.65,
                         position: None,
+                        bidirectional: true,
+                        category: None,
+                        context: None,
                     });
                 }
             }
@@ -914,7 +2189,7 @@ Return relationships in JSON format:
     /// Analyze contextual information about an entity
     fn analyze_entity_context(&self, processed_text: &ProcessedText, entity_name: &str) -> std::collections::HashMap<String, String> {
         let mut context_info = std::collections::HashMap::new();
-        let text = &processed_text.cleaned_text.to_lowercase();
+        let text = &self.prompt_text(processed_text).to_lowercase();
         let entity_lower = entity_name.to_lowercase();
 
         // Look for role indicators
@@ -961,43 +2236,34 @@ Return relationships in JSON format:
             .map(|e| (e.name.to_lowercase(), e))
             .collect();
 
-        // Enhanced patterns for sophisticated relationships
-        let enhanced_patterns = [
-            (r"(\w+)\s+manages?\s+(\w+)", RelationshipType::Other("manages".to_string())),
-            (r"(\w+)\s+depends?\s+on\s+(\w+)", RelationshipType::Other("depends_on".to_string())),
-            (r"(\w+)\s+implements?\s+(\w+)", RelationshipType::Other("implements".to_string())),
-            (r"(\w+)\s+inherits?\s+from\s+(\w+)", RelationshipType::Other("inherits_from".to_string())),
-            (r"(\w+)\s+communicates?\s+with\s+(\w+)", RelationshipType::Other("communicates_with".to_string())),
-            (r"(\w+)\s+provides?\s+(\w+)", RelationshipType::Other("provides".to_string())),
-            (r"(\w+)\s+requires?\s+(\w+)", RelationshipType::Other("requires".to_string())),
-        ];
-
-        for (pattern_str, rel_type) in enhanced_patterns {
-            if let Ok(pattern) = Regex::new(pattern_str) {
-                for capture in pattern.captures_iter(&processed_text.cleaned_text.to_lowercase()) {
-                    if let (Some(entity1_match), Some(entity2_match)) = (capture.get(1), capture.get(2)) {
-                        let entity1_name = entity1_match.as_str();
-                        let entity2_name = entity2_match.as_str();
-
-                        if let (Some(entity1), Some(entity2)) = (
-                            entity_names.get(entity1_name),
-                            entity_names.get(entity2_name)
-                        ) {
-                            let label = match &rel_type {
-                                RelationshipType::Other(label) => label.clone(),
-                                _ => "enhanced relationship".to_string(),
-                            };
-                            
-                            relationships.push(Relationship {
-                                id: uuid::Uuid::new_v4().to_string(),
-                                source_entity_id: entity1.id.clone(),
-                                target_entity_id: entity2.id.clone(),
-                                relationship_type: rel_type.clone(),
-                                label,
-                                confidence: 0.75, // Higher confidence for enhanced patterns
-                                position: None,
-                            });
-                        }
+        let text = self.prompt_text(processed_text).to_lowercase();
+        for (pattern, rel_type) in &self.enhanced_relationship_patterns {
+            for capture in pattern.captures_iter(&text) {
+                if let (Some(entity1_match), Some(entity2_match)) = (capture.get(1), capture.get(2)) {
+                    let entity1_name = entity1_match.as_str();
+                    let entity2_name = entity2_match.as_str();
+
+                    if let (Some(entity1), Some(entity2)) = (
+                        entity_names.get(entity1_name),
+                        entity_names.get(entity2_name)
+                    ) {
+                        let label = match rel_type {
+                            RelationshipType::Other(label) => label.clone(),
+                            _ => "enhanced relationship".to_string(),
+                        };
+                        
+                        relationships.push(Relationship {
+                            id: uuid::Uuid::new_v4().to_string(),
+                            source_entity_id: entity1.id.clone(),
+                            target_entity_id: entity2.id.clone(),
+                            relationship_type: rel_type.clone(),
+                            bidirectional: is_symmetric_relation(&label),
+                            label,
+                            confidence: 0.75, // Higher confidence for enhanced patterns
+                            position: None,
+                            category: None,
+                            context: None,
+                        });
                     }
                 }
             }
@@ -1013,3 +2279,473 @@ impl Default for EntityExtractor {
             .expect("Failed to create default EntityExtractor")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entity(name: &str) -> Entity {
+        Entity {
+            id: name.to_lowercase(),
+            name: name.to_string(),
+            entity_type: EntityType::Person,
+            attributes: Vec::new(),
+            confidence: 0.7,
+            position: None,
+            mentions: 1,
+            disambiguation: None,
+        }
+    }
+
+    fn relationship(extractor: &EntityExtractor, first: &str, second: &str, sentence: &str) -> Relationship {
+        extractor
+            .find_relationship_between_entities(
+                &entity(first),
+                &entity(second),
+                sentence,
+                0,
+                false,
+                &extractor.relationship_patterns,
+            )
+            .expect("Relationship lookup failed")
+            .expect("No relationship found")
+    }
+
+    #[test]
+    fn test_passive_voice_reverses_direction() {
+        let extractor = EntityExtractor::default();
+
+        let active = relationship(&extractor, "Bob", "Database", "Bob has the Database.");
+        assert_eq!(active.source_entity_id, "bob");
+        assert!(!active.bidirectional);
+
+        // Entity list order shouldn't matter; the agent after "by" is the source
+        let passive = relationship(&extractor, "Database", "Bob", "The Database is managed by Bob.");
+        assert_eq!(passive.source_entity_id, "bob");
+        assert_eq!(passive.target_entity_id, "database");
+    }
+
+    #[test]
+    fn test_edge_label_is_connecting_verb_phrase() {
+        let extractor = EntityExtractor::default();
+
+        let rel = relationship(&extractor, "Alice", "Bob", "Alice is connected to Bob.");
+        assert_eq!(rel.label, "is connected to");
+        assert!(matches!(rel.relationship_type, RelationshipType::IsA));
+
+        // Passive phrases are turned around along with the edge
+        let passive = relationship(&extractor, "Carol", "Initech", "Carol was hired by Initech.");
+        assert_eq!(passive.source_entity_id, "initech");
+        assert_eq!(passive.label, "hired");
+
+        // Too far apart to be a verb phrase: fall back to the generated label
+        let far = relationship(
+            &extractor,
+            "Alice",
+            "Bob",
+            "Alice, who has lived here for many years, is connected to Bob.",
+        );
+        assert!(far.label.starts_with("Alice ") && far.label.ends_with(" Bob"));
+    }
+
+    #[test]
+    fn test_symmetric_relations_are_bidirectional() {
+        let extractor = EntityExtractor::default();
+        let rel = relationship(&extractor, "Alice", "Bob", "Alice is connected to Bob.");
+        assert!(rel.bidirectional);
+    }
+
+    #[test]
+    fn test_named_source_group_sets_direction() {
+        let config = ExtractionConfig {
+            relationship_patterns: vec![r"(?P<target>\w+) reports to (?P<source>\w+)".to_string()],
+            ..ExtractionConfig::default()
+        };
+        let extractor = EntityExtractor::new(config).expect("Failed to create extractor");
+
+        let rel = relationship(&extractor, "Carol", "Dave", "Carol reports to Dave.");
+        assert_eq!(rel.source_entity_id, "dave");
+        assert_eq!(rel.target_entity_id, "carol");
+    }
+
+    #[test]
+    fn test_retain_entities_keeps_whitelisted_subgraph() {
+        let extractor = EntityExtractor::default();
+        let mut result = ExtractionResult {
+            entities: vec![entity("Alice Smith"), entity("Bob"), entity("Carol")],
+            relationships: vec![
+                relationship(&extractor, "Alice Smith", "Bob", "Alice Smith has the Bob."),
+                relationship(&extractor, "Bob", "Carol", "Bob has the Carol."),
+            ],
+            concepts: Vec::new(),
+            metadata: ExtractionMetadata {
+                total_entities: 3,
+                total_relationships: 2,
+                total_concepts: 0,
+                processing_time_ms: 0,
+                confidence_threshold: 0.5,
+                extraction_method: "test".to_string(),
+            },
+        };
+
+        result.retain_entities(&["alice".to_string(), "BOB".to_string(), " ".to_string()]);
+        let names: Vec<&str> = result.entities.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["Alice Smith", "Bob"]);
+        assert_eq!(result.relationships.len(), 1);
+        assert_eq!(result.metadata.total_relationships, 1);
+    }
+
+    #[test]
+    fn test_stable_ids_depend_only_on_content() {
+        let extractor = EntityExtractor::default();
+        let result = |names: &[&str]| ExtractionResult {
+            entities: names.iter().map(|name| entity(name)).collect(),
+            relationships: vec![relationship(&extractor, "Bob", "Carol", "Bob has the Carol.")],
+            concepts: Vec::new(),
+            metadata: ExtractionMetadata {
+                total_entities: names.len(),
+                total_relationships: 1,
+                total_concepts: 0,
+                processing_time_ms: 0,
+                confidence_threshold: 0.5,
+                extraction_method: "test".to_string(),
+            },
+        };
+
+        let mut first = result(&["Carol", "Bob", "Alice"]);
+        let mut second = result(&["Alice", "Carol", "Bob"]);
+        // Random ids from a real run must not leak into the stable ones
+        second.entities.iter_mut().for_each(|entity| entity.id = Uuid::new_v4().to_string());
+        second.relationships[0].id = Uuid::new_v4().to_string();
+        second.relationships[0].source_entity_id = second.entities[2].id.clone();
+        second.relationships[0].target_entity_id = second.entities[1].id.clone();
+        first.assign_stable_ids();
+        second.assign_stable_ids();
+
+        let ids = |result: &ExtractionResult| result.entities.iter().map(|e| (e.name.clone(), e.id.clone())).collect::<Vec<_>>();
+        assert_eq!(ids(&first), ids(&second));
+        assert_eq!(first.entities[0].name, "Alice");
+        assert!(first.entities[0].id.starts_with("entity-"));
+        let bob = &first.entities[1].id;
+        assert_eq!(&first.relationships[0].source_entity_id, bob);
+        assert_eq!(first.relationships[0].id, second.relationships[0].id);
+
+        // Same name and type: the second one gets a suffix
+        let mut twins = result(&["Alice", "alice"]);
+        twins.assign_stable_ids();
+        assert_eq!(twins.entities[1].id, format!("{}-2", twins.entities[0].id));
+    }
+
+    #[test]
+    fn test_concepts_normalized_before_dedup() {
+        let extractor = EntityExtractor::default();
+        let concept = |name: &str, confidence: f64| Concept {
+            id: name.to_string(),
+            name: name.to_string(),
+            description: String::new(),
+            related_entities: Vec::new(),
+            confidence,
+            position: None,
+        };
+
+        let concepts = extractor.normalize_concepts(
+            vec![
+                concept("The analytics module", 0.6),
+                concept("analytics Module", 0.9),
+                concept("the", 0.5),
+                concept("A caching strategy", 0.7),
+            ],
+            false,
+        );
+        let names: Vec<&str> = concepts.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["analytics module", "caching strategy"]);
+        assert_eq!(concepts[0].confidence, 0.9);
+    }
+
+    #[test]
+    fn test_pattern_set_returns_only_matching_patterns_in_order() {
+        let patterns: Vec<String> = [r"\bworks at\b", r"\bmanages\b", r"\bis connected to\b"]
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        let set = PatternSet::new(&patterns).unwrap();
+        let matched: Vec<&str> = set.matching("Bob manages Alice, who is connected to Carol").map(|r| r.as_str()).collect();
+        assert_eq!(matched, vec![patterns[1].as_str(), patterns[2].as_str()]);
+        assert_eq!(set.matching("nothing here").count(), 0);
+        assert!(PatternSet::new(&["(unclosed".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_keyword_concepts_replace_patterns() {
+        let mut config = ExtractionConfig::default();
+        config.keywords.enabled = true;
+        config.keywords.replace_patterns = true;
+        let extractor = EntityExtractor::new(config).expect("Failed to create extractor");
+        let processed = TextProcessor::new()
+            .unwrap()
+            .process_text(
+                "The billing service sends invoices. The billing service retries failed payments. \
+                 Failed payments trigger a support ticket.",
+                crate::text_processor::SourceType::Document,
+            )
+            .unwrap();
+
+        let concepts = extractor.extract_concepts_with_patterns(&processed).unwrap();
+        let names: Vec<&str> = concepts.iter().map(|c| c.name.as_str()).collect();
+        assert!(names.contains(&"billing service"));
+        assert!(names.contains(&"failed payments"));
+        assert!(concepts.iter().all(|c| c.description.contains("keyword") && c.confidence <= 0.9));
+    }
+
+    #[test]
+    fn test_custom_entity_types_reach_the_llm_prompt_and_schema() {
+        let mut config = ExtractionConfig::default();
+        let extractor = EntityExtractor::new(config.clone()).unwrap();
+        // Without custom types the prompt is unchanged, so cached responses stay valid
+        assert!(extractor.entity_prompt("x", &[]).contains(r#""type": "Person|Place|Organization|System|Process|Concept|Other""#));
+
+        config.entity_types = vec![crate::config::EntityTypeDefinition {
+            name: "Gene".to_string(),
+            keywords: Vec::new(),
+            color: None,
+            shape: None,
+        }];
+        let extractor = EntityExtractor::new(config.clone()).unwrap();
+        assert!(extractor.entity_prompt("x", &[]).contains("Concept|Gene|Other"));
+        let schema = LlmItemKind::Entities.schema(&config.llm_entity_types());
+        let allowed = &schema["properties"]["entities"]["items"]["properties"]["type"]["enum"];
+        assert_eq!(allowed.as_array().unwrap().len(), 8);
+        assert_eq!(allowed[6], "Gene");
+        assert!(config.entity_type_definition("GENE").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_gazetteer_names_are_found_and_typed() {
+        let dir = tempfile::tempdir().unwrap();
+        let products = dir.path().join("products.txt");
+        let people = dir.path().join("people.txt");
+        std::fs::write(&products, "WidgetPro\n").unwrap();
+        std::fs::write(&people, "Alice Smith\n").unwrap();
+        let config = ExtractionConfig {
+            gazetteer_files: vec![
+                crate::gazetteer::GazetteerFile {
+                    path: products.display().to_string(),
+                    entity_type: Some("product".to_string()),
+                    case_sensitive: false,
+                },
+                crate::gazetteer::GazetteerFile {
+                    path: people.display().to_string(),
+                    entity_type: Some("person".to_string()),
+                    case_sensitive: true,
+                },
+            ],
+            ..ExtractionConfig::default()
+        };
+        let extractor = EntityExtractor::new(config).expect("Failed to create extractor");
+        let processed = TextProcessor::new()
+            .unwrap()
+            .process_text(
+                "Alice Smith demoed the new widgetpro release.",
+                crate::text_processor::SourceType::Document,
+            )
+            .unwrap();
+
+        let result = extractor.extract_from_text(&processed).await.unwrap();
+        let entity = |name: &str| result.entities.iter().find(|e| e.name == name).expect(name);
+        // Lowercase "widgetpro" is invisible to the capitalized-word pattern
+        assert!(matches!(&entity("WidgetPro").entity_type, EntityType::Other(t) if t == "product"));
+        assert_eq!(entity("WidgetPro").confidence, GAZETTEER_CONFIDENCE);
+        assert!(matches!(entity("Alice Smith").entity_type, EntityType::Person));
+        assert_eq!(entity("Alice Smith").confidence, GAZETTEER_CONFIDENCE);
+    }
+
+    #[test]
+    fn test_prompt_text_keeps_stopwords_inside_matched_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let banks = dir.path().join("banks.txt");
+        std::fs::write(&banks, "bank of the west\n").unwrap();
+        let mut config = ExtractionConfig {
+            gazetteer_files: vec![crate::gazetteer::GazetteerFile {
+                path: banks.display().to_string(),
+                entity_type: Some("organization".to_string()),
+                case_sensitive: false,
+            }],
+            ..ExtractionConfig::default()
+        };
+        config.entity_patterns.push(r"\bstate of the art\b".to_string());
+        let extractor = EntityExtractor::new(config).expect("Failed to create extractor");
+        let mut processor = TextProcessor::new().unwrap();
+        processor.set_strip_prompt_stopwords(true);
+        let processed = processor
+            .process_text("The state of the art loans came from bank of the west.", crate::text_processor::SourceType::Document)
+            .unwrap();
+
+        assert_eq!(processed.cleaned_text(), "state art loans bank west.");
+        assert_eq!(extractor.prompt_text(&processed), "state of the art loans bank of the west.");
+    }
+
+    #[tokio::test]
+    async fn test_relationship_rules_replace_generic_pattern_matches() {
+        let config = ExtractionConfig {
+            relationship_rules: vec!["{object} is (?:managed|run) by {subject} -> manages".to_string()],
+            ..ExtractionConfig::default()
+        };
+        let extractor = EntityExtractor::new(config).expect("Failed to create extractor");
+        let processed = TextProcessor::new()
+            .unwrap()
+            .process_text(
+                "Billing is run by Carol. Erin is connected to Dave.",
+                crate::text_processor::SourceType::Document,
+            )
+            .unwrap();
+
+        let result = extractor.extract_from_text(&processed).await.unwrap();
+        let id = |name: &str| result.entities.iter().find(|e| e.name == name).expect(name).id.clone();
+        let manages: Vec<_> = result.relationships.iter().filter(|r| r.target_entity_id == id("Billing")).collect();
+        assert_eq!(manages.len(), 1);
+        assert_eq!((manages[0].source_entity_id.clone(), manages[0].label.as_str()), (id("Carol"), "manages"));
+        assert_eq!(manages[0].confidence, RULE_CONFIDENCE);
+        // Sentences no rule matches still use the relationship patterns
+        assert!(result.relationships.iter().any(|r| r.target_entity_id == id("Dave") && r.confidence < RULE_CONFIDENCE));
+
+        assert!(EntityExtractor::new(ExtractionConfig {
+            relationship_rules: vec!["{subject} manages".to_string()],
+            ..ExtractionConfig::default()
+        })
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_relationship_validation_drops_distant_pattern_matches() {
+        let text = "Alice, who spent many long years travelling through several distant cities before finally coming home, is connected to Globex. Bob is connected to Initech.";
+        let processed = TextProcessor::new()
+            .unwrap()
+            .process_text(text, crate::text_processor::SourceType::Document)
+            .unwrap();
+        let linked = |result: &ExtractionResult, first: &str, second: &str| {
+            let id = |name: &str| result.entities.iter().find(|e| e.name == name).expect(name).id.clone();
+            result.relationships.iter().any(|r| {
+                [r.source_entity_id.clone(), r.target_entity_id.clone()] == [id(first), id(second)]
+            })
+        };
+
+        let result = EntityExtractor::default().extract_from_text(&processed).await.unwrap();
+        assert!(!linked(&result, "Alice", "Globex"));
+        assert!(linked(&result, "Bob", "Initech"));
+
+        let mut config = ExtractionConfig::default();
+        config.relationship_validation.enabled = false;
+        let result = EntityExtractor::new(config).unwrap().extract_from_text(&processed).await.unwrap();
+        assert!(linked(&result, "Alice", "Globex"));
+    }
+
+    #[tokio::test]
+    async fn test_noun_phrase_detector_finds_lowercase_entities() {
+        let config = ExtractionConfig {
+            entity_detector: EntityDetector::NounPhrases,
+            ..ExtractionConfig::default()
+        };
+        let extractor = EntityExtractor::new(config).expect("Failed to create extractor");
+        let processed = TextProcessor::new()
+            .unwrap()
+            .process_text(
+                "The analytics module depends on the billing service. Acme Corp owns it.",
+                crate::text_processor::SourceType::Document,
+            )
+            .unwrap();
+
+        let result = extractor.extract_from_text(&processed).await.unwrap();
+        let entity = |name: &str| result.entities.iter().find(|e| e.name == name).expect(name);
+        assert_eq!(entity("analytics module").confidence, NOUN_PHRASE_CONFIDENCE);
+        assert_eq!(entity("billing service").position.as_ref().unwrap().sentence_index, 0);
+        assert_eq!(entity("Acme Corp").confidence, 0.7);
+        assert_eq!(result.entities.len(), 3);
+        assert!(result.relationships.iter().any(|r| r.source_entity_id == entity("analytics module").id
+            && r.target_entity_id == entity("billing service").id));
+    }
+
+    #[test]
+    fn test_appositive_roles_and_affiliations() {
+        let found = |context: &str| find_appositive("Alice", context);
+        let role_at = |role: &str, org: &str| Some((role.to_string(), Some(org.to_string())));
+        assert_eq!(found("Alice, CTO of TechCorp, joined."), role_at("CTO", "TechCorp"));
+        assert_eq!(found("Alice, the Chief Technology Officer at Acme Labs."), role_at("Chief Technology Officer", "Acme Labs"));
+        assert_eq!(found("Alice, a senior engineer for Initech, left"), role_at("senior engineer", "Initech"));
+        assert_eq!(found("Alice, VP of Engineering at TechCorp, spoke"), role_at("VP of Engineering", "TechCorp"));
+        assert_eq!(found("Alice, CEO, said no."), Some(("CEO".to_string(), None)));
+        assert_eq!(found("Alice, who works at TechCorp, left."), None);
+        assert_eq!(found("Alice, Bob, and Carol met."), None);
+    }
+
+    #[tokio::test]
+    async fn test_appositives_give_typed_attributes_and_an_organization_edge() {
+        let processed = TextProcessor::new()
+            .unwrap()
+            .process_text(
+                "Alice, CTO of TechCorp, joined the board. Bob, a senior engineer at Initech, met Carol.",
+                crate::text_processor::SourceType::Document,
+            )
+            .unwrap();
+
+        let result = EntityExtractor::default().extract_from_text(&processed).await.unwrap();
+        let entity = |name: &str| result.entities.iter().find(|e| e.name == name).expect(name);
+        let attribute = |name: &str, attribute_type: &str| {
+            entity(name)
+                .attributes
+                .iter()
+                .find(|attr| format!("{:?}", attr.attribute_type) == attribute_type)
+                .map(|attr| attr.value.clone())
+        };
+        assert_eq!(attribute("Alice", "Role").as_deref(), Some("CTO"));
+        assert_eq!(attribute("Alice", "Affiliation").as_deref(), Some("TechCorp"));
+        assert_eq!(attribute("Alice", "Description"), None);
+        assert_eq!(attribute("Bob", "Role").as_deref(), Some("senior engineer"));
+
+        // "TechCorp" is missed by the capitalized-word pattern but named by the appositive
+        assert!(matches!(entity("TechCorp").entity_type, EntityType::Organization));
+        let edge = |from: &str, to: &str| {
+            result
+                .relationships
+                .iter()
+                .find(|r| r.source_entity_id == entity(from).id && r.target_entity_id == entity(to).id)
+        };
+        assert!(edge("Alice", "TechCorp").is_some());
+        let affiliated = edge("Bob", "Initech").expect("Bob -> Initech");
+        assert_eq!(affiliated.label, "affiliated with");
+        assert_eq!(affiliated.confidence, APPOSITIVE_CONFIDENCE);
+    }
+
+    #[tokio::test]
+    async fn test_language_patterns_follow_the_detected_language() {
+        let extract = |text: &'static str| async move {
+            let processed = TextProcessor::new()
+                .unwrap()
+                .process_text(text, crate::text_processor::SourceType::Document)
+                .unwrap();
+            (processed.metadata.language.clone(), EntityExtractor::default().extract_from_text(&processed).await.unwrap())
+        };
+        let entity_type = |result: &ExtractionResult, name: &str| {
+            result.entities.iter().find(|e| e.name == name).map(|e| e.entity_type.clone())
+        };
+
+        let (language, result) =
+            extract("Frau Schmidt leitet die Siemens AG in München. Der Vertrag mit der Deutschen Bahn ist neu.").await;
+        assert_eq!(language, "german");
+        assert!(matches!(entity_type(&result, "Schmidt"), Some(EntityType::Person)));
+        assert!(matches!(entity_type(&result, "Siemens AG"), Some(EntityType::Organization)));
+        assert!(entity_type(&result, "Vertrag").is_none() && entity_type(&result, "Der Vertrag").is_none());
+        assert!(!matches!(entity_type(&result, "Deutschen Bahn"), Some(EntityType::Person) | None));
+        assert_eq!(result.relationships.len(), 1);
+
+        let (language, result) =
+            extract("El Banco de España tiene un acuerdo con la Universidad de Salamanca. La empresa es nueva.").await;
+        assert_eq!(language, "spanish");
+        assert!(matches!(entity_type(&result, "Banco de España"), Some(EntityType::Organization)));
+        assert!(matches!(entity_type(&result, "Universidad de Salamanca"), Some(EntityType::Organization)));
+        assert!(entity_type(&result, "La").is_none());
+        assert_eq!(result.relationships.len(), 1);
+
+        let config = ExtractionConfig { language: Some("french".to_string()), ..ExtractionConfig::default() };
+        assert!(EntityExtractor::new(config).is_err());
+    }
+}
@@ -20,6 +20,9 @@ pub enum GraphError {
     #[error("Configuration error: {0}")]
     Configuration(String),
     
+    #[error("Storage error: {0}")]
+    Storage(String),
+    
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
     
@@ -29,8 +32,13 @@ pub enum GraphError {
     #[error("Regex error: {0}")]
     Regex(#[from] regex::Error),
     
+    #[cfg(feature = "native")]
     #[error("HTTP request error: {0}")]
     Http(#[from] reqwest::Error),
+    
+    #[cfg(feature = "native")]
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
 }
 
 pub type Result<T> = std::result::Result<T, GraphError>;
@@ -0,0 +1,193 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Rough characters per token for English text with common LLM tokenizers
+pub const CHARS_PER_TOKEN: f64 = 4.0;
+
+/// Tokens one extracted item (a JSON object in the response) typically costs
+pub const RESPONSE_TOKENS_PER_ITEM: usize = 25;
+
+/// Approximate token count of `text`
+pub fn estimate_tokens(text: &str) -> usize {
+    (text.chars().count() as f64 / CHARS_PER_TOKEN).ceil() as usize
+}
+
+/// Pricing and budget for LLM runs. Prices default to zero, as for a local Ollama model;
+/// set them for paid backends to see a projected cost.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LlmCostConfig {
+    /// Price of one million prompt tokens
+    pub input_cost_per_million: f64,
+    /// Price of one million response tokens
+    pub output_cost_per_million: f64,
+    /// Currency shown next to costs
+    pub currency: String,
+    /// `--confirm` asks before runs projected to cost more than this
+    pub budget: Option<f64>,
+    /// `--confirm` asks before runs estimated to use more tokens than this
+    pub budget_tokens: Option<usize>,
+}
+
+impl LlmCostConfig {
+    pub fn is_priced(&self) -> bool {
+        self.input_cost_per_million > 0.0 || self.output_cost_per_million > 0.0
+    }
+
+    fn currency(&self) -> &str {
+        if self.currency.is_empty() { "USD" } else { &self.currency }
+    }
+}
+
+/// What a generate run would do, reported by `--dry-run` before any LLM call is made
+#[derive(Debug, Clone, Serialize)]
+pub struct RunEstimate {
+    /// `entities` or `cooccurrence`
+    pub mode: String,
+    /// LLM model, when extraction uses one
+    pub model: Option<String>,
+    pub characters: usize,
+    pub words: usize,
+    pub sentences: usize,
+    /// Segments the text is split into to fit the model's context window, see
+    /// [`PromptBudgetConfig`](crate::prompt_budget::PromptBudgetConfig)
+    pub chunks: usize,
+    /// Requests that would reach the LLM
+    pub llm_calls: usize,
+    /// Requests answered from the response cache instead
+    pub cached_calls: usize,
+    /// Estimated prompt tokens of the requests that would reach the LLM
+    pub prompt_tokens: usize,
+    /// Rough response tokens, from what the pattern extractor finds
+    pub response_tokens: usize,
+    /// Largest single prompt, to compare with the model's context window
+    pub largest_prompt_tokens: usize,
+    /// WebAssembly extractor plugins that would run
+    pub plugins: usize,
+    /// Projected cost of the tokens, when pricing is configured
+    pub cost: Option<f64>,
+    /// Currency of `cost`
+    pub currency: String,
+}
+
+impl RunEstimate {
+    pub fn total_tokens(&self) -> usize {
+        self.prompt_tokens + self.response_tokens
+    }
+
+    /// Fill in `cost` from the configured prices; unpriced backends have no cost
+    pub fn with_pricing(mut self, pricing: &LlmCostConfig) -> Self {
+        self.currency = pricing.currency().to_string();
+        self.cost = pricing.is_priced().then(|| {
+            (self.prompt_tokens as f64 * pricing.input_cost_per_million
+                + self.response_tokens as f64 * pricing.output_cost_per_million)
+                / 1_000_000.0
+        });
+        self
+    }
+
+    /// Why this run goes over the configured budget, or `None` when it fits
+    pub fn budget_overrun(&self, pricing: &LlmCostConfig) -> Option<String> {
+        if let (Some(cost), Some(budget)) = (self.cost, pricing.budget) {
+            if cost > budget {
+                return Some(format!(
+                    "projected cost {:.4} {} exceeds the budget of {:.4} {}",
+                    cost, self.currency, budget, self.currency
+                ));
+            }
+        }
+        match pricing.budget_tokens {
+            Some(limit) if self.total_tokens() > limit => {
+                Some(format!("~{} estimated tokens exceed the budget of {}", self.total_tokens(), limit))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for RunEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Mode: {}", self.mode)?;
+        writeln!(f, "Model: {}", self.model.as_deref().unwrap_or("none (pattern-based)"))?;
+        writeln!(
+            f,
+            "Input: {} characters, {} words, {} sentences",
+            self.characters, self.words, self.sentences
+        )?;
+        writeln!(f, "Chunks: {}", self.chunks)?;
+        writeln!(f, "LLM calls: {} ({} more answered from cache)", self.llm_calls, self.cached_calls)?;
+        writeln!(
+            f,
+            "Estimated tokens: ~{} prompt + ~{} response (largest prompt ~{})",
+            self.prompt_tokens, self.response_tokens, self.largest_prompt_tokens
+        )?;
+        if let Some(cost) = self.cost {
+            writeln!(f, "Projected cost: ~{:.4} {}", cost, self.currency)?;
+        }
+        write!(f, "Plugins: {}", self.plugins)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GraphConfig;
+    use crate::pipeline::Pipeline;
+
+    #[test]
+    fn test_estimate_counts_llm_calls_without_calling() {
+        let text = "Alice works at TechCorp. Bob is connected to Alice.";
+        let mut config = GraphConfig::default();
+        config.extraction.llm_cache.enabled = false;
+
+        let estimate = Pipeline::new().with_config(config.clone()).with_text(text).estimate().unwrap();
+        assert_eq!((estimate.llm_calls, estimate.prompt_tokens, estimate.chunks), (0, 0, 0));
+
+        // Nothing listens on this port; an estimate must not try to connect
+        config.extraction.use_llm = true;
+        config.extraction.llm_endpoint = "http://127.0.0.1:9/api/generate".to_string();
+        let estimate = Pipeline::new().with_config(config).with_text(text).estimate().unwrap();
+        assert_eq!(estimate.llm_calls, 3);
+        assert_eq!(estimate.chunks, 1);
+        assert!(estimate.prompt_tokens > 3 * estimate_tokens(text));
+        assert!(estimate.response_tokens >= RESPONSE_TOKENS_PER_ITEM);
+        assert!(estimate.to_string().contains("LLM calls: 3"));
+        assert!(!estimate.to_string().contains("Projected cost"));
+    }
+
+    #[test]
+    fn test_cost_and_budget() {
+        let estimate = RunEstimate {
+            mode: "entities".to_string(),
+            model: Some("gpt".to_string()),
+            characters: 0,
+            words: 0,
+            sentences: 0,
+            chunks: 1,
+            llm_calls: 2,
+            cached_calls: 0,
+            prompt_tokens: 200_000,
+            response_tokens: 50_000,
+            largest_prompt_tokens: 100_000,
+            plugins: 0,
+            cost: None,
+            currency: String::new(),
+        };
+        let mut pricing = LlmCostConfig {
+            input_cost_per_million: 2.5,
+            output_cost_per_million: 10.0,
+            budget: Some(1.5),
+            ..LlmCostConfig::default()
+        };
+        let priced = estimate.clone().with_pricing(&pricing);
+        assert_eq!(priced.cost, Some(1.0));
+        assert!(priced.to_string().contains("Projected cost: ~1.0000 USD"));
+        assert_eq!(priced.budget_overrun(&pricing), None);
+
+        pricing.budget_tokens = Some(100_000);
+        assert!(priced.budget_overrun(&pricing).unwrap().contains("250000 estimated tokens"));
+        pricing.budget = Some(0.5);
+        assert!(priced.budget_overrun(&pricing).unwrap().contains("projected cost 1.0000 USD"));
+        assert_eq!(estimate.with_pricing(&LlmCostConfig::default()).cost, None);
+    }
+}
@@ -0,0 +1,193 @@
+use crate::config::ExtractionConfig;
+use crate::entity_extractor::EntityExtractor;
+use crate::error::Result;
+use crate::graph_builder::{GraphEdge, GraphNode, InteractiveGraph, NodeType};
+use regex::Regex;
+use std::collections::HashSet;
+
+/// Upper bound on edges sent to the LLM, to keep prompts small for large graphs
+pub const MAX_CONTEXT_EDGES: usize = 60;
+
+/// The part of a graph relevant to a question
+#[derive(Debug, Clone)]
+pub struct Subgraph {
+    /// Nodes named in the question, in the order they are mentioned
+    pub focus: Vec<GraphNode>,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    /// Shortest chain of edges between the first two mentioned entities, if they are connected
+    pub path: Vec<GraphEdge>,
+}
+
+/// An LLM answer together with the graph evidence it was given
+#[derive(Debug, Clone)]
+pub struct Explanation {
+    pub answer: String,
+    pub subgraph: Subgraph,
+}
+
+impl Subgraph {
+    fn label_of<'a>(&'a self, id: &'a str) -> &'a str {
+        self.nodes.iter().find(|node| node.id == id).map(|node| node.label.as_str()).unwrap_or(id)
+    }
+
+    /// `Alice --works at--> TechCorp`, or `<-->` for bidirectional edges
+    pub fn describe_edge(&self, edge: &GraphEdge) -> String {
+        let arrow = if edge.metadata.bidirectional { "<-->" } else { "-->" };
+        let tail = if edge.metadata.bidirectional { "<--" } else { "--" };
+        format!(
+            "{} {}{}{} {}",
+            self.label_of(&edge.from),
+            tail,
+            edge.label,
+            arrow,
+            self.label_of(&edge.to)
+        )
+    }
+}
+
+/// Nodes (other than attributes) whose label appears as a whole word in the question,
+/// ordered by where they are first mentioned
+fn mentioned_nodes<'a>(graph: &'a InteractiveGraph, question: &str) -> Vec<&'a GraphNode> {
+    let mut mentions: Vec<(usize, &GraphNode)> = graph
+        .nodes
+        .iter()
+        .filter(|node| !matches!(node.node_type, NodeType::Attribute) && !node.label.trim().is_empty())
+        .filter_map(|node| {
+            let pattern = format!(r"(?i)\b{}\b", regex::escape(node.label.trim()));
+            Regex::new(&pattern).ok()?.find(question).map(|found| (found.start(), node))
+        })
+        .collect();
+    mentions.sort_by_key(|(position, _)| *position);
+    mentions.into_iter().map(|(_, node)| node).collect()
+}
+
+/// Select the nodes and edges relevant to `question`: the path between the first two
+/// entities it mentions plus the edges around every mentioned node. Questions that name
+/// no node fall back to the best-connected part of the graph.
+pub fn relevant_subgraph(graph: &InteractiveGraph, question: &str, max_edges: usize) -> Subgraph {
+    let focus = mentioned_nodes(graph, question);
+
+    // Group focus nodes by label so duplicate nodes for one entity count as one mention
+    let mut labels: Vec<String> = Vec::new();
+    for node in &focus {
+        let label = node.label.to_lowercase();
+        if !labels.contains(&label) {
+            labels.push(label);
+        }
+    }
+    let ids_for = |label: &str| -> HashSet<&str> {
+        focus.iter().filter(|n| n.label.to_lowercase() == label).map(|n| n.id.as_str()).collect()
+    };
+
+    let path: Vec<usize> = match labels.as_slice() {
+        [first, second, ..] => {
+            let (from, to): (Vec<&str>, Vec<&str>) = (ids_for(first).into_iter().collect(), ids_for(second).into_iter().collect());
+            graph.shortest_paths(&from, &to, 1).pop().map(|path| path.edges).unwrap_or_default()
+        }
+        _ => Vec::new(),
+    };
+
+    let focus_ids: HashSet<&str> = focus.iter().map(|n| n.id.as_str()).collect();
+    let mut selected: Vec<usize> = path.clone();
+    let mut neighbourhood: Vec<usize> = (0..graph.edges.len())
+        .filter(|index| !path.contains(index))
+        .filter(|&index| {
+            let edge = &graph.edges[index];
+            focus_ids.is_empty() || focus_ids.contains(edge.from.as_str()) || focus_ids.contains(edge.to.as_str())
+        })
+        .collect();
+    if focus_ids.is_empty() {
+        neighbourhood.sort_by(|a, b| graph.edges[*b].metadata.confidence.total_cmp(&graph.edges[*a].metadata.confidence));
+    }
+    selected.extend(neighbourhood.into_iter().take(max_edges.saturating_sub(path.len())));
+
+    let edges: Vec<GraphEdge> = selected.iter().map(|&index| graph.edges[index].clone()).collect();
+    let node_ids: HashSet<&str> = edges
+        .iter()
+        .flat_map(|edge| [edge.from.as_str(), edge.to.as_str()])
+        .chain(focus_ids.iter().copied())
+        .collect();
+
+    Subgraph {
+        focus: focus.into_iter().cloned().collect(),
+        nodes: graph.nodes.iter().filter(|n| node_ids.contains(n.id.as_str())).cloned().collect(),
+        path: path.iter().map(|&index| graph.edges[index].clone()).collect(),
+        edges,
+    }
+}
+
+/// Prompt asking the LLM to answer `question` from the subgraph only
+pub fn explain_prompt(question: &str, subgraph: &Subgraph) -> String {
+    let mut prompt = String::from(
+        "You are answering a question about an entity relationship graph extracted from a text. \
+        Use only the facts listed below. If they do not answer the question, say so. \
+        Answer in a few sentences and mention the relationships you relied on.\n\nEntities:\n",
+    );
+    for node in &subgraph.nodes {
+        let kind = node.metadata.entity_type.as_deref().unwrap_or(node.node_type.metadata_key());
+        prompt.push_str(&format!("- {} ({})\n", node.label, kind));
+    }
+    prompt.push_str("\nRelationships:\n");
+    for edge in &subgraph.edges {
+        prompt.push_str(&format!("- {}\n", subgraph.describe_edge(edge)));
+    }
+    if !subgraph.path.is_empty() {
+        prompt.push_str("\nConnecting path:\n");
+        for edge in &subgraph.path {
+            prompt.push_str(&format!("- {}\n", subgraph.describe_edge(edge)));
+        }
+    }
+    prompt.push_str(&format!("\nQuestion: {}\nAnswer:", question));
+    prompt
+}
+
+/// Answer a natural-language question about `graph` with the configured LLM
+pub async fn explain(graph: &InteractiveGraph, question: &str, config: &ExtractionConfig) -> Result<Explanation> {
+    let subgraph = relevant_subgraph(graph, question, MAX_CONTEXT_EDGES);
+    let extractor = EntityExtractor::new(config.clone())?;
+    let answer = extractor.call_ollama(&explain_prompt(question, &subgraph)).await?;
+    Ok(Explanation {
+        answer: answer.trim().to_string(),
+        subgraph,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::Pipeline;
+
+    async fn sample_graph() -> InteractiveGraph {
+        Pipeline::new()
+            .with_text("Alice works at TechCorp. Bob manages the database system. Alice is connected to Bob.")
+            .run()
+            .await
+            .expect("Pipeline failed")
+    }
+
+    #[tokio::test]
+    async fn test_subgraph_follows_path_between_mentioned_entities() {
+        let graph = sample_graph().await;
+        let subgraph = relevant_subgraph(&graph, "How is alice connected to Bob?", MAX_CONTEXT_EDGES);
+
+        let focus: Vec<&str> = subgraph.focus.iter().map(|n| n.label.as_str()).collect();
+        assert_eq!(focus.first(), Some(&"Alice"));
+        assert!(focus.contains(&"Bob"));
+        assert!(!subgraph.path.is_empty());
+        assert!(subgraph.path.iter().all(|edge| subgraph.edges.iter().any(|e| e.id == edge.id)));
+
+        let prompt = explain_prompt("How is alice connected to Bob?", &subgraph);
+        assert!(prompt.contains("Connecting path:"));
+        assert!(prompt.ends_with("Answer:"));
+    }
+
+    #[tokio::test]
+    async fn test_unmatched_question_falls_back_to_capped_graph() {
+        let graph = sample_graph().await;
+        let subgraph = relevant_subgraph(&graph, "What is this about?", 2);
+        assert!(subgraph.focus.is_empty());
+        assert!(subgraph.path.is_empty());
+        assert!(subgraph.edges.len() <= 2);
+    }
+}
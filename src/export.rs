@@ -1,9 +1,34 @@
-use crate::graph_builder::InteractiveGraph;
-use crate::web_interface::WebInterface;
+use crate::entity_extractor::entity_type_name;
+use crate::graph_builder::{
+    parallel_edge_bends, parse_hex_color, GraphEdge, GraphNode, GraphStats, InteractiveGraph, NodeType,
+};
+use crate::report::{write_html_report, write_markdown_report};
+use crate::hierarchy::write_outline;
+use crate::graph_chunks::{chunk_graph, write_chunks, ChunkManifest};
+use crate::share::{is_local_viewer, share_url};
+use crate::streaming::json_lines;
+use crate::timestamps::timestamp_now;
+use crate::web_interface::{PageChunks, PageOptions, PageTheme, PageTimeline, VisNetworkSource, WebInterface};
+use crate::config::{CsvLayout, Theme};
 use crate::error::{GraphError, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
+use std::io::Write;
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tracing::warn;
+
+/// Buffer size at which [`GraphExporter::export_to_async_writer`] hands data to the sink
+pub const ASYNC_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Written first in CSV exports with `csv.bom`, so Excel reads them as UTF-8
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Node sizes are in vis.js pixels; DOT widths are in inches
+const DOT_PIXELS_PER_INCH: f64 = 40.0;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExportOptions {
@@ -12,19 +37,144 @@ pub struct ExportOptions {
     pub include_styling: bool,
     pub compact_output: bool,
     pub file_path: Option<String>,
+    /// Inline the vis-network bundle from `assets.vis_network_path` into HTML exports
+    /// so they open without internet access
+    #[serde(default)]
+    pub embed_assets: bool,
+    /// Text the graph was built from; reports quote the sentences mentioning each entity
+    #[serde(default)]
+    pub source_text: Option<String>,
+    /// Palette of HTML exports, overriding the graph's `theme` when set
+    #[serde(default)]
+    pub theme: Option<Theme>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ExportFormat {
     Html,
     Json,
     Csv,
     GraphML,
     Dot,
+    /// LaTeX/TikZ picture using the layout positions
+    Tikz,
+    /// Mermaid `graph` flowchart for Markdown renderers
+    Mermaid,
+    /// Markdown report: ranked entity table of contents and one section per entity
+    Markdown,
+    /// Standalone HTML page with the same content as [`ExportFormat::Markdown`]
+    HtmlReport,
+    /// A viewer link with the graph compressed into its fragment, see [`crate::share`]
+    ShareUrl,
+    /// Viewer page with a chunk overview, plus a `<stem>_chunks` directory of chunk files
+    /// it loads while zooming, see [`crate::graph_chunks`]. Rendered to a string or writer,
+    /// only the page is produced; it expects the chunks in `graph_chunks/`
+    Chunked,
+    /// Tab-separated `source`, `target`, `weight` lines, one per edge, for igraph,
+    /// networkx and R
+    EdgeList,
+    /// Weighted adjacency matrix as CSV, with node labels heading the rows and columns
+    AdjacencyMatrix,
+    /// Indented outline of the hierarchy, see [`crate::hierarchy::write_outline`]
+    Outline,
+    /// One JSON object per line: a `graph` header, the nodes, the edges and a `metadata`
+    /// footer, see [`crate::streaming::GraphLine`]
+    JsonLines,
+    /// Viewer page showing only the timeline of the dates mentioned with the entities,
+    /// see [`crate::timeline`]
+    Timeline,
+}
+
+impl ExportFormat {
+    /// File extension (without the dot) used for this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Html => "html",
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+            ExportFormat::GraphML => "graphml",
+            ExportFormat::Dot => "dot",
+            ExportFormat::Tikz => "tex",
+            ExportFormat::Mermaid => "mmd",
+            ExportFormat::Markdown => "md",
+            ExportFormat::HtmlReport => "html",
+            ExportFormat::ShareUrl => "txt",
+            ExportFormat::Chunked => "html",
+            ExportFormat::EdgeList => "tsv",
+            ExportFormat::AdjacencyMatrix => "csv",
+            ExportFormat::Outline => "txt",
+            ExportFormat::JsonLines => "jsonl",
+            ExportFormat::Timeline => "html",
+        }
+    }
+
+    /// Human-readable name recorded in export metadata
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ExportFormat::Html => "HTML",
+            ExportFormat::Json => "JSON",
+            ExportFormat::Csv => "CSV",
+            ExportFormat::GraphML => "GraphML",
+            ExportFormat::Dot => "DOT",
+            ExportFormat::Tikz => "TikZ",
+            ExportFormat::Mermaid => "Mermaid",
+            ExportFormat::Markdown => "Markdown",
+            ExportFormat::HtmlReport => "HTML report",
+            ExportFormat::ShareUrl => "Share URL",
+            ExportFormat::Chunked => "Chunked HTML",
+            ExportFormat::EdgeList => "Edge list",
+            ExportFormat::AdjacencyMatrix => "Adjacency matrix",
+            ExportFormat::Outline => "Outline",
+            ExportFormat::JsonLines => "JSON Lines",
+            ExportFormat::Timeline => "Timeline",
+        }
+    }
+
+    /// Markdown and HTML reports, which quote `ExportOptions::source_text`
+    pub fn is_report(&self) -> bool {
+        matches!(self, ExportFormat::Markdown | ExportFormat::HtmlReport)
+    }
+}
+
+/// Row format for appending to shared corpus tables
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AppendFormat {
+    Csv,
+    Jsonl,
+}
+
+impl AppendFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AppendFormat::Csv => "csv",
+            AppendFormat::Jsonl => "jsonl",
+        }
+    }
+}
+
+/// Where and how [`GraphExporter::append_graph`] writes rows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppendOptions {
+    /// Directory holding the shared `nodes.*` and `edges.*` files
+    pub directory: String,
+    pub format: AppendFormat,
+    /// Value of the `run_id` column on every row written by this call
+    pub run_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppendResult {
+    pub nodes_path: String,
+    pub edges_path: String,
+    pub nodes_written: usize,
+    pub edges_written: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExportResult {
+    /// Graph schema version of the exported content, see [`crate::schema::GRAPH_SCHEMA_VERSION`]
+    #[serde(default = "crate::schema::legacy_schema_version")]
+    pub schema_version: u32,
     pub success: bool,
     pub file_path: Option<String>,
     pub content: Option<String>,
@@ -39,8 +189,24 @@ pub struct ExportMetadata {
     pub original_graph_edges: usize,
     pub exported_format: String,
     pub file_size_bytes: Option<usize>,
+    /// Structure of the exported graph
+    #[serde(default)]
+    pub graph_stats: GraphStats,
+}
+
+/// One piece of a streamed CSV/GraphML/DOT export
+enum Record<'a> {
+    Header,
+    Node(&'a GraphNode),
+    EdgesStart,
+    Edge(&'a GraphEdge),
+    Footer,
 }
 
+/// Most formats [`GraphExporter::export_graphs`] renders and writes at once; rendering
+/// holds the whole document in memory, so this also bounds peak memory use
+pub const MAX_PARALLEL_EXPORTS: usize = 4;
+
 pub struct GraphExporter {
     web_interface: WebInterface,
 }
@@ -53,7 +219,7 @@ impl GraphExporter {
     }
 
     /// Create serialized filename in the 0_networks directory
-    fn create_output_path(&self, requested_path: &str) -> Result<String> {
+    pub fn create_output_path(&self, requested_path: &str) -> Result<String> {
         let path = Path::new(requested_path);
         let filename = path.file_name()
             .ok_or_else(|| GraphError::Export("Invalid filename".to_string()))?;
@@ -88,470 +254,1133 @@ impl GraphExporter {
     }
 
     pub fn export_graph(&self, graph: &InteractiveGraph, options: &ExportOptions) -> Result<ExportResult> {
-        match options.format {
-            ExportFormat::Html => self.export_to_html(graph, options),
-            ExportFormat::Json => self.export_to_json(graph, options),
-            ExportFormat::Csv => self.export_to_csv(graph, options),
-            ExportFormat::GraphML => self.export_to_graphml(graph, options),
-            ExportFormat::Dot => self.export_to_dot(graph, options),
-        }
-    }
+        let timestamp = timestamp_now(&graph.config.timestamps)?;
 
-    fn export_to_html(&self, graph: &InteractiveGraph, options: &ExportOptions) -> Result<ExportResult> {
-        let timestamp = chrono::Utc::now().to_rfc3339();
-        
-        // Create output path with serialization
-        let output_path = if let Some(path) = &options.file_path {
-            self.create_output_path(path)?
+        // Create output path with serialization; CSV tables are named after the nodes file
+        let tables = options.format == ExportFormat::Csv && graph.config.csv.layout == CsvLayout::Tables;
+        let requested = options.file_path.clone().unwrap_or_else(|| format!("graph.{}", options.format.extension()));
+        let output_path = if tables {
+            self.create_output_path(&Self::csv_table_path(&requested, "nodes"))?
         } else {
-            self.create_output_path("graph.html")?
+            self.create_output_path(&requested)?
         };
-        
-        // Create the HTML content with embedded vis.js
-        let title = "Entity Relationship Graph";
-        let html_template = self.web_interface.create_html_template(title);
-        
-        // Embed the graph data directly in the HTML
-        let nodes_json = serde_json::to_string(&graph.nodes)?;
-        let edges_json = serde_json::to_string(&graph.edges)?;
-        let config_json = serde_json::to_string(&graph.config)?;
-        
-        let embedded_script = format!(r#"
-        <script>
-            // Graph data embedded directly in HTML
-            window.graphData = {{
-                nodes: {},
-                edges: {},
-                config: {}
-            }};
-            
-            // Initialize the graph when page loads
-            window.addEventListener('load', function() {{
-                initializeGraph();
-            }});
-            
-            function initializeGraph() {{
-                // Sync physics enabled state with config
-                physicsEnabled = window.graphData.config.physics.enabled;
-                
-                const container = document.getElementById('{}');
-                const nodes = new vis.DataSet(window.graphData.nodes.map(node => ({{
-                    id: node.id,
-                    label: node.label,
-                    originalLabel: node.label, // Store original label for toggle functionality
-                    color: node.color,
-                    shape: node.shape,
-                    size: node.size,
-                    x: node.x,
-                    y: node.y,
-                    physics: node.physics,
-                    title: `Type: ${{node.node_type}}<br/>Confidence: ${{node.metadata.confidence.toFixed(2)}}`,
-                    group: node.node_type.toLowerCase(),
-                    node_type: node.node_type,
-                    confidence: node.metadata.confidence
-                }})));
-                
-                const edges = new vis.DataSet(window.graphData.edges.map(edge => ({{
-                    id: edge.id,
-                    from: edge.from,
-                    to: edge.to,
-                    label: edge.label,
-                    originalLabel: edge.label, // Store original label for toggle functionality
-                    color: edge.color,
-                    width: edge.width,
-                    arrows: edge.arrows,
-                    title: `Type: ${{edge.metadata.relationship_type}}<br/>Confidence: ${{edge.metadata.confidence.toFixed(2)}}`,
-                    smooth: {{ type: "continuous" }},
-                    relationship_type: edge.metadata.relationship_type
-                }})));
-                
-                // Store original data globally for filtering and label toggling
-                originalNodes = nodes.get();
-                originalEdges = edges.get();
-                
-                const data = {{ nodes: nodes, edges: edges }};
-                
-                const options = {{
-                    nodes: {{
-                        shape: 'dot',
-                        size: 25,
-                        font: {{
-                            size: 14,
-                            color: '#343434',
-                            face: 'arial'
-                        }},
-                        borderWidth: 2,
-                        shadow: true
-                    }},
-                    edges: {{
-                        width: 2,
-                        arrows: {{
-                            to: {{
-                                enabled: true,
-                                scaleFactor: 1
-                            }}
-                        }},
-                        smooth: true,
-                        shadow: true
-                    }},
-                    physics: {{
-                        enabled: window.graphData.config.physics.enabled,
-                        stabilization: {{
-                            enabled: window.graphData.config.physics.stabilization,
-                            iterations: 1000
-                        }},
-                        repulsion: {{
-                            nodeDistance: window.graphData.config.physics.repulsion,
-                            centralGravity: 0.1,
-                            springLength: window.graphData.config.physics.spring_length,
-                            springConstant: window.graphData.config.physics.spring_constant
-                        }}
-                    }},
-                    interaction: {{
-                        dragNodes: true,
-                        dragView: true,
-                        zoomView: true,
-                        selectConnectedEdges: true,
-                        hover: true
-                    }}
-                }};
-                
-                // Assign to the global variable (not window.currentNetwork)
-                currentNetwork = new vis.Network(container, data, options);
-                
-                // Set up event listeners
-                currentNetwork.on('selectNode', function(params) {{
-                    onNodeSelected(params.nodes[0]);
-                }});
-                
-                currentNetwork.on('selectEdge', function(params) {{
-                    onEdgeSelected(params.edges[0]);
-                }});
-                
-                // Initialize toggle button states
-                updateToggleButton('physicsToggle', physicsEnabled, 'Physics: ON', 'Physics: OFF');
-                updateToggleButton('nodeLabelsToggle', showNodeLabels, 'Node Labels: ON', 'Node Labels: OFF');
-                updateToggleButton('edgeLabelsToggle', showEdgeLabels, 'Edge Labels: ON', 'Edge Labels: OFF');
-                updateToggleButton('uniqueNodesToggle', uniqueNodesEnabled, 'Unique Nodes: ON', 'Unique Nodes: OFF');
-                
-                console.log('Graph initialized successfully');
-            }}
-        </script>
-        "#, nodes_json, edges_json, config_json, self.web_interface.get_container_id());
-        
-        // Insert the script before the closing body tag
-        let final_html = html_template.replace("</body>", &format!("{}\n</body>", embedded_script));
-        
+
+        let write_error = |e: std::io::Error| {
+            GraphError::Export(format!(
+                "Failed to write {} file: {}",
+                options.format.display_name(),
+                e
+            ))
+        };
+
+        // Compact exports don't hand the content back, so stream straight to disk
+        // instead of building the whole document in memory first
+        let (content, file_size) = if tables {
+            let edges_path = Self::csv_edges_path(&output_path);
+            let nodes = fs::File::create(&output_path).map_err(write_error)?;
+            let edges = fs::File::create(&edges_path).map_err(write_error)?;
+            self.export_csv_tables(graph, std::io::BufWriter::new(nodes), std::io::BufWriter::new(edges))?;
+            let size = fs::metadata(&output_path).map_err(write_error)?.len() + fs::metadata(&edges_path).map_err(write_error)?.len();
+            (None, size as usize)
+        } else if options.format == ExportFormat::Chunked {
+            let content = self.write_chunked(graph, options, &output_path)?;
+            fs::write(&output_path, &content).map_err(write_error)?;
+            let size = content.len();
+            (Some(content), size)
+        } else if options.compact_output {
+            let file = fs::File::create(&output_path).map_err(write_error)?;
+            self.export_to_writer(graph, options, std::io::BufWriter::new(file))?;
+            let size = fs::metadata(&output_path).map_err(write_error)?.len() as usize;
+            (None, size)
+        } else {
+            let content = self.export_to_string_with_options(graph, options)?;
+            fs::write(&output_path, &content).map_err(write_error)?;
+            let size = content.len();
+            (Some(content), size)
+        };
+        if options.format == ExportFormat::ShareUrl {
+            self.write_share_viewer(graph, &output_path)?;
+        }
+
         let metadata = ExportMetadata {
             export_timestamp: timestamp,
             original_graph_nodes: graph.nodes.len(),
             original_graph_edges: graph.edges.len(),
-            exported_format: "HTML".to_string(),
-            file_size_bytes: Some(final_html.len()),
+            exported_format: options.format.display_name().to_string(),
+            file_size_bytes: Some(file_size),
+            graph_stats: graph.stats(),
         };
-        
-        // Write to file
-        fs::write(&output_path, &final_html)
-            .map_err(|e| GraphError::Export(format!("Failed to write HTML file: {}", e)))?;
-        
+
         Ok(ExportResult {
+            schema_version: graph.schema_version,
             success: true,
             file_path: Some(output_path),
-            content: if options.compact_output { None } else { Some(final_html) },
+            content,
             error_message: None,
             metadata,
         })
     }
 
-    fn export_to_json(&self, graph: &InteractiveGraph, options: &ExportOptions) -> Result<ExportResult> {
-        let timestamp = chrono::Utc::now().to_rfc3339();
-        
-        // Create output path with serialization
-        let output_path = if let Some(path) = &options.file_path {
-            self.create_output_path(path)?
+    /// `graph.csv` -> `graph_nodes.csv`
+    fn csv_table_path(requested: &str, table: &str) -> String {
+        let path = Path::new(requested);
+        let stem = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_else(|| "graph".to_string());
+        let extension = path.extension().map(|extension| extension.to_string_lossy().to_string()).unwrap_or_else(|| "csv".to_string());
+        path.with_file_name(format!("{}_{}.{}", stem, table, extension)).to_string_lossy().to_string()
+    }
+
+    /// The edges table next to a nodes table: `graph_nodes_01.csv` -> `graph_edges_01.csv`
+    pub fn csv_edges_path(nodes_path: &str) -> String {
+        let path = Path::new(nodes_path);
+        let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        let name = match name.rfind("_nodes") {
+            Some(at) => format!("{}_edges{}", &name[..at], &name[at + "_nodes".len()..]),
+            None => format!("edges_{}", name),
+        };
+        path.with_file_name(name).to_string_lossy().to_string()
+    }
+
+    /// Write the nodes and edges as two CSV tables with the headers Gephi's spreadsheet
+    /// import and Neo4j's `LOAD CSV` expect: `Id,Label,...` and `Source,Target,Type,Weight,...`.
+    /// `Type` is `Directed` or `Undirected`, and `Source` is the node the edge points away from.
+    pub fn export_csv_tables<N: Write, E: Write>(&self, graph: &InteractiveGraph, mut nodes: N, mut edges: E) -> Result<()> {
+        if graph.config.csv.bom {
+            nodes.write_all(UTF8_BOM)?;
+            edges.write_all(UTF8_BOM)?;
+        }
+        writeln!(nodes, "Id,Label,Type,EntityType,Color,Shape,Size,Confidence")?;
+        for node in &graph.nodes {
+            let entity_type = node.metadata.entity_type.as_deref().map(entity_type_name).unwrap_or_default();
+            writeln!(
+                nodes,
+                "{},{},{:?},{},{},{},{},{}",
+                Self::csv_field(&node.id),
+                Self::csv_field(&node.label),
+                node.node_type,
+                Self::csv_field(entity_type),
+                Self::csv_field(&node.color),
+                Self::csv_field(&node.shape),
+                node.size,
+                node.metadata.confidence
+            )?;
+        }
+
+        writeln!(edges, "Source,Target,Type,Weight,Id,Label,Relationship,Color,Confidence")?;
+        for edge in &graph.edges {
+            let (source, target, direction) = match edge.arrow_ends() {
+                (true, false) => (&edge.from, &edge.to, "Directed"),
+                (false, true) => (&edge.to, &edge.from, "Directed"),
+                _ => (&edge.from, &edge.to, "Undirected"),
+            };
+            writeln!(
+                edges,
+                "{},{},{},{},{},{},{:?},{},{}",
+                Self::csv_field(source),
+                Self::csv_field(target),
+                direction,
+                edge.metadata.weight,
+                Self::csv_field(&edge.id),
+                Self::csv_field(&edge.label),
+                edge.edge_type,
+                Self::csv_field(&edge.color),
+                edge.metadata.confidence
+            )?;
+        }
+        nodes.flush()?;
+        edges.flush()?;
+        Ok(())
+    }
+
+    /// Share links to a relative viewer path need the data-less viewer page next to them;
+    /// a page already there is kept
+    fn write_share_viewer(&self, graph: &InteractiveGraph, link_path: &str) -> Result<()> {
+        let viewer_url = &graph.config.share.viewer_url;
+        if !is_local_viewer(viewer_url) {
+            return Ok(());
+        }
+        let path = Path::new(link_path).parent().unwrap_or(Path::new(".")).join(viewer_url);
+        if path.exists() {
+            return Ok(());
+        }
+        let options = PageOptions {
+            vis_network: VisNetworkSource::Url(graph.config.assets.vis_network_url.clone()),
+            theme: Self::page_theme(&graph.config.theme)?,
+            ..PageOptions::default()
+        };
+        let page = self.web_interface.render_page(None, &options)?;
+        fs::write(&path, page)
+            .map_err(|e| GraphError::Export(format!("Failed to write share viewer {}: {}", path.display(), e)))
+    }
+
+    /// Write the chunk directory next to `page_path` and render the page that loads it
+    fn write_chunked(&self, graph: &InteractiveGraph, options: &ExportOptions, page_path: &str) -> Result<String> {
+        let page_path = Path::new(page_path);
+        let stem = page_path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+        let directory_name = format!("{}_chunks", stem);
+        let (manifest, chunks) = chunk_graph(graph, &graph.config.chunks);
+        write_chunks(&manifest, &chunks, &page_path.parent().unwrap_or(Path::new(".")).join(&directory_name))?;
+        self.render_chunked(graph, options, &manifest, &format!("{}/", directory_name))
+    }
+
+    fn render_chunked(
+        &self,
+        graph: &InteractiveGraph,
+        options: &ExportOptions,
+        manifest: &ChunkManifest,
+        chunk_base: &str,
+    ) -> Result<String> {
+        let page_options = PageOptions {
+            generated_at: Some(graph.metadata.creation_timestamp.clone()),
+            vis_network: Self::vis_network_source(graph, options)?,
+            theme: Self::page_theme(options.theme.as_ref().unwrap_or(&graph.config.theme))?,
+            chunks: Some(PageChunks {
+                manifest: serde_json::to_string(manifest)?,
+                base: chunk_base.to_string(),
+            }),
+            ..PageOptions::default()
+        };
+        self.web_interface.render_page(None, &page_options)
+    }
+
+    /// Export the graph once per entry of `options`, rendering and writing up to
+    /// [`MAX_PARALLEL_EXPORTS`] formats at a time. The graph is only read, so formats with
+    /// expensive rendering (HTML, GraphML) overlap instead of queueing behind each other.
+    /// Results are in the order of `options`; one failing format does not stop the others.
+    pub fn export_graphs(&self, graph: &InteractiveGraph, options: &[ExportOptions]) -> Vec<Result<ExportResult>> {
+        let workers = std::thread::available_parallelism()
+            .map_or(1, |cores| cores.get())
+            .min(MAX_PARALLEL_EXPORTS)
+            .min(options.len());
+        if workers <= 1 {
+            return options.iter().map(|options| self.export_graph(graph, options)).collect();
+        }
+
+        let next = AtomicUsize::new(0);
+        let results: Mutex<Vec<Option<Result<ExportResult>>>> =
+            Mutex::new(options.iter().map(|_| None).collect());
+        std::thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(options) = options.get(index) else {
+                        break;
+                    };
+                    let result = self.export_graph(graph, options);
+                    results.lock().unwrap_or_else(|poisoned| poisoned.into_inner())[index] = Some(result);
+                });
+            }
+        });
+        results
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .into_iter()
+            .map(|result| result.unwrap_or_else(|| Err(GraphError::Export("Export worker stopped early".to_string()))))
+            .collect()
+    }
+
+    /// Render the graph in the given format without touching the filesystem,
+    /// using the default export options for everything but the format.
+    pub fn export_to_string(&self, graph: &InteractiveGraph, format: &ExportFormat) -> Result<String> {
+        let options = ExportOptions {
+            format: format.clone(),
+            ..ExportOptions::default()
+        };
+        self.export_to_string_with_options(graph, &options)
+    }
+
+    /// Render the graph as `options.format` without touching the filesystem.
+    /// `options.file_path` is ignored.
+    pub fn export_to_string_with_options(&self, graph: &InteractiveGraph, options: &ExportOptions) -> Result<String> {
+        match options.format {
+            ExportFormat::Html | ExportFormat::Timeline => self.render_html(graph, options),
+            ExportFormat::Json => self.render_json(graph, options),
+            ExportFormat::Chunked => {
+                let (manifest, _) = chunk_graph(graph, &graph.config.chunks);
+                self.render_chunked(graph, options, &manifest, "graph_chunks/")
+            }
+            ExportFormat::Csv
+            | ExportFormat::GraphML
+            | ExportFormat::Dot
+            | ExportFormat::Tikz
+            | ExportFormat::Mermaid
+            | ExportFormat::Markdown
+            | ExportFormat::HtmlReport
+            | ExportFormat::ShareUrl
+            | ExportFormat::EdgeList
+            | ExportFormat::AdjacencyMatrix
+            | ExportFormat::Outline
+            | ExportFormat::JsonLines => {
+                String::from_utf8(self.export_to_bytes(graph, options)?)
+                    .map_err(|e| GraphError::Export(format!("Export produced invalid UTF-8: {}", e)))
+            }
+        }
+    }
+
+    /// Render the graph as `options.format` into a byte buffer without touching the
+    /// filesystem, e.g. for an HTTP response body. `options.file_path` is ignored, and a
+    /// chunked page expects its chunks in `graph_chunks/`.
+    pub fn export_to_bytes(&self, graph: &InteractiveGraph, options: &ExportOptions) -> Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        self.export_to_writer(graph, options, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Write the graph as `options.format` into any `io::Write` sink.
+    ///
+    /// CSV, GraphML, DOT and JSON Lines are streamed record by record, so very large graphs never
+    /// need to be held in memory as a single string. Wrap file or socket sinks in a
+    /// `BufWriter`; this method does not buffer on its own. `options.file_path` is ignored.
+    pub fn export_to_writer<W: Write>(&self, graph: &InteractiveGraph, options: &ExportOptions, mut writer: W) -> Result<()> {
+        match options.format {
+            ExportFormat::Html | ExportFormat::Timeline => writer.write_all(self.render_html(graph, options)?.as_bytes())?,
+            ExportFormat::Json => {
+                if options.include_metadata {
+                    serde_json::to_writer_pretty(&mut writer, graph)?;
+                } else {
+                    let simplified = Self::simplified_json(graph);
+                    if options.compact_output {
+                        serde_json::to_writer(&mut writer, &simplified)?;
+                    } else {
+                        serde_json::to_writer_pretty(&mut writer, &simplified)?;
+                    }
+                }
+            }
+            ExportFormat::Csv | ExportFormat::GraphML | ExportFormat::Dot => {
+                if options.format == ExportFormat::Csv && graph.config.csv.bom {
+                    writer.write_all(UTF8_BOM)?;
+                }
+                for record in Self::records(graph) {
+                    Self::write_record(&options.format, &record, &mut writer)?;
+                }
+            }
+            ExportFormat::Tikz => Self::write_tikz(graph, &mut writer)?,
+            ExportFormat::Mermaid => Self::write_mermaid(graph, &mut writer)?,
+            ExportFormat::EdgeList => Self::write_edge_list(graph, &mut writer)?,
+            ExportFormat::AdjacencyMatrix => Self::write_adjacency_matrix(graph, &mut writer)?,
+            ExportFormat::Outline => write_outline(graph, &mut writer)?,
+            ExportFormat::JsonLines => {
+                for line in json_lines(graph) {
+                    line.write_to(&mut writer)?;
+                }
+            }
+            ExportFormat::Markdown => write_markdown_report(graph, options.source_text.as_deref(), &mut writer)?,
+            ExportFormat::HtmlReport => write_html_report(graph, options.source_text.as_deref(), &mut writer)?,
+            ExportFormat::ShareUrl => writeln!(writer, "{}", share_url(graph)?)?,
+            ExportFormat::Chunked => writer.write_all(self.export_to_string_with_options(graph, options)?.as_bytes())?,
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Async counterpart of [`export_to_writer`](Self::export_to_writer) for
+    /// `tokio::io::AsyncWrite` sinks.
+    ///
+    /// Streamed formats are rendered into a small buffer that is flushed to the sink
+    /// whenever it grows past [`ASYNC_CHUNK_SIZE`], so memory use stays bounded
+    /// regardless of graph size. HTML and JSON are rendered in full and then written.
+    pub async fn export_to_async_writer<W>(&self, graph: &InteractiveGraph, options: &ExportOptions, writer: &mut W) -> Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        match options.format {
+            ExportFormat::Html
+            | ExportFormat::Timeline
+            | ExportFormat::Json
+            | ExportFormat::Tikz
+            | ExportFormat::Mermaid
+            | ExportFormat::Markdown
+            | ExportFormat::HtmlReport
+            | ExportFormat::ShareUrl
+            | ExportFormat::Chunked
+            | ExportFormat::EdgeList
+            | ExportFormat::AdjacencyMatrix
+            | ExportFormat::Outline => {
+                let content = self.export_to_string_with_options(graph, options)?;
+                writer.write_all(content.as_bytes()).await?;
+            }
+            ExportFormat::Csv | ExportFormat::GraphML | ExportFormat::Dot => {
+                let mut buffer = Vec::with_capacity(ASYNC_CHUNK_SIZE);
+                if options.format == ExportFormat::Csv && graph.config.csv.bom {
+                    buffer.extend_from_slice(UTF8_BOM);
+                }
+                for record in Self::records(graph) {
+                    Self::write_record(&options.format, &record, &mut buffer)?;
+                    if buffer.len() >= ASYNC_CHUNK_SIZE {
+                        writer.write_all(&buffer).await?;
+                        buffer.clear();
+                    }
+                }
+                writer.write_all(&buffer).await?;
+            }
+            ExportFormat::JsonLines => {
+                let mut buffer = Vec::with_capacity(ASYNC_CHUNK_SIZE);
+                for line in json_lines(graph) {
+                    line.write_to(&mut buffer)?;
+                    if buffer.len() >= ASYNC_CHUNK_SIZE {
+                        writer.write_all(&buffer).await?;
+                        buffer.clear();
+                    }
+                }
+                writer.write_all(&buffer).await?;
+            }
+        }
+        writer.flush().await?;
+        Ok(())
+    }
+
+    /// Where the exported page loads vis-network from: the configured URL, or the local
+    /// bundle inlined when `options.embed_assets` is set
+    fn vis_network_source(graph: &InteractiveGraph, options: &ExportOptions) -> Result<VisNetworkSource> {
+        let assets = &graph.config.assets;
+        if !options.embed_assets {
+            return Ok(VisNetworkSource::Url(assets.vis_network_url.clone()));
+        }
+        let path = assets.vis_network_path.as_deref().ok_or_else(|| {
+            GraphError::Configuration(
+                "Offline HTML export needs assets.vis_network_path pointing at a local vis-network.min.js".to_string(),
+            )
+        })?;
+        let bundle = fs::read_to_string(path)
+            .map_err(|e| GraphError::Export(format!("Failed to read vis-network bundle {}: {}", path, e)))?;
+        Ok(VisNetworkSource::Inline(bundle))
+    }
+
+    /// The timeline of an HTML export: always for [`ExportFormat::Timeline`], beside the
+    /// network when some entity has dates. Offline pages need `assets.vis_timeline_path`;
+    /// without it a network page leaves the timeline out.
+    fn page_timeline(graph: &InteractiveGraph, options: &ExportOptions) -> Result<Option<PageTimeline>> {
+        let only = options.format == ExportFormat::Timeline;
+        if !only && graph.nodes.iter().all(|node| node.metadata.dates.is_empty()) {
+            return Ok(None);
+        }
+        let assets = &graph.config.assets;
+        let bundle = if !options.embed_assets {
+            VisNetworkSource::Url(assets.vis_timeline_url.clone())
+        } else if let Some(path) = assets.vis_timeline_path.as_deref() {
+            let bundle = fs::read_to_string(path)
+                .map_err(|e| GraphError::Export(format!("Failed to read vis-timeline bundle {}: {}", path, e)))?;
+            VisNetworkSource::Inline(bundle)
+        } else if only {
+            return Err(GraphError::Configuration(
+                "Offline timeline export needs assets.vis_timeline_path pointing at a local vis-timeline-graph2d.min.js"
+                    .to_string(),
+            ));
         } else {
-            self.create_output_path("graph.json")?
+            warn!("🕒 Leaving the timeline out of the offline page: assets.vis_timeline_path is not set");
+            return Ok(None);
         };
-        
+        Ok(Some(PageTimeline { bundle, only }))
+    }
+
+    /// The page palette of `theme`, reading a custom theme's stylesheet
+    fn page_theme(theme: &Theme) -> Result<PageTheme> {
+        let css = match theme {
+            Theme::Custom(path) => Some(
+                fs::read_to_string(path)
+                    .map_err(|e| GraphError::Export(format!("Failed to read theme stylesheet {}: {}", path, e)))?,
+            ),
+            _ => None,
+        };
+        Ok(PageTheme {
+            palette: theme.palette(),
+            css,
+        })
+    }
+
+    fn render_html(&self, graph: &InteractiveGraph, options: &ExportOptions) -> Result<String> {
+        let (title, vis_network) = if options.format == ExportFormat::Timeline {
+            // The timeline page never draws the network
+            ("Entity Timeline".to_string(), VisNetworkSource::Inline(String::new()))
+        } else {
+            (PageOptions::default().title, Self::vis_network_source(graph, options)?)
+        };
+        let page_options = PageOptions {
+            title,
+            generated_at: Some(graph.metadata.creation_timestamp.clone()),
+            vis_network,
+            theme: Self::page_theme(options.theme.as_ref().unwrap_or(&graph.config.theme))?,
+            timeline: Self::page_timeline(graph, options)?,
+            chunks: None,
+        };
+        // Embed the graph data directly in the HTML
+        self.web_interface.render_page(Some(graph), &page_options)
+    }
+
+    fn render_json(&self, graph: &InteractiveGraph, options: &ExportOptions) -> Result<String> {
         let json_data = if options.include_metadata {
             serde_json::to_string_pretty(graph)?
         } else {
             // Export only nodes and edges
-            let simplified = serde_json::json!({
-                "nodes": graph.nodes,
-                "edges": graph.edges
-            });
+            let simplified = Self::simplified_json(graph);
             if options.compact_output {
                 serde_json::to_string(&simplified)?
             } else {
                 serde_json::to_string_pretty(&simplified)?
             }
         };
-        
-        let metadata = ExportMetadata {
-            export_timestamp: timestamp,
-            original_graph_nodes: graph.nodes.len(),
-            original_graph_edges: graph.edges.len(),
-            exported_format: "JSON".to_string(),
-            file_size_bytes: Some(json_data.len()),
-        };
-        
-        fs::write(&output_path, &json_data)
-            .map_err(|e| GraphError::Export(format!("Failed to write JSON file: {}", e)))?;
-        
-        Ok(ExportResult {
-            success: true,
-            file_path: Some(output_path),
-            content: if options.compact_output { None } else { Some(json_data) },
-            error_message: None,
-            metadata,
+
+        Ok(json_data)
+    }
+
+    fn simplified_json(graph: &InteractiveGraph) -> serde_json::Value {
+        serde_json::json!({
+            "nodes": graph.nodes,
+            "edges": graph.edges
         })
     }
 
-    fn export_to_csv(&self, graph: &InteractiveGraph, options: &ExportOptions) -> Result<ExportResult> {
-        let timestamp = chrono::Utc::now().to_rfc3339();
-        
-        // Create output path with serialization
-        let output_path = if let Some(path) = &options.file_path {
-            self.create_output_path(path)?
-        } else {
-            self.create_output_path("graph.csv")?
-        };
-        
-        // Create separate CSV sections for nodes and edges
-        let mut csv_content = String::new();
-        
-        // Nodes section
-        csv_content.push_str("# NODES\n");
-        csv_content.push_str("id,label,type,color,shape,size,confidence\n");
-        
-        for node in &graph.nodes {
-            csv_content.push_str(&format!(
-                "{},{},{:?},{},{},{},{}\n",
-                node.id,
-                node.label.replace(',', ";"), // Escape commas
+    /// The sequence of records a streamed export is made of, in output order
+    fn records(graph: &InteractiveGraph) -> impl Iterator<Item = Record<'_>> {
+        std::iter::once(Record::Header)
+            .chain(graph.nodes.iter().map(Record::Node))
+            .chain(std::iter::once(Record::EdgesStart))
+            .chain(graph.edges.iter().map(Record::Edge))
+            .chain(std::iter::once(Record::Footer))
+    }
+
+    fn write_record<W: Write>(format: &ExportFormat, record: &Record<'_>, writer: &mut W) -> std::io::Result<()> {
+        match format {
+            ExportFormat::Csv => Self::write_csv_record(record, writer),
+            ExportFormat::GraphML => Self::write_graphml_record(record, writer),
+            ExportFormat::Dot => Self::write_dot_record(record, writer),
+            ExportFormat::Html
+            | ExportFormat::Timeline
+            | ExportFormat::Json
+            | ExportFormat::Tikz
+            | ExportFormat::Mermaid
+            | ExportFormat::Markdown
+            | ExportFormat::HtmlReport
+            | ExportFormat::ShareUrl
+            | ExportFormat::Chunked
+            | ExportFormat::EdgeList
+            | ExportFormat::AdjacencyMatrix
+            | ExportFormat::Outline
+            | ExportFormat::JsonLines => Ok(()),
+        }
+    }
+
+    fn write_csv_record<W: Write>(record: &Record<'_>, w: &mut W) -> std::io::Result<()> {
+        match record {
+            Record::Header => {
+                // Create separate CSV sections for nodes and edges
+                w.write_all(b"# NODES\n")?;
+                w.write_all(b"id,label,type,color,shape,size,confidence\n")
+            }
+            Record::Node(node) => writeln!(
+                w,
+                "{},{},{:?},{},{},{},{}",
+                Self::csv_field(&node.id),
+                Self::csv_field(&node.label),
                 node.node_type,
-                node.color,
-                node.shape,
+                Self::csv_field(&node.color),
+                Self::csv_field(&node.shape),
                 node.size,
                 node.metadata.confidence
-            ));
-        }
-        
-        // Edges section
-        csv_content.push_str("\n# EDGES\n");
-        csv_content.push_str("id,from,to,label,type,color,width,confidence\n");
-        
-        for edge in &graph.edges {
-            csv_content.push_str(&format!(
-                "{},{},{},{},{},{},{},{}\n",
-                edge.id,
-                edge.from,
-                edge.to,
-                edge.label.replace(',', ";"), // Escape commas
-                format!("{:?}", edge.edge_type),
-                edge.color,
+            ),
+            Record::EdgesStart => {
+                w.write_all(b"\n# EDGES\n")?;
+                w.write_all(b"id,from,to,label,type,color,width,confidence\n")
+            }
+            Record::Edge(edge) => writeln!(
+                w,
+                "{},{},{},{},{:?},{},{},{}",
+                Self::csv_field(&edge.id),
+                Self::csv_field(&edge.from),
+                Self::csv_field(&edge.to),
+                Self::csv_field(&edge.label),
+                edge.edge_type,
+                Self::csv_field(&edge.color),
                 edge.width,
                 edge.metadata.confidence
-            ));
+            ),
+            Record::Footer => Ok(()),
         }
-        
-        let metadata = ExportMetadata {
-            export_timestamp: timestamp,
-            original_graph_nodes: graph.nodes.len(),
-            original_graph_edges: graph.edges.len(),
-            exported_format: "CSV".to_string(),
-            file_size_bytes: Some(csv_content.len()),
-        };
-        
-        fs::write(&output_path, &csv_content)
-            .map_err(|e| GraphError::Export(format!("Failed to write CSV file: {}", e)))?;
-        
-        Ok(ExportResult {
-            success: true,
-            file_path: Some(output_path),
-            content: if options.compact_output { None } else { Some(csv_content) },
-            error_message: None,
-            metadata,
-        })
     }
 
-    fn export_to_graphml(&self, graph: &InteractiveGraph, options: &ExportOptions) -> Result<ExportResult> {
-        let timestamp = chrono::Utc::now().to_rfc3339();
-        
-        // Create output path with serialization
-        let output_path = if let Some(path) = &options.file_path {
-            self.create_output_path(path)?
-        } else {
-            self.create_output_path("graph.graphml")?
-        };
-        
-        let mut graphml_content = String::new();
-        
-        // GraphML header
-        graphml_content.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>
+    fn write_graphml_record<W: Write>(record: &Record<'_>, w: &mut W) -> std::io::Result<()> {
+        match record {
+            Record::Header => {
+                // GraphML header
+                w.write_all(br#"<?xml version="1.0" encoding="UTF-8"?>
 <graphml xmlns="http://graphml.graphdrawing.org/xmlns"
          xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"
          xsi:schemaLocation="http://graphml.graphdrawing.org/xmlns 
          http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd">
 
-"#);
-        
-        // Define attributes
-        graphml_content.push_str(r#"  <key id="d0" for="node" attr.name="label" attr.type="string"/>
+"#)?;
+                // Define attributes
+                w.write_all(br#"  <key id="d0" for="node" attr.name="label" attr.type="string"/>
   <key id="d1" for="node" attr.name="type" attr.type="string"/>
   <key id="d2" for="node" attr.name="confidence" attr.type="double"/>
   <key id="d3" for="edge" attr.name="label" attr.type="string"/>
   <key id="d4" for="edge" attr.name="type" attr.type="string"/>
   <key id="d5" for="edge" attr.name="confidence" attr.type="double"/>
+  <key id="d6" for="node" attr.name="color" attr.type="string"/>
+  <key id="d7" for="node" attr.name="size" attr.type="double"/>
+  <key id="d8" for="node" attr.name="url" attr.type="string"/>
+  <key id="d9" for="node" attr.name="links" attr.type="string"/>
+  <key id="d10" for="edge" attr.name="color" attr.type="string"/>
+  <key id="d11" for="edge" attr.name="width" attr.type="double"/>
+  <key id="d12" for="edge" attr.name="arrows" attr.type="string"/>
+  <key id="d13" for="edge" attr.name="dashes" attr.type="string"/>
 
-"#);
-        
-        // Graph element
-        graphml_content.push_str("  <graph id=\"G\" edgedefault=\"directed\">\n");
-        
-        // Nodes
-        for node in &graph.nodes {
-            graphml_content.push_str(&format!(
-                "    <node id=\"{}\">\n",
-                Self::escape_xml(&node.id)
-            ));
-            graphml_content.push_str(&format!(
-                "      <data key=\"d0\">{}</data>\n",
-                Self::escape_xml(&node.label)
-            ));
-            graphml_content.push_str(&format!(
-                "      <data key=\"d1\">{:?}</data>\n",
-                node.node_type
-            ));
-            graphml_content.push_str(&format!(
-                "      <data key=\"d2\">{}</data>\n",
-                node.metadata.confidence
-            ));
-            graphml_content.push_str("    </node>\n");
-        }
-        
-        // Edges
-        for edge in &graph.edges {
-            graphml_content.push_str(&format!(
-                "    <edge id=\"{}\" source=\"{}\" target=\"{}\">\n",
-                Self::escape_xml(&edge.id),
-                Self::escape_xml(&edge.from),
-                Self::escape_xml(&edge.to)
-            ));
-            graphml_content.push_str(&format!(
-                "      <data key=\"d3\">{}</data>\n",
-                Self::escape_xml(&edge.label)
-            ));
-            graphml_content.push_str(&format!(
-                "      <data key=\"d4\">{:?}</data>\n",
-                edge.edge_type
-            ));
-            graphml_content.push_str(&format!(
-                "      <data key=\"d5\">{}</data>\n",
-                edge.metadata.confidence
-            ));
-            graphml_content.push_str("    </edge>\n");
+"#)?;
+                // Graph element
+                w.write_all(b"  <graph id=\"G\" edgedefault=\"directed\">\n")
+            }
+            Record::Node(node) => {
+                writeln!(w, "    <node id=\"{}\">", Self::escape_xml(&node.id))?;
+                writeln!(w, "      <data key=\"d0\">{}</data>", Self::escape_xml(&node.label))?;
+                writeln!(w, "      <data key=\"d1\">{:?}</data>", node.node_type)?;
+                writeln!(w, "      <data key=\"d2\">{}</data>", node.metadata.confidence)?;
+                writeln!(w, "      <data key=\"d6\">{}</data>", Self::escape_xml(&node.color))?;
+                writeln!(w, "      <data key=\"d7\">{}</data>", node.size)?;
+                // `url` is the first link, which yEd and Gephi open from the node; `links`
+                // lists them all, space-separated
+                if let Some(first) = node.metadata.links.first() {
+                    writeln!(w, "      <data key=\"d8\">{}</data>", Self::escape_xml(&first.url))?;
+                    let all: Vec<&str> = node.metadata.links.iter().map(|link| link.url.as_str()).collect();
+                    writeln!(w, "      <data key=\"d9\">{}</data>", Self::escape_xml(&all.join(" ")))?;
+                }
+                w.write_all(b"    </node>\n")
+            }
+            Record::EdgesStart => Ok(()),
+            Record::Edge(edge) => {
+                writeln!(
+                    w,
+                    "    <edge id=\"{}\" source=\"{}\" target=\"{}\"{}>",
+                    Self::escape_xml(&edge.id),
+                    Self::escape_xml(&edge.from),
+                    Self::escape_xml(&edge.to),
+                    if edge.metadata.bidirectional { " directed=\"false\"" } else { "" }
+                )?;
+                writeln!(w, "      <data key=\"d3\">{}</data>", Self::escape_xml(&edge.label))?;
+                writeln!(w, "      <data key=\"d4\">{:?}</data>", edge.edge_type)?;
+                writeln!(w, "      <data key=\"d5\">{}</data>", edge.metadata.confidence)?;
+                writeln!(w, "      <data key=\"d10\">{}</data>", Self::escape_xml(&edge.color))?;
+                writeln!(w, "      <data key=\"d11\">{}</data>", edge.width)?;
+                writeln!(w, "      <data key=\"d12\">{}</data>", Self::escape_xml(&edge.arrows))?;
+                // Dash and gap lengths, space-separated; solid edges have none
+                if edge.dashes && !edge.dash_pattern.is_empty() {
+                    let pattern: Vec<String> = edge.dash_pattern.iter().map(|length| length.to_string()).collect();
+                    writeln!(w, "      <data key=\"d13\">{}</data>", pattern.join(" "))?;
+                }
+                w.write_all(b"    </edge>\n")
+            }
+            Record::Footer => {
+                // Close graph and graphml
+                w.write_all(b"  </graph>\n")?;
+                w.write_all(b"</graphml>\n")
+            }
         }
-        
-        // Close graph and graphml
-        graphml_content.push_str("  </graph>\n");
-        graphml_content.push_str("</graphml>\n");
-        
-        let metadata = ExportMetadata {
-            export_timestamp: timestamp,
-            original_graph_nodes: graph.nodes.len(),
-            original_graph_edges: graph.edges.len(),
-            exported_format: "GraphML".to_string(),
-            file_size_bytes: Some(graphml_content.len()),
-        };
-        
-        fs::write(&output_path, &graphml_content)
-            .map_err(|e| GraphError::Export(format!("Failed to write GraphML file: {}", e)))?;
-        
-        Ok(ExportResult {
-            success: true,
-            file_path: Some(output_path),
-            content: if options.compact_output { None } else { Some(graphml_content) },
-            error_message: None,
-            metadata,
-        })
     }
 
-    fn export_to_dot(&self, graph: &InteractiveGraph, options: &ExportOptions) -> Result<ExportResult> {
-        let timestamp = chrono::Utc::now().to_rfc3339();
-        
-        // Create output path with serialization
-        let output_path = if let Some(path) = &options.file_path {
-            self.create_output_path(path)?
-        } else {
-            self.create_output_path("graph.dot")?
-        };
-        
-        let mut dot_content = String::new();
-        
-        // DOT header
-        dot_content.push_str("digraph EntityRelationshipGraph {\n");
-        dot_content.push_str("  rankdir=TB;\n");
-        dot_content.push_str("  node [shape=ellipse, style=filled];\n");
-        dot_content.push_str("  edge [fontsize=10];\n\n");
-        
-        // Nodes
-        for node in &graph.nodes {
-            let shape = match node.node_type {
-                crate::graph_builder::NodeType::Entity => "ellipse",
-                crate::graph_builder::NodeType::Concept => "circle",
-                crate::graph_builder::NodeType::Attribute => "box",
-                crate::graph_builder::NodeType::Relationship => "diamond",
-            };
-            
-            dot_content.push_str(&format!(
-                "  \"{}\" [label=\"{}\", shape={}, fillcolor=\"{}\", tooltip=\"Confidence: {:.2}\"];\n",
-                Self::escape_dot(&node.id),
-                Self::escape_dot(&node.label),
-                shape,
-                node.color,
-                node.metadata.confidence
-            ));
-        }
-        
-        dot_content.push_str("\n");
-        
-        // Edges
-        for edge in &graph.edges {
-            dot_content.push_str(&format!(
-                "  \"{}\" -> \"{}\" [label=\"{}\", color=\"{}\", penwidth={}, tooltip=\"Confidence: {:.2}\"];\n",
+    fn write_dot_record<W: Write>(record: &Record<'_>, w: &mut W) -> std::io::Result<()> {
+        match record {
+            Record::Header => {
+                // DOT header
+                w.write_all(b"digraph EntityRelationshipGraph {\n")?;
+                w.write_all(b"  rankdir=TB;\n")?;
+                w.write_all(b"  node [shape=ellipse, style=filled];\n")?;
+                w.write_all(b"  edge [fontsize=10];\n")?;
+                // Route parallel edges apart when the nodes are pinned with `pos`
+                w.write_all(b"  splines=true;\n\n")
+            }
+            Record::Node(node) => {
+                let shape = match node.node_type {
+                    crate::graph_builder::NodeType::Entity => "ellipse",
+                    crate::graph_builder::NodeType::Concept => "circle",
+                    crate::graph_builder::NodeType::Attribute => "box",
+                    crate::graph_builder::NodeType::Relationship => "diamond",
+                };
+
+                writeln!(
+                    w,
+                    "  \"{}\" [label=\"{}\", shape={}, fillcolor=\"{}\", width={:.2}, tooltip=\"Confidence: {:.2}\"{}];",
+                    Self::escape_dot(&node.id),
+                    Self::escape_dot(&match &node.badge {
+                        Some(badge) => format!("{} {}", node.label, badge),
+                        None => node.label.clone(),
+                    }),
+                    shape,
+                    node.color,
+                    node.size / DOT_PIXELS_PER_INCH,
+                    node.metadata.confidence,
+                    // Pinned layout position in points (y up), used by `neato -n` and `fdp`
+                    match (node.x, node.y) {
+                        (Some(x), Some(y)) => format!(
+                            ", pos=\"{:.2},{:.2}!\"",
+                            x / DOT_PIXELS_PER_INCH * 72.0,
+                            -y / DOT_PIXELS_PER_INCH * 72.0 + 0.0
+                        ),
+                        _ => String::new(),
+                    }
+                )
+            }
+            Record::EdgesStart => w.write_all(b"\n"),
+            Record::Edge(edge) => writeln!(
+                w,
+                "  \"{}\" -> \"{}\" [label=\"{}\", color=\"{}\", penwidth={}, tooltip=\"{}\"{}{}];",
                 Self::escape_dot(&edge.from),
                 Self::escape_dot(&edge.to),
                 Self::escape_dot(&edge.label),
-                edge.color,
+                Self::dot_color(&edge.color, edge.opacity),
                 edge.width,
-                edge.metadata.confidence
-            ));
+                Self::escape_dot(&Self::edge_tooltip(edge)),
+                // Symmetric relations are drawn without arrowheads
+                match edge.arrow_ends() {
+                    (true, false) => "",
+                    (false, false) => ", dir=none",
+                    (false, true) => ", dir=back",
+                    (true, true) => ", dir=both",
+                },
+                match (edge.dashes, edge.dash_pattern.first()) {
+                    (false, _) => "",
+                    // Graphviz only has two patterns; short dashes read as dots
+                    (true, Some(dash)) if *dash <= 2.0 => ", style=dotted",
+                    (true, _) => ", style=dashed",
+                }
+            ),
+            Record::Footer => w.write_all(b"}\n"),
         }
-        
-        dot_content.push_str("}\n");
-        
-        let metadata = ExportMetadata {
-            export_timestamp: timestamp,
-            original_graph_nodes: graph.nodes.len(),
-            original_graph_edges: graph.edges.len(),
-            exported_format: "DOT".to_string(),
-            file_size_bytes: Some(dot_content.len()),
+    }
+
+    /// Confidence of an edge, or of each relationship folded into a merged edge
+    fn edge_tooltip(edge: &GraphEdge) -> String {
+        if edge.metadata.merged.is_empty() {
+            return format!("Confidence: {:.2}", edge.metadata.confidence);
+        }
+        edge.metadata
+            .merged
+            .iter()
+            .map(|relation| format!("{} ({:.2})", relation.label, relation.confidence))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    /// A hex color with the edge opacity as alpha (`#RRGGBBAA`); other colors are kept
+    fn dot_color(color: &str, opacity: Option<f64>) -> String {
+        match (parse_hex_color(color), opacity) {
+            (Some((r, g, b)), Some(opacity)) => {
+                format!("#{:02X}{:02X}{:02X}{:02X}", r, g, b, (opacity.clamp(0.0, 1.0) * 255.0).round() as u8)
+            }
+            _ => color.to_string(),
+        }
+    }
+
+    /// Write a TikZ picture placing every node at its layout position (y flipped, scaled
+    /// by `tikz.scale`). Nodes without a position are put on a circle instead.
+    fn write_tikz<W: Write>(graph: &InteractiveGraph, w: &mut W) -> std::io::Result<()> {
+        let tikz = &graph.config.tikz;
+        let names: HashMap<&str, String> =
+            graph.nodes.iter().enumerate().map(|(i, node)| (node.id.as_str(), format!("n{}", i))).collect();
+
+        // One named color per distinct hex color; anything else is passed through as an
+        // xcolor name
+        let mut colors: Vec<String> = Vec::new();
+        let mut color_name = |color: &str| -> String {
+            if parse_hex_color(color).is_none() {
+                return color.trim().to_string();
+            }
+            let index = colors.iter().position(|c| *c == color).unwrap_or_else(|| {
+                colors.push(color.to_string());
+                colors.len() - 1
+            });
+            format!("msgnet{}", index)
         };
-        
-        fs::write(&output_path, &dot_content)
-            .map_err(|e| GraphError::Export(format!("Failed to write DOT file: {}", e)))?;
-        
-        Ok(ExportResult {
-            success: true,
-            file_path: Some(output_path),
-            content: if options.compact_output { None } else { Some(dot_content) },
-            error_message: None,
-            metadata,
+        let node_colors: Vec<String> = graph.nodes.iter().map(|node| color_name(&node.color)).collect();
+        let edge_colors: Vec<String> = graph.edges.iter().map(|edge| color_name(&edge.color)).collect();
+
+        writeln!(w, "% Entity relationship graph exported by msg_net ({} nodes, {} edges)", graph.nodes.len(), graph.edges.len())?;
+        if tikz.standalone {
+            w.write_all(b"\\documentclass[tikz,border=10pt]{standalone}\n")?;
+            w.write_all(b"\\usetikzlibrary{shapes.geometric,arrows.meta}\n")?;
+            w.write_all(b"\\begin{document}\n")?;
+        }
+        w.write_all(b"\\begin{tikzpicture}[>=Stealth, every node/.style={font=\\small}]\n")?;
+        for (index, color) in colors.iter().enumerate() {
+            writeln!(w, "  \\definecolor{{msgnet{}}}{{HTML}}{{{}}}", index, color.trim_start_matches('#').to_uppercase())?;
+        }
+
+        let count = graph.nodes.len().max(1) as f64;
+        for (index, node) in graph.nodes.iter().enumerate() {
+            let (x, y) = match (node.x, node.y) {
+                (Some(x), Some(y)) => (x, y),
+                _ => {
+                    let angle = index as f64 * 2.0 * std::f64::consts::PI / count;
+                    (300.0 * angle.cos(), 300.0 * angle.sin())
+                }
+            };
+            let style = match node.node_type {
+                NodeType::Entity => &tikz.node_styles.entity,
+                NodeType::Relationship => &tikz.node_styles.relationship,
+                NodeType::Concept => &tikz.node_styles.concept,
+                NodeType::Attribute => &tikz.node_styles.attribute,
+            };
+            let label = match &node.badge {
+                Some(badge) => format!("{} {}", node.label, badge),
+                None => node.label.clone(),
+            };
+            writeln!(
+                w,
+                "  \\node[{}, fill={}!40] ({}) at ({:.2}, {:.2}) {{{}}};",
+                style,
+                node_colors[index],
+                names[node.id.as_str()],
+                x * tikz.scale,
+                // TikZ's y axis points up; `+ 0.0` avoids printing "-0.00"
+                -y * tikz.scale + 0.0,
+                Self::escape_latex(&label)
+            )?;
+        }
+
+        let bends = parallel_edge_bends(&graph.edges);
+        for (index, edge) in graph.edges.iter().enumerate() {
+            let (Some(from), Some(to)) = (names.get(edge.from.as_str()), names.get(edge.to.as_str())) else {
+                continue;
+            };
+            let mut options = vec![
+                match edge.arrow_ends() {
+                    (true, false) => "->",
+                    (false, false) => "-",
+                    (false, true) => "<-",
+                    (true, true) => "<->",
+                }
+                .to_string(),
+                format!("draw={}", edge_colors[index]),
+                format!("line width={:.1}pt", edge.width * 0.4),
+            ];
+            if edge.dashes {
+                match edge.dash_pattern.as_slice() {
+                    [] => options.push("dashed".to_string()),
+                    pattern => options.push(format!(
+                        "dash pattern={}",
+                        pattern
+                            .iter()
+                            .enumerate()
+                            .map(|(i, length)| format!("{} {:.1}pt", if i % 2 == 0 { "on" } else { "off" }, length * 0.4))
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    )),
+                }
+            }
+            if let Some(opacity) = edge.opacity {
+                options.push(format!("draw opacity={:.2}", opacity));
+            }
+            let path = match bends[index] {
+                _ if from == to => "to[loop above]".to_string(),
+                0.0 => "--".to_string(),
+                // Parallel edges bend apart, up to 24 degrees
+                bend if bend > 0.0 => format!("to[bend right={:.0}]", bend * 60.0),
+                bend => format!("to[bend left={:.0}]", -bend * 60.0),
+            };
+            writeln!(
+                w,
+                "  \\draw[{}] ({}) {} node[midway, sloped, above, font=\\scriptsize] {{{}}} ({});",
+                options.join(", "),
+                from,
+                path,
+                Self::escape_latex(&edge.label),
+                to
+            )?;
+        }
+
+        w.write_all(b"\\end{tikzpicture}\n")?;
+        if tikz.standalone {
+            w.write_all(b"\\end{document}\n")?;
+        }
+        Ok(())
+    }
+
+    /// Write a Mermaid flowchart. Node shapes follow the node type and every distinct
+    /// fill color becomes a `classDef`; with `mermaid.max_nodes` only the most connected
+    /// nodes (and the edges between them) are kept.
+    fn write_mermaid<W: Write>(graph: &InteractiveGraph, w: &mut W) -> std::io::Result<()> {
+        let mermaid = &graph.config.mermaid;
+        let mut degree: HashMap<&str, usize> = HashMap::new();
+        for edge in &graph.edges {
+            *degree.entry(edge.from.as_str()).or_default() += 1;
+            *degree.entry(edge.to.as_str()).or_default() += 1;
+        }
+        let mut kept: Vec<usize> = (0..graph.nodes.len()).collect();
+        if let Some(max_nodes) = mermaid.max_nodes.filter(|max| *max < graph.nodes.len()) {
+            kept.sort_by_key(|&index| std::cmp::Reverse(degree.get(graph.nodes[index].id.as_str()).copied().unwrap_or(0)));
+            kept.truncate(max_nodes);
+            kept.sort_unstable();
+        }
+        let names: HashMap<&str, String> =
+            kept.iter().map(|&index| (graph.nodes[index].id.as_str(), format!("n{}", index))).collect();
+        let label = |text: &str| Self::escape_mermaid(&Self::truncate_label(text, mermaid.max_label_length));
+
+        writeln!(w, "%% Entity relationship graph exported by msg_net ({} nodes, {} edges)", graph.nodes.len(), graph.edges.len())?;
+        if kept.len() < graph.nodes.len() {
+            writeln!(w, "%% Showing the {} most connected nodes", kept.len())?;
+        }
+        writeln!(w, "graph {}", mermaid.direction)?;
+
+        let mut colors: Vec<&str> = Vec::new();
+        for &index in &kept {
+            let node = &graph.nodes[index];
+            let class = colors.iter().position(|c| *c == node.color).unwrap_or_else(|| {
+                colors.push(&node.color);
+                colors.len() - 1
+            });
+            let text = match &node.badge {
+                Some(badge) => format!("{} {}", node.label, badge),
+                None => node.label.clone(),
+            };
+            let (open, close) = match node.node_type {
+                NodeType::Entity => ("([", "])"),
+                NodeType::Relationship => ("[", "]"),
+                NodeType::Concept => ("((", "))"),
+                NodeType::Attribute => ("{{", "}}"),
+            };
+            writeln!(w, "    {}{}\"{}\"{}:::c{}", names[node.id.as_str()], open, label(&text), close, class)?;
+        }
+
+        let mut link_styles = Vec::new();
+        let mut links = 0;
+        for edge in &graph.edges {
+            let (Some(from), Some(to)) = (names.get(edge.from.as_str()), names.get(edge.to.as_str())) else {
+                continue;
+            };
+            // Mermaid draws arrowheads at the end, so a `from` arrow swaps the ends
+            let (to_arrow, from_arrow) = edge.arrow_ends();
+            let (from, to) = if from_arrow && !to_arrow { (to, from) } else { (from, to) };
+            let arrow = match (edge.dashes, to_arrow || from_arrow, to_arrow && from_arrow) {
+                (false, false, _) => "---",
+                (false, true, false) => "-->",
+                (false, true, true) => "<-->",
+                (true, false, _) => "-.-",
+                (true, true, false) => "-.->",
+                (true, true, true) => "<-.->",
+            };
+            if edge.label.is_empty() {
+                writeln!(w, "    {} {} {}", from, arrow, to)?;
+            } else {
+                writeln!(w, "    {} {}|\"{}\"| {}", from, arrow, label(&edge.label), to)?;
+            }
+            if parse_hex_color(&edge.color).is_some() {
+                let opacity = edge.opacity.map(|opacity| format!(",stroke-opacity:{:.2}", opacity)).unwrap_or_default();
+                link_styles.push(format!(
+                    "    linkStyle {} stroke:{},stroke-width:{}px{}",
+                    links,
+                    edge.color,
+                    edge.width.max(1.0),
+                    opacity
+                ));
+            }
+            links += 1;
+        }
+
+        for (index, color) in colors.iter().enumerate() {
+            writeln!(w, "    classDef c{} fill:{},stroke:#333,color:#000", index, color)?;
+        }
+        for style in link_styles {
+            writeln!(w, "{}", style)?;
+        }
+        Ok(())
+    }
+
+    /// Node labels usable as vertex names: tabs and line breaks become spaces, and a
+    /// label shared by several nodes gets " (2)", " (3)", ... after the first
+    fn vertex_names(graph: &InteractiveGraph) -> HashMap<&str, String> {
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        graph
+            .nodes
+            .iter()
+            .map(|node| {
+                let label = node.label.replace(['\t', '\n', '\r'], " ");
+                let count = seen.entry(label.clone()).or_default();
+                *count += 1;
+                let name = if *count == 1 { label } else { format!("{} ({})", label, count) };
+                (node.id.as_str(), name)
+            })
+            .collect()
+    }
+
+    /// The (source, target) pairs an edge contributes: its drawn direction, or both
+    /// ways for a symmetric edge
+    fn edge_directions(edge: &GraphEdge) -> Vec<(&str, &str)> {
+        match edge.arrow_ends() {
+            (true, false) => vec![(edge.from.as_str(), edge.to.as_str())],
+            (false, true) => vec![(edge.to.as_str(), edge.from.as_str())],
+            _ => vec![(edge.from.as_str(), edge.to.as_str()), (edge.to.as_str(), edge.from.as_str())],
+        }
+    }
+
+    fn write_edge_list<W: Write>(graph: &InteractiveGraph, w: &mut W) -> std::io::Result<()> {
+        let names = Self::vertex_names(graph);
+        for edge in &graph.edges {
+            let (Some(from), Some(to)) = (names.get(edge.from.as_str()), names.get(edge.to.as_str())) else {
+                continue;
+            };
+            // A symmetric edge is listed once; readers treat the list as undirected or
+            // directed as the caller asks
+            let (from, to) = match edge.arrow_ends() {
+                (false, true) => (to, from),
+                _ => (from, to),
+            };
+            writeln!(w, "{}\t{}\t{}", from, to, edge.metadata.weight)?;
+        }
+        Ok(())
+    }
+
+    fn write_adjacency_matrix<W: Write>(graph: &InteractiveGraph, w: &mut W) -> std::io::Result<()> {
+        let names = Self::vertex_names(graph);
+        let index: HashMap<&str, usize> =
+            graph.nodes.iter().enumerate().map(|(i, node)| (node.id.as_str(), i)).collect();
+        // Summed weights by row and column; the matrix is mostly zeros, so only the
+        // edges are held in memory and each row is written out as it is filled in
+        let mut weights: HashMap<usize, HashMap<usize, f64>> = HashMap::new();
+        for edge in &graph.edges {
+            for (from, to) in Self::edge_directions(edge) {
+                if let (Some(&row), Some(&column)) = (index.get(from), index.get(to)) {
+                    *weights.entry(row).or_default().entry(column).or_default() += edge.metadata.weight;
+                }
+            }
+        }
+
+        let header: Vec<String> = graph.nodes.iter().map(|node| Self::csv_field(&names[node.id.as_str()])).collect();
+        writeln!(w, ",{}", header.join(","))?;
+        let no_edges = HashMap::new();
+        for (row, name) in header.iter().enumerate() {
+            let row_weights = weights.get(&row).unwrap_or(&no_edges);
+            write!(w, "{}", name)?;
+            for column in 0..header.len() {
+                write!(w, ",{}", row_weights.get(&column).copied().unwrap_or(0.0))?;
+            }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+
+    /// Quote a CSV field when it contains a comma, quote or line break (RFC 4180)
+    fn csv_field(text: &str) -> String {
+        if text.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", text.replace('"', "\"\""))
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Cut `text` to `max` characters, ending in "…"; `0` means no limit
+    fn truncate_label(text: &str, max: usize) -> String {
+        if max == 0 || text.chars().count() <= max {
+            return text.to_string();
+        }
+        let mut truncated: String = text.chars().take(max.saturating_sub(1)).collect();
+        truncated.push('…');
+        truncated
+    }
+
+    /// Quoted Mermaid labels cannot contain `"`; Mermaid's entity codes stand in for
+    /// characters that would otherwise end the label or start markup
+    fn escape_mermaid(text: &str) -> String {
+        text.replace('"', "#quot;")
+            .replace('<', "#lt;")
+            .replace('>', "#gt;")
+            .replace(['\n', '\r'], " ")
+    }
+
+    /// Append the graph's nodes and edges to `nodes.<ext>` and `edges.<ext>` in
+    /// `options.directory`, tagging every row with `options.run_id`.
+    ///
+    /// The files are created (with a CSV header) on first use and appended to afterwards,
+    /// so a corpus run accumulates into two tables instead of one file per document.
+    /// Unlike [`export_graph`](Self::export_graph), paths are not serialized into `0_networks`.
+    pub fn append_graph(&self, graph: &InteractiveGraph, options: &AppendOptions) -> Result<AppendResult> {
+        let directory = Path::new(&options.directory);
+        fs::create_dir_all(directory)
+            .map_err(|e| GraphError::Export(format!("Failed to create directory: {}", e)))?;
+
+        let extension = options.format.extension();
+        let nodes_path = directory.join(format!("nodes.{}", extension));
+        let edges_path = directory.join(format!("edges.{}", extension));
+
+        Self::append_rows(&nodes_path, options, "run_id,id,label,type,color,shape,size,confidence", |w| {
+            for node in &graph.nodes {
+                match options.format {
+                    AppendFormat::Csv => writeln!(
+                        w,
+                        "{},{},{},{:?},{},{},{},{}",
+                        Self::csv_field(&options.run_id),
+                        Self::csv_field(&node.id),
+                        Self::csv_field(&node.label),
+                        node.node_type,
+                        Self::csv_field(&node.color),
+                        Self::csv_field(&node.shape),
+                        node.size,
+                        node.metadata.confidence
+                    )?,
+                    AppendFormat::Jsonl => Self::write_jsonl_row(w, &options.run_id, node)?,
+                }
+            }
+            Ok(())
+        })?;
+
+        Self::append_rows(&edges_path, options, "run_id,id,from,to,label,type,color,width,confidence", |w| {
+            for edge in &graph.edges {
+                match options.format {
+                    AppendFormat::Csv => writeln!(
+                        w,
+                        "{},{},{},{},{},{:?},{},{},{}",
+                        Self::csv_field(&options.run_id),
+                        Self::csv_field(&edge.id),
+                        Self::csv_field(&edge.from),
+                        Self::csv_field(&edge.to),
+                        Self::csv_field(&edge.label),
+                        edge.edge_type,
+                        Self::csv_field(&edge.color),
+                        edge.width,
+                        edge.metadata.confidence
+                    )?,
+                    AppendFormat::Jsonl => Self::write_jsonl_row(w, &options.run_id, edge)?,
+                }
+            }
+            Ok(())
+        })?;
+
+        Ok(AppendResult {
+            nodes_path: nodes_path.to_string_lossy().to_string(),
+            edges_path: edges_path.to_string_lossy().to_string(),
+            nodes_written: graph.nodes.len(),
+            edges_written: graph.edges.len(),
         })
     }
 
+    fn append_rows<F>(path: &Path, options: &AppendOptions, csv_header: &str, write_rows: F) -> Result<()>
+    where
+        F: FnOnce(&mut std::io::BufWriter<fs::File>) -> std::io::Result<()>,
+    {
+        let append_error = |e: std::io::Error| {
+            GraphError::Export(format!("Failed to append to {}: {}", path.display(), e))
+        };
+
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(append_error)?;
+        let is_new = file.metadata().map_err(append_error)?.len() == 0;
+        let mut writer = std::io::BufWriter::new(file);
+
+        if is_new && options.format == AppendFormat::Csv {
+            writeln!(writer, "{}", csv_header).map_err(append_error)?;
+        }
+        write_rows(&mut writer).map_err(append_error)?;
+        writer.flush().map_err(append_error)
+    }
+
+    fn write_jsonl_row<W: Write, T: Serialize>(w: &mut W, run_id: &str, item: &T) -> std::io::Result<()> {
+        let mut row = serde_json::to_value(item)?;
+        if let serde_json::Value::Object(fields) = &mut row {
+            fields.insert("run_id".to_string(), serde_json::Value::String(run_id.to_string()));
+        }
+        serde_json::to_writer(&mut *w, &row)?;
+        w.write_all(b"\n")
+    }
+
     fn escape_xml(text: &str) -> String {
         text.replace('&', "&amp;")
             .replace('<', "&lt;")
@@ -560,6 +1389,24 @@ impl GraphExporter {
             .replace('\'', "&apos;")
     }
 
+    fn escape_latex(text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            match c {
+                '\\' => escaped.push_str("\\textbackslash{}"),
+                '~' => escaped.push_str("\\textasciitilde{}"),
+                '^' => escaped.push_str("\\textasciicircum{}"),
+                '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                    escaped.push('\\');
+                    escaped.push(c);
+                }
+                '\n' | '\r' | '\t' => escaped.push(' '),
+                _ => escaped.push(c),
+            }
+        }
+        escaped
+    }
+
     fn escape_dot(text: &str) -> String {
         text.replace('\\', "\\\\")
             .replace('"', "\\\"")
@@ -575,6 +1422,17 @@ impl GraphExporter {
             ExportFormat::Csv,
             ExportFormat::GraphML,
             ExportFormat::Dot,
+            ExportFormat::Tikz,
+            ExportFormat::Mermaid,
+            ExportFormat::Markdown,
+            ExportFormat::HtmlReport,
+            ExportFormat::ShareUrl,
+            ExportFormat::Chunked,
+            ExportFormat::EdgeList,
+            ExportFormat::AdjacencyMatrix,
+            ExportFormat::Outline,
+            ExportFormat::JsonLines,
+            ExportFormat::Timeline,
         ]
     }
 
@@ -592,13 +1450,7 @@ impl GraphExporter {
         }
         
         // Check file extension matches format
-        let expected_extension = match format {
-            ExportFormat::Html => "html",
-            ExportFormat::Json => "json",
-            ExportFormat::Csv => "csv",
-            ExportFormat::GraphML => "graphml",
-            ExportFormat::Dot => "dot",
-        };
+        let expected_extension = format.extension();
         
         if let Some(extension) = path.extension() {
             if extension.to_string_lossy().to_lowercase() != expected_extension {
@@ -628,6 +1480,498 @@ impl Default for ExportOptions {
             include_styling: true,
             compact_output: false,
             file_path: None,
+            embed_assets: false,
+            source_text: None,
+            theme: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity_extractor::EntityExtractor;
+    use crate::graph_builder::GraphBuilder;
+    use crate::text_processor::{SourceType, TextProcessor};
+
+    async fn sample_graph() -> InteractiveGraph {
+        let text = "Alice is connected to Bob. Bob has a \"Data, Inc\" system.";
+        let processed = TextProcessor::new()
+            .expect("Failed to create processor")
+            .process_text(text, SourceType::Document)
+            .expect("Failed to process text");
+        let extraction = EntityExtractor::default()
+            .extract_from_text(&processed)
+            .await
+            .expect("Failed to extract");
+        GraphBuilder::default()
+            .build_graph(&extraction, text)
+            .expect("Failed to build graph")
+    }
+
+    #[tokio::test]
+    async fn test_export_to_string_covers_every_format() {
+        let graph = sample_graph().await;
+        let exporter = GraphExporter::new();
+
+        for format in GraphExporter::get_supported_formats() {
+            let content = exporter
+                .export_to_string(&graph, &format)
+                .expect("Failed to render graph");
+            assert!(!content.is_empty(), "{:?} export is empty", format);
+            let options = ExportOptions { format: format.clone(), ..ExportOptions::default() };
+            let bytes = exporter.export_to_bytes(&graph, &options).expect("Failed to render graph");
+            assert_eq!(bytes, content.as_bytes(), "{:?} bytes differ from the string", format);
+            let content = match format {
+                ExportFormat::ShareUrl => crate::share::decode_share_fragment(&content).unwrap().to_string(),
+                _ => content,
+            };
+            assert!(content.contains("Alice"), "{:?} export is missing nodes", format);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_to_string_json_round_trips() {
+        let graph = sample_graph().await;
+        let content = GraphExporter::new()
+            .export_to_string(&graph, &ExportFormat::Json)
+            .expect("Failed to render JSON");
+
+        let parsed: InteractiveGraph = serde_json::from_str(&content).expect("Failed to parse JSON export");
+        assert_eq!(parsed.nodes.len(), graph.nodes.len());
+        assert_eq!(parsed.edges.len(), graph.edges.len());
+    }
+
+    #[tokio::test]
+    async fn test_writer_exports_match_string_exports() {
+        let graph = sample_graph().await;
+        let exporter = GraphExporter::new();
+
+        for format in GraphExporter::get_supported_formats() {
+            let options = ExportOptions {
+                format: format.clone(),
+                ..ExportOptions::default()
+            };
+            let expected = exporter
+                .export_to_string_with_options(&graph, &options)
+                .expect("Failed to render graph");
+
+            let mut sync_sink = Vec::new();
+            exporter
+                .export_to_writer(&graph, &options, &mut sync_sink)
+                .expect("Failed to write graph");
+            assert_eq!(String::from_utf8(sync_sink).unwrap(), expected, "{:?} writer output differs", format);
+
+            let mut async_sink = Vec::new();
+            exporter
+                .export_to_async_writer(&graph, &options, &mut async_sink)
+                .await
+                .expect("Failed to write graph asynchronously");
+            assert_eq!(String::from_utf8(async_sink).unwrap(), expected, "{:?} async output differs", format);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_html_embeds_graph_stats_for_info_panel() {
+        let graph = sample_graph().await;
+        let html = GraphExporter::new()
+            .export_to_string(&graph, &ExportFormat::Html)
+            .expect("Failed to render HTML");
+        let start = html.find("window.graphData = ").unwrap() + "window.graphData = ".len();
+        let end = start + html[start..].find(";\n").unwrap();
+        let data: serde_json::Value = serde_json::from_str(&html[start..end]).unwrap();
+        let stats: GraphStats = serde_json::from_value(data["stats"].clone()).unwrap();
+        assert_eq!(stats, graph.stats());
+        assert!(html.contains("<div id=\"graph-stats\" class=\"graph-stats\">"));
+    }
+
+    #[tokio::test]
+    async fn test_html_embeds_provenance_for_about_section() {
+        let graph = sample_graph().await;
+        let provenance = &graph.metadata.provenance;
+        assert_eq!(provenance.extraction_method, "Pattern-based");
+        assert_eq!(provenance.llm_model, None);
+        assert_eq!(provenance.input_hash.len(), 64);
+        assert_eq!(provenance.config_summary["layout.algorithm"], graph.config.layout.algorithm);
+        assert_eq!(provenance.config_summary["extraction.mode"], "entities");
+
+        let html = GraphExporter::new()
+            .export_to_string(&graph, &ExportFormat::Html)
+            .expect("Failed to render HTML");
+        let start = html.find("window.graphData = ").unwrap() + "window.graphData = ".len();
+        let end = start + html[start..].find(";\n").unwrap();
+        let data: serde_json::Value = serde_json::from_str(&html[start..end]).unwrap();
+        assert_eq!(data["metadata"]["provenance"]["input_hash"], provenance.input_hash.as_str());
+        assert_eq!(data["metadata"]["node_types"]["entity"], graph.metadata.node_types["entity"]);
+        assert!(html.contains("<h4>About this graph</h4>"));
+    }
+
+    #[tokio::test]
+    async fn test_html_embeds_clusters_for_level_of_detail() {
+        let embedded = |graph: &InteractiveGraph| {
+            let html = GraphExporter::new().export_to_string(graph, &ExportFormat::Html).expect("Failed to render HTML");
+            let start = html.find("window.graphData = ").unwrap() + "window.graphData = ".len();
+            let end = start + html[start..].find(";\n").unwrap();
+            serde_json::from_str::<serde_json::Value>(&html[start..end]).unwrap()
+        };
+        let mut graph = sample_graph().await;
+        assert!(embedded(&graph).get("lod").is_none(), "small graphs are drawn in full");
+
+        graph.config.lod.min_nodes = 1;
+        let data = embedded(&graph);
+        assert_eq!(data["lod"]["clusters"].as_array().unwrap().len(), graph.nodes.len());
+        assert_eq!(data["config"]["lod"]["min_nodes"], 1);
+
+        graph.config.lod.enabled = false;
+        assert!(embedded(&graph).get("lod").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_html_embeds_a_graph_the_viewer_can_write_back_after_edits() {
+        let graph = sample_graph().await;
+        let html = GraphExporter::new()
+            .export_to_string(&graph, &ExportFormat::Html)
+            .expect("Failed to render HTML");
+        let start = html.find("window.graphData = ").unwrap() + "window.graphData = ".len();
+        let end = start + html[start..].find(";\n").unwrap();
+        let mut data: serde_json::Value = serde_json::from_str(&html[start..end]).unwrap();
+        assert_eq!(data["schema_version"], crate::schema::GRAPH_SCHEMA_VERSION);
+
+        // What "Export edited graph JSON" writes after a rename and a deleted node
+        let removed = data["nodes"][0]["id"].as_str().unwrap().to_string();
+        let fields = data.as_object_mut().unwrap();
+        fields.remove("stats");
+        let nodes = fields["nodes"].as_array_mut().unwrap();
+        nodes.retain(|node| node["id"] != removed.as_str());
+        nodes[0]["label"] = "Renamed".into();
+        let total_nodes = nodes.len();
+        fields["edges"].as_array_mut().unwrap().retain(|edge| edge["from"] != removed.as_str() && edge["to"] != removed.as_str());
+        let total_edges = fields["edges"].as_array().unwrap().len();
+        fields["metadata"]["total_nodes"] = total_nodes.into();
+        fields["metadata"]["total_edges"] = total_edges.into();
+        fields["metadata"]["node_types"]["entity"] = total_nodes.into();
+        fields["metadata"]["edge_types"] = serde_json::json!({});
+        if total_edges > 0 {
+            fields["metadata"]["edge_types"]["relationship"] = total_edges.into();
+        }
+
+        let edited = crate::schema::graph_from_json(&data.to_string()).expect("edited graph re-imports");
+        assert_eq!(edited.nodes.len(), graph.nodes.len() - 1);
+        assert_eq!(edited.nodes[0].label, "Renamed");
+        assert_eq!(edited.metadata.provenance, graph.metadata.provenance);
+        let report = edited.validate();
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+    }
+
+    #[tokio::test]
+    async fn test_embed_assets_inlines_local_bundle() {
+        let mut graph = sample_graph().await;
+        let exporter = GraphExporter::new();
+        let options = ExportOptions { embed_assets: true, ..ExportOptions::default() };
+
+        assert!(matches!(
+            exporter.export_to_string_with_options(&graph, &options),
+            Err(GraphError::Configuration(_))
+        ));
+
+        let mut bundle = tempfile::NamedTempFile::new().expect("Failed to create temp file");
+        write!(bundle, "/* local vis-network */ var vis = {{}};").unwrap();
+        graph.config.assets.vis_network_path = Some(bundle.path().to_string_lossy().to_string());
+        let html = exporter
+            .export_to_string_with_options(&graph, &options)
+            .expect("Failed to render offline HTML");
+        assert!(html.contains("/* local vis-network */ var vis = {};"));
+        assert!(!html.contains("<script type=\"text/javascript\" src="));
+    }
+
+    #[tokio::test]
+    async fn test_timeline_joins_dated_graphs_or_replaces_the_network() {
+        let mut graph = sample_graph().await;
+        let exporter = GraphExporter::new();
+        let timeline_script = format!("src=\"{}\"", crate::web_interface::DEFAULT_VIS_TIMELINE_URL);
+        let html = exporter.export_to_string(&graph, &ExportFormat::Html).unwrap();
+        assert!(!html.contains(&timeline_script), "graphs without dates have no timeline");
+
+        graph.nodes[0].metadata.dates = vec![crate::timeline::DatedMention {
+            date: "2021-03-04".to_string(),
+            text: "March 4, 2021".to_string(),
+            sentence: 0,
+            excerpt: "On March 4, 2021 Alice joined TechCorp.".to_string(),
+        }];
+        let html = exporter.export_to_string(&graph, &ExportFormat::Html).unwrap();
+        assert!(html.contains(&timeline_script));
+        assert!(html.contains(r#"data-view="graph""#));
+
+        let page = exporter.export_to_string(&graph, &ExportFormat::Timeline).unwrap();
+        assert!(page.contains(&timeline_script));
+        assert!(page.contains(r#"data-view="timeline""#));
+        let network_script = format!("src=\"{}\"", crate::web_interface::DEFAULT_VIS_NETWORK_URL);
+        assert!(!page.contains(&network_script), "the timeline page never draws the network");
+
+        // Offline, a network page drops the timeline but a timeline page can't
+        let mut bundle = tempfile::NamedTempFile::new().expect("Failed to create temp file");
+        write!(bundle, "var vis = {{}};").unwrap();
+        graph.config.assets.vis_network_path = Some(bundle.path().to_string_lossy().to_string());
+        let offline = |format| ExportOptions { format, embed_assets: true, ..ExportOptions::default() };
+        let html = exporter.export_to_string_with_options(&graph, &offline(ExportFormat::Html)).unwrap();
+        assert!(!html.contains("window.visTimeline = "));
+        assert!(matches!(
+            exporter.export_to_string_with_options(&graph, &offline(ExportFormat::Timeline)),
+            Err(GraphError::Configuration(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_theme_comes_from_the_options_or_the_graph() {
+        let mut graph = sample_graph().await;
+        let exporter = GraphExporter::new();
+        let html = exporter.export_to_string_with_options(&graph, &ExportOptions::default()).unwrap();
+        assert!(html.contains(r#"<html lang="en" data-theme="light">"#));
+        assert!(html.contains(r#"onclick="toggleTheme()""#));
+
+        graph.config.theme = Theme::Auto;
+        let html = exporter.export_to_string_with_options(&graph, &ExportOptions::default()).unwrap();
+        assert!(html.contains(r#"data-theme="auto""#));
+        let options = ExportOptions { theme: Some(Theme::Dark), ..ExportOptions::default() };
+        assert!(exporter.export_to_string_with_options(&graph, &options).unwrap().contains(r#"data-theme="dark""#));
+
+        let mut stylesheet = tempfile::NamedTempFile::new().expect("Failed to create temp file");
+        write!(stylesheet, ":root {{ --canvas-bg: #002b36; }}").unwrap();
+        let path = stylesheet.path().to_string_lossy().to_string();
+        let options = ExportOptions { theme: Some(Theme::from(path.clone())), ..ExportOptions::default() };
+        let html = exporter.export_to_string_with_options(&graph, &options).unwrap();
+        assert!(html.contains(r#"data-theme="light""#));
+        assert!(html.contains(":root { --canvas-bg: #002b36; }"));
+
+        let options = ExportOptions { theme: Some(Theme::from("missing.css".to_string())), ..ExportOptions::default() };
+        assert!(matches!(exporter.export_to_string_with_options(&graph, &options), Err(GraphError::Export(_))));
+
+        // Themes are stored by name, custom ones by path
+        graph.config.theme = Theme::Custom(path.clone());
+        let json = serde_json::to_value(&graph.config).unwrap();
+        assert_eq!(json["theme"], path.as_str());
+        assert_eq!(serde_json::from_value::<Theme>(serde_json::json!("Dark")).unwrap(), Theme::Dark);
+    }
+
+    #[tokio::test]
+    async fn test_tikz_uses_layout_positions_and_escapes_labels() {
+        let mut graph = sample_graph().await;
+        graph.nodes[0].x = Some(100.0);
+        graph.nodes[0].y = Some(-200.0);
+        graph.nodes[0].label = "R&D_50%".to_string();
+        let content = GraphExporter::new()
+            .export_to_string(&graph, &ExportFormat::Tikz)
+            .expect("Failed to render TikZ");
+
+        assert!(content.starts_with("% Entity relationship graph"));
+        assert!(content.contains("\\documentclass[tikz,border=10pt]{standalone}"));
+        assert!(content.contains("(n0) at (2.00, 4.00) {R\\&D\\_50\\%};"));
+        assert_eq!(content.matches("\\node[").count(), graph.nodes.len());
+        assert_eq!(content.matches("\\draw[").count(), graph.edges.len());
+        assert!(content.contains("\\definecolor{msgnet0}{HTML}{"));
+        assert!(content.trim_end().ends_with("\\end{document}"));
+
+        graph.config.tikz.standalone = false;
+        let content = GraphExporter::new()
+            .export_to_string(&graph, &ExportFormat::Tikz)
+            .expect("Failed to render TikZ");
+        assert!(!content.contains("\\documentclass"));
+        assert!(content.trim_end().ends_with("\\end{tikzpicture}"));
+    }
+
+    #[tokio::test]
+    async fn test_edge_styles_reach_dot_tikz_mermaid_and_graphml() {
+        let mut graph = sample_graph().await;
+        graph.edges.truncate(1);
+        let edge = &mut graph.edges[0];
+        edge.color = "#FF0000".to_string();
+        edge.arrows = "from".to_string();
+        edge.dashes = true;
+        edge.dash_pattern = vec![2.0, 4.0];
+        edge.opacity = Some(0.5);
+        let exporter = GraphExporter::new();
+
+        let dot = exporter.export_to_string(&graph, &ExportFormat::Dot).expect("Failed to render DOT");
+        assert!(dot.contains("color=\"#FF000080\""));
+        assert!(dot.contains(", dir=back, style=dotted];"));
+
+        let tikz = exporter.export_to_string(&graph, &ExportFormat::Tikz).expect("Failed to render TikZ");
+        assert!(tikz.contains("\\draw[<-,"));
+        assert!(tikz.contains("dash pattern=on 0.8pt off 1.6pt, draw opacity=0.50]"));
+
+        let mermaid = exporter.export_to_string(&graph, &ExportFormat::Mermaid).expect("Failed to render Mermaid");
+        assert!(mermaid.contains("-.->"));
+        assert!(mermaid.contains(",stroke-opacity:0.50"));
+
+        let graphml = exporter.export_to_string(&graph, &ExportFormat::GraphML).expect("Failed to render GraphML");
+        assert!(graphml.contains("<data key=\"d10\">#FF0000</data>"));
+        assert!(graphml.contains("<data key=\"d12\">from</data>"));
+        assert!(graphml.contains("<data key=\"d13\">2 4</data>"));
+
+        graph.edges[0].arrows = String::new();
+        graph.edges[0].dashes = false;
+        let dot = exporter.export_to_string(&graph, &ExportFormat::Dot).expect("Failed to render DOT");
+        assert!(dot.contains(", dir=none];"));
+        let graphml = exporter.export_to_string(&graph, &ExportFormat::GraphML).expect("Failed to render GraphML");
+        assert!(!graphml.contains("<data key=\"d13\">"));
+    }
+
+    #[tokio::test]
+    async fn test_parallel_edges_bend_in_tikz_and_list_merged_relations_in_dot() {
+        let mut graph = sample_graph().await;
+        graph.edges.truncate(1);
+        let mut second = graph.edges[0].clone();
+        second.label = "advises".to_string();
+        graph.edges.push(second);
+        let exporter = GraphExporter::new();
+
+        let tikz = exporter.export_to_string(&graph, &ExportFormat::Tikz).expect("Failed to render TikZ");
+        assert!(tikz.contains("to[bend left=24]"));
+        assert!(tikz.contains("to[bend right=24]"));
+
+        let first_label = graph.edges[0].label.clone();
+        graph.merge_parallel_edges();
+        let dot = exporter.export_to_string(&graph, &ExportFormat::Dot).expect("Failed to render DOT");
+        assert!(dot.contains("splines=true;"));
+        assert!(dot.contains(&format!("tooltip=\"{} (", first_label)));
+        assert!(dot.contains("; advises ("));
+        assert_eq!(dot.matches(" -> ").count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_mermaid_flowchart_caps_nodes_and_truncates_labels() {
+        let mut graph = sample_graph().await;
+        graph.nodes[0].label = "A \"quoted\" label that is much too long to show".to_string();
+        let content = GraphExporter::new()
+            .export_to_string(&graph, &ExportFormat::Mermaid)
+            .expect("Failed to render Mermaid");
+
+        assert!(content.contains("\ngraph TD\n"));
+        assert!(content.contains("n0([\"A #quot;quoted#quot; label that is much too long …\"]):::c"));
+        assert_eq!(content.matches(":::c").count(), graph.nodes.len());
+        assert!(content.contains("classDef c0 fill:#"));
+        assert!(content.lines().any(|line| line.contains("|\"") && line.contains("--")));
+
+        graph.config.mermaid.max_nodes = Some(2);
+        graph.config.mermaid.direction = "LR".to_string();
+        let content = GraphExporter::new()
+            .export_to_string(&graph, &ExportFormat::Mermaid)
+            .expect("Failed to render Mermaid");
+        assert!(content.contains("graph LR"));
+        assert!(content.contains("Showing the 2 most connected nodes"));
+        assert_eq!(content.matches(":::c").count(), 2);
+        // Every edge left joins two kept nodes
+        let kept: Vec<&str> = content
+            .lines()
+            .filter(|line| line.contains(":::c"))
+            .map(|line| line.trim_start().split(['(', '[', '{']).next().unwrap())
+            .collect();
+        for line in content.lines().filter(|line| line.contains("--")) {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            assert!(kept.contains(&words[0]) && kept.contains(words.last().unwrap()), "{}", line);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_edge_list_and_adjacency_matrix_follow_edge_directions() {
+        let mut graph = sample_graph().await;
+        graph.edges[0].arrows = "from".to_string();
+        let exporter = GraphExporter::new();
+        let label = |id: &str| graph.nodes.iter().find(|node| node.id == id).unwrap().label.clone();
+        let position = |id: &str| graph.nodes.iter().position(|node| node.id == id).unwrap();
+        let reversed = &graph.edges[0];
+
+        let edge_list = exporter.export_to_string(&graph, &ExportFormat::EdgeList).expect("Failed to render edge list");
+        assert_eq!(edge_list.lines().count(), graph.edges.len());
+        let first: Vec<&str> = edge_list.lines().next().unwrap().split('\t').collect();
+        assert_eq!(first, [label(&reversed.to), label(&reversed.from), reversed.metadata.weight.to_string()]);
+
+        let matrix = exporter
+            .export_to_string(&graph, &ExportFormat::AdjacencyMatrix)
+            .expect("Failed to render adjacency matrix");
+        let rows: Vec<&str> = matrix.lines().collect();
+        assert_eq!(rows.len(), graph.nodes.len() + 1);
+        for node in graph.nodes.iter().filter(|node| node.label.contains(',')) {
+            assert!(rows[0].contains(&format!("\"{}\"", node.label)), "{} is not quoted", node.label);
+        }
+        let n = graph.nodes.len();
+        let cells: Vec<Vec<f64>> = rows[1..]
+            .iter()
+            .map(|row| {
+                let mut cells: Vec<f64> = row.rsplitn(n + 1, ',').take(n).map(|cell| cell.parse().unwrap()).collect();
+                cells.reverse();
+                cells
+            })
+            .collect();
+        assert!(cells[position(&reversed.to)][position(&reversed.from)] >= reversed.metadata.weight);
+        // One-way edges fill one cell, symmetric edges both
+        let total: f64 = cells.iter().flatten().sum();
+        let expected: f64 = graph
+            .edges
+            .iter()
+            .map(|edge| edge.metadata.weight * if edge.arrow_ends().0 != edge.arrow_ends().1 { 1.0 } else { 2.0 })
+            .sum();
+        assert!((total - expected).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_csv_quotes_fields_and_splits_into_gephi_tables() {
+        let mut graph = sample_graph().await;
+        graph.edges[0].arrows = "from".to_string();
+        graph.nodes[0].label = "Data, \"Inc\"".to_string();
+        graph.config.csv.bom = true;
+        let exporter = GraphExporter::new();
+
+        let sectioned = exporter.export_to_string(&graph, &ExportFormat::Csv).expect("Failed to render CSV");
+        assert!(sectioned.starts_with("\u{feff}# NODES\n"));
+        assert!(sectioned.contains(",\"Data, \"\"Inc\"\"\","));
+
+        let (mut nodes, mut edges) = (Vec::new(), Vec::new());
+        exporter.export_csv_tables(&graph, &mut nodes, &mut edges).expect("Failed to write CSV tables");
+        let (nodes, edges) = (String::from_utf8(nodes).unwrap(), String::from_utf8(edges).unwrap());
+        assert!(nodes.starts_with("\u{feff}Id,Label,Type,"));
+        assert_eq!(nodes.lines().count(), graph.nodes.len() + 1);
+        assert!(edges.starts_with("\u{feff}Source,Target,Type,Weight,"));
+        // An edge drawn pointing at its `from` node runs from `to`
+        let reversed = &graph.edges[0];
+        let first = edges.lines().nth(1).unwrap();
+        assert!(first.starts_with(&format!("{},{},Directed,{},", reversed.to, reversed.from, reversed.metadata.weight)));
+
+        assert_eq!(GraphExporter::csv_table_path("out/graph.csv", "nodes"), "out/graph_nodes.csv");
+        assert_eq!(GraphExporter::csv_edges_path("0_networks/graph_nodes_01.csv"), "0_networks/graph_edges_01.csv");
+    }
+
+    #[tokio::test]
+    async fn test_append_graph_accumulates_rows_with_run_ids() {
+        let graph = sample_graph().await;
+        let exporter = GraphExporter::new();
+        let temp_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+
+        for format in [AppendFormat::Csv, AppendFormat::Jsonl] {
+            for run_id in ["run-a", "run-b"] {
+                let options = AppendOptions {
+                    directory: temp_dir.path().to_string_lossy().to_string(),
+                    format,
+                    run_id: run_id.to_string(),
+                };
+                exporter.append_graph(&graph, &options).expect("Failed to append graph");
+            }
+
+            let nodes = fs::read_to_string(temp_dir.path().join(format!("nodes.{}", format.extension())))
+                .expect("Failed to read nodes file");
+            let edges = fs::read_to_string(temp_dir.path().join(format!("edges.{}", format.extension())))
+                .expect("Failed to read edges file");
+            let header_rows = if format == AppendFormat::Csv { 1 } else { 0 };
+            assert_eq!(nodes.lines().count(), header_rows + 2 * graph.nodes.len());
+            assert_eq!(edges.lines().count(), header_rows + 2 * graph.edges.len());
+            assert_eq!(nodes.matches("run-b").count(), graph.nodes.len());
+
+            if format == AppendFormat::Jsonl {
+                for line in nodes.lines() {
+                    let row: serde_json::Value = serde_json::from_str(line).expect("Invalid JSONL row");
+                    assert!(row["run_id"].is_string());
+                }
+            }
         }
     }
 }
@@ -0,0 +1,348 @@
+use crate::entity_extractor::{Concept, Entity, EntityExtractor, EntityType, Relationship};
+use crate::error::Result;
+use crate::text_processor::ProcessedText;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+/// One extraction strategy in the chain an [`EntityExtractor`] runs.
+///
+/// The chain runs in three rounds: every strategy's entities are merged first, then each
+/// strategy is asked for relationships among the merged entities, then for concepts. A
+/// strategy implements only the rounds it takes part in; the others default to nothing.
+#[async_trait]
+pub trait Extractor: Send + Sync {
+    /// Shown in logs and in the result's `extraction_method`
+    fn name(&self) -> String;
+
+    async fn entities(&self, _text: &ProcessedText) -> Result<Vec<Entity>> {
+        Ok(Vec::new())
+    }
+
+    /// Relationships among `entities`, the merged entities of the whole chain
+    async fn relationships(&self, _text: &ProcessedText, _entities: &[Entity]) -> Result<Vec<Relationship>> {
+        Ok(Vec::new())
+    }
+
+    async fn concepts(&self, _text: &ProcessedText) -> Result<Vec<Concept>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Built-in strategies that `extraction.extractors` can list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtractorKind {
+    /// `entity_patterns` (or the noun-phrase chunker), relationship patterns and rules,
+    /// concept patterns and keywords
+    Patterns,
+    /// The Ollama model; falls back to the patterns when a call fails
+    Llm,
+    /// Known names from `gazetteer_files`
+    Gazetteer,
+}
+
+/// An entry of `extraction.extractors`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractorStep {
+    pub kind: ExtractorKind,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+pub(crate) struct PatternExtractor<'a>(pub &'a EntityExtractor);
+pub(crate) struct LlmExtractor<'a>(pub &'a EntityExtractor);
+pub(crate) struct GazetteerExtractor<'a>(pub &'a EntityExtractor);
+
+#[async_trait]
+impl Extractor for PatternExtractor<'_> {
+    fn name(&self) -> String {
+        "Pattern-based".to_string()
+    }
+
+    async fn entities(&self, text: &ProcessedText) -> Result<Vec<Entity>> {
+        self.0.extract_entities_with_patterns(text)
+    }
+
+    async fn relationships(&self, text: &ProcessedText, entities: &[Entity]) -> Result<Vec<Relationship>> {
+        self.0.extract_relationships_with_patterns(text, entities)
+    }
+
+    async fn concepts(&self, text: &ProcessedText) -> Result<Vec<Concept>> {
+        self.0.extract_concepts_with_patterns(text)
+    }
+}
+
+#[async_trait]
+impl Extractor for LlmExtractor<'_> {
+    fn name(&self) -> String {
+        format!("LLM-{}", self.0.config().llm_model)
+    }
+
+    async fn entities(&self, text: &ProcessedText) -> Result<Vec<Entity>> {
+        self.0.extract_entities_with_llm(text).await
+    }
+
+    async fn relationships(&self, text: &ProcessedText, entities: &[Entity]) -> Result<Vec<Relationship>> {
+        self.0.extract_relationships_with_llm(text, entities).await
+    }
+
+    async fn concepts(&self, text: &ProcessedText) -> Result<Vec<Concept>> {
+        self.0.extract_concepts_with_llm(text).await
+    }
+}
+
+#[async_trait]
+impl Extractor for GazetteerExtractor<'_> {
+    fn name(&self) -> String {
+        "Gazetteer".to_string()
+    }
+
+    async fn entities(&self, text: &ProcessedText) -> Result<Vec<Entity>> {
+        Ok(self.0.extract_entities_with_gazetteers(text))
+    }
+}
+
+/// Add `found` to `entities`. An entity whose name is already present, ignoring case,
+/// keeps the existing id and name, takes the higher confidence, and takes the new type
+/// when the new entity is at least as confident (a gazetteer's type beats a guess).
+pub fn merge_entities(entities: &mut Vec<Entity>, found: Vec<Entity>) {
+    for entity in found {
+        match entities.iter_mut().find(|existing| existing.name.eq_ignore_ascii_case(&entity.name)) {
+            Some(existing) => {
+                if entity.confidence >= existing.confidence {
+                    existing.entity_type = entity.entity_type;
+                    existing.confidence = entity.confidence;
+                }
+            }
+            None => entities.push(entity),
+        }
+    }
+}
+
+/// How duplicates of an entity found in several chunks of a text (prompt segments) are
+/// reconciled (`extraction.chunk_merge`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkMergePolicy {
+    /// The type of the most confident finding
+    #[default]
+    MaxConfidence,
+    /// The type found in the most chunks, ties going to the more confident type
+    MajorityVote,
+}
+
+/// Merge the entities found in separate chunks of one text. Same-named findings (ignoring
+/// case) become one entity with the id and name of the first; their mentions add up,
+/// their attributes are unioned, `policy` picks the type, and the confidence is the
+/// highest among findings of that type. Remaining ties go to the earlier finding.
+pub fn reduce_chunk_entities(chunks: Vec<Vec<Entity>>, policy: ChunkMergePolicy) -> Vec<Entity> {
+    let mut groups: Vec<Vec<Entity>> = Vec::new();
+    for entity in chunks.into_iter().flatten() {
+        match groups.iter_mut().find(|group| group[0].name.eq_ignore_ascii_case(&entity.name)) {
+            Some(group) => group.push(entity),
+            None => groups.push(vec![entity]),
+        }
+    }
+    groups.into_iter().map(|findings| reduce_findings(findings, policy)).collect()
+}
+
+/// One entity from the non-empty `findings` of a name
+fn reduce_findings(findings: Vec<Entity>, policy: ChunkMergePolicy) -> Entity {
+    // Mentions and best confidence per type, in order of first finding
+    let mut votes: Vec<(&EntityType, usize, f64)> = Vec::new();
+    for finding in &findings {
+        match votes.iter_mut().find(|(entity_type, ..)| **entity_type == finding.entity_type) {
+            Some(vote) => {
+                vote.1 += finding.mentions;
+                vote.2 = vote.2.max(finding.confidence);
+            }
+            None => votes.push((&finding.entity_type, finding.mentions, finding.confidence)),
+        }
+    }
+    let rank = |&(_, mentions, confidence): &(&EntityType, usize, f64)| match policy {
+        ChunkMergePolicy::MaxConfidence => (confidence, mentions as f64),
+        ChunkMergePolicy::MajorityVote => (mentions as f64, confidence),
+    };
+    let (entity_type, confidence) = votes
+        .iter()
+        .fold(None, |best, vote| match best {
+            Some(best) if rank(best) >= rank(vote) => Some(best),
+            _ => Some(vote),
+        })
+        .map(|(entity_type, _, confidence)| ((*entity_type).clone(), *confidence))
+        .expect("findings are never empty");
+    let mentions = findings.iter().map(|finding| finding.mentions).sum();
+
+    let mut findings = findings.into_iter();
+    let mut merged = findings.next().expect("findings are never empty");
+    for finding in findings {
+        for attribute in finding.attributes {
+            let known = merged.attributes.iter().any(|existing| {
+                existing.name == attribute.name && existing.value.eq_ignore_ascii_case(&attribute.value)
+            });
+            if !known {
+                merged.attributes.push(attribute);
+            }
+        }
+    }
+    merged.entity_type = entity_type;
+    merged.confidence = confidence;
+    merged.mentions = mentions;
+    merged
+}
+
+/// Add `found` to `relationships`, skipping ones an earlier strategy already reported
+/// between the same entities with the same label
+pub fn merge_relationships(relationships: &mut Vec<Relationship>, found: Vec<Relationship>) {
+    let earlier = relationships.len();
+    for relationship in found {
+        let duplicate = relationships[..earlier].iter().any(|existing| {
+            existing.source_entity_id == relationship.source_entity_id
+                && existing.target_entity_id == relationship.target_entity_id
+                && existing.label.eq_ignore_ascii_case(&relationship.label)
+        });
+        if !duplicate {
+            relationships.push(relationship);
+        }
+    }
+}
+
+/// Add `found` to `concepts`, merging same-named ones (ignoring case) into the higher confidence
+pub fn merge_concepts(concepts: &mut Vec<Concept>, found: Vec<Concept>) {
+    for concept in found {
+        match concepts.iter_mut().find(|existing| existing.name.eq_ignore_ascii_case(&concept.name)) {
+            Some(existing) => existing.confidence = existing.confidence.max(concept.confidence),
+            None => concepts.push(concept),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ExtractionConfig;
+    use crate::entity_extractor::EntityType;
+    use crate::text_processor::{SourceType, TextProcessor};
+
+    /// Finds every "widget" as a product
+    struct WidgetExtractor;
+
+    #[async_trait]
+    impl Extractor for WidgetExtractor {
+        fn name(&self) -> String {
+            "Widgets".to_string()
+        }
+
+        async fn entities(&self, text: &ProcessedText) -> Result<Vec<Entity>> {
+            Ok(text
+                .sentences()
+                .filter(|sentence| sentence.contains("widget"))
+                .map(|_| Entity {
+                    id: "widget".to_string(),
+                    name: "widget".to_string(),
+                    entity_type: EntityType::Other("Product".to_string()),
+                    attributes: Vec::new(),
+                    confidence: 0.9,
+                    position: None,
+                    mentions: 1,
+                    disambiguation: None,
+                })
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chain_merges_custom_extractor_and_honours_enable_flags() {
+        let processed = TextProcessor::new()
+            .unwrap()
+            .process_text("Alice owns the widget. Bob is connected to Alice.", SourceType::Document)
+            .unwrap();
+
+        let mut extractor = EntityExtractor::new(ExtractionConfig::default()).unwrap();
+        extractor.add_extractor(Box::new(WidgetExtractor));
+        let result = extractor.extract_from_text(&processed).await.unwrap();
+        let names: Vec<&str> = result.entities.iter().map(|entity| entity.name.as_str()).collect();
+        assert_eq!(names, vec!["Alice", "Bob", "widget"]);
+        assert_eq!(result.metadata.extraction_method, "Pattern-based + Widgets");
+        // The patterns relate the custom strategy's entity like any other
+        assert!(result.relationships.iter().any(|r| r.target_entity_id == "widget" || r.source_entity_id == "widget"));
+
+        let config = ExtractionConfig {
+            extractors: vec![ExtractorStep { kind: ExtractorKind::Patterns, enabled: false }],
+            ..ExtractionConfig::default()
+        };
+        let mut extractor = EntityExtractor::new(config).unwrap();
+        extractor.add_extractor(Box::new(WidgetExtractor));
+        let result = extractor.extract_from_text(&processed).await.unwrap();
+        assert_eq!(result.entities.len(), 1);
+        assert!(result.relationships.is_empty());
+    }
+
+    #[test]
+    fn test_merge_keeps_first_name_and_most_confident_type() {
+        let entity = |name: &str, entity_type: EntityType, confidence: f64| Entity {
+            id: name.to_string(),
+            name: name.to_string(),
+            entity_type,
+            attributes: Vec::new(),
+            confidence,
+            position: None,
+            mentions: 1,
+            disambiguation: None,
+        };
+        let mut entities = vec![entity("Acme", EntityType::Person, 0.7)];
+        merge_entities(
+            &mut entities,
+            vec![entity("ACME", EntityType::Organization, 0.95), entity("Bob", EntityType::Person, 0.7)],
+        );
+        assert_eq!(entities.len(), 2);
+        assert_eq!((entities[0].id.as_str(), entities[0].confidence), ("Acme", 0.95));
+        assert!(matches!(entities[0].entity_type, EntityType::Organization));
+        merge_entities(&mut entities, vec![entity("acme", EntityType::Person, 0.5)]);
+        assert!(matches!(entities[0].entity_type, EntityType::Organization));
+    }
+
+    #[test]
+    fn test_chunk_reducer_votes_on_type_and_unions_attributes() {
+        let entity = |name: &str, entity_type: EntityType, confidence: f64, role: &str| Entity {
+            id: format!("{}-{}", name, confidence),
+            name: name.to_string(),
+            entity_type,
+            attributes: vec![crate::entity_extractor::Attribute {
+                id: role.to_string(),
+                name: "role".to_string(),
+                value: role.to_string(),
+                attribute_type: crate::entity_extractor::AttributeType::Role,
+                confidence,
+            }],
+            confidence,
+            position: None,
+            mentions: 1,
+            disambiguation: None,
+        };
+        let chunks = || {
+            vec![
+                vec![entity("Acme", EntityType::Organization, 0.6, "vendor"), entity("Bob", EntityType::Person, 0.8, "cto")],
+                vec![entity("ACME", EntityType::Person, 0.9, "vendor")],
+                vec![entity("acme", EntityType::Organization, 0.7, "supplier")],
+            ]
+        };
+
+        let reduced = reduce_chunk_entities(chunks(), ChunkMergePolicy::MaxConfidence);
+        assert_eq!(reduced.len(), 2);
+        let acme = &reduced[0];
+        assert_eq!((acme.id.as_str(), acme.name.as_str(), acme.mentions), ("Acme-0.6", "Acme", 3));
+        assert_eq!((acme.entity_type.clone(), acme.confidence), (EntityType::Person, 0.9));
+        let roles: Vec<&str> = acme.attributes.iter().map(|attribute| attribute.value.as_str()).collect();
+        assert_eq!(roles, ["vendor", "supplier"]);
+        assert_eq!(reduced[1].mentions, 1);
+
+        let reduced = reduce_chunk_entities(chunks(), ChunkMergePolicy::MajorityVote);
+        assert_eq!((reduced[0].entity_type.clone(), reduced[0].confidence), (EntityType::Organization, 0.7));
+        assert_eq!(reduced[0].mentions, 3);
+    }
+}
@@ -0,0 +1,150 @@
+use crate::entity_extractor::{entity_type_from_name, EntityType};
+use crate::error::{GraphError, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+
+/// Confidence given to entities found in a gazetteer
+pub const GAZETTEER_CONFIDENCE: f64 = 0.95;
+
+/// A known-entity list (`extraction.gazetteer_files`): one name per line, `#` starts a comment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GazetteerFile {
+    pub path: String,
+    /// Type given to every entity in the file ("person", "organization", "product", ...);
+    /// when unset the usual heuristics decide
+    #[serde(default)]
+    pub entity_type: Option<String>,
+    /// Match names exactly instead of ignoring case
+    #[serde(default)]
+    pub case_sensitive: bool,
+}
+
+/// A compiled gazetteer
+#[derive(Debug, Clone)]
+pub struct Gazetteer {
+    /// All names as one alternation, longest first so "Acme Corp" wins over "Acme"
+    regex: Option<Regex>,
+    /// Matched text (lowercased unless case-sensitive) to the name as listed
+    names: HashMap<String, String>,
+    entity_type: Option<EntityType>,
+    case_sensitive: bool,
+}
+
+/// A gazetteer name found in a text
+#[derive(Debug, Clone, PartialEq)]
+pub struct GazetteerMatch<'a> {
+    /// The name as written in the gazetteer, whatever the case in the text
+    pub name: &'a str,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Gazetteer {
+    /// Read and compile a gazetteer file
+    pub fn load(file: &GazetteerFile) -> Result<Self> {
+        let content = fs::read_to_string(&file.path).map_err(|e| {
+            GraphError::Configuration(format!("Cannot read gazetteer {}: {}", file.path, e))
+        })?;
+        let names = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(String::from);
+        Self::from_names(names, file.entity_type.as_deref(), file.case_sensitive)
+    }
+
+    pub fn from_names(
+        names: impl IntoIterator<Item = String>,
+        entity_type: Option<&str>,
+        case_sensitive: bool,
+    ) -> Result<Self> {
+        let key = |name: &str| if case_sensitive { name.to_string() } else { name.to_lowercase() };
+        let mut lookup = HashMap::new();
+        for name in names {
+            lookup.entry(key(&name)).or_insert(name);
+        }
+
+        let mut alternatives: Vec<&String> = lookup.values().collect();
+        alternatives.sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+        let regex = if alternatives.is_empty() {
+            None
+        } else {
+            let alternation = alternatives.iter().map(|name| regex::escape(name)).collect::<Vec<_>>().join("|");
+            let flags = if case_sensitive { "" } else { "(?i)" };
+            Some(Regex::new(&format!(r"{}\b(?:{})\b", flags, alternation))?)
+        };
+
+        Ok(Self {
+            regex,
+            names: lookup,
+            entity_type: entity_type.map(entity_type_from_name),
+            case_sensitive,
+        })
+    }
+
+    /// Type assigned to this gazetteer's entities, if the file sets one
+    pub fn entity_type(&self) -> Option<&EntityType> {
+        self.entity_type.as_ref()
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Non-overlapping occurrences of listed names in `text`, in order
+    pub fn find_iter<'a>(&'a self, text: &'a str) -> impl Iterator<Item = GazetteerMatch<'a>> + 'a {
+        self.regex.iter().flat_map(move |regex| {
+            regex.find_iter(text).filter_map(move |found| {
+                let key = if self.case_sensitive { found.as_str().to_string() } else { found.as_str().to_lowercase() };
+                self.names.get(&key).map(|name| GazetteerMatch {
+                    name: name.as_str(),
+                    start: found.start(),
+                    end: found.end(),
+                })
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_case_insensitive_matches_return_listed_name_longest_first() {
+        let gazetteer = Gazetteer::from_names(names(&["Acme", "Acme Corp", "WidgetPro"]), Some("organization"), false).unwrap();
+        let text = "We bought a widgetpro from ACME CORP, not from Acmeville.";
+        let found: Vec<_> = gazetteer.find_iter(text).map(|m| (m.name, &text[m.start..m.end])).collect();
+
+        assert_eq!(found, vec![("WidgetPro", "widgetpro"), ("Acme Corp", "ACME CORP")]);
+        assert!(matches!(gazetteer.entity_type(), Some(EntityType::Organization)));
+    }
+
+    #[test]
+    fn test_case_sensitive_and_file_loading() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("roster.txt");
+        fs::write(&path, "# Employee roster\nDana Scully\n\nfox mulder\n").unwrap();
+        let file = GazetteerFile {
+            path: path.display().to_string(),
+            entity_type: Some("person".to_string()),
+            case_sensitive: true,
+        };
+        let gazetteer = Gazetteer::load(&file).unwrap();
+
+        assert_eq!(gazetteer.len(), 2);
+        let found: Vec<_> = gazetteer.find_iter("Dana Scully met Fox Mulder and fox mulder").map(|m| m.start).collect();
+        assert_eq!(found, vec![0, 31]);
+        assert!(Gazetteer::load(&GazetteerFile { path: "missing.txt".to_string(), ..file }).is_err());
+    }
+}
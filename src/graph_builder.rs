@@ -1,8 +1,24 @@
-use crate::config::GraphConfig;
-use crate::entity_extractor::{Entity, Relationship, Concept, ExtractionResult};
-use crate::error::Result;
+use crate::config::{ColorMode, GraphConfig, NodeMetric, ParallelEdges, RenderConfig};
+use crate::cooccurrence::ExtractionMode;
+use crate::disambiguation::Disambiguation;
+use crate::entity_extractor::{entity_type_name, AttributeType, Entity, EntityType, Relationship, Concept, ExtractionMetadata, ExtractionResult};
+use crate::schema::{graph_from_json, GRAPH_SCHEMA_VERSION};
+use crate::sentence_splitter::SentenceSplitter;
+use crate::sentiment::apply_sentiment;
+use crate::timeline::{apply_timeline, DatedMention};
+use crate::error::{GraphError, Result};
+use crate::layout;
+use crate::style_rules::apply_style_rules;
+use crate::timestamps::run_timestamp;
+use crate::transforms::{apply_transforms, config_transforms, limit_graph_size};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use tracing::warn;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GraphNode {
@@ -16,6 +32,9 @@ pub struct GraphNode {
     pub y: Option<f64>,
     pub physics: bool,
     pub metadata: NodeMetadata,
+    /// Marker shown next to the label, set by styling rules
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub badge: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,9 +48,26 @@ pub struct GraphEdge {
     pub arrows: String,
     pub edge_type: EdgeType,
     pub metadata: EdgeMetadata,
+    /// Draw dashed, set by styling rules and `edge_styles`
+    #[serde(default)]
+    pub dashes: bool,
+    /// Dash and gap lengths in pixels for a dashed edge; empty for the exporter's default
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dash_pattern: Vec<f64>,
+    /// 0..=1, from `edge_styles`; `None` is opaque
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opacity: Option<f64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl GraphEdge {
+    /// Whether an arrowhead is drawn at the `to` and at the `from` end
+    pub fn arrow_ends(&self) -> (bool, bool) {
+        let arrows = self.arrows.to_lowercase();
+        (arrows.contains("to"), arrows.contains("from"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum NodeType {
     Entity,
     Concept,
@@ -39,7 +75,19 @@ pub enum NodeType {
     Relationship,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl NodeType {
+    /// Key used for this type in `GraphMetadata::node_types`
+    pub fn metadata_key(&self) -> &'static str {
+        match self {
+            NodeType::Entity => "entity",
+            NodeType::Concept => "concept",
+            NodeType::Attribute => "attribute",
+            NodeType::Relationship => "relationship",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum EdgeType {
     EntityRelationship,
     EntityAttribute,
@@ -48,13 +96,93 @@ pub enum EdgeType {
     Hierarchy,
 }
 
+impl EdgeType {
+    /// Key used for this type in `GraphMetadata::edge_types`
+    pub fn metadata_key(&self) -> &'static str {
+        match self {
+            EdgeType::EntityRelationship => "relationship",
+            EdgeType::EntityAttribute => "entity_attribute",
+            EdgeType::ConceptEntity => "concept_entity",
+            EdgeType::ConceptConcept => "concept_concept",
+            EdgeType::Hierarchy => "hierarchy",
+        }
+    }
+}
+
+/// Write a map with its keys in order, so the same graph always serializes to the same text
+pub(crate) fn sorted_map<S: serde::Serializer, V: Serialize>(map: &HashMap<String, V>, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NodeMetadata {
     pub confidence: f64,
     pub original_text: String,
     pub entity_type: Option<String>,
+    #[serde(serialize_with = "sorted_map")]
     pub attributes: HashMap<String, String>,
     pub position_in_text: Option<(usize, usize)>,
+    /// Mean sentiment (-1..=1) of the sentences mentioning the entity, when `sentiment` is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sentiment: Option<f64>,
+    /// Web, email and Wikidata links found in the attributes, by attribute name
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<NodeLink>,
+    /// Input files mentioning the node, when several were combined into one graph
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<String>,
+    /// Sense of the entity's name, when same-named mentions were split apart
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disambiguation: Option<Disambiguation>,
+    /// Dates mentioned in the sentences mentioning the entity, for the viewer's timeline
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dates: Vec<DatedMention>,
+}
+
+/// Clickable link taken from a node attribute
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeLink {
+    /// Attribute the link came from
+    pub name: String,
+    /// `http(s)://` or `mailto:` address
+    pub url: String,
+}
+
+/// Link for an attribute value, if it is one: a web address (`https://`, `http://` or
+/// `www.`), an email address, or a Wikidata id (`Q42`) in an attribute named `wikidata`
+pub fn attribute_link(name: &str, value: &str) -> Option<String> {
+    let value = value.trim();
+    if value.is_empty() || value.chars().any(char::is_whitespace) {
+        return None;
+    }
+    let lower = value.to_lowercase();
+    if lower.starts_with("https://") || lower.starts_with("http://") || lower.starts_with("mailto:") {
+        return Some(value.to_string());
+    }
+    if lower.starts_with("www.") {
+        return Some(format!("https://{}", value));
+    }
+    if let Some((local, domain)) = value.split_once('@') {
+        let domain_ok = domain.contains('.') && !domain.starts_with('.') && !domain.ends_with('.');
+        if !local.is_empty() && !domain.contains('@') && domain_ok {
+            return Some(format!("mailto:{}", value));
+        }
+    }
+    let is_qid = value.len() > 1 && value.starts_with(['Q', 'q']) && value[1..].chars().all(|c| c.is_ascii_digit());
+    if name.to_lowercase().contains("wikidata") && is_qid {
+        return Some(format!("https://www.wikidata.org/wiki/{}", value.to_uppercase()));
+    }
+    None
+}
+
+/// Links among `(name, value)` attributes, ordered by attribute name
+fn attribute_links<'a>(attributes: impl IntoIterator<Item = (&'a str, &'a str)>) -> Vec<NodeLink> {
+    let mut links: Vec<NodeLink> = attributes
+        .into_iter()
+        .filter_map(|(name, value)| attribute_link(name, value).map(|url| NodeLink { name: name.to_string(), url }))
+        .collect();
+    links.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.url.cmp(&b.url)));
+    links
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,13 +191,40 @@ pub struct EdgeMetadata {
     pub relationship_type: String,
     pub bidirectional: bool,
     pub weight: f64,
+    /// Mean sentiment (-1..=1) of the sentences mentioning both endpoints, when `sentiment` is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sentiment: Option<f64>,
+    /// Edges folded into this one when `parallel_edges` is `merge`, this edge's own first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub merged: Vec<MergedRelation>,
+    /// Deep-analysis category of the relationship (`temporal`, `dependency`, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Supporting text the LLM gave for the relationship
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+}
+
+/// One of the relationships a merged edge stands for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MergedRelation {
+    pub label: String,
+    pub relationship_type: String,
+    pub confidence: f64,
+    /// Points from the merged edge's `to` node to its `from` node
+    #[serde(default)]
+    pub reversed: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InteractiveGraph {
+    /// Layout version of the serialized graph, see [`crate::schema::GRAPH_SCHEMA_VERSION`]
+    #[serde(default = "crate::schema::legacy_schema_version")]
+    pub schema_version: u32,
     pub nodes: Vec<GraphNode>,
     pub edges: Vec<GraphEdge>,
-    pub config: GraphConfig,
+    /// Rendering settings and the id of the config the graph was built with
+    pub config: RenderConfig,
     pub metadata: GraphMetadata,
 }
 
@@ -77,19 +232,471 @@ pub struct InteractiveGraph {
 pub struct GraphMetadata {
     pub total_nodes: usize,
     pub total_edges: usize,
+    #[serde(serialize_with = "sorted_map")]
     pub node_types: HashMap<String, usize>,
+    #[serde(serialize_with = "sorted_map")]
     pub edge_types: HashMap<String, usize>,
     pub creation_timestamp: String,
     pub source_text_length: usize,
+    /// How the graph was made; empty for graphs saved before it was recorded
+    #[serde(default)]
+    pub provenance: Provenance,
+}
+
+/// Run details shown in the viewer's "About this graph" section
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Provenance {
+    /// msg_net version that built the graph
+    pub version: String,
+    /// Extractors that ran, e.g. `Pattern-based + Gazetteer`
+    pub extraction_method: String,
+    /// Model used when an LLM took part in the extraction
+    pub llm_model: Option<String>,
+    pub processing_time_ms: u64,
+    /// SHA-256 of the source text, in hex, to tell which input a graph came from
+    pub input_hash: String,
+    /// The settings that shape the graph most, by config key
+    pub config_summary: BTreeMap<String, String>,
+}
+
+impl Provenance {
+    fn new(config: &GraphConfig, extraction: &ExtractionMetadata, source_text: &str) -> Self {
+        let setting = |value: serde_json::Value| match value {
+            serde_json::Value::String(text) => text,
+            other => other.to_string(),
+        };
+        let mut config_summary = BTreeMap::from([
+            ("extraction.mode".to_string(), setting(serde_json::json!(config.extraction.mode))),
+            ("extraction.entity_detector".to_string(), setting(serde_json::json!(config.extraction.entity_detector))),
+            ("layout.algorithm".to_string(), config.layout.algorithm.clone()),
+            ("text_processing.remove_stopwords".to_string(), config.text_processing.remove_stopwords.to_string()),
+            ("parallel_edges".to_string(), setting(serde_json::json!(config.parallel_edges))),
+        ]);
+        if !config.transforms.is_empty() {
+            config_summary.insert("transforms".to_string(), config.transforms.join(", "));
+        }
+        if config.min_degree > 0 {
+            config_summary.insert("min_degree".to_string(), config.min_degree.to_string());
+        }
+        if let Some(max_nodes) = config.max_nodes {
+            config_summary.insert("max_nodes".to_string(), max_nodes.to_string());
+        }
+        if let Some(max_edges) = config.max_edges {
+            config_summary.insert("max_edges".to_string(), max_edges.to_string());
+        }
+        if config.prune_isolated {
+            config_summary.insert("prune_isolated".to_string(), "true".to_string());
+        }
+        if config.deterministic {
+            config_summary.insert("deterministic".to_string(), "true".to_string());
+        }
+
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            extraction_method: extraction.extraction_method.clone(),
+            llm_model: extraction.extraction_method.contains("LLM").then(|| config.extraction.llm_model.clone()),
+            // Timings differ from run to run, so a deterministic run leaves them out
+            processing_time_ms: if config.deterministic { 0 } else { extraction.processing_time_ms },
+            input_hash: Sha256::digest(source_text.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect(),
+            config_summary,
+        }
+    }
+}
+
+impl InteractiveGraph {
+    /// Load a graph from a JSON export of this or any earlier release, with or without
+    /// `--include-metadata`
+    pub fn from_json_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        graph_from_json(&content).map_err(|e| {
+            GraphError::Configuration(format!(
+                "{} is not a graph JSON export (generate it with -f json): {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    /// Recompute the node/edge totals and per-type counts in `metadata` after the
+    /// node or edge lists have been changed
+    pub fn refresh_metadata_counts(&mut self) {
+        let mut node_types = HashMap::new();
+        for node in &self.nodes {
+            *node_types.entry(node.node_type.metadata_key().to_string()).or_insert(0) += 1;
+        }
+        let mut edge_types = HashMap::new();
+        for edge in &self.edges {
+            *edge_types.entry(edge.edge_type.metadata_key().to_string()).or_insert(0) += 1;
+        }
+
+        self.metadata.total_nodes = self.nodes.len();
+        self.metadata.total_edges = self.edges.len();
+        self.metadata.node_types = node_types;
+        self.metadata.edge_types = edge_types;
+    }
+
+    pub fn stats(&self) -> GraphStats {
+        GraphStats::compute(self)
+    }
+
+    /// Replace the edges between each pair of nodes, in either direction, by the first of
+    /// them, labeled with every distinct label and listing them all in `metadata.merged`.
+    /// It keeps the highest confidence and the summed weight, and gets arrowheads at both
+    /// ends when the merged edges point both ways.
+    pub fn merge_parallel_edges(&mut self) {
+        let mut merged: Vec<GraphEdge> = Vec::new();
+        let mut by_pair: HashMap<(String, String), usize> = HashMap::new();
+        for edge in std::mem::take(&mut self.edges) {
+            let pair = if edge.from <= edge.to {
+                (edge.from.clone(), edge.to.clone())
+            } else {
+                (edge.to.clone(), edge.from.clone())
+            };
+            let index = match by_pair.entry(pair) {
+                Entry::Occupied(entry) => *entry.get(),
+                Entry::Vacant(entry) => {
+                    entry.insert(merged.len());
+                    merged.push(edge);
+                    continue;
+                }
+            };
+            let kept = &mut merged[index];
+            if kept.metadata.merged.is_empty() {
+                kept.metadata.merged.push(MergedRelation {
+                    label: kept.label.clone(),
+                    relationship_type: kept.metadata.relationship_type.clone(),
+                    confidence: kept.metadata.confidence,
+                    reversed: false,
+                });
+            }
+            let reversed = edge.from != kept.from;
+            kept.metadata.merged.push(MergedRelation {
+                label: edge.label.clone(),
+                relationship_type: edge.metadata.relationship_type.clone(),
+                confidence: edge.metadata.confidence,
+                reversed,
+            });
+            if !edge.label.is_empty() && !kept.label.split(" / ").any(|label| label == edge.label) {
+                kept.label = if kept.label.is_empty() { edge.label } else { format!("{} / {}", kept.label, edge.label) };
+            }
+            kept.metadata.confidence = kept.metadata.confidence.max(edge.metadata.confidence);
+            kept.metadata.weight += edge.metadata.weight;
+            kept.width = kept.width.max(edge.width);
+            if reversed && !kept.metadata.bidirectional && !edge.metadata.bidirectional {
+                kept.arrows = "to, from".to_string();
+            }
+        }
+        self.edges = merged;
+        self.refresh_metadata_counts();
+    }
+
+    /// Nodes labelled `label`, ignoring case and surrounding whitespace
+    pub fn nodes_labelled(&self, label: &str) -> Vec<&GraphNode> {
+        let label = label.trim();
+        self.nodes.iter().filter(|node| node.label.trim().eq_ignore_ascii_case(label)).collect()
+    }
+
+    /// The shortest paths from any of the `from` nodes to any of the `to` nodes (by id),
+    /// following edges in either direction. Every path with the fewest edges is returned
+    /// once, up to `limit` of them; none when the nodes are not connected.
+    pub fn shortest_paths(&self, from: &[&str], to: &[&str], limit: usize) -> Vec<GraphPath> {
+        let mut adjacency: HashMap<&str, Vec<(usize, &str)>> = HashMap::new();
+        for (index, edge) in self.edges.iter().enumerate() {
+            adjacency.entry(edge.from.as_str()).or_default().push((index, edge.to.as_str()));
+            adjacency.entry(edge.to.as_str()).or_default().push((index, edge.from.as_str()));
+        }
+
+        // Breadth-first from all starts, keeping every edge that reaches a node first
+        let mut distance: HashMap<&str, usize> = from.iter().map(|id| (*id, 0)).collect();
+        let mut parents: HashMap<&str, Vec<(usize, &str)>> = HashMap::new();
+        let mut queue: VecDeque<&str> = from.iter().copied().collect();
+        let mut found: Option<usize> = None;
+        while let Some(current) = queue.pop_front() {
+            let depth = distance[current];
+            if found.is_some_and(|shortest| depth > shortest) {
+                break;
+            }
+            if to.contains(&current) {
+                found = Some(depth);
+            }
+            if found.is_some() {
+                continue;
+            }
+            for &(edge, next) in adjacency.get(current).into_iter().flatten() {
+                match distance.get(next) {
+                    None => {
+                        distance.insert(next, depth + 1);
+                        parents.entry(next).or_default().push((edge, current));
+                        queue.push_back(next);
+                    }
+                    Some(&seen) if seen == depth + 1 => parents.entry(next).or_default().push((edge, current)),
+                    Some(_) => {}
+                }
+            }
+        }
+        let Some(shortest) = found else {
+            return Vec::new();
+        };
+
+        // Walk back from each reached end to the starts
+        let mut paths = Vec::new();
+        let ends = to.iter().filter(|id| distance.get(*id) == Some(&shortest));
+        let mut stack: Vec<(&str, Vec<usize>, Vec<&str>)> = ends.map(|id| (*id, Vec::new(), vec![*id])).collect();
+        stack.reverse();
+        while let Some((node, edges, nodes)) = stack.pop() {
+            if paths.len() >= limit {
+                break;
+            }
+            if distance[node] == 0 {
+                paths.push(GraphPath {
+                    nodes: nodes.iter().rev().map(|id| id.to_string()).collect(),
+                    edges: edges.into_iter().rev().collect(),
+                });
+                continue;
+            }
+            for &(edge, previous) in parents[node].iter().rev() {
+                let mut edges = edges.clone();
+                edges.push(edge);
+                let mut nodes = nodes.clone();
+                nodes.push(previous);
+                stack.push((previous, edges, nodes));
+            }
+        }
+        paths
+    }
+
+    /// Draw `paths` in [`PATH_HIGHLIGHT_COLOR`] with thicker edges, and fade every edge
+    /// off them
+    pub fn highlight_paths(&mut self, paths: &[GraphPath]) {
+        let on_path: HashSet<usize> = paths.iter().flat_map(|path| path.edges.iter().copied()).collect();
+        let path_nodes: HashSet<&str> = paths.iter().flat_map(|path| path.nodes.iter().map(String::as_str)).collect();
+        for node in &mut self.nodes {
+            if path_nodes.contains(node.id.as_str()) {
+                node.color = PATH_HIGHLIGHT_COLOR.to_string();
+            }
+        }
+        for (index, edge) in self.edges.iter_mut().enumerate() {
+            if on_path.contains(&index) {
+                edge.color = PATH_HIGHLIGHT_COLOR.to_string();
+                edge.width = (edge.width * 2.0).max(3.0);
+                edge.opacity = None;
+            } else {
+                edge.opacity = Some(0.25);
+            }
+        }
+    }
+}
+
+/// Color of the nodes and edges of a highlighted path
+pub const PATH_HIGHLIGHT_COLOR: &str = "#E74C3C";
+
+/// One shortest path through a graph, see [`InteractiveGraph::shortest_paths`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphPath {
+    /// Node ids from start to end
+    pub nodes: Vec<String>,
+    /// Indices into the graph's `edges` of each step, in order
+    pub edges: Vec<usize>,
+}
+
+impl GraphPath {
+    /// `Alice --works at--> TechCorp <--connected to-- Bob`: each step shows the edge label,
+    /// pointing the way the edge does, or both ways for bidirectional edges
+    pub fn describe(&self, graph: &InteractiveGraph) -> String {
+        let label = |id: &str| graph.nodes.iter().find(|node| node.id == id).map_or(id.to_string(), |node| node.label.clone());
+        let mut text = label(&self.nodes[0]);
+        for (step, &index) in self.edges.iter().enumerate() {
+            let edge = &graph.edges[index];
+            let forward = edge.from == self.nodes[step];
+            let (tail, head) = match (edge.metadata.bidirectional, forward) {
+                (true, _) => ("<--", "-->"),
+                (false, true) => ("--", "-->"),
+                (false, false) => ("<--", "--"),
+            };
+            text.push_str(&format!(" {}{}{} {}", tail, edge.label, head, label(&self.nodes[step + 1])));
+        }
+        text
+    }
+}
+
+/// Signed bend for each edge so edges between the same two nodes are drawn apart: 0 for an
+/// edge on its own, else evenly spread in -1..=1 with positive bending clockwise from the
+/// edge's `from` node, and the middle one of an odd group straight
+pub fn parallel_edge_bends(edges: &[GraphEdge]) -> Vec<f64> {
+    let mut groups: HashMap<(&str, &str), Vec<usize>> = HashMap::new();
+    for (index, edge) in edges.iter().enumerate() {
+        let pair = if edge.from <= edge.to { (edge.from.as_str(), edge.to.as_str()) } else { (edge.to.as_str(), edge.from.as_str()) };
+        groups.entry(pair).or_default().push(index);
+    }
+
+    let mut bends = vec![0.0; edges.len()];
+    for members in groups.values().filter(|members| members.len() > 1) {
+        let middle = (members.len() - 1) as f64 / 2.0;
+        let step = PARALLEL_EDGE_BEND / middle;
+        for (position, &index) in members.iter().enumerate() {
+            let bend = (position as f64 - middle) * step;
+            // Bends are measured from the pair's first node, so a reversed edge flips sign
+            bends[index] = if edges[index].from <= edges[index].to { bend } else { -bend };
+        }
+    }
+    bends
 }
 
+/// Bend of the outermost edges of a parallel group
+const PARALLEL_EDGE_BEND: f64 = 0.4;
+
+/// Structural summary of a graph. Edges count as undirected and self-loops are ignored
+/// for density, components and diameter.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphStats {
+    pub nodes: usize,
+    pub edges: usize,
+    /// Connected node pairs over all possible pairs, 0..=1
+    pub density: f64,
+    pub connected_components: usize,
+    /// Edge endpoints per node: `2 * edges / nodes`
+    pub average_degree: f64,
+    /// Longest shortest path, in edges, within the largest connected component; estimated
+    /// from below for components of more than [`EXACT_DIAMETER_LIMIT`] nodes
+    pub diameter: usize,
+    /// Nodes without any edge
+    pub isolated_nodes: usize,
+}
+
+impl GraphStats {
+    pub fn compute(graph: &InteractiveGraph) -> Self {
+        let n = graph.nodes.len();
+        let index: HashMap<&str, usize> = graph.nodes.iter().enumerate().map(|(i, node)| (node.id.as_str(), i)).collect();
+        let mut neighbours: Vec<HashSet<usize>> = vec![HashSet::new(); n];
+        let mut degree = vec![0usize; n];
+        for edge in &graph.edges {
+            if let (Some(&from), Some(&to)) = (index.get(edge.from.as_str()), index.get(edge.to.as_str())) {
+                degree[from] += 1;
+                degree[to] += 1;
+                if from != to {
+                    neighbours[from].insert(to);
+                    neighbours[to].insert(from);
+                }
+            }
+        }
+
+        let pairs: usize = neighbours.iter().map(HashSet::len).sum::<usize>() / 2;
+        let density = if n > 1 { pairs as f64 / (n * (n - 1) / 2) as f64 } else { 0.0 };
+
+        // Components by breadth-first search, keeping the largest for the diameter
+        let mut component = vec![usize::MAX; n];
+        let mut components = 0;
+        let mut largest: Vec<usize> = Vec::new();
+        for start in 0..n {
+            if component[start] != usize::MAX {
+                continue;
+            }
+            let members = bfs_distances(&neighbours, start).into_keys().collect::<Vec<_>>();
+            for &member in &members {
+                component[member] = components;
+            }
+            components += 1;
+            if members.len() > largest.len() {
+                largest = members;
+            }
+        }
+        let diameter = if largest.len() <= EXACT_DIAMETER_LIMIT {
+            largest
+                .iter()
+                .map(|&node| bfs_distances(&neighbours, node).into_values().max().unwrap_or(0))
+                .max()
+                .unwrap_or(0)
+        } else {
+            double_sweep_diameter(&neighbours, largest.iter().copied().min().unwrap_or(0))
+        };
+
+        Self {
+            nodes: n,
+            edges: graph.edges.len(),
+            density,
+            connected_components: components,
+            average_degree: if n > 0 { 2.0 * graph.edges.len() as f64 / n as f64 } else { 0.0 },
+            diameter,
+            isolated_nodes: degree.iter().filter(|&&d| d == 0).count(),
+        }
+    }
+}
+
+impl fmt::Display for GraphStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Nodes: {}, edges: {}", self.nodes, self.edges)?;
+        writeln!(f, "Density: {:.4}", self.density)?;
+        writeln!(f, "Average degree: {:.2}", self.average_degree)?;
+        writeln!(f, "Connected components: {}", self.connected_components)?;
+        writeln!(f, "Diameter of largest component: {}", self.diameter)?;
+        write!(f, "Isolated nodes: {}", self.isolated_nodes)
+    }
+}
+
+/// Vertical distance between the levels of the `tree` layout, as in the viewer's
+/// hierarchical layout
+const TREE_LEVEL_SEPARATION: f64 = 150.0;
+
+/// Largest component whose diameter is computed exactly. A search from every node takes
+/// minutes on graphs with 100k edges, so larger components get an estimate instead.
+pub const EXACT_DIAMETER_LIMIT: usize = 2000;
+
+/// Searches made for the diameter estimate
+const DIAMETER_SWEEPS: usize = 4;
+
+/// Lower bound on the diameter of `start`'s component: search from `start`, then again
+/// from the farthest node found, and so on. Usually exact on sparse real-world graphs.
+fn double_sweep_diameter(neighbours: &[HashSet<usize>], start: usize) -> usize {
+    let mut from = start;
+    let mut diameter = 0;
+    for _ in 0..DIAMETER_SWEEPS {
+        // Ties go to the lowest index so the estimate is deterministic
+        let Some((farthest, distance)) = bfs_distances(neighbours, from)
+            .into_iter()
+            .max_by(|(a, da), (b, db)| da.cmp(db).then(b.cmp(a)))
+        else {
+            break;
+        };
+        diameter = diameter.max(distance);
+        from = farthest;
+    }
+    diameter
+}
+
+/// Hops from `start` to every node it can reach (itself included)
+fn bfs_distances(neighbours: &[HashSet<usize>], start: usize) -> HashMap<usize, usize> {
+    let mut distances = HashMap::from([(start, 0)]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        let distance = distances[&node];
+        for &next in &neighbours[node] {
+            if let Entry::Vacant(entry) = distances.entry(next) {
+                entry.insert(distance + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+    distances
+}
+
+type WarningCallback = Arc<dyn Fn(&str) + Send + Sync>;
+
 pub struct GraphBuilder {
     config: GraphConfig,
+    warning: Option<WarningCallback>,
 }
 
 impl GraphBuilder {
     pub fn new(config: GraphConfig) -> Self {
-        Self { config }
+        Self { config, warning: None }
+    }
+
+    /// Call `callback` with each problem building recovers from, such as the graph being
+    /// cut down to its size limit; the same messages are also logged as warnings
+    pub fn on_warning(&mut self, callback: impl Fn(&str) + Send + Sync + 'static) {
+        self.warning = Some(Arc::new(callback));
     }
 
     pub fn build_graph(&self, extraction_result: &ExtractionResult, source_text: &str) -> Result<InteractiveGraph> {
@@ -129,7 +736,7 @@ impl GraphBuilder {
         // Build relationship edges
         for relationship in &extraction_result.relationships {
             let edge = self.create_relationship_edge(relationship)?;
-            *edge_types.entry("relationship".to_string()).or_insert(0) += 1;
+            *edge_types.entry(edge.edge_type.metadata_key().to_string()).or_insert(0) += 1;
             edges.push(edge);
         }
 
@@ -141,16 +748,121 @@ impl GraphBuilder {
             total_edges: edges.len(),
             node_types,
             edge_types,
-            creation_timestamp: chrono::Utc::now().to_rfc3339(),
+            creation_timestamp: run_timestamp(&self.config.timestamps, self.config.deterministic)?,
             source_text_length: source_text.len(),
+            provenance: Provenance::new(&self.config, &extraction_result.metadata, source_text),
         };
 
-        Ok(InteractiveGraph {
+        let graph = InteractiveGraph {
+            schema_version: GRAPH_SCHEMA_VERSION,
             nodes,
             edges,
-            config: self.config.clone(),
+            config: RenderConfig::from(&self.config),
             metadata,
-        })
+        };
+
+        // Cleanup recipe and pruning first, so styling metrics such as degree reflect the final graph
+        let transforms = config_transforms(&self.config)?;
+        let mut graph = apply_transforms(graph, &transforms, source_text)?.into_graph();
+        if self.config.parallel_edges == ParallelEdges::Merge {
+            graph.merge_parallel_edges();
+        }
+        let (mut graph, truncation) = limit_graph_size(graph, &self.config, source_text)?;
+        if let Some(truncation) = truncation {
+            let message = format!("Graph is over its size limit: {}", truncation);
+            warn!("✂️  {}", message);
+            if let Some(callback) = &self.warning {
+                callback(&message);
+            }
+        }
+        self.apply_styling(&mut graph, source_text)?;
+        if self.config.sentiment.enabled {
+            apply_sentiment(&mut graph, source_text, &self.config.sentiment);
+        }
+        if self.config.timeline.enabled {
+            let splitter = SentenceSplitter::new(&self.config.text_processing.sentence_splitting);
+            apply_timeline(&mut graph, source_text, &splitter, &self.config.timeline);
+        }
+
+        Ok(graph)
+    }
+
+    /// Apply the `styling` rules, overriding the per-type sizes and colors set when the
+    /// nodes were created. Exporters read `size` and `color` straight off the nodes.
+    pub fn apply_styling(&self, graph: &mut InteractiveGraph, source_text: &str) -> Result<()> {
+        let styling = &self.config.styling;
+
+        if let Some(metric) = styling.size_by {
+            let scaled = normalize(&node_metric(graph, metric, source_text));
+            for (node, t) in graph.nodes.iter_mut().zip(scaled) {
+                node.size = styling.min_size + t * (styling.max_size - styling.min_size);
+            }
+        }
+
+        match styling.color_by {
+            ColorMode::NodeType => {}
+            ColorMode::EntityType => {
+                let mut assigned: HashMap<String, String> = HashMap::new();
+                for node in &mut graph.nodes {
+                    let key = node
+                        .metadata
+                        .entity_type
+                        .clone()
+                        .unwrap_or_else(|| node.node_type.metadata_key().to_string());
+                    let next = assigned.len();
+                    let color = assigned.entry(key.clone()).or_insert_with(|| {
+                        let name = entity_type_name(&key);
+                        styling
+                            .entity_type_colors
+                            .get(&key)
+                            .or_else(|| styling.entity_type_colors.get(name))
+                            .cloned()
+                            .or_else(|| {
+                                self.config.extraction.entity_type_definition(name).and_then(|definition| definition.color.clone())
+                            })
+                            .unwrap_or_else(|| palette_color(&styling.palette, next, &node.color))
+                    });
+                    node.color = color.clone();
+                }
+            }
+            ColorMode::Community => {
+                let communities = detect_communities(graph);
+                for (node, community) in graph.nodes.iter_mut().zip(communities) {
+                    node.color = palette_color(&styling.palette, community, &node.color);
+                    node.metadata.attributes.insert("community".to_string(), community.to_string());
+                }
+            }
+            ColorMode::Gradient => {
+                let scaled = normalize(&node_metric(graph, styling.gradient_metric, source_text));
+                for (node, t) in graph.nodes.iter_mut().zip(scaled) {
+                    if let Some(color) = interpolate_color(&styling.gradient_start, &styling.gradient_end, t) {
+                        node.color = color;
+                    }
+                }
+            }
+        }
+
+        for edge in &mut graph.edges {
+            let style = styling.edge_style(edge.edge_type.metadata_key(), &edge.metadata.relationship_type);
+            if let Some(color) = style.color {
+                edge.color = color;
+            }
+            if let Some(width) = style.width {
+                edge.width = width.max(0.0);
+            }
+            if let Some(arrows) = style.arrows {
+                edge.arrows = arrows.vis_arrows().to_string();
+            }
+            if let Some(pattern) = style.dashes {
+                edge.dashes = !pattern.is_empty();
+                edge.dash_pattern = pattern;
+            }
+            if let Some(opacity) = style.opacity {
+                edge.opacity = Some(opacity.clamp(0.0, 1.0));
+            }
+        }
+
+        apply_style_rules(graph, &styling.rules, source_text)
     }
 
     fn create_entity_node(&self, entity: &Entity) -> Result<GraphNode> {
@@ -163,19 +875,38 @@ impl GraphBuilder {
                 .collect(),
             position_in_text: entity.position.as_ref()
                 .map(|pos| (pos.start, pos.end)),
+            sentiment: None,
+            links: attribute_links(entity.attributes.iter().map(|attr| (attr.name.as_str(), attr.value.as_str()))),
+            sources: Vec::new(),
+            disambiguation: entity.disambiguation.clone(),
+            dates: Vec::new(),
         };
 
+        // Custom types from `extraction.entity_types` bring their own look
+        let definition = match &entity.entity_type {
+            EntityType::Other(name) => self.config.extraction.entity_type_definition(name),
+            _ => None,
+        };
+        let label = match &entity.disambiguation {
+            Some(disambiguation) => format!("{} ({})", entity.name, disambiguation.sense),
+            None => entity.name.clone(),
+        };
         Ok(GraphNode {
             id: entity.id.clone(),
-            label: entity.name.clone(),
+            label,
             node_type: NodeType::Entity,
-            color: self.config.node_colors.entity.clone(),
-            shape: self.config.node_shapes.entity.clone(),
+            color: definition
+                .and_then(|definition| definition.color.clone())
+                .unwrap_or_else(|| self.config.node_colors.entity.clone()),
+            shape: definition
+                .and_then(|definition| definition.shape.clone())
+                .unwrap_or_else(|| self.config.node_shapes.entity.clone()),
             size: self.calculate_node_size(entity.confidence, &entity.attributes),
             x: None,
             y: None,
             physics: true,
             metadata,
+            badge: None,
         })
     }
 
@@ -190,6 +921,11 @@ impl GraphBuilder {
             ].iter().cloned().collect(),
             position_in_text: concept.position.as_ref()
                 .map(|pos| (pos.start, pos.end)),
+            sentiment: None,
+            links: Vec::new(),
+            sources: Vec::new(),
+            disambiguation: None,
+            dates: Vec::new(),
         };
 
         Ok(GraphNode {
@@ -203,6 +939,7 @@ impl GraphBuilder {
             y: None,
             physics: true,
             metadata,
+            badge: None,
         })
     }
 
@@ -216,6 +953,11 @@ impl GraphBuilder {
                 ("parent_entity".to_string(), entity.name.clone()),
             ].iter().cloned().collect(),
             position_in_text: None,
+            sentiment: None,
+            links: attribute_links([(attribute.name.as_str(), attribute.value.as_str())]),
+            sources: Vec::new(),
+            disambiguation: None,
+            dates: Vec::new(),
         };
 
         Ok(GraphNode {
@@ -229,6 +971,7 @@ impl GraphBuilder {
             y: None,
             physics: true,
             metadata,
+            badge: None,
         })
     }
 
@@ -236,8 +979,12 @@ impl GraphBuilder {
         let metadata = EdgeMetadata {
             confidence: relationship.confidence,
             relationship_type: format!("{:?}", relationship.relationship_type),
-            bidirectional: false, // Can be enhanced based on relationship type
+            bidirectional: relationship.bidirectional,
             weight: relationship.confidence,
+            sentiment: None,
+            merged: Vec::new(),
+            category: relationship.category.clone(),
+            context: relationship.context.clone(),
         };
 
         Ok(GraphEdge {
@@ -247,30 +994,56 @@ impl GraphBuilder {
             label: relationship.label.clone(),
             color: self.config.node_colors.relationship.clone(),
             width: self.calculate_edge_width(relationship.confidence),
-            arrows: "to".to_string(),
-            edge_type: EdgeType::EntityRelationship,
+            arrows: Self::edge_arrows(relationship.bidirectional),
+            edge_type: if self.config.extraction.mode == ExtractionMode::Hierarchy {
+                EdgeType::Hierarchy
+            } else {
+                EdgeType::EntityRelationship
+            },
             metadata,
+            dashes: false,
+            dash_pattern: Vec::new(),
+            opacity: None,
         })
     }
 
+    /// Edge label naming the attribute ("has role"); descriptions are just "description"
+    fn attribute_edge_label(attribute: &crate::entity_extractor::Attribute) -> String {
+        match attribute.attribute_type {
+            AttributeType::Description => "description".to_string(),
+            _ => format!("has {}", attribute.name.replace('_', " ")),
+        }
+    }
+
     fn create_attribute_edge(&self, entity: &Entity, attribute: &crate::entity_extractor::Attribute) -> Result<GraphEdge> {
+        let attribute_type = match &attribute.attribute_type {
+            AttributeType::Other(name) => name.clone(),
+            other => format!("{:?}", other),
+        };
         let metadata = EdgeMetadata {
             confidence: attribute.confidence,
             relationship_type: "has_attribute".to_string(),
             bidirectional: false,
             weight: attribute.confidence,
+            sentiment: None,
+            merged: Vec::new(),
+            category: None,
+            context: None,
         };
 
         Ok(GraphEdge {
             id: format!("{}-{}", entity.id, attribute.id),
             from: entity.id.clone(),
             to: attribute.id.clone(),
-            label: "has".to_string(),
-            color: "#888888".to_string(),
+            label: Self::attribute_edge_label(attribute),
+            color: self.config.styling.attribute_edge_color(&attribute_type),
             width: 1.0,
             arrows: "to".to_string(),
             edge_type: EdgeType::EntityAttribute,
             metadata,
+            dashes: false,
+            dash_pattern: Vec::new(),
+            opacity: None,
         })
     }
 
@@ -312,6 +1085,10 @@ impl GraphBuilder {
             relationship_type: "related_to".to_string(),
             bidirectional: true,
             weight: 0.5,
+            sentiment: None,
+            merged: Vec::new(),
+            category: None,
+            context: None,
         };
 
         Ok(GraphEdge {
@@ -321,12 +1098,20 @@ impl GraphBuilder {
             label: "relates to".to_string(),
             color: "#CCCCCC".to_string(),
             width: 1.0,
-            arrows: "to".to_string(),
+            arrows: Self::edge_arrows(true),
             edge_type: EdgeType::ConceptEntity,
             metadata,
+            dashes: false,
+            dash_pattern: Vec::new(),
+            opacity: None,
         })
     }
 
+    /// vis.js arrow spec: symmetric relations are drawn without arrowheads
+    fn edge_arrows(bidirectional: bool) -> String {
+        if bidirectional { String::new() } else { "to".to_string() }
+    }
+
     fn calculate_node_size(&self, confidence: f64, attributes: &[crate::entity_extractor::Attribute]) -> f64 {
         let base_size = 30.0;
         let confidence_factor = 1.0 + confidence * 0.5;
@@ -348,14 +1133,32 @@ impl GraphBuilder {
     }
 
     pub fn apply_layout(&self, graph: &mut InteractiveGraph) -> Result<()> {
-        match self.config.layout.algorithm.as_str() {
+        match self.config.effective_layout().algorithm.as_str() {
             "hierarchical" => self.apply_hierarchical_layout(graph),
-            "force" => self.apply_force_layout(graph),
+            "tree" => self.apply_tree_layout(graph),
+            "force" | "fruchterman-reingold" => self.apply_force_layout(graph),
+            "kamada-kawai" => self.apply_positions(graph, layout::kamada_kawai),
+            "grid" => self.apply_positions(graph, |neighbours, spacing, _| layout::grid(neighbours, spacing)),
             "circular" => self.apply_circular_layout(graph),
             _ => self.apply_force_layout(graph), // Default to force layout
         }
     }
 
+    /// Place every node with one of the [`layout`] algorithms
+    fn apply_positions(
+        &self,
+        graph: &mut InteractiveGraph,
+        algorithm: impl Fn(&[Vec<usize>], f64, usize) -> Vec<(f64, f64)>,
+    ) -> Result<()> {
+        let layout = &self.config.layout;
+        let positions = algorithm(&layout::neighbour_lists(graph), layout.spacing, layout.iterations);
+        for (node, (x, y)) in graph.nodes.iter_mut().zip(positions) {
+            node.x = Some(x);
+            node.y = Some(y);
+        }
+        Ok(())
+    }
+
     fn apply_hierarchical_layout(&self, graph: &mut InteractiveGraph) -> Result<()> {
         // Simple hierarchical layout: entities at top, concepts in middle, attributes at bottom
         let mut entity_nodes = Vec::new();
@@ -398,12 +1201,56 @@ impl GraphBuilder {
         Ok(())
     }
 
-    fn apply_force_layout(&self, _graph: &mut InteractiveGraph) -> Result<()> {
-        // For force layout, we let vis.js handle the positioning
-        // Just ensure physics is enabled for all nodes
+    /// Layered layout following the edges: every node one level below its deepest parent,
+    /// each level centred under the one above
+    fn apply_tree_layout(&self, graph: &mut InteractiveGraph) -> Result<()> {
+        let index: HashMap<&str, usize> = graph.nodes.iter().enumerate().map(|(i, node)| (node.id.as_str(), i)).collect();
+        let n = graph.nodes.len();
+        let mut children = vec![Vec::new(); n];
+        let mut parents = vec![0usize; n];
+        for edge in &graph.edges {
+            if let (Some(&from), Some(&to)) = (index.get(edge.from.as_str()), index.get(edge.to.as_str())) {
+                if from != to {
+                    children[from].push(to);
+                    parents[to] += 1;
+                }
+            }
+        }
+
+        // Longest path from a root; nodes on a cycle are never reached and stay on top
+        let mut level = vec![0usize; n];
+        let mut queue: VecDeque<usize> = (0..n).filter(|&i| parents[i] == 0).collect();
+        while let Some(node) = queue.pop_front() {
+            for &child in &children[node] {
+                level[child] = level[child].max(level[node] + 1);
+                parents[child] -= 1;
+                if parents[child] == 0 {
+                    queue.push_back(child);
+                }
+            }
+        }
+
+        let mut rows: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for (node, &depth) in level.iter().enumerate() {
+            rows.entry(depth).or_default().push(node);
+        }
+        let spacing = self.config.layout.spacing;
+        for (depth, row) in rows {
+            let offset = (row.len() - 1) as f64 / 2.0;
+            for (position, node) in row.into_iter().enumerate() {
+                graph.nodes[node].x = Some((position as f64 - offset) * spacing);
+                graph.nodes[node].y = Some(depth as f64 * TREE_LEVEL_SEPARATION);
+            }
+        }
         Ok(())
     }
 
+    /// Fruchterman-Reingold positions; with physics enabled the viewer keeps simulating
+    /// from them
+    fn apply_force_layout(&self, graph: &mut InteractiveGraph) -> Result<()> {
+        self.apply_positions(graph, layout::fruchterman_reingold)
+    }
+
     fn apply_circular_layout(&self, graph: &mut InteractiveGraph) -> Result<()> {
         use std::f64::consts::PI;
         
@@ -425,8 +1272,556 @@ impl GraphBuilder {
     }
 }
 
+/// Raw value of `metric` for every node, in node order
+pub(crate) fn node_metric(graph: &InteractiveGraph, metric: NodeMetric, source_text: &str) -> Vec<f64> {
+    match metric {
+        NodeMetric::Confidence => graph.nodes.iter().map(|node| node.metadata.confidence).collect(),
+        NodeMetric::Degree => {
+            let mut degree: HashMap<&str, usize> = HashMap::new();
+            for edge in &graph.edges {
+                *degree.entry(edge.from.as_str()).or_insert(0) += 1;
+                *degree.entry(edge.to.as_str()).or_insert(0) += 1;
+            }
+            graph
+                .nodes
+                .iter()
+                .map(|node| degree.get(node.id.as_str()).copied().unwrap_or(0) as f64)
+                .collect()
+        }
+        NodeMetric::MentionCount => {
+            let text = source_text.to_lowercase();
+            graph
+                .nodes
+                .iter()
+                .map(|node| {
+                    let label = node.label.to_lowercase();
+                    if label.is_empty() { 0.0 } else { text.matches(label.as_str()).count() as f64 }
+                })
+                .collect()
+        }
+    }
+}
+
+/// Rescale values to 0..=1; when all values are equal every node lands in the middle
+fn normalize(values: &[f64]) -> Vec<f64> {
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|value| if max > min { (value - min) / (max - min) } else { 0.5 })
+        .collect()
+}
+
+fn palette_color(palette: &[String], index: usize, fallback: &str) -> String {
+    if palette.is_empty() {
+        fallback.to_string()
+    } else {
+        palette[index % palette.len()].clone()
+    }
+}
+
+pub(crate) fn parse_hex_color(color: &str) -> Option<(u8, u8, u8)> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |range: std::ops::Range<usize>| u8::from_str_radix(&hex[range], 16).ok();
+    Some((channel(0..2)?, channel(2..4)?, channel(4..6)?))
+}
+
+/// Linear blend of two `#RRGGBB` colors; `None` if either is not a hex color
+fn interpolate_color(start: &str, end: &str, t: f64) -> Option<String> {
+    let (r1, g1, b1) = parse_hex_color(start)?;
+    let (r2, g2, b2) = parse_hex_color(end)?;
+    let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t.clamp(0.0, 1.0)).round() as u8;
+    Some(format!("#{:02X}{:02X}{:02X}", mix(r1, r2), mix(g1, g2), mix(b1, b2)))
+}
+
+/// Community index per node (in node order) from synchronous-order label propagation.
+///
+/// Ties go to the smallest label so the result is deterministic; communities are
+/// numbered by first appearance.
+pub(crate) fn detect_communities(graph: &InteractiveGraph) -> Vec<usize> {
+    let index: HashMap<&str, usize> = graph.nodes.iter().enumerate().map(|(i, node)| (node.id.as_str(), i)).collect();
+    let mut neighbours = vec![Vec::new(); graph.nodes.len()];
+    for edge in &graph.edges {
+        if let (Some(&from), Some(&to)) = (index.get(edge.from.as_str()), index.get(edge.to.as_str())) {
+            if from != to {
+                neighbours[from].push(to);
+                neighbours[to].push(from);
+            }
+        }
+    }
+
+    let mut labels: Vec<usize> = (0..graph.nodes.len()).collect();
+    for _ in 0..20 {
+        let mut changed = false;
+        for node in 0..labels.len() {
+            if neighbours[node].is_empty() {
+                continue;
+            }
+            let mut counts: BTreeMap<usize, usize> = BTreeMap::new();
+            for &neighbour in &neighbours[node] {
+                *counts.entry(labels[neighbour]).or_insert(0) += 1;
+            }
+            let best = counts.values().copied().max().unwrap_or(0);
+            let label = counts
+                .iter()
+                .find(|(_, &count)| count == best)
+                .map(|(&label, _)| label)
+                .unwrap_or(labels[node]);
+            if label != labels[node] {
+                labels[node] = label;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let mut renumbered: HashMap<usize, usize> = HashMap::new();
+    labels
+        .into_iter()
+        .map(|label| {
+            let next = renumbered.len();
+            *renumbered.entry(label).or_insert(next)
+        })
+        .collect()
+}
+
 impl Default for GraphBuilder {
     fn default() -> Self {
         Self::new(GraphConfig::default())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StylingConfig;
+    use crate::pipeline::Pipeline;
+
+    const TEXT: &str = "Alice works at TechCorp. Bob is connected to Alice. Alice manages the database system.";
+
+    async fn styled_graph(styling: StylingConfig) -> InteractiveGraph {
+        let config = GraphConfig { styling, ..GraphConfig::default() };
+        Pipeline::new().with_config(config).with_text(TEXT).run().await.expect("Pipeline failed")
+    }
+
+    #[tokio::test]
+    async fn test_size_by_degree_spans_configured_range() {
+        let graph = styled_graph(StylingConfig {
+            size_by: Some(NodeMetric::Degree),
+            min_size: 10.0,
+            max_size: 50.0,
+            ..StylingConfig::default()
+        })
+        .await;
+
+        let sizes: Vec<f64> = graph.nodes.iter().map(|n| n.size).collect();
+        assert!(sizes.iter().all(|&size| (10.0..=50.0).contains(&size)));
+        assert!(sizes.contains(&50.0));
+    }
+
+    #[tokio::test]
+    async fn test_color_by_entity_type_and_community() {
+        let mut entity_type_colors = HashMap::new();
+        entity_type_colors.insert("Person".to_string(), "#123456".to_string());
+        let graph = styled_graph(StylingConfig {
+            color_by: ColorMode::EntityType,
+            entity_type_colors,
+            ..StylingConfig::default()
+        })
+        .await;
+        let alice = graph.nodes.iter().find(|n| n.label == "Alice").expect("Alice missing");
+        assert_eq!(alice.color, "#123456");
+
+        let styling = StylingConfig { color_by: ColorMode::Community, ..StylingConfig::default() };
+        let palette = styling.palette.clone();
+        let graph = styled_graph(styling).await;
+        assert!(graph.nodes.iter().all(|n| palette.contains(&n.color)));
+        assert!(graph.nodes.iter().all(|n| n.metadata.attributes.contains_key("community")));
+    }
+
+    #[tokio::test]
+    async fn test_custom_entity_types_classify_style_and_filter() {
+        let mut config = GraphConfig::default();
+        config.extraction.entity_types = vec![crate::config::EntityTypeDefinition {
+            name: "Medication".to_string(),
+            keywords: vec!["CILLIN".to_string(), "aspirin".to_string()],
+            color: Some("#8E24AA".to_string()),
+            shape: Some("box".to_string()),
+        }];
+        let text = "Alice takes Amoxicillin daily. Bob prefers Aspirin.";
+        let graph = Pipeline::new().with_config(config.clone()).with_text(text).run().await.expect("Pipeline failed");
+        let node = |label: &str| graph.nodes.iter().find(|n| n.label == label).unwrap_or_else(|| panic!("{} missing", label));
+        for medication in ["Amoxicillin", "Aspirin"] {
+            assert_eq!(node(medication).metadata.entity_type.as_deref(), Some("Other(\"Medication\")"));
+            assert_eq!((node(medication).color.as_str(), node(medication).shape.as_str()), ("#8E24AA", "box"));
+        }
+        assert_eq!(node("Alice").color, config.node_colors.entity);
+
+        let filter: crate::query::GraphFilter = "type:medication".parse().unwrap();
+        let mut labels: Vec<String> = filter.subgraph(&graph).nodes.into_iter().map(|n| n.label).collect();
+        labels.sort();
+        assert_eq!(labels, vec!["Amoxicillin", "Aspirin"]);
+
+        // Coloring by entity type uses the type's color unless `entity_type_colors` names it
+        config.styling.color_by = ColorMode::EntityType;
+        let graph = Pipeline::new().with_config(config.clone()).with_text(text).run().await.unwrap();
+        assert!(graph.nodes.iter().any(|n| n.label == "Aspirin" && n.color == "#8E24AA"));
+        config.styling.entity_type_colors.insert("Medication".to_string(), "#00FF00".to_string());
+        let graph = Pipeline::new().with_config(config).with_text(text).run().await.unwrap();
+        assert!(graph.nodes.iter().any(|n| n.label == "Aspirin" && n.color == "#00FF00"));
+    }
+
+    #[test]
+    fn test_attribute_edges_are_named_and_colored_by_type() {
+        use crate::entity_extractor::{Attribute, EntityType, ExtractionMetadata};
+
+        let attribute = |name: &str, attribute_type: AttributeType| Attribute {
+            id: name.to_string(),
+            name: name.to_string(),
+            value: "value".to_string(),
+            attribute_type,
+            confidence: 0.8,
+        };
+        let result = ExtractionResult {
+            entities: vec![Entity {
+                id: "alice".to_string(),
+                name: "Alice".to_string(),
+                entity_type: EntityType::Person,
+                attributes: vec![
+                    attribute("description", AttributeType::Description),
+                    attribute("job_role", AttributeType::Other("Role".to_string())),
+                    attribute("domain", AttributeType::Category),
+                ],
+                confidence: 0.7,
+                position: None,
+                mentions: 1,
+                disambiguation: None,
+            }],
+            relationships: Vec::new(),
+            concepts: Vec::new(),
+            metadata: ExtractionMetadata {
+                total_entities: 1,
+                total_relationships: 0,
+                total_concepts: 0,
+                processing_time_ms: 0,
+                confidence_threshold: 0.5,
+                extraction_method: "Pattern-based".to_string(),
+            },
+        };
+        let mut config = GraphConfig::default();
+        config.styling.attribute_edge_colors.insert("Role".to_string(), "#010203".to_string());
+        let graph = GraphBuilder::new(config).build_graph(&result, "Alice").unwrap();
+
+        let edge = |to: &str| graph.edges.iter().find(|e| e.to == to).expect(to);
+        assert_eq!((edge("description").label.as_str(), edge("description").color.as_str()), ("description", "#8E7CC3"));
+        assert_eq!((edge("job_role").label.as_str(), edge("job_role").color.as_str()), ("has job role", "#010203"));
+        assert_eq!((edge("domain").label.as_str(), edge("domain").color.as_str()), ("has domain", "#C27BA0"));
+    }
+
+    #[tokio::test]
+    async fn test_edge_styles_layer_by_edge_and_relationship_type() {
+        use crate::config::{ArrowStyle, EdgeStyle};
+
+        let styled = |styling: StylingConfig| async move {
+            let mut graph = styled_graph(StylingConfig::default()).await;
+            let mut attribute_edge = graph.edges[0].clone();
+            attribute_edge.edge_type = EdgeType::EntityAttribute;
+            attribute_edge.metadata.relationship_type = "description".to_string();
+            attribute_edge.opacity = None;
+            graph.edges.push(attribute_edge);
+            let config = GraphConfig { styling, ..GraphConfig::default() };
+            GraphBuilder::new(config).apply_styling(&mut graph, TEXT).unwrap();
+            graph
+        };
+        let attribute_edge = |graph: &InteractiveGraph| graph.edges.last().unwrap().clone();
+
+        let graph = styled(StylingConfig::default()).await;
+        let edge = attribute_edge(&graph);
+        assert_eq!((edge.dashes, edge.dash_pattern, edge.opacity), (true, vec![6.0, 4.0], None));
+        assert!(graph
+            .edges
+            .iter()
+            .filter(|e| e.edge_type == EdgeType::EntityRelationship)
+            .all(|e| !e.dashes && e.opacity.is_none()));
+
+        let relationship = graph.edges[0].metadata.relationship_type.clone();
+        let mut styling = StylingConfig::default();
+        styling.edge_styles.insert(
+            "entity_attribute".to_string(),
+            EdgeStyle { arrows: Some(ArrowStyle::None), opacity: Some(1.5), ..EdgeStyle::default() },
+        );
+        styling.edge_styles.insert(
+            relationship.clone(),
+            EdgeStyle {
+                color: Some("#E74C3C".to_string()),
+                width: Some(4.0),
+                arrows: Some(ArrowStyle::Both),
+                dashes: Some(vec![1.0, 3.0]),
+                ..EdgeStyle::default()
+            },
+        );
+        let graph = styled(styling).await;
+        // The configured style keeps the built-in dashes it does not override
+        let edge = attribute_edge(&graph);
+        assert_eq!((edge.arrow_ends(), edge.dash_pattern, edge.opacity), ((false, false), vec![6.0, 4.0], Some(1.0)));
+        let edge = &graph.edges[0];
+        assert_eq!((edge.arrow_ends(), edge.dashes, edge.dash_pattern.as_slice()), ((true, true), true, &[1.0, 3.0][..]));
+        assert_eq!((edge.color.as_str(), edge.width), ("#E74C3C", 4.0));
+        // Edges of other types keep their built-in color and width
+        let edge = attribute_edge(&graph);
+        assert_ne!(edge.color, "#E74C3C");
+        assert_eq!(edge.width, 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_shortest_paths_cover_ties_and_follow_edges_backwards() {
+        let mut graph = styled_graph(StylingConfig::default()).await;
+        let (node, edge) = (graph.nodes[0].clone(), graph.edges[0].clone());
+        graph.nodes = ["a", "b", "c", "d", "e"]
+            .iter()
+            .map(|id| GraphNode { id: id.to_string(), label: id.to_uppercase(), ..node.clone() })
+            .collect();
+        let link = |from: &str, to: &str, label: &str| GraphEdge {
+            id: format!("{}{}", from, to),
+            from: from.to_string(),
+            to: to.to_string(),
+            label: label.to_string(),
+            metadata: EdgeMetadata { bidirectional: false, ..edge.metadata.clone() },
+            ..edge.clone()
+        };
+        graph.edges = vec![link("a", "b", "knows"), link("b", "d", "runs"), link("c", "a", "hired"), link("c", "d", "owns"), link("e", "e", "self")];
+
+        let paths = graph.shortest_paths(&["a"], &["d"], 5);
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[0], GraphPath { nodes: vec!["a".into(), "b".into(), "d".into()], edges: vec![0, 1] });
+        assert_eq!(paths[0].describe(&graph), "A --knows--> B --runs--> D");
+        assert_eq!(paths[1].describe(&graph), "A <--hired-- C --owns--> D");
+        assert_eq!(graph.shortest_paths(&["a"], &["d"], 1).len(), 1);
+        assert!(graph.shortest_paths(&["a"], &["e"], 5).is_empty());
+        assert!(graph.shortest_paths(&["a"], &["a"], 5)[0].edges.is_empty());
+        assert_eq!(graph.nodes_labelled(" b ")[0].id, "b");
+
+        graph.highlight_paths(&paths[..1]);
+        assert_eq!((graph.edges[0].color.as_str(), graph.edges[0].opacity), (PATH_HIGHLIGHT_COLOR, None));
+        assert_eq!(graph.edges[2].opacity, Some(0.25));
+        assert_eq!(graph.nodes[3].color, PATH_HIGHLIGHT_COLOR);
+        assert_ne!(graph.nodes[2].color, PATH_HIGHLIGHT_COLOR);
+    }
+
+    #[tokio::test]
+    async fn test_parallel_edges_curve_apart_or_merge() {
+        let mut graph = styled_graph(StylingConfig::default()).await;
+        graph.edges.truncate(1);
+        let mut second = graph.edges[0].clone();
+        second.id = "second".to_string();
+        second.label = "advises".to_string();
+        second.metadata.confidence = 0.95;
+        std::mem::swap(&mut second.from, &mut second.to);
+        let mut third = graph.edges[0].clone();
+        third.id = "third".to_string();
+        graph.edges.extend([second, third]);
+        graph.refresh_metadata_counts();
+
+        let bends = parallel_edge_bends(&graph.edges);
+        // The two same-way edges bend to opposite sides and the reversed middle one is straight
+        assert_eq!((bends[0].abs(), bends[1], bends[0] + bends[2]), (0.4, 0.0, 0.0));
+        assert_eq!(parallel_edge_bends(&graph.edges[..1]), vec![0.0]);
+
+        let first = graph.edges[0].clone();
+        graph.merge_parallel_edges();
+        assert_eq!(graph.edges.len(), 1);
+        assert_eq!(graph.metadata.total_edges, 1);
+        let edge = &graph.edges[0];
+        assert_eq!((edge.id.as_str(), edge.from.as_str()), (first.id.as_str(), first.from.as_str()));
+        assert_eq!(edge.label, format!("{} / advises", first.label));
+        assert_eq!(edge.metadata.confidence, 0.95);
+        assert!((edge.metadata.weight - first.metadata.weight * 3.0).abs() < 1e-9);
+        let reversed: Vec<bool> = edge.metadata.merged.iter().map(|relation| relation.reversed).collect();
+        assert_eq!(reversed, vec![false, true, false]);
+        if !first.metadata.bidirectional {
+            assert_eq!(edge.arrow_ends(), (true, true));
+        }
+    }
+
+    #[test]
+    fn test_links_from_url_email_and_wikidata_attributes() {
+        assert_eq!(attribute_link("site", "www.example.org").as_deref(), Some("https://www.example.org"));
+        assert_eq!(attribute_link("contact", " ada@example.org ").as_deref(), Some("mailto:ada@example.org"));
+        assert_eq!(attribute_link("wikidata_id", "q7259").as_deref(), Some("https://www.wikidata.org/wiki/Q7259"));
+        assert!(attribute_link("serial", "Q7259").is_none());
+        assert!(attribute_link("handle", "@ada").is_none());
+        assert!(attribute_link("note", "see https://example.org").is_none());
+
+        let attribute = |name: &str, value: &str| crate::entity_extractor::Attribute {
+            id: name.to_string(),
+            name: name.to_string(),
+            value: value.to_string(),
+            attribute_type: AttributeType::Property,
+            confidence: 0.8,
+        };
+        let entity = Entity {
+            id: "ada".to_string(),
+            name: "Ada".to_string(),
+            entity_type: crate::entity_extractor::EntityType::Person,
+            attributes: vec![
+                attribute("website", "https://example.org/ada"),
+                attribute("email", "ada@example.org"),
+                attribute("role", "Engineer"),
+            ],
+            confidence: 0.9,
+            position: None,
+            mentions: 1,
+            disambiguation: None,
+        };
+        let node = GraphBuilder::default().create_entity_node(&entity).unwrap();
+        assert_eq!(
+            node.metadata.links.iter().map(|link| (link.name.as_str(), link.url.as_str())).collect::<Vec<_>>(),
+            vec![("email", "mailto:ada@example.org"), ("website", "https://example.org/ada")]
+        );
+
+        let graph = InteractiveGraph {
+            schema_version: GRAPH_SCHEMA_VERSION,
+            nodes: vec![node],
+            edges: Vec::new(),
+            config: RenderConfig::default(),
+            metadata: GraphMetadata {
+                total_nodes: 1,
+                total_edges: 0,
+                node_types: HashMap::new(),
+                edge_types: HashMap::new(),
+                creation_timestamp: String::new(),
+                source_text_length: 0,
+                provenance: Provenance::default(),
+            },
+        };
+        let graphml = crate::export::GraphExporter::new()
+            .export_to_string(&graph, &crate::export::ExportFormat::GraphML)
+            .unwrap();
+        assert!(graphml.contains("<data key=\"d8\">mailto:ada@example.org</data>"));
+        assert!(graphml.contains("<data key=\"d9\">mailto:ada@example.org https://example.org/ada</data>"));
+    }
+
+    #[test]
+    fn test_interpolate_color() {
+        assert_eq!(interpolate_color("#000000", "#FFFFFF", 0.5).as_deref(), Some("#808080"));
+        assert_eq!(interpolate_color("#000000", "#FFFFFF", 1.0).as_deref(), Some("#FFFFFF"));
+        assert!(interpolate_color("red", "#FFFFFF", 0.5).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_graph_stores_config_id_and_loads_legacy_exports() {
+        let config = GraphConfig::default();
+        let graph = styled_graph(StylingConfig::default()).await;
+        assert_eq!(graph.config.config_id, config.config_id());
+
+        let json = serde_json::to_value(&graph).unwrap();
+        assert!(json["config"].get("extraction").is_none());
+        assert!(json["config"].get("node_colors").is_none());
+
+        // Exports from before config ids embedded the full GraphConfig
+        let mut legacy = json.clone();
+        legacy["config"] = serde_json::to_value(&config).unwrap();
+        let migrated: InteractiveGraph = serde_json::from_value(legacy).expect("Legacy export failed to load");
+        assert_eq!(migrated.config.config_id, config.config_id());
+        assert_eq!(migrated.config.physics.repulsion, config.physics.repulsion);
+        assert_eq!(serde_json::to_value(&migrated).unwrap()["config"], json["config"]);
+
+        let other = GraphConfig { transforms: vec!["dedupe".to_string()], ..GraphConfig::default() };
+        assert_ne!(other.config_id(), config.config_id());
+    }
+
+    #[tokio::test]
+    async fn test_rust_layouts_place_every_node_deterministically() {
+        let graph = styled_graph(StylingConfig::default()).await;
+        for algorithm in ["force", "kamada-kawai", "grid"] {
+            let mut config = GraphConfig::default();
+            config.layout.algorithm = algorithm.to_string();
+            let builder = GraphBuilder::new(config);
+            let mut laid_out = graph.clone();
+            builder.apply_layout(&mut laid_out).unwrap();
+            let positions: Vec<(Option<f64>, Option<f64>)> = laid_out.nodes.iter().map(|n| (n.x, n.y)).collect();
+            assert!(positions.iter().all(|(x, y)| x.is_some() && y.is_some()), "{} left nodes unplaced", algorithm);
+
+            let mut again = graph.clone();
+            builder.apply_layout(&mut again).unwrap();
+            assert_eq!(positions, again.nodes.iter().map(|n| (n.x, n.y)).collect::<Vec<_>>(), "{}", algorithm);
+
+            let dot = crate::export::GraphExporter::new()
+                .export_to_string(&laid_out, &crate::export::ExportFormat::Dot)
+                .unwrap();
+            assert_eq!(dot.matches(", pos=\"").count(), graph.nodes.len());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_from_json_file_reads_full_and_simplified_exports() {
+        let graph = styled_graph(StylingConfig::default()).await;
+        let dir = tempfile::tempdir().unwrap();
+        let full = dir.path().join("full.json");
+        let simplified = dir.path().join("simplified.json");
+        std::fs::write(&full, serde_json::to_string(&graph).unwrap()).unwrap();
+        std::fs::write(&simplified, serde_json::json!({"nodes": graph.nodes, "edges": graph.edges}).to_string()).unwrap();
+
+        for path in [&full, &simplified] {
+            let loaded = InteractiveGraph::from_json_file(path).expect("Export failed to load");
+            assert_eq!((loaded.nodes.len(), loaded.edges.len()), (graph.nodes.len(), graph.edges.len()));
+            assert_eq!(loaded.metadata.total_edges, graph.edges.len());
+        }
+
+        let other = dir.path().join("other.json");
+        std::fs::write(&other, r#"{"entities": []}"#).unwrap();
+        let error = InteractiveGraph::from_json_file(&other).unwrap_err().to_string();
+        assert!(error.contains("other.json is not a graph JSON export"), "{}", error);
+        assert!(InteractiveGraph::from_json_file(dir.path().join("missing.json")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stats_on_path_triangle_and_isolated_node() {
+        let mut graph = styled_graph(StylingConfig::default()).await;
+        graph.nodes.truncate(1);
+        for id in ["b", "c", "d", "e", "f", "g"] {
+            let mut node = graph.nodes[0].clone();
+            node.id = id.to_string();
+            graph.nodes.push(node);
+        }
+        graph.nodes[0].id = "a".to_string();
+        let template = graph.edges[0].clone();
+        // Path a-b-c-d, a triangle e-f-g with a duplicate edge and a self-loop, isolated h
+        graph.edges = [("a", "b"), ("b", "c"), ("c", "d"), ("e", "f"), ("f", "g"), ("g", "e"), ("f", "e"), ("g", "g")]
+            .into_iter()
+            .map(|(from, to)| GraphEdge { from: from.to_string(), to: to.to_string(), ..template.clone() })
+            .collect();
+        graph.nodes.push(GraphNode { id: "h".to_string(), ..graph.nodes[0].clone() });
+
+        let stats = graph.stats();
+        assert_eq!((stats.nodes, stats.edges), (8, 8));
+        assert_eq!(stats.connected_components, 3);
+        assert_eq!(stats.diameter, 3);
+        assert_eq!(stats.isolated_nodes, 1);
+        assert_eq!(stats.average_degree, 2.0);
+        // 6 distinct pairs out of 28
+        assert!((stats.density - 6.0 / 28.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_diameter_of_large_components_is_estimated_quickly() {
+        let mut graph = styled_graph(StylingConfig::default()).await;
+        let node = graph.nodes[0].clone();
+        let edge = graph.edges[0].clone();
+        // A path with a short branch off its middle, well over the exact limit
+        let length = EXACT_DIAMETER_LIMIT + 500;
+        graph.nodes = (0..length + 3).map(|i| GraphNode { id: format!("n{}", i), ..node.clone() }).collect();
+        graph.edges = (1..length)
+            .map(|i| (i - 1, i))
+            .chain([(length / 2, length), (length, length + 1), (length + 1, length + 2)])
+            .map(|(from, to)| GraphEdge { from: format!("n{}", from), to: format!("n{}", to), ..edge.clone() })
+            .collect();
+
+        assert_eq!(graph.stats().diameter, length - 1);
+    }
+}
@@ -0,0 +1,300 @@
+//! Graphs split into chunk files for viewers that load them piece by piece.
+//!
+//! Graphs too large for one HTML payload are cut along their communities: every
+//! community goes into one chunk, large ones are split and small ones are packed
+//! together, so no chunk holds more than `chunks.max_chunk_nodes` nodes. Each chunk is
+//! laid out as a disc around its center, hubs in the middle, and the discs are placed on
+//! a spiral; the viewer starts from a manifest with one summary node per chunk and
+//! fetches a chunk's nodes when the chunk is zoomed into view. Edges between chunks are
+//! stored in the files of both chunks, so they appear once both ends are loaded.
+
+use crate::config::{ChunkConfig, RenderConfig};
+use crate::error::{GraphError, Result};
+use crate::graph_builder::{detect_communities, GraphEdge, GraphNode, GraphStats, InteractiveGraph};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+/// Version of the manifest and chunk file layout, checked by the viewer
+pub const CHUNK_FORMAT_VERSION: u32 = 1;
+
+/// Name of the manifest file in the chunk directory
+pub const CHUNK_MANIFEST_FILE: &str = "manifest.json";
+
+/// Labels listed per chunk in the manifest
+const TOP_LABELS: usize = 5;
+
+const GOLDEN_ANGLE: f64 = 2.399_963_229_728_653;
+
+/// One chunk as the overview shows it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkSummary {
+    pub id: usize,
+    /// File name, relative to the chunk directory
+    pub file: String,
+    pub nodes: usize,
+    /// Edges stored in the chunk file, including those to other chunks
+    pub edges: usize,
+    /// Labels of the best-connected nodes, most connected first
+    pub top_labels: Vec<String>,
+    pub color: String,
+    /// Center and radius of the chunk's disc in the precomputed layout
+    pub x: f64,
+    pub y: f64,
+    pub radius: f64,
+}
+
+/// Edges running between two chunks, counted once
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkLink {
+    pub from: usize,
+    pub to: usize,
+    pub edges: usize,
+}
+
+/// Everything the viewer needs before loading any chunk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkManifest {
+    pub version: u32,
+    pub total_nodes: usize,
+    pub total_edges: usize,
+    pub chunks: Vec<ChunkSummary>,
+    pub links: Vec<ChunkLink>,
+    pub config: RenderConfig,
+    pub stats: GraphStats,
+}
+
+/// The nodes of one chunk, positioned, and every edge touching them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphChunk {
+    pub version: u32,
+    pub id: usize,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+/// Layout coordinates to the hundredth, which keeps the files small
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// Node indices per chunk: communities, largest first, split at `max_nodes` and packed
+/// first-fit, each chunk ordered by degree
+fn assign_chunks(graph: &InteractiveGraph, degree: &[usize], max_nodes: usize) -> Vec<Vec<usize>> {
+    let max_nodes = max_nodes.max(1);
+    let mut communities: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (node, community) in detect_communities(graph).into_iter().enumerate() {
+        communities.entry(community).or_default().push(node);
+    }
+
+    let mut pieces: Vec<Vec<usize>> = Vec::new();
+    for mut members in communities.into_values() {
+        members.sort_by(|a, b| degree[*b].cmp(&degree[*a]));
+        pieces.extend(members.chunks(max_nodes).map(<[usize]>::to_vec));
+    }
+    // Stable, so equally sized pieces keep community order
+    pieces.sort_by_key(|piece| std::cmp::Reverse(piece.len()));
+
+    let mut chunks: Vec<Vec<usize>> = Vec::new();
+    for piece in pieces {
+        match chunks.iter_mut().find(|chunk| chunk.len() + piece.len() <= max_nodes) {
+            Some(chunk) => chunk.extend(piece),
+            None => chunks.push(piece),
+        }
+    }
+    for chunk in &mut chunks {
+        chunk.sort_by(|a, b| degree[*b].cmp(&degree[*a]));
+    }
+    chunks
+}
+
+/// Disc radius for a chunk of `nodes` nodes
+fn chunk_radius(nodes: usize, spacing: f64) -> f64 {
+    spacing * ((nodes as f64).sqrt() + 1.0)
+}
+
+/// Centers on a spiral, each moved outward until its disc clears the ones placed before
+fn place_chunks(radii: &[f64], spacing: f64) -> Vec<(f64, f64)> {
+    let mut centers: Vec<(f64, f64)> = Vec::with_capacity(radii.len());
+    for (index, &radius) in radii.iter().enumerate() {
+        let angle = index as f64 * GOLDEN_ANGLE;
+        let mut distance = 0.0;
+        let center = loop {
+            let center = (distance * angle.cos(), distance * angle.sin());
+            let clear = centers.iter().zip(radii).all(|(&(x, y), &other)| {
+                (center.0 - x).hypot(center.1 - y) >= radius + other + spacing
+            });
+            if clear {
+                break center;
+            }
+            distance += (radius / 2.0).max(spacing);
+        };
+        centers.push(center);
+    }
+    centers
+}
+
+/// Split `graph` into a manifest and its chunks, see the [module docs](self)
+pub fn chunk_graph(graph: &InteractiveGraph, config: &ChunkConfig) -> (ChunkManifest, Vec<GraphChunk>) {
+    let index: HashMap<&str, usize> = graph.nodes.iter().enumerate().map(|(i, node)| (node.id.as_str(), i)).collect();
+    let mut degree = vec![0usize; graph.nodes.len()];
+    for edge in &graph.edges {
+        if let (Some(&from), Some(&to)) = (index.get(edge.from.as_str()), index.get(edge.to.as_str())) {
+            degree[from] += 1;
+            degree[to] += 1;
+        }
+    }
+
+    let assigned = assign_chunks(graph, &degree, config.max_chunk_nodes);
+    let mut chunk_of = vec![0usize; graph.nodes.len()];
+    for (chunk, members) in assigned.iter().enumerate() {
+        for &node in members {
+            chunk_of[node] = chunk;
+        }
+    }
+
+    let spacing = config.node_spacing.max(1.0);
+    let radii: Vec<f64> = assigned.iter().map(|members| chunk_radius(members.len(), spacing)).collect();
+    let centers = place_chunks(&radii, spacing);
+
+    let mut chunks: Vec<GraphChunk> = assigned
+        .iter()
+        .enumerate()
+        .map(|(id, members)| {
+            let (cx, cy) = centers[id];
+            let nodes = members
+                .iter()
+                .enumerate()
+                .map(|(rank, &node)| {
+                    // Sunflower layout: even density, the best-connected nodes in the middle
+                    let distance = spacing * (rank as f64).sqrt();
+                    let angle = rank as f64 * GOLDEN_ANGLE;
+                    let mut node = graph.nodes[node].clone();
+                    node.x = Some(round2(cx + distance * angle.cos()));
+                    node.y = Some(round2(cy + distance * angle.sin()));
+                    node.physics = false;
+                    node
+                })
+                .collect();
+            GraphChunk { version: CHUNK_FORMAT_VERSION, id, nodes, edges: Vec::new() }
+        })
+        .collect();
+
+    let mut links: BTreeMap<(usize, usize), usize> = BTreeMap::new();
+    for edge in &graph.edges {
+        let (Some(&from), Some(&to)) = (index.get(edge.from.as_str()), index.get(edge.to.as_str())) else {
+            continue;
+        };
+        let (a, b) = (chunk_of[from], chunk_of[to]);
+        chunks[a].edges.push(edge.clone());
+        if a != b {
+            chunks[b].edges.push(edge.clone());
+            *links.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+        }
+    }
+
+    let summaries = chunks
+        .iter()
+        .map(|chunk| ChunkSummary {
+            id: chunk.id,
+            file: format!("chunk_{:04}.json", chunk.id),
+            nodes: chunk.nodes.len(),
+            edges: chunk.edges.len(),
+            top_labels: chunk.nodes.iter().take(TOP_LABELS).map(|node| node.label.clone()).collect(),
+            color: chunk.nodes.first().map(|node| node.color.clone()).unwrap_or_default(),
+            x: round2(centers[chunk.id].0),
+            y: round2(centers[chunk.id].1),
+            radius: round2(radii[chunk.id]),
+        })
+        .collect();
+
+    let manifest = ChunkManifest {
+        version: CHUNK_FORMAT_VERSION,
+        total_nodes: graph.nodes.len(),
+        total_edges: graph.edges.len(),
+        chunks: summaries,
+        links: links.into_iter().map(|((from, to), edges)| ChunkLink { from, to, edges }).collect(),
+        config: graph.config.clone(),
+        stats: graph.stats(),
+    };
+    (manifest, chunks)
+}
+
+/// Write the manifest and one JSON file per chunk into `directory`, creating it
+pub fn write_chunks(manifest: &ChunkManifest, chunks: &[GraphChunk], directory: &Path) -> Result<()> {
+    let write_error =
+        |path: &Path, e: std::io::Error| GraphError::Export(format!("Failed to write chunk file {}: {}", path.display(), e));
+    fs::create_dir_all(directory).map_err(|e| write_error(directory, e))?;
+    let path = directory.join(CHUNK_MANIFEST_FILE);
+    fs::write(&path, serde_json::to_string(manifest)?).map_err(|e| write_error(&path, e))?;
+    for (summary, chunk) in manifest.chunks.iter().zip(chunks) {
+        let path = directory.join(&summary.file);
+        fs::write(&path, serde_json::to_string(chunk)?).map_err(|e| write_error(&path, e))?;
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity_extractor::EntityExtractor;
+    use crate::graph_builder::GraphBuilder;
+    use crate::text_processor::{SourceType, TextProcessor};
+    use std::collections::HashSet;
+
+    #[tokio::test]
+    async fn test_chunks_cover_the_graph_without_overlapping() {
+        let text = "Alice Smith works at Initech in Berlin. Alice Smith met Bob Jones in Berlin. \
+                    Carol White called Dave Brown. Dave Brown lives in Paris. Erin Green visited Oslo.";
+        let processed = TextProcessor::new().unwrap().process_text(text, SourceType::Document).unwrap();
+        let extraction = EntityExtractor::default().extract_from_text(&processed).await.unwrap();
+        let graph = GraphBuilder::default().build_graph(&extraction, text).unwrap();
+        let config = ChunkConfig { max_chunk_nodes: 3, ..ChunkConfig::default() };
+
+        let (manifest, chunks) = chunk_graph(&graph, &config);
+        assert!(chunks.len() > 1);
+        assert_eq!(manifest.chunks.len(), chunks.len());
+        assert_eq!(manifest.total_nodes, graph.nodes.len());
+
+        let mut seen = HashSet::new();
+        let mut chunk_of = HashMap::new();
+        for chunk in &chunks {
+            assert!(!chunk.nodes.is_empty() && chunk.nodes.len() <= 3);
+            for node in &chunk.nodes {
+                assert!(seen.insert(node.id.clone()), "{} is in two chunks", node.id);
+                assert!(node.x.is_some() && node.y.is_some() && !node.physics);
+                chunk_of.insert(node.id.clone(), chunk.id);
+            }
+        }
+        assert_eq!(seen.len(), graph.nodes.len());
+
+        // Every edge is in the chunks of both ends; cross-chunk edges are counted in the links
+        let mut crossing = 0;
+        for edge in &graph.edges {
+            let (from, to) = (chunk_of[&edge.from], chunk_of[&edge.to]);
+            for chunk in [from, to] {
+                assert!(chunks[chunk].edges.iter().any(|e| e.id == edge.id));
+            }
+            crossing += usize::from(from != to);
+        }
+        assert_eq!(manifest.links.iter().map(|link| link.edges).sum::<usize>(), crossing);
+
+        for (i, a) in manifest.chunks.iter().enumerate() {
+            assert_eq!(a.top_labels[0], chunks[i].nodes[0].label);
+            for b in &manifest.chunks[i + 1..] {
+                assert!((a.x - b.x).hypot(a.y - b.y) >= a.radius + b.radius, "chunks {} and {} overlap", a.id, b.id);
+            }
+        }
+
+        let directory = tempfile::tempdir().unwrap();
+        write_chunks(&manifest, &chunks, directory.path()).unwrap();
+        let written: ChunkManifest =
+            serde_json::from_str(&fs::read_to_string(directory.path().join(CHUNK_MANIFEST_FILE)).unwrap()).unwrap();
+        assert_eq!(written.chunks, manifest.chunks);
+        let first: GraphChunk =
+            serde_json::from_str(&fs::read_to_string(directory.path().join(&manifest.chunks[0].file)).unwrap()).unwrap();
+        assert_eq!(first.nodes.len(), manifest.chunks[0].nodes);
+    }
+}
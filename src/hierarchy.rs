@@ -0,0 +1,419 @@
+use crate::entity_extractor::{Entity, ExtractionResult, Relationship, RelationshipType, TextPosition};
+use crate::graph_builder::{EdgeType, GraphEdge, InteractiveGraph};
+use crate::relationship_rules::{RelationshipRule, RULE_CONFIDENCE};
+use crate::text_processor::ProcessedText;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::Write;
+use std::sync::OnceLock;
+use uuid::Uuid;
+
+/// Optional article before a group or organization
+const THE: &str = "(?:the |a |an )?";
+
+/// Built-in hierarchy rules with `{subject}` as the parent and `{object}` as the child,
+/// so every link points down the hierarchy whichever way the sentence runs
+const RULES: &[&str] = &[
+    r"{subject} (?:manages|managed|supervises|supervised|leads|led|heads|headed|oversees|oversaw) {THE}{object} -> manages",
+    r"{object} (?:is|was|are|were) (?:managed|supervised|led|headed|overseen) by {subject} -> manages",
+    r"{object} (?:reports|reported|report) (?:directly )?to {subject} -> manages",
+    r"{object} (?:is|are|was|were) (?:a |an |the )?(?:part|member|members|subsidiary|division|unit|branch|department) of {THE}{subject} -> contains",
+    r"{object} (?:belongs|belonged|belong) to {THE}{subject} -> contains",
+    r"{subject} (?:includes|included|contains|contained|comprises|comprised|consists of) {THE}{object} -> contains",
+];
+
+/// Words dropped from the front of an extracted relationship's label before it is
+/// matched against [`LINK_PHRASES`]
+const AUXILIARIES: &[&str] = &["is", "are", "was", "were", "has", "have", "been", "a", "an", "the", "directly"];
+
+/// Relationship labels that describe a hierarchy: the label of the parent-to-child link
+/// and whether the phrase names the child first ("Bob reports to Alice")
+const LINK_PHRASES: &[(&str, &str, bool)] = &[
+    ("manages", "manages", false),
+    ("managed", "manages", false),
+    ("supervises", "manages", false),
+    ("leads", "manages", false),
+    ("heads", "manages", false),
+    ("oversees", "manages", false),
+    ("managed by", "manages", true),
+    ("supervised by", "manages", true),
+    ("led by", "manages", true),
+    ("headed by", "manages", true),
+    ("reports to", "manages", true),
+    ("reported to", "manages", true),
+    ("part of", "contains", true),
+    ("member of", "contains", true),
+    ("subsidiary of", "contains", true),
+    ("division of", "contains", true),
+    ("belongs to", "contains", true),
+    ("contains", "contains", false),
+    ("includes", "contains", false),
+    ("comprises", "contains", false),
+    ("consists of", "contains", false),
+];
+
+/// A cycle found among the inferred links, broken by dropping its least confident link
+#[derive(Debug, Clone, PartialEq)]
+pub struct HierarchyCycle {
+    /// Entity names around the cycle, starting and ending with the same name
+    pub names: Vec<String>,
+    /// Parent and child of the dropped link
+    pub dropped: (String, String),
+}
+
+impl fmt::Display for HierarchyCycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (dropped {} -> {})", self.names.join(" -> "), self.dropped.0, self.dropped.1)
+    }
+}
+
+/// The built-in rules, compiled once per process
+fn compiled_rules() -> &'static [RelationshipRule] {
+    static COMPILED: OnceLock<Vec<RelationshipRule>> = OnceLock::new();
+    COMPILED.get_or_init(|| {
+        RULES
+            .iter()
+            .map(|rule| {
+                rule.replace("{THE}", THE)
+                    .parse::<RelationshipRule>()
+                    .expect("built-in hierarchy rules are valid")
+                    .adjacent()
+            })
+            .collect()
+    })
+}
+
+/// Parent-to-child label and child-first flag of an extracted relationship that
+/// describes a hierarchy, by its label or else its type
+fn hierarchy_relation(relationship: &Relationship) -> Option<(&'static str, bool)> {
+    let label = relationship.label.to_lowercase();
+    let phrase = label
+        .split_whitespace()
+        .skip_while(|word| AUXILIARIES.contains(word))
+        .collect::<Vec<_>>()
+        .join(" ");
+    LINK_PHRASES
+        .iter()
+        .find(|(link, _, _)| *link == phrase)
+        .map(|(_, parent_label, child_first)| (*parent_label, *child_first))
+        .or(match relationship.relationship_type {
+            RelationshipType::PartOf => Some(("contains", true)),
+            RelationshipType::Contains => Some(("contains", false)),
+            _ => None,
+        })
+}
+
+/// A parent-to-child link
+fn link(parent: &str, child: &str, label: &str, confidence: f64, position: Option<TextPosition>, context: Option<String>) -> Relationship {
+    Relationship {
+        id: Uuid::new_v4().to_string(),
+        source_entity_id: parent.to_string(),
+        target_entity_id: child.to_string(),
+        relationship_type: if label == "contains" {
+            RelationshipType::Contains
+        } else {
+            RelationshipType::Other(label.to_string())
+        },
+        label: label.to_string(),
+        confidence,
+        position,
+        bidirectional: false,
+        category: Some("hierarchical".to_string()),
+        context,
+    }
+}
+
+/// Turn an entity extraction into a hierarchy (`extraction.mode = "hierarchy"`): links
+/// found by the built-in manages / reports to / part of rules and extracted relationships
+/// with such labels, each pointing from parent to child. Other relationships, concepts and
+/// entities without a link are dropped. Cycles are broken by dropping their least
+/// confident link, and returned so the caller can report them.
+pub fn infer_hierarchy(processed_text: &ProcessedText, result: &mut ExtractionResult) -> Vec<HierarchyCycle> {
+    let mut links: Vec<Relationship> = result
+        .relationships
+        .iter()
+        .filter_map(|relationship| {
+            let (label, child_first) = hierarchy_relation(relationship)?;
+            let (parent, child) = if child_first {
+                (&relationship.target_entity_id, &relationship.source_entity_id)
+            } else {
+                (&relationship.source_entity_id, &relationship.target_entity_id)
+            };
+            Some(link(parent, child, label, relationship.confidence, relationship.position.clone(), relationship.context.clone()))
+        })
+        .collect();
+
+    for (sentence_index, sentence) in processed_text.sentences().enumerate() {
+        // Every entity named in the sentence, not only those first mentioned there
+        let sentence_entities: Vec<&Entity> = result.entities.iter().filter(|entity| sentence.contains(&entity.name)).collect();
+        if sentence_entities.len() < 2 {
+            continue;
+        }
+        for rule in compiled_rules() {
+            for found in rule.find(sentence, &sentence_entities) {
+                links.push(link(
+                    &found.subject.id,
+                    &found.object.id,
+                    &rule.label(),
+                    RULE_CONFIDENCE,
+                    Some(TextPosition {
+                        start: found.start,
+                        end: found.end,
+                        sentence_index,
+                    }),
+                    Some(sentence[found.start..found.end].to_string()),
+                ));
+            }
+        }
+    }
+
+    // One link per parent and child, the most confident
+    let mut best: HashMap<(String, String), usize> = HashMap::new();
+    let mut deduped: Vec<Relationship> = Vec::new();
+    for candidate in links {
+        let key = (candidate.source_entity_id.clone(), candidate.target_entity_id.clone());
+        match best.get(&key) {
+            Some(&index) if deduped[index].confidence >= candidate.confidence => {}
+            Some(&index) => deduped[index] = candidate,
+            None => {
+                best.insert(key, deduped.len());
+                deduped.push(candidate);
+            }
+        }
+    }
+    let mut links = deduped;
+
+    let names: HashMap<&str, &str> = result.entities.iter().map(|entity| (entity.id.as_str(), entity.name.as_str())).collect();
+    let name = |id: &str| names.get(id).copied().unwrap_or(id).to_string();
+    let mut cycles = Vec::new();
+    while let Some(cycle) = find_cycle(&links) {
+        // The least confident link goes; ties drop the one found last
+        let weakest = *cycle
+            .iter()
+            .min_by(|a, b| links[**a].confidence.total_cmp(&links[**b].confidence).then(b.cmp(a)))
+            .expect("a cycle has at least one link");
+        let mut cycle_names: Vec<String> = cycle.iter().map(|&index| name(&links[index].source_entity_id)).collect();
+        cycle_names.push(cycle_names[0].clone());
+        let dropped = links.remove(weakest);
+        cycles.push(HierarchyCycle {
+            names: cycle_names,
+            dropped: (name(&dropped.source_entity_id), name(&dropped.target_entity_id)),
+        });
+    }
+
+    let linked: HashSet<&str> = links
+        .iter()
+        .flat_map(|link| [link.source_entity_id.as_str(), link.target_entity_id.as_str()])
+        .collect();
+    let entities: Vec<Entity> = result.entities.iter().filter(|entity| linked.contains(entity.id.as_str())).cloned().collect();
+
+    result.entities = entities;
+    result.relationships = links;
+    result.concepts.clear();
+    result.metadata.total_entities = result.entities.len();
+    result.metadata.total_relationships = result.relationships.len();
+    result.metadata.total_concepts = 0;
+    cycles
+}
+
+/// Indices of the links around one cycle, each link's child the next one's parent
+fn find_cycle(links: &[Relationship]) -> Option<Vec<usize>> {
+    let mut outgoing: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (index, link) in links.iter().enumerate() {
+        outgoing.entry(link.source_entity_id.as_str()).or_default().push(index);
+    }
+
+    let mut finished: HashSet<&str> = HashSet::new();
+    for start in links.iter().map(|link| link.source_entity_id.as_str()) {
+        if finished.contains(start) {
+            continue;
+        }
+        // Depth-first from `start`: the nodes on the current path with the next link to
+        // try from each, and the links taken between them
+        let mut stack: Vec<(&str, usize)> = vec![(start, 0)];
+        let mut path: Vec<usize> = Vec::new();
+        while let Some(&(node, next)) = stack.last() {
+            match outgoing.get(node).and_then(|edges| edges.get(next)) {
+                Some(&edge) => {
+                    stack.last_mut().expect("path is not empty").1 += 1;
+                    let child = links[edge].target_entity_id.as_str();
+                    if let Some(position) = stack.iter().position(|(on_path, _)| *on_path == child) {
+                        let mut cycle = path[position..].to_vec();
+                        cycle.push(edge);
+                        return Some(cycle);
+                    }
+                    if !finished.contains(child) {
+                        stack.push((child, 0));
+                        path.push(edge);
+                    }
+                }
+                None => {
+                    finished.insert(node);
+                    stack.pop();
+                    path.pop();
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Indented outline of a hierarchy graph (`-f outline`), one Markdown list item per node
+/// under its parents, with the link label after each child:
+///
+/// ```text
+/// - Alice
+///   - Bob (manages)
+///     - Carol (manages)
+/// ```
+///
+/// Follows the hierarchy edges, or every relationship edge for graphs built in another
+/// mode. Roots are nodes without a parent; a node with several parents is listed under
+/// each of them.
+pub fn write_outline<W: Write>(graph: &InteractiveGraph, w: &mut W) -> std::io::Result<()> {
+    let hierarchy = graph.edges.iter().any(|edge| edge.edge_type == EdgeType::Hierarchy);
+    let wanted = if hierarchy { EdgeType::Hierarchy } else { EdgeType::EntityRelationship };
+    let edges: Vec<&GraphEdge> = graph.edges.iter().filter(|edge| edge.edge_type == wanted && edge.from != edge.to).collect();
+
+    let labels: HashMap<&str, &str> = graph.nodes.iter().map(|node| (node.id.as_str(), node.label.as_str())).collect();
+    let mut children: HashMap<&str, Vec<&GraphEdge>> = HashMap::new();
+    let mut has_parent: HashSet<&str> = HashSet::new();
+    for edge in &edges {
+        children.entry(edge.from.as_str()).or_default().push(edge);
+        has_parent.insert(edge.to.as_str());
+    }
+
+    let linked: Vec<&str> = graph
+        .nodes
+        .iter()
+        .map(|node| node.id.as_str())
+        .filter(|id| children.contains_key(id) || has_parent.contains(id))
+        .collect();
+    let mut written: HashSet<&str> = HashSet::new();
+    for root in linked.iter().filter(|id| !has_parent.contains(*id)) {
+        write_subtree(w, root, None, 0, &labels, &children, &mut Vec::new(), &mut written)?;
+    }
+    // Nodes only reachable around a cycle, when the graph has one
+    for node in &linked {
+        if !written.contains(node) {
+            write_subtree(w, node, None, 0, &labels, &children, &mut Vec::new(), &mut written)?;
+        }
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn write_subtree<'a, W: Write>(
+    w: &mut W,
+    node: &'a str,
+    link_label: Option<&str>,
+    depth: usize,
+    labels: &HashMap<&str, &str>,
+    children: &HashMap<&'a str, Vec<&'a GraphEdge>>,
+    path: &mut Vec<&'a str>,
+    written: &mut HashSet<&'a str>,
+) -> std::io::Result<()> {
+    let label = labels.get(node).copied().unwrap_or(node);
+    match link_label.filter(|link_label| !link_label.is_empty()) {
+        Some(link_label) => writeln!(w, "{}- {} ({})", "  ".repeat(depth), label, link_label)?,
+        None => writeln!(w, "{}- {}", "  ".repeat(depth), label)?,
+    }
+    written.insert(node);
+    path.push(node);
+    for edge in children.get(node).map(Vec::as_slice).unwrap_or(&[]) {
+        // A child already on the path would repeat the cycle forever
+        if !path.contains(&edge.to.as_str()) {
+            write_subtree(w, &edge.to, Some(&edge.label), depth + 1, labels, children, path, written)?;
+        }
+    }
+    path.pop();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GraphConfig;
+    use crate::cooccurrence::ExtractionMode;
+    use crate::entity_extractor::{EntityType, ExtractionMetadata};
+    use crate::pipeline::Pipeline;
+    use crate::text_processor::{SourceType, TextProcessor};
+
+    fn entity(name: &str, entity_type: EntityType) -> Entity {
+        Entity {
+            id: name.to_lowercase(),
+            name: name.to_string(),
+            entity_type,
+            attributes: Vec::new(),
+            confidence: 0.8,
+            position: None,
+            mentions: 1,
+            disambiguation: None,
+        }
+    }
+
+    #[test]
+    fn test_links_point_from_parent_to_child_and_cycles_are_broken() {
+        let text = "Alice manages Bob. Carol reports to Bob. Dave reports to Carol. Alice reports to Dave. \
+                    Bob is part of Acme. Alice likes Erin.";
+        let processed = TextProcessor::new().unwrap().process_text(text, SourceType::Document).unwrap();
+        let mut result = ExtractionResult {
+            entities: ["Alice", "Bob", "Carol", "Dave", "Erin"]
+                .iter()
+                .map(|name| entity(name, EntityType::Person))
+                .chain([entity("Acme", EntityType::Organization)])
+                .collect(),
+            relationships: Vec::new(),
+            concepts: Vec::new(),
+            metadata: ExtractionMetadata {
+                total_entities: 6,
+                total_relationships: 0,
+                total_concepts: 0,
+                processing_time_ms: 0,
+                confidence_threshold: 0.5,
+                extraction_method: "Pattern-based".to_string(),
+            },
+        };
+
+        let cycles = infer_hierarchy(&processed, &mut result);
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].to_string(), "Alice -> Bob -> Carol -> Dave -> Alice (dropped Dave -> Alice)");
+
+        let pairs: Vec<(&str, &str, &str)> = result
+            .relationships
+            .iter()
+            .map(|r| (r.source_entity_id.as_str(), r.target_entity_id.as_str(), r.label.as_str()))
+            .collect();
+        assert_eq!(
+            pairs,
+            [("alice", "bob", "manages"), ("bob", "carol", "manages"), ("carol", "dave", "manages"), ("acme", "bob", "contains")]
+        );
+        // Erin has no hierarchy link
+        assert!(result.entities.iter().all(|e| e.name != "Erin"));
+        assert_eq!(result.metadata.total_entities, 5);
+    }
+
+    #[tokio::test]
+    async fn test_hierarchy_mode_builds_a_layered_tree_and_outline() {
+        let mut config = GraphConfig::default();
+        config.extraction.mode = ExtractionMode::Hierarchy;
+        let graph = Pipeline::new()
+            .with_config(config)
+            .with_text("Alice manages Bob. Carol reports to Bob. Bob is part of Acme Labs.")
+            .run()
+            .await
+            .expect("Pipeline failed");
+
+        assert!(graph.edges.iter().all(|edge| edge.edge_type == EdgeType::Hierarchy));
+        let y = |label: &str| graph.nodes.iter().find(|node| node.label == label).unwrap().y.unwrap();
+        assert!(y("Alice") < y("Bob") && y("Bob") < y("Carol"));
+        assert_eq!(y("Alice"), y("Acme Labs"));
+
+        let mut outline = Vec::new();
+        write_outline(&graph, &mut outline).unwrap();
+        assert_eq!(
+            String::from_utf8(outline).unwrap(),
+            "- Alice\n  - Bob (manages)\n    - Carol (manages)\n- Acme Labs\n  - Bob (contains)\n    - Carol (manages)\n"
+        );
+    }
+}
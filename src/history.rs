@@ -0,0 +1,310 @@
+use crate::error::{GraphError, Result};
+use crate::graph_builder::{GraphEdge, GraphMetadata, GraphNode, InteractiveGraph, NodeType};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// A reversible transformation of an [`InteractiveGraph`].
+///
+/// Operations are plain data so the history doubles as a serializable audit trail of
+/// how a graph was derived from the extraction output.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GraphOperation {
+    /// Remove the given nodes and every edge touching them
+    RemoveNodes { node_ids: Vec<String> },
+    /// Remove the given edges
+    RemoveEdges { edge_ids: Vec<String> },
+    /// Keep only nodes of the listed types (and edges between kept nodes)
+    FilterNodeTypes { keep: Vec<NodeType> },
+    /// Remove nodes whose confidence is below the threshold
+    FilterMinConfidence { min_confidence: f64 },
+    /// Remove nodes with no edges
+    PruneIsolated,
+    /// Fold `sources` into `target`: their edges are re-pointed at `target` and they are removed
+    MergeNodes { target: String, sources: Vec<String> },
+}
+
+impl GraphOperation {
+    /// Short human-readable description for audit output
+    pub fn describe(&self) -> String {
+        match self {
+            GraphOperation::RemoveNodes { node_ids } => format!("remove {} node(s)", node_ids.len()),
+            GraphOperation::RemoveEdges { edge_ids } => format!("remove {} edge(s)", edge_ids.len()),
+            GraphOperation::FilterNodeTypes { keep } => format!("keep node types {:?}", keep),
+            GraphOperation::FilterMinConfidence { min_confidence } => {
+                format!("drop nodes with confidence < {:.2}", min_confidence)
+            }
+            GraphOperation::PruneIsolated => "prune isolated nodes".to_string(),
+            GraphOperation::MergeNodes { target, sources } => {
+                format!("merge {} node(s) into {}", sources.len(), target)
+            }
+        }
+    }
+}
+
+/// What it takes to put the graph back the way it was before an operation
+#[derive(Debug, Clone)]
+struct UndoData {
+    /// Edges changed in place, with their index and previous value
+    modified_edges: Vec<(usize, GraphEdge)>,
+    /// Removed nodes and edges with their original indices, ascending
+    removed_nodes: Vec<(usize, GraphNode)>,
+    removed_edges: Vec<(usize, GraphEdge)>,
+    metadata: GraphMetadata,
+}
+
+/// An applied operation in the history
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub operation: GraphOperation,
+    pub nodes_removed: usize,
+    pub edges_removed: usize,
+    undo: UndoData,
+}
+
+/// An [`InteractiveGraph`] together with the operations applied to it, supporting undo and redo
+#[derive(Debug, Clone)]
+pub struct GraphHistory {
+    graph: InteractiveGraph,
+    applied: Vec<HistoryEntry>,
+    undone: Vec<GraphOperation>,
+}
+
+impl GraphHistory {
+    pub fn new(graph: InteractiveGraph) -> Self {
+        Self {
+            graph,
+            applied: Vec::new(),
+            undone: Vec::new(),
+        }
+    }
+
+    /// The graph with all applied operations
+    pub fn graph(&self) -> &InteractiveGraph {
+        &self.graph
+    }
+
+    pub fn into_graph(self) -> InteractiveGraph {
+        self.graph
+    }
+
+    /// Applied operations, oldest first: the audit trail of how the current graph was derived
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.applied
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.applied.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    /// Apply an operation and record it. Clears the redo stack.
+    pub fn apply(&mut self, operation: GraphOperation) -> Result<&HistoryEntry> {
+        self.undone.clear();
+        self.apply_recorded(operation)
+    }
+
+    fn apply_recorded(&mut self, operation: GraphOperation) -> Result<&HistoryEntry> {
+        let metadata = self.graph.metadata.clone();
+        let mut modified_edges = Vec::new();
+
+        let remove_nodes: HashSet<String> = match &operation {
+            GraphOperation::RemoveNodes { node_ids } => node_ids.iter().cloned().collect(),
+            GraphOperation::RemoveEdges { .. } => HashSet::new(),
+            GraphOperation::FilterNodeTypes { keep } => self
+                .graph
+                .nodes
+                .iter()
+                .filter(|node| !keep.contains(&node.node_type))
+                .map(|node| node.id.clone())
+                .collect(),
+            GraphOperation::FilterMinConfidence { min_confidence } => self
+                .graph
+                .nodes
+                .iter()
+                .filter(|node| node.metadata.confidence < *min_confidence)
+                .map(|node| node.id.clone())
+                .collect(),
+            GraphOperation::PruneIsolated => {
+                let connected: HashSet<&str> = self
+                    .graph
+                    .edges
+                    .iter()
+                    .flat_map(|edge| [edge.from.as_str(), edge.to.as_str()])
+                    .collect();
+                self.graph
+                    .nodes
+                    .iter()
+                    .filter(|node| !connected.contains(node.id.as_str()))
+                    .map(|node| node.id.clone())
+                    .collect()
+            }
+            GraphOperation::MergeNodes { target, sources } => {
+                if !self.graph.nodes.iter().any(|node| &node.id == target) {
+                    return Err(GraphError::GraphBuilding(format!("Merge target {} does not exist", target)));
+                }
+                let sources: HashSet<String> = sources.iter().filter(|id| *id != target).cloned().collect();
+                for (index, edge) in self.graph.edges.iter_mut().enumerate() {
+                    if sources.contains(&edge.from) || sources.contains(&edge.to) {
+                        modified_edges.push((index, edge.clone()));
+                        if sources.contains(&edge.from) {
+                            edge.from = target.clone();
+                        }
+                        if sources.contains(&edge.to) {
+                            edge.to = target.clone();
+                        }
+                    }
+                }
+                sources
+            }
+        };
+
+        let remove_edges: HashSet<String> = match &operation {
+            GraphOperation::RemoveEdges { edge_ids } => edge_ids.iter().cloned().collect(),
+            // Merging can turn an edge between two merged nodes into a self-loop
+            GraphOperation::MergeNodes { target, .. } => self
+                .graph
+                .edges
+                .iter()
+                .filter(|edge| &edge.from == target && &edge.to == target)
+                .map(|edge| edge.id.clone())
+                .collect(),
+            _ => HashSet::new(),
+        };
+
+        let removed_edges = drain_indexed(&mut self.graph.edges, |edge| {
+            remove_edges.contains(&edge.id) || remove_nodes.contains(&edge.from) || remove_nodes.contains(&edge.to)
+        });
+        let removed_nodes = drain_indexed(&mut self.graph.nodes, |node| remove_nodes.contains(&node.id));
+        self.graph.refresh_metadata_counts();
+
+        self.applied.push(HistoryEntry {
+            operation,
+            nodes_removed: removed_nodes.len(),
+            edges_removed: removed_edges.len(),
+            undo: UndoData {
+                modified_edges,
+                removed_nodes,
+                removed_edges,
+                metadata,
+            },
+        });
+        Ok(self.applied.last().expect("entry was just pushed"))
+    }
+
+    /// Revert the most recent operation, returning it
+    pub fn undo(&mut self) -> Option<GraphOperation> {
+        let entry = self.applied.pop()?;
+        let undo = entry.undo;
+
+        for (index, node) in undo.removed_nodes {
+            self.graph.nodes.insert(index, node);
+        }
+        for (index, edge) in undo.removed_edges {
+            self.graph.edges.insert(index, edge);
+        }
+        for (index, edge) in undo.modified_edges {
+            self.graph.edges[index] = edge;
+        }
+        self.graph.metadata = undo.metadata;
+
+        self.undone.push(entry.operation.clone());
+        Some(entry.operation)
+    }
+
+    /// Re-apply the most recently undone operation
+    pub fn redo(&mut self) -> Result<Option<&HistoryEntry>> {
+        match self.undone.pop() {
+            Some(operation) => self.apply_recorded(operation).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Remove matching items, returning them with their original indices in ascending order
+fn drain_indexed<T>(items: &mut Vec<T>, mut remove: impl FnMut(&T) -> bool) -> Vec<(usize, T)> {
+    let mut removed = Vec::new();
+    let mut kept = Vec::with_capacity(items.len());
+    for (index, item) in items.drain(..).enumerate() {
+        if remove(&item) {
+            removed.push((index, item));
+        } else {
+            kept.push(item);
+        }
+    }
+    *items = kept;
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::Pipeline;
+
+    async fn sample_graph() -> InteractiveGraph {
+        Pipeline::new()
+            .with_text("Alice works at TechCorp. Bob is connected to Alice. Carol has the database system.")
+            .run()
+            .await
+            .expect("Pipeline failed")
+    }
+
+    fn ids(graph: &InteractiveGraph) -> (Vec<String>, Vec<(String, String, String)>) {
+        (
+            graph.nodes.iter().map(|n| n.id.clone()).collect(),
+            graph.edges.iter().map(|e| (e.id.clone(), e.from.clone(), e.to.clone())).collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_undo_restores_graph_exactly() {
+        let graph = sample_graph().await;
+        let original = ids(&graph);
+        let node_ids: Vec<String> = graph.nodes.iter().map(|n| n.id.clone()).collect();
+        let mut history = GraphHistory::new(graph);
+
+        history
+            .apply(GraphOperation::MergeNodes { target: node_ids[0].clone(), sources: vec![node_ids[1].clone()] })
+            .expect("Merge failed");
+        history
+            .apply(GraphOperation::FilterNodeTypes { keep: vec![NodeType::Entity] })
+            .expect("Filter failed");
+        history.apply(GraphOperation::PruneIsolated).expect("Prune failed");
+        assert_eq!(history.entries().len(), 3);
+        assert!(history.graph().nodes.iter().all(|n| n.node_type == NodeType::Entity));
+        assert_eq!(history.graph().metadata.total_nodes, history.graph().nodes.len());
+
+        while history.undo().is_some() {}
+        assert_eq!(ids(history.graph()), original);
+        assert!(!history.can_undo());
+    }
+
+    #[tokio::test]
+    async fn test_redo_reapplies_and_new_operation_clears_redo() {
+        let graph = sample_graph().await;
+        let mut history = GraphHistory::new(graph);
+
+        let removed = history.apply(GraphOperation::FilterMinConfidence { min_confidence: 0.9 }).unwrap().nodes_removed;
+        let after = ids(history.graph());
+        history.undo();
+        assert!(history.can_redo());
+
+        let redone = history.redo().unwrap().expect("Nothing to redo");
+        assert_eq!(redone.nodes_removed, removed);
+        assert_eq!(ids(history.graph()), after);
+
+        history.undo();
+        history.apply(GraphOperation::PruneIsolated).unwrap();
+        assert!(!history.can_redo());
+    }
+
+    #[tokio::test]
+    async fn test_merge_into_missing_target_fails() {
+        let mut history = GraphHistory::new(sample_graph().await);
+        let result = history.apply(GraphOperation::MergeNodes { target: "missing".to_string(), sources: vec![] });
+        assert!(result.is_err());
+        assert!(history.entries().is_empty());
+    }
+}
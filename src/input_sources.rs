@@ -0,0 +1,246 @@
+//! Input that does not come from a file: the system clipboard and web pages.
+//!
+//! Pages are reduced to their main content the way readability tools do it: paragraphs
+//! are scored by length and commas, the scores go to their containers, containers full
+//! of links or with navigation-like class names lose out, and only the text blocks of
+//! the best container are kept. Menus, sidebars, footers and comment sections do not
+//! turn into entities.
+
+use crate::error::{GraphError, Result};
+use crate::source_format::end_sentence;
+use ego_tree::NodeId;
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use std::collections::HashMap;
+
+/// Elements that never hold main content
+const BOILERPLATE_TAGS: &[&str] = &[
+    "nav", "header", "footer", "aside", "script", "style", "noscript", "form", "iframe", "svg", "button", "template",
+];
+
+/// Text blocks kept from the main content container
+const TEXT_BLOCKS: &str = "h1, h2, h3, h4, h5, h6, p, li, blockquote, pre, figcaption";
+
+/// Paragraphs shorter than this are not scored
+const MIN_PARAGRAPH_CHARS: usize = 25;
+
+/// Main content of a web page
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebPage {
+    pub url: String,
+    pub title: Option<String>,
+    /// Text blocks in document order, whitespace collapsed
+    pub paragraphs: Vec<String>,
+}
+
+impl WebPage {
+    /// Keep only the main content of `html`
+    pub fn from_html(url: &str, html: &str) -> Self {
+        let document = Html::parse_document(html);
+        let title = select_text(&document, "meta[property='og:title']", |element| element.attr("content").map(str::to_string))
+            .or_else(|| select_text(&document, "title", |element| Some(collapse(element.text()))))
+            .or_else(|| select_text(&document, "h1", |element| Some(collapse(element.text()))));
+
+        let paragraphs = match main_content(&document) {
+            Some(container) => text_blocks(container, title.as_deref()),
+            None => Vec::new(),
+        };
+        Self { url: url.to_string(), title, paragraphs }
+    }
+
+    /// Title and paragraphs as text for the pipeline, one block per line
+    pub fn to_text(&self) -> String {
+        self.title
+            .iter()
+            .chain(&self.paragraphs)
+            .map(|block| end_sentence(block))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+/// Download `url` and keep its main content; non-HTML responses are used as they are
+pub async fn fetch_url(url: &str) -> Result<WebPage> {
+    let response = reqwest::Client::builder()
+        .user_agent(concat!("msg_net/", env!("CARGO_PKG_VERSION")))
+        .build()?
+        .get(url)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(GraphError::TextProcessing(format!("{} returned error status: {}", url, response.status())));
+    }
+    let is_html = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_none_or(|value| value.contains("html"));
+    let body = response.text().await?;
+    if is_html {
+        Ok(WebPage::from_html(url, &body))
+    } else {
+        let paragraphs = body.split("\n\n").map(str::trim).filter(|block| !block.is_empty()).map(str::to_string).collect();
+        Ok(WebPage { url: url.to_string(), title: None, paragraphs })
+    }
+}
+
+/// Text currently on the system clipboard
+pub fn read_clipboard() -> Result<String> {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .map_err(|e| GraphError::Configuration(format!("Cannot read text from the clipboard: {}", e)))
+}
+
+fn select_text(document: &Html, selector: &str, text: impl Fn(ElementRef) -> Option<String>) -> Option<String> {
+    let selector = Selector::parse(selector).ok()?;
+    document.select(&selector).find_map(text).map(|title| title.trim().to_string()).filter(|title| !title.is_empty())
+}
+
+fn collapse<'a>(text: impl Iterator<Item = &'a str>) -> String {
+    text.flat_map(str::split_whitespace).collect::<Vec<_>>().join(" ")
+}
+
+/// -25 for class and id names of page chrome, +25 for names of article bodies
+fn class_weight(element: ElementRef) -> f64 {
+    let names = format!("{} {}", element.attr("class").unwrap_or(""), element.attr("id").unwrap_or("")).to_lowercase();
+    let negative = Regex::new(r"comment|sidebar|footer|footnote|menu|nav|share|social|promo|sponsor|banner|cookie|related|widget|masthead|breadcrumb|\bads?\b")
+        .expect("valid regex");
+    let positive = Regex::new(r"article|content|entry|main|post|story|text|body").expect("valid regex");
+    let mut weight = 0.0;
+    if negative.is_match(&names) {
+        weight -= 25.0;
+    }
+    if positive.is_match(&names) {
+        weight += 25.0;
+    }
+    weight
+}
+
+fn is_boilerplate(element: ElementRef) -> bool {
+    BOILERPLATE_TAGS.contains(&element.value().name()) || element.attr("role").is_some_and(|role| role == "navigation")
+}
+
+/// Whether `element` sits inside page chrome, up to (not including) `within`
+fn in_boilerplate(element: ElementRef, within: Option<NodeId>) -> bool {
+    std::iter::once(*element)
+        .chain(element.ancestors())
+        .take_while(|node| Some(node.id()) != within)
+        .filter_map(ElementRef::wrap)
+        .any(|element| is_boilerplate(element) || class_weight(element) < 0.0 && element.value().name() != "body")
+}
+
+/// Share of the text that is link text
+fn link_density(element: ElementRef) -> f64 {
+    let total = collapse(element.text()).len();
+    if total == 0 {
+        return 1.0;
+    }
+    let anchors = Selector::parse("a").expect("valid selector");
+    let linked: usize = element.select(&anchors).map(|anchor| collapse(anchor.text()).len()).sum();
+    linked as f64 / total as f64
+}
+
+/// The container with the best paragraph score, or `<article>`, `<main>` or `<body>`
+fn main_content(document: &Html) -> Option<ElementRef<'_>> {
+    let paragraphs = Selector::parse("p, pre, blockquote").expect("valid selector");
+    let mut scores: HashMap<NodeId, f64> = HashMap::new();
+    for paragraph in document.select(&paragraphs) {
+        if in_boilerplate(paragraph, None) {
+            continue;
+        }
+        let text = collapse(paragraph.text());
+        if text.chars().count() < MIN_PARAGRAPH_CHARS {
+            continue;
+        }
+        let score = 1.0 + text.matches(',').count() as f64 + (text.len() as f64 / 100.0).min(3.0);
+        let mut ancestors = paragraph.ancestors().filter_map(ElementRef::wrap);
+        if let Some(parent) = ancestors.next() {
+            *scores.entry(parent.id()).or_insert(class_weight(parent)) += score;
+        }
+        if let Some(grandparent) = ancestors.next() {
+            *scores.entry(grandparent.id()).or_insert(class_weight(grandparent)) += score / 2.0;
+        }
+    }
+
+    let best = scores
+        .into_iter()
+        .filter_map(|(id, score)| document.tree.get(id).and_then(ElementRef::wrap).map(|element| (element, score)))
+        .map(|(element, score)| (element, score * (1.0 - link_density(element))))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(element, _)| element);
+    best.or_else(|| {
+        ["article", "main", "body"]
+            .iter()
+            .find_map(|tag| document.select(&Selector::parse(tag).expect("valid selector")).next())
+    })
+}
+
+/// The text blocks of `container`, outermost only, without chrome nested inside it
+fn text_blocks(container: ElementRef, title: Option<&str>) -> Vec<String> {
+    let blocks = Selector::parse(TEXT_BLOCKS).expect("valid selector");
+    let within = Some(container.id());
+    let mut paragraphs: Vec<String> = container
+        .select(&blocks)
+        .filter(|block| !in_boilerplate(*block, within))
+        .filter(|block| {
+            !block
+                .ancestors()
+                .take_while(|node| Some(node.id()) != within)
+                .filter_map(ElementRef::wrap)
+                .any(|ancestor| blocks.matches(&ancestor))
+        })
+        .map(|block| collapse(block.text()))
+        .filter(|text| !text.is_empty() && Some(text.as_str()) != title)
+        .collect();
+    if paragraphs.is_empty() && !in_boilerplate(container, None) {
+        let text = collapse(container.text());
+        if !text.is_empty() {
+            paragraphs.push(text);
+        }
+    }
+    paragraphs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"<!DOCTYPE html>
+<html><head><title>Initech opens Berlin office</title><script>var tracking = "Acme Analytics";</script></head>
+<body>
+  <header><nav><a href="/">Home</a> <a href="/world">World</a> <a href="/tech">Tech News Daily</a></nav></header>
+  <div class="sidebar"><p>Trending: Globex merger talks continue, sources say, as Hooli waits.</p></div>
+  <div id="main-content" class="article-body">
+    <h1>Initech opens Berlin office</h1>
+    <p>Initech announced on Monday that it has opened an office in Berlin, its first in Germany.</p>
+    <p>Chief executive Bill Lumbergh said the team, led by Peter Gibbons, would serve clients across Europe.</p>
+    <ul><li><p>Hiring starts in March</p></li></ul>
+    <div class="share-buttons"><p>Share this story on Chirper, Facebook and LinkedIn, please share!</p></div>
+  </div>
+  <div class="comments"><p>Great news, I hope Milton Waddams gets his stapler back, finally.</p></div>
+  <footer><p>Copyright Tech News Daily, all rights reserved, since 1999.</p></footer>
+</body></html>"#;
+
+    #[test]
+    fn test_keeps_main_content_and_drops_chrome() {
+        let page = WebPage::from_html("https://example.com/initech", PAGE);
+        assert_eq!(page.title.as_deref(), Some("Initech opens Berlin office"));
+        assert_eq!(page.paragraphs.len(), 3, "{:?}", page.paragraphs);
+        assert!(page.paragraphs[0].starts_with("Initech announced on Monday"));
+        assert_eq!(page.paragraphs[2], "Hiring starts in March");
+
+        let text = page.to_text();
+        assert!(text.starts_with("Initech opens Berlin office.\n\nInitech announced"));
+        assert!(text.ends_with("Hiring starts in March."));
+        for chrome in ["Globex", "Chirper", "Milton", "Copyright", "World", "Acme"] {
+            assert!(!text.contains(chrome), "{} leaked into {}", chrome, text);
+        }
+    }
+
+    #[test]
+    fn test_falls_back_to_body_without_paragraphs() {
+        let page = WebPage::from_html("https://example.com", "<html><body><div>Alice met Bob.</div></body></html>");
+        assert_eq!(page.title, None);
+        assert_eq!(page.to_text(), "Alice met Bob.");
+    }
+}
@@ -0,0 +1,262 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// How candidate phrases are ranked
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeywordMethod {
+    /// Phrase frequency weighted by how few sentences contain it, sentences acting as documents
+    #[default]
+    Tfidf,
+    /// Rapid Automatic Keyword Extraction: sum of word degree/frequency ratios
+    Rake,
+}
+
+/// Settings for keyword concepts (`extraction.keywords`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeywordConfig {
+    /// Add the top-ranked keywords as concept nodes in pattern mode
+    pub enabled: bool,
+    pub method: KeywordMethod,
+    /// Number of keywords to keep
+    pub top_n: usize,
+    /// Longest candidate phrase, in words
+    pub max_words: usize,
+    /// Ignore single-word candidates shorter than this
+    pub min_word_length: usize,
+    /// Use keywords instead of `concept_patterns` rather than alongside them
+    pub replace_patterns: bool,
+}
+
+impl Default for KeywordConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            method: KeywordMethod::Tfidf,
+            top_n: 10,
+            max_words: 3,
+            min_word_length: 4,
+            replace_patterns: false,
+        }
+    }
+}
+
+/// A ranked phrase and where it first occurs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyword {
+    /// Surface form of the first occurrence
+    pub phrase: String,
+    pub score: f64,
+    pub sentence_index: usize,
+    /// Byte offsets within the sentence
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A run of consecutive content words within one sentence
+struct Run<'a> {
+    words: Vec<&'a str>,
+    /// Byte span of each word in the sentence
+    spans: Vec<(usize, usize)>,
+    sentence_index: usize,
+}
+
+/// A phrase considered for ranking: `len` words of a run starting at `offset`
+struct Candidate {
+    run: usize,
+    offset: usize,
+    len: usize,
+}
+
+/// Split each sentence into runs of content words at stopwords, punctuation and numbers,
+/// as RAKE does
+fn runs<'a, S: AsRef<str>>(sentences: &'a [S], stopwords: &HashSet<String>) -> Vec<Run<'a>> {
+    let mut result = Vec::new();
+
+    for (sentence_index, sentence) in sentences.iter().enumerate() {
+        let mut current = Run { words: Vec::new(), spans: Vec::new(), sentence_index };
+        let mut offset = 0;
+        for token in sentence.as_ref().split_inclusive(char::is_whitespace) {
+            let token_start = offset;
+            offset += token.len();
+            let trimmed = token.trim_end();
+            let word = trimmed.trim_matches(|c: char| !c.is_alphanumeric());
+            let word_start = token_start + trimmed.find(word).unwrap_or(0);
+            let is_content = word.chars().count() > 1
+                && word.chars().all(|c| c.is_alphabetic() || c == '-')
+                && !stopwords.contains(&word.to_lowercase());
+
+            if is_content {
+                current.words.push(word);
+                current.spans.push((word_start, word_start + word.len()));
+            }
+            // A stopword, or punctuation around the word, ends the run
+            if (!is_content || trimmed.len() != word.len()) && !current.words.is_empty() {
+                let next = Run { words: Vec::new(), spans: Vec::new(), sentence_index };
+                result.push(std::mem::replace(&mut current, next));
+            }
+        }
+        if !current.words.is_empty() {
+            result.push(current);
+        }
+    }
+    result
+}
+
+fn phrase_key(words: &[&str]) -> String {
+    words.iter().map(|word| word.to_lowercase()).collect::<Vec<_>>().join(" ")
+}
+
+/// Rank multi-word terms across `sentences` and return the `top_n` best, highest score first.
+/// Words in `stopwords` split phrases and never start or end one.
+pub fn extract_keywords<S: AsRef<str>>(sentences: &[S], stopwords: &HashSet<String>, config: &KeywordConfig) -> Vec<Keyword> {
+    let runs = runs(sentences, stopwords);
+    let max_words = config.max_words.max(1);
+    let too_short = |words: &[&str]| words.len() == 1 && words[0].chars().count() < config.min_word_length;
+
+    // TF-IDF counts every n-gram inside a run; RAKE ranks whole runs
+    let candidates: Vec<Candidate> = runs
+        .iter()
+        .enumerate()
+        .flat_map(|(index, run)| {
+            let lengths: Vec<usize> = match config.method {
+                KeywordMethod::Tfidf => (1..=max_words.min(run.words.len())).collect(),
+                KeywordMethod::Rake if run.words.len() <= max_words => vec![run.words.len()],
+                KeywordMethod::Rake => Vec::new(),
+            };
+            lengths.into_iter().flat_map(move |len| {
+                (0..=run.words.len() - len).map(move |offset| Candidate { run: index, offset, len })
+            })
+        })
+        .filter(|c| !too_short(&runs[c.run].words[c.offset..c.offset + c.len]))
+        .collect();
+    if candidates.is_empty() || config.top_n == 0 {
+        return Vec::new();
+    }
+    let words_of = |c: &Candidate| &runs[c.run].words[c.offset..c.offset + c.len];
+
+    // First occurrence of each phrase, in text order
+    let mut first: HashMap<String, usize> = HashMap::new();
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut sentence_sets: HashMap<String, HashSet<usize>> = HashMap::new();
+    for (index, candidate) in candidates.iter().enumerate() {
+        let key = phrase_key(words_of(candidate));
+        first.entry(key.clone()).or_insert(index);
+        *counts.entry(key.clone()).or_insert(0) += 1;
+        sentence_sets.entry(key).or_default().insert(runs[candidate.run].sentence_index);
+    }
+
+    let scores: HashMap<&str, f64> = match config.method {
+        KeywordMethod::Tfidf => {
+            let documents = sentences.len().max(1) as f64;
+            let total = candidates.len() as f64;
+            // Highest count of any longer phrase containing each phrase; a phrase that
+            // only ever occurs inside a longer one adds nothing
+            let mut container_counts: HashMap<String, usize> = HashMap::new();
+            for (key, &count) in &counts {
+                let words: Vec<&str> = key.split(' ').collect();
+                for len in 1..words.len() {
+                    for part in words.windows(len) {
+                        let entry = container_counts.entry(part.join(" ")).or_insert(0);
+                        *entry = (*entry).max(count);
+                    }
+                }
+            }
+            counts
+                .iter()
+                .filter(|(key, &count)| container_counts.get(key.as_str()).copied().unwrap_or(0) < count)
+                .map(|(key, &count)| {
+                    let tf = count as f64 / total;
+                    let idf = (1.0 + documents / sentence_sets[key].len() as f64).ln();
+                    // Longer phrases are more specific, so they rank above their parts
+                    let length = key.split(' ').count() as f64;
+                    (key.as_str(), tf * idf * length.sqrt())
+                })
+                .collect()
+        }
+        KeywordMethod::Rake => {
+            // Word degree and frequency come from every run, however long
+            let mut frequency: HashMap<String, f64> = HashMap::new();
+            let mut degree: HashMap<String, f64> = HashMap::new();
+            for run in &runs {
+                for word in &run.words {
+                    let word = word.to_lowercase();
+                    *frequency.entry(word.clone()).or_insert(0.0) += 1.0;
+                    *degree.entry(word).or_insert(0.0) += run.words.len() as f64;
+                }
+            }
+            counts
+                .keys()
+                .map(|key| {
+                    let score = key.split(' ').map(|word| degree[word] / frequency[word]).sum();
+                    (key.as_str(), score)
+                })
+                .collect()
+        }
+    };
+
+    let mut ranked: Vec<(&str, f64)> = scores.into_iter().collect();
+    // Ties go to the phrase that appears first so output is stable
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(first[a.0].cmp(&first[b.0])));
+
+    ranked
+        .into_iter()
+        .take(config.top_n)
+        .map(|(key, score)| {
+            let candidate = &candidates[first[key]];
+            let run = &runs[candidate.run];
+            let start = run.spans[candidate.offset].0;
+            let end = run.spans[candidate.offset + candidate.len - 1].1;
+            Keyword {
+                phrase: sentences[run.sentence_index].as_ref()[start..end].to_string(),
+                score,
+                sentence_index: run.sentence_index,
+                start,
+                end,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text_processor::TextProcessor;
+
+    fn sentences() -> Vec<String> {
+        [
+            "The recommendation engine ranks products for each shopper",
+            "Our recommendation engine uses purchase history and browsing sessions",
+            "Purchase history is stored in the data warehouse",
+            "The data warehouse refreshes nightly",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+    }
+
+    #[test]
+    fn test_tfidf_ranks_repeated_multi_word_terms_first() {
+        let stopwords = TextProcessor::default_english_stopwords();
+        let keywords = extract_keywords(&sentences(), &stopwords, &KeywordConfig::default());
+
+        let phrases: Vec<&str> = keywords.iter().map(|k| k.phrase.as_str()).collect();
+        assert_eq!(&phrases[..3], &["recommendation engine", "purchase history", "data warehouse"]);
+        assert!(keywords.windows(2).all(|pair| pair[0].score >= pair[1].score));
+        let first = &keywords[0];
+        assert_eq!(&sentences()[first.sentence_index][first.start..first.end], "recommendation engine");
+        assert!(phrases.iter().all(|p| !p.starts_with("The ") && !p.contains(" and ")));
+    }
+
+    #[test]
+    fn test_rake_scores_and_top_n() {
+        let stopwords = TextProcessor::default_english_stopwords();
+        let config = KeywordConfig { method: KeywordMethod::Rake, top_n: 2, ..KeywordConfig::default() };
+        let keywords = extract_keywords(&sentences(), &stopwords, &config);
+
+        assert_eq!(keywords.len(), 2);
+        assert!(keywords.iter().all(|k| k.phrase.contains(' ')));
+        assert!(extract_keywords::<&str>(&[], &stopwords, &config).is_empty());
+    }
+}
@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Frequent function words by language, used to guess the language of a text
+const FUNCTION_WORDS: [(&str, &[&str]); 3] = [
+    ("english", &["the", "and", "or", "but", "in", "on", "at", "to", "for", "of", "with", "by"]),
+    (
+        "german",
+        &[
+            "der", "die", "das", "und", "ist", "nicht", "ein", "eine", "mit", "von", "zu", "den", "im", "auf", "für", "sich",
+            "dem", "des",
+        ],
+    ),
+    (
+        "spanish",
+        &["el", "la", "los", "las", "y", "de", "del", "en", "que", "un", "una", "por", "con", "para", "es", "se", "al"],
+    ),
+];
+
+/// Share of function words above which a text counts as written in that language
+const MIN_FUNCTION_WORD_RATIO: f64 = 0.1;
+
+/// `english`, `german` or `spanish` by the share of each language's function words, or
+/// `unknown` when none reaches 10%; English wins ties
+pub fn detect_language(text: &str) -> String {
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphabetic()).to_lowercase())
+        .collect();
+    if words.is_empty() {
+        return "unknown".to_string();
+    }
+
+    let mut best = ("unknown", MIN_FUNCTION_WORD_RATIO);
+    for (language, function_words) in FUNCTION_WORDS {
+        let count = words.iter().filter(|word| function_words.contains(&word.as_str())).count();
+        let ratio = count as f64 / words.len() as f64;
+        if ratio > best.1 {
+            best = (language, ratio);
+        }
+    }
+    best.0.to_string()
+}
+
+/// Extraction patterns and naming conventions of one language (`extraction.languages`),
+/// used instead of the English defaults when a text is detected as, or forced to, that
+/// language; an empty pattern list keeps the base one
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LanguagePatterns {
+    /// Entity regexes; a named group `entity` marks the name inside a longer match
+    /// ("Frau (?P<entity>Schmidt)")
+    pub entity_patterns: Vec<String>,
+    pub relationship_patterns: Vec<String>,
+    pub concept_patterns: Vec<String>,
+    /// Titles that mark the following name as a person ("Frau", "Sra.")
+    pub person_titles: Vec<String>,
+    /// Words in a name that make it an organization ("GmbH", "Universidad")
+    pub organization_keywords: Vec<String>,
+    /// Capitalized words stripped from the start of a match, such as sentence-initial
+    /// articles ("Die", "El"); titles are stripped too
+    pub leading_words: Vec<String>,
+    /// Whether a short capitalized name is taken for a person, as in English; off for
+    /// languages that capitalize every noun
+    pub capitalized_names: bool,
+}
+
+impl LanguagePatterns {
+    /// German: every noun is capitalized, so names need a title, a legal form, an acronym
+    /// or a second capitalized word
+    pub fn german() -> Self {
+        const NAME: &str = r"[A-ZÄÖÜ][a-zäöüß]+(?:-[A-ZÄÖÜ][a-zäöüß]+)?";
+        Self {
+            entity_patterns: vec![
+                format!(r"\b(?:Herr|Frau|Dr\.|Prof\.)\s+(?P<entity>{NAME}(?:\s+{NAME})?)"),
+                r"\b(?:[A-ZÄÖÜ][\w&.-]*\s+){1,3}(?:(?:GmbH|AG|KG|SE)\b|e\.V\.)".to_string(),
+                format!(r"\b{NAME}(?:\s+{NAME})+\b"),
+                r"\b[A-ZÄÖÜ]{3,}\b".to_string(),
+            ],
+            relationship_patterns: vec![
+                r"\b(?:hat|haben|ist|sind|war|waren|enthält|umfasst|besitzt|gehört|leitet|gründete|arbeitet)\b".to_string(),
+                r"\b(?:verbunden mit|verknüpft mit|zusammen mit|bezogen auf)\b".to_string(),
+            ],
+            concept_patterns: vec![
+                r"\b(?:Konzept|Idee|Prinzip|Theorie|Methode|Ansatz|Strategie)\b".to_string(),
+                r"\b(?:System|Prozess|Arbeitsablauf|Verfahren|Protokoll)\b".to_string(),
+            ],
+            person_titles: ["Herr", "Frau", "Dr.", "Prof."].map(String::from).to_vec(),
+            organization_keywords: ["GmbH", "AG", "KG", "SE", "e.V.", "Bank", "Universität", "Verein", "Ministerium"]
+                .map(String::from)
+                .to_vec(),
+            leading_words: ["Der", "Die", "Das", "Den", "Dem", "Des", "Ein", "Eine", "Einen", "Einem", "Einer", "Und"]
+                .map(String::from)
+                .to_vec(),
+            capitalized_names: false,
+        }
+    }
+
+    /// Spanish: names are capitalized as in English but may be joined by "de", "del" or
+    /// "de la" ("Banco de España")
+    pub fn spanish() -> Self {
+        const NAME: &str = r"[A-ZÁÉÍÓÚÑ][a-záéíóúñü]+";
+        Self {
+            entity_patterns: vec![
+                format!(r"\b{NAME}(?:\s+(?:(?:de|del|de la|de los)\s+)?{NAME})*\b"),
+                r"\b(?:persona|personas|individuo|usuario|cliente)\b".to_string(),
+            ],
+            relationship_patterns: vec![
+                r"\b(?:tiene|tienen|es|son|era|fue|contiene|incluye|posee|pertenece|dirige|fundó|trabaja)\b".to_string(),
+                r"\b(?:conectado con|relacionado con|asociado con|vinculado a)\b".to_string(),
+            ],
+            concept_patterns: vec![
+                r"\b(?:concepto|idea|principio|teoría|método|enfoque|estrategia)\b".to_string(),
+                r"\b(?:sistema|proceso|flujo de trabajo|procedimiento|protocolo)\b".to_string(),
+            ],
+            person_titles: ["Sr.", "Sra.", "Srta.", "Don", "Doña", "Dr.", "Dra."].map(String::from).to_vec(),
+            organization_keywords: ["S.A.", "S.L.", "Banco", "Universidad", "Ministerio", "Grupo", "Compañía", "Empresa"]
+                .map(String::from)
+                .to_vec(),
+            leading_words: ["El", "La", "Los", "Las", "Un", "Una", "En", "De", "Del", "Y", "Con", "Para", "Por"]
+                .map(String::from)
+                .to_vec(),
+            capitalized_names: true,
+        }
+    }
+}
+
+/// The built-in language configurations, keyed by the names `detect_language` returns
+pub fn default_languages() -> BTreeMap<String, LanguagePatterns> {
+    BTreeMap::from([
+        ("german".to_string(), LanguagePatterns::german()),
+        ("spanish".to_string(), LanguagePatterns::spanish()),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_language_by_function_words() {
+        assert_eq!(detect_language("The cat sat on the mat and purred at the dog."), "english");
+        assert_eq!(detect_language("Die Firma hat mit der Bank einen Vertrag für das Projekt."), "german");
+        assert_eq!(detect_language("La empresa firmó un contrato con el banco para el proyecto."), "spanish");
+        assert_eq!(detect_language("Lorem ipsum dolor sit amet"), "unknown");
+        assert_eq!(detect_language(""), "unknown");
+    }
+}
@@ -0,0 +1,329 @@
+//! Node placement computed in Rust, so positions are deterministic and available to the
+//! static exporters (DOT, TikZ, SVG) as well as the HTML viewer.
+//!
+//! Every function takes the graph as undirected neighbour lists (see [`neighbour_lists`])
+//! and returns one `(x, y)` per node, centered on the origin, with `spacing` as the
+//! ideal distance between neighbours.
+
+use crate::graph_builder::InteractiveGraph;
+use std::collections::{HashMap, VecDeque};
+
+/// Above this many nodes repulsion is only computed between nodes in neighbouring grid
+/// cells, the grid variant of Fruchterman-Reingold
+pub const EXACT_REPULSION_LIMIT: usize = 1000;
+
+/// Kamada-Kawai keeps a distance matrix and costs O(n²) per sweep; larger graphs are laid
+/// out with Fruchterman-Reingold instead
+pub const KAMADA_KAWAI_LIMIT: usize = 1000;
+
+/// Pull towards the origin, relative to a spring, that keeps components together
+const GRAVITY: f64 = 0.05;
+
+/// Newton-Raphson steps per node and Kamada-Kawai sweep
+const NEWTON_STEPS: usize = 5;
+
+const GOLDEN_ANGLE: f64 = 2.399_963_229_728_653;
+
+/// Undirected, deduplicated neighbours of every node (in node order), without self-loops
+/// and edges to missing nodes
+pub fn neighbour_lists(graph: &InteractiveGraph) -> Vec<Vec<usize>> {
+    let index: HashMap<&str, usize> = graph.nodes.iter().enumerate().map(|(i, node)| (node.id.as_str(), i)).collect();
+    let mut neighbours = vec![Vec::new(); graph.nodes.len()];
+    for edge in &graph.edges {
+        if let (Some(&from), Some(&to)) = (index.get(edge.from.as_str()), index.get(edge.to.as_str())) {
+            if from != to {
+                neighbours[from].push(to);
+                neighbours[to].push(from);
+            }
+        }
+    }
+    for list in &mut neighbours {
+        list.sort_unstable();
+        list.dedup();
+    }
+    neighbours
+}
+
+/// Fruchterman-Reingold: neighbours attract with d²/k, every pair repels with k²/d, and
+/// moves are capped by a temperature that cools linearly over `iterations`. Starts from
+/// a sunflower spiral, so the result only depends on the graph.
+pub fn fruchterman_reingold(neighbours: &[Vec<usize>], spacing: f64, iterations: usize) -> Vec<(f64, f64)> {
+    let n = neighbours.len();
+    let k = spacing.max(1.0);
+    let mut positions: Vec<(f64, f64)> = (0..n)
+        .map(|i| {
+            let radius = k * (i as f64).sqrt() * 0.5;
+            let angle = i as f64 * GOLDEN_ANGLE;
+            (radius * angle.cos(), radius * angle.sin())
+        })
+        .collect();
+    let start_temperature = k.max(k * (n as f64).sqrt() / 10.0);
+
+    for iteration in 0..iterations {
+        let mut displacement = vec![(0.0, 0.0); n];
+        let mut repel = |i: usize, j: usize, positions: &[(f64, f64)], cutoff: Option<f64>| {
+            let (dx, dy, distance) = separation(positions[i], positions[j], i, j);
+            if cutoff.is_some_and(|cutoff| distance > cutoff) {
+                return;
+            }
+            let force = k * k / distance;
+            displacement[i].0 += dx / distance * force;
+            displacement[i].1 += dy / distance * force;
+            displacement[j].0 -= dx / distance * force;
+            displacement[j].1 -= dy / distance * force;
+        };
+        if n <= EXACT_REPULSION_LIMIT {
+            for i in 0..n {
+                for j in i + 1..n {
+                    repel(i, j, &positions, None);
+                }
+            }
+        } else {
+            let cell_size = 2.0 * k;
+            let cell = |(x, y): (f64, f64)| ((x / cell_size).floor() as i64, (y / cell_size).floor() as i64);
+            let mut cells: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+            for (i, &position) in positions.iter().enumerate() {
+                cells.entry(cell(position)).or_default().push(i);
+            }
+            for i in 0..n {
+                let (cx, cy) = cell(positions[i]);
+                for gx in cx - 1..=cx + 1 {
+                    for gy in cy - 1..=cy + 1 {
+                        for &j in cells.get(&(gx, gy)).into_iter().flatten() {
+                            if j > i {
+                                repel(i, j, &positions, Some(cell_size));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        for (i, list) in neighbours.iter().enumerate() {
+            for &j in list.iter().filter(|&&j| j > i) {
+                let (dx, dy, distance) = separation(positions[i], positions[j], i, j);
+                let force = distance * distance / k;
+                displacement[i].0 -= dx / distance * force;
+                displacement[i].1 -= dy / distance * force;
+                displacement[j].0 += dx / distance * force;
+                displacement[j].1 += dy / distance * force;
+            }
+        }
+
+        let temperature = start_temperature * (1.0 - iteration as f64 / iterations as f64);
+        for (position, (mut dx, mut dy)) in positions.iter_mut().zip(displacement) {
+            let distance = (position.0 * position.0 + position.1 * position.1).sqrt();
+            dx -= position.0 * GRAVITY * distance / k;
+            dy -= position.1 * GRAVITY * distance / k;
+            let length = (dx * dx + dy * dy).sqrt();
+            if length > 0.0 {
+                let step = length.min(temperature);
+                position.0 += dx / length * step;
+                position.1 += dy / length * step;
+            }
+        }
+    }
+    centered(positions)
+}
+
+/// Kamada-Kawai: springs between every pair of nodes, with rest lengths proportional to
+/// their shortest-path distance, relaxed one node at a time by Newton-Raphson for up to
+/// `iterations` sweeps. Nodes in different components are kept one step further apart
+/// than the farthest connected pair. Graphs over [`KAMADA_KAWAI_LIMIT`] nodes fall back
+/// to [`fruchterman_reingold`].
+pub fn kamada_kawai(neighbours: &[Vec<usize>], spacing: f64, iterations: usize) -> Vec<(f64, f64)> {
+    let n = neighbours.len();
+    if n > KAMADA_KAWAI_LIMIT {
+        return fruchterman_reingold(neighbours, spacing, iterations);
+    }
+    if n < 2 {
+        return vec![(0.0, 0.0); n];
+    }
+
+    let mut distances: Vec<Vec<usize>> = (0..n).map(|start| hop_distances(neighbours, start)).collect();
+    let farthest = distances.iter().flatten().copied().filter(|&d| d != usize::MAX).max().unwrap_or(1).max(1);
+    for distance in distances.iter_mut().flatten() {
+        if *distance == usize::MAX {
+            *distance = farthest + 1;
+        }
+    }
+
+    let radius = spacing * farthest as f64 / 2.0;
+    let mut positions: Vec<(f64, f64)> = (0..n)
+        .map(|i| {
+            let angle = i as f64 * 2.0 * std::f64::consts::PI / n as f64;
+            (radius * angle.cos(), radius * angle.sin())
+        })
+        .collect();
+    let tolerance = spacing * 1e-3;
+
+    for _ in 0..iterations {
+        let mut largest_gradient: f64 = 0.0;
+        for m in 0..n {
+            for _ in 0..NEWTON_STEPS {
+                let (mut ex, mut ey, mut exx, mut exy, mut eyy) = (0.0, 0.0, 0.0, 0.0, 0.0);
+                for i in (0..n).filter(|&i| i != m) {
+                    let (dx, dy, distance) = separation(positions[m], positions[i], m, i);
+                    let hops = distances[m][i] as f64;
+                    let strength = 1.0 / (hops * hops);
+                    let rest = spacing * hops;
+                    let cube = distance * distance * distance;
+                    ex += strength * (dx - rest * dx / distance);
+                    ey += strength * (dy - rest * dy / distance);
+                    exx += strength * (1.0 - rest * dy * dy / cube);
+                    exy += strength * rest * dx * dy / cube;
+                    eyy += strength * (1.0 - rest * dx * dx / cube);
+                }
+                let gradient = (ex * ex + ey * ey).sqrt();
+                largest_gradient = largest_gradient.max(gradient);
+                let determinant = exx * eyy - exy * exy;
+                if gradient < tolerance || determinant.abs() < 1e-12 {
+                    break;
+                }
+                positions[m].0 += (ey * exy - ex * eyy) / determinant;
+                positions[m].1 += (ex * exy - ey * exx) / determinant;
+            }
+        }
+        if largest_gradient < tolerance {
+            break;
+        }
+    }
+    centered(positions)
+}
+
+/// Square grid, `spacing` apart, filled row by row in alternating directions. Nodes are
+/// taken in breadth-first order from the best connected node of each component, so
+/// neighbours tend to land in adjacent cells.
+pub fn grid(neighbours: &[Vec<usize>], spacing: f64) -> Vec<(f64, f64)> {
+    let n = neighbours.len();
+    let mut starts: Vec<usize> = (0..n).collect();
+    starts.sort_by_key(|&node| std::cmp::Reverse(neighbours[node].len()));
+
+    let mut order = Vec::with_capacity(n);
+    let mut visited = vec![false; n];
+    for start in starts {
+        if visited[start] {
+            continue;
+        }
+        visited[start] = true;
+        let mut queue = VecDeque::from([start]);
+        while let Some(node) = queue.pop_front() {
+            order.push(node);
+            for &next in &neighbours[node] {
+                if !visited[next] {
+                    visited[next] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+
+    let columns = (n as f64).sqrt().ceil().max(1.0) as usize;
+    let rows = n.div_ceil(columns);
+    let mut positions = vec![(0.0, 0.0); n];
+    for (slot, node) in order.into_iter().enumerate() {
+        let row = slot / columns;
+        let column = if row.is_multiple_of(2) { slot % columns } else { columns - 1 - slot % columns };
+        positions[node] = (
+            (column as f64 - (columns - 1) as f64 / 2.0) * spacing,
+            (row as f64 - (rows.max(1) - 1) as f64 / 2.0) * spacing,
+        );
+    }
+    positions
+}
+
+/// Offset from `b` to `a` and its length; coincident nodes are pushed apart along a
+/// direction derived from their indices
+fn separation(a: (f64, f64), b: (f64, f64), i: usize, j: usize) -> (f64, f64, f64) {
+    let (mut dx, mut dy) = (a.0 - b.0, a.1 - b.1);
+    let mut distance = (dx * dx + dy * dy).sqrt();
+    if distance < 1e-6 {
+        let angle = (i * 31 + j * 17) as f64;
+        dx = angle.cos() * 1e-3;
+        dy = angle.sin() * 1e-3;
+        distance = 1e-3;
+    }
+    (dx, dy, distance)
+}
+
+/// Edge hops from `start` to every node, `usize::MAX` for unreachable ones
+fn hop_distances(neighbours: &[Vec<usize>], start: usize) -> Vec<usize> {
+    let mut distances = vec![usize::MAX; neighbours.len()];
+    distances[start] = 0;
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        for &next in &neighbours[node] {
+            if distances[next] == usize::MAX {
+                distances[next] = distances[node] + 1;
+                queue.push_back(next);
+            }
+        }
+    }
+    distances
+}
+
+fn centered(mut positions: Vec<(f64, f64)>) -> Vec<(f64, f64)> {
+    let count = positions.len().max(1) as f64;
+    let cx = positions.iter().map(|p| p.0).sum::<f64>() / count;
+    let cy = positions.iter().map(|p| p.1).sum::<f64>() / count;
+    for position in &mut positions {
+        position.0 -= cx;
+        position.1 -= cy;
+    }
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+        ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+    }
+
+    /// Two 4-cycles joined by one edge, plus an isolated node
+    fn two_squares() -> Vec<Vec<usize>> {
+        let edges = [(0, 1), (1, 2), (2, 3), (3, 0), (4, 5), (5, 6), (6, 7), (7, 4), (3, 4)];
+        let mut neighbours = vec![Vec::new(); 9];
+        for (a, b) in edges {
+            neighbours[a].push(b);
+            neighbours[b].push(a);
+        }
+        neighbours.iter_mut().for_each(|list| list.sort_unstable());
+        neighbours
+    }
+
+    #[test]
+    fn test_force_layouts_are_deterministic_and_keep_neighbours_close() {
+        let neighbours = two_squares();
+        for layout in [fruchterman_reingold, kamada_kawai] {
+            let positions = layout(&neighbours, 100.0, 200);
+            assert_eq!(positions, layout(&neighbours, 100.0, 200));
+            assert!(positions.iter().all(|p| p.0.is_finite() && p.1.is_finite()));
+
+            // Adjacent nodes sit closer than nodes on opposite sides of the bridge
+            let edge_length = distance(positions[0], positions[1]);
+            assert!(edge_length > 20.0 && edge_length < 250.0, "edge length {}", edge_length);
+            assert!(distance(positions[1], positions[6]) > 1.5 * edge_length);
+            for i in 0..9 {
+                for j in i + 1..9 {
+                    assert!(distance(positions[i], positions[j]) > 10.0, "nodes {} and {} overlap", i, j);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_grid_fills_rows_with_neighbours_adjacent() {
+        let positions = grid(&two_squares(), 50.0);
+        let mut cells: Vec<(i64, i64)> = positions.iter().map(|p| (p.0 as i64, p.1 as i64)).collect();
+        cells.sort();
+        cells.dedup();
+        assert_eq!(cells.len(), 9);
+        assert!(positions.iter().all(|p| p.0.abs() <= 50.0 && p.1.abs() <= 50.0));
+        // Node 3 is the first of the best connected nodes, so the grid starts from it and
+        // its first neighbour
+        assert_eq!(positions[3], (-50.0, -50.0));
+        assert_eq!(distance(positions[3], positions[0]), 50.0);
+    }
+}
@@ -4,7 +4,61 @@ pub mod text_processor;
 pub mod web_interface;
 pub mod export;
 pub mod config;
+pub mod cache;
+pub mod cooccurrence;
+pub mod timestamps;
 pub mod error;
+pub mod pipeline;
+#[cfg(feature = "native")]
+pub mod storage;
+pub mod history;
+pub mod transforms;
+pub mod style_rules;
+pub mod conditions;
+pub mod explain;
+pub mod keywords;
+pub mod schema;
+pub mod validation;
+pub mod plugins;
+pub mod sentiment;
+pub mod timeline;
+pub mod estimate;
+pub mod gazetteer;
+pub mod presets;
+pub mod relationship_rules;
+pub mod relationship_validation;
+pub mod prompt_budget;
+pub mod diagnostics;
+pub mod obsidian;
+pub mod pattern_library;
+pub mod languages;
+pub mod hierarchy;
+pub mod disambiguation;
+pub mod streaming;
+pub mod sentence_splitter;
+pub mod chunker;
+pub mod ner_model;
+pub mod extractors;
+pub mod report;
+pub mod annotations;
+pub mod dataset;
+pub mod source_format;
+pub mod llm_output;
+pub mod ocr;
+#[cfg(feature = "native")]
+pub mod transcription;
+#[cfg(feature = "native")]
+pub mod input_sources;
+#[cfg(feature = "native")]
+pub mod preview;
+pub mod share;
+pub mod query;
+pub mod graph_chunks;
+pub mod compare;
+pub mod documents;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod layout;
 
 pub use entity_extractor::*;
 pub use graph_builder::*;
@@ -12,4 +66,53 @@ pub use text_processor::*;
 pub use web_interface::*;
 pub use export::*;
 pub use config::*;
+pub use cache::*;
+pub use cooccurrence::*;
+pub use timestamps::*;
 pub use error::*;
+pub use pipeline::*;
+#[cfg(feature = "native")]
+pub use storage::*;
+pub use history::*;
+pub use transforms::*;
+pub use style_rules::*;
+pub use explain::*;
+pub use keywords::*;
+pub use schema::*;
+pub use validation::*;
+pub use plugins::*;
+pub use sentiment::*;
+pub use timeline::*;
+pub use estimate::*;
+pub use gazetteer::*;
+pub use presets::*;
+pub use relationship_rules::*;
+pub use relationship_validation::*;
+pub use prompt_budget::*;
+pub use diagnostics::*;
+pub use obsidian::*;
+pub use pattern_library::*;
+pub use languages::*;
+pub use hierarchy::*;
+pub use disambiguation::*;
+pub use streaming::*;
+pub use sentence_splitter::*;
+pub use chunker::*;
+pub use ner_model::*;
+pub use extractors::*;
+pub use report::*;
+pub use annotations::*;
+pub use dataset::*;
+pub use llm_output::*;
+pub use ocr::*;
+#[cfg(feature = "native")]
+pub use transcription::*;
+#[cfg(feature = "native")]
+pub use input_sources::*;
+#[cfg(feature = "native")]
+pub use preview::*;
+pub use share::*;
+pub use query::*;
+pub use graph_chunks::*;
+pub use compare::*;
+pub use documents::*;
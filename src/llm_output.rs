@@ -0,0 +1,312 @@
+use crate::config::LlmOutputFormat;
+use crate::error::{GraphError, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use serde_json::{json, Value};
+use tracing::debug;
+
+/// Confidence given to items the model returned without one
+const DEFAULT_LLM_CONFIDENCE: f64 = 0.8;
+
+/// Built-in entity types offered to the model, before any custom ones and `Other`
+pub const LLM_ENTITY_TYPES: [&str; 6] = ["Person", "Place", "Organization", "System", "Process", "Concept"];
+
+/// Kinds of relationship deep analysis asks the model for
+pub const DEEP_RELATIONSHIP_CATEGORIES: [&str; 5] = ["implicit", "temporal", "hierarchical", "functional", "dependency"];
+
+/// What one LLM extraction call returns: the key its items are listed under and the
+/// fields each item has
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmItemKind {
+    Entities,
+    Relationships,
+    /// Deep-analysis relationships, which also carry a category and supporting text
+    DeepRelationships,
+    Concepts,
+}
+
+impl LlmItemKind {
+    /// Key of the array in the `{"entities": [...]}` object the prompts ask for
+    pub fn key(&self) -> &'static str {
+        match self {
+            LlmItemKind::Entities => "entities",
+            LlmItemKind::Relationships | LlmItemKind::DeepRelationships => "relationships",
+            LlmItemKind::Concepts => "concepts",
+        }
+    }
+
+    fn item_schema(&self, entity_types: &[String]) -> Value {
+        match self {
+            LlmItemKind::Entities => json!({
+                "type": "object",
+                "properties": {
+                    "name": {"type": "string"},
+                    "type": {"type": "string", "enum": entity_types},
+                    "confidence": {"type": "number"}
+                },
+                "required": ["name", "type", "confidence"]
+            }),
+            LlmItemKind::Relationships => json!({
+                "type": "object",
+                "properties": {
+                    "from": {"type": "string"},
+                    "to": {"type": "string"},
+                    "relationship": {"type": "string"},
+                    "confidence": {"type": "number"}
+                },
+                "required": ["from", "to", "relationship", "confidence"]
+            }),
+            LlmItemKind::DeepRelationships => json!({
+                "type": "object",
+                "properties": {
+                    "from": {"type": "string"},
+                    "to": {"type": "string"},
+                    "relationship": {"type": "string"},
+                    "category": {"type": "string", "enum": DEEP_RELATIONSHIP_CATEGORIES},
+                    "confidence": {"type": "number"},
+                    "context": {"type": "string"}
+                },
+                "required": ["from", "to", "relationship", "category", "confidence", "context"]
+            }),
+            LlmItemKind::Concepts => json!({
+                "type": "object",
+                "properties": {
+                    "name": {"type": "string"},
+                    "description": {"type": "string"},
+                    "confidence": {"type": "number"}
+                },
+                "required": ["name", "description", "confidence"]
+            }),
+        }
+    }
+
+    /// The JSON schema of the whole response; entity types must be one of `entity_types`
+    /// (see [`ExtractionConfig::llm_entity_types`](crate::config::ExtractionConfig::llm_entity_types))
+    pub fn schema(&self, entity_types: &[String]) -> Value {
+        json!({
+            "type": "object",
+            "properties": {self.key(): {"type": "array", "items": self.item_schema(entity_types)}},
+            "required": [self.key()]
+        })
+    }
+
+    /// The Ollama `format` value for this call, if the output is constrained at all
+    pub fn ollama_format(&self, format: LlmOutputFormat, entity_types: &[String]) -> Option<Value> {
+        match format {
+            LlmOutputFormat::Schema => Some(self.schema(entity_types)),
+            LlmOutputFormat::Json => Some(Value::String("json".to_string())),
+            LlmOutputFormat::Text => None,
+        }
+    }
+}
+
+/// An entity as the model reports it; field names vary between models, so common
+/// alternatives are accepted
+#[derive(Debug, Deserialize)]
+pub(crate) struct LlmEntity {
+    #[serde(alias = "entity", alias = "text", alias = "label", alias = "entity_name")]
+    pub name: String,
+    #[serde(rename = "type", alias = "entity_type", alias = "category", alias = "kind", default)]
+    pub entity_type: String,
+    #[serde(default = "default_confidence", deserialize_with = "lenient_confidence")]
+    pub confidence: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct LlmRelationship {
+    #[serde(alias = "source", alias = "subject", alias = "head", alias = "entity1")]
+    pub from: String,
+    #[serde(alias = "target", alias = "object", alias = "tail", alias = "entity2")]
+    pub to: String,
+    #[serde(alias = "relation", alias = "type", alias = "label", alias = "predicate", alias = "relationship_type")]
+    pub relationship: String,
+    #[serde(default = "default_confidence", deserialize_with = "lenient_confidence")]
+    pub confidence: f64,
+    /// One of [`DEEP_RELATIONSHIP_CATEGORIES`], in deep-analysis answers
+    #[serde(alias = "relationship_category", default)]
+    pub category: Option<String>,
+    /// The text the model cites for the relationship
+    #[serde(alias = "evidence", alias = "supporting_text", alias = "quote", default)]
+    pub context: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct LlmConcept {
+    #[serde(alias = "concept", alias = "concept_name", alias = "term")]
+    pub name: String,
+    #[serde(alias = "summary", alias = "definition", default)]
+    pub description: String,
+    #[serde(default = "default_confidence", deserialize_with = "lenient_confidence")]
+    pub confidence: f64,
+}
+
+fn default_confidence() -> f64 {
+    DEFAULT_LLM_CONFIDENCE
+}
+
+/// `0.8`, `"0.8"`, `"80%"` or `null`, clamped to 0..=1
+fn lenient_confidence<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<f64, D::Error> {
+    let value = Value::deserialize(deserializer)?;
+    let confidence = match &value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => match text.trim().strip_suffix('%') {
+            Some(percent) => percent.trim().parse::<f64>().ok().map(|p| p / 100.0),
+            None => text.trim().parse().ok(),
+        },
+        _ => None,
+    };
+    Ok(confidence.map_or(DEFAULT_LLM_CONFIDENCE, |c| if c > 1.0 { c / 100.0 } else { c }).clamp(0.0, 1.0))
+}
+
+/// The items of an LLM extraction response. Accepts a bare array, an object holding
+/// the array under `kind.key()` (or any single array field), or a lone item, after
+/// [`repair_json`] if the response does not parse as is. Items missing required
+/// fields are skipped; it is an error only when nothing usable is left of a non-empty
+/// response.
+pub(crate) fn parse_llm_items<T: DeserializeOwned>(response: &str, kind: LlmItemKind) -> Result<Vec<T>> {
+    let value = serde_json::from_str::<Value>(response.trim())
+        .or_else(|_| serde_json::from_str::<Value>(&repair_json(response)))
+        .map_err(|e| GraphError::EntityExtraction(format!("Failed to parse LLM {}: {}", kind.key(), e)))?;
+
+    let items = match value {
+        Value::Array(items) => items,
+        Value::Object(mut object) => match object.remove(kind.key()) {
+            Some(Value::Array(items)) => items,
+            _ => {
+                let arrays: Vec<Value> = object.values().filter(|value| value.is_array()).cloned().collect();
+                match <[Value; 1]>::try_from(arrays) {
+                    Ok([Value::Array(items)]) => items,
+                    _ if object.is_empty() => Vec::new(),
+                    _ => vec![Value::Object(object)],
+                }
+            }
+        },
+        other => {
+            return Err(GraphError::EntityExtraction(format!(
+                "Failed to parse LLM {}: expected a JSON array or object, got {}",
+                kind.key(),
+                other
+            )))
+        }
+    };
+
+    let total = items.len();
+    let parsed: Vec<T> = items
+        .into_iter()
+        .filter_map(|item| match serde_json::from_value(item) {
+            Ok(parsed) => Some(parsed),
+            Err(e) => {
+                debug!("Skipping malformed LLM {} item: {}", kind.key(), e);
+                None
+            }
+        })
+        .collect();
+    if total > 0 && parsed.is_empty() {
+        return Err(GraphError::EntityExtraction(format!(
+            "Failed to parse LLM {}: none of the {} items had the expected fields",
+            kind.key(),
+            total
+        )));
+    }
+    Ok(parsed)
+}
+
+/// Best-effort fix of the JSON chatty models produce: prose and code fences around it,
+/// curly quotes, trailing commas and brackets left open by a truncated answer
+pub fn repair_json(response: &str) -> String {
+    let text = response.replace(['\u{201c}', '\u{201d}'], "\"").replace(['\u{2018}', '\u{2019}'], "'");
+    let body = &text[text.find(['[', '{']).unwrap_or(0)..];
+
+    // Copy up to where the first value closes, so prose after it is left out
+    let mut repaired = String::with_capacity(body.len());
+    let mut open = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in body.chars() {
+        if in_string {
+            repaired.push(c);
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '[' | '{' => open.push(c),
+            ']' | '}' => {
+                drop_trailing_comma(&mut repaired);
+                open.pop();
+                if open.is_empty() {
+                    repaired.push(c);
+                    break;
+                }
+            }
+            _ => {}
+        }
+        repaired.push(c);
+    }
+    if in_string {
+        repaired.push('"');
+    }
+    while let Some(bracket) = open.pop() {
+        drop_trailing_comma(&mut repaired);
+        repaired.push(if bracket == '[' { ']' } else { '}' });
+    }
+    repaired
+}
+
+fn drop_trailing_comma(text: &mut String) {
+    let trimmed = text.trim_end().len();
+    if text[..trimmed].ends_with(',') {
+        text.truncate(trimmed - 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lenient_shapes_and_field_names() {
+        let wrapped = r#"{"entities": [{"entity": "Alice", "category": "Person", "confidence": "90%"}, {"name": "TechCorp"}]}"#;
+        let entities: Vec<LlmEntity> = parse_llm_items(wrapped, LlmItemKind::Entities).unwrap();
+        assert_eq!((entities[0].name.as_str(), entities[0].entity_type.as_str()), ("Alice", "Person"));
+        assert_eq!(entities[0].confidence, 0.9);
+        assert_eq!(entities[1].confidence, DEFAULT_LLM_CONFIDENCE);
+
+        let other_key = r#"{"result": [{"source": "Alice", "target": "Bob", "predicate": "knows", "confidence": 0.7}, {"oops": 1}]}"#;
+        let relationships: Vec<LlmRelationship> = parse_llm_items(other_key, LlmItemKind::Relationships).unwrap();
+        assert_eq!(relationships.len(), 1);
+        assert_eq!((relationships[0].from.as_str(), relationships[0].relationship.as_str()), ("Alice", "knows"));
+        assert_eq!(relationships[0].category, None);
+
+        let deep = r#"[{"from": "Alice", "to": "Bob", "relationship": "mentors", "relationship_category": "functional", "supporting_text": "Alice trains Bob"}]"#;
+        let relationships: Vec<LlmRelationship> = parse_llm_items(deep, LlmItemKind::DeepRelationships).unwrap();
+        assert_eq!(relationships[0].category.as_deref(), Some("functional"));
+        assert_eq!(relationships[0].context.as_deref(), Some("Alice trains Bob"));
+
+        let single = r#"{"name": "CRM", "description": "Sales software"}"#;
+        let concepts: Vec<LlmConcept> = parse_llm_items(single, LlmItemKind::Concepts).unwrap();
+        assert_eq!(concepts[0].name, "CRM");
+
+        let empty: Vec<LlmConcept> = parse_llm_items(r#"{"concepts": []}"#, LlmItemKind::Concepts).unwrap();
+        assert!(empty.is_empty());
+        assert!(parse_llm_items::<LlmEntity>(r#"[{"size": 3}]"#, LlmItemKind::Entities).is_err());
+        assert!(parse_llm_items::<LlmEntity>("I could not find any.", LlmItemKind::Entities).is_err());
+    }
+
+    #[test]
+    fn test_repair_handles_chatty_and_truncated_output() {
+        let chatty = "Sure! Here are the entities:\n```json\n[{\u{201c}name\u{201d}: \"Alice\", \"type\": \"Person\",},]\n```\nLet me know!";
+        let entities: Vec<LlmEntity> = parse_llm_items(chatty, LlmItemKind::Entities).unwrap();
+        assert_eq!(entities[0].name, "Alice");
+
+        let truncated = r#"{"entities": [{"name": "Alice", "type": "Person"}, {"name": "Bo"#;
+        assert_eq!(repair_json(truncated), r#"{"entities": [{"name": "Alice", "type": "Person"}, {"name": "Bo"}]}"#);
+        let entities: Vec<LlmEntity> = parse_llm_items(truncated, LlmItemKind::Entities).unwrap();
+        assert_eq!(entities.len(), 2);
+    }
+}
@@ -0,0 +1,119 @@
+// Logging setup for the CLI.
+//
+// The library reports progress through `tracing` events and spans and never writes to
+// stdout directly; this module installs the subscriber that turns those into terminal
+// output. In the default text format events are printed as plain lines (the same emoji
+// messages the tool has always shown) and long-running spans such as LLM calls are
+// rendered as indicatif progress bars. The JSON format emits one structured record per
+// event instead, which is easier to consume from scripts and log collectors.
+
+use clap::ValueEnum;
+use std::fmt;
+use tracing::{Event, Level, Subscriber};
+use tracing_indicatif::IndicatifLayer;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// Output format for log messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable lines with progress bars
+    Text,
+    /// One JSON object per log event
+    Json,
+}
+
+/// Verbosity selected on the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+}
+
+impl Verbosity {
+    pub fn from_flags(quiet: bool, verbose: bool) -> Self {
+        if quiet {
+            Verbosity::Quiet
+        } else if verbose {
+            Verbosity::Verbose
+        } else {
+            Verbosity::Normal
+        }
+    }
+
+    fn level(self) -> Level {
+        match self {
+            Verbosity::Quiet => Level::WARN,
+            Verbosity::Normal => Level::INFO,
+            Verbosity::Verbose => Level::DEBUG,
+        }
+    }
+}
+
+/// Formats an event as its message and fields only, without timestamps, levels,
+/// targets or span context, so text output reads like ordinary console messages.
+struct MessageOnly;
+
+impl<S, N> FormatEvent<S, N> for MessageOnly
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        ctx.field_format().format_fields(writer.by_ref(), event)?;
+        writeln!(writer)
+    }
+}
+
+/// Install the global tracing subscriber for the CLI.
+///
+/// Events from this crate are shown at the level chosen by `verbosity`; other crates
+/// (HTTP clients and the like) only surface warnings. Progress bars are drawn only in
+/// text mode and are suppressed by `--quiet`.
+pub fn init(verbosity: Verbosity, format: LogFormat) {
+    let filter = Targets::new()
+        .with_target("msg_net", verbosity.level())
+        .with_default(Level::WARN);
+
+    match format {
+        LogFormat::Json => {
+            let fmt_layer = tracing_subscriber::fmt::layer()
+                .json()
+                .with_writer(std::io::stdout);
+            let _ = tracing_subscriber::registry()
+                .with(fmt_layer)
+                .with(filter)
+                .try_init();
+        }
+        LogFormat::Text if verbosity == Verbosity::Quiet => {
+            let fmt_layer = tracing_subscriber::fmt::layer()
+                .event_format(MessageOnly)
+                .with_writer(std::io::stdout);
+            let _ = tracing_subscriber::registry()
+                .with(fmt_layer)
+                .with(filter)
+                .try_init();
+        }
+        LogFormat::Text => {
+            let indicatif_layer = IndicatifLayer::new();
+            let fmt_layer = tracing_subscriber::fmt::layer()
+                .event_format(MessageOnly)
+                .with_writer(indicatif_layer.get_stdout_writer());
+            let _ = tracing_subscriber::registry()
+                .with(fmt_layer)
+                .with(indicatif_layer)
+                .with(filter)
+                .try_init();
+        }
+    }
+}
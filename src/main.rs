@@ -1,13 +1,39 @@
 use clap::{Parser, Subcommand};
 use msg_net::{
-    config::GraphConfig,
-    entity_extractor::EntityExtractor,
-    export::{ExportFormat, ExportOptions, GraphExporter},
-    graph_builder::GraphBuilder,
+    annotations::{AnnotationFormat, StandoffAnnotations},
+    config::{CsvLayout, ExtractionConfig, GraphConfig, ParallelEdges, RenderConfig, Theme},
+    dataset::{dataset_inputs, write_dataset, DatasetRecord},
+    compare::ComparisonOverlay,
+    cooccurrence::ExtractionMode,
+    diagnostics::{empty_result_hints, is_sparse},
+    documents::{combine_documents, record_sources, SourceDocument},
+    entity_extractor::{EntityExtractor, LlmProgress},
+    extractors::{ExtractorKind, ExtractorStep},
+    export::{AppendFormat, AppendOptions, ExportFormat, ExportOptions, GraphExporter},
+    graph_builder::{GraphBuilder, InteractiveGraph},
+    input_sources::{fetch_url, read_clipboard},
+    obsidian::{extract_vault, write_entity_notes, Vault},
+    ocr::{is_ocr_input, ocr_file, OcrText},
+    pipeline::{Pipeline, PipelineEvent, PipelineObserver, PipelineOutput, PipelineStage},
+    plugins::WasmPlugin,
+    presets::Preset,
+    preview::{open_in_browser, FileWatcher, PreviewServer},
+    query::GraphFilter,
+    report::rank_entities,
+    schema::{graph_from_json, graph_json_schema, GRAPH_SCHEMA_VERSION},
+    sentence_splitter::SentenceSplitter,
+    storage::{GraphStore, RunInfo},
+    validation::{validate_graph_json, Severity},
+    streaming::stream_graph,
     text_processor::{SourceType, TextProcessor},
+    transcription::{is_audio_input, transcribe, AsrBackend},
     Result,
 };
 use std::fs;
+use std::io::{BufRead, BufReader, BufWriter, Read};
+use tracing::{info, warn, Span};
+use tracing_indicatif::span_ext::IndicatifSpanExt;
+use tracing_indicatif::style::ProgressStyle;
 
 #[derive(Parser)]
 #[command(name = "msg_net")]
@@ -16,27 +42,58 @@ use std::fs;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Only print warnings and errors (no banner, progress messages or bars)
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Show debug messages and detailed analysis output
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    /// Log output format
+    #[arg(long, value_enum, default_value = "text", global = true)]
+    log_format: logging::LogFormat,
+
+    /// Apply this named profile from the config file's `profiles` block
+    #[arg(long, global = true)]
+    profile: Option<String>,
 }
 
+// Parsed once at startup, so the size of the Generate variant doesn't matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 enum Commands {
     /// Process text and generate an interactive graph
     Generate {
-        /// Input text file path
-        #[arg(short, long)]
-        input: String,
+        /// Input text file path; repeat -i or separate paths with commas to combine several files into one graph
+        #[arg(short, long, value_delimiter = ',', required_unless_present_any = ["from_clipboard", "from_url"])]
+        input: Vec<String>,
+
+        /// Read the input text from the system clipboard instead of a file
+        #[arg(long, conflicts_with_all = ["input", "from_url"])]
+        from_clipboard: bool,
+
+        /// Fetch a web page and use its main content, without menus, sidebars and footers, as input
+        #[arg(long, value_name = "URL", conflicts_with = "input")]
+        from_url: Option<String>,
         
         /// Output file path (format determined by extension)
         #[arg(short, long)]
         output: String,
         
-        /// Source type of the input text
-        #[arg(short, long, default_value = "document")]
+        /// Source type of the input text: auto, document, chat, email, article, html or markdown.
+        /// `auto` detects it and, for chat logs, uses the chat-forensics preset
+        #[arg(short, long, default_value = "auto")]
         source_type: String,
         
         /// Configuration file path (JSON)
         #[arg(short, long)]
         config: Option<String>,
+
+        /// Built-in configuration: meeting-notes, novel, incident-report, research-paper or chat-forensics
+        #[arg(long, conflicts_with = "config")]
+        preset: Option<String>,
         
         /// Export format
         #[arg(short, long, default_value = "html")]
@@ -69,17 +126,276 @@ enum Commands {
         /// Disable stopword removal entirely
         #[arg(long)]
         no_remove_stopwords: bool,
+
+        /// Also append nodes/edges to shared nodes.<ext>/edges.<ext> files in this directory
+        #[arg(long)]
+        append_dir: Option<String>,
+
+        /// Row format for --append-dir (csv or jsonl)
+        #[arg(long, default_value = "csv")]
+        append_format: String,
+
+        /// Run id recorded on appended rows (defaults to a random UUID)
+        #[arg(long)]
+        run_id: Option<String>,
+
+        /// Always call the LLM instead of reusing cached responses (nothing is cached either)
+        #[arg(long)]
+        no_cache: bool,
+
+        /// Refetch cached LLM responses older than this many seconds
+        #[arg(long)]
+        cache_ttl: Option<u64>,
+
+        /// Also save the graph and extraction result to this SQLite database
+        #[arg(long)]
+        db: Option<String>,
+
+        /// Keep only these entities and the relationships between them (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        only: Vec<String>,
+
+        /// Like --only, reading entity names from a file (one per line)
+        #[arg(long)]
+        only_file: Option<String>,
+
+        /// Extraction mode: entities (patterns/LLM), cooccurrence (term network, no patterns or LLM) or hierarchy (org chart)
+        #[arg(long)]
+        mode: Option<String>,
+
+        /// Embed vis-network in the HTML so it opens without internet (needs assets.vis_network_path)
+        #[arg(long)]
+        offline: bool,
+
+        /// HTML palette: light, dark, auto (follow the browser) or a custom CSS file
+        #[arg(long)]
+        theme: Option<String>,
+
+        /// WebAssembly extractor plugin to run after extraction (repeatable; needs `--features plugins`)
+        #[arg(long = "plugin")]
+        plugins: Vec<String>,
+
+        /// Write the output with this WebAssembly exporter plugin instead of --format
+        #[arg(long)]
+        export_plugin: Option<String>,
+
+        /// Report the LLM calls and estimated tokens a run would use, without extracting or writing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Remove nodes without edges after the graph is built
+        #[arg(long)]
+        prune_isolated: bool,
+
+        /// Remove nodes with fewer than this many edges after the graph is built
+        #[arg(long)]
+        min_degree: Option<usize>,
+
+        /// Keep only this many nodes, the best connected ones, when the graph is larger
+        #[arg(long)]
+        max_nodes: Option<usize>,
+
+        /// Keep only this many edges, the most confident ones, when the graph has more
+        #[arg(long)]
+        max_edges: Option<usize>,
+
+        /// Ask before LLM runs whose estimate exceeds `extraction.llm_cost.budget` or `budget_tokens`
+        #[arg(long, conflicts_with = "dry_run")]
+        confirm: bool,
+
+        /// Fail without writing output when the graph has fewer nodes than this
+        #[arg(long)]
+        min_results: Option<usize>,
+
+        /// Also write the extraction as standoff annotations over the input text (.ann for brat, .json)
+        #[arg(long)]
+        annotations: Option<String>,
+
+        /// Tesseract language for scanned PDF and image input, e.g. eng or deu+eng
+        /// (needs a build with --features ocr)
+        #[arg(long, default_value = "eng")]
+        ocr_language: String,
+
+        /// Speech recognizer for audio input, run without a shell; {input} and {output_dir}
+        /// are filled in, e.g. "whisper {input} --output_format json --output_dir {output_dir}"
+        #[arg(long, conflicts_with = "asr_endpoint")]
+        asr_command: Option<String>,
+
+        /// OpenAI-compatible transcription endpoint for audio input,
+        /// e.g. http://localhost:8000/v1/audio/transcriptions
+        #[arg(long)]
+        asr_endpoint: Option<String>,
+
+        /// Model name sent to --asr-endpoint
+        #[arg(long, default_value = "whisper-1")]
+        asr_model: String,
+
+        /// Keep only the nodes matching this expression and the edges between them,
+        /// e.g. "type:Person AND degree>=3" (see the query command)
+        #[arg(long)]
+        filter: Option<String>,
+
+        /// Content-hashed ids, sorted results and a fixed timestamp, so reruns on the same
+        /// text give identical output (set SOURCE_DATE_EPOCH to choose the timestamp)
+        #[arg(long)]
+        deterministic: bool,
+
+        /// Open the generated HTML in the default browser (or $BROWSER)
+        #[arg(long)]
+        open: bool,
+
+        /// Serve the HTML on localhost, regenerate whenever an input or config file is saved,
+        /// and reload the page in the browser; runs until Ctrl-C
+        #[arg(long, conflicts_with_all = ["dry_run", "confirm"])]
+        serve_preview: bool,
+
+        /// Port for --serve-preview
+        #[arg(long, default_value_t = 8765, requires = "serve_preview")]
+        preview_port: u16,
+
+        /// Read and extract plain-text input a chunk at a time (`streaming.chunk_bytes`),
+        /// writing nodes and edges to the output as each chunk finishes; needs -f jsonl
+        #[arg(
+            long,
+            conflicts_with_all = ["dry_run", "serve_preview", "open", "append_dir", "db", "annotations", "export_plugin", "filter", "min_results"]
+        )]
+        bounded_memory: bool,
+    },
+
+    /// Build a graph from an Obsidian vault: notes and [[wikilinks]] plus entities extracted from note bodies
+    Obsidian {
+        /// Vault directory
+        #[arg(short, long)]
+        input: String,
+
+        /// Output file path
+        #[arg(short, long, default_value = "graph.html")]
+        output: String,
+
+        /// Configuration file path (JSON)
+        #[arg(short, long)]
+        config: Option<String>,
+
+        /// Export format
+        #[arg(short, long, default_value = "html")]
+        format: String,
+
+        /// Write one summary note per extracted entity, with backlinks, into the vault
+        #[arg(long)]
+        write_notes: bool,
+
+        /// Folder for --write-notes (default: msg_net inside the vault)
+        #[arg(long)]
+        notes_dir: Option<String>,
     },
     
-    /// Validate and process text without generating output
-    Analyze {
+    /// Select the part of a generated graph matching a filter expression and export it
+    Query {
+        /// Graph JSON file (generated with -f json --include-metadata)
+        #[arg(short, long)]
+        graph: String,
+
+        /// Filter expression over type, label, confidence, degree and community,
+        /// e.g. "type:Person AND degree>=3" or "label~^Al OR community:2"
+        #[arg(short, long)]
+        expression: String,
+
+        /// Output file path
+        #[arg(short, long)]
+        output: String,
+
+        /// Export format (comma-separated for several)
+        #[arg(short, long, default_value = "json")]
+        format: String,
+    },
+
+    /// Re-render a graph JSON export in other formats, without re-running extraction
+    Render {
+        /// Graph JSON file (any -f json export)
+        #[arg(short, long)]
+        graph: String,
+
+        /// Output file path
+        #[arg(short, long)]
+        output: String,
+
+        /// Export format (comma-separated for several)
+        #[arg(short, long, default_value = "html")]
+        format: String,
+
+        /// Configuration file whose layout, physics and export settings replace the stored ones
+        #[arg(short, long)]
+        config: Option<String>,
+
+        /// Lay the graph out again, dropping the stored node positions
+        #[arg(long, value_parser = ["force", "kamada-kawai", "grid", "hierarchical", "tree", "circular"])]
+        layout: Option<String>,
+
+        /// Include metadata in the output
+        #[arg(long)]
+        include_metadata: bool,
+    },
+
+    /// Write a per-entity dossier (Markdown, or HTML for a .html output) from a graph JSON export
+    Report {
+        /// Graph JSON file (any -f json export)
+        #[arg(short, long)]
+        graph: String,
+
+        /// Output file path; .html writes an HTML report, anything else Markdown
+        #[arg(short, long)]
+        output: String,
+
+        /// Text the graph was built from, to quote the sentences mentioning each entity
+        #[arg(short, long)]
+        input: Option<String>,
+
+        /// Configuration file whose report settings replace the stored ones
+        #[arg(short, long)]
+        config: Option<String>,
+    },
+
+    /// Run pattern and LLM extraction on the same text and export a graph colored by which
+    /// method found each entity and relationship, with agreement statistics
+    CompareMethods {
         /// Input text file path
         #[arg(short, long)]
         input: String,
-        
-        /// Show detailed analysis
+
+        /// Output file path
+        #[arg(short, long, default_value = "compare.html")]
+        output: String,
+
+        /// Export format(s), comma-separated (html, json, csv, graphml, dot, tikz, mermaid, markdown, html-report)
+        #[arg(short, long, default_value = "html")]
+        format: String,
+
+        /// Configuration file path (JSON); its extractor chain is replaced by each method in turn
         #[arg(short, long)]
-        verbose: bool,
+        config: Option<String>,
+
+        /// LLM model to use instead of the configured one
+        #[arg(long)]
+        llm_model: Option<String>,
+
+        /// LLM endpoint URL to use instead of the configured one
+        #[arg(long)]
+        llm_endpoint: Option<String>,
+    },
+
+    /// Validate and process text without generating output
+    Analyze {
+        /// Input text file path
+        #[arg(short, long, required_unless_present_any = ["from_clipboard", "from_url"])]
+        input: Option<String>,
+
+        /// Read the input text from the system clipboard instead of a file
+        #[arg(long, conflicts_with_all = ["input", "from_url"])]
+        from_clipboard: bool,
+
+        /// Fetch a web page and use its main content, without menus, sidebars and footers, as input
+        #[arg(long, value_name = "URL", conflicts_with = "input")]
+        from_url: Option<String>,
         
         /// Configuration file path (JSON)
         #[arg(short, long)]
@@ -94,11 +410,91 @@ enum Commands {
         no_remove_stopwords: bool,
     },
     
+    /// Save, load and search graphs in a SQLite database
+    Db {
+        /// SQLite database file (created if missing)
+        #[arg(long, default_value = "msg_net.db")]
+        db: String,
+
+        #[command(subcommand)]
+        action: DbAction,
+    },
+    
+    /// Answer a question about a generated graph using the configured LLM
+    Explain {
+        /// Graph JSON file (generated with -f json --include-metadata)
+        #[arg(short, long)]
+        graph: String,
+
+        /// Question to answer, e.g. "How is Alice connected to the database?"
+        /// (-Q, since -q is the global quiet flag)
+        #[arg(short = 'Q', long)]
+        question: String,
+
+        /// Configuration file path (JSON) for the LLM settings; defaults to the built-in ones
+        #[arg(short, long)]
+        config: Option<String>,
+
+        /// LLM model to use instead of the configured one
+        #[arg(long)]
+        llm_model: Option<String>,
+
+        /// LLM endpoint URL to use instead of the configured one
+        #[arg(long)]
+        llm_endpoint: Option<String>,
+    },
+
+    /// Print the shortest paths between two nodes of a generated graph, with edge labels
+    Path {
+        /// Graph JSON file (any -f json export)
+        #[arg(short, long)]
+        graph: String,
+
+        /// Label of the start node, ignoring case
+        #[arg(long)]
+        from: String,
+
+        /// Label of the end node, ignoring case
+        #[arg(long)]
+        to: String,
+
+        /// Most paths to print when several are equally short
+        #[arg(long, default_value_t = 5)]
+        max_paths: usize,
+
+        /// Also write an HTML export of the graph with the paths highlighted to this file
+        #[arg(long)]
+        highlight: Option<String>,
+    },
+
+    /// Check a graph JSON export for dangling edges, duplicate ids, invalid positions,
+    /// empty labels and schema version mismatches; exits with an error when it finds errors
+    Validate {
+        /// Graph JSON file (any -f json export)
+        #[arg(short, long)]
+        graph: String,
+
+        /// Print the diagnostics as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Print the JSON Schema of graph JSON exports, for validating them in other tools
+    Schema {
+        /// Write the schema to this file instead of standard output
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    
     /// Generate a sample configuration file
     Config {
         /// Output path for the configuration file
         #[arg(short, long, default_value = "graph_config.json")]
         output: String,
+
+        /// Write this built-in preset instead of the defaults, as a starting point for edits
+        #[arg(long)]
+        preset: Option<String>,
     },
     
     /// Show example usage and sample text
@@ -128,13 +524,79 @@ enum Commands {
         output: String,
     },
     
+    /// Extract from text files and write sentence-level NER/relation training data (JSONL)
+    BootstrapDataset {
+        /// Input directory (searched recursively for .txt and .md files) or a single file
+        #[arg(short, long)]
+        input: String,
+
+        /// Output dataset file (JSON Lines, one sentence per line)
+        #[arg(short, long, default_value = "dataset.jsonl")]
+        output: String,
+
+        /// Configuration file path (JSON)
+        #[arg(short, long)]
+        config: Option<String>,
+
+        /// Use LLM for extraction
+        #[arg(long)]
+        use_llm: bool,
+
+        /// Hold out some sentences into this file for manual verification
+        #[arg(long)]
+        review: Option<String>,
+
+        /// Share of sentences held out for --review, between 0 and 1
+        #[arg(long, default_value_t = 0.1)]
+        review_fraction: f64,
+    },
+
     /// Show comprehensive usage examples and command samples
     BigHelp,
 }
 
 
+#[derive(Subcommand)]
+enum DbAction {
+    /// Save a graph JSON export (made with --include-metadata) as a new run
+    Save {
+        /// Graph JSON file
+        #[arg(short, long)]
+        input: String,
+
+        /// Name for the run (defaults to the input file name)
+        #[arg(short, long)]
+        name: Option<String>,
+    },
+
+    /// Export a saved run's graph
+    Load {
+        /// Run id (see `db list`)
+        #[arg(short, long)]
+        run: i64,
+
+        /// Output file path
+        #[arg(short, long)]
+        output: String,
+
+        /// Export format
+        #[arg(short, long, default_value = "html")]
+        format: String,
+    },
+
+    /// List saved runs
+    List,
+
+    /// Find entities by name across all saved runs
+    Find {
+        /// Text to look for in node labels (case-insensitive)
+        entity: String,
+    },
+}
+
 // use colored::Colorize;
 
+mod logging; // Tracing subscriber and progress bar setup
 mod toml_extract; // Extract and print the version information according to the toml file
 
 // Function to display the banner
@@ -167,20 +629,32 @@ fn show_banner() {
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Show the banner
-    show_banner();
+    let cli = Cli::parse();
+
+    logging::init(
+        logging::Verbosity::from_flags(cli.quiet, cli.verbose),
+        cli.log_format,
+    );
 
-    // Display version information from the toml file
-    toml_extract::main();
+    // The banner is decoration for interactive use; keep quiet and JSON output clean
+    let prints_json = matches!(cli.command, Commands::Schema { output: None });
+    if !cli.quiet && cli.log_format == logging::LogFormat::Text && !prints_json {
+        // Show the banner
+        show_banner();
 
-    let cli = Cli::parse();
+        // Display version information from the toml file
+        toml_extract::main();
+    }
 
     match cli.command {
         Commands::Generate {
             input,
+            from_clipboard,
+            from_url,
             output,
             source_type,
             config,
+            preset,
             format,
             include_metadata,
             use_llm,
@@ -189,31 +663,163 @@ async fn main() -> Result<()> {
             llm_endpoint,
             stopwords_file,
             no_remove_stopwords,
+            append_dir,
+            append_format,
+            run_id,
+            no_cache,
+            cache_ttl,
+            db,
+            only,
+            only_file,
+            mode,
+            offline,
+            theme,
+            plugins,
+            export_plugin,
+            dry_run,
+            confirm,
+            prune_isolated,
+            min_degree,
+            max_nodes,
+            max_edges,
+            min_results,
+            annotations,
+            ocr_language,
+            asr_command,
+            asr_endpoint,
+            asr_model,
+            filter,
+            deterministic,
+            open,
+            serve_preview,
+            preview_port,
+            bounded_memory,
         } => {
-            generate_graph(
-                &input,
-                &output,
-                &source_type,
-                config.as_deref(),
-                &format,
-                include_metadata,
-                use_llm,
-                deep_analysis,
-                &llm_model,
-                &llm_endpoint,
-                stopwords_file.as_deref(),
-                no_remove_stopwords,
-            )
-            .await
+            let whitelist = load_whitelist(only, only_file.as_deref())?;
+            let append = match append_dir {
+                Some(directory) => Some(AppendOptions {
+                    directory,
+                    format: parse_append_format(&append_format)?,
+                    run_id: run_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+                }),
+                None => None,
+            };
+            let (input_name, input_text) =
+                read_input_source(input.first().cloned(), from_clipboard, from_url.as_deref()).await?;
+            let inputs = if input_text.is_some() { vec![input_name] } else { input };
+            let asr = match (asr_command, asr_endpoint) {
+                (Some(command), _) => Some(AsrBackend::Command(command)),
+                (None, Some(url)) => Some(AsrBackend::Endpoint { url, model: asr_model }),
+                (None, None) => None,
+            };
+            let preset = preset.as_deref().map(str::parse::<Preset>).transpose()?;
+            let mode = mode.as_deref().map(parse_extraction_mode).transpose()?;
+            let theme = theme.map(Theme::from);
+            let filter = filter.as_deref().map(str::parse::<GraphFilter>).transpose()?;
+
+            // With --serve-preview, generate again each time a watched file is saved
+            let mut watcher = FileWatcher::new(&inputs.iter().chain(config.as_ref()).collect::<Vec<_>>());
+            let mut preview: Option<PreviewServer> = None;
+            loop {
+                let written = generate_graph(
+                    &inputs,
+                    input_text.clone(),
+                    &output,
+                    &source_type,
+                    config.as_deref(),
+                    preset,
+                    cli.profile.as_deref(),
+                    &format,
+                    include_metadata,
+                    use_llm,
+                    deep_analysis,
+                    &llm_model,
+                    &llm_endpoint,
+                    stopwords_file.as_deref(),
+                    no_remove_stopwords,
+                    append.as_ref(),
+                    no_cache,
+                    cache_ttl,
+                    db.as_deref(),
+                    whitelist.clone(),
+                    mode,
+                    offline,
+                    theme.clone(),
+                    plugins.clone(),
+                    export_plugin.as_deref(),
+                    dry_run,
+                    confirm,
+                    prune_isolated,
+                    min_degree,
+                    max_nodes,
+                    max_edges,
+                    min_results,
+                    annotations.as_deref(),
+                    &ocr_language,
+                    asr.as_ref(),
+                    filter.as_ref(),
+                    deterministic,
+                    bounded_memory,
+                )
+                .await;
+                let page = match written {
+                    Ok(written) => written.into_iter().find(|path| path.ends_with(".html")),
+                    Err(error) if preview.is_some() => {
+                        warn!("❌ Regeneration failed, still showing the previous page: {}", error);
+                        None
+                    }
+                    Err(error) => return Err(error),
+                };
+
+                match (&preview, page) {
+                    (Some(server), Some(page)) => {
+                        server.set_page(page);
+                        info!("🔄 Preview updated");
+                    }
+                    (Some(_), None) => {}
+                    (None, Some(page)) if serve_preview => {
+                        let server = PreviewServer::start(&page, preview_port).await?;
+                        info!("👀 Previewing at {} (Ctrl-C to stop)", server.url());
+                        if open {
+                            open_in_browser(&server.url())?;
+                        }
+                        if watcher.is_empty() {
+                            info!("   The input does not come from a file, so the page is not regenerated");
+                        }
+                        preview = Some(server);
+                    }
+                    (None, Some(page)) => {
+                        if open {
+                            open_in_browser(&page)?;
+                        }
+                        return Ok(());
+                    }
+                    (None, None) if serve_preview || open => {
+                        return Err(msg_net::error::GraphError::Export(
+                            "--open and --serve-preview need an HTML export (-f html, html-report or chunked)".to_string(),
+                        ))
+                    }
+                    (None, None) => return Ok(()),
+                }
+
+                tokio::select! {
+                    _ = watcher.wait_for_change() => info!("✏️  Input changed, regenerating..."),
+                    _ = tokio::signal::ctrl_c() => return Ok(()),
+                }
+            }
         }
         Commands::Analyze {
             input,
-            verbose,
+            from_clipboard,
+            from_url,
             config,
             stopwords_file,
             no_remove_stopwords,
-        } => analyze_text(&input, verbose, config.as_deref(), stopwords_file.as_deref(), no_remove_stopwords).await,
-        Commands::Config { output } => generate_config(&output),
+        } => {
+            let (input, input_text) = read_input_source(input, from_clipboard, from_url.as_deref()).await?;
+            analyze_text(&input, input_text, cli.verbose, config.as_deref(), cli.profile.as_deref(), stopwords_file.as_deref(), no_remove_stopwords).await
+        }
+        Commands::Config { output, preset } => generate_config(&output, preset.as_deref()),
         Commands::Example {
             generate_text,
             generate_ai_story,
@@ -230,15 +836,91 @@ async fn main() -> Result<()> {
                 show_usage_examples()
             }
         }
+        Commands::Db { db, action } => run_db_command(&db, action),
+        Commands::Query { graph, expression, output, format } => query_graph(&graph, &expression, &output, &format),
+        Commands::Render {
+            graph,
+            output,
+            format,
+            config,
+            layout,
+            include_metadata,
+        } => render_graph(
+            &graph,
+            &output,
+            &format,
+            config.as_deref(),
+            cli.profile.as_deref(),
+            layout.as_deref(),
+            include_metadata,
+        ),
+        Commands::Report { graph, output, input, config } => {
+            report_graph(&graph, &output, input.as_deref(), config.as_deref(), cli.profile.as_deref())
+        }
+        Commands::CompareMethods {
+            input,
+            output,
+            format,
+            config,
+            llm_model,
+            llm_endpoint,
+        } => {
+            compare_methods(&input, &output, &format, config.as_deref(), cli.profile.as_deref(), llm_model, llm_endpoint)
+                .await
+        }
+        Commands::Explain {
+            graph,
+            question,
+            config,
+            llm_model,
+            llm_endpoint,
+        } => explain_graph(&graph, &question, config.as_deref(), cli.profile.as_deref(), llm_model, llm_endpoint).await,
+        Commands::Path {
+            graph,
+            from,
+            to,
+            max_paths,
+            highlight,
+        } => print_paths(&graph, &from, &to, max_paths, highlight.as_deref()),
+        Commands::Validate { graph, json } => validate_graph(&graph, json),
+        Commands::Schema { output } => print_graph_schema(output.as_deref()),
+        Commands::Obsidian {
+            input,
+            output,
+            config,
+            format,
+            write_notes,
+            notes_dir,
+        } => {
+            let notes_dir = write_notes.then(|| notes_dir.unwrap_or_else(|| format!("{}/msg_net", input.trim_end_matches('/'))));
+            obsidian_graph(&input, &output, config.as_deref(), cli.profile.as_deref(), &format, notes_dir.as_deref()).await
+        }
+        Commands::BootstrapDataset {
+            input,
+            output,
+            config,
+            use_llm,
+            review,
+            review_fraction,
+        } => {
+            bootstrap_dataset(&input, &output, config.as_deref(), cli.profile.as_deref(), use_llm, review.as_deref(), review_fraction)
+                .await
+        }
         Commands::BigHelp => show_comprehensive_help(),
     }
 }
 
+/// Run `generate`, returning the paths of the files it exported
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(name = "generate", skip_all)]
 async fn generate_graph(
-    input_path: &str,
+    input_paths: &[String],
+    input_text: Option<String>,
     output_path: &str,
     source_type: &str,
     config_path: Option<&str>,
+    preset: Option<Preset>,
+    profile: Option<&str>,
     format: &str,
     include_metadata: bool,
     use_llm: bool,
@@ -247,30 +929,107 @@ async fn generate_graph(
     llm_endpoint: &str,
     stopwords_file: Option<&str>,
     no_remove_stopwords: bool,
-) -> Result<()> {
-    println!("🚀 Starting Entity Relationship Graph generation...");
-    
-    // Load and validate input
-    let text = fs::read_to_string(input_path)
-        .map_err(|e| msg_net::error::GraphError::Io(e))?;
+    append: Option<&AppendOptions>,
+    no_cache: bool,
+    cache_ttl: Option<u64>,
+    db_path: Option<&str>,
+    whitelist: Option<Vec<String>>,
+    mode: Option<ExtractionMode>,
+    offline: bool,
+    theme: Option<Theme>,
+    plugins: Vec<String>,
+    export_plugin: Option<&str>,
+    dry_run: bool,
+    confirm: bool,
+    prune_isolated: bool,
+    min_degree: Option<usize>,
+    max_nodes: Option<usize>,
+    max_edges: Option<usize>,
+    min_results: Option<usize>,
+    annotations_path: Option<&str>,
+    ocr_language: &str,
+    asr: Option<&AsrBackend>,
+    filter: Option<&GraphFilter>,
+    deterministic: bool,
+    bounded_memory: bool,
+) -> Result<Vec<String>> {
+    info!("🚀 Starting Entity Relationship Graph generation...");
+
+    // One progress step per pipeline stage: process, extract, build, export
+    let span = Span::current();
+    if let Ok(style) = ProgressStyle::with_template("{spinner} {span_name} [{bar:20}] {pos}/{len} stages {msg}") {
+        span.pb_set_style(&style);
+    }
+    span.pb_set_length(4);
     
-    if text.trim().is_empty() {
-        return Err(msg_net::error::GraphError::TextProcessing(
-            "Input file is empty".to_string(),
-        ));
+    // Load and validate input; scans and images go through OCR, recordings through ASR
+    let mut documents = Vec::new();
+    let mut ocr = None;
+    match input_text {
+        Some(text) => documents.push(SourceDocument::new(input_paths[0].clone(), text)),
+        // Bounded-memory runs read the files while extracting them
+        None if bounded_memory => {}
+        None => {
+            for input_path in input_paths {
+                let (text, file_ocr) = load_input_file(input_path, ocr_language, asr).await?;
+                // OCR word confidences only line up with a single document's text
+                ocr = file_ocr.filter(|_| input_paths.len() == 1);
+                documents.push(SourceDocument::new(input_path.clone(), text));
+            }
+        }
+    }
+    for document in &documents {
+        if document.text.trim().is_empty() {
+            return Err(msg_net::error::GraphError::TextProcessing(if documents.len() == 1 {
+                "Input file is empty".to_string()
+            } else {
+                format!("Input file {} is empty", document.name)
+            }));
+        }
+    }
+    let input_path = input_paths.join(", ");
+    let text = combine_documents(&documents);
+
+    if documents.is_empty() {
+        info!("📖 Streaming text from: {}", input_path);
+    } else if documents.len() > 1 {
+        info!("📖 Combined {} files ({} characters)", documents.len(), text.len());
+    } else {
+        info!("📖 Loaded text from: {} ({} characters)", input_path, text.len());
+    }
+    if let Some(path) = annotations_path {
+        AnnotationFormat::from_path(path)?;
     }
 
-    println!("📖 Loaded text from: {} ({} characters)", input_path, text.len());
+    // Parse source type; the pipeline detects it again and records it as detected
+    let source_type = match source_type.to_lowercase().as_str() {
+        "auto" => SourceType::Auto,
+        "chat" | "chatmessage" => SourceType::ChatMessage,
+        "document" | "doc" => SourceType::Document,
+        "email" => SourceType::Email,
+        "article" => SourceType::Article,
+        "html" => SourceType::Html,
+        "markdown" | "md" => SourceType::Markdown,
+        _ => SourceType::Unknown,
+    };
+    let detected_preset = if matches!(source_type, SourceType::Auto) && !text.is_empty() {
+        let detected = SourceType::detect(&text);
+        info!("🔎 Detected source type: {:?}", detected);
+        detected.preset()
+    } else {
+        None
+    };
 
     // Load configuration
     let mut config = if let Some(config_path) = config_path {
-        let config_content = fs::read_to_string(config_path)
-            .map_err(|e| msg_net::error::GraphError::Io(e))?;
-        serde_json::from_str::<GraphConfig>(&config_content)
-            .map_err(|e| msg_net::error::GraphError::Json(e))?
+        load_config(config_path)?
+    } else if let Some(preset) = preset.or(detected_preset) {
+        info!("🎛️  Using the {} preset: {}", preset, preset.description());
+        preset.config()
     } else {
         GraphConfig::default()
     };
+    apply_profile(&mut config, profile)?;
 
     // Override config with CLI options
     if use_llm {
@@ -278,109 +1037,994 @@ async fn generate_graph(
         config.extraction.llm_model = llm_model.to_string();
         config.extraction.llm_endpoint = llm_endpoint.to_string();
     }
+    if no_cache {
+        config.extraction.llm_cache.enabled = false;
+    }
+    if let Some(ttl) = cache_ttl {
+        config.extraction.llm_cache.ttl_seconds = Some(ttl);
+    }
+    if whitelist.is_some() {
+        config.extraction.entity_whitelist = whitelist;
+    }
+    if let Some(mode) = mode {
+        config.extraction.mode = mode;
+    }
+    config.extraction.plugins.extend(plugins);
+    if deterministic {
+        config.deterministic = true;
+    }
+    if prune_isolated {
+        config.prune_isolated = true;
+    }
+    if let Some(min_degree) = min_degree {
+        config.min_degree = min_degree;
+    }
+    if max_nodes.is_some() {
+        config.max_nodes = max_nodes;
+    }
+    if max_edges.is_some() {
+        config.max_edges = max_edges;
+    }
 
-    // Parse source type
-    let source_type = match source_type.to_lowercase().as_str() {
-        "chat" | "chatmessage" => SourceType::ChatMessage,
-        "document" | "doc" => SourceType::Document,
-        "email" => SourceType::Email,
-        "article" => SourceType::Article,
-        _ => SourceType::Unknown,
-    };
+    // CLI stopword flags take precedence over the config file
+    config.text_processing.remove_stopwords = !no_remove_stopwords;
+    if let Some(stopwords_file) = stopwords_file {
+        config.text_processing.stopwords_file = Some(stopwords_file.to_string());
+    }
 
-    // Process text
-    println!("🔍 Processing text...");
-    let processor = TextProcessor::new_with_options(stopwords_file, !no_remove_stopwords)?;
-    let processed_text = processor.process_text(&text, source_type)?;
-    
-    println!(
-        "📊 Text processed: {} words, {} sentences",
-        processed_text.metadata.word_count,
-        processed_text.metadata.sentence_count
-    );
+    if bounded_memory {
+        return stream_json_lines(input_paths, &text, output_path, format, &config, source_type, deep_analysis).await;
+    }
+
+    if dry_run {
+        let estimate = Pipeline::new()
+            .with_config(config)
+            .with_text(text)
+            .with_source_type(source_type)
+            .with_deep_analysis(deep_analysis)
+            .estimate()?;
+        println!("\n🧮 DRY RUN: nothing was extracted or written");
+        println!("==========================================");
+        println!("{}", estimate);
+        return Ok(Vec::new());
+    }
+
+    // Show what an LLM run will cost before making any calls, and stop if it is over budget
+    if config.extraction.uses_llm() {
+        let estimate = Pipeline::new()
+            .with_config(config.clone())
+            .with_text(text.clone())
+            .with_source_type(source_type.clone())
+            .with_deep_analysis(deep_analysis)
+            .estimate()?;
+        if estimate.llm_calls > 0 {
+            info!(
+                "🧮 Estimated {} LLM calls, ~{} tokens{}",
+                estimate.llm_calls,
+                estimate.total_tokens(),
+                estimate.cost.map(|cost| format!(", ~{:.4} {}", cost, estimate.currency)).unwrap_or_default()
+            );
+            if let Some(overrun) = estimate.budget_overrun(&config.extraction.llm_cost) {
+                if !confirm {
+                    warn!("💸 The {}; pass --confirm to be asked before such runs", overrun);
+                } else if !confirm_prompt(&format!("The {}. Continue?", overrun))? {
+                    return Err(msg_net::error::GraphError::Configuration(format!("Cancelled: the {}", overrun)));
+                }
+            }
+        }
+    }
+
+    // Process text, extract entities and build the graph, reporting progress as we go
+    let mut pipeline = Pipeline::new().with_config(config.clone()).with_text(text.clone());
+    if let Some(ocr) = ocr {
+        pipeline = pipeline.with_ocr(ocr);
+    }
+    let output = pipeline
+        .with_source_type(source_type)
+        .with_deep_analysis(deep_analysis)
+        .with_observer(CliProgress { span: span.clone() })
+        .run_detailed()
+        .await?;
+    let PipelineOutput { mut graph, extraction } = output;
+    if documents.len() > 1 {
+        record_sources(&mut graph, &documents);
+    }
+    if let Some(filter) = filter {
+        let total = graph.nodes.len();
+        graph = filter.subgraph(&graph);
+        info!("🔎 Filter {} kept {} of {} nodes", filter, graph.nodes.len(), total);
+    }
+    info!("📐 Graph statistics:");
+    for line in graph.stats().to_string().lines() {
+        info!("   {}", line);
+    }
+
+    let below_minimum = min_results.is_some_and(|min| graph.nodes.len() < min);
+    if is_sparse(&graph) || below_minimum {
+        warn!(
+            "⚠️  The graph has only {} nodes ({} entities and {} concepts extracted). Things to try:",
+            graph.nodes.len(),
+            extraction.entities.len(),
+            extraction.concepts.len()
+        );
+        for hint in empty_result_hints(&config, &extraction, &graph) {
+            warn!("   💡 {}", hint);
+        }
+    }
+    if let (Some(min), true) = (min_results, below_minimum) {
+        return Err(msg_net::error::GraphError::GraphBuilding(format!(
+            "Found {} nodes, fewer than --min-results {}; nothing was written",
+            graph.nodes.len(),
+            min
+        )));
+    }
+
+    // Export graph
+    info!("💾 Exporting graph...");
+    let exporter = GraphExporter::new();
+    let mut written = Vec::new();
+    if let Some(plugin_path) = export_plugin {
+        let plugin = WasmPlugin::load(plugin_path)?;
+        let contents = plugin.export(&graph)?;
+        fs::write(output_path, &contents)?;
+        span.pb_inc(1);
+        info!("✅ Graph exported by plugin {} to: {} ({} bytes)", plugin.name(), output_path, contents.len());
+        written.push(output_path.to_string());
+    } else {
+        let mut all_options = Vec::new();
+        for (export_format, path) in export_targets(output_path, format)? {
+            GraphExporter::validate_export_path(&path, &export_format)?;
+            all_options.push(ExportOptions {
+                source_text: export_format.is_report().then(|| text.clone()),
+                format: export_format,
+                include_metadata,
+                include_styling: true,
+                compact_output: false,
+                file_path: Some(path),
+                embed_assets: offline,
+                theme: theme.clone(),
+            });
+        }
+
+        // Formats are independent, so several are rendered and written side by side
+        let results = exporter.export_graphs(&graph, &all_options);
+        span.pb_inc(1);
+
+        let mut first_error = None;
+        for (export_options, export_result) in all_options.iter().zip(results) {
+            let export_result = match export_result {
+                Ok(export_result) if export_result.success => export_result,
+                Ok(export_result) => {
+                    let error = export_result.error_message.unwrap_or_else(|| "Export failed".to_string());
+                    first_error.get_or_insert(msg_net::error::GraphError::Export(error));
+                    continue;
+                }
+                Err(error) => {
+                    warn!("❌ {} export failed: {}", export_options.format.display_name(), error);
+                    first_error.get_or_insert(error);
+                    continue;
+                }
+            };
+            let actual_path = export_result.file_path.as_deref().or(export_options.file_path.as_deref()).unwrap_or(output_path);
+            info!("✅ Graph exported successfully to: {}", actual_path);
+            written.push(actual_path.to_string());
+            if let Some(file_size) = export_result.metadata.file_size_bytes {
+                info!("📦 File size: {} bytes", file_size);
+            }
+
+            if export_options.format == ExportFormat::Html {
+                info!("🌐 Open the HTML file in your web browser to view the interactive graph!");
+            }
+            if export_options.format == ExportFormat::Timeline && graph.nodes.iter().all(|node| node.metadata.dates.is_empty()) {
+                warn!("🕒 No dates were found next to any entity, so the timeline is empty");
+            }
+            if export_options.format == ExportFormat::Csv && graph.config.csv.layout == CsvLayout::Tables {
+                let edges_path = GraphExporter::csv_edges_path(actual_path);
+                info!("✅ Edges table exported to: {}", edges_path);
+                written.push(edges_path);
+            }
+            if export_options.format == ExportFormat::Chunked {
+                let folder = std::path::Path::new(actual_path).parent().map(|dir| dir.display().to_string()).unwrap_or_default();
+                info!("🌐 Serve {} over HTTP (e.g. python3 -m http.server) so the page can load its chunk files", folder);
+            }
+            // The link itself goes to stdout, even with -q, so it can be piped to a clipboard tool
+            if let (ExportFormat::ShareUrl, Some(link)) = (&export_options.format, &export_result.content) {
+                println!("{}", link.trim_end());
+            }
+        }
+        if let Some(error) = first_error {
+            return Err(error);
+        }
+    }
+
+    if let Some(path) = annotations_path {
+        let annotations = StandoffAnnotations::from_extraction(&text, &extraction);
+        annotations.write(path)?;
+        info!(
+            "🏷️  Wrote {} mentions and {} relations as annotations to {}",
+            annotations.mentions.len(),
+            annotations.relations.len(),
+            path
+        );
+    }
+
+    if let Some(db_path) = db_path {
+        let mut store = GraphStore::open(db_path)?;
+        let info = RunInfo {
+            name: match input_paths {
+                [first, rest @ ..] if !rest.is_empty() => format!("{} (+{} more)", run_name(first), rest.len()),
+                _ => run_name(&input_path),
+            },
+            source: Some(input_path.clone()),
+        };
+        let run_id = store.save_run(&graph, Some(&extraction), &info)?;
+        info!("🗄️  Saved run {} to {}", run_id, db_path);
+    }
+
+    if let Some(append) = append {
+        let append_result = exporter.append_graph(&graph, append)?;
+        info!(
+            "🗂️  Appended {} nodes to {} and {} edges to {} (run id: {})",
+            append_result.nodes_written,
+            append_result.nodes_path,
+            append_result.edges_written,
+            append_result.edges_path,
+            append.run_id
+        );
+    }
+
+    Ok(written)
+}
+
+/// Logs `generate`'s progress lines and advances its progress bar as the pipeline runs.
+/// Warnings need nothing here, the library logs them itself.
+struct CliProgress {
+    span: Span,
+}
+
+impl PipelineObserver for CliProgress {
+    fn on_stage_start(&self, stage: PipelineStage) {
+        match stage {
+            PipelineStage::TextProcessing => info!("🔍 Processing text..."),
+            PipelineStage::Extraction => info!("🧠 Extracting entities and relationships..."),
+            PipelineStage::GraphBuilding => info!("🎯 Building interactive graph..."),
+        }
+    }
+
+    fn on_stage_end(&self, _stage: PipelineStage, _elapsed: std::time::Duration, summary: &PipelineEvent) {
+        match summary {
+            PipelineEvent::TextProcessed { word_count, sentence_count } => {
+                info!("📊 Text processed: {} words, {} sentences", word_count, sentence_count)
+            }
+            PipelineEvent::ExtractionCompleted { entities, relationships, concepts } => info!(
+                "✨ Extracted: {} entities, {} relationships, {} concepts",
+                entities, relationships, concepts
+            ),
+            PipelineEvent::GraphBuilt { nodes, edges } => info!("📈 Graph built: {} nodes, {} edges", nodes, edges),
+            _ => {}
+        }
+        self.span.pb_inc(1);
+    }
+
+    fn on_llm_progress(&self, llm: &LlmProgress) {
+        if llm.done {
+            self.span.pb_set_message("");
+        } else {
+            self.span.pb_set_message(&format!("🤖 {} tokens", llm.tokens));
+        }
+    }
+}
 
-    // Extract entities, relationships, and concepts
-    println!("🧠 Extracting entities and relationships...");
-    let extractor = EntityExtractor::new(config.extraction.clone())?;
-    let extraction_result = if deep_analysis {
-        extractor.extract_with_deep_analysis(&processed_text).await?
+/// Text of one `-i` file, through ASR for recordings and OCR (whose word confidences
+/// are returned too) for scans and images
+async fn load_input_file(input_path: &str, ocr_language: &str, asr: Option<&AsrBackend>) -> Result<(String, Option<OcrText>)> {
+    if is_audio_input(input_path) {
+        let backend = asr.ok_or_else(|| {
+            msg_net::error::GraphError::Configuration(format!(
+                "Cannot read {}: audio input needs --asr-command or --asr-endpoint",
+                input_path
+            ))
+        })?;
+        info!("🎙️  Transcribing {}", input_path);
+        let transcript = transcribe(input_path, backend).await?;
+        info!(
+            "🎙️  Transcribed {} segments from {} speakers",
+            transcript.segments.len(),
+            transcript.speakers().len()
+        );
+        return Ok((transcript.to_chat_text(), None));
+    }
+    if is_ocr_input(input_path) {
+        info!("🖨️  Reading {} with OCR ({})", input_path, ocr_language);
+        let ocr = ocr_file(input_path, ocr_language)?;
+        if let Some(confidence) = ocr.mean_confidence() {
+            info!("🖨️  Recognized {} words, mean confidence {:.0}%", ocr.words.len(), confidence * 100.0);
+        }
+        return Ok((ocr.text.clone(), Some(ocr)));
+    }
+    Ok((fs::read_to_string(input_path).map_err(msg_net::error::GraphError::Io)?, None))
+}
+
+/// The name and, unless it is still to be read from the `-i` file, the text of the input
+async fn read_input_source(input: Option<String>, from_clipboard: bool, from_url: Option<&str>) -> Result<(String, Option<String>)> {
+    if from_clipboard {
+        info!("📋 Reading input from the clipboard");
+        return Ok(("clipboard".to_string(), Some(read_clipboard()?)));
+    }
+    if let Some(url) = from_url {
+        info!("🌐 Fetching {}", url);
+        let page = fetch_url(url).await?;
+        info!("🌐 Kept {} blocks of main content", page.paragraphs.len() + usize::from(page.title.is_some()));
+        return Ok((url.to_string(), Some(page.to_text())));
+    }
+    // clap requires -i when neither source is given
+    Ok((input.unwrap_or_default(), None))
+}
+
+/// Run name recorded in the database: the input's file name
+fn run_name(input_path: &str) -> String {
+    std::path::Path::new(input_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| input_path.to_string())
+}
+
+/// Parse the JSON config file at `path`
+fn load_config(path: &str) -> Result<GraphConfig> {
+    let content = fs::read_to_string(path).map_err(msg_net::error::GraphError::Io)?;
+    serde_json::from_str::<GraphConfig>(&content).map_err(msg_net::error::GraphError::Json)
+}
+
+/// Apply `--profile`, if given, to the loaded config
+fn apply_profile(config: &mut GraphConfig, profile: Option<&str>) -> Result<()> {
+    if let Some(profile) = profile {
+        info!("🎛️  Using the {} profile", profile);
+        config.apply_profile(profile)?;
+    }
+    Ok(())
+}
+
+/// `generate --bounded-memory`: extract `text`, or the input files when it is empty, one
+/// chunk at a time, writing each chunk's new nodes and edges to the JSON Lines output
+async fn stream_json_lines(
+    input_paths: &[String],
+    text: &str,
+    output_path: &str,
+    format: &str,
+    config: &GraphConfig,
+    source_type: SourceType,
+    deep_analysis: bool,
+) -> Result<Vec<String>> {
+    if parse_export_format(format)? != ExportFormat::JsonLines {
+        return Err(msg_net::error::GraphError::Configuration(
+            "--bounded-memory writes the graph as it is built, which needs -f jsonl".to_string(),
+        ));
+    }
+    GraphExporter::validate_export_path(output_path, &ExportFormat::JsonLines)?;
+    let path = GraphExporter::new().create_output_path(output_path)?;
+
+    let reader: Box<dyn BufRead + '_> = if text.is_empty() {
+        let mut files: Box<dyn BufRead> = Box::new(std::io::empty());
+        for input_path in input_paths {
+            files = Box::new(files.chain(BufReader::new(fs::File::open(input_path)?)));
+        }
+        files
     } else {
-        extractor.extract_from_text(&processed_text).await?
+        Box::new(text.as_bytes())
     };
-    
-    println!(
-        "✨ Extracted: {} entities, {} relationships, {} concepts",
-        extraction_result.metadata.total_entities,
-        extraction_result.metadata.total_relationships,
-        extraction_result.metadata.total_concepts
+    let writer = BufWriter::new(fs::File::create(&path)?);
+    let metadata = stream_graph(config, source_type, deep_analysis, reader, writer).await?;
+    info!(
+        "✅ Streamed {} nodes and {} edges to: {}",
+        metadata.total_nodes, metadata.total_edges, path
     );
+    Ok(vec![path])
+}
 
-    // Build graph
-    println!("🎯 Building interactive graph...");
-    let graph_builder = GraphBuilder::new(config);
-    let mut graph = graph_builder.build_graph(&extraction_result, &text)?;
-    
-    // Apply layout
-    graph_builder.apply_layout(&mut graph)?;
-    
-    println!("📈 Graph built: {} nodes, {} edges", graph.nodes.len(), graph.edges.len());
+/// The format and output path of each export: `-f html,graphml` writes `graph.html` and
+/// `graph.graphml` next to `-o graph.html`. A single format keeps the output path as given.
+fn export_targets(output_path: &str, formats: &str) -> Result<Vec<(ExportFormat, String)>> {
+    let mut parsed: Vec<(&str, ExportFormat)> = Vec::new();
+    for name in formats.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+        let export_format = parse_export_format(name)?;
+        if !parsed.iter().any(|(_, existing)| *existing == export_format) {
+            parsed.push((name, export_format));
+        }
+    }
+    match parsed.len() {
+        0 => Err(msg_net::error::GraphError::Export("No export format given".to_string())),
+        1 => Ok(vec![(parsed.remove(0).1, output_path.to_string())]),
+        _ => {
+            let path = std::path::Path::new(output_path);
+            let stem = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_else(|| "graph".to_string());
+            let mut targets: Vec<(ExportFormat, String)> = Vec::new();
+            for (name, export_format) in parsed {
+                let mut target = path.with_file_name(format!("{}.{}", stem, export_format.extension()));
+                // HTML graph and HTML report (or CSV and adjacency matrix) share an extension; tell them apart by name
+                if targets.iter().any(|(_, existing)| std::path::Path::new(existing) == target) {
+                    target = path.with_file_name(format!("{}_{}.{}", stem, name, export_format.extension()));
+                }
+                targets.push((export_format, target.to_string_lossy().to_string()));
+            }
+            Ok(targets)
+        }
+    }
+}
 
-    // Export graph
-    println!("💾 Exporting graph...");
-    let export_format = match format.to_lowercase().as_str() {
-        "html" => ExportFormat::Html,
-        "json" => ExportFormat::Json,
-        "csv" => ExportFormat::Csv,
-        "graphml" => ExportFormat::GraphML,
-        "dot" => ExportFormat::Dot,
-        _ => return Err(msg_net::error::GraphError::Export(
+fn parse_export_format(format: &str) -> Result<ExportFormat> {
+    match format.to_lowercase().as_str() {
+        "html" => Ok(ExportFormat::Html),
+        "json" => Ok(ExportFormat::Json),
+        "csv" => Ok(ExportFormat::Csv),
+        "graphml" => Ok(ExportFormat::GraphML),
+        "dot" => Ok(ExportFormat::Dot),
+        "tikz" | "tex" => Ok(ExportFormat::Tikz),
+        "mermaid" | "mmd" => Ok(ExportFormat::Mermaid),
+        "markdown" | "md" => Ok(ExportFormat::Markdown),
+        "html-report" | "report" => Ok(ExportFormat::HtmlReport),
+        "share-url" | "share" | "url" => Ok(ExportFormat::ShareUrl),
+        "chunked" | "chunked-html" | "chunks" => Ok(ExportFormat::Chunked),
+        "edgelist" | "edge-list" | "tsv" => Ok(ExportFormat::EdgeList),
+        "adjacency" | "adjacency-matrix" | "matrix" => Ok(ExportFormat::AdjacencyMatrix),
+        "outline" | "tree" => Ok(ExportFormat::Outline),
+        "jsonl" | "jsonlines" | "ndjson" => Ok(ExportFormat::JsonLines),
+        "timeline" => Ok(ExportFormat::Timeline),
+        _ => Err(msg_net::error::GraphError::Export(
             format!("Unsupported export format: {}", format)
         )),
+    }
+}
+
+fn parse_extraction_mode(mode: &str) -> Result<ExtractionMode> {
+    match mode.to_lowercase().as_str() {
+        "entities" | "entity" => Ok(ExtractionMode::Entities),
+        "cooccurrence" | "co-occurrence" => Ok(ExtractionMode::Cooccurrence),
+        "hierarchy" | "org-chart" | "orgchart" => Ok(ExtractionMode::Hierarchy),
+        _ => Err(msg_net::error::GraphError::Configuration(format!(
+            "Unknown extraction mode '{}': expected entities, cooccurrence or hierarchy",
+            mode
+        ))),
+    }
+}
+
+/// Combine `--only` names and `--only-file` lines; `None` when neither was given
+fn load_whitelist(only: Vec<String>, only_file: Option<&str>) -> Result<Option<Vec<String>>> {
+    let requested = !only.is_empty() || only_file.is_some();
+    let mut names: Vec<String> = only.into_iter().map(|name| name.trim().to_string()).collect();
+    if let Some(path) = only_file {
+        let content = fs::read_to_string(path)
+            .map_err(msg_net::error::GraphError::Io)?;
+        names.extend(content.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')).map(String::from));
+    }
+    names.retain(|name| !name.is_empty());
+
+    match (requested, names.is_empty()) {
+        (false, _) => Ok(None),
+        (true, true) => Err(msg_net::error::GraphError::Configuration(
+            "--only/--only-file given but no entity names found".to_string(),
+        )),
+        (true, false) => Ok(Some(names)),
+    }
+}
+
+async fn explain_graph(
+    graph_path: &str,
+    question: &str,
+    config_path: Option<&str>,
+    profile: Option<&str>,
+    llm_model: Option<String>,
+    llm_endpoint: Option<String>,
+) -> Result<()> {
+    let graph = InteractiveGraph::from_json_file(graph_path)?;
+
+    let mut extraction = match config_path {
+        Some(config_path) => {
+            let mut config = load_config(config_path)?;
+            apply_profile(&mut config, profile)?;
+            config.extraction
+        }
+        // Graphs only keep rendering settings, not the extraction config they were built with
+        None => ExtractionConfig::default(),
     };
+    if let Some(model) = llm_model {
+        extraction.llm_model = model;
+    }
+    if let Some(endpoint) = llm_endpoint {
+        extraction.llm_endpoint = endpoint;
+    }
 
-    let export_options = ExportOptions {
-        format: export_format,
-        include_metadata,
-        include_styling: true,
-        compact_output: false,
-        file_path: Some(output_path.to_string()),
+    info!("🤔 Asking {} about {} ({} nodes, {} edges)...", extraction.llm_model, graph_path, graph.nodes.len(), graph.edges.len());
+    let explanation = msg_net::explain::explain(&graph, question, &extraction).await?;
+    let subgraph = &explanation.subgraph;
+
+    println!("{}", explanation.answer);
+    println!();
+    if subgraph.path.is_empty() {
+        println!("Relevant edges ({} of {}):", subgraph.edges.len(), graph.edges.len());
+        for edge in subgraph.edges.iter().take(10) {
+            println!("  {}", subgraph.describe_edge(edge));
+        }
+    } else {
+        println!("Supporting path:");
+        for edge in &subgraph.path {
+            println!("  {}", subgraph.describe_edge(edge));
+        }
+    }
+
+    Ok(())
+}
+
+fn run_db_command(db_path: &str, action: DbAction) -> Result<()> {
+    let mut store = GraphStore::open(db_path)?;
+
+    match action {
+        DbAction::Save { input, name } => {
+            let content = fs::read_to_string(&input)
+                .map_err(msg_net::error::GraphError::Io)?;
+            let graph = graph_from_json(&content).map_err(|e| msg_net::error::GraphError::Storage(format!(
+                "{} is not a graph JSON export (generate it with -f json): {}",
+                input, e
+            )))?;
+            let info = RunInfo {
+                name: name.unwrap_or_else(|| run_name(&input)),
+                source: Some(input.clone()),
+            };
+            let run_id = store.save_run(&graph, None, &info)?;
+            info!("🗄️  Saved {} as run {} in {}", input, run_id, db_path);
+        }
+        DbAction::Load { run, output, format } => {
+            let graph = store.load_graph(run)?;
+            let export_format = parse_export_format(&format)?;
+            GraphExporter::validate_export_path(&output, &export_format)?;
+            let options = ExportOptions {
+                format: export_format,
+                file_path: Some(output.clone()),
+                ..ExportOptions::default()
+            };
+            let export_result = GraphExporter::new().export_graph(&graph, &options)?;
+            let actual_path = export_result.file_path.as_deref().unwrap_or(&output);
+            info!("✅ Graph exported successfully to: {}", actual_path);
+        }
+        DbAction::List => {
+            let runs = store.list_runs()?;
+            if runs.is_empty() {
+                println!("No saved runs in {}", db_path);
+            }
+            for run in runs {
+                println!(
+                    "{:>4}  {}  {}  ({} nodes, {} edges{})",
+                    run.id,
+                    run.saved_at,
+                    run.name,
+                    run.node_count,
+                    run.edge_count,
+                    if run.has_extraction { ", with extraction" } else { "" }
+                );
+            }
+        }
+        DbAction::Find { entity } => {
+            let matches = store.find_entity(&entity)?;
+            if matches.is_empty() {
+                println!("No entities matching '{}' in {}", entity, db_path);
+            }
+            for found in matches {
+                println!("run {:>4} ({})  {}  [{}]", found.run_id, found.run_name, found.label, found.node_type);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_append_format(format: &str) -> Result<AppendFormat> {
+    match format.to_lowercase().as_str() {
+        "csv" => Ok(AppendFormat::Csv),
+        "jsonl" => Ok(AppendFormat::Jsonl),
+        _ => Err(msg_net::error::GraphError::Export(format!(
+            "Unsupported append format: {}",
+            format
+        ))),
+    }
+}
+
+/// Export the part of the graph at `graph_path` matching `expression`
+fn print_paths(graph_path: &str, from: &str, to: &str, max_paths: usize, highlight: Option<&str>) -> Result<()> {
+    let mut graph = InteractiveGraph::from_json_file(graph_path)?;
+    let ids = |label: &str| -> Result<Vec<String>> {
+        let nodes = graph.nodes_labelled(label);
+        if nodes.is_empty() {
+            return Err(msg_net::error::GraphError::Configuration(format!("No node labelled \"{}\" in {}", label, graph_path)));
+        }
+        Ok(nodes.iter().map(|node| node.id.clone()).collect())
     };
+    let (from_ids, to_ids) = (ids(from)?, ids(to)?);
+    let paths = graph.shortest_paths(
+        &from_ids.iter().map(String::as_str).collect::<Vec<_>>(),
+        &to_ids.iter().map(String::as_str).collect::<Vec<_>>(),
+        max_paths,
+    );
+
+    match paths.first() {
+        None => println!("No path between \"{}\" and \"{}\"", from, to),
+        Some(shortest) => {
+            let steps = shortest.edges.len();
+            println!("{} shortest path{} of {} step{}:", paths.len(), plural(paths.len()), steps, plural(steps));
+            for path in &paths {
+                println!("  {}", path.describe(&graph));
+            }
+        }
+    }
+
+    if let Some(path) = highlight {
+        graph.highlight_paths(&paths);
+        let options = ExportOptions {
+            format: ExportFormat::Html,
+            file_path: Some(path.to_string()),
+            ..ExportOptions::default()
+        };
+        GraphExporter::validate_export_path(path, &options.format)?;
+        let export_result = GraphExporter::new().export_graph(&graph, &options)?;
+        info!("✅ Highlighted graph written to: {}", export_result.file_path.as_deref().unwrap_or(path));
+    }
+    Ok(())
+}
+
+fn validate_graph(graph_path: &str, json: bool) -> Result<()> {
+    let report = validate_graph_json(&fs::read_to_string(graph_path)?);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("{}", report);
+    }
+    if report.is_valid() {
+        Ok(())
+    } else {
+        Err(msg_net::error::GraphError::GraphBuilding(format!(
+            "{} has {} error(s)",
+            graph_path,
+            report.errors()
+        )))
+    }
+}
+
+fn print_graph_schema(output_path: Option<&str>) -> Result<()> {
+    let schema = serde_json::to_string_pretty(&graph_json_schema())?;
+    match output_path {
+        Some(path) => {
+            fs::write(path, schema + "\n")?;
+            info!("✅ Graph JSON Schema (version {}) written to: {}", GRAPH_SCHEMA_VERSION, path);
+        }
+        None => println!("{}", schema),
+    }
+    Ok(())
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 { "" } else { "s" }
+}
+
+fn query_graph(graph_path: &str, expression: &str, output_path: &str, format: &str) -> Result<()> {
+    let filter: GraphFilter = expression.parse()?;
+    let graph = InteractiveGraph::from_json_file(graph_path)?;
+
+    let subgraph = filter.subgraph(&graph);
+    info!(
+        "🔎 {} matches {} of {} nodes and {} of {} edges",
+        filter,
+        subgraph.nodes.len(),
+        graph.nodes.len(),
+        subgraph.edges.len(),
+        graph.edges.len()
+    );
 
     let exporter = GraphExporter::new();
-    GraphExporter::validate_export_path(output_path, &export_options.format)?;
-    let export_result = exporter.export_graph(&graph, &export_options)?;
+    for (export_format, path) in export_targets(output_path, format)? {
+        GraphExporter::validate_export_path(&path, &export_format)?;
+        // Metadata is kept so the result can be queried again
+        let options = ExportOptions {
+            format: export_format,
+            include_metadata: true,
+            file_path: Some(path.clone()),
+            ..ExportOptions::default()
+        };
+        let export_result = exporter.export_graph(&subgraph, &options)?;
+        info!("✅ Graph exported successfully to: {}", export_result.file_path.as_deref().unwrap_or(&path));
+        if let (ExportFormat::ShareUrl, Some(link)) = (&options.format, &export_result.content) {
+            println!("{}", link.trim_end());
+        }
+    }
+    Ok(())
+}
 
-    if export_result.success {
-        let actual_path = export_result.file_path.as_deref().unwrap_or(output_path);
-        println!("✅ Graph exported successfully to: {}", actual_path);
-        if let Some(file_size) = export_result.metadata.file_size_bytes {
-            println!("📦 File size: {} bytes", file_size);
+fn render_graph(
+    graph_path: &str,
+    output_path: &str,
+    format: &str,
+    config_path: Option<&str>,
+    profile: Option<&str>,
+    layout: Option<&str>,
+    include_metadata: bool,
+) -> Result<()> {
+    let mut graph = InteractiveGraph::from_json_file(graph_path)?;
+    info!("📂 Loaded {}: {} nodes, {} edges", graph_path, graph.nodes.len(), graph.edges.len());
+    let report = graph.validate();
+    if !report.is_valid() {
+        for issue in report.issues.iter().filter(|issue| issue.severity == Severity::Error) {
+            warn!("⚠️  {}: {}", issue.path, issue.message);
         }
-        
-        if format == "html" {
-            println!("🌐 Open the HTML file in your web browser to view the interactive graph!");
+        warn!("   The graph may not render as expected; msg_net validate -g {} lists every issue", graph_path);
+    }
+
+    if let Some(config_path) = config_path {
+        let mut config = load_config(config_path)?;
+        apply_profile(&mut config, profile)?;
+        graph.config = RenderConfig::from(&config);
+        if config.parallel_edges == ParallelEdges::Merge {
+            graph.merge_parallel_edges();
         }
-    } else {
+    }
+    if let Some(algorithm) = layout {
+        graph.config.layout.algorithm = algorithm.to_string();
+        for node in &mut graph.nodes {
+            node.x = None;
+            node.y = None;
+        }
+        let config = GraphConfig { layout: graph.config.layout.clone(), ..GraphConfig::default() };
+        GraphBuilder::new(config).apply_layout(&mut graph)?;
+        info!("📐 Applied {} layout", algorithm);
+    }
+
+    let exporter = GraphExporter::new();
+    for (export_format, path) in export_targets(output_path, format)? {
+        GraphExporter::validate_export_path(&path, &export_format)?;
+        let options = ExportOptions {
+            format: export_format,
+            include_metadata,
+            file_path: Some(path.clone()),
+            ..ExportOptions::default()
+        };
+        let export_result = exporter.export_graph(&graph, &options)?;
+        info!("✅ Graph exported successfully to: {}", export_result.file_path.as_deref().unwrap_or(&path));
+        if let (ExportFormat::ShareUrl, Some(link)) = (&options.format, &export_result.content) {
+            println!("{}", link.trim_end());
+        }
+    }
+    Ok(())
+}
+
+fn report_graph(
+    graph_path: &str,
+    output_path: &str,
+    input_path: Option<&str>,
+    config_path: Option<&str>,
+    profile: Option<&str>,
+) -> Result<()> {
+    let mut graph = InteractiveGraph::from_json_file(graph_path)?;
+    if let Some(config_path) = config_path {
+        let mut config = load_config(config_path)?;
+        apply_profile(&mut config, profile)?;
+        graph.config = RenderConfig::from(&config);
+    }
+    let source_text = input_path.map(fs::read_to_string).transpose()?;
+
+    let is_html = std::path::Path::new(output_path)
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("html") || extension.eq_ignore_ascii_case("htm"));
+    let options = ExportOptions {
+        format: if is_html { ExportFormat::HtmlReport } else { ExportFormat::Markdown },
+        file_path: Some(output_path.to_string()),
+        source_text,
+        ..ExportOptions::default()
+    };
+    GraphExporter::validate_export_path(output_path, &options.format)?;
+    let export_result = GraphExporter::new().export_graph(&graph, &options)?;
+    if let Some(error) = export_result.error_message {
+        return Err(msg_net::error::GraphError::Export(error));
+    }
+    info!(
+        "✅ {} for {} entities written to: {}",
+        options.format.display_name(),
+        rank_entities(&graph).len(),
+        export_result.file_path.as_deref().unwrap_or(output_path)
+    );
+    Ok(())
+}
+
+async fn compare_methods(
+    input_path: &str,
+    output_path: &str,
+    format: &str,
+    config_path: Option<&str>,
+    profile: Option<&str>,
+    llm_model: Option<String>,
+    llm_endpoint: Option<String>,
+) -> Result<()> {
+    let mut config = match config_path {
+        Some(config_path) => load_config(config_path)?,
+        None => GraphConfig::default(),
+    };
+    apply_profile(&mut config, profile)?;
+    if let Some(model) = llm_model {
+        config.extraction.llm_model = model;
+    }
+    if let Some(endpoint) = llm_endpoint {
+        config.extraction.llm_endpoint = endpoint;
+    }
+    let text = fs::read_to_string(input_path)?;
+
+    let mut extractions = Vec::new();
+    for kind in [ExtractorKind::Patterns, ExtractorKind::Llm] {
+        let mut method_config = config.clone();
+        method_config.extraction.mode = ExtractionMode::Entities;
+        method_config.extraction.extractors = vec![ExtractorStep { kind, enabled: true }];
+        info!("🧠 Extracting with {:?}...", kind);
+        extractions.push(Pipeline::new().with_config(method_config).with_text(text.clone()).extract().await?);
+    }
+    let overlay = ComparisonOverlay::new(&extractions[0], &extractions[1]);
+    let graph = overlay.build_graph(&config, &text)?;
+
+    // The statistics go to stdout, even with -q, like other command results
+    println!("{}", overlay.comparison);
+
+    let exporter = GraphExporter::new();
+    for (export_format, path) in export_targets(output_path, format)? {
+        GraphExporter::validate_export_path(&path, &export_format)?;
+        let options = ExportOptions {
+            source_text: export_format.is_report().then(|| text.clone()),
+            format: export_format,
+            file_path: Some(path.clone()),
+            ..ExportOptions::default()
+        };
+        let export_result = exporter.export_graph(&graph, &options)?;
         if let Some(error) = export_result.error_message {
             return Err(msg_net::error::GraphError::Export(error));
         }
+        info!("✅ Comparison exported to: {}", export_result.file_path.as_deref().unwrap_or(&path));
+    }
+    Ok(())
+}
+
+async fn obsidian_graph(
+    vault_path: &str,
+    output_path: &str,
+    config_path: Option<&str>,
+    profile: Option<&str>,
+    format: &str,
+    notes_dir: Option<&str>,
+) -> Result<()> {
+    let mut config = match config_path {
+        Some(config_path) => load_config(config_path)?,
+        None => GraphConfig::default(),
+    };
+    apply_profile(&mut config, profile)?;
+
+    let vault = Vault::load(vault_path)?;
+    info!("📚 Loaded {} notes from vault: {}", vault.notes.len(), vault_path);
+    let extraction = extract_vault(&vault, &config).await?;
+    info!(
+        "✨ Extracted: {} entities (including notes), {} relationships, {} concepts",
+        extraction.entities.len(),
+        extraction.relationships.len(),
+        extraction.concepts.len()
+    );
+
+    let graph_builder = GraphBuilder::new(config);
+    let mut graph = graph_builder.build_graph(&extraction, &vault.source_text())?;
+    graph_builder.apply_layout(&mut graph)?;
+    info!("📈 Graph built: {} nodes, {} edges", graph.nodes.len(), graph.edges.len());
+
+    let export_format = parse_export_format(format)?;
+    let export_options = ExportOptions {
+        source_text: export_format.is_report().then(|| vault.source_text()),
+        format: export_format,
+        include_metadata: false,
+        include_styling: true,
+        compact_output: false,
+        file_path: Some(output_path.to_string()),
+        embed_assets: false,
+        theme: None,
+    };
+    GraphExporter::validate_export_path(output_path, &export_options.format)?;
+    let export_result = GraphExporter::new().export_graph(&graph, &export_options)?;
+    if let Some(error) = export_result.error_message {
+        return Err(msg_net::error::GraphError::Export(error));
+    }
+    info!("✅ Graph exported successfully to: {}", export_result.file_path.as_deref().unwrap_or(output_path));
+
+    if let Some(notes_dir) = notes_dir {
+        let written = write_entity_notes(&extraction, notes_dir)?;
+        info!("📝 Wrote {} entity notes to {}", written, notes_dir);
+    }
+
+    Ok(())
+}
+
+/// Ask a yes/no question on the terminal; anything but y/yes is no
+fn confirm_prompt(question: &str) -> Result<bool> {
+    use std::io::Write;
+    print!("{} [y/N] ", question);
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+async fn bootstrap_dataset(
+    input_path: &str,
+    output_path: &str,
+    config_path: Option<&str>,
+    profile: Option<&str>,
+    use_llm: bool,
+    review_path: Option<&str>,
+    review_fraction: f64,
+) -> Result<()> {
+    if !(0.0..=1.0).contains(&review_fraction) {
+        return Err(msg_net::error::GraphError::Configuration(format!(
+            "--review-fraction must be between 0 and 1, got {}",
+            review_fraction
+        )));
+    }
+    let mut config = match config_path {
+        Some(config_path) => load_config(config_path)?,
+        None => GraphConfig::default(),
+    };
+    apply_profile(&mut config, profile)?;
+    if use_llm {
+        config.extraction.use_llm = true;
+    }
+    let splitter = SentenceSplitter::new(&config.text_processing.sentence_splitting);
+
+    let files = dataset_inputs(input_path)?;
+    info!("📚 Bootstrapping a dataset from {} files in {}", files.len(), input_path);
+    let mut records = Vec::new();
+    for file in &files {
+        let text = fs::read_to_string(file)?;
+        if text.trim().is_empty() {
+            continue;
+        }
+        let output = Pipeline::new().with_config(config.clone()).with_text(text.clone()).run_detailed().await?;
+        let annotations = StandoffAnnotations::from_extraction(&text, &output.extraction);
+        let source = file.display().to_string();
+        let file_records = DatasetRecord::from_annotations(&source, &annotations, &splitter);
+        info!(
+            "  {}: {} sentences, {} mentions, {} relations",
+            source,
+            file_records.len(),
+            annotations.mentions.len(),
+            annotations.relations.len()
+        );
+        records.extend(file_records);
     }
 
+    let (review, train): (Vec<_>, Vec<_>) = match review_path {
+        Some(_) => records.iter().partition(|record| record.in_review_split(review_fraction)),
+        None => (Vec::new(), records.iter().collect()),
+    };
+    let written = write_dataset(output_path, train)?;
+    info!("✅ Wrote {} sentences to {}", written, output_path);
+    if let Some(review_path) = review_path {
+        let held_out = write_dataset(review_path, review)?;
+        info!("🔎 Held out {} sentences for review in {}", held_out, review_path);
+    }
     Ok(())
 }
 
 async fn analyze_text(
     input_path: &str,
+    input_text: Option<String>,
     verbose: bool,
     config_path: Option<&str>,
+    profile: Option<&str>,
     stopwords_file: Option<&str>,
     no_remove_stopwords: bool,
 ) -> Result<()> {
-    println!("🔍 Analyzing text file: {}", input_path);
+    info!("🔍 Analyzing text file: {}", input_path);
 
     // Load text
-    let text = fs::read_to_string(input_path)
-        .map_err(|e| msg_net::error::GraphError::Io(e))?;
+    let text = match input_text {
+        Some(text) => text,
+        None => fs::read_to_string(input_path).map_err(msg_net::error::GraphError::Io)?,
+    };
     
     if text.trim().is_empty() {
         return Err(msg_net::error::GraphError::TextProcessing(
@@ -389,18 +2033,19 @@ async fn analyze_text(
     }
 
     // Load configuration
-    let config = if let Some(config_path) = config_path {
-        let config_content = fs::read_to_string(config_path)
-            .map_err(|e| msg_net::error::GraphError::Io(e))?;
-        serde_json::from_str::<GraphConfig>(&config_content)
-            .map_err(|e| msg_net::error::GraphError::Json(e))?
+    let mut config = if let Some(config_path) = config_path {
+        load_config(config_path)?
     } else {
         GraphConfig::default()
     };
+    apply_profile(&mut config, profile)?;
 
     // Process text
-    let processor = TextProcessor::new_with_options(stopwords_file, !no_remove_stopwords)?;
-    let processed_text = processor.process_text(&text, SourceType::Document)?;
+    let mut processor = TextProcessor::new_with_options(stopwords_file, !no_remove_stopwords)?;
+    processor.set_strip_prompt_stopwords(config.text_processing.strip_prompt_stopwords);
+    processor.set_stem_words(config.text_processing.stem_words);
+    processor.set_sentence_splitter(SentenceSplitter::new(&config.text_processing.sentence_splitting));
+    let processed_text = processor.process_text(&text, SourceType::Auto)?;
 
     // Basic analysis
     println!("\n📊 TEXT ANALYSIS RESULTS");
@@ -409,21 +2054,26 @@ async fn analyze_text(
     println!("Word count: {}", processed_text.metadata.word_count);
     println!("Sentence count: {}", processed_text.metadata.sentence_count);
     println!("Detected language: {}", processed_text.metadata.language);
-    println!("Source type: {:?}", processed_text.metadata.source_type);
+    println!(
+        "Source type: {:?}{}",
+        processed_text.metadata.source_type,
+        if processed_text.metadata.source_type_detected { " (detected)" } else { "" }
+    );
 
     if verbose {
         println!("\n🔍 DETAILED ANALYSIS");
         println!("====================");
         
         // Extract key phrases
-        let key_phrases = processor.extract_key_phrases(&processed_text.cleaned_text)?;
+        let key_phrases = processor.extract_key_phrases(&processed_text.cleaned_text())?;
         println!("Key phrases found: {}", key_phrases.len());
         for (i, phrase) in key_phrases.iter().take(10).enumerate() {
             println!("  {}. {}", i + 1, phrase);
         }
         
         // Preview entities extraction
-        let extractor = EntityExtractor::new(config.extraction.clone())?;
+        let mut extractor = EntityExtractor::new(config.extraction.clone())?;
+        extractor.set_sentence_splitter(processor.sentence_splitter().clone());
         let extraction_result = extractor.extract_from_text(&processed_text).await?;
         
         println!("\n🧠 ENTITY EXTRACTION PREVIEW");
@@ -443,25 +2093,33 @@ async fn analyze_text(
         for (i, concept) in extraction_result.concepts.iter().take(5).enumerate() {
             println!("  {}. {}", i + 1, concept.name);
         }
+
+        let graph = GraphBuilder::new(config.clone()).build_graph(&extraction_result, &text)?;
+        println!("\n📐 GRAPH STATISTICS");
+        println!("===================");
+        println!("{}", graph.stats());
     }
 
-    println!("\n✅ Analysis complete!");
+    info!("✅ Analysis complete!");
     
     Ok(())
 }
 
-fn generate_config(output_path: &str) -> Result<()> {
-    println!("📄 Generating sample configuration file...");
+fn generate_config(output_path: &str, preset: Option<&str>) -> Result<()> {
+    info!("📄 Generating sample configuration file...");
     
-    let config = GraphConfig::default();
+    let config = match preset {
+        Some(name) => name.parse::<Preset>()?.config(),
+        None => GraphConfig::default(),
+    };
     let config_json = serde_json::to_string_pretty(&config)
-        .map_err(|e| msg_net::error::GraphError::Json(e))?;
+        .map_err(msg_net::error::GraphError::Json)?;
     
     fs::write(output_path, config_json)
-        .map_err(|e| msg_net::error::GraphError::Io(e))?;
+        .map_err(msg_net::error::GraphError::Io)?;
     
-    println!("✅ Configuration file created: {}", output_path);
-    println!("📝 You can edit this file to customize graph appearance and extraction settings.");
+    info!("✅ Configuration file created: {}", output_path);
+    info!("📝 You can edit this file to customize graph appearance and extraction settings.");
     
     Ok(())
 }
@@ -480,11 +2138,11 @@ The development team, led by Carol, continuously improves the system by adding n
 "#;
 
     fs::write(output_path, example_text.trim())
-        .map_err(|e| msg_net::error::GraphError::Io(e))?;
+        .map_err(msg_net::error::GraphError::Io)?;
     
-    println!("✅ Example text file created: {}", output_path);
-    println!("📝 You can use this file to test the graph generation:");
-    println!("   msg_net generate -i {} -o example_graph.html", output_path);
+    info!("✅ Example text file created: {}", output_path);
+    info!("📝 You can use this file to test the graph generation:");
+    info!("   msg_net generate -i {} -o example_graph.html", output_path);
     
     Ok(())
 }
@@ -513,7 +2171,7 @@ async fn generate_ai_story_text(
         done: bool,
     }
 
-    println!("🤖 Generating AI story with {} words using {}...", word_count, llm_model);
+    info!("🤖 Generating AI story with {} words using {}...", word_count, llm_model);
     
     let prompt = format!(
         "Write a short story of approximately {} words that includes several characters, locations, and organizations. \
@@ -530,7 +2188,7 @@ async fn generate_ai_story_text(
         stream: false,
     };
 
-    println!("📡 Calling Ollama API...");
+    info!("📡 Calling Ollama API...");
     let response = client
         .post(llm_endpoint)
         .json(&request)
@@ -556,12 +2214,12 @@ async fn generate_ai_story_text(
     let actual_words = story.split_whitespace().count();
     
     fs::write(output_path, story)
-        .map_err(|e| msg_net::error::GraphError::Io(e))?;
+        .map_err(msg_net::error::GraphError::Io)?;
     
-    println!("✅ AI-generated story created: {}", output_path);
-    println!("📊 Generated {} words (requested: {})", actual_words, word_count);
-    println!("📝 You can use this file to test the graph generation:");
-    println!("   msg_net generate -i {} -o ai_story_graph.html", output_path);
+    info!("✅ AI-generated story created: {}", output_path);
+    info!("📊 Generated {} words (requested: {})", actual_words, word_count);
+    info!("📝 You can use this file to test the graph generation:");
+    info!("   msg_net generate -i {} -o ai_story_graph.html", output_path);
     
     Ok(())
 }
@@ -608,6 +2266,7 @@ fn show_usage_examples() -> Result<()> {
     println!("  • CSV (tabular format)");
     println!("  • GraphML (XML-based graph format)");
     println!("  • DOT (Graphviz format)");
+    println!("  • TikZ (LaTeX picture for papers)");
     println!();
     
     println!("🔧 FEATURES:");
@@ -649,6 +2308,9 @@ fn show_comprehensive_help() -> Result<()> {
     println!("   cargo run -- generate -i sample.txt -o data.csv -f csv");
     println!("   cargo run -- generate -i sample.txt -o graph.graphml -f graphml");
     println!("   cargo run -- generate -i sample.txt -o graph.dot -f dot");
+    println!("   cargo run -- generate -i sample.txt -o graph.tex -f tikz");
+    println!("   cargo run -- generate -i sample.txt -o graph.mmd -f mermaid");
+    println!("   cargo run -- generate -i sample.txt -o report.md -f markdown");
     println!();
     
     println!("🧠 ADVANCED ANALYSIS:");
@@ -692,6 +2354,9 @@ fn show_comprehensive_help() -> Result<()> {
     println!("   cargo run -- config -o my_config.json");
     println!("   # Edit my_config.json to customize colors, shapes, extraction patterns");
     println!("   cargo run -- generate -i text.txt -o graph.html -c my_config.json");
+    println!("   # Or start from a preset tuned for a document type:");
+    println!("   cargo run -- generate -i standup.txt -o graph.html --preset meeting-notes");
+    println!("   cargo run -- config -o novel.json --preset novel");
     println!();
     
     println!("12. Analyze text without generating output:");
@@ -770,7 +2435,7 @@ fn show_comprehensive_help() -> Result<()> {
     println!("   • Physics simulation toggle");
     println!("   • Label visibility controls (Node/Edge labels)");
     println!("   • Node type filtering (Entities, Concepts, Attributes)");
-    println!("   • Export functionality (JSON, PNG)");
+    println!("   • Export functionality (JSON, PNG, SVG)");
     println!("   • Node/Edge selection with detailed information");
     println!();
     
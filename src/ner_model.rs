@@ -0,0 +1,338 @@
+//! Entity recognition with a local ONNX token-classification model, such as a distilled
+//! BERT NER model exported with its Hugging Face `tokenizer.json`. Selected with
+//! `extraction.entity_detector = "ner_model"`; running the model needs a build with
+//! `--features ner` and the onnxruntime shared library, decoding its labels does not.
+
+use crate::entity_extractor::EntityType;
+use crate::error::{GraphError, Result};
+use serde::{Deserialize, Serialize};
+
+/// Model files and label set for `entity_detector = "ner_model"`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NerModelConfig {
+    /// The `.onnx` model, taking `input_ids` and `attention_mask` (and `token_type_ids`
+    /// when it has that input) and returning per-token logits over `labels`
+    pub model_path: Option<String>,
+    /// The `tokenizer.json` the model was trained with; `tokenizer.json` next to the
+    /// model when unset
+    pub tokenizer_path: Option<String>,
+    /// The model's labels in output order (its `id2label`), tagged `B-`/`I-` or untagged:
+    /// `PER`, `ORG`, `LOC`, `MISC` and their common spellings get entity types, others are skipped
+    pub labels: Vec<String>,
+    /// Entities whose words the model tagged with a lower mean probability are dropped
+    pub min_confidence: f64,
+    /// onnxruntime shared library (`libonnxruntime.so`, `onnxruntime.dll`); the
+    /// `ORT_DYLIB_PATH` environment variable or the system's library when unset
+    pub runtime_path: Option<String>,
+}
+
+impl Default for NerModelConfig {
+    fn default() -> Self {
+        Self {
+            model_path: None,
+            tokenizer_path: None,
+            // The CoNLL-2003 label order of dslim/bert-base-NER and its distilled versions
+            labels: ["O", "B-MISC", "I-MISC", "B-PER", "I-PER", "B-ORG", "I-ORG", "B-LOC", "I-LOC"]
+                .map(String::from)
+                .to_vec(),
+            min_confidence: 0.5,
+            runtime_path: None,
+        }
+    }
+}
+
+/// An entity the model found, as byte offsets into the text it was given
+#[derive(Debug, Clone, PartialEq)]
+pub struct NerSpan {
+    pub start: usize,
+    pub end: usize,
+    pub entity_type: EntityType,
+    /// Mean probability of the label on the span's words
+    pub confidence: f64,
+}
+
+/// Entity type of a label's class: `PER`, `B-ORG`, `I-LOC`, ...
+fn label_type(class: &str) -> Option<EntityType> {
+    match class.to_uppercase().as_str() {
+        "PER" | "PERSON" => Some(EntityType::Person),
+        "ORG" | "ORGANIZATION" | "ORGANISATION" => Some(EntityType::Organization),
+        "LOC" | "LOCATION" | "GPE" => Some(EntityType::Place),
+        "EVENT" | "EVE" => Some(EntityType::Event),
+        "PRODUCT" | "PROD" => Some(EntityType::Product),
+        "MISC" => Some(EntityType::Other("Misc".to_string())),
+        _ => None,
+    }
+}
+
+/// A label split into its BIO prefix, if any, and its class
+fn split_label(label: &str) -> (Option<char>, &str) {
+    match label.split_once('-') {
+        Some((prefix, class)) if prefix.len() == 1 => (prefix.chars().next(), class),
+        _ => (None, label),
+    }
+}
+
+/// The span being built while decoding
+struct OpenSpan<'a> {
+    class: &'a str,
+    start: usize,
+    end: usize,
+    probability: f64,
+    words: usize,
+}
+
+/// Decode per-token logits into entity spans. `offsets` are each token's byte range in
+/// the text and `word_ids` the word it belongs to (`None` for special tokens), as the
+/// tokenizer reports them; `logits` holds one row of `labels.len()` values per token.
+/// A word takes the label of its first sub-word token, and a span runs on while the
+/// following words carry `I-` (or untagged) labels of the same class.
+pub fn decode_spans(
+    offsets: &[(usize, usize)],
+    word_ids: &[Option<u32>],
+    logits: &[f32],
+    labels: &[String],
+    min_confidence: f64,
+) -> Vec<NerSpan> {
+    let mut spans = Vec::new();
+    let mut open: Option<OpenSpan> = None;
+    let close = |open: Option<OpenSpan>, spans: &mut Vec<NerSpan>| {
+        let Some(open) = open else { return };
+        let confidence = open.probability / open.words as f64;
+        if let (Some(entity_type), true) = (label_type(open.class), confidence >= min_confidence) {
+            spans.push(NerSpan {
+                start: open.start,
+                end: open.end,
+                entity_type,
+                confidence,
+            });
+        }
+    };
+
+    let mut previous_word = None;
+    for (token, row) in logits.chunks(labels.len().max(1)).enumerate().take(offsets.len()) {
+        let (start, end) = offsets[token];
+        let word = word_ids.get(token).copied().flatten();
+        if word.is_none() {
+            close(open.take(), &mut spans);
+            previous_word = None;
+            continue;
+        }
+        if word == previous_word {
+            if let Some(open) = open.as_mut() {
+                open.end = end;
+            }
+            continue;
+        }
+        previous_word = word;
+
+        // Softmax probability of the best label
+        let max = row.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        let total: f64 = row.iter().map(|&logit| f64::from(logit - max).exp()).sum();
+        let Some((best, _)) = row.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1)) else { continue };
+        let probability = 1.0 / total;
+        let label = labels.get(best).map(String::as_str).unwrap_or("O");
+
+        let (prefix, class) = split_label(label);
+        if class.eq_ignore_ascii_case("O") {
+            close(open.take(), &mut spans);
+            continue;
+        }
+        match open.as_mut() {
+            Some(span) if prefix != Some('B') && span.class == class => {
+                span.end = end;
+                span.probability += probability;
+                span.words += 1;
+            }
+            _ => {
+                close(open.take(), &mut spans);
+                open = Some(OpenSpan {
+                    class,
+                    start,
+                    end,
+                    probability,
+                    words: 1,
+                });
+            }
+        }
+    }
+    close(open, &mut spans);
+    spans
+}
+
+/// A loaded NER model and its tokenizer
+pub struct NerModel {
+    #[cfg(feature = "ner")]
+    session: std::sync::Mutex<ort::session::Session>,
+    #[cfg(feature = "ner")]
+    tokenizer: tokenizers::Tokenizer,
+    config: NerModelConfig,
+}
+
+impl NerModel {
+    /// Load the model and tokenizer named in `config`
+    pub fn load(config: &NerModelConfig) -> Result<Self> {
+        let model_path = config.model_path.as_deref().ok_or_else(|| {
+            GraphError::Configuration(
+                "extraction.entity_detector is \"ner_model\", but extraction.ner_model.model_path is not set".to_string(),
+            )
+        })?;
+        #[cfg(feature = "ner")]
+        {
+            let (session, tokenizer) = onnx::load(config, model_path)?;
+            Ok(Self {
+                session: std::sync::Mutex::new(session),
+                tokenizer,
+                config: config.clone(),
+            })
+        }
+        #[cfg(not(feature = "ner"))]
+        {
+            Err(GraphError::Configuration(format!(
+                "Cannot load {}: the NER model detector needs a build with --features ner",
+                model_path
+            )))
+        }
+    }
+
+    /// The entities the model finds in `text`, usually one sentence. Text longer than
+    /// the model's input is truncated.
+    pub fn spans(&self, text: &str) -> Result<Vec<NerSpan>> {
+        #[cfg(feature = "ner")]
+        {
+            let mut session = self.session.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            onnx::spans(&mut session, &self.tokenizer, &self.config, text)
+        }
+        #[cfg(not(feature = "ner"))]
+        {
+            let _ = (text, &self.config);
+            Ok(Vec::new())
+        }
+    }
+}
+
+#[cfg(feature = "ner")]
+mod onnx {
+    use super::{decode_spans, NerModelConfig, NerSpan};
+    use crate::error::{GraphError, Result};
+    use ort::session::{Session, SessionInputValue};
+    use ort::value::Tensor;
+    use std::borrow::Cow;
+    use std::path::Path;
+    use tokenizers::{Tokenizer, TruncationParams};
+
+    /// Tokens per model call; BERT-style models take at most 512
+    const MAX_TOKENS: usize = 512;
+
+    fn model_error(error: impl std::fmt::Display) -> GraphError {
+        GraphError::Configuration(format!("NER model: {}", error))
+    }
+
+    /// The onnxruntime library to use, opened once up front: ort panics instead of
+    /// returning an error when it cannot load it
+    fn runtime_library(config: &NerModelConfig) -> Result<String> {
+        let path = config
+            .runtime_path
+            .clone()
+            .or_else(|| std::env::var("ORT_DYLIB_PATH").ok().filter(|path| !path.is_empty()))
+            .unwrap_or_else(|| libloading::library_filename("onnxruntime").to_string_lossy().to_string());
+        // SAFETY: opening onnxruntime only runs its library initializers
+        unsafe { libloading::Library::new(&path) }.map_err(|e| {
+            GraphError::Configuration(format!(
+                "Cannot load onnxruntime from {} (set extraction.ner_model.runtime_path or ORT_DYLIB_PATH): {}",
+                path, e
+            ))
+        })?;
+        Ok(path)
+    }
+
+    pub(super) fn load(config: &NerModelConfig, model_path: &str) -> Result<(Session, Tokenizer)> {
+        ort::init_from(runtime_library(config)?).commit().map_err(model_error)?;
+        let session = Session::builder()
+            .and_then(|builder| builder.commit_from_file(model_path))
+            .map_err(|e| GraphError::Configuration(format!("Cannot load NER model {}: {}", model_path, e)))?;
+
+        let tokenizer_path = config.tokenizer_path.clone().unwrap_or_else(|| {
+            Path::new(model_path).with_file_name("tokenizer.json").to_string_lossy().to_string()
+        });
+        let mut tokenizer = Tokenizer::from_file(&tokenizer_path)
+            .map_err(|e| GraphError::Configuration(format!("Cannot load NER tokenizer {}: {}", tokenizer_path, e)))?;
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length: MAX_TOKENS,
+                ..TruncationParams::default()
+            }))
+            .map_err(model_error)?;
+        Ok((session, tokenizer))
+    }
+
+    pub(super) fn spans(session: &mut Session, tokenizer: &Tokenizer, config: &NerModelConfig, text: &str) -> Result<Vec<NerSpan>> {
+        let encoding = tokenizer.encode(text, true).map_err(model_error)?;
+        let length = encoding.get_ids().len();
+        let tensor = |values: &[u32]| {
+            Tensor::from_array((vec![1, length as i64], values.iter().map(|&value| i64::from(value)).collect::<Vec<_>>()))
+                .map_err(model_error)
+        };
+
+        let mut inputs: Vec<(Cow<str>, SessionInputValue)> = Vec::new();
+        for input in &session.inputs {
+            let values = match input.name.as_str() {
+                "input_ids" => encoding.get_ids(),
+                "attention_mask" => encoding.get_attention_mask(),
+                "token_type_ids" => encoding.get_type_ids(),
+                other => return Err(model_error(format!("unexpected input '{}'", other))),
+            };
+            inputs.push((Cow::Owned(input.name.clone()), tensor(values)?.into()));
+        }
+        let outputs = session.run(inputs).map_err(model_error)?;
+        let (_, logits) = outputs[0].try_extract_tensor::<f32>().map_err(model_error)?;
+
+        let offsets: Vec<(usize, usize)> = encoding.get_offsets().to_vec();
+        Ok(decode_spans(&offsets, encoding.get_word_ids(), logits, &config.labels, config.min_confidence))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Logits strongly favouring one label per token
+    fn logits(labels: &[String], tags: &[&str]) -> Vec<f32> {
+        tags.iter()
+            .flat_map(|tag| labels.iter().map(move |label| if label == tag { 8.0 } else { 0.0 }))
+            .collect()
+    }
+
+    #[test]
+    fn test_decode_spans_joins_subwords_and_inside_tags() {
+        let text = "Ada Lovelace visited Londonderry.";
+        let labels = NerModelConfig::default().labels;
+        // [CLS] Ada Love ##lace visited London ##derry . [SEP]
+        let offsets = [(0, 0), (0, 3), (4, 8), (8, 12), (13, 20), (21, 27), (27, 32), (32, 33), (0, 0)];
+        let word_ids = [None, Some(0), Some(1), Some(1), Some(2), Some(3), Some(3), Some(4), None];
+        let tags = ["O", "B-PER", "I-PER", "O", "O", "B-LOC", "O", "O", "O"];
+
+        let spans = decode_spans(&offsets, &word_ids, &logits(&labels, &tags), &labels, 0.5);
+
+        let found: Vec<(&str, EntityType)> =
+            spans.iter().map(|span| (&text[span.start..span.end], span.entity_type.clone())).collect();
+        assert_eq!(found, vec![("Ada Lovelace", EntityType::Person), ("Londonderry", EntityType::Place)]);
+        assert!(spans.iter().all(|span| span.confidence > 0.99));
+    }
+
+    #[test]
+    fn test_decode_spans_splits_on_begin_tags_and_drops_unsure_words() {
+        let labels = NerModelConfig::default().labels;
+        let offsets = [(0, 4), (5, 9), (10, 14)];
+        let word_ids = [Some(0), Some(1), Some(2)];
+        let mut scores = logits(&labels, &["B-ORG", "B-ORG", "B-PER"]);
+        // The last word is a coin toss between B-PER and O
+        scores[2 * labels.len()] = 8.0;
+
+        let spans = decode_spans(&offsets, &word_ids, &scores, &labels, 0.6);
+
+        assert_eq!(spans.len(), 2);
+        assert_eq!((spans[0].start, spans[0].end), (0, 4));
+        assert_eq!((spans[1].start, spans[1].end), (5, 9));
+    }
+}
@@ -0,0 +1,387 @@
+use crate::config::GraphConfig;
+use crate::entity_extractor::{
+    Concept, Entity, EntityType, ExtractionMetadata, ExtractionResult, Relationship, RelationshipType,
+};
+use crate::error::{GraphError, Result};
+use crate::pipeline::Pipeline;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Entity type given to nodes that stand for vault notes
+pub const NOTE_ENTITY_TYPE: &str = "Note";
+
+/// Frontmatter marker of the summary notes msg_net writes back; such notes are skipped
+/// when the vault is read again
+const GENERATED_MARKER: &str = "generated_by: msg_net";
+
+/// One Markdown note of a vault
+#[derive(Debug, Clone)]
+pub struct VaultNote {
+    /// File name without `.md`, which is what `[[wikilinks]]` refer to
+    pub title: String,
+    pub path: PathBuf,
+    /// Text without frontmatter, each wikilink replaced by its display text
+    pub body: String,
+    /// Titles of the notes this note links to, in order of first appearance
+    pub links: Vec<String>,
+}
+
+/// The Markdown notes of an Obsidian vault
+#[derive(Debug, Clone, Default)]
+pub struct Vault {
+    pub notes: Vec<VaultNote>,
+}
+
+impl Vault {
+    /// Read every `.md` file under `dir`, skipping hidden folders such as `.obsidian`
+    /// and summary notes msg_net generated earlier
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref();
+        if !dir.is_dir() {
+            return Err(GraphError::Configuration(format!("{} is not a vault directory", dir.display())));
+        }
+        let mut paths = Vec::new();
+        collect_markdown(dir, &mut paths)?;
+        paths.sort();
+
+        let mut notes = Vec::new();
+        for path in paths {
+            let content = fs::read_to_string(&path)?;
+            let title = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+            if let Some(note) = Self::parse_note(&title, &content) {
+                notes.push(VaultNote { path, ..note });
+            }
+        }
+        Ok(Self { notes })
+    }
+
+    /// Parse a note's Markdown; `None` for summary notes generated by msg_net
+    pub fn parse_note(title: &str, content: &str) -> Option<VaultNote> {
+        let (frontmatter, body) = split_frontmatter(content);
+        if frontmatter.lines().any(|line| line.trim() == GENERATED_MARKER) {
+            return None;
+        }
+
+        // [[Target]], [[Target|alias]], [[folder/Target#Heading]] and ![[embeds]]
+        let wikilink = Regex::new(r"!?\[\[([^\]|#]*)(?:#[^\]|]*)?(?:\|([^\]]*))?\]\]").expect("valid wikilink regex");
+        let mut links: Vec<String> = Vec::new();
+        let body = wikilink.replace_all(body, |captures: &regex::Captures| {
+            let target = captures[1].trim();
+            let target = target.rsplit('/').next().unwrap_or(target);
+            let target = target.strip_suffix(".md").unwrap_or(target);
+            // Attachments ("diagram.png") are not notes
+            let is_note = !target.is_empty() && !target.contains('.');
+            if is_note && !links.iter().any(|link| link.eq_ignore_ascii_case(target)) {
+                links.push(target.to_string());
+            }
+            match captures.get(2) {
+                Some(alias) => alias.as_str().trim().to_string(),
+                None if is_note => target.to_string(),
+                None => String::new(),
+            }
+        });
+
+        Some(VaultNote {
+            title: title.to_string(),
+            path: PathBuf::from(format!("{}.md", title)),
+            body: body.into_owned(),
+            links,
+        })
+    }
+
+    /// All note bodies, for the graph's mention counts and sentiment
+    pub fn source_text(&self) -> String {
+        self.notes.iter().map(|note| note.body.as_str()).collect::<Vec<_>>().join("\n\n")
+    }
+}
+
+fn collect_markdown(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let hidden = path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if hidden {
+            continue;
+        }
+        if path.is_dir() {
+            collect_markdown(&path, paths)?;
+        } else if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("md")) {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Split `---` delimited YAML frontmatter from the body
+fn split_frontmatter(content: &str) -> (&str, &str) {
+    let Some(rest) = content.strip_prefix("---\n").or_else(|| content.strip_prefix("---\r\n")) else {
+        return ("", content);
+    };
+    match rest.find("\n---") {
+        Some(end) => {
+            let after = &rest[end + 4..];
+            (&rest[..end], after.split_once('\n').map_or("", |(_, body)| body))
+        }
+        None => ("", content),
+    }
+}
+
+/// Entities, relationships and concepts of several notes merged into one result
+#[derive(Default)]
+struct VaultMerge {
+    entities: Vec<Entity>,
+    by_name: HashMap<String, usize>,
+    relationships: Vec<Relationship>,
+    concepts: Vec<Concept>,
+}
+
+impl VaultMerge {
+    /// Id of the entity named `name` (ignoring case), adding it if new. Notes keep
+    /// their type; confidence is the highest seen.
+    fn entity(&mut self, name: &str, entity_type: EntityType, confidence: f64) -> String {
+        let key = name.to_lowercase();
+        if let Some(&index) = self.by_name.get(&key) {
+            let existing = &mut self.entities[index];
+            existing.confidence = existing.confidence.max(confidence);
+            return existing.id.clone();
+        }
+        let id = Uuid::new_v4().to_string();
+        self.by_name.insert(key, self.entities.len());
+        self.entities.push(Entity {
+            id: id.clone(),
+            name: name.to_string(),
+            entity_type,
+            attributes: Vec::new(),
+            confidence,
+            position: None,
+            mentions: 1,
+            disambiguation: None,
+        });
+        id
+    }
+
+    fn has_edge(&self, source: &str, target: &str, label: Option<&str>) -> bool {
+        self.relationships.iter().any(|r| {
+            r.source_entity_id == source
+                && r.target_entity_id == target
+                && label.is_none_or(|label| r.label.eq_ignore_ascii_case(label))
+        })
+    }
+
+    fn relationship(&mut self, relationship: Relationship) {
+        if relationship.source_entity_id != relationship.target_entity_id
+            && !self.has_edge(&relationship.source_entity_id, &relationship.target_entity_id, Some(&relationship.label))
+        {
+            self.relationships.push(relationship);
+        }
+    }
+
+    fn edge(source: String, target: String, label: &str, confidence: f64) -> Relationship {
+        Relationship {
+            id: Uuid::new_v4().to_string(),
+            source_entity_id: source,
+            target_entity_id: target,
+            relationship_type: RelationshipType::Other(label.replace(' ', "_")),
+            label: label.to_string(),
+            confidence,
+            position: None,
+            bidirectional: false,
+            category: None,
+            context: None,
+        }
+    }
+
+    fn add_note(&mut self, note_id: &str, extraction: ExtractionResult) {
+        let mut ids: HashMap<String, String> = HashMap::new();
+        for entity in extraction.entities {
+            let id = self.entity(&entity.name, entity.entity_type, entity.confidence);
+            // A name the note already links to needs no extra "mentions" edge
+            if id != note_id && !self.has_edge(note_id, &id, None) {
+                self.relationship(Self::edge(note_id.to_string(), id.clone(), "mentions", entity.confidence));
+            }
+            ids.insert(entity.id, id);
+        }
+        for relationship in extraction.relationships {
+            let (Some(source), Some(target)) =
+                (ids.get(&relationship.source_entity_id), ids.get(&relationship.target_entity_id))
+            else {
+                continue;
+            };
+            self.relationship(Relationship {
+                source_entity_id: source.clone(),
+                target_entity_id: target.clone(),
+                ..relationship
+            });
+        }
+        for mut concept in extraction.concepts {
+            concept.related_entities = concept.related_entities.iter().filter_map(|id| ids.get(id).cloned()).collect();
+            match self.concepts.iter_mut().find(|existing| existing.name.eq_ignore_ascii_case(&concept.name)) {
+                Some(existing) => {
+                    existing.confidence = existing.confidence.max(concept.confidence);
+                    for id in concept.related_entities {
+                        if !existing.related_entities.contains(&id) {
+                            existing.related_entities.push(id);
+                        }
+                    }
+                }
+                None => self.concepts.push(concept),
+            }
+        }
+    }
+}
+
+/// Extract every note of the vault with `config` and merge the results.
+///
+/// Each note is an entity of type [`NOTE_ENTITY_TYPE`] and each `[[wikilink]]` a
+/// "links to" relationship with confidence 1.0; links to notes that do not exist yet
+/// still get a node, as in Obsidian's graph view. Entities extracted from a note body
+/// are merged across notes by name (and with notes of the same title), and the note
+/// gets a "mentions" edge to each of them.
+pub async fn extract_vault(vault: &Vault, config: &GraphConfig) -> Result<ExtractionResult> {
+    let start_time = web_time::Instant::now();
+    let note_type = || EntityType::Other(NOTE_ENTITY_TYPE.to_string());
+    let mut merged = VaultMerge::default();
+    for note in &vault.notes {
+        merged.entity(&note.title, note_type(), 1.0);
+    }
+
+    for note in &vault.notes {
+        let note_id = merged.entity(&note.title, note_type(), 1.0);
+        for link in &note.links {
+            let target = merged.entity(link, note_type(), 1.0);
+            merged.relationship(VaultMerge::edge(note_id.clone(), target, "links to", 1.0));
+        }
+        if note.body.trim().is_empty() {
+            continue;
+        }
+        let extraction = Pipeline::new().with_config(config.clone()).with_text(note.body.as_str()).extract().await?;
+        merged.add_note(&note_id, extraction);
+    }
+
+    let mut result = ExtractionResult {
+        metadata: ExtractionMetadata {
+            total_entities: merged.entities.len(),
+            total_relationships: merged.relationships.len(),
+            total_concepts: merged.concepts.len(),
+            processing_time_ms: start_time.elapsed().as_millis() as u64,
+            confidence_threshold: 0.5,
+            extraction_method: "obsidian_vault".to_string(),
+        },
+        entities: merged.entities,
+        relationships: merged.relationships,
+        concepts: merged.concepts,
+    };
+    if config.deterministic {
+        result.assign_stable_ids();
+    }
+    Ok(result)
+}
+
+/// Write one Markdown summary note per extracted entity into `dir`, listing the notes
+/// that mention it as `[[backlinks]]` and its relationships. Entities that are vault
+/// notes are skipped, so no note of the user's is ever overwritten. Returns the number
+/// of notes written.
+pub fn write_entity_notes(result: &ExtractionResult, dir: impl AsRef<Path>) -> Result<usize> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+    let names: HashMap<&str, &Entity> = result.entities.iter().map(|entity| (entity.id.as_str(), entity)).collect();
+    let is_note = |entity: &Entity| matches!(&entity.entity_type, EntityType::Other(name) if name == NOTE_ENTITY_TYPE);
+
+    let mut written = 0;
+    for entity in result.entities.iter().filter(|entity| !is_note(entity)) {
+        let mut mentioned_in = Vec::new();
+        let mut relationships = Vec::new();
+        for relationship in &result.relationships {
+            let (Some(source), Some(target)) = (
+                names.get(relationship.source_entity_id.as_str()),
+                names.get(relationship.target_entity_id.as_str()),
+            ) else {
+                continue;
+            };
+            if target.id == entity.id && is_note(source) && relationship.label == "mentions" {
+                mentioned_in.push(format!("- [[{}]]", source.name));
+            } else if source.id == entity.id {
+                relationships.push(format!("- {} [[{}]]", relationship.label, target.name));
+            } else if target.id == entity.id {
+                relationships.push(format!("- [[{}]] {}", source.name, relationship.label));
+            }
+        }
+
+        let entity_type = match &entity.entity_type {
+            EntityType::Other(name) => name.clone(),
+            other => format!("{:?}", other),
+        };
+        let mut content = format!("---\n{}\ntype: {}\n---\n# {}\n", GENERATED_MARKER, entity_type, entity.name);
+        if !mentioned_in.is_empty() {
+            content.push_str(&format!("\n## Mentioned in\n\n{}\n", mentioned_in.join("\n")));
+        }
+        if !relationships.is_empty() {
+            content.push_str(&format!("\n## Relationships\n\n{}\n", relationships.join("\n")));
+        }
+        fs::write(dir.join(format!("{}.md", note_file_name(&entity.name))), content)?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+/// Entity name with the characters Obsidian does not allow in note names replaced
+fn note_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if "/\\:*?\"<>|#^[]".contains(c) { '-' } else { c })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_note_collects_wikilinks_and_strips_frontmatter() {
+        let note = Vault::parse_note(
+            "Standup",
+            "---\ntags: [meeting]\n---\nAlice met [[Bob Smith|Bob]] about [[projects/Apollo#Risks]]. ![[diagram.png]] See [[Apollo]].",
+        )
+        .unwrap();
+        assert_eq!(note.links, vec!["Bob Smith", "Apollo"]);
+        assert_eq!(note.body, "Alice met Bob about Apollo.  See Apollo.");
+        assert!(Vault::parse_note("Alice", &format!("---\n{}\n---\n# Alice", GENERATED_MARKER)).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_vault_links_and_mentions_merge_and_write_back() {
+        let vault_dir = tempfile::tempdir().unwrap();
+        fs::create_dir(vault_dir.path().join(".obsidian")).unwrap();
+        fs::write(vault_dir.path().join(".obsidian").join("ignored.md"), "Mallory").unwrap();
+        fs::write(vault_dir.path().join("Apollo.md"), "Alice is connected to Bob on [[Roadmap]].").unwrap();
+        fs::write(vault_dir.path().join("Bob.md"), "Bob reviews [[Apollo]] with Alice.").unwrap();
+
+        let vault = Vault::load(vault_dir.path()).unwrap();
+        assert_eq!(vault.notes.len(), 2);
+        let result = extract_vault(&vault, &GraphConfig::default()).await.unwrap();
+
+        let id = |name: &str| result.entities.iter().find(|e| e.name == name).expect(name).id.clone();
+        let edge = |from: &str, to: &str| {
+            result.relationships.iter().find(|r| r.source_entity_id == id(from) && r.target_entity_id == id(to))
+        };
+        assert_eq!(edge("Apollo", "Roadmap").unwrap().label, "links to");
+        assert_eq!(edge("Bob", "Apollo").unwrap().label, "links to");
+        assert_eq!(edge("Apollo", "Alice").unwrap().label, "mentions");
+        // "Bob" in Apollo's body is the Bob note, and Alice is one entity across notes
+        assert!(edge("Apollo", "Bob").is_some());
+        assert_eq!(result.entities.iter().filter(|e| e.name == "Alice").count(), 1);
+        assert!(matches!(&result.entities.iter().find(|e| e.name == "Bob").unwrap().entity_type,
+            EntityType::Other(name) if name == NOTE_ENTITY_TYPE));
+
+        let notes_dir = vault_dir.path().join("msg_net");
+        let written = write_entity_notes(&result, &notes_dir).unwrap();
+        assert_eq!(written, 1);
+        let alice = fs::read_to_string(notes_dir.join("Alice.md")).unwrap();
+        assert!(alice.contains("## Mentioned in\n\n- [[Apollo]]\n- [[Bob]]"));
+        // Generated notes are not read back as vault notes
+        assert_eq!(Vault::load(vault_dir.path()).unwrap().notes.len(), 2);
+    }
+}
@@ -0,0 +1,259 @@
+//! Text from scanned PDFs and images, for paper archives.
+//!
+//! Recognition uses Tesseract and needs a build with `--features ocr` (and the
+//! Tesseract and Leptonica libraries installed); without it [`ocr_file`] returns a
+//! configuration error. Scanned PDFs are rasterized page by page with `pdftoppm` from
+//! poppler-utils first. Tesseract's per-word confidence is kept so entities read from
+//! smudged words end up less confident than ones read cleanly.
+
+use crate::entity_extractor::ExtractionResult;
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Extensions read through OCR instead of as text
+pub const OCR_EXTENSIONS: &[&str] = &["pdf", "png", "jpg", "jpeg", "tif", "tiff", "bmp", "gif", "webp", "pnm"];
+
+/// Resolution scanned PDF pages are rasterized at; Tesseract is tuned for 300 dpi
+pub const OCR_DPI: u32 = 300;
+
+/// One recognized word and how sure Tesseract was of it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OcrWord {
+    pub text: String,
+    /// 0 to 1
+    pub confidence: f64,
+}
+
+/// Text recognized from a scan, with word confidences
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OcrText {
+    /// Recognized text, one line per scanned line and a blank line between blocks and pages
+    pub text: String,
+    pub words: Vec<OcrWord>,
+}
+
+impl OcrText {
+    /// Build from Tesseract's TSV output (`level page block par line word left top width
+    /// height conf text`), appending to what earlier pages produced
+    pub fn push_tsv(&mut self, tsv: &str) {
+        let mut last_line = None;
+        let mut last_block = None;
+        if !self.text.is_empty() {
+            self.text.push_str("\n\n");
+        }
+        for row in tsv.lines().skip(1) {
+            let columns: Vec<&str> = row.splitn(12, '\t').collect();
+            if columns.len() < 12 || columns[0] != "5" {
+                continue;
+            }
+            let text = columns[11].trim();
+            let Ok(confidence) = columns[10].parse::<f64>() else { continue };
+            if text.is_empty() || confidence < 0.0 {
+                continue;
+            }
+            let block = (columns[1], columns[2]);
+            let line = (columns[1], columns[2], columns[3], columns[4]);
+            if last_line.is_some() && last_line != Some(line) {
+                self.text.push_str(if last_block != Some(block) { "\n\n" } else { "\n" });
+            } else if last_line.is_some() {
+                self.text.push(' ');
+            }
+            self.text.push_str(text);
+            self.words.push(OcrWord { text: text.to_string(), confidence: (confidence / 100.0).clamp(0.0, 1.0) });
+            last_line = Some(line);
+            last_block = Some(block);
+        }
+    }
+
+    /// Mean confidence over all words, or `None` when nothing was recognized
+    pub fn mean_confidence(&self) -> Option<f64> {
+        (!self.words.is_empty())
+            .then(|| self.words.iter().map(|word| word.confidence).sum::<f64>() / self.words.len() as f64)
+    }
+
+    /// How confident the OCR was of the words in `name`: the lowest mean confidence of
+    /// any of its words, each averaged over its occurrences. `None` if a word was never
+    /// recognized on its own (e.g. it spans a line break).
+    pub fn name_confidence(&self, name: &str) -> Option<f64> {
+        let mut by_word: HashMap<String, (f64, usize)> = HashMap::new();
+        for word in &self.words {
+            let entry = by_word.entry(normalize_word(&word.text)).or_insert((0.0, 0));
+            entry.0 += word.confidence;
+            entry.1 += 1;
+        }
+        let mut lowest: Option<f64> = None;
+        for word in name.split_whitespace().map(normalize_word).filter(|word| !word.is_empty()) {
+            let (total, count) = by_word.get(&word)?;
+            let mean = total / *count as f64;
+            lowest = Some(lowest.map_or(mean, |low| low.min(mean)));
+        }
+        lowest
+    }
+
+    /// Scale entity and concept confidences by how well their words were read
+    pub fn apply_confidence(&self, result: &mut ExtractionResult) {
+        for entity in &mut result.entities {
+            if let Some(confidence) = self.name_confidence(&entity.name) {
+                entity.confidence *= confidence;
+            }
+        }
+        for concept in &mut result.concepts {
+            if let Some(confidence) = self.name_confidence(&concept.name) {
+                concept.confidence *= confidence;
+            }
+        }
+    }
+}
+
+fn normalize_word(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase()
+}
+
+/// Whether `path` is a scan or image to read with [`ocr_file`]
+pub fn is_ocr_input(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| OCR_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Recognize the text of an image, or of every page of a scanned PDF, with Tesseract.
+/// `language` is a Tesseract language code such as `eng` or `deu+eng`.
+pub fn ocr_file(path: &str, language: &str) -> Result<OcrText> {
+    #[cfg(feature = "ocr")]
+    {
+        tesseract::recognize(Path::new(path), language)
+    }
+    #[cfg(not(feature = "ocr"))]
+    {
+        let _ = language;
+        Err(crate::error::GraphError::Configuration(format!(
+            "Cannot read {}: OCR input needs a build with --features ocr",
+            path
+        )))
+    }
+}
+
+#[cfg(feature = "ocr")]
+mod tesseract {
+    use super::{OcrText, OCR_DPI};
+    use crate::error::{GraphError, Result};
+    use leptess::LepTess;
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    pub(super) fn recognize(path: &Path, language: &str) -> Result<OcrText> {
+        let mut engine = LepTess::new(None, language)
+            .map_err(|e| GraphError::Configuration(format!("Tesseract could not load '{}': {}", language, e)))?;
+        let mut ocr = OcrText::default();
+        let is_pdf = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"));
+        let pages = if is_pdf { rasterize_pdf(path)? } else { vec![path.to_path_buf()] };
+        let result = pages.iter().try_for_each(|page| {
+            engine
+                .set_image(page)
+                .map_err(|e| GraphError::TextProcessing(format!("Cannot read image {}: {}", page.display(), e)))?;
+            engine.set_fallback_source_resolution(OCR_DPI as i32);
+            let tsv = engine
+                .get_tsv_text(0)
+                .map_err(|e| GraphError::TextProcessing(format!("Tesseract returned invalid text: {}", e)))?;
+            ocr.push_tsv(&tsv);
+            Ok(())
+        });
+        if is_pdf {
+            if let Some(dir) = pages.first().and_then(|page| page.parent()) {
+                let _ = std::fs::remove_dir_all(dir);
+            }
+        }
+        result.map(|()| ocr)
+    }
+
+    /// Render each page to a PNG in a fresh temporary folder, in page order
+    fn rasterize_pdf(path: &Path) -> Result<Vec<PathBuf>> {
+        let dir = std::env::temp_dir().join(format!("msg_net_ocr_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir)?;
+        let status = Command::new("pdftoppm")
+            .arg("-r")
+            .arg(OCR_DPI.to_string())
+            .arg("-png")
+            .arg(path)
+            .arg(dir.join("page"))
+            .status()
+            .map_err(|e| GraphError::Configuration(format!("Cannot run pdftoppm (install poppler-utils): {}", e)))?;
+        if !status.success() {
+            let _ = std::fs::remove_dir_all(&dir);
+            return Err(GraphError::TextProcessing(format!("pdftoppm failed on {}", path.display())));
+        }
+        let mut pages: Vec<PathBuf> = std::fs::read_dir(&dir)?.map(|entry| entry.map(|e| e.path())).collect::<std::io::Result<_>>()?;
+        // page-01.png, page-02.png, ...: zero-padded, so name order is page order
+        pages.sort();
+        Ok(pages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity_extractor::{Entity, EntityType, ExtractionMetadata};
+
+    const TSV: &str = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext\n\
+        1\t1\t0\t0\t0\t0\t0\t0\t100\t100\t-1\t\n\
+        5\t1\t1\t1\t1\t1\t0\t0\t10\t10\t96\tAlice\n\
+        5\t1\t1\t1\t1\t2\t0\t0\t10\t10\t91\tworks\n\
+        5\t1\t1\t1\t2\t1\t0\t0\t10\t10\t88\tat\n\
+        5\t1\t1\t1\t2\t2\t0\t0\t10\t10\t40\tInitech.\n\
+        5\t1\t2\t1\t1\t1\t0\t0\t10\t10\t90\tAlice\n";
+
+    #[test]
+    fn test_tsv_gives_lines_blocks_and_word_confidences() {
+        let mut ocr = OcrText::default();
+        ocr.push_tsv(TSV);
+        assert_eq!(ocr.text, "Alice works\nat Initech.\n\nAlice");
+        assert_eq!(ocr.words.len(), 5);
+        assert_eq!(ocr.name_confidence("Initech"), Some(0.4));
+        assert_eq!(ocr.name_confidence("Alice Initech"), Some(0.4));
+        assert!((ocr.name_confidence("alice").unwrap() - 0.93).abs() < 1e-9);
+        assert_eq!(ocr.name_confidence("Bob"), None);
+
+        ocr.push_tsv(TSV);
+        assert!(ocr.text.contains("Alice\n\nAlice works"));
+    }
+
+    #[test]
+    fn test_confidence_scales_entities() {
+        let mut ocr = OcrText::default();
+        ocr.push_tsv(TSV);
+        let entity = |name: &str| Entity {
+            id: name.to_string(),
+            name: name.to_string(),
+            entity_type: EntityType::Organization,
+            attributes: Vec::new(),
+            confidence: 0.8,
+            position: None,
+            mentions: 1,
+            disambiguation: None,
+        };
+        let mut result = ExtractionResult {
+            entities: vec![entity("Initech"), entity("Bob")],
+            relationships: Vec::new(),
+            concepts: Vec::new(),
+            metadata: ExtractionMetadata {
+                total_entities: 2,
+                total_relationships: 0,
+                total_concepts: 0,
+                processing_time_ms: 0,
+                confidence_threshold: 0.0,
+                extraction_method: "test".to_string(),
+            },
+        };
+        ocr.apply_confidence(&mut result);
+        assert!((result.entities[0].confidence - 0.32).abs() < 1e-9);
+        assert_eq!(result.entities[1].confidence, 0.8);
+
+        assert!(is_ocr_input("scans/Letter.PDF") && is_ocr_input("page.png"));
+        assert!(!is_ocr_input("notes.txt"));
+        #[cfg(not(feature = "ocr"))]
+        assert!(ocr_file("page.png", "eng").is_err());
+    }
+}
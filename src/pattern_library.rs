@@ -0,0 +1,132 @@
+use crate::relationship_rules::RelationshipRule;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Groups of built-in relationship rules (`extraction.pattern_library`), each a set of
+/// verb phrases in the [relationship rule](crate::relationship_rules::RelationshipRule)
+/// syntax
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PatternCategory {
+    /// works at, hired by, CEO of, founded, joined, left
+    Employment,
+    /// owns, acquired, belongs to
+    Ownership,
+    /// lives in, based in, moved to, born in, visited
+    Location,
+    /// married to, parent of, child of, sibling of
+    Family,
+    /// wrote to, called, spoke with, met, informed
+    Communication,
+    /// caused, prevented, depends on
+    Causation,
+}
+
+/// Optional article between a verb and its object
+const THE: &str = "(?:the |a |an )?";
+/// "'s", also as left by text cleaning, which turns the apostrophe into a space
+const POSSESSIVE: &str = "(?:'s|\u{2019}s| s)";
+
+impl PatternCategory {
+    pub const ALL: [PatternCategory; 6] = [
+        PatternCategory::Employment,
+        PatternCategory::Ownership,
+        PatternCategory::Location,
+        PatternCategory::Family,
+        PatternCategory::Communication,
+        PatternCategory::Causation,
+    ];
+
+    /// The category's relationship rules, most specific first
+    pub fn rules(&self) -> Vec<String> {
+        let rules: &[&str] = match self {
+            PatternCategory::Employment => &[
+                r"{subject},? {THE}(?:CEO|CTO|CFO|COO|founder|co-founder|president|chair|director|head|manager|engineer|employee|intern) (?:of|at) {object} -> works_at",
+                r"{subject} (?:works|worked|is working|was working|has worked) (?:at|for) {object} -> works_at",
+                r"{subject} (?:is|was) employed (?:at|by) {object} -> works_at",
+                r"{subject} (?:was|were|is|has been) (?:hired|recruited) by {object} -> works_at",
+                r"{object} (?:hired|hires|employs|employed|recruited) {subject} -> works_at",
+                r"{subject} (?:founded|co-founded|started) {object} -> founded",
+                r"{object} (?:was|is) (?:founded|started) by {subject} -> founded",
+                r"{subject} (?:joined|joins) {object} -> joined",
+                r"{subject} (?:left|quit|resigned from|retired from) {object} -> left",
+            ],
+            PatternCategory::Ownership => &[
+                r"{object} (?:was|is|were) (?:acquired|bought|purchased) by {subject} -> acquired",
+                r"{subject} (?:acquired|acquires|bought|buys|purchased) {THE}{object} -> acquired",
+                r"{object} (?:is|was|are|were) owned by {subject} -> owns",
+                r"{subject} (?:owns|owned) {THE}{object} -> owns",
+                r"{object} (?:belongs|belonged) to {subject} -> owns",
+            ],
+            PatternCategory::Location => &[
+                r"{subject} (?:lives|lived|resides|resided|is living) in {object} -> lives_in",
+                r"{subject} (?:is|was|are|were) (?:based|located|headquartered) in {object} -> located_in",
+                r"{subject} (?:moved|relocated|moves) to {object} -> moved_to",
+                r"{subject} (?:was|were) born in {object} -> born_in",
+                r"{subject} (?:visited|visits|traveled to|travelled to|flew to) {object} -> visited",
+            ],
+            PatternCategory::Family => &[
+                r"{subject} (?:is|was) (?:married to|the (?:wife|husband|spouse) of) {object} -> married_to",
+                r"{subject} (?:married|marries) {object} -> married_to",
+                r"{subject} (?:is|was) the (?:father|mother|parent) of {object} -> parent_of",
+                r"{object}{POSSESSIVE} (?:father|mother),? {subject} -> parent_of",
+                r"{subject} (?:is|was) the (?:son|daughter|child) of {object} -> child_of",
+                r"{object}{POSSESSIVE} (?:son|daughter),? {subject} -> child_of",
+                r"{subject} (?:is|was) the (?:brother|sister|sibling) of {object} -> sibling_of",
+                r"{object}{POSSESSIVE} (?:brother|sister),? {subject} -> sibling_of",
+            ],
+            PatternCategory::Communication => &[
+                r"{subject} (?:emailed|e-mailed|messaged|texted|wrote to|writes to) {object} -> wrote_to",
+                r"{subject} (?:called|phoned|rang) {object} -> called",
+                r"{subject} (?:spoke|speaks|talked|talks) (?:to|with) {object} -> spoke_with",
+                r"{subject} (?:met with|met|meets with|meets) {object} -> met",
+                r"{subject} (?:told|informed|notified|warned|briefed) {object} -> informed",
+            ],
+            PatternCategory::Causation => &[
+                r"{object} (?:was|were|is) (?:caused|triggered) by {subject} -> caused",
+                r"{subject} (?:caused|causes|triggered|triggers|led to|leads to|resulted in) {THE}{object} -> caused",
+                r"{subject} (?:prevented|prevents|blocked|blocks) {THE}{object} -> prevented",
+                r"{subject} (?:depends|depended|relies|relied) on {THE}{object} -> depends_on",
+            ],
+        };
+        rules
+            .iter()
+            .map(|rule| rule.replace("{THE}", THE).replace("{POSSESSIVE}", POSSESSIVE))
+            .collect()
+    }
+
+    /// Rules of every category in `categories`, in that order
+    pub fn rules_for(categories: &[PatternCategory]) -> Vec<String> {
+        categories.iter().flat_map(PatternCategory::rules).collect()
+    }
+
+    /// The category's rules, compiled once per process (the slot patterns make each
+    /// regex costly to build, and an extractor is created for every run). Library rules
+    /// only bind entities adjacent to the verb phrase.
+    pub fn compiled_rules(&self) -> &'static [RelationshipRule] {
+        static COMPILED: [OnceLock<Vec<RelationshipRule>>; 6] = [const { OnceLock::new() }; 6];
+        let index = PatternCategory::ALL.iter().position(|category| category == self).expect("listed in ALL");
+        COMPILED[index].get_or_init(|| {
+            self.rules()
+                .iter()
+                .map(|rule| rule.parse::<RelationshipRule>().expect("built-in relationship rules are valid").adjacent())
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_library_rule_compiles() {
+        for category in PatternCategory::ALL {
+            for rule in category.rules() {
+                assert!(rule.parse::<RelationshipRule>().is_ok(), "{:?}: {}", category, rule);
+            }
+        }
+        let categories: Vec<PatternCategory> = serde_json::from_str(r#"["employment", "family"]"#).unwrap();
+        assert_eq!(PatternCategory::rules_for(&categories).len(), 17);
+    }
+}
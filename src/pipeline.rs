@@ -0,0 +1,530 @@
+use crate::config::GraphConfig;
+use crate::cooccurrence::{extract_cooccurrence, ExtractionMode};
+use crate::entity_extractor::{EntityExtractor, ExtractionResult, LlmProgress};
+use crate::error::{GraphError, Result};
+use crate::estimate::{estimate_tokens, RunEstimate, RESPONSE_TOKENS_PER_ITEM};
+use crate::graph_builder::{GraphBuilder, InteractiveGraph};
+use crate::hierarchy::infer_hierarchy;
+use crate::ocr::OcrText;
+use crate::plugins::{run_extractor_plugins, PluginInput};
+use crate::sentence_splitter::SentenceSplitter;
+use crate::text_processor::{SourceType, TextProcessor};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::warn;
+use web_time::Instant;
+
+/// Stages of the text-to-graph pipeline, in the order they run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PipelineStage {
+    TextProcessing,
+    Extraction,
+    GraphBuilding,
+}
+
+/// Progress reported by [`Pipeline::run`] to its event callback and channel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PipelineEvent {
+    StageStarted(PipelineStage),
+    TextProcessed {
+        word_count: usize,
+        sentence_count: usize,
+    },
+    ExtractionCompleted {
+        entities: usize,
+        relationships: usize,
+        concepts: usize,
+    },
+    GraphBuilt {
+        nodes: usize,
+        edges: usize,
+    },
+    /// A streamed LLM response grew; sent for every chunk, so expect many
+    LlmProgress(LlmProgress),
+    /// A problem the run recovered from, such as an LLM call falling back to patterns
+    Warning(String),
+}
+
+/// Structured hooks into a pipeline run, for metrics, telemetry or custom progress output.
+/// Every method defaults to doing nothing, so implement only the ones you need.
+///
+/// ```no_run
+/// # async fn demo() -> msg_net::Result<()> {
+/// use msg_net::pipeline::{Pipeline, PipelineEvent, PipelineObserver, PipelineStage};
+/// use std::time::Duration;
+///
+/// struct Timings;
+///
+/// impl PipelineObserver for Timings {
+///     fn on_stage_end(&self, stage: PipelineStage, elapsed: Duration, _summary: &PipelineEvent) {
+///         eprintln!("{:?} took {:?}", stage, elapsed);
+///     }
+/// }
+///
+/// let graph = Pipeline::new().with_text("Alice works at TechCorp.").with_observer(Timings).run().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait PipelineObserver: Send + Sync {
+    fn on_stage_start(&self, _stage: PipelineStage) {}
+
+    /// `summary` is the stage's [`PipelineEvent::TextProcessed`],
+    /// [`PipelineEvent::ExtractionCompleted`] or [`PipelineEvent::GraphBuilt`]
+    fn on_stage_end(&self, _stage: PipelineStage, _elapsed: Duration, _summary: &PipelineEvent) {}
+
+    /// A problem the run recovered from; it is also logged as a warning
+    fn on_warning(&self, _message: &str) {}
+
+    /// A streamed LLM response grew; called for every chunk, so expect many
+    fn on_llm_progress(&self, _progress: &LlmProgress) {}
+}
+
+/// Everything a pipeline run produced, for callers that need more than the graph
+#[derive(Debug, Clone)]
+pub struct PipelineOutput {
+    pub graph: InteractiveGraph,
+    pub extraction: ExtractionResult,
+}
+
+type EventCallback = Arc<dyn Fn(&PipelineEvent) + Send + Sync>;
+
+/// Everyone listening to a run: the event callback and channel, and the observers
+#[derive(Clone, Default)]
+struct Listeners {
+    callback: Option<EventCallback>,
+    sender: Option<UnboundedSender<PipelineEvent>>,
+    observers: Vec<Arc<dyn PipelineObserver>>,
+}
+
+impl Listeners {
+    fn is_empty(&self) -> bool {
+        self.callback.is_none() && self.sender.is_none() && self.observers.is_empty()
+    }
+
+    fn emit(&self, event: PipelineEvent) {
+        if let Some(callback) = &self.callback {
+            callback(&event);
+        }
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(event);
+        }
+    }
+
+    fn stage_started(&self, stage: PipelineStage) -> Instant {
+        for observer in &self.observers {
+            observer.on_stage_start(stage);
+        }
+        self.emit(PipelineEvent::StageStarted(stage));
+        Instant::now()
+    }
+
+    fn stage_finished(&self, stage: PipelineStage, started: Instant, summary: PipelineEvent) {
+        let elapsed = started.elapsed();
+        for observer in &self.observers {
+            observer.on_stage_end(stage, elapsed, &summary);
+        }
+        self.emit(summary);
+    }
+
+    fn warning(&self, message: &str) {
+        for observer in &self.observers {
+            observer.on_warning(message);
+        }
+        self.emit(PipelineEvent::Warning(message.to_string()));
+    }
+
+    fn llm_progress(&self, progress: &LlmProgress) {
+        for observer in &self.observers {
+            observer.on_llm_progress(progress);
+        }
+        self.emit(PipelineEvent::LlmProgress(progress.clone()));
+    }
+}
+
+/// Builder for running the full text-to-graph pipeline from library code.
+///
+/// Unlike the CLI, a pipeline never prints and never touches the filesystem, other than
+/// reading `text_processing.stopwords_file`, the LLM response cache
+/// (`extraction.llm_cache`) and `extraction.plugins` when the config enables them. Progress is delivered as
+/// [`PipelineEvent`]s to an optional callback and/or channel, and to any
+/// [`PipelineObserver`]s.
+///
+/// ```no_run
+/// # async fn demo() -> msg_net::Result<()> {
+/// use msg_net::pipeline::Pipeline;
+///
+/// let graph = Pipeline::new()
+///     .with_text("Alice works at TechCorp.")
+///     .on_event(|event| eprintln!("{:?}", event))
+///     .run()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Pipeline {
+    config: GraphConfig,
+    text: Option<String>,
+    source_type: SourceType,
+    deep_analysis: bool,
+    ocr: Option<OcrText>,
+    listeners: Listeners,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self {
+            config: GraphConfig::default(),
+            text: None,
+            source_type: SourceType::Document,
+            deep_analysis: false,
+            ocr: None,
+            listeners: Listeners::default(),
+        }
+    }
+
+    pub fn with_config(mut self, config: GraphConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn with_text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    pub fn with_source_type(mut self, source_type: SourceType) -> Self {
+        self.source_type = source_type;
+        self
+    }
+
+    /// Use the LLM deep-analysis extraction path instead of the standard one
+    pub fn with_deep_analysis(mut self, deep_analysis: bool) -> Self {
+        self.deep_analysis = deep_analysis;
+        self
+    }
+
+    /// Take the text from an OCR scan, scaling extraction confidences by how well each
+    /// name was read
+    pub fn with_ocr(mut self, ocr: OcrText) -> Self {
+        self.text = Some(ocr.text.clone());
+        self.ocr = Some(ocr);
+        self
+    }
+
+    /// Call `callback` synchronously for every event as the pipeline runs
+    pub fn on_event(mut self, callback: impl Fn(&PipelineEvent) + Send + Sync + 'static) -> Self {
+        self.listeners.callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Send every event to `sender`; events are dropped silently once the receiver is gone
+    pub fn with_event_sender(mut self, sender: UnboundedSender<PipelineEvent>) -> Self {
+        self.listeners.sender = Some(sender);
+        self
+    }
+
+    /// Add an observer; several can be added and each sees every stage
+    pub fn with_observer(mut self, observer: impl PipelineObserver + 'static) -> Self {
+        self.listeners.observers.push(Arc::new(observer));
+        self
+    }
+
+    /// Build a text processor from the `text_processing` section of the config
+    fn text_processor(&self) -> Result<TextProcessor> {
+        let settings = &self.config.text_processing;
+        let mut processor =
+            TextProcessor::new_with_options(settings.stopwords_file.as_deref(), settings.remove_stopwords)?;
+        if let Some(custom) = &settings.custom_stopwords {
+            let mut stopwords = processor.stopwords().clone();
+            stopwords.extend(custom.iter().map(|word| word.trim().to_lowercase()));
+            processor.set_stopwords(stopwords);
+        }
+        processor.set_strip_prompt_stopwords(settings.strip_prompt_stopwords);
+        processor.set_stem_words(settings.stem_words);
+        processor.set_sentence_splitter(SentenceSplitter::new(&settings.sentence_splitting));
+        Ok(processor)
+    }
+
+    /// Process the text and work out which LLM calls a run would make and roughly how
+    /// many tokens they would use, without extracting anything or contacting the LLM
+    pub fn estimate(&self) -> Result<RunEstimate> {
+        let text = self.input_text()?;
+        let processor = self.text_processor()?;
+        let processed_text = processor.process_text(text, self.source_type.clone())?;
+
+        let calls = match self.config.extraction.mode {
+            ExtractionMode::Cooccurrence => Vec::new(),
+            ExtractionMode::Entities | ExtractionMode::Hierarchy => {
+                let mut extractor = EntityExtractor::new(self.config.extraction.clone())?;
+                extractor.set_stopwords(processor.stopwords().clone());
+                extractor.set_sentence_splitter(processor.sentence_splitter().clone());
+                extractor.planned_llm_calls(&processed_text)?
+            }
+        };
+        let sent: Vec<_> = calls.iter().filter(|call| !call.cached).collect();
+        let uses_llm = self.config.extraction.uses_llm() && self.config.extraction.mode != ExtractionMode::Cooccurrence;
+
+        Ok(RunEstimate {
+            mode: format!("{:?}", self.config.extraction.mode).to_lowercase(),
+            model: uses_llm.then(|| self.config.extraction.llm_model.clone()),
+            characters: text.chars().count(),
+            words: processed_text.metadata.word_count,
+            sentences: processed_text.metadata.sentence_count,
+            chunks: calls.iter().filter(|call| call.stage == "entities").count(),
+            llm_calls: sent.len(),
+            cached_calls: calls.len() - sent.len(),
+            prompt_tokens: sent.iter().map(|call| estimate_tokens(&call.prompt)).sum(),
+            response_tokens: sent.iter().map(|call| call.expected_items.max(1) * RESPONSE_TOKENS_PER_ITEM).sum(),
+            largest_prompt_tokens: calls.iter().map(|call| estimate_tokens(&call.prompt)).max().unwrap_or(0),
+            plugins: self.config.extraction.plugins.len(),
+            cost: None,
+            currency: String::new(),
+        }
+        .with_pricing(&self.config.extraction.llm_cost))
+    }
+
+    fn input_text(&self) -> Result<&str> {
+        let text = self
+            .text
+            .as_deref()
+            .ok_or_else(|| GraphError::Configuration("Pipeline has no input text".to_string()))?;
+        if text.trim().is_empty() {
+            return Err(GraphError::TextProcessing("Input text is empty".to_string()));
+        }
+        Ok(text)
+    }
+
+    /// Process the text, extract entities and build the laid-out graph
+    pub async fn run(self) -> Result<InteractiveGraph> {
+        Ok(self.run_detailed().await?.graph)
+    }
+
+    /// Like [`run`](Self::run), also returning the extraction result the graph was built from
+    pub async fn run_detailed(self) -> Result<PipelineOutput> {
+        let text = self.input_text()?;
+        let extraction_result = self.extract().await?;
+
+        let started = self.listeners.stage_started(PipelineStage::GraphBuilding);
+        let mut graph_builder = GraphBuilder::new(self.config.clone());
+        if !self.listeners.is_empty() {
+            let listeners = self.listeners.clone();
+            graph_builder.on_warning(move |message| listeners.warning(message));
+        }
+        let mut graph = graph_builder.build_graph(&extraction_result, text)?;
+        graph_builder.apply_layout(&mut graph)?;
+        self.listeners.stage_finished(
+            PipelineStage::GraphBuilding,
+            started,
+            PipelineEvent::GraphBuilt {
+                nodes: graph.nodes.len(),
+                edges: graph.edges.len(),
+            },
+        );
+
+        Ok(PipelineOutput {
+            graph,
+            extraction: extraction_result,
+        })
+    }
+
+    /// Process the text and extract entities, relationships and concepts without building
+    /// a graph, for callers that combine several extractions into one graph
+    pub async fn extract(&self) -> Result<ExtractionResult> {
+        let text = self.input_text()?;
+
+        let started = self.listeners.stage_started(PipelineStage::TextProcessing);
+        let processor = self.text_processor()?;
+        let processed_text = processor.process_text(text, self.source_type.clone())?;
+        self.listeners.stage_finished(
+            PipelineStage::TextProcessing,
+            started,
+            PipelineEvent::TextProcessed {
+                word_count: processed_text.metadata.word_count,
+                sentence_count: processed_text.metadata.sentence_count,
+            },
+        );
+
+        let started = self.listeners.stage_started(PipelineStage::Extraction);
+        let mut extraction_result = match self.config.extraction.mode {
+            ExtractionMode::Cooccurrence => {
+                let mut result =
+                    extract_cooccurrence(&processed_text, &processor, &self.config.extraction.cooccurrence)?;
+                if let Some(names) = &self.config.extraction.entity_whitelist {
+                    result.retain_entities(names);
+                }
+                result
+            }
+            ExtractionMode::Entities | ExtractionMode::Hierarchy => {
+                let mut extractor = EntityExtractor::new(self.config.extraction.clone())?;
+                extractor.set_stopwords(processor.stopwords().clone());
+                extractor.set_sentence_splitter(processor.sentence_splitter().clone());
+                if !self.listeners.is_empty() {
+                    let listeners = self.listeners.clone();
+                    extractor.on_llm_progress(move |progress| listeners.llm_progress(progress));
+                    let listeners = self.listeners.clone();
+                    extractor.on_warning(move |message| listeners.warning(message));
+                }
+                let mut result = if self.deep_analysis {
+                    extractor.extract_with_deep_analysis(&processed_text).await?
+                } else {
+                    extractor.extract_from_text(&processed_text).await?
+                };
+                if self.config.extraction.mode == ExtractionMode::Hierarchy {
+                    for cycle in infer_hierarchy(&processed_text, &mut result) {
+                        let message = format!("Hierarchy cycle broken: {}", cycle);
+                        warn!("⚠️  {}", message);
+                        self.listeners.warning(&message);
+                    }
+                }
+                result
+            }
+        };
+        if !self.config.extraction.plugins.is_empty() {
+            let input = PluginInput {
+                text: text.to_string(),
+                sentences: processed_text.sentences().map(str::to_string).collect(),
+            };
+            run_extractor_plugins(&self.config.extraction.plugins, &input, &mut extraction_result)?;
+            if let Some(names) = &self.config.extraction.entity_whitelist {
+                extraction_result.retain_entities(names);
+            }
+        }
+        if let Some(ocr) = &self.ocr {
+            ocr.apply_confidence(&mut extraction_result);
+        }
+        if self.config.deterministic {
+            extraction_result.assign_stable_ids();
+        }
+        self.listeners.stage_finished(
+            PipelineStage::Extraction,
+            started,
+            PipelineEvent::ExtractionCompleted {
+                entities: extraction_result.metadata.total_entities,
+                relationships: extraction_result.metadata.total_relationships,
+                concepts: extraction_result.metadata.total_concepts,
+            },
+        );
+
+        Ok(extraction_result)
+    }
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    const TEXT: &str = "Alice works at TechCorp. Bob manages the database system. Alice is connected to Bob.";
+
+    #[tokio::test]
+    async fn test_pipeline_reports_stages_in_order() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&events);
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+
+        let graph = Pipeline::new()
+            .with_text(TEXT)
+            .on_event(move |event| recorded.lock().unwrap().push(event.clone()))
+            .with_event_sender(sender)
+            .run()
+            .await
+            .expect("Pipeline failed");
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 6);
+        assert_eq!(events[0], PipelineEvent::StageStarted(PipelineStage::TextProcessing));
+        assert_eq!(
+            events[5],
+            PipelineEvent::GraphBuilt {
+                nodes: graph.nodes.len(),
+                edges: graph.edges.len()
+            }
+        );
+
+        let mut channel_events = Vec::new();
+        while let Ok(event) = receiver.try_recv() {
+            channel_events.push(event);
+        }
+        assert_eq!(channel_events, *events);
+    }
+
+    /// Records every observer call as a line
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl PipelineObserver for Recorder {
+        fn on_stage_start(&self, stage: PipelineStage) {
+            self.0.lock().unwrap().push(format!("start {:?}", stage));
+        }
+
+        fn on_stage_end(&self, stage: PipelineStage, _elapsed: Duration, summary: &PipelineEvent) {
+            let kind = format!("{:?}", summary);
+            self.0.lock().unwrap().push(format!("end {:?} {}", stage, kind.split(' ').next().unwrap()));
+        }
+
+        fn on_warning(&self, message: &str) {
+            self.0.lock().unwrap().push(format!("warning {}", message));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_observers_see_stages_and_warnings() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut config = GraphConfig::default();
+        config.extraction.use_llm = true;
+        config.extraction.llm_cache.enabled = false;
+        // Nothing listens on this port, so every LLM stage falls back to patterns
+        config.extraction.llm_endpoint = "http://127.0.0.1:9/api/generate".to_string();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&events);
+
+        let graph = Pipeline::new()
+            .with_config(config)
+            .with_text(TEXT)
+            .with_observer(Recorder(Arc::clone(&calls)))
+            .on_event(move |event| recorded.lock().unwrap().push(event.clone()))
+            .run()
+            .await
+            .expect("Pipeline failed");
+        assert!(!graph.nodes.is_empty());
+
+        let calls = calls.lock().unwrap();
+        let stages: Vec<&str> = calls.iter().filter(|call| !call.starts_with("warning")).map(String::as_str).collect();
+        assert_eq!(
+            stages,
+            [
+                "start TextProcessing",
+                "end TextProcessing TextProcessed",
+                "start Extraction",
+                "end Extraction ExtractionCompleted",
+                "start GraphBuilding",
+                "end GraphBuilding GraphBuilt",
+            ]
+        );
+        let warnings: Vec<&String> = calls.iter().filter(|call| call.starts_with("warning LLM call failed")).collect();
+        assert_eq!(warnings.len(), 3, "{:?}", calls);
+        // Warnings come between the extraction stage's start and end
+        let position = |call: &str| calls.iter().position(|recorded| recorded == call).unwrap();
+        assert!(position(warnings[0]) > position("start Extraction"));
+        assert!(position(warnings[2]) < position("end Extraction ExtractionCompleted"));
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.iter().filter(|event| matches!(event, PipelineEvent::Warning(_))).count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_requires_text() {
+        let result = Pipeline::new().run().await;
+        assert!(matches!(result, Err(GraphError::Configuration(_))));
+
+        let result = Pipeline::new().with_text("   ").run().await;
+        assert!(matches!(result, Err(GraphError::TextProcessing(_))));
+    }
+}
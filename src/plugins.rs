@@ -0,0 +1,355 @@
+//! WebAssembly extractor and exporter plugins.
+//!
+//! A plugin is a `.wasm` module with no imports that exports:
+//!
+//! | Export                                  | Purpose                                              |
+//! |-----------------------------------------|------------------------------------------------------|
+//! | `memory`                                | Linear memory used to pass data both ways            |
+//! | `msg_net_alloc(len: i32) -> i32`        | Reserve `len` bytes for the input, returning a pointer |
+//! | `msg_net_extract(ptr: i32, len: i32) -> i64` | Extractor: read [`PluginInput`] JSON, return [`PluginExtraction`] JSON |
+//! | `msg_net_export(ptr: i32, len: i32) -> i64`  | Exporter: read graph JSON, return the file contents |
+//!
+//! Results are returned as `(ptr << 32) | len` of a buffer in the plugin's memory. A module
+//! may implement either entry point or both. Plugins run sandboxed: they get no imports, so
+//! no filesystem or network access, and each call is limited to [`PLUGIN_FUEL`] units of work.
+//!
+//! Loading plugins needs a build with `--features plugins`; without it
+//! [`WasmPlugin::load`] returns a configuration error.
+
+use crate::entity_extractor::{
+    entity_type_from_name, is_symmetric_relation, Concept, Entity, ExtractionResult, Relationship,
+    RelationshipType,
+};
+use crate::error::{GraphError, Result};
+use crate::graph_builder::InteractiveGraph;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Instructions a single plugin call may execute before it is aborted
+pub const PLUGIN_FUEL: u64 = 1_000_000_000;
+
+/// What an extractor plugin receives
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginInput {
+    pub text: String,
+    pub sentences: Vec<String>,
+}
+
+/// What an extractor plugin returns; the same shape the LLM prompts ask for
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PluginExtraction {
+    pub entities: Vec<PluginEntity>,
+    pub relationships: Vec<PluginRelationship>,
+    pub concepts: Vec<PluginConcept>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginEntity {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub entity_type: String,
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+}
+
+/// Relationship between two entities, by name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginRelationship {
+    pub from: String,
+    pub to: String,
+    pub relationship: String,
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginConcept {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+}
+
+fn default_confidence() -> f64 {
+    0.8
+}
+
+impl PluginExtraction {
+    /// Add this output to `result`. Entities already present (same name, ignoring case) are
+    /// reused rather than duplicated, and relationships naming an unknown entity are dropped.
+    pub fn merge_into(self, result: &mut ExtractionResult, plugin_name: &str) {
+        for plugin_entity in self.entities {
+            let exists = result
+                .entities
+                .iter()
+                .any(|entity| entity.name.eq_ignore_ascii_case(&plugin_entity.name));
+            if exists || plugin_entity.name.trim().is_empty() {
+                continue;
+            }
+            result.entities.push(Entity {
+                id: Uuid::new_v4().to_string(),
+                entity_type: entity_type_from_name(&plugin_entity.entity_type),
+                name: plugin_entity.name,
+                attributes: Vec::new(),
+                confidence: plugin_entity.confidence,
+                position: None,
+                mentions: 1,
+                disambiguation: None,
+            });
+        }
+
+        let ids: HashMap<String, String> = result
+            .entities
+            .iter()
+            .map(|entity| (entity.name.to_lowercase(), entity.id.clone()))
+            .collect();
+        for plugin_rel in self.relationships {
+            let (Some(source), Some(target)) =
+                (ids.get(&plugin_rel.from.to_lowercase()), ids.get(&plugin_rel.to.to_lowercase()))
+            else {
+                continue;
+            };
+            result.relationships.push(Relationship {
+                id: Uuid::new_v4().to_string(),
+                source_entity_id: source.clone(),
+                target_entity_id: target.clone(),
+                relationship_type: RelationshipType::Other(plugin_rel.relationship.clone()),
+                bidirectional: is_symmetric_relation(&plugin_rel.relationship),
+                label: plugin_rel.relationship,
+                confidence: plugin_rel.confidence,
+                position: None,
+                category: None,
+                context: None,
+            });
+        }
+
+        for plugin_concept in self.concepts {
+            result.concepts.push(Concept {
+                id: Uuid::new_v4().to_string(),
+                name: plugin_concept.name,
+                description: plugin_concept.description,
+                related_entities: Vec::new(),
+                confidence: plugin_concept.confidence,
+                position: None,
+            });
+        }
+
+        result.metadata.total_entities = result.entities.len();
+        result.metadata.total_relationships = result.relationships.len();
+        result.metadata.total_concepts = result.concepts.len();
+        result.metadata.extraction_method = format!("{}+plugin:{}", result.metadata.extraction_method, plugin_name);
+    }
+}
+
+/// A compiled plugin module
+pub struct WasmPlugin {
+    name: String,
+    path: PathBuf,
+    #[cfg(feature = "plugins")]
+    engine: wasmtime::Engine,
+    #[cfg(feature = "plugins")]
+    module: wasmtime::Module,
+}
+
+impl WasmPlugin {
+    /// Compile the module at `path` (binary `.wasm`, or `.wat` text)
+    #[cfg(feature = "plugins")]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)?;
+        let mut config = wasmtime::Config::new();
+        config.consume_fuel(true);
+        let engine = wasmtime::Engine::new(&config).map_err(|e| plugin_error(path, e))?;
+        let module = wasmtime::Module::new(&engine, &bytes).map_err(|e| plugin_error(path, e))?;
+        Ok(Self {
+            name: plugin_name(path),
+            path: path.to_path_buf(),
+            engine,
+            module,
+        })
+    }
+
+    /// Compile the module at `path` (binary `.wasm`, or `.wat` text)
+    #[cfg(not(feature = "plugins"))]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Err(GraphError::Configuration(format!(
+            "Cannot load plugin {}: msg_net was built without plugin support; rebuild with `--features plugins`",
+            path.as_ref().display()
+        )))
+    }
+
+    /// File stem of the module, used in the extraction method and log messages
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Run the plugin's `msg_net_extract` on `input`
+    pub fn extract(&self, input: &PluginInput) -> Result<PluginExtraction> {
+        let output = self.call("msg_net_extract", &serde_json::to_vec(input)?)?;
+        serde_json::from_slice(&output).map_err(|e| {
+            GraphError::EntityExtraction(format!("Plugin {} returned invalid JSON: {}", self.name, e))
+        })
+    }
+
+    /// Run the plugin's `msg_net_export` on `graph`, returning the file contents
+    pub fn export(&self, graph: &InteractiveGraph) -> Result<Vec<u8>> {
+        self.call("msg_net_export", &serde_json::to_vec(graph)?)
+    }
+
+    /// Copy `input` into a fresh instance, call `function` on it and copy the result out
+    #[cfg(feature = "plugins")]
+    fn call(&self, function: &str, input: &[u8]) -> Result<Vec<u8>> {
+        use wasmtime::{Instance, Store};
+
+        let fail = |e: wasmtime::Error| plugin_error(&self.path, e);
+        let mut store = Store::new(&self.engine, ());
+        store.set_fuel(PLUGIN_FUEL).map_err(fail)?;
+        let instance = Instance::new(&mut store, &self.module, &[]).map_err(fail)?;
+        let memory = instance.get_memory(&mut store, "memory").ok_or_else(|| {
+            GraphError::Configuration(format!("Plugin {} does not export `memory`", self.name))
+        })?;
+        let alloc = instance.get_typed_func::<i32, i32>(&mut store, "msg_net_alloc").map_err(fail)?;
+        let entry = instance.get_typed_func::<(i32, i32), i64>(&mut store, function).map_err(fail)?;
+
+        let len = i32::try_from(input.len())
+            .map_err(|_| GraphError::Configuration(format!("Input too large for plugin {}", self.name)))?;
+        let ptr = alloc.call(&mut store, len).map_err(fail)?;
+        memory.write(&mut store, ptr as u32 as usize, input).map_err(|e| plugin_error(&self.path, e))?;
+
+        let packed = entry.call(&mut store, (ptr, len)).map_err(fail)? as u64;
+        let (out_ptr, out_len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+        let mut output = vec![0; out_len];
+        memory.read(&store, out_ptr, &mut output).map_err(|e| plugin_error(&self.path, e))?;
+        Ok(output)
+    }
+
+    #[cfg(not(feature = "plugins"))]
+    fn call(&self, _function: &str, _input: &[u8]) -> Result<Vec<u8>> {
+        unreachable!("plugins cannot be loaded without the `plugins` feature")
+    }
+}
+
+#[cfg(feature = "plugins")]
+fn plugin_error(path: &Path, error: impl std::fmt::Display) -> GraphError {
+    GraphError::Configuration(format!("Plugin {} failed: {}", path.display(), error))
+}
+
+#[cfg(feature = "plugins")]
+fn plugin_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// Run every plugin in `paths` over `input` and merge their output into `result`, in order
+pub fn run_extractor_plugins(paths: &[String], input: &PluginInput, result: &mut ExtractionResult) -> Result<()> {
+    for path in paths {
+        let plugin = WasmPlugin::load(path)?;
+        plugin.extract(input)?.merge_into(result, plugin.name());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity_extractor::{EntityType, ExtractionMetadata};
+
+    fn empty_result() -> ExtractionResult {
+        ExtractionResult {
+            entities: Vec::new(),
+            relationships: Vec::new(),
+            concepts: Vec::new(),
+            metadata: ExtractionMetadata {
+                total_entities: 0,
+                total_relationships: 0,
+                total_concepts: 0,
+                processing_time_ms: 0,
+                confidence_threshold: 0.5,
+                extraction_method: "Pattern-based".to_string(),
+            },
+        }
+    }
+
+    const CHEMISTRY: &str = r#"{
+        "entities": [
+            {"name": "Sodium", "type": "element"},
+            {"name": "Chlorine", "type": "element", "confidence": 0.9},
+            {"name": "sodium", "type": "element"}
+        ],
+        "relationships": [
+            {"from": "sodium", "to": "Chlorine", "relationship": "bonds with"},
+            {"from": "Sodium", "to": "Argon", "relationship": "ignores"}
+        ],
+        "concepts": [{"name": "ionic bond"}]
+    }"#;
+
+    #[test]
+    fn test_plugin_output_merges_by_entity_name() {
+        let mut result = empty_result();
+        let output: PluginExtraction = serde_json::from_str(CHEMISTRY).unwrap();
+        output.merge_into(&mut result, "chem");
+
+        assert_eq!(result.entities.len(), 2);
+        assert!(matches!(&result.entities[0].entity_type, EntityType::Other(t) if t == "element"));
+        assert_eq!(result.relationships.len(), 1);
+        assert_eq!(result.relationships[0].source_entity_id, result.entities[0].id);
+        assert_eq!(result.relationships[0].label, "bonds with");
+        assert_eq!(result.metadata.total_concepts, 1);
+        assert_eq!(result.metadata.extraction_method, "Pattern-based+plugin:chem");
+    }
+
+    #[cfg(not(feature = "plugins"))]
+    #[test]
+    fn test_load_without_feature_explains_rebuild() {
+        let error = WasmPlugin::load("chem.wasm").err().expect("Load should fail");
+        assert!(error.to_string().contains("--features plugins"));
+    }
+
+    #[cfg(feature = "plugins")]
+    #[tokio::test]
+    async fn test_wasm_extractor_and_exporter() {
+        let json = serde_json::to_string(&serde_json::from_str::<serde_json::Value>(CHEMISTRY).unwrap()).unwrap();
+        let escaped: String = json.bytes().map(|b| format!("\\{:02x}", b)).collect();
+        // Extract returns the fixed JSON at offset 16; export echoes its input back
+        let wat = format!(
+            r#"(module
+                (memory (export "memory") 1)
+                (data (i32.const 16) "{escaped}")
+                (func (export "msg_net_alloc") (param i32) (result i32) i32.const 4096)
+                (func (export "msg_net_extract") (param i32 i32) (result i64)
+                    i64.const {packed})
+                (func (export "msg_net_export") (param i32 i32) (result i64)
+                    local.get 0
+                    i64.extend_i32_u
+                    i64.const 32
+                    i64.shl
+                    local.get 1
+                    i64.extend_i32_u
+                    i64.or))"#,
+            packed = (16u64 << 32) | json.len() as u64,
+        );
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chem.wat");
+        std::fs::write(&path, wat).unwrap();
+
+        let plugin = WasmPlugin::load(&path).expect("Plugin failed to load");
+        assert_eq!(plugin.name(), "chem");
+        let input = PluginInput { text: "Sodium bonds with chlorine.".to_string(), sentences: Vec::new() };
+        let mut result = empty_result();
+        run_extractor_plugins(&[path.display().to_string()], &input, &mut result).unwrap();
+        assert_eq!(result.entities.len(), 2);
+
+        let graph = crate::pipeline::Pipeline::new().with_text("Alice works at TechCorp.").run().await.unwrap();
+        let echoed = plugin.export(&graph).unwrap();
+        assert_eq!(echoed, serde_json::to_vec(&graph).unwrap());
+    }
+}
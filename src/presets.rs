@@ -0,0 +1,205 @@
+use crate::config::GraphConfig;
+use crate::error::{GraphError, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// Ready-made configurations tuned for common document types (`--preset`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    MeetingNotes,
+    Novel,
+    IncidentReport,
+    ResearchPaper,
+    ChatForensics,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 5] = [
+        Preset::MeetingNotes,
+        Preset::Novel,
+        Preset::IncidentReport,
+        Preset::ResearchPaper,
+        Preset::ChatForensics,
+    ];
+
+    /// Name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Preset::MeetingNotes => "meeting-notes",
+            Preset::Novel => "novel",
+            Preset::IncidentReport => "incident-report",
+            Preset::ResearchPaper => "research-paper",
+            Preset::ChatForensics => "chat-forensics",
+        }
+    }
+
+    /// One-line summary for help output
+    pub fn description(&self) -> &'static str {
+        match self {
+            Preset::MeetingNotes => "attendees, owners, decisions and action items",
+            Preset::Novel => "characters and how they relate, trimmed to the most mentioned",
+            Preset::IncidentReport => "services, hosts and error codes linked by cause and mitigation",
+            Preset::ResearchPaper => "methods, datasets and acronyms, with keyword concepts",
+            Preset::ChatForensics => "handles, contacts and transfers, with sentiment-colored edges",
+        }
+    }
+
+    /// The full configuration: the defaults with this preset's patterns, stopwords,
+    /// transforms, colors and layout
+    pub fn config(&self) -> GraphConfig {
+        let mut config = GraphConfig::default();
+        let strings = |values: &[&str]| values.iter().map(|value| value.to_string()).collect::<Vec<_>>();
+
+        match self {
+            Preset::MeetingNotes => {
+                config.extraction.entity_patterns = strings(&[
+                    r"\b[A-Z][a-z]+(?:\s+[A-Z][a-z]+)*\b",
+                    r"\b(?:action item|decision|deadline|blocker)s?\b",
+                ]);
+                config.extraction.relationship_patterns = strings(&[
+                    r"\b(?:assigned to|owns|owner of|will|agreed|decided|reports to|follows up with)\b",
+                    r"\b(?:has|is|are|was|were|needs|blocks|depends on)\b",
+                ]);
+                config.extraction.concept_patterns = strings(&[
+                    r"\b(?:agenda|decision|action item|deadline|blocker|follow-up|milestone|budget)\b",
+                ]);
+                config.text_processing.custom_stopwords =
+                    Some(strings(&["meeting", "notes", "minutes", "um", "ok", "okay", "yeah", "thanks"]));
+                config.transforms = strings(&["dedupe", "prune_isolated"]);
+                config.node_colors.entity = "#5C6BC0".to_string();
+                config.node_colors.concept = "#FFB300".to_string();
+                config.layout.algorithm = "force".to_string();
+                config.layout.hierarchical = false;
+            }
+            Preset::Novel => {
+                config.extraction.entity_patterns = strings(&[r"\b[A-Z][a-z]+(?:\s+[A-Z][a-z]+)*\b"]);
+                config.extraction.relationship_patterns = strings(&[
+                    r"\b(?:loves|loved|hates|hated|married|met|killed|betrayed|visited|told|kissed|followed)\b",
+                    r"\b(?:father of|mother of|son of|daughter of|friend of|sister of|brother of|wife of|husband of)\b",
+                ]);
+                config.extraction.concept_patterns = strings(&[
+                    r"\b(?:love|war|death|betrayal|revenge|family|honor|freedom|fate|secret)\b",
+                ]);
+                config.text_processing.custom_stopwords =
+                    Some(strings(&["said", "chapter", "mr", "mrs", "miss", "sir", "lady", "upon"]));
+                config.transforms = strings(&["dedupe", "min_confidence 0.6", "keep_top 60 by mention_count"]);
+                config.node_colors.entity = "#8E24AA".to_string();
+                config.node_colors.concept = "#26A69A".to_string();
+                config.layout.algorithm = "force".to_string();
+                config.layout.hierarchical = false;
+                config.layout.spacing = 250.0;
+            }
+            Preset::IncidentReport => {
+                config.extraction.entity_patterns = strings(&[
+                    r"\b[A-Z][a-z]+(?:\s+[A-Z][a-z]+)*\b",
+                    r"\b[a-z0-9]+(?:-[a-z0-9]+)*(?:\.[a-z0-9-]+)+\b",
+                    r"\b[a-z]+(?:-[a-z]+)*-(?:service|api|db|worker|gateway)\b",
+                    r"\b[A-Z]{2,}-\d+\b",
+                ]);
+                config.extraction.relationship_patterns = strings(&[
+                    r"\b(?:caused|triggered|depends on|failed|restarted|rolled back|escalated to|paged|mitigated|blocked)\b",
+                    r"\b(?:has|is|are|was|were|connected to|calls|writes to|reads from)\b",
+                ]);
+                config.extraction.concept_patterns = strings(&[
+                    r"\b(?:outage|root cause|mitigation|impact|rollback|timeline|alert|latency|error rate|postmortem)\b",
+                ]);
+                config.text_processing.custom_stopwords =
+                    Some(strings(&["incident", "report", "utc", "approximately", "ticket"]));
+                config.transforms = strings(&["dedupe", "prune_isolated"]);
+                config.node_colors.entity = "#E53935".to_string();
+                config.node_colors.concept = "#FB8C00".to_string();
+                config.node_colors.relationship = "#546E7A".to_string();
+                config.layout.algorithm = "hierarchical".to_string();
+                config.layout.hierarchical = true;
+            }
+            Preset::ResearchPaper => {
+                config.extraction.entity_patterns = strings(&[
+                    r"\b[A-Z][a-z]+(?:\s+[A-Z][a-z]+)*\b",
+                    r"\b[A-Z]{2,}[a-z]*(?:-\d+)?\b",
+                ]);
+                config.extraction.relationship_patterns = strings(&[
+                    r"\b(?:outperforms|extends|based on|compared with|compared to|proposes|evaluates on|improves on|builds on)\b",
+                    r"\b(?:uses|is|are|has|introduces|cites)\b",
+                ]);
+                config.extraction.concept_patterns = strings(&[
+                    r"\b(?:method|dataset|model|baseline|hypothesis|experiment|metric|benchmark|ablation|theory)\b",
+                ]);
+                config.extraction.keywords.enabled = true;
+                config.text_processing.custom_stopwords =
+                    Some(strings(&["et", "al", "fig", "figure", "table", "section", "paper", "we", "our"]));
+                config.transforms = strings(&["dedupe", "keep_top 150 by degree"]);
+                config.node_colors.entity = "#1E88E5".to_string();
+                config.node_colors.concept = "#43A047".to_string();
+                config.layout.algorithm = "force".to_string();
+                config.layout.hierarchical = false;
+            }
+            Preset::ChatForensics => {
+                config.extraction.entity_patterns = strings(&[
+                    r"@\w+",
+                    r"\b[A-Z][a-z]+(?:\s+[A-Z][a-z]+)*\b",
+                    r"\b[\w.+-]+@[\w-]+\.[\w.]+\b",
+                    r"\+?\d[\d -]{7,}\d",
+                    r"https?://\S+",
+                ]);
+                config.extraction.relationship_patterns = strings(&[
+                    r"\b(?:sent|messaged|replied to|called|met|paid|transferred|shared|forwarded|deleted)\b",
+                    r"\b(?:knows|is|was|has|owes|works with)\b",
+                ]);
+                config.extraction.concept_patterns = strings(&[
+                    r"\b(?:meeting|payment|transfer|password|account|location|address|wallet|invoice)\b",
+                ]);
+                config.text_processing.custom_stopwords =
+                    Some(strings(&["lol", "ok", "okay", "yeah", "hey", "hi", "u", "ur", "pls", "thx"]));
+                config.sentiment.enabled = true;
+                config.transforms = strings(&["dedupe"]);
+                config.node_colors.entity = "#37474F".to_string();
+                config.node_colors.concept = "#FDD835".to_string();
+                config.layout.algorithm = "force".to_string();
+                config.layout.hierarchical = false;
+            }
+        }
+        config
+    }
+}
+
+impl FromStr for Preset {
+    type Err = GraphError;
+
+    fn from_str(name: &str) -> Result<Self> {
+        let normalized = name.trim().to_lowercase().replace('_', "-");
+        Preset::ALL.into_iter().find(|preset| preset.name() == normalized).ok_or_else(|| {
+            let names: Vec<&str> = Preset::ALL.iter().map(Preset::name).collect();
+            GraphError::Configuration(format!("Unknown preset '{}': expected one of {}", name, names.join(", ")))
+        })
+    }
+}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::Pipeline;
+
+    #[tokio::test]
+    async fn test_every_preset_builds_a_graph() {
+        let text = "Alice met Bob at the checkout-service review. Alice transferred the invoice to @bob. \
+                    The outage caused a rollback, and Carol proposes a new method. Dana agreed with Erin on the budget.";
+        for preset in Preset::ALL {
+            assert_eq!(preset.name().parse::<Preset>().unwrap(), preset);
+            let graph = Pipeline::new()
+                .with_config(preset.config())
+                .with_text(text)
+                .run()
+                .await
+                .unwrap_or_else(|e| panic!("{} failed: {}", preset, e));
+            assert!(!graph.nodes.is_empty(), "{} found nothing", preset);
+        }
+        assert!("chat_forensics".parse::<Preset>().is_ok());
+        assert!("poetry".parse::<Preset>().is_err());
+    }
+}
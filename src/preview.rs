@@ -0,0 +1,303 @@
+//! Local preview of generated pages (`generate --serve-preview`, `--open`).
+//!
+//! The server shows the latest page at `/` and serves the files next to it, such as the
+//! chunk files of a chunked page. Pages get a small script that polls the server and
+//! reloads when a regeneration replaces the page.
+
+use crate::error::{GraphError, Result};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// Path the reload script polls for the page version
+const VERSION_PATH: &str = "/__preview/version";
+
+/// How often the reload script asks for the page version
+const RELOAD_POLL_MS: u64 = 1000;
+
+/// How often watched input files are checked for changes
+const WATCH_POLL: Duration = Duration::from_millis(500);
+
+/// Largest request head read before answering
+const MAX_REQUEST_BYTES: usize = 8192;
+
+/// The page being previewed and a counter bumped each time it is replaced
+#[derive(Debug)]
+struct PreviewState {
+    page: PathBuf,
+    version: u64,
+}
+
+/// HTTP server on localhost showing the latest generated page; stops when dropped
+pub struct PreviewServer {
+    address: std::net::SocketAddr,
+    state: Arc<Mutex<PreviewState>>,
+    task: JoinHandle<()>,
+}
+
+impl PreviewServer {
+    /// Serve `page` on `127.0.0.1:port` (0 picks a free port)
+    pub async fn start(page: impl Into<PathBuf>, port: u16) -> Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .await
+            .map_err(|e| GraphError::Configuration(format!("Cannot serve the preview on port {}: {}", port, e)))?;
+        let address = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(PreviewState { page: page.into(), version: 1 }));
+
+        let shared = Arc::clone(&state);
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = Arc::clone(&shared);
+                tokio::spawn(async move {
+                    // A client hanging up mid-response is not worth reporting
+                    let _ = handle_connection(stream, &state).await;
+                });
+            }
+        });
+
+        Ok(Self { address, state, task })
+    }
+
+    /// Address of the page, e.g. `http://127.0.0.1:8765/`
+    pub fn url(&self) -> String {
+        format!("http://{}/", self.address)
+    }
+
+    /// Show `page` from now on; open browser tabs reload
+    pub fn set_page(&self, page: impl Into<PathBuf>) {
+        let mut state = self.state.lock().expect("preview state lock poisoned");
+        state.page = page.into();
+        state.version += 1;
+    }
+}
+
+impl Drop for PreviewServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Answer one request: the page at `/`, its version for the reload script, or a file
+/// from the page's directory
+async fn handle_connection(mut stream: TcpStream, state: &Mutex<PreviewState>) -> std::io::Result<()> {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") && request.len() < MAX_REQUEST_BYTES {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buffer[..read]);
+    }
+    let head = String::from_utf8_lossy(&request);
+    let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+    let (method, target) = (request_line.next().unwrap_or_default(), request_line.next().unwrap_or("/"));
+    let path = target.split(['?', '#']).next().unwrap_or("/");
+
+    let (page, version) = {
+        let state = state.lock().expect("preview state lock poisoned");
+        (state.page.clone(), state.version)
+    };
+    let (status, content_type, body) = if method != "GET" {
+        ("405 Method Not Allowed", "text/plain", b"Only GET is supported".to_vec())
+    } else if path == VERSION_PATH {
+        ("200 OK", "text/plain", version.to_string().into_bytes())
+    } else if path == "/" {
+        match tokio::fs::read(&page).await {
+            Ok(content) => ("200 OK", content_type(&page), with_reload_script(content, &page, version)),
+            Err(e) => ("404 Not Found", "text/plain", format!("{}: {}", page.display(), e).into_bytes()),
+        }
+    } else {
+        match page_relative_file(&page, path) {
+            Some(file) => match tokio::fs::read(&file).await {
+                Ok(content) => ("200 OK", content_type(&file), content),
+                Err(_) => ("404 Not Found", "text/plain", b"Not found".to_vec()),
+            },
+            None => ("404 Not Found", "text/plain", b"Not found".to_vec()),
+        }
+    };
+
+    let header = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    );
+    stream.write_all(header.as_bytes()).await?;
+    stream.write_all(&body).await?;
+    stream.shutdown().await
+}
+
+/// `path` from a request resolved inside the page's directory; `None` for paths that
+/// would leave it
+fn page_relative_file(page: &Path, path: &str) -> Option<PathBuf> {
+    let relative = Path::new(path.trim_start_matches('/'));
+    if relative.components().any(|component| !matches!(component, Component::Normal(_))) {
+        return None;
+    }
+    Some(page.parent().unwrap_or(Path::new(".")).join(relative))
+}
+
+/// HTML pages with a script reloading them when the page version moves past `version`
+fn with_reload_script(content: Vec<u8>, page: &Path, version: u64) -> Vec<u8> {
+    if page.extension().and_then(|extension| extension.to_str()) != Some("html") {
+        return content;
+    }
+    let script = format!(
+        "<script>setInterval(function () {{ fetch('{}').then(function (r) {{ return r.text(); }})\
+         .then(function (v) {{ if (Number(v) !== {}) {{ location.reload(); }} }}).catch(function () {{}}); }}, {});</script>\n",
+        VERSION_PATH, version, RELOAD_POLL_MS
+    );
+    let html = String::from_utf8_lossy(&content);
+    match html.rfind("</body>") {
+        Some(end) => format!("{}{}{}", &html[..end], script, &html[end..]).into_bytes(),
+        None => format!("{}{}", html, script).into_bytes(),
+    }
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|extension| extension.to_str()).unwrap_or_default() {
+        "html" => "text/html; charset=utf-8",
+        "json" => "application/json",
+        "js" => "text/javascript",
+        "css" => "text/css",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        _ => "text/plain; charset=utf-8",
+    }
+}
+
+/// Open `target` (a file path or URL) in the default browser: `$BROWSER` when set, else
+/// `open` on macOS, `start` on Windows and `xdg-open` elsewhere. Does not wait for the
+/// browser to exit.
+pub fn open_in_browser(target: &str) -> Result<()> {
+    let mut command = match std::env::var("BROWSER") {
+        Ok(browser) if !browser.trim().is_empty() => std::process::Command::new(browser.trim()),
+        _ if cfg!(target_os = "macos") => std::process::Command::new("open"),
+        _ if cfg!(windows) => {
+            let mut command = std::process::Command::new("cmd");
+            command.args(["/C", "start", ""]);
+            command
+        }
+        _ => std::process::Command::new("xdg-open"),
+    };
+    command
+        .arg(target)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| GraphError::Configuration(format!("Cannot open {} in a browser: {}", target, e)))
+}
+
+/// Input files whose modification times are polled, so a preview can regenerate when
+/// one is saved
+pub struct FileWatcher {
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl FileWatcher {
+    /// Watch the `paths` that are files; others (URLs, missing paths) are skipped
+    pub fn new<P: AsRef<Path>>(paths: &[P]) -> Self {
+        let files = paths
+            .iter()
+            .map(|path| path.as_ref().to_path_buf())
+            .filter(|path| path.is_file())
+            .map(|path| {
+                let modified = modified(&path);
+                (path, modified)
+            })
+            .collect();
+        Self { files }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Whether any file changed since the last call (or since the watcher was made)
+    pub fn changed(&mut self) -> bool {
+        let mut changed = false;
+        for (path, last) in &mut self.files {
+            let now = modified(path);
+            if now != *last {
+                *last = now;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Wait until a watched file changes; never returns when nothing is watched
+    pub async fn wait_for_change(&mut self) {
+        loop {
+            tokio::time::sleep(WATCH_POLL).await;
+            if self.changed() {
+                return;
+            }
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn get(server: &PreviewServer, path: &str) -> String {
+        let mut stream = TcpStream::connect(server.address).await.unwrap();
+        stream
+            .write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).as_bytes())
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_preview_serves_the_latest_page_with_a_reload_script() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("graph.html");
+        let second = dir.path().join("graph_01.html");
+        std::fs::write(&first, "<html><body>first</body></html>").unwrap();
+        std::fs::write(&second, "<html><body>second</body></html>").unwrap();
+        std::fs::write(dir.path().join("data.json"), "{}").unwrap();
+
+        let server = PreviewServer::start(&first, 0).await.unwrap();
+        assert!(server.url().starts_with("http://127.0.0.1:"));
+        let page = get(&server, "/").await;
+        assert!(page.starts_with("HTTP/1.1 200 OK"));
+        assert!(page.contains("first<script>") && page.contains("Number(v) !== 1"));
+        assert!(get(&server, VERSION_PATH).await.ends_with("\r\n\r\n1"));
+
+        server.set_page(&second);
+        assert!(get(&server, VERSION_PATH).await.ends_with("\r\n\r\n2"));
+        assert!(get(&server, "/").await.contains("second"));
+
+        // Files next to the page are served as they are; nothing outside its directory is
+        assert!(get(&server, "/data.json").await.ends_with("\r\n\r\n{}"));
+        assert!(get(&server, "/../graph.html").await.starts_with("HTTP/1.1 404"));
+    }
+
+    #[test]
+    fn test_file_watcher_notices_a_saved_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input.txt");
+        std::fs::write(&input, "Alice manages Bob.").unwrap();
+        let mut watcher = FileWatcher::new(&[input.as_path(), Path::new("https://example.com")]);
+        assert!(!watcher.is_empty());
+        assert!(!watcher.changed());
+
+        let file = std::fs::File::options().write(true).open(&input).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(5)).unwrap();
+        assert!(watcher.changed());
+        assert!(!watcher.changed());
+    }
+}
@@ -0,0 +1,204 @@
+use crate::estimate::{estimate_tokens, CHARS_PER_TOKEN};
+use crate::sentence_splitter::SentenceSplitter;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Share of a split prompt's text budget kept free for names carried over from earlier
+/// segments (entity prompts) or mentioned in the segment (relationship prompts)
+const CARRYOVER_SHARE: f64 = 0.25;
+
+/// Fewest text tokens a segment may hold, so a prompt template larger than the context
+/// window still makes progress through the text
+const MIN_SEGMENT_TOKENS: usize = 64;
+
+/// Context window budget for LLM prompts (`extraction.prompt_budget`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PromptBudgetConfig {
+    /// Context window, in tokens, of models not listed in `models`
+    pub context_tokens: usize,
+    /// Tokens of the window kept free for the model's response
+    pub response_tokens: usize,
+    /// Context window per model; a name without a `:tag` covers every tag of that model
+    pub models: BTreeMap<String, usize>,
+}
+
+impl Default for PromptBudgetConfig {
+    fn default() -> Self {
+        Self {
+            context_tokens: 4096,
+            response_tokens: 1024,
+            models: BTreeMap::new(),
+        }
+    }
+}
+
+impl PromptBudgetConfig {
+    /// Context window of `model`: its exact entry, else the entry for its untagged name,
+    /// else `context_tokens`
+    pub fn context_tokens_for(&self, model: &str) -> usize {
+        let base = model.split(':').next().unwrap_or(model);
+        self.models
+            .get(model)
+            .or_else(|| self.models.get(base))
+            .copied()
+            .unwrap_or(self.context_tokens)
+    }
+
+    /// How `text` is sent to `model` in prompts whose template (the prompt with an empty
+    /// text) is `template_tokens` long, splitting it between the sentences `splitter` finds
+    pub fn plan<'a>(&self, model: &str, text: &'a str, template_tokens: usize, splitter: &SentenceSplitter) -> PromptPlan<'a> {
+        let available = self
+            .context_tokens_for(model)
+            .saturating_sub(self.response_tokens + template_tokens)
+            .max(MIN_SEGMENT_TOKENS);
+        if estimate_tokens(text) <= available {
+            return PromptPlan {
+                segments: vec![text],
+                carryover_tokens: 0,
+            };
+        }
+        let carryover_tokens = (available as f64 * CARRYOVER_SHARE) as usize;
+        PromptPlan {
+            segments: split_to_budget(text, available - carryover_tokens, splitter),
+            carryover_tokens,
+        }
+    }
+}
+
+/// The pieces of a text sent in separate prompts, see [`PromptBudgetConfig::plan`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptPlan<'a> {
+    pub segments: Vec<&'a str>,
+    /// Tokens left in each segment's prompt for a list of entity names; 0 when the text
+    /// fits one prompt and nothing needs carrying over
+    pub carryover_tokens: usize,
+}
+
+impl PromptPlan<'_> {
+    pub fn is_split(&self) -> bool {
+        self.segments.len() > 1
+    }
+}
+
+/// Split `text` into consecutive slices of at most `max_tokens` estimated tokens,
+/// breaking between the sentences `splitter` finds, or between words inside a sentence
+/// that is too long alone
+pub fn split_to_budget<'a>(text: &'a str, max_tokens: usize, splitter: &SentenceSplitter) -> Vec<&'a str> {
+    let mut pieces = Vec::new();
+    for (start, end) in splitter.split_spans(text) {
+        if estimate_tokens(&text[start..end]) <= max_tokens {
+            pieces.push((start, end));
+        } else {
+            pieces.extend(word_pieces(text, start, end, max_tokens));
+        }
+    }
+
+    let mut segments = Vec::new();
+    let mut current: Option<(usize, usize)> = None;
+    for (start, end) in pieces {
+        current = match current {
+            Some((segment_start, _)) if estimate_tokens(&text[segment_start..end]) <= max_tokens => Some((segment_start, end)),
+            Some((segment_start, segment_end)) => {
+                segments.push(&text[segment_start..segment_end]);
+                Some((start, end))
+            }
+            None => Some((start, end)),
+        };
+    }
+    segments.extend(current.map(|(start, end)| &text[start..end]));
+    segments
+}
+
+/// Byte ranges of `text[start..end]` cut at whitespace into runs of at most `max_tokens`;
+/// a single word longer than that becomes its own run
+fn word_pieces(text: &str, start: usize, end: usize, max_tokens: usize) -> Vec<(usize, usize)> {
+    let max_chars = (max_tokens as f64 * CHARS_PER_TOKEN) as usize;
+    let mut pieces = Vec::new();
+    let mut piece: Option<(usize, usize)> = None;
+    let mut offset = start;
+    for word in text[start..end].split_inclusive(char::is_whitespace) {
+        let word_start = offset;
+        offset += word.len();
+        let word_end = word_start + word.trim_end().len();
+        piece = match piece {
+            Some((piece_start, _)) if text[piece_start..word_end].chars().count() <= max_chars => Some((piece_start, word_end)),
+            Some(full) => {
+                pieces.push(full);
+                Some((word_start, word_end))
+            }
+            None => Some((word_start, word_end)),
+        };
+    }
+    pieces.extend(piece);
+    pieces.retain(|(start, end)| start < end);
+    pieces
+}
+
+/// The first `names` whose list, as quoted in a prompt, fits in `max_tokens`
+pub fn names_within_budget<'a>(names: &[&'a str], max_tokens: usize) -> Vec<&'a str> {
+    let mut tokens = 0;
+    names
+        .iter()
+        .copied()
+        .take_while(|name| {
+            // Quotes, comma and space around each name
+            tokens += estimate_tokens(name) + 1;
+            tokens <= max_tokens
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SentenceSplittingConfig;
+
+    #[test]
+    fn test_budget_per_model_and_splitting() {
+        let mut config = PromptBudgetConfig::default();
+        config.models.insert("llama3.2".to_string(), 8192);
+        config.models.insert("llama3.2:1b".to_string(), 2048);
+        assert_eq!(config.context_tokens_for("llama3.2:1b"), 2048);
+        assert_eq!(config.context_tokens_for("llama3.2:3b"), 8192);
+        assert_eq!(config.context_tokens_for("mistral"), 4096);
+
+        let text = "Alice works at Initech. Bob lives in Berlin. Carol met Dave in Paris.";
+        let splitter = SentenceSplitter::default();
+        assert_eq!(config.plan("mistral", text, 100, &splitter).segments, [text]);
+        assert!(!config.plan("mistral", text, 100, &splitter).is_split());
+
+        // Sentences are packed while they fit and kept whole
+        let segments = split_to_budget(text, 12, &splitter);
+        assert_eq!(segments, ["Alice works at Initech. Bob lives in Berlin.", "Carol met Dave in Paris."]);
+        // A sentence too long alone is cut between words
+        let segments = split_to_budget("one two three four five six seven eight", 4, &splitter);
+        assert_eq!(segments, ["one two three", "four five six", "seven eight"]);
+        assert!(segments.iter().all(|segment| estimate_tokens(segment) <= 4));
+
+        config.context_tokens = 1024 + 200 + 100;
+        let long = text.repeat(20);
+        let plan = config.plan("mistral", &long, 100, &splitter);
+        assert!(plan.is_split());
+        assert_eq!(plan.carryover_tokens, 50);
+        assert!(plan.segments.iter().all(|segment| estimate_tokens(segment) <= 150));
+        assert_eq!(plan.segments.join(" ").split_whitespace().count(), long.split_whitespace().count());
+
+        assert_eq!(names_within_budget(&["Alice", "Initech", "Berlin"], 6), ["Alice", "Initech"]);
+    }
+
+    #[test]
+    fn test_splitting_keeps_the_configured_abbreviations_inside_sentences() {
+        let text = "Alice met Bob there. Warner Bros. Pictures hired Carol.";
+        assert_eq!(
+            split_to_budget(text, 9, &SentenceSplitter::default()),
+            ["Alice met Bob there. Warner Bros.", "Pictures hired Carol."]
+        );
+
+        let config = SentenceSplittingConfig { abbreviations: vec!["Bros.".to_string()], ..SentenceSplittingConfig::default() };
+        assert_eq!(
+            split_to_budget(text, 9, &SentenceSplitter::new(&config)),
+            ["Alice met Bob there.", "Warner Bros. Pictures hired Carol."]
+        );
+    }
+}
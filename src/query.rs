@@ -0,0 +1,336 @@
+//! Filter expressions that select part of a graph.
+//!
+//! An expression is a list of predicates joined by `AND`, `OR` and `NOT`, with
+//! parentheses for grouping, e.g. `type:Person AND degree>=3` or
+//! `(label~"^Alice" OR community:2) AND NOT confidence<0.5`. Keywords are
+//! case-insensitive, `AND` binds tighter than `OR`, and predicates side by side
+//! without a keyword are joined by `AND`.
+//!
+//! | Predicate                  | Matches nodes                                                  |
+//! |----------------------------|----------------------------------------------------------------|
+//! | `type:Person`              | with this entity type or node type (`entity`, `concept`, ...)   |
+//! | `label:"Alice Smith"`      | with exactly this label, ignoring case                         |
+//! | `label~^Al`, `label~/A\|B/` | whose label matches the regex, ignoring case                   |
+//! | `confidence>=0.8`          | compared with `=`, `!=`, `>`, `>=`, `<` or `<=` (`:` is `=`)    |
+//! | `degree>=3`                | by their number of edges                                        |
+//! | `community:2`              | in this community (from `color_by: community`, else detected)  |
+//!
+//! Any other field is looked up in the node attributes. The matching subgraph keeps
+//! the matching nodes and the edges between them.
+
+use crate::conditions::{is_operator_char, Condition};
+use crate::config::NodeMetric;
+use crate::entity_extractor::entity_type_name;
+use crate::error::{GraphError, Result};
+use crate::graph_builder::{detect_communities, node_metric, GraphNode, InteractiveGraph};
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Predicate(Condition),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+/// A parsed filter expression, see the [module docs](self) for the syntax
+#[derive(Debug, Clone)]
+pub struct GraphFilter {
+    expression: String,
+    root: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Term(String),
+}
+
+fn invalid(expression: &str, reason: impl fmt::Display) -> GraphError {
+    GraphError::Configuration(format!("Invalid filter '{}': {}", expression, reason))
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        if c == '(' || c == ')' {
+            chars.next();
+            tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            continue;
+        }
+
+        let mut term = String::new();
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() || c == '(' || c == ')' {
+                break;
+            }
+            chars.next();
+            term.push(c);
+            // Quoted and /regex/ values run to their closing delimiter, spaces and parentheses included
+            let starts_value = term.chars().rev().nth(1).is_some_and(is_operator_char);
+            if c == '"' || c == '/' && starts_value {
+                let mut escaped = false;
+                let mut closed = false;
+                for next in chars.by_ref() {
+                    term.push(next);
+                    if next == c && !escaped {
+                        closed = true;
+                        break;
+                    }
+                    escaped = next == '\\' && !escaped;
+                }
+                if !closed {
+                    return Err(invalid(expression, format!("unterminated {}", c)));
+                }
+            }
+        }
+        tokens.push(match term.to_uppercase().as_str() {
+            "AND" | "&&" => Token::And,
+            "OR" | "||" => Token::Or,
+            "NOT" | "!" => Token::Not,
+            _ => Token::Term(term),
+        });
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    expression: &'a str,
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut left = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut left = self.not()?;
+        loop {
+            match self.peek() {
+                Some(Token::And) => {
+                    self.next();
+                }
+                Some(Token::Not | Token::Open | Token::Term(_)) => {}
+                _ => return Ok(left),
+            }
+            left = Expr::And(Box::new(left), Box::new(self.not()?));
+        }
+    }
+
+    fn not(&mut self) -> Result<Expr> {
+        if self.peek() == Some(&Token::Not) {
+            self.next();
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        match self.next() {
+            Some(Token::Open) => {
+                let inner = self.or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(inner),
+                    _ => Err(invalid(self.expression, "missing )")),
+                }
+            }
+            Some(Token::Term(term)) => {
+                Ok(Expr::Predicate(Condition::parse(&term).map_err(|reason| invalid(self.expression, reason))?))
+            }
+            Some(token) => Err(invalid(self.expression, format!("unexpected {:?}", token))),
+            None => Err(invalid(self.expression, "expected a predicate")),
+        }
+    }
+}
+
+impl FromStr for GraphFilter {
+    type Err = GraphError;
+
+    fn from_str(expression: &str) -> Result<Self> {
+        let mut parser = Parser { expression, tokens: tokenize(expression)?, position: 0 };
+        let root = parser.or()?;
+        if let Some(token) = parser.peek() {
+            return Err(invalid(expression, format!("unexpected {:?}", token)));
+        }
+        Ok(Self { expression: expression.trim().to_string(), root })
+    }
+}
+
+impl fmt::Display for GraphFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.expression)
+    }
+}
+
+/// What the predicates need to know about each node beyond the node itself
+struct NodeFacts {
+    degrees: Vec<f64>,
+    communities: Vec<usize>,
+}
+
+fn node_values(node: &GraphNode, field: &str, index: usize, facts: &NodeFacts) -> Vec<String> {
+    match field {
+        "type" => {
+            let mut values = vec![node.node_type.metadata_key().to_string()];
+            if let Some(stored) = &node.metadata.entity_type {
+                values.push(stored.clone());
+                // Custom types are stored as `Other("Medication")`; match them by name too
+                if entity_type_name(stored) != stored {
+                    values.push(entity_type_name(stored).to_string());
+                }
+            }
+            values
+        }
+        "label" => vec![node.label.clone()],
+        "confidence" => vec![node.metadata.confidence.to_string()],
+        "degree" => vec![facts.degrees[index].to_string()],
+        "community" => vec![node
+            .metadata
+            .attributes
+            .get("community")
+            .cloned()
+            .unwrap_or_else(|| facts.communities[index].to_string())],
+        _ => node.metadata.attributes.get(field).cloned().into_iter().collect(),
+    }
+}
+
+impl Expr {
+    fn matches(&self, node: &GraphNode, index: usize, facts: &NodeFacts) -> bool {
+        match self {
+            Expr::Predicate(condition) => condition.matches(&node_values(node, &condition.field, index, facts)),
+            Expr::Not(inner) => !inner.matches(node, index, facts),
+            Expr::And(left, right) => left.matches(node, index, facts) && right.matches(node, index, facts),
+            Expr::Or(left, right) => left.matches(node, index, facts) || right.matches(node, index, facts),
+        }
+    }
+}
+
+impl GraphFilter {
+    /// Whether each node of `graph` matches, in node order
+    pub fn matching_nodes(&self, graph: &InteractiveGraph) -> Vec<bool> {
+        let facts = NodeFacts {
+            degrees: node_metric(graph, NodeMetric::Degree, ""),
+            communities: detect_communities(graph),
+        };
+        graph
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| self.root.matches(node, index, &facts))
+            .collect()
+    }
+
+    /// The matching nodes and the edges between them; degrees and communities are
+    /// those of the whole graph
+    pub fn subgraph(&self, graph: &InteractiveGraph) -> InteractiveGraph {
+        let matching = self.matching_nodes(graph);
+        let mut subgraph = graph.clone();
+        subgraph.nodes = graph.nodes.iter().zip(matching).filter(|(_, keep)| *keep).map(|(node, _)| node.clone()).collect();
+        let kept: HashSet<&str> = subgraph.nodes.iter().map(|node| node.id.as_str()).collect();
+        subgraph.edges = graph
+            .edges
+            .iter()
+            .filter(|edge| kept.contains(edge.from.as_str()) && kept.contains(edge.to.as_str()))
+            .cloned()
+            .collect();
+        subgraph.refresh_metadata_counts();
+        subgraph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity_extractor::EntityExtractor;
+    use crate::graph_builder::GraphBuilder;
+    use crate::text_processor::{SourceType, TextProcessor};
+
+    async fn sample_graph() -> InteractiveGraph {
+        let text = "Alice Smith works at Initech in Berlin. Alice Smith met Bob Jones in Berlin. \
+                    Bob Jones called Carol White.";
+        let processed = TextProcessor::new().unwrap().process_text(text, SourceType::Document).unwrap();
+        let extraction = EntityExtractor::default().extract_from_text(&processed).await.unwrap();
+        GraphBuilder::default().build_graph(&extraction, text).unwrap()
+    }
+
+    fn labels(graph: &InteractiveGraph) -> Vec<&str> {
+        graph.nodes.iter().map(|node| node.label.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_filters_nodes_and_keeps_edges_between_them() {
+        let graph = sample_graph().await;
+        let degrees = node_metric(&graph, NodeMetric::Degree, "");
+
+        let people: GraphFilter = "type:Person".parse().unwrap();
+        let subgraph = people.subgraph(&graph);
+        assert!(!subgraph.nodes.is_empty());
+        assert!(subgraph.nodes.iter().all(|node| node.metadata.entity_type.as_deref() == Some("Person")));
+        assert_eq!(subgraph.metadata.total_nodes, subgraph.nodes.len());
+        let ids: HashSet<&str> = subgraph.nodes.iter().map(|node| node.id.as_str()).collect();
+        assert!(subgraph.edges.iter().all(|edge| ids.contains(edge.from.as_str()) && ids.contains(edge.to.as_str())));
+
+        let max_degree = degrees.iter().copied().fold(0.0, f64::max);
+        let hubs: GraphFilter = format!("degree>={}", max_degree).parse().unwrap();
+        let expected = degrees.iter().filter(|degree| **degree >= max_degree).count();
+        assert_eq!(hubs.subgraph(&graph).nodes.len(), expected);
+
+        let named: GraphFilter = r#"label~/^(alice|bob)/ AND NOT label:"Bob Jones""#.parse().unwrap();
+        assert_eq!(labels(&named.subgraph(&graph)), vec!["Alice Smith"]);
+
+        let either: GraphFilter = "(label:Berlin OR label~carol) and confidence>0".parse().unwrap();
+        let subgraph = either.subgraph(&graph);
+        let mut found = labels(&subgraph);
+        found.sort();
+        assert_eq!(found, vec!["Berlin", "Carol White"]);
+
+        let community: GraphFilter = "community:0".parse().unwrap();
+        assert!(community.matching_nodes(&graph)[0]);
+    }
+
+    #[test]
+    fn test_rejects_malformed_expressions() {
+        for expression in ["", "type:Person AND", "(type:Person", "degree", "label~(", "type:Person )", "label:\"open"] {
+            let error = expression.parse::<GraphFilter>().unwrap_err().to_string();
+            assert!(error.contains("Invalid filter"), "{}: {}", expression, error);
+        }
+        let filter: GraphFilter = " type:Person  degree>=2 ".parse().unwrap();
+        assert_eq!(filter.to_string(), "type:Person  degree>=2");
+    }
+
+    #[tokio::test]
+    async fn test_parses_values_ending_in_non_ascii_characters() {
+        for expression in ["label:José", "type:Café", "label~é", "label:\"Zoë Ångström\"", "city=Zürich", "名前:東京"] {
+            assert!(expression.parse::<GraphFilter>().is_ok(), "{}", expression);
+        }
+
+        let mut graph = sample_graph().await;
+        graph.nodes[0].label = "José".to_string();
+        let filter: GraphFilter = "label:JOSÉ".parse().unwrap();
+        assert_eq!(labels(&filter.subgraph(&graph)), vec!["José"]);
+    }
+}
@@ -0,0 +1,229 @@
+use crate::entity_extractor::{Entity, EntityType, RelationshipType};
+use crate::error::{GraphError, Result};
+use regex::Regex;
+use std::str::FromStr;
+
+/// Confidence of a relationship found by a rule, above the 0.6 of a bare verb match
+pub const RULE_CONFIDENCE: f64 = 0.75;
+
+/// Words a slot may span; the entity mention closest to the rule's text is chosen
+const MAX_SLOT_WORDS: usize = 6;
+
+/// A relationship rule (`extraction.relationship_rules`):
+///
+/// ```text
+/// {subject:person} reports to {object:person|organization} -> reports_to
+/// {object} was acquired by {subject:organization} -> acquired
+/// ```
+///
+/// The text between the slots is a case-insensitive regex. `{subject}` and `{object}`
+/// each bind to an entity mentioned in the slot, optionally restricted to the listed
+/// entity types, and the name after `->` is the relationship type and edge label.
+#[derive(Debug, Clone)]
+pub struct RelationshipRule {
+    regex: Regex,
+    subject_types: Vec<String>,
+    object_types: Vec<String>,
+    subject_first: bool,
+    relationship: String,
+    /// Only bind mentions that touch the rule's text rather than anywhere in the slot
+    adjacent: bool,
+}
+
+/// Entities bound by a rule in one sentence
+#[derive(Debug, Clone)]
+pub struct RuleMatch<'a> {
+    pub subject: &'a Entity,
+    pub object: &'a Entity,
+    /// Byte range of the whole match within the sentence
+    pub start: usize,
+    pub end: usize,
+}
+
+impl RelationshipRule {
+    /// The declared relationship type ("reports_to" -> `Other("reports_to")`, "has" -> `Has`)
+    pub fn relationship_type(&self) -> RelationshipType {
+        match self.relationship.to_lowercase().as_str() {
+            "has" => RelationshipType::Has,
+            "is_a" => RelationshipType::IsA,
+            "part_of" => RelationshipType::PartOf,
+            "connected_to" => RelationshipType::ConnectedTo,
+            "related_to" => RelationshipType::RelatedTo,
+            "contains" => RelationshipType::Contains,
+            "owns" => RelationshipType::Owns,
+            "uses" => RelationshipType::Uses,
+            "creates" => RelationshipType::Creates,
+            "influences" => RelationshipType::Influences,
+            _ => RelationshipType::Other(self.relationship.clone()),
+        }
+    }
+
+    /// Require each slot's entity to sit right next to the rule's text, so "bought
+    /// groceries near Oscar" does not make Oscar the object of "bought"
+    pub fn adjacent(mut self) -> Self {
+        self.adjacent = true;
+        self
+    }
+
+    /// Edge label: the relationship name with underscores as spaces
+    pub fn label(&self) -> String {
+        self.relationship.replace('_', " ")
+    }
+
+    /// Matches of this rule in `sentence` whose slots each hold a mention of an entity of
+    /// an allowed type. Mentions are located with `str::find`, as in pattern extraction.
+    pub fn find<'a>(&self, sentence: &str, entities: &[&'a Entity]) -> Vec<RuleMatch<'a>> {
+        let mentions: Vec<(&'a Entity, usize, usize)> = entities
+            .iter()
+            .filter_map(|entity| sentence.find(&entity.name).map(|pos| (*entity, pos, pos + entity.name.len())))
+            .collect();
+
+        self.regex
+            .captures_iter(sentence)
+            .filter_map(|captures| {
+                let whole = captures.get(0)?;
+                let subject = captures.name("subject")?;
+                let object = captures.name("object")?;
+                // The mention nearest the rule's text: the last one in a leading slot,
+                // the first one in a trailing slot
+                let bind = |slot: regex::Match, types: &[String], leading: bool| {
+                    let inside = mentions.iter().filter(|(entity, start, end)| {
+                        let touching = if leading { *end == slot.end() } else { *start == slot.start() };
+                        *start >= slot.start()
+                            && *end <= slot.end()
+                            && (touching || !self.adjacent)
+                            && type_allowed(&entity.entity_type, types)
+                    });
+                    if leading {
+                        inside.max_by_key(|(_, _, end)| *end).map(|(entity, _, _)| *entity)
+                    } else {
+                        inside.min_by_key(|(_, start, _)| *start).map(|(entity, _, _)| *entity)
+                    }
+                };
+                let subject = bind(subject, &self.subject_types, self.subject_first)?;
+                let object = bind(object, &self.object_types, !self.subject_first)?;
+                (subject.id != object.id).then_some(RuleMatch {
+                    subject,
+                    object,
+                    start: whole.start(),
+                    end: whole.end(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl FromStr for RelationshipRule {
+    type Err = GraphError;
+
+    fn from_str(rule: &str) -> Result<Self> {
+        let invalid = |reason: &str| GraphError::Configuration(format!("Invalid relationship rule '{}': {}", rule, reason));
+
+        let (pattern, relationship) = rule.rsplit_once("->").ok_or_else(|| invalid("expected '-> relationship_type'"))?;
+        let relationship = relationship.trim();
+        if relationship.is_empty() || !relationship.chars().all(|c| c.is_alphanumeric() || c == '_' || c == ' ') {
+            return Err(invalid("the relationship type must be a word such as works_at"));
+        }
+
+        let slot = Regex::new(r"\{\s*(subject|object)\s*(?::\s*([\w|\s]+))?\}").expect("valid slot regex");
+        let mut regex = String::from("(?i)");
+        let mut subject_types = None;
+        let mut object_types = None;
+        let mut subject_first = false;
+        let mut last = 0;
+        for captures in slot.captures_iter(pattern) {
+            let whole = captures.get(0).expect("group 0 always matches");
+            let role = &captures[1];
+            let types: Vec<String> = captures
+                .get(2)
+                .map(|types| types.as_str().split('|').map(|t| t.trim().to_lowercase()).filter(|t| !t.is_empty()).collect())
+                .unwrap_or_default();
+            let existing = if role == "subject" {
+                subject_first = object_types.is_none();
+                &mut subject_types
+            } else {
+                &mut object_types
+            };
+            if existing.replace(types).is_some() {
+                return Err(invalid(&format!("{{{}}} appears more than once", role)));
+            }
+
+            regex.push_str(&pattern[last..whole.start()]);
+            regex.push_str(&format!(r"(?P<{}>[^\s,;:]+(?:\s+[^\s,;:]+){{0,{}}})", role, MAX_SLOT_WORDS - 1));
+            last = whole.end();
+        }
+        regex.push_str(&pattern[last..]);
+
+        let (Some(subject_types), Some(object_types)) = (subject_types, object_types) else {
+            return Err(invalid("both {subject} and {object} slots are required"));
+        };
+        let regex = Regex::new(regex.trim()).map_err(|e| invalid(&e.to_string()))?;
+
+        Ok(Self {
+            regex,
+            subject_types,
+            object_types,
+            subject_first,
+            relationship: relationship.replace(' ', "_"),
+            adjacent: false,
+        })
+    }
+}
+
+/// Whether an entity of `entity_type` may fill a slot restricted to `types` (empty: any)
+fn type_allowed(entity_type: &EntityType, types: &[String]) -> bool {
+    let name = match entity_type {
+        EntityType::Other(name) => name.to_lowercase(),
+        other => format!("{:?}", other).to_lowercase(),
+    };
+    types.is_empty() || types.contains(&name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity_extractor::EntityType;
+
+    fn entity(name: &str, entity_type: EntityType) -> Entity {
+        Entity {
+            id: name.to_lowercase(),
+            name: name.to_string(),
+            entity_type,
+            attributes: Vec::new(),
+            confidence: 0.7,
+            position: None,
+            mentions: 1,
+            disambiguation: None,
+        }
+    }
+
+    #[test]
+    fn test_slots_bind_typed_entities_in_either_order() {
+        let bob = entity("Bob", EntityType::Person);
+        let alice = entity("Alice", EntityType::Person);
+        let acme = entity("Acme", EntityType::Organization);
+        let entities = [&bob, &alice, &acme];
+
+        let rule: RelationshipRule = "{subject:person} reports to {object:person|organization} -> reports_to".parse().unwrap();
+        let found = rule.find("Yesterday Bob reports to Alice at Acme.", &entities);
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].subject.name.as_str(), found[0].object.name.as_str()), ("Bob", "Alice"));
+        assert_eq!(rule.label(), "reports to");
+
+        let rule: RelationshipRule = "{object} (?:was|is) acquired by {subject:organization} -> acquired".parse().unwrap();
+        let found = rule.find("Bob was acquired by Acme", &entities);
+        assert_eq!((found[0].subject.name.as_str(), found[0].object.name.as_str()), ("Acme", "Bob"));
+        // The subject must be an organization
+        assert!(rule.find("Acme was acquired by Alice", &entities).is_empty());
+        assert!(matches!(rule.relationship_type(), RelationshipType::Other(name) if name == "acquired"));
+    }
+
+    #[test]
+    fn test_invalid_rules_are_rejected() {
+        assert!("{subject} owns {object}".parse::<RelationshipRule>().is_err());
+        assert!("{subject} owns it -> owns".parse::<RelationshipRule>().is_err());
+        assert!("{subject} and {subject} -> knows".parse::<RelationshipRule>().is_err());
+        assert!("{subject} (owns {object} -> owns".parse::<RelationshipRule>().is_err());
+        assert!(matches!("{subject} owns {object} -> owns".parse::<RelationshipRule>().unwrap().relationship_type(), RelationshipType::Owns));
+    }
+}
@@ -0,0 +1,109 @@
+use crate::config::SentenceSplittingConfig;
+use crate::sentence_splitter::SentenceSplitter;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// Settings for the plausibility check on pattern relationships
+/// (`extraction.relationship_validation`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RelationshipValidationConfig {
+    /// Score generic pattern matches and drop the implausible ones
+    pub enabled: bool,
+    /// Most words allowed between the two entity mentions
+    pub max_token_distance: usize,
+    /// Matches scoring below this (0..=1) are dropped
+    pub min_score: f64,
+}
+
+impl Default for RelationshipValidationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_token_distance: 12,
+            min_score: 0.5,
+        }
+    }
+}
+
+/// Scores how plausibly a relationship keyword found between two entity mentions
+/// actually links them.
+///
+/// A pair scores 0 when either mention is part of a longer word ("Al" in "Alice") or a
+/// sentence boundary lies between them. Otherwise the score falls linearly from 1 for
+/// adjacent mentions to 0.5 at `max_token_distance` words apart, and is 0 beyond that.
+#[derive(Debug, Clone)]
+pub struct RelationshipValidator {
+    config: RelationshipValidationConfig,
+    splitter: SentenceSplitter,
+}
+
+impl RelationshipValidator {
+    pub fn new(config: &RelationshipValidationConfig) -> Self {
+        Self {
+            config: config.clone(),
+            splitter: SentenceSplitter::new(&SentenceSplittingConfig::default()),
+        }
+    }
+
+    /// Plausibility (0..=1) of a relationship between the mentions at byte ranges
+    /// `first` and `second` of `sentence`
+    pub fn score(&self, sentence: &str, first: Range<usize>, second: Range<usize>) -> f64 {
+        let (first, second) = if first.start <= second.start { (first, second) } else { (second, first) };
+        if !is_whole_word(sentence, &first) || !is_whole_word(sentence, &second) || first.end > second.start {
+            return 0.0;
+        }
+        let between = &sentence[first.end..second.start];
+        if between.contains(['\n', '\r']) || self.splitter.sentence_ranges(between).len() > 1 {
+            return 0.0;
+        }
+        let distance = between.split_whitespace().filter(|word| word.chars().any(char::is_alphanumeric)).count();
+        if distance > self.config.max_token_distance {
+            return 0.0;
+        }
+        1.0 - 0.5 * distance as f64 / self.config.max_token_distance.max(1) as f64
+    }
+
+    /// Whether `score` clears `min_score`
+    pub fn accepts(&self, score: f64) -> bool {
+        score > 0.0 && score >= self.config.min_score
+    }
+}
+
+/// Whether the text at `range` is not glued to letters or digits on either side
+fn is_whole_word(sentence: &str, range: &Range<usize>) -> bool {
+    let before = sentence[..range.start].chars().next_back();
+    let after = sentence[range.end..].chars().next();
+    !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(sentence: &str, word: &str) -> Range<usize> {
+        let start = sentence.find(word).unwrap();
+        start..start + word.len()
+    }
+
+    #[test]
+    fn test_score_checks_words_boundaries_and_distance() {
+        let validator = RelationshipValidator::new(&RelationshipValidationConfig::default());
+        let score = |sentence: &str, first: &str, second: &str| validator.score(sentence, span(sentence, first), span(sentence, second));
+
+        assert_eq!(score("Alice works at TechCorp", "Alice", "TechCorp"), 1.0 - 0.5 * 2.0 / 12.0);
+        assert_eq!(score("Alice works at TechCorp", "TechCorp", "Alice"), score("Alice works at TechCorp", "Alice", "TechCorp"));
+        // "Al" is only the start of "Alice"
+        assert_eq!(score("Alice works at TechCorp", "Al", "TechCorp"), 0.0);
+        assert_eq!(score("Bob left. Alice joined TechCorp", "Bob", "TechCorp"), 0.0);
+        assert_eq!(score("Bob left\nAlice joined TechCorp", "Bob", "TechCorp"), 0.0);
+        // Abbreviations do not end a sentence
+        assert!(validator.accepts(score("Alice met Dr. Smith", "Alice", "Smith")));
+
+        let far = "Alice, who spent many long years in several distant cities before returning home, works at TechCorp";
+        assert_eq!(score(far, "Alice", "TechCorp"), 0.0);
+        assert!(!validator.accepts(score(far, "Alice", "TechCorp")));
+        assert!(validator.accepts(0.5));
+        assert!(!validator.accepts(0.49));
+    }
+}
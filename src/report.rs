@@ -0,0 +1,524 @@
+use crate::config::SentenceSplittingConfig;
+use crate::entity_extractor::entity_type_name;
+use crate::graph_builder::{GraphNode, InteractiveGraph, NodeType};
+use crate::sentence_splitter::SentenceSplitter;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+
+/// Attributes whose values (comma- or semicolon-separated) are other names of the entity
+const ALIAS_ATTRIBUTES: &[&str] = &["alias", "aliases", "aka", "also_known_as"];
+
+const PAGERANK_DAMPING: f64 = 0.85;
+const PAGERANK_ITERATIONS: usize = 50;
+
+/// An entity node with its place in the report's table of contents
+#[derive(Debug, Clone)]
+pub struct RankedEntity<'a> {
+    pub node: &'a GraphNode,
+    /// Edges touching the entity
+    pub degree: usize,
+    /// Degree centrality: `degree` over the number of other nodes
+    pub centrality: f64,
+    /// Share of [`pagerank`] over all nodes
+    pub pagerank: f64,
+    /// Id of the entity's section, unique within the report
+    pub anchor: String,
+}
+
+/// Entity nodes by degree centrality, most central first (ties by label)
+pub fn rank_entities(graph: &InteractiveGraph) -> Vec<RankedEntity<'_>> {
+    let mut degrees: HashMap<&str, usize> = HashMap::new();
+    for edge in &graph.edges {
+        *degrees.entry(edge.from.as_str()).or_insert(0) += 1;
+        *degrees.entry(edge.to.as_str()).or_insert(0) += 1;
+    }
+    let others = graph.nodes.len().saturating_sub(1).max(1) as f64;
+    let pageranks = pagerank(graph);
+
+    let mut ranked: Vec<RankedEntity> = graph
+        .nodes
+        .iter()
+        .filter(|node| node.node_type == NodeType::Entity)
+        .map(|node| {
+            let degree = degrees.get(node.id.as_str()).copied().unwrap_or(0);
+            RankedEntity {
+                node,
+                degree,
+                centrality: degree as f64 / others,
+                pagerank: pageranks.get(node.id.as_str()).copied().unwrap_or(0.0),
+                anchor: String::new(),
+            }
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.degree.cmp(&a.degree).then_with(|| a.node.label.cmp(&b.node.label)));
+
+    let mut used = HashSet::new();
+    for entity in &mut ranked {
+        let base = format!("entity-{}", slug(&entity.node.label));
+        let mut anchor = base.clone();
+        let mut suffix = 2;
+        while !used.insert(anchor.clone()) {
+            anchor = format!("{}-{}", base, suffix);
+            suffix += 1;
+        }
+        entity.anchor = anchor;
+    }
+    ranked
+}
+
+/// PageRank of every node with the edges taken as undirected, summing to 1. Nodes without
+/// edges spread their rank evenly, so isolated nodes get the smallest scores.
+pub fn pagerank(graph: &InteractiveGraph) -> HashMap<&str, f64> {
+    let n = graph.nodes.len();
+    let index: HashMap<&str, usize> = graph.nodes.iter().enumerate().map(|(i, node)| (node.id.as_str(), i)).collect();
+    let mut neighbours: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for edge in &graph.edges {
+        if let (Some(&from), Some(&to)) = (index.get(edge.from.as_str()), index.get(edge.to.as_str())) {
+            if from != to {
+                neighbours[from].push(to);
+                neighbours[to].push(from);
+            }
+        }
+    }
+
+    let mut rank = vec![1.0 / n.max(1) as f64; n];
+    for _ in 0..PAGERANK_ITERATIONS {
+        let dangling: f64 = (0..n).filter(|&i| neighbours[i].is_empty()).map(|i| rank[i]).sum();
+        let mut next = vec![(1.0 - PAGERANK_DAMPING + PAGERANK_DAMPING * dangling) / n as f64; n];
+        for (i, list) in neighbours.iter().enumerate().filter(|(_, list)| !list.is_empty()) {
+            let share = PAGERANK_DAMPING * rank[i] / list.len() as f64;
+            for &j in list {
+                next[j] += share;
+            }
+        }
+        rank = next;
+    }
+    graph.nodes.iter().map(|node| node.id.as_str()).zip(rank).collect()
+}
+
+/// One line of an entity's ego section: the edge label, which way it points, the
+/// neighbour's label and anchor (when the neighbour has a section), and the confidence
+struct Neighbour<'a> {
+    label: &'a str,
+    outgoing: bool,
+    name: &'a str,
+    anchor: Option<&'a str>,
+    confidence: f64,
+}
+
+/// The entities that get a section, and for each its neighbours and the sentences of the
+/// source text mentioning it
+struct Report<'a> {
+    graph: &'a InteractiveGraph,
+    sections: Vec<RankedEntity<'a>>,
+    toc_size: usize,
+    sentences: Vec<&'a str>,
+}
+
+impl<'a> Report<'a> {
+    fn new(graph: &'a InteractiveGraph, source_text: Option<&'a str>) -> Self {
+        let mut sections = rank_entities(graph);
+        if let Some(max) = graph.config.report.max_sections {
+            sections.truncate(max);
+        }
+        let sentences = match source_text {
+            Some(text) => SentenceSplitter::new(&SentenceSplittingConfig::default())
+                .sentence_ranges(text)
+                .into_iter()
+                .map(|range| text[range].trim())
+                .collect(),
+            None => Vec::new(),
+        };
+        Self { graph, sections, toc_size: graph.config.report.toc_size, sentences }
+    }
+
+    /// The `report.max_relationships` most confident relationships, and how many were left out
+    fn top_neighbours(&self, entity: &RankedEntity<'a>) -> (Vec<Neighbour<'_>>, usize) {
+        let mut neighbours = self.neighbours(entity);
+        neighbours.sort_by(|a, b| b.confidence.total_cmp(&a.confidence).then_with(|| a.name.cmp(b.name)));
+        let hidden = neighbours.len().saturating_sub(self.graph.config.report.max_relationships);
+        neighbours.truncate(self.graph.config.report.max_relationships);
+        (neighbours, hidden)
+    }
+
+    /// Up to `report.max_sentences` sentences naming the entity or one of its aliases
+    fn supporting_sentences(&self, entity: &RankedEntity<'a>) -> Vec<&'a str> {
+        if self.sentences.is_empty() {
+            return Vec::new();
+        }
+        let names: Vec<String> =
+            std::iter::once(entity.node.label.clone()).chain(aliases(entity.node)).map(|name| regex::escape(&name)).collect();
+        let Ok(mention) = Regex::new(&format!(r"(?i)(?:^|\W)(?:{})(?:\W|$)", names.join("|"))) else {
+            return Vec::new();
+        };
+        self.sentences
+            .iter()
+            .filter(|sentence| mention.is_match(sentence))
+            .take(self.graph.config.report.max_sentences)
+            .copied()
+            .collect()
+    }
+
+    fn neighbours(&self, entity: &RankedEntity<'a>) -> Vec<Neighbour<'_>> {
+        let nodes: HashMap<&str, &GraphNode> = self.graph.nodes.iter().map(|node| (node.id.as_str(), node)).collect();
+        let anchors: HashMap<&str, &str> =
+            self.sections.iter().map(|section| (section.node.id.as_str(), section.anchor.as_str())).collect();
+        self.graph
+            .edges
+            .iter()
+            .filter_map(|edge| {
+                let (other, outgoing) = if edge.from == entity.node.id {
+                    (edge.to.as_str(), true)
+                } else if edge.to == entity.node.id {
+                    (edge.from.as_str(), false)
+                } else {
+                    return None;
+                };
+                let node = nodes.get(other)?;
+                Some(Neighbour {
+                    label: edge.label.as_str(),
+                    outgoing,
+                    name: node.label.as_str(),
+                    anchor: anchors.get(other).copied(),
+                    confidence: edge.metadata.confidence,
+                })
+            })
+            .collect()
+    }
+
+    fn attributes(entity: &RankedEntity<'a>) -> Vec<(&'a String, &'a String)> {
+        let mut attributes: Vec<_> = entity
+            .node
+            .metadata
+            .attributes
+            .iter()
+            .filter(|(name, _)| name.as_str() != "name" && !ALIAS_ATTRIBUTES.contains(&name.to_lowercase().as_str()))
+            .collect();
+        attributes.sort();
+        attributes
+    }
+}
+
+/// Markdown report: a ranked table of contents of the `report.toc_size` most central
+/// entities linking into one dossier per entity with its aliases, attributes, centrality,
+/// top relationships and, given the `source_text`, the sentences mentioning it
+pub fn write_markdown_report<W: Write>(graph: &InteractiveGraph, source_text: Option<&str>, w: &mut W) -> std::io::Result<()> {
+    let report = Report::new(graph, source_text);
+    writeln!(w, "# Graph Report")?;
+    writeln!(w)?;
+    writeln!(
+        w,
+        "Generated {}. {} nodes, {} edges, {} entities.",
+        graph.metadata.creation_timestamp,
+        graph.nodes.len(),
+        graph.edges.len(),
+        report.sections.len()
+    )?;
+    writeln!(w)?;
+
+    writeln!(w, "## Top Entities")?;
+    writeln!(w)?;
+    writeln!(w, "| # | Entity | Type | Connections | Centrality | PageRank |")?;
+    writeln!(w, "|---|--------|------|-------------|------------|----------|")?;
+    for (rank, entity) in report.sections.iter().take(report.toc_size).enumerate() {
+        writeln!(
+            w,
+            "| {} | [{}](#{}) | {} | {} | {:.2} | {:.3} |",
+            rank + 1,
+            escape_markdown(&entity.node.label),
+            entity.anchor,
+            escape_markdown(&entity_type(entity.node)),
+            entity.degree,
+            entity.centrality,
+            entity.pagerank
+        )?;
+    }
+    writeln!(w)?;
+
+    writeln!(w, "## Entities")?;
+    for entity in &report.sections {
+        writeln!(w)?;
+        writeln!(w, "<a id=\"{}\"></a>", entity.anchor)?;
+        writeln!(w)?;
+        writeln!(w, "### {}", escape_markdown(&entity.node.label))?;
+        writeln!(w)?;
+        writeln!(w, "- **Type:** {}", escape_markdown(&entity_type(entity.node)))?;
+        let aliases = aliases(entity.node);
+        if !aliases.is_empty() {
+            let aliases: Vec<String> = aliases.iter().map(|alias| escape_markdown(alias)).collect();
+            writeln!(w, "- **Aliases:** {}", aliases.join(", "))?;
+        }
+        writeln!(w, "- **Confidence:** {:.2}", entity.node.metadata.confidence)?;
+        writeln!(
+            w,
+            "- **Centrality:** {:.2} ({} connections), PageRank {:.3}",
+            entity.centrality, entity.degree, entity.pagerank
+        )?;
+        for (name, value) in Report::attributes(entity) {
+            writeln!(w, "- **{}:** {}", escape_markdown(name), escape_markdown(value))?;
+        }
+
+        let (neighbours, hidden) = report.top_neighbours(entity);
+        if !neighbours.is_empty() {
+            writeln!(w)?;
+            writeln!(w, "**Top relationships**")?;
+            writeln!(w)?;
+            for neighbour in neighbours {
+                let name = match neighbour.anchor {
+                    Some(anchor) => format!("[{}](#{})", escape_markdown(neighbour.name), anchor),
+                    None => escape_markdown(neighbour.name),
+                };
+                let arrow = if neighbour.outgoing { "→" } else { "←" };
+                writeln!(w, "- {} {} {} ({:.2})", escape_markdown(neighbour.label), arrow, name, neighbour.confidence)?;
+            }
+            if hidden > 0 {
+                writeln!(w, "- … and {} more", hidden)?;
+            }
+        }
+
+        let sentences = report.supporting_sentences(entity);
+        if !sentences.is_empty() {
+            writeln!(w)?;
+            writeln!(w, "**Supporting sentences**")?;
+            for sentence in sentences {
+                writeln!(w)?;
+                writeln!(w, "> {}", escape_markdown(sentence))?;
+            }
+        }
+        writeln!(w)?;
+        writeln!(w, "[Back to top](#top-entities)")?;
+    }
+    Ok(())
+}
+
+/// Standalone HTML version of [`write_markdown_report`]
+pub fn write_html_report<W: Write>(graph: &InteractiveGraph, source_text: Option<&str>, w: &mut W) -> std::io::Result<()> {
+    let report = Report::new(graph, source_text);
+    writeln!(w, "<!DOCTYPE html>")?;
+    writeln!(w, "<html lang=\"en\">")?;
+    writeln!(w, "<head>")?;
+    writeln!(w, "<meta charset=\"utf-8\">")?;
+    writeln!(w, "<title>Graph Report</title>")?;
+    writeln!(
+        w,
+        "<style>body{{font-family:sans-serif;max-width:60rem;margin:2rem auto;padding:0 1rem}}\
+         table{{border-collapse:collapse}}th,td{{border:1px solid #ccc;padding:.3rem .6rem;text-align:left}}\
+         section{{border-top:1px solid #eee;margin-top:1.5rem}}</style>"
+    )?;
+    writeln!(w, "</head>")?;
+    writeln!(w, "<body>")?;
+    writeln!(w, "<h1>Graph Report</h1>")?;
+    writeln!(
+        w,
+        "<p>Generated {}. {} nodes, {} edges, {} entities.</p>",
+        escape_html(&graph.metadata.creation_timestamp),
+        graph.nodes.len(),
+        graph.edges.len(),
+        report.sections.len()
+    )?;
+
+    writeln!(w, "<h2 id=\"top-entities\">Top Entities</h2>")?;
+    writeln!(w, "<table>")?;
+    writeln!(w, "<tr><th>#</th><th>Entity</th><th>Type</th><th>Connections</th><th>Centrality</th><th>PageRank</th></tr>")?;
+    for (rank, entity) in report.sections.iter().take(report.toc_size).enumerate() {
+        writeln!(
+            w,
+            "<tr><td>{}</td><td><a href=\"#{}\">{}</a></td><td>{}</td><td>{}</td><td>{:.2}</td><td>{:.3}</td></tr>",
+            rank + 1,
+            entity.anchor,
+            escape_html(&entity.node.label),
+            escape_html(&entity_type(entity.node)),
+            entity.degree,
+            entity.centrality,
+            entity.pagerank
+        )?;
+    }
+    writeln!(w, "</table>")?;
+
+    writeln!(w, "<h2>Entities</h2>")?;
+    for entity in &report.sections {
+        writeln!(w, "<section id=\"{}\">", entity.anchor)?;
+        writeln!(w, "<h3>{}</h3>", escape_html(&entity.node.label))?;
+        writeln!(w, "<ul>")?;
+        writeln!(w, "<li><strong>Type:</strong> {}</li>", escape_html(&entity_type(entity.node)))?;
+        let aliases = aliases(entity.node);
+        if !aliases.is_empty() {
+            let aliases: Vec<String> = aliases.iter().map(|alias| escape_html(alias)).collect();
+            writeln!(w, "<li><strong>Aliases:</strong> {}</li>", aliases.join(", "))?;
+        }
+        writeln!(w, "<li><strong>Confidence:</strong> {:.2}</li>", entity.node.metadata.confidence)?;
+        writeln!(
+            w,
+            "<li><strong>Centrality:</strong> {:.2} ({} connections), PageRank {:.3}</li>",
+            entity.centrality, entity.degree, entity.pagerank
+        )?;
+        for (name, value) in Report::attributes(entity) {
+            writeln!(w, "<li><strong>{}:</strong> {}</li>", escape_html(name), escape_html(value))?;
+        }
+        writeln!(w, "</ul>")?;
+
+        let (neighbours, hidden) = report.top_neighbours(entity);
+        if !neighbours.is_empty() {
+            writeln!(w, "<h4>Top relationships</h4>")?;
+            writeln!(w, "<ul>")?;
+            for neighbour in neighbours {
+                let name = match neighbour.anchor {
+                    Some(anchor) => format!("<a href=\"#{}\">{}</a>", anchor, escape_html(neighbour.name)),
+                    None => escape_html(neighbour.name),
+                };
+                let arrow = if neighbour.outgoing { "&rarr;" } else { "&larr;" };
+                writeln!(w, "<li>{} {} {} ({:.2})</li>", escape_html(neighbour.label), arrow, name, neighbour.confidence)?;
+            }
+            if hidden > 0 {
+                writeln!(w, "<li>&hellip; and {} more</li>", hidden)?;
+            }
+            writeln!(w, "</ul>")?;
+        }
+
+        let sentences = report.supporting_sentences(entity);
+        if !sentences.is_empty() {
+            writeln!(w, "<h4>Supporting sentences</h4>")?;
+            for sentence in sentences {
+                writeln!(w, "<blockquote>{}</blockquote>", escape_html(sentence))?;
+            }
+        }
+        writeln!(w, "<p><a href=\"#top-entities\">Back to top</a></p>")?;
+        writeln!(w, "</section>")?;
+    }
+    writeln!(w, "</body>")?;
+    writeln!(w, "</html>")?;
+    Ok(())
+}
+
+/// Other names of an entity: the text it was extracted as when that differs from its label,
+/// and the values of its alias attributes, each once ignoring case
+fn aliases(node: &GraphNode) -> Vec<String> {
+    let mut seen: HashSet<String> = HashSet::from([node.label.to_lowercase()]);
+    let mut attributes: Vec<(&String, &String)> = node
+        .metadata
+        .attributes
+        .iter()
+        .filter(|(name, _)| ALIAS_ATTRIBUTES.contains(&name.to_lowercase().as_str()))
+        .collect();
+    attributes.sort();
+    std::iter::once(node.metadata.original_text.as_str())
+        .chain(attributes.into_iter().flat_map(|(_, value)| value.split([',', ';'])))
+        .map(str::trim)
+        .filter(|alias| !alias.is_empty() && seen.insert(alias.to_lowercase()))
+        .map(str::to_string)
+        .collect()
+}
+
+/// "Other(\"product\")" -> "product"; entity nodes store the Debug form of their type
+fn entity_type(node: &GraphNode) -> String {
+    entity_type_name(node.metadata.entity_type.as_deref().unwrap_or("entity")).to_string()
+}
+
+/// Lowercase letters and digits, other runs of characters as one `-`
+fn slug(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() { "unnamed".to_string() } else { slug.to_string() }
+}
+
+/// Keep labels from breaking tables and links
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '|' | '[' | ']' | '*' | '_' | '`' | '<' | '>' | '#') {
+            escaped.push('\\');
+        }
+        escaped.push(if c == '\n' { ' ' } else { c });
+    }
+    escaped
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::Pipeline;
+
+    #[tokio::test]
+    async fn test_reports_rank_entities_and_link_into_sections() {
+        let text = "Alice is connected to Bob. Alice has Carol. Bob is connected to Dave. Alice owns Erin.";
+        let mut graph = Pipeline::new().with_text(text).run().await.expect("Pipeline failed");
+        graph.config.report.toc_size = 2;
+
+        let ranked = rank_entities(&graph);
+        assert_eq!(ranked[0].node.label, "Alice");
+        assert_eq!(ranked[0].anchor, "entity-alice");
+        assert!(ranked.windows(2).all(|pair| pair[0].degree >= pair[1].degree));
+
+        let mut markdown = Vec::new();
+        write_markdown_report(&graph, None, &mut markdown).unwrap();
+        let markdown = String::from_utf8(markdown).unwrap();
+        let toc = &markdown[markdown.find("## Top Entities").unwrap()..markdown.find("## Entities").unwrap()];
+        assert!(toc.contains("| 1 | [Alice](#entity-alice) |"));
+        assert_eq!(toc.matches("](#entity-").count(), 2);
+        assert!(markdown.contains("<a id=\"entity-dave\"></a>"));
+        assert!(markdown.contains("[Bob](#entity-bob)"));
+
+        let mut html = Vec::new();
+        write_html_report(&graph, None, &mut html).unwrap();
+        let html = String::from_utf8(html).unwrap();
+        assert!(html.contains("<a href=\"#entity-alice\">Alice</a>"));
+        assert!(html.contains("<section id=\"entity-alice\">"));
+
+        assert_eq!(slug("Acme Corp. (EU)"), "acme-corp-eu");
+    }
+
+    #[tokio::test]
+    async fn test_dossier_lists_aliases_top_relationships_and_sentences() {
+        let text = "Alice is connected to Bob. Alice is connected to Carol. Ally owns Dave. Bob met Erin.";
+        let mut graph = Pipeline::new().with_text(text).run().await.expect("Pipeline failed");
+        graph.config.report.max_relationships = 1;
+        graph.config.report.max_sentences = 2;
+        let alice = graph.nodes.iter_mut().find(|node| node.label == "Alice").expect("Alice missing");
+        alice.metadata.attributes.insert("aliases".to_string(), "Ally; alice".to_string());
+        let carol = graph.nodes.iter().find(|node| node.label == "Carol").expect("Carol missing").id.clone();
+        let mut second = graph.edges.iter().find(|edge| edge.label == "is connected to").expect("Edge missing").clone();
+        second.to = carol;
+        second.metadata.confidence = 0.1;
+        graph.edges.push(second);
+
+        let pageranks = pagerank(&graph);
+        assert!((pageranks.values().sum::<f64>() - 1.0).abs() < 1e-9);
+        let ranked = rank_entities(&graph);
+        assert!(ranked[0].pagerank > ranked.last().unwrap().pagerank);
+
+        let mut markdown = Vec::new();
+        write_markdown_report(&graph, Some(text), &mut markdown).unwrap();
+        let markdown = String::from_utf8(markdown).unwrap();
+        let alice = &markdown[markdown.find("### Alice").unwrap()..];
+        let alice = &alice[..alice.find("[Back to top]").unwrap()];
+        assert!(alice.contains("- **Aliases:** Ally\n"));
+        assert!(!alice.contains("**aliases:**"));
+        assert!(alice.contains(", PageRank 0."));
+        assert_eq!(alice.matches(" → ").count() + alice.matches(" ← ").count(), 1);
+        assert!(alice.contains("- … and 1 more"));
+        // The alias finds the third sentence, and the cap stops before it
+        assert!(alice.contains("> Alice is connected to Bob\n\n> Alice is connected to Carol\n"));
+        assert!(!alice.contains("> Ally owns Dave"));
+
+        let mut without_text = Vec::new();
+        write_html_report(&graph, None, &mut without_text).unwrap();
+        let without_text = String::from_utf8(without_text).unwrap();
+        assert!(without_text.contains("<h4>Top relationships</h4>"));
+        assert!(!without_text.contains("Supporting sentences"));
+    }
+}
@@ -0,0 +1,394 @@
+use crate::config::RenderConfig;
+use crate::error::{GraphError, Result};
+use crate::graph_builder::InteractiveGraph;
+use serde_json::{json, Value};
+
+/// Version of the graph JSON layout written by this release.
+///
+/// | Version | Layout                                                                    |
+/// |---------|---------------------------------------------------------------------------|
+/// | 1       | No `schema_version`; `config` holds the full `GraphConfig`                 |
+/// | 2       | `schema_version`; `config` holds the rendering subset plus a `config_id`  |
+///
+/// Bump it whenever a change to [`InteractiveGraph`] can't be read by `#[serde(default)]`
+/// alone, and add the matching step to [`migrate_graph_value`].
+pub const GRAPH_SCHEMA_VERSION: u32 = 2;
+
+/// `schema_version` assumed for JSON written before the field existed
+pub fn legacy_schema_version() -> u32 {
+    1
+}
+
+/// Bring a graph JSON value from any earlier schema up to [`GRAPH_SCHEMA_VERSION`].
+///
+/// Besides full exports of older releases this accepts the node/edge-only JSON written
+/// without `--include-metadata`: the rendering config falls back to the defaults and the
+/// metadata counts are recomputed on load. JSON from a newer release is rejected rather
+/// than silently losing fields.
+pub fn migrate_graph_value(mut value: Value) -> Result<Value> {
+    let fields = value
+        .as_object_mut()
+        .ok_or_else(|| GraphError::Storage("Graph JSON must be an object".to_string()))?;
+    if !fields.contains_key("nodes") || !fields.contains_key("edges") {
+        return Err(GraphError::Storage("Graph JSON has no nodes and edges".to_string()));
+    }
+
+    let stored = match fields.get("schema_version") {
+        None => u64::from(legacy_schema_version()),
+        Some(version) => version
+            .as_u64()
+            .ok_or_else(|| GraphError::Storage(format!("Invalid schema_version {}", version)))?,
+    };
+    // Versions past u32 are unsupported too, rather than wrapped around to an older one
+    let version = u32::try_from(stored)
+        .ok()
+        .filter(|version| *version <= GRAPH_SCHEMA_VERSION)
+        .ok_or_else(|| {
+            GraphError::Storage(format!(
+                "Graph uses schema version {}, but this msg_net only reads up to version {}; upgrade msg_net",
+                stored, GRAPH_SCHEMA_VERSION
+            ))
+        })?;
+
+    if version < 2 {
+        // The full config is reduced to the rendering subset by RenderConfig's own
+        // deserializer; only the parts a simplified export lacks need filling in
+        if !fields.contains_key("config") {
+            fields.insert("config".to_string(), serde_json::to_value(RenderConfig::default())?);
+        }
+        fields.entry("metadata").or_insert_with(|| {
+            json!({
+                "total_nodes": 0,
+                "total_edges": 0,
+                "node_types": {},
+                "edge_types": {},
+                "creation_timestamp": "",
+                "source_text_length": 0,
+            })
+        });
+    }
+
+    fields.insert("schema_version".to_string(), json!(GRAPH_SCHEMA_VERSION));
+    Ok(value)
+}
+
+/// Parse graph JSON written by this or any earlier msg_net release
+pub fn graph_from_json(json: &str) -> Result<InteractiveGraph> {
+    let value: Value = serde_json::from_str(json)?;
+    let recount = value.get("metadata").is_none();
+    let mut graph: InteractiveGraph = serde_json::from_value(migrate_graph_value(value)?)?;
+    if recount {
+        graph.refresh_metadata_counts();
+    }
+    Ok(graph)
+}
+
+/// JSON Schema (draft 2020-12) of the graph JSON written by this release, printed by
+/// `msg_net schema`. It describes [`GRAPH_SCHEMA_VERSION`]; older files validate against
+/// it once [`migrate_graph_value`] has upgraded them. The `config` sections are left
+/// open since they follow the configuration file, documented separately.
+pub fn graph_json_schema() -> Value {
+    let number_or_null = json!({ "type": ["number", "null"] });
+    let string_or_null = json!({ "type": ["string", "null"] });
+    let counts = json!({ "type": "object", "additionalProperties": { "type": "integer", "minimum": 0 } });
+    let sentiment = json!({ "type": "number", "minimum": -1, "maximum": 1 });
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": format!("https://github.com/developmentAC/msg_net/schema/graph-v{}.json", GRAPH_SCHEMA_VERSION),
+        "title": "msg_net graph",
+        "description": "Knowledge graph exported by msg_net",
+        "type": "object",
+        "required": ["schema_version", "nodes", "edges", "config", "metadata"],
+        "properties": {
+            "schema_version": { "const": GRAPH_SCHEMA_VERSION },
+            "nodes": { "type": "array", "items": { "$ref": "#/$defs/node" } },
+            "edges": { "type": "array", "items": { "$ref": "#/$defs/edge" } },
+            "config": {
+                "description": "Rendering settings, laid out as the same sections of the configuration file",
+                "type": "object",
+                "required": ["config_id"],
+                "properties": {
+                    "config_id": { "type": "string" },
+                    "layout": {},
+                    "physics": {},
+                    "timestamps": {},
+                    "assets": {},
+                    "theme": {},
+                    "tikz": {},
+                    "mermaid": {},
+                    "report": {},
+                    "share": {},
+                    "chunks": {},
+                    "csv": {},
+                    "lod": {}
+                }
+            },
+            "metadata": { "$ref": "#/$defs/graph_metadata" }
+        },
+        "$defs": {
+            "node": {
+                "type": "object",
+                "required": ["id", "label", "node_type", "color", "shape", "size", "physics", "metadata"],
+                "properties": {
+                    "id": { "type": "string" },
+                    "label": { "type": "string" },
+                    "node_type": { "enum": ["Entity", "Concept", "Attribute", "Relationship"] },
+                    "color": { "type": "string" },
+                    "shape": { "type": "string" },
+                    "size": { "type": "number" },
+                    "x": number_or_null,
+                    "y": number_or_null,
+                    "physics": { "type": "boolean" },
+                    "metadata": { "$ref": "#/$defs/node_metadata" },
+                    "badge": { "type": "string" }
+                }
+            },
+            "node_metadata": {
+                "type": "object",
+                "required": ["confidence", "original_text", "attributes"],
+                "properties": {
+                    "confidence": { "type": "number" },
+                    "original_text": { "type": "string" },
+                    "entity_type": string_or_null,
+                    "attributes": { "type": "object", "additionalProperties": { "type": "string" } },
+                    "position_in_text": {
+                        "description": "Start and end byte offsets in the source text",
+                        "type": ["array", "null"],
+                        "prefixItems": [{ "type": "integer", "minimum": 0 }, { "type": "integer", "minimum": 0 }],
+                        "items": false
+                    },
+                    "sentiment": sentiment,
+                    "links": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "required": ["name", "url"],
+                            "properties": { "name": { "type": "string" }, "url": { "type": "string" } }
+                        }
+                    },
+                    "sources": { "type": "array", "items": { "type": "string" } },
+                    "disambiguation": {
+                        "type": "object",
+                        "required": ["sense", "senses", "sentences", "method"],
+                        "properties": {
+                            "sense": { "type": "string" },
+                            "senses": { "type": "integer", "minimum": 0 },
+                            "sentences": { "type": "array", "items": { "type": "integer", "minimum": 0 } },
+                            "cues": { "type": "array", "items": { "type": "string" } },
+                            "method": { "enum": ["context", "llm"] }
+                        }
+                    },
+                    "dates": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "required": ["date", "text", "sentence", "excerpt"],
+                            "properties": {
+                                "date": {
+                                    "description": "ISO 8601 day, month or year",
+                                    "type": "string",
+                                    "pattern": "^\\d{4}(-\\d{2}(-\\d{2})?)?$"
+                                },
+                                "text": { "type": "string" },
+                                "sentence": { "type": "integer", "minimum": 0 },
+                                "excerpt": { "type": "string" }
+                            }
+                        }
+                    }
+                }
+            },
+            "edge": {
+                "type": "object",
+                "required": ["id", "from", "to", "label", "color", "width", "arrows", "edge_type", "metadata"],
+                "properties": {
+                    "id": { "type": "string" },
+                    "from": { "type": "string", "description": "id of the source node" },
+                    "to": { "type": "string", "description": "id of the target node" },
+                    "label": { "type": "string" },
+                    "color": { "type": "string" },
+                    "width": { "type": "number" },
+                    "arrows": { "type": "string" },
+                    "edge_type": {
+                        "enum": ["EntityRelationship", "EntityAttribute", "ConceptEntity", "ConceptConcept", "Hierarchy"]
+                    },
+                    "metadata": { "$ref": "#/$defs/edge_metadata" },
+                    "dashes": { "type": "boolean" },
+                    "dash_pattern": { "type": "array", "items": { "type": "number" } },
+                    "opacity": { "type": "number", "minimum": 0, "maximum": 1 }
+                }
+            },
+            "edge_metadata": {
+                "type": "object",
+                "required": ["confidence", "relationship_type", "bidirectional", "weight"],
+                "properties": {
+                    "confidence": { "type": "number" },
+                    "relationship_type": { "type": "string" },
+                    "bidirectional": { "type": "boolean" },
+                    "weight": { "type": "number" },
+                    "sentiment": sentiment,
+                    "merged": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "required": ["label", "relationship_type", "confidence"],
+                            "properties": {
+                                "label": { "type": "string" },
+                                "relationship_type": { "type": "string" },
+                                "confidence": { "type": "number" },
+                                "reversed": { "type": "boolean" }
+                            }
+                        }
+                    },
+                    "category": { "type": "string" },
+                    "context": { "type": "string" }
+                }
+            },
+            "graph_metadata": {
+                "type": "object",
+                "required": ["total_nodes", "total_edges", "node_types", "edge_types", "creation_timestamp", "source_text_length"],
+                "properties": {
+                    "total_nodes": { "type": "integer", "minimum": 0 },
+                    "total_edges": { "type": "integer", "minimum": 0 },
+                    "node_types": counts,
+                    "edge_types": counts,
+                    "creation_timestamp": { "type": "string" },
+                    "source_text_length": { "type": "integer", "minimum": 0 },
+                    "provenance": {
+                        "type": "object",
+                        "properties": {
+                            "version": { "type": "string" },
+                            "extraction_method": { "type": "string" },
+                            "llm_model": string_or_null,
+                            "processing_time_ms": { "type": "integer", "minimum": 0 },
+                            "input_hash": { "type": "string" },
+                            "config_summary": { "type": "object", "additionalProperties": { "type": "string" } }
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GraphConfig;
+    use crate::pipeline::Pipeline;
+
+    async fn sample_graph() -> InteractiveGraph {
+        Pipeline::new()
+            .with_text("Alice works at TechCorp. Bob is connected to Alice.")
+            .run()
+            .await
+            .expect("Pipeline failed")
+    }
+
+    #[tokio::test]
+    async fn test_current_and_legacy_exports_load() {
+        let graph = sample_graph().await;
+        assert_eq!(graph.schema_version, GRAPH_SCHEMA_VERSION);
+        let current = serde_json::to_value(&graph).unwrap();
+        let loaded = graph_from_json(&current.to_string()).expect("Current export failed to load");
+        assert_eq!(loaded.nodes.len(), graph.nodes.len());
+
+        // Version 1: no schema_version, full GraphConfig, no badge/dashes fields
+        let mut legacy = current.clone();
+        let fields = legacy.as_object_mut().unwrap();
+        fields.remove("schema_version");
+        fields.insert("config".to_string(), serde_json::to_value(GraphConfig::default()).unwrap());
+        for edge in fields["edges"].as_array_mut().unwrap() {
+            edge.as_object_mut().unwrap().remove("dashes");
+        }
+        let loaded = graph_from_json(&legacy.to_string()).expect("Legacy export failed to load");
+        assert_eq!(loaded.schema_version, GRAPH_SCHEMA_VERSION);
+        assert_eq!(loaded.config.config_id, GraphConfig::default().config_id());
+        assert_eq!(loaded.edges.len(), graph.edges.len());
+    }
+
+    #[tokio::test]
+    async fn test_simplified_export_loads_and_newer_schema_is_rejected() {
+        let graph = sample_graph().await;
+        let simplified = json!({ "nodes": graph.nodes, "edges": graph.edges });
+        let loaded = graph_from_json(&simplified.to_string()).expect("Simplified export failed to load");
+        assert_eq!(loaded.metadata.total_nodes, graph.nodes.len());
+        assert_eq!(loaded.metadata.total_edges, graph.edges.len());
+
+        let mut future = serde_json::to_value(&graph).unwrap();
+        future["schema_version"] = json!(GRAPH_SCHEMA_VERSION + 1);
+        assert!(matches!(graph_from_json(&future.to_string()), Err(GraphError::Storage(_))));
+        // Would read as version 1 if cut down to 32 bits
+        future["schema_version"] = json!(u64::from(u32::MAX) + 2);
+        let error = graph_from_json(&future.to_string()).unwrap_err().to_string();
+        assert!(error.contains("schema version 4294967297"), "{}", error);
+        assert!(graph_from_json(r#"{"nodes": []}"#).is_err());
+    }
+
+    /// Keys of `value` missing from the schema object `schema`, recursing into nested
+    /// objects and arrays the schema describes, with JSON paths like `nodes[0].metadata.x`
+    fn undocumented_keys(value: &Value, schema: &Value, root: &Value, path: &str, missing: &mut Vec<String>) {
+        let schema = match schema.get("$ref").and_then(Value::as_str) {
+            Some(reference) => root.pointer(reference.trim_start_matches('#')).unwrap(),
+            None => schema,
+        };
+        match value {
+            Value::Object(fields) => {
+                let Some(properties) = schema.get("properties") else { return };
+                for required in schema["required"].as_array().into_iter().flatten() {
+                    if !fields.contains_key(required.as_str().unwrap()) {
+                        missing.push(format!("{}.{} (required, not written)", path, required));
+                    }
+                }
+                for (key, field) in fields {
+                    match properties.get(key) {
+                        Some(property) => undocumented_keys(field, property, root, &format!("{}.{}", path, key), missing),
+                        None => missing.push(format!("{}.{}", path, key)),
+                    }
+                }
+            }
+            Value::Array(items) => {
+                if let Some(item_schema) = schema.get("items").filter(|items| items.is_object()) {
+                    for (index, item) in items.iter().enumerate() {
+                        undocumented_keys(item, item_schema, root, &format!("{}[{}]", path, index), missing);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    #[tokio::test]
+    async fn test_schema_documents_every_field_written() {
+        let mut graph = Pipeline::new()
+            .with_text("Alice is connected to Bob. Bob works with Carol at TechCorp.")
+            .run()
+            .await
+            .expect("Pipeline failed");
+        // Fill in the fields only written when set
+        graph.nodes[0].badge = Some("★".to_string());
+        graph.nodes[0].metadata.sentiment = Some(0.5);
+        graph.nodes[0].metadata.sources = vec!["a.txt".to_string()];
+        graph.nodes[0].metadata.dates = vec![crate::timeline::DatedMention {
+            date: "2020-05".to_string(),
+            text: "May 2020".to_string(),
+            sentence: 0,
+            excerpt: "Alice is connected to Bob since May 2020.".to_string(),
+        }];
+        graph.edges[0].dash_pattern = vec![4.0, 2.0];
+        graph.edges[0].opacity = Some(0.5);
+        graph.edges[0].metadata.category = Some("temporal".to_string());
+        graph.edges[0].metadata.context = Some("Bob is connected to Alice".to_string());
+
+        let schema = graph_json_schema();
+        let mut value = serde_json::to_value(&graph).unwrap();
+        let mut missing = Vec::new();
+        undocumented_keys(&value, &schema, &schema, "", &mut missing);
+        assert!(missing.is_empty(), "Graph JSON fields missing from the schema: {:?}", missing);
+
+        value["edges"][0]["metadata"]["new_field"] = json!(1);
+        undocumented_keys(&value, &schema, &schema, "", &mut missing);
+        assert_eq!(missing, vec![".edges[0].metadata.new_field"]);
+        assert_eq!(schema["properties"]["schema_version"]["const"], json!(GRAPH_SCHEMA_VERSION));
+    }
+}
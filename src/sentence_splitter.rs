@@ -0,0 +1,162 @@
+use crate::config::{SentenceSplitMethod, SentenceSplittingConfig};
+use regex::Regex;
+use std::collections::HashSet;
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Abbreviations that never end a sentence, lowercase and without the final period
+pub const DEFAULT_ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "mt", "rev", "hon", "gen", "col", "capt", "lt", "sgt",
+    "gov", "sen", "rep", "pres", "vs", "no", "fig", "approx", "dept", "u.s", "u.k", "u.n", "e.g", "i.e",
+];
+
+/// Splits cleaned text into sentences (`text_processing.sentence_splitting`).
+///
+/// The `unicode` method follows the Unicode sentence boundary rules (UAX #29), which
+/// already keep "U.S. market" and "3.14" together, then rejoins boundaries that follow
+/// a listed abbreviation ("Dr. Smith") or a single-letter initial ("J. Smith"). Sentences
+/// lose their closing punctuation unless it belongs to an abbreviation.
+#[derive(Debug, Clone)]
+pub struct SentenceSplitter {
+    method: SentenceSplitMethod,
+    abbreviations: HashSet<String>,
+    punctuation_regex: Regex,
+}
+
+impl SentenceSplitter {
+    pub fn new(config: &SentenceSplittingConfig) -> Self {
+        let mut abbreviations: HashSet<String> = if config.default_abbreviations {
+            DEFAULT_ABBREVIATIONS.iter().map(|abbreviation| abbreviation.to_string()).collect()
+        } else {
+            HashSet::new()
+        };
+        abbreviations.extend(config.abbreviations.iter().map(|abbreviation| normalize(abbreviation)));
+
+        Self {
+            method: config.method,
+            abbreviations,
+            punctuation_regex: Regex::new(r"[.!?]+\s*").expect("valid sentence regex"),
+        }
+    }
+
+    pub fn split(&self, text: &str) -> Vec<String> {
+        self.sentence_ranges(text).into_iter().map(|range| text[range].to_string()).collect()
+    }
+
+    /// Byte ranges of the sentences [`split`](Self::split) returns, so callers can keep
+    /// slices of `text` instead of copies
+    pub fn sentence_ranges(&self, text: &str) -> Vec<Range<usize>> {
+        self.split_spans(text)
+            .into_iter()
+            .map(|(start, end)| match self.method {
+                SentenceSplitMethod::Punctuation => start..end,
+                SentenceSplitMethod::Unicode => start..start + self.strip_terminator(&text[start..end]).trim_end().len(),
+            })
+            .filter(|range| !range.is_empty())
+            .collect()
+    }
+
+    /// Byte ranges of the sentences in `text`, without surrounding whitespace. Unlike
+    /// [`split`](Self::split) the closing punctuation is kept (except by the
+    /// `punctuation` method, which never includes it).
+    pub fn split_spans(&self, text: &str) -> Vec<(usize, usize)> {
+        let spans = match self.method {
+            SentenceSplitMethod::Punctuation => {
+                let mut spans = Vec::new();
+                let mut start = 0;
+                for boundary in self.punctuation_regex.find_iter(text) {
+                    spans.push((start, boundary.start()));
+                    start = boundary.end();
+                }
+                spans.push((start, text.len()));
+                spans
+            }
+            SentenceSplitMethod::Unicode => self.unicode_spans(text),
+        };
+        spans
+            .into_iter()
+            .filter_map(|(start, end)| {
+                let sentence = &text[start..end];
+                let trimmed_start = start + (sentence.len() - sentence.trim_start().len());
+                let trimmed_end = start + sentence.trim_end().len();
+                (trimmed_start < trimmed_end).then_some((trimmed_start, trimmed_end))
+            })
+            .collect()
+    }
+
+    fn unicode_spans(&self, text: &str) -> Vec<(usize, usize)> {
+        let mut spans = Vec::new();
+        let mut start = 0;
+        for (offset, segment) in text.split_sentence_bound_indices() {
+            let end = offset + segment.len();
+            if !self.ends_with_abbreviation(text[start..end].trim_end()) {
+                spans.push((start, end));
+                start = end;
+            }
+        }
+        if start < text.len() {
+            spans.push((start, text.len()));
+        }
+        spans
+    }
+
+    /// Whether `text` ends in "Dr." or "J.", so the boundary after it is not a real one
+    fn ends_with_abbreviation(&self, text: &str) -> bool {
+        let Some(last_word) = text.split_whitespace().last() else {
+            return false;
+        };
+        let Some(word) = last_word.strip_suffix('.') else {
+            return false;
+        };
+        let word = word.trim_start_matches(|c: char| !c.is_alphanumeric());
+        let is_initial = word.chars().count() == 1 && word.chars().all(char::is_uppercase);
+        is_initial || self.abbreviations.contains(&normalize(word))
+    }
+
+    fn strip_terminator<'a>(&self, sentence: &'a str) -> &'a str {
+        if self.ends_with_abbreviation(sentence) {
+            sentence
+        } else {
+            sentence.trim_end_matches(['.', '!', '?'])
+        }
+    }
+}
+
+impl Default for SentenceSplitter {
+    fn default() -> Self {
+        Self::new(&SentenceSplittingConfig::default())
+    }
+}
+
+/// "U.S." and "u.s" both become "u.s"
+fn normalize(abbreviation: &str) -> String {
+    abbreviation.trim().trim_end_matches('.').to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_abbreviations_and_initials_do_not_end_sentences() {
+        let splitter = SentenceSplitter::default();
+        let sentences = splitter.split("Dr. Smith met J. Doe in the U.S. office. Pi is 3.14! Did Mrs. Jones agree? Yes.");
+        assert_eq!(
+            sentences,
+            vec!["Dr. Smith met J. Doe in the U.S. office", "Pi is 3.14", "Did Mrs. Jones agree", "Yes"]
+        );
+    }
+
+    #[test]
+    fn test_custom_abbreviations_and_punctuation_method() {
+        let config = SentenceSplittingConfig {
+            abbreviations: vec!["Approx.".to_string(), "Corp.".to_string()],
+            ..SentenceSplittingConfig::default()
+        };
+        let sentences = SentenceSplitter::new(&config).split("Initech Corp. Hired Alice. She left.");
+        assert_eq!(sentences, vec!["Initech Corp. Hired Alice", "She left"]);
+
+        let config = SentenceSplittingConfig { method: SentenceSplitMethod::Punctuation, ..SentenceSplittingConfig::default() };
+        assert_eq!(SentenceSplitter::new(&config).split("Dr. Smith left."), vec!["Dr", "Smith left"]);
+    }
+}
@@ -0,0 +1,236 @@
+use crate::graph_builder::{EdgeType, InteractiveGraph, NodeType};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Settings for the sentiment pass (`sentiment`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SentimentConfig {
+    /// Score entity nodes and relationship edges
+    pub enabled: bool,
+    /// Recolor relationship edges whose score is beyond `neutral_threshold`
+    pub color_edges: bool,
+    pub positive_color: String,
+    pub negative_color: String,
+    /// Scores within this distance of zero count as neutral
+    pub neutral_threshold: f64,
+}
+
+impl Default for SentimentConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            color_edges: true,
+            positive_color: "#2E7D32".to_string(),
+            negative_color: "#C62828".to_string(),
+            neutral_threshold: 0.05,
+        }
+    }
+}
+
+/// Word valences on VADER's -4..=4 scale
+const LEXICON: &[(&str, f64)] = &[
+    ("amazing", 2.8), ("awesome", 3.1), ("best", 3.2), ("better", 1.9), ("brilliant", 2.8),
+    ("calm", 1.3), ("clean", 1.7), ("comfortable", 1.5), ("delight", 2.9), ("delighted", 2.9),
+    ("easy", 1.9), ("effective", 2.1), ("enjoy", 2.2), ("enjoyed", 2.3), ("excellent", 2.7),
+    ("excited", 1.7), ("fantastic", 2.6), ("fast", 1.3), ("favorite", 2.0), ("fine", 0.8),
+    ("friendly", 2.2), ("glad", 2.0), ("good", 1.9), ("great", 3.1), ("happy", 2.7),
+    ("helpful", 1.8), ("impressed", 2.1), ("improve", 1.9), ("improved", 2.1), ("like", 1.5),
+    ("liked", 1.8), ("love", 3.2), ("loved", 2.9), ("nice", 1.8), ("perfect", 2.7),
+    ("pleasant", 2.3), ("pleased", 1.9), ("praise", 2.6), ("recommend", 1.5), ("reliable", 1.7),
+    ("resolved", 1.7), ("satisfied", 1.8), ("smooth", 1.3), ("success", 2.7), ("successful", 2.8),
+    ("support", 1.7), ("thank", 1.5), ("thanks", 1.9), ("trust", 2.3), ("useful", 1.9),
+    ("win", 2.8), ("wonderful", 2.7), ("worth", 0.9),
+    ("angry", -2.3), ("annoyed", -1.6), ("annoying", -1.7), ("awful", -2.0), ("bad", -2.5),
+    ("broken", -1.9), ("bug", -1.1), ("buggy", -1.6), ("complain", -1.7), ("complaint", -1.6),
+    ("confusing", -1.4), ("crash", -1.7), ("crashed", -1.8), ("delay", -1.3), ("delayed", -1.4),
+    ("difficult", -1.5), ("disappointed", -1.9), ("disappointing", -2.2), ("dislike", -1.6),
+    ("error", -1.4), ("fail", -2.3), ("failed", -2.3), ("failure", -2.3), ("frustrated", -2.0),
+    ("frustrating", -1.9), ("hate", -2.7), ("hated", -3.2), ("horrible", -2.5), ("issue", -0.7),
+    ("lost", -1.3), ("poor", -2.1), ("problem", -1.7), ("refund", -0.6), ("rude", -2.0),
+    ("sad", -2.1), ("slow", -1.1), ("terrible", -2.1), ("ugly", -2.3), ("unhappy", -1.8),
+    ("unreliable", -1.6), ("upset", -1.6), ("useless", -1.8), ("wasted", -2.2), ("worse", -2.1),
+    ("worst", -3.1), ("wrong", -2.1),
+];
+
+/// Words that strengthen (or, when negative, soften) the next sentiment word
+const BOOSTERS: &[(&str, f64)] = &[
+    ("absolutely", 0.293), ("completely", 0.293), ("extremely", 0.293), ("incredibly", 0.293),
+    ("really", 0.293), ("so", 0.293), ("totally", 0.293), ("very", 0.293),
+    ("barely", -0.293), ("slightly", -0.293), ("somewhat", -0.293), ("kind", -0.293),
+];
+
+const NEGATIONS: &[&str] = &["not", "no", "never", "none", "nothing", "neither", "nor", "without", "hardly"];
+
+/// VADER constants: negated valence scale, caps emphasis, exclamation boost, normalization alpha
+const NEGATION_SCALE: f64 = -0.74;
+const CAPS_INCREMENT: f64 = 0.733;
+const EXCLAMATION_INCREMENT: f64 = 0.292;
+const NORMALIZATION_ALPHA: f64 = 15.0;
+
+fn is_negation(word: &str) -> bool {
+    NEGATIONS.contains(&word) || word.ends_with("n't")
+}
+
+/// VADER-style compound score of one sentence, from -1 (most negative) to 1 (most positive).
+///
+/// Sums lexicon valences with the usual adjustments: boosters ("very good"), negation in
+/// the three preceding words ("not good"), ALL-CAPS emphasis in mixed-case text, "but"
+/// shifting weight to the clause after it, and exclamation marks.
+pub fn sentence_sentiment(sentence: &str) -> f64 {
+    let lexicon: HashMap<&str, f64> = LEXICON.iter().copied().collect();
+    let boosters: HashMap<&str, f64> = BOOSTERS.iter().copied().collect();
+
+    let tokens: Vec<&str> = sentence
+        .split_whitespace()
+        .map(|token| token.trim_matches(|c: char| !c.is_alphanumeric() && c != '\''))
+        .filter(|token| !token.is_empty())
+        .collect();
+    let lower: Vec<String> = tokens.iter().map(|token| token.to_lowercase()).collect();
+    let mixed_case = tokens.iter().any(|t| t.chars().any(char::is_lowercase))
+        && tokens.iter().any(|t| t.len() > 1 && t.chars().all(|c| !c.is_alphabetic() || c.is_uppercase()));
+    let but_index = lower.iter().position(|word| word == "but");
+
+    let mut valences = Vec::new();
+    for (index, word) in lower.iter().enumerate() {
+        let Some(&base) = lexicon.get(word.as_str()) else {
+            continue;
+        };
+        let mut valence = base;
+        if mixed_case && tokens[index].chars().all(|c| c.is_uppercase()) {
+            valence += CAPS_INCREMENT * valence.signum();
+        }
+        for previous in lower[index.saturating_sub(3)..index].iter() {
+            if let Some(&boost) = boosters.get(previous.as_str()) {
+                valence += boost * valence.signum();
+            }
+        }
+        if lower[index.saturating_sub(3)..index].iter().any(|previous| is_negation(previous)) {
+            valence *= NEGATION_SCALE;
+        }
+        match but_index {
+            Some(but) if index < but => valence *= 0.5,
+            Some(but) if index > but => valence *= 1.5,
+            _ => {}
+        }
+        valences.push(valence);
+    }
+    if valences.is_empty() {
+        return 0.0;
+    }
+
+    let mut sum: f64 = valences.iter().sum();
+    let exclamations = sentence.matches('!').count().min(3) as f64;
+    sum += exclamations * EXCLAMATION_INCREMENT * sum.signum();
+    (sum / (sum * sum + NORMALIZATION_ALPHA).sqrt()).clamp(-1.0, 1.0)
+}
+
+/// Score entity nodes and relationship edges, storing the result in their `sentiment`
+/// metadata.
+///
+/// An entity's score is the mean over the sentences mentioning it; an edge's is the mean
+/// over the sentences mentioning both of its endpoints. Items never mentioned keep no
+/// score. With `color_edges`, clearly positive and negative edges are recolored.
+pub fn apply_sentiment(graph: &mut InteractiveGraph, source_text: &str, config: &SentimentConfig) {
+    let sentence_regex = Regex::new(r"[.!?]+\s+|\n\s*\n").expect("valid sentence regex");
+    let sentences: Vec<(String, f64)> = split_keeping_punctuation(&sentence_regex, source_text)
+        .into_iter()
+        .map(|sentence| (sentence.to_lowercase(), sentence_sentiment(&sentence)))
+        .collect();
+
+    let mention_regex = |label: &str| Regex::new(&format!(r"\b{}\b", regex::escape(&label.to_lowercase()))).ok();
+    let mut mentions: HashMap<String, Vec<usize>> = HashMap::new();
+    for node in graph.nodes.iter_mut().filter(|node| node.node_type == NodeType::Entity) {
+        let Some(regex) = mention_regex(&node.label) else {
+            continue;
+        };
+        let indices: Vec<usize> = sentences
+            .iter()
+            .enumerate()
+            .filter(|(_, (sentence, _))| regex.is_match(sentence))
+            .map(|(index, _)| index)
+            .collect();
+        node.metadata.sentiment = mean(indices.iter().map(|&index| sentences[index].1));
+        mentions.insert(node.id.clone(), indices);
+    }
+
+    for edge in graph.edges.iter_mut().filter(|edge| edge.edge_type == EdgeType::EntityRelationship) {
+        let (Some(from), Some(to)) = (mentions.get(&edge.from), mentions.get(&edge.to)) else {
+            continue;
+        };
+        edge.metadata.sentiment = mean(from.iter().filter(|index| to.contains(index)).map(|&index| sentences[index].1));
+        match edge.metadata.sentiment {
+            Some(score) if config.color_edges && score >= config.neutral_threshold => {
+                edge.color = config.positive_color.clone()
+            }
+            Some(score) if config.color_edges && score <= -config.neutral_threshold => {
+                edge.color = config.negative_color.clone()
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Split on `regex`, keeping the terminating punctuation (exclamation marks affect the score)
+fn split_keeping_punctuation(regex: &Regex, text: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    for separator in regex.find_iter(text) {
+        sentences.push(text[start..separator.end()].trim().to_string());
+        start = separator.end();
+    }
+    sentences.push(text[start..].trim().to_string());
+    sentences.retain(|sentence| !sentence.is_empty());
+    sentences
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0usize), |(sum, count), value| (sum + value, count + 1));
+    (count > 0).then(|| sum / count as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sentence_sentiment_handles_negation_boosters_and_but() {
+        assert!(sentence_sentiment("The support team was great.") > 0.5);
+        assert!(sentence_sentiment("The support team was not great.") < 0.0);
+        assert!(sentence_sentiment("The app is very good.") > sentence_sentiment("The app is good."));
+        assert!(sentence_sentiment("The app is GOOD!") > sentence_sentiment("The app is good."));
+        assert!(sentence_sentiment("The food was good, but the service was terrible.") < 0.0);
+        assert_eq!(sentence_sentiment("Alice works at TechCorp."), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_graph_entities_and_edges_get_scores_and_colors() {
+        let mut config = crate::config::GraphConfig::default();
+        config.sentiment.enabled = true;
+        let graph = crate::pipeline::Pipeline::new()
+            .with_config(config.clone())
+            .with_text(
+                "Alice is connected to Bob and the partnership is excellent. \
+                 Carol is connected to Dave, sadly a terrible and frustrating one.",
+            )
+            .run()
+            .await
+            .expect("Pipeline failed");
+
+        let node = |label: &str| graph.nodes.iter().find(|node| node.label == label).expect(label);
+        assert!(node("Alice").metadata.sentiment.unwrap() > 0.0);
+        assert!(node("Dave").metadata.sentiment.unwrap() < 0.0);
+        let edge = |from: &str| {
+            let id = &node(from).id;
+            graph
+                .edges
+                .iter()
+                .find(|edge| edge.edge_type == EdgeType::EntityRelationship && (&edge.from == id || &edge.to == id))
+                .expect(from)
+        };
+        assert_eq!(edge("Alice").color, config.sentiment.positive_color);
+        assert_eq!(edge("Carol").color, config.sentiment.negative_color);
+        assert!(edge("Carol").metadata.sentiment.unwrap() < 0.0);
+    }
+}
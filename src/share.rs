@@ -0,0 +1,142 @@
+//! Small graphs shared as a single link.
+//!
+//! The graph is reduced to what the viewer draws (labels, types, colors, shapes, sizes,
+//! confidences and the physics settings), deflated and base64url-encoded into the
+//! `#g=` fragment of a link to the data-less viewer page. Fragments never reach the
+//! server, so a static copy of the viewer on any host can open every link, and the
+//! graph stays between the people who have the link.
+
+use crate::error::{GraphError, Result};
+use crate::graph_builder::InteractiveGraph;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+/// Fragment key the viewer reads the graph from, as in `msg_net_viewer.html#g=...`
+pub const SHARE_FRAGMENT_KEY: &str = "g";
+
+/// Version of the payload layout, checked by the viewer
+pub const SHARE_PAYLOAD_VERSION: u64 = 1;
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// The compact form of the graph put in share links: nodes as
+/// `[label, node_type, color, shape, size, confidence]` and edges as
+/// `[from, to, label, relationship_type, color, width, confidence, dashes, arrows]`, with nodes
+/// referred to by index instead of their long ids
+pub fn share_payload(graph: &InteractiveGraph) -> Value {
+    let index: HashMap<&str, usize> = graph.nodes.iter().enumerate().map(|(i, node)| (node.id.as_str(), i)).collect();
+    let nodes: Vec<Value> = graph
+        .nodes
+        .iter()
+        .map(|node| {
+            json!([node.label, node.node_type, node.color, node.shape, round2(node.size), round2(node.metadata.confidence)])
+        })
+        .collect();
+    let edges: Vec<Value> = graph
+        .edges
+        .iter()
+        .filter_map(|edge| Some((index.get(edge.from.as_str())?, index.get(edge.to.as_str())?, edge)))
+        .map(|(from, to, edge)| {
+            json!([
+                from,
+                to,
+                edge.label,
+                edge.metadata.relationship_type,
+                edge.color,
+                round2(edge.width),
+                round2(edge.metadata.confidence),
+                edge.dashes,
+                edge.arrows
+            ])
+        })
+        .collect();
+    json!({"v": SHARE_PAYLOAD_VERSION, "p": graph.config.physics, "n": nodes, "e": edges})
+}
+
+/// The graph deflated and base64url-encoded, without padding
+pub fn encode_share_fragment(graph: &InteractiveGraph) -> Result<String> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(serde_json::to_string(&share_payload(graph))?.as_bytes())?;
+    Ok(URL_SAFE_NO_PAD.encode(encoder.finish()?))
+}
+
+/// The payload in a share link, a bare fragment or `g=...`
+pub fn decode_share_fragment(link: &str) -> Result<Value> {
+    let fragment = link.rsplit_once('#').map_or(link, |(_, fragment)| fragment);
+    let encoded = fragment.strip_prefix(&format!("{}=", SHARE_FRAGMENT_KEY)).unwrap_or(fragment).trim();
+    let compressed = URL_SAFE_NO_PAD
+        .decode(encoded)
+        .map_err(|e| GraphError::Export(format!("Share link is not valid base64url: {}", e)))?;
+    let mut json = String::new();
+    DeflateDecoder::new(compressed.as_slice())
+        .read_to_string(&mut json)
+        .map_err(|e| GraphError::Export(format!("Share link does not hold a compressed graph: {}", e)))?;
+    let payload: Value = serde_json::from_str(&json)?;
+    match payload.get("v").and_then(Value::as_u64) {
+        Some(SHARE_PAYLOAD_VERSION) => Ok(payload),
+        version => Err(GraphError::Export(format!("Unsupported share link version: {:?}", version))),
+    }
+}
+
+/// Link to `graph.config.share.viewer_url` with the graph in its fragment. Fails for
+/// graphs whose link would be longer than `graph.config.share.max_url_length`.
+pub fn share_url(graph: &InteractiveGraph) -> Result<String> {
+    let share = &graph.config.share;
+    let url = format!("{}#{}={}", share.viewer_url, SHARE_FRAGMENT_KEY, encode_share_fragment(graph)?);
+    if url.len() > share.max_url_length {
+        return Err(GraphError::Export(format!(
+            "A share link for {} nodes and {} edges would be {} characters, over the {} allowed \
+             (share.max_url_length); filter the graph first or share an html or json export",
+            graph.nodes.len(),
+            graph.edges.len(),
+            url.len(),
+            share.max_url_length
+        )));
+    }
+    Ok(url)
+}
+
+/// Whether `viewer_url` points at a page to write next to the link rather than a hosted one
+pub fn is_local_viewer(viewer_url: &str) -> bool {
+    !viewer_url.contains("://") && !viewer_url.starts_with('/')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity_extractor::EntityExtractor;
+    use crate::graph_builder::GraphBuilder;
+    use crate::text_processor::{SourceType, TextProcessor};
+
+    #[tokio::test]
+    async fn test_share_link_round_trips_and_respects_the_limit() {
+        let text = "Alice Smith works at Initech. Alice Smith met Bob Jones in Berlin.";
+        let processed = TextProcessor::new().unwrap().process_text(text, SourceType::Document).unwrap();
+        let extraction = EntityExtractor::default().extract_from_text(&processed).await.unwrap();
+        let mut graph = GraphBuilder::default().build_graph(&extraction, text).unwrap();
+
+        let link = share_url(&graph).unwrap();
+        let fragment = link.strip_prefix("msg_net_viewer.html#g=").unwrap();
+        assert!(fragment.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+
+        let payload = decode_share_fragment(&link).unwrap();
+        let nodes = payload["n"].as_array().unwrap();
+        assert_eq!(nodes.len(), graph.nodes.len());
+        assert_eq!(nodes[0][0], graph.nodes[0].label);
+        assert_eq!(payload["e"].as_array().unwrap().len(), graph.edges.len());
+        assert_eq!(payload["p"]["spring_length"], graph.config.physics.spring_length);
+
+        graph.config.share.max_url_length = 20;
+        assert!(share_url(&graph).unwrap_err().to_string().contains("max_url_length"));
+        assert!(decode_share_fragment("g=not*base64").is_err());
+        assert!(is_local_viewer("viewer.html") && !is_local_viewer("https://example.com/viewer.html"));
+    }
+}
@@ -0,0 +1,222 @@
+use crate::presets::Preset;
+use crate::text_processor::SourceType;
+use regex::Regex;
+use std::borrow::Cow;
+
+/// How many leading lines the detector looks at; enough to see headers and a few
+/// chat messages without scanning a whole book
+const DETECTION_LINES: usize = 50;
+
+/// Headers that carry names worth extracting; other header lines are dropped
+const KEPT_EMAIL_HEADERS: &[&str] = &["from", "to", "cc", "subject"];
+
+fn regex(pattern: &str) -> Regex {
+    Regex::new(pattern).expect("valid source format regex")
+}
+
+fn email_header_regex() -> Regex {
+    regex(r"^([A-Za-z][A-Za-z-]*):\s")
+}
+
+fn chat_timestamp_regex() -> Regex {
+    // [12:30], 12:30 PM -, [2024-05-01 09:15:02], 5/1/24, 9:15 -
+    regex(
+        r"^\s*\[?(?:\d{4}-\d{2}-\d{2}[ T]\d{1,2}:\d{2}(?::\d{2})?|\d{1,2}/\d{1,2}/\d{2,4},?\s+\d{1,2}:\d{2}(?::\d{2})?|\d{1,2}:\d{2}(?::\d{2})?)(?:\s*[AaPp][Mm])?\]?\s*[-–:]?\s*",
+    )
+}
+
+impl SourceType {
+    /// Guess the source type from the text: email headers at the top, HTML tags,
+    /// timestamped chat lines or markdown syntax, in that order. Anything else is a
+    /// `Document`.
+    pub fn detect(text: &str) -> SourceType {
+        let lines: Vec<&str> = text.lines().take(DETECTION_LINES).collect();
+        let content: Vec<&str> = lines.iter().copied().filter(|line| !line.trim().is_empty()).collect();
+        if content.is_empty() {
+            return SourceType::Document;
+        }
+
+        if looks_like_email(&lines) {
+            return SourceType::Email;
+        }
+
+        let sample = lines.join("\n");
+        let document_tag = regex(r"(?i)<!doctype html|<html[\s>]|<body[\s>]");
+        let tag = regex(r"(?i)</?(?:p|div|span|a|h[1-6]|ul|ol|li|br|table|tr|td|em|strong|b|i)\b[^>]*>");
+        if document_tag.is_match(&sample) || tag.find_iter(&sample).count() >= 3 {
+            return SourceType::Html;
+        }
+
+        let timestamp = chat_timestamp_regex();
+        let messages = content.iter().filter(|line| timestamp.is_match(line)).count();
+        if messages >= 2 && messages * 2 >= content.len() {
+            return SourceType::ChatMessage;
+        }
+
+        let strong = regex(r"^#{1,6}\s|^```|!?\[[^\]]+\]\([^)]+\)|\*\*[^*]+\*\*");
+        let list_item = regex(r"^\s*(?:[-*+]|\d+\.)\s+\S");
+        let strong_signals = content.iter().filter(|line| strong.is_match(line)).count();
+        let list_items = content.iter().filter(|line| list_item.is_match(line)).count();
+        if strong_signals >= 2 || (strong_signals >= 1 && list_items >= 2) {
+            return SourceType::Markdown;
+        }
+
+        SourceType::Document
+    }
+
+    /// The built-in preset suited to this source type, used when neither `--config` nor
+    /// `--preset` is given
+    pub fn preset(&self) -> Option<Preset> {
+        match self {
+            SourceType::ChatMessage => Some(Preset::ChatForensics),
+            _ => None,
+        }
+    }
+
+    /// `text` with the markup of this source type removed, so tags, header fields,
+    /// timestamps and markdown syntax do not turn into entities. Lines that end a block
+    /// (a heading, a list item, a chat message) get a closing period so they stay
+    /// separate sentences once whitespace is collapsed. Plain documents are borrowed.
+    pub fn prepare<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        match self {
+            SourceType::Email => Cow::Owned(prepare_email(text)),
+            SourceType::Html => Cow::Owned(prepare_html(text)),
+            SourceType::Markdown => Cow::Owned(prepare_markdown(text)),
+            SourceType::ChatMessage => Cow::Owned(prepare_chat(text)),
+            _ => Cow::Borrowed(text),
+        }
+    }
+}
+
+/// At least two header lines before the first blank line, one of them `From:` or `Subject:`
+fn looks_like_email(lines: &[&str]) -> bool {
+    let header = email_header_regex();
+    let names: Vec<String> = lines
+        .iter()
+        .skip_while(|line| line.trim().is_empty())
+        .take_while(|line| !line.trim().is_empty())
+        .filter_map(|line| header.captures(line).map(|captures| captures[1].to_lowercase()))
+        .collect();
+    names.len() >= 2 && names.iter().any(|name| name == "from" || name == "subject")
+}
+
+/// Add a period unless the line already ends a sentence
+pub(crate) fn end_sentence(line: &str) -> String {
+    let line = line.trim();
+    if line.ends_with(['.', '!', '?', ':']) {
+        line.to_string()
+    } else {
+        format!("{}.", line)
+    }
+}
+
+fn block_lines(lines: impl Iterator<Item = String>) -> String {
+    lines.filter(|line| !line.trim().is_empty()).map(|line| end_sentence(&line)).collect::<Vec<_>>().join("\n")
+}
+
+fn prepare_email(text: &str) -> String {
+    let header = email_header_regex();
+    let mut lines = text.lines().skip_while(|line| line.trim().is_empty()).peekable();
+    let mut kept = Vec::new();
+    while let Some(line) = lines.next_if(|line| !line.trim().is_empty()) {
+        // Continuation lines of folded headers start with whitespace and are dropped with them
+        if let Some(captures) = header.captures(line) {
+            if KEPT_EMAIL_HEADERS.contains(&captures[1].to_lowercase().as_str()) {
+                kept.push(end_sentence(line));
+            }
+        }
+    }
+    kept.extend(lines.map(str::to_string));
+    kept.join("\n")
+}
+
+fn prepare_html(text: &str) -> String {
+    let without_code = regex(r"(?is)<(script|style)\b[^>]*>.*?</(?:script|style)>|<!--.*?-->").replace_all(text, " ");
+    let with_breaks = regex(r"(?i)<br\s*/?>|</?(?:p|div|h[1-6]|li|tr|section|article|header|footer|blockquote|title)\b[^>]*>")
+        .replace_all(&without_code, "\n");
+    let without_tags = regex(r"<[^>]*>").replace_all(&with_breaks, "");
+    let decoded = without_tags
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&");
+    block_lines(decoded.lines().map(str::to_string))
+}
+
+fn prepare_markdown(text: &str) -> String {
+    let fence = regex(r"^\s*(?:```|~~~)");
+    let prefix = regex(r"^\s*(?:#{1,6}\s+|>\s*|[-*+]\s+|\d+\.\s+)+");
+    let rule = regex(r"^\s*(?:[-*_]\s*){3,}$");
+    let link = regex(r"!?\[([^\]]*)\]\([^)]*\)");
+    let emphasis = regex(r"\*\*([^*]+)\*\*|__([^_]+)__|\*([^*\s][^*]*)\*|`([^`]+)`");
+    let trailing_hashes = regex(r"\s+#+\s*$");
+
+    let mut in_code = false;
+    let lines = text.lines().filter_map(|line| {
+        if fence.is_match(line) {
+            in_code = !in_code;
+            return None;
+        }
+        if in_code || rule.is_match(line) {
+            return None;
+        }
+        let line = prefix.replace(line, "");
+        let line = trailing_hashes.replace(&line, "");
+        let line = link.replace_all(&line, "$1");
+        let line = emphasis.replace_all(&line, "$1$2$3$4");
+        Some(line.into_owned())
+    });
+    block_lines(lines)
+}
+
+fn prepare_chat(text: &str) -> String {
+    let timestamp = chat_timestamp_regex();
+    block_lines(text.lines().map(|line| timestamp.replace(line, "").into_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_each_source_type() {
+        let email = "From: Alice Smith <alice@example.com>\nTo: Bob <bob@example.com>\nDate: Mon, 1 Jan 2024\n\nHi Bob,\nthe server is down.";
+        let html = "<html><body><h1>Report</h1><p>Alice met <b>Bob</b>.</p></body></html>";
+        let chat = "[09:15] Alice: hey\n[09:16] Bob: paid the invoice\n[09:20] Alice: thx";
+        let whatsapp = "5/1/24, 9:15 PM - Alice: hey\n5/1/24, 9:16 PM - Bob: sent it";
+        let markdown = "# Release notes\n\n- Alice fixed the parser\n- Bob added **exports**\n\nSee [the docs](https://example.com).";
+        let document = "Alice works at TechCorp. Bob manages the database system.\n\n1. Not a list of markdown.";
+
+        assert!(matches!(SourceType::detect(email), SourceType::Email));
+        assert!(matches!(SourceType::detect(html), SourceType::Html));
+        assert!(matches!(SourceType::detect(chat), SourceType::ChatMessage));
+        assert!(matches!(SourceType::detect(whatsapp), SourceType::ChatMessage));
+        assert!(matches!(SourceType::detect(markdown), SourceType::Markdown));
+        assert!(matches!(SourceType::detect(document), SourceType::Document));
+        assert!(matches!(SourceType::detect(""), SourceType::Document));
+        assert_eq!(SourceType::detect(chat).preset(), Some(Preset::ChatForensics));
+    }
+
+    #[test]
+    fn test_prepare_strips_markup_and_keeps_sentences_apart() {
+        let email = "From: Alice Smith <alice@example.com>\nMessage-ID: <1@x>\nSubject: Outage\n\nBob restarted the server.";
+        assert_eq!(
+            SourceType::Email.prepare(email),
+            "From: Alice Smith <alice@example.com>.\nSubject: Outage.\n\nBob restarted the server."
+        );
+
+        let html = "<html><head><style>p { color: red }</style></head><body><h1>Report</h1><p>Alice &amp; <b>Bob</b> met.</p></body></html>";
+        assert_eq!(SourceType::Html.prepare(html), "Report.\nAlice & Bob met.");
+
+        let markdown = "## Team\n\n- **Alice** leads [Initech](https://initech.com)\n\n```\nlet x = 1;\n```\nDone.";
+        assert_eq!(SourceType::Markdown.prepare(markdown), "Team.\nAlice leads Initech.\nDone.");
+
+        let chat = "[2024-05-01 09:15] Alice: hey\n[2024-05-01 09:16] Bob: paid it!";
+        assert_eq!(SourceType::ChatMessage.prepare(chat), "Alice: hey.\nBob: paid it!");
+
+        assert!(matches!(SourceType::Document.prepare("As is"), Cow::Borrowed("As is")));
+    }
+}
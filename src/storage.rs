@@ -0,0 +1,239 @@
+use crate::entity_extractor::ExtractionResult;
+use crate::error::{GraphError, Result};
+use crate::graph_builder::{InteractiveGraph, NodeType};
+use crate::schema::graph_from_json;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id              INTEGER PRIMARY KEY AUTOINCREMENT,
+    name            TEXT NOT NULL,
+    source          TEXT,
+    saved_at        TEXT NOT NULL,
+    node_count      INTEGER NOT NULL,
+    edge_count      INTEGER NOT NULL,
+    graph_json      TEXT NOT NULL,
+    extraction_json TEXT
+);
+CREATE TABLE IF NOT EXISTS run_entities (
+    run_id    INTEGER NOT NULL REFERENCES runs(id) ON DELETE CASCADE,
+    node_id   TEXT NOT NULL,
+    label     TEXT NOT NULL,
+    node_type TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_run_entities_label ON run_entities(label COLLATE NOCASE);
+";
+
+/// Descriptive information stored alongside a saved graph
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunInfo {
+    /// Human-readable name for the run (e.g. the input file name)
+    pub name: String,
+    /// Where the text came from, if known
+    pub source: Option<String>,
+}
+
+/// Summary of a saved run, as returned by [`GraphStore::list_runs`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub id: i64,
+    pub name: String,
+    pub source: Option<String>,
+    pub saved_at: String,
+    pub node_count: usize,
+    pub edge_count: usize,
+    pub has_extraction: bool,
+}
+
+/// A node whose label matched [`GraphStore::find_entity`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityMatch {
+    pub run_id: i64,
+    pub run_name: String,
+    pub node_id: String,
+    pub label: String,
+    pub node_type: String,
+}
+
+/// SQLite-backed store of generated graphs and their extraction results.
+///
+/// Each saved run keeps the full graph (and optionally the extraction result) as JSON,
+/// plus an indexed table of node labels so entities can be looked up across runs.
+pub struct GraphStore {
+    conn: Connection,
+}
+
+impl GraphStore {
+    /// Open (or create) a database file
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    /// Open a throwaway database that lives only as long as the store
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// Save a graph (and optionally the extraction it was built from), returning the run id
+    pub fn save_run(
+        &mut self,
+        graph: &InteractiveGraph,
+        extraction: Option<&ExtractionResult>,
+        info: &RunInfo,
+    ) -> Result<i64> {
+        let graph_json = serde_json::to_string(graph)?;
+        let extraction_json = extraction.map(serde_json::to_string).transpose()?;
+
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO runs (name, source, saved_at, node_count, edge_count, graph_json, extraction_json)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                info.name,
+                info.source,
+                crate::timestamps::timestamp_now(&graph.config.timestamps)?,
+                graph.nodes.len() as i64,
+                graph.edges.len() as i64,
+                graph_json,
+                extraction_json,
+            ],
+        )?;
+        let run_id = tx.last_insert_rowid();
+
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO run_entities (run_id, node_id, label, node_type) VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for node in graph.nodes.iter().filter(|n| !matches!(n.node_type, NodeType::Attribute)) {
+                insert.execute(params![run_id, node.id, node.label, format!("{:?}", node.node_type)])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(run_id)
+    }
+
+    /// Load the graph saved under `run_id`
+    pub fn load_graph(&self, run_id: i64) -> Result<InteractiveGraph> {
+        let graph_json: Option<String> = self
+            .conn
+            .query_row("SELECT graph_json FROM runs WHERE id = ?1", [run_id], |row| row.get(0))
+            .optional()?;
+        let graph_json = graph_json
+            .ok_or_else(|| GraphError::Storage(format!("No saved run with id {}", run_id)))?;
+        // Runs saved by older releases are migrated on the way out
+        graph_from_json(&graph_json)
+    }
+
+    /// Load the extraction result saved with `run_id`, if one was stored
+    pub fn load_extraction(&self, run_id: i64) -> Result<Option<ExtractionResult>> {
+        let extraction_json: Option<Option<String>> = self
+            .conn
+            .query_row("SELECT extraction_json FROM runs WHERE id = ?1", [run_id], |row| row.get(0))
+            .optional()?;
+        match extraction_json {
+            None => Err(GraphError::Storage(format!("No saved run with id {}", run_id))),
+            Some(None) => Ok(None),
+            Some(Some(json)) => Ok(Some(serde_json::from_str(&json)?)),
+        }
+    }
+
+    /// All saved runs, newest first
+    pub fn list_runs(&self) -> Result<Vec<RunRecord>> {
+        let mut statement = self.conn.prepare(
+            "SELECT id, name, source, saved_at, node_count, edge_count, extraction_json IS NOT NULL
+             FROM runs ORDER BY id DESC",
+        )?;
+        let runs = statement
+            .query_map([], |row| {
+                Ok(RunRecord {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    source: row.get(2)?,
+                    saved_at: row.get(3)?,
+                    node_count: row.get::<_, i64>(4)? as usize,
+                    edge_count: row.get::<_, i64>(5)? as usize,
+                    has_extraction: row.get(6)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(runs)
+    }
+
+    /// Nodes whose label contains `name` (case-insensitive), across all runs
+    pub fn find_entity(&self, name: &str) -> Result<Vec<EntityMatch>> {
+        let pattern = format!("%{}%", name.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"));
+        let mut statement = self.conn.prepare(
+            "SELECT e.run_id, r.name, e.node_id, e.label, e.node_type
+             FROM run_entities e JOIN runs r ON r.id = e.run_id
+             WHERE e.label LIKE ?1 ESCAPE '\\'
+             ORDER BY e.run_id DESC, e.label",
+        )?;
+        let matches = statement
+            .query_map([pattern], |row| {
+                Ok(EntityMatch {
+                    run_id: row.get(0)?,
+                    run_name: row.get(1)?,
+                    node_id: row.get(2)?,
+                    label: row.get(3)?,
+                    node_type: row.get(4)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(matches)
+    }
+
+    /// Delete a saved run; returns false if it didn't exist
+    pub fn delete_run(&self, run_id: i64) -> Result<bool> {
+        Ok(self.conn.execute("DELETE FROM runs WHERE id = ?1", [run_id])? > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::Pipeline;
+
+    #[tokio::test]
+    async fn test_save_load_and_find_across_runs() {
+        let output = Pipeline::new()
+            .with_text("Alice works at TechCorp. Bob is connected to Alice.")
+            .run_detailed()
+            .await
+            .expect("Pipeline failed");
+        let mut store = GraphStore::open_in_memory().expect("Failed to open database");
+
+        let info = RunInfo { name: "first".to_string(), source: Some("notes.txt".to_string()) };
+        let first = store.save_run(&output.graph, Some(&output.extraction), &info).expect("Failed to save");
+        let second = store
+            .save_run(&output.graph, None, &RunInfo { name: "second".to_string(), source: None })
+            .expect("Failed to save");
+
+        let loaded = store.load_graph(first).expect("Failed to load graph");
+        assert_eq!(loaded.nodes.len(), output.graph.nodes.len());
+        assert!(store.load_extraction(first).unwrap().is_some());
+        assert!(store.load_extraction(second).unwrap().is_none());
+
+        let runs = store.list_runs().expect("Failed to list runs");
+        assert_eq!(runs.iter().map(|r| r.id).collect::<Vec<_>>(), vec![second, first]);
+
+        let matches = store.find_entity("alice").expect("Failed to search");
+        assert_eq!(matches.len(), 2);
+        assert!(matches.iter().all(|m| m.label == "Alice"));
+    }
+
+    #[test]
+    fn test_missing_run_is_an_error() {
+        let store = GraphStore::open_in_memory().expect("Failed to open database");
+        assert!(matches!(store.load_graph(42), Err(GraphError::Storage(_))));
+        assert!(!store.delete_run(42).unwrap());
+    }
+}
@@ -0,0 +1,283 @@
+//! Bounded-memory generation for very large inputs: the text is read and extracted one
+//! chunk at a time, and each chunk's nodes and edges are written to a JSON Lines stream
+//! as soon as its graph is built, instead of combining everything and exporting at the end.
+
+use crate::config::{GraphConfig, RenderConfig};
+use crate::error::{GraphError, Result};
+use crate::graph_builder::{GraphEdge, GraphMetadata, GraphNode, InteractiveGraph, NodeType};
+use crate::pipeline::Pipeline;
+use crate::schema::GRAPH_SCHEMA_VERSION;
+use crate::text_processor::SourceType;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, Write};
+use tracing::info;
+
+/// Settings for `generate --bounded-memory`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StreamingConfig {
+    /// Bytes of input extracted at a time. A chunk ends at the first line break past
+    /// this size, so a single line longer than this is never split.
+    pub chunk_bytes: usize,
+}
+
+impl Default for StreamingConfig {
+    fn default() -> Self {
+        Self { chunk_bytes: 32 * 1024 }
+    }
+}
+
+/// One line of a JSON Lines graph export: a `graph` header, a `node` or `edge` line per
+/// node and edge, and a closing `metadata` line with the totals
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GraphLine<'a> {
+    Graph {
+        schema_version: u32,
+        config: &'a RenderConfig,
+    },
+    Node(&'a GraphNode),
+    Edge(&'a GraphEdge),
+    Metadata(&'a GraphMetadata),
+}
+
+impl GraphLine<'_> {
+    /// Write the line as compact JSON followed by a newline
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        serde_json::to_writer(&mut *writer, self)?;
+        writer.write_all(b"\n")
+    }
+}
+
+/// The lines of a JSON Lines export of a complete graph, in output order
+pub fn json_lines(graph: &InteractiveGraph) -> impl Iterator<Item = GraphLine<'_>> {
+    std::iter::once(GraphLine::Graph {
+        schema_version: graph.schema_version,
+        config: &graph.config,
+    })
+    .chain(graph.nodes.iter().map(GraphLine::Node))
+    .chain(graph.edges.iter().map(GraphLine::Edge))
+    .chain(std::iter::once(GraphLine::Metadata(&graph.metadata)))
+}
+
+/// Splits the text read from a `BufRead` into chunks of about `chunk_bytes`, ending each
+/// at a line break so sentences are rarely cut. The chunks concatenate to the input.
+pub struct TextChunks<R> {
+    reader: R,
+    chunk_bytes: usize,
+}
+
+impl<R: BufRead> TextChunks<R> {
+    pub fn new(reader: R, chunk_bytes: usize) -> Self {
+        Self {
+            reader,
+            chunk_bytes: chunk_bytes.max(1),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for TextChunks<R> {
+    type Item = std::io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut chunk = String::new();
+        while chunk.len() < self.chunk_bytes {
+            match self.reader.read_line(&mut chunk) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(error) => return Some(Err(error)),
+            }
+        }
+        (!chunk.is_empty()).then_some(Ok(chunk))
+    }
+}
+
+/// Writes the graphs of successive chunks as one JSON Lines graph. An entity or concept
+/// found again in a later chunk is written once, under the id it was first written with,
+/// and edges between the same nodes with the same label are written once. Only these
+/// ids are kept, so memory grows with the number of distinct nodes and edges, not with
+/// the input.
+pub struct JsonLinesStream<W: Write> {
+    writer: W,
+    node_ids: HashMap<String, String>,
+    edges_written: HashSet<(String, String, String)>,
+    metadata: Option<GraphMetadata>,
+}
+
+impl<W: Write> JsonLinesStream<W> {
+    /// Start the stream, writing its `graph` header line
+    pub fn new(mut writer: W, config: &RenderConfig) -> Result<Self> {
+        GraphLine::Graph {
+            schema_version: GRAPH_SCHEMA_VERSION,
+            config,
+        }
+        .write_to(&mut writer)?;
+        Ok(Self {
+            writer,
+            node_ids: HashMap::new(),
+            edges_written: HashSet::new(),
+            metadata: None,
+        })
+    }
+
+    /// Nodes that are the same across chunks: entities and concepts by type and label,
+    /// attribute and relationship nodes only by id
+    fn node_key(node: &GraphNode) -> String {
+        match node.node_type {
+            NodeType::Entity | NodeType::Concept => {
+                format!("{}:{}", node.node_type.metadata_key(), node.label.to_lowercase())
+            }
+            NodeType::Attribute | NodeType::Relationship => format!("id:{}", node.id),
+        }
+    }
+
+    /// Write the nodes and edges of `graph` that are not in the stream yet, and return
+    /// how many of each were written
+    pub fn write_graph(&mut self, graph: &InteractiveGraph) -> Result<(usize, usize)> {
+        let metadata = self.metadata.get_or_insert_with(|| {
+            let mut metadata = graph.metadata.clone();
+            metadata.total_nodes = 0;
+            metadata.total_edges = 0;
+            metadata.node_types.clear();
+            metadata.edge_types.clear();
+            metadata.source_text_length = 0;
+            metadata.provenance.processing_time_ms = 0;
+            metadata
+        });
+        metadata.source_text_length += graph.metadata.source_text_length;
+        metadata.provenance.processing_time_ms += graph.metadata.provenance.processing_time_ms;
+
+        let mut ids = HashMap::new();
+        let mut nodes = 0;
+        for node in &graph.nodes {
+            let key = Self::node_key(node);
+            if let Some(id) = self.node_ids.get(&key) {
+                ids.insert(node.id.as_str(), id.clone());
+                continue;
+            }
+            GraphLine::Node(node).write_to(&mut self.writer)?;
+            self.node_ids.insert(key, node.id.clone());
+            *metadata.node_types.entry(node.node_type.metadata_key().to_string()).or_insert(0) += 1;
+            nodes += 1;
+        }
+
+        let mut edges = 0;
+        for edge in &graph.edges {
+            let from = ids.get(edge.from.as_str()).unwrap_or(&edge.from);
+            let to = ids.get(edge.to.as_str()).unwrap_or(&edge.to);
+            if !self.edges_written.insert((from.clone(), to.clone(), edge.label.clone())) {
+                continue;
+            }
+            if (from, to) == (&edge.from, &edge.to) {
+                GraphLine::Edge(edge).write_to(&mut self.writer)?;
+            } else {
+                let edge = GraphEdge {
+                    from: from.clone(),
+                    to: to.clone(),
+                    ..edge.clone()
+                };
+                GraphLine::Edge(&edge).write_to(&mut self.writer)?;
+            }
+            *metadata.edge_types.entry(edge.edge_type.metadata_key().to_string()).or_insert(0) += 1;
+            edges += 1;
+        }
+
+        metadata.total_nodes += nodes;
+        metadata.total_edges += edges;
+        Ok((nodes, edges))
+    }
+
+    /// Write the closing `metadata` line and flush; fails when no graph was written
+    pub fn finish(mut self) -> Result<GraphMetadata> {
+        let metadata = self
+            .metadata
+            .take()
+            .ok_or_else(|| GraphError::TextProcessing("Input text is empty".to_string()))?;
+        GraphLine::Metadata(&metadata).write_to(&mut self.writer)?;
+        self.writer.flush()?;
+        Ok(metadata)
+    }
+}
+
+/// Generate a graph from the text read from `reader`, one [`TextChunks`] chunk at a time,
+/// writing it to `writer` as JSON Lines as each chunk finishes. Each chunk runs through its
+/// own [`Pipeline`], so relationships are only found between entities of the same chunk.
+pub async fn stream_graph<R: BufRead, W: Write>(
+    config: &GraphConfig,
+    source_type: SourceType,
+    deep_analysis: bool,
+    reader: R,
+    writer: W,
+) -> Result<GraphMetadata> {
+    let mut stream = JsonLinesStream::new(writer, &RenderConfig::from(config))?;
+    let mut input_hash = Sha256::new();
+    for (index, chunk) in TextChunks::new(reader, config.streaming.chunk_bytes).enumerate() {
+        let chunk = chunk?;
+        input_hash.update(chunk.as_bytes());
+        if chunk.trim().is_empty() {
+            continue;
+        }
+        let graph = Pipeline::new()
+            .with_config(config.clone())
+            .with_text(chunk)
+            .with_source_type(source_type.clone())
+            .with_deep_analysis(deep_analysis)
+            .run()
+            .await?;
+        let (nodes, edges) = stream.write_graph(&graph)?;
+        info!("🧱 Chunk {}: {} new nodes, {} new edges", index + 1, nodes, edges);
+    }
+    if let Some(metadata) = stream.metadata.as_mut() {
+        metadata.provenance.input_hash = input_hash.finalize().iter().map(|byte| format!("{:02x}", byte)).collect();
+    }
+    stream.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_chunks_end_at_line_breaks() {
+        let text = "Alice met Bob.\nBob met Carol.\nCarol met Dave.\nDave left.";
+        let chunks: Vec<String> = TextChunks::new(text.as_bytes(), 20).collect::<std::io::Result<_>>().unwrap();
+
+        assert_eq!(chunks, vec!["Alice met Bob.\nBob met Carol.\n", "Carol met Dave.\nDave left."]);
+        assert_eq!(chunks.concat(), text);
+    }
+
+    #[tokio::test]
+    async fn test_stream_writes_each_entity_once_across_chunks() {
+        let text = "Alice Smith is connected to Bob Jones.\nBob Jones is connected to Carol White.\n";
+        let mut config = GraphConfig::default();
+        config.streaming.chunk_bytes = 1;
+
+        let mut output = Vec::new();
+        let metadata = stream_graph(&config, SourceType::Document, false, text.as_bytes(), &mut output)
+            .await
+            .expect("Streaming failed");
+
+        let lines: Vec<serde_json::Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines[0]["type"], "graph");
+        assert_eq!(lines[lines.len() - 1]["type"], "metadata");
+        let nodes: Vec<_> = lines.iter().filter(|line| line["type"] == "node").collect();
+        let edges: Vec<_> = lines.iter().filter(|line| line["type"] == "edge").collect();
+        let bobs: Vec<_> = nodes.iter().filter(|node| node["label"] == "Bob Jones").collect();
+        assert_eq!(bobs.len(), 1);
+        assert_eq!(metadata.total_nodes, nodes.len());
+        assert_eq!(metadata.total_edges, edges.len());
+
+        // The second chunk's edge points at the Bob Jones node written for the first chunk
+        let ids: HashSet<_> = nodes.iter().map(|node| node["id"].as_str().unwrap()).collect();
+        assert!(edges.iter().any(|edge| edge["to"] == bobs[0]["id"] || edge["from"] == bobs[0]["id"]));
+        assert!(edges
+            .iter()
+            .all(|edge| ids.contains(edge["from"].as_str().unwrap()) && ids.contains(edge["to"].as_str().unwrap())));
+    }
+}
@@ -0,0 +1,174 @@
+use crate::conditions::Condition;
+use crate::config::NodeMetric;
+use crate::error::{GraphError, Result};
+use crate::graph_builder::{node_metric, GraphEdge, GraphNode, InteractiveGraph};
+use serde::{Deserialize, Serialize};
+
+/// What a [`StyleRule`] matches against
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleTarget {
+    #[default]
+    Nodes,
+    Edges,
+}
+
+/// A conditional styling rule from `styling.rules`.
+///
+/// `when` is a list of conditions joined by `and`, each `field op value` with
+/// `=`, `!=`, `>`, `>=`, `<`, `<=` or `~` (see [`crate::conditions`]). Node fields are `type` (entity type or node type),
+/// `label`, `confidence`, `degree`, `mention_count`, plus any node attribute such as
+/// `community`; edge fields are `relationship`, `type`, `label`, `confidence` and `weight`.
+/// Rules run in order after the `size_by`/`color_by` styling, so later rules win.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StyleRule {
+    #[serde(default)]
+    pub target: RuleTarget,
+    pub when: String,
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Node size
+    #[serde(default)]
+    pub size: Option<f64>,
+    /// Short marker shown next to the node label, e.g. `"⭐"`
+    #[serde(default)]
+    pub badge: Option<String>,
+    /// Edge width
+    #[serde(default)]
+    pub width: Option<f64>,
+    /// Draw the edge dashed
+    #[serde(default)]
+    pub dashed: Option<bool>,
+}
+
+fn parse_conditions(when: &str) -> Result<Vec<Condition>> {
+    when.split(" and ")
+        .map(|clause| {
+            Condition::parse(clause).map_err(|reason| {
+                GraphError::Configuration(format!("Invalid style rule condition '{}': {}", when, reason))
+            })
+        })
+        .collect()
+}
+
+fn node_field(node: &GraphNode, field: &str, degree: f64, mentions: f64) -> Vec<String> {
+    match field {
+        "type" => {
+            let mut values = vec![node.node_type.metadata_key().to_string()];
+            values.extend(node.metadata.entity_type.clone());
+            values
+        }
+        "label" => vec![node.label.clone()],
+        "confidence" => vec![node.metadata.confidence.to_string()],
+        "degree" => vec![degree.to_string()],
+        "mention_count" => vec![mentions.to_string()],
+        _ => node.metadata.attributes.get(field).cloned().into_iter().collect(),
+    }
+}
+
+fn edge_field(edge: &GraphEdge, field: &str) -> Vec<String> {
+    match field {
+        "relationship" => vec![edge.metadata.relationship_type.clone()],
+        "type" => vec![edge.edge_type.metadata_key().to_string()],
+        "label" => vec![edge.label.clone()],
+        "confidence" => vec![edge.metadata.confidence.to_string()],
+        "weight" => vec![edge.metadata.weight.to_string()],
+        _ => Vec::new(),
+    }
+}
+
+/// Evaluate `rules` in order and apply the styles of every matching rule
+pub fn apply_style_rules(graph: &mut InteractiveGraph, rules: &[StyleRule], source_text: &str) -> Result<()> {
+    if rules.is_empty() {
+        return Ok(());
+    }
+    let compiled: Vec<(Vec<Condition>, &StyleRule)> = rules
+        .iter()
+        .map(|rule| parse_conditions(&rule.when).map(|conditions| (conditions, rule)))
+        .collect::<Result<_>>()?;
+
+    let degrees = node_metric(graph, NodeMetric::Degree, source_text);
+    let mentions = node_metric(graph, NodeMetric::MentionCount, source_text);
+
+    for (conditions, rule) in &compiled {
+        match rule.target {
+            RuleTarget::Nodes => {
+                for (index, node) in graph.nodes.iter_mut().enumerate() {
+                    let matched = conditions
+                        .iter()
+                        .all(|c| c.matches(&node_field(node, &c.field, degrees[index], mentions[index])));
+                    if matched {
+                        if let Some(color) = &rule.color {
+                            node.color = color.clone();
+                        }
+                        if let Some(size) = rule.size {
+                            node.size = size;
+                        }
+                        if let Some(badge) = &rule.badge {
+                            node.badge = Some(badge.clone());
+                        }
+                    }
+                }
+            }
+            RuleTarget::Edges => {
+                for edge in &mut graph.edges {
+                    if conditions.iter().all(|c| c.matches(&edge_field(edge, &c.field))) {
+                        if let Some(color) = &rule.color {
+                            edge.color = color.clone();
+                        }
+                        if let Some(width) = rule.width {
+                            edge.width = width;
+                        }
+                        if let Some(dashed) = rule.dashed {
+                            edge.dashes = dashed;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GraphConfig;
+    use crate::pipeline::Pipeline;
+
+    fn rule(target: RuleTarget, when: &str) -> StyleRule {
+        StyleRule { target, when: when.to_string(), color: None, size: None, badge: None, width: None, dashed: None }
+    }
+
+    #[test]
+    fn test_condition_parsing() {
+        let conditions = parse_conditions("type=Person and degree>=2").unwrap();
+        assert_eq!(conditions.len(), 2);
+        assert_eq!(conditions[1].field, "degree");
+        assert!(conditions[0].matches(&["entity".to_string(), "Person".to_string()]));
+        assert!(!conditions[1].matches(&["1".to_string()]));
+        assert!(parse_conditions("degree 5").is_err());
+        assert!(parse_conditions("=Person").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rules_style_matching_nodes_and_edges() {
+        let mut config = GraphConfig::default();
+        config.styling.rules = vec![
+            StyleRule { color: Some("gold".to_string()), badge: Some("⭐".to_string()), ..rule(RuleTarget::Nodes, "type=Person and degree>0") },
+            StyleRule { dashed: Some(true), ..rule(RuleTarget::Edges, "type!=relationship") },
+        ];
+        let graph = Pipeline::new()
+            .with_config(config)
+            .with_text("Alice works at TechCorp. Bob manages the database system. Alice is connected to Bob.")
+            .run()
+            .await
+            .expect("Pipeline failed");
+
+        let alice = graph.nodes.iter().find(|n| n.label == "Alice").expect("Alice missing");
+        assert_eq!(alice.color, "gold");
+        assert_eq!(alice.badge.as_deref(), Some("⭐"));
+        assert!(graph.nodes.iter().filter(|n| n.metadata.entity_type.as_deref() != Some("Person")).all(|n| n.badge.is_none()));
+        assert!(graph.edges.iter().all(|e| e.dashes == (e.edge_type != crate::graph_builder::EdgeType::EntityRelationship)));
+    }
+}
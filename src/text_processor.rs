@@ -1,17 +1,108 @@
 use crate::error::Result;
+use crate::languages::detect_language;
+use crate::sentence_splitter::SentenceSplitter;
 use regex::Regex;
+use rust_stemmers::{Algorithm, Stemmer};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::HashSet;
-
+use std::ops::Range;
+use tracing::info;
+
+/// Cleaned input text with its sentences and words.
+///
+/// The cleaned text is stored once; sentences, words and the stopword-filtered text are
+/// byte ranges into it, read through the accessors, so a large corpus is not held four
+/// or five times over as separate strings.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessedText {
-    pub original_text: String,
-    pub sentences: Vec<String>,
-    pub words: Vec<String>,
-    pub cleaned_text: String,
+    text: String,
+    sentences: Vec<Range<usize>>,
+    /// Words left after stopword removal
+    words: Vec<Range<usize>>,
+    /// Whitespace-separated tokens kept by stopword removal; `None` unless stopwords are
+    /// also stripped from the prompt text
+    kept_tokens: Option<Vec<Range<usize>>>,
+    /// Stems aligned with `words()`; empty unless stemming is enabled
+    #[serde(default)]
+    pub stems: Vec<String>,
     pub metadata: TextMetadata,
 }
 
+impl ProcessedText {
+    /// The input after whitespace and character cleanup, stopwords included
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Sentences without their closing punctuation, in text order
+    pub fn sentences(&self) -> impl ExactSizeIterator<Item = &str> + DoubleEndedIterator + Clone + '_ {
+        self.sentences.iter().map(|range| &self.text[range.clone()])
+    }
+
+    pub fn sentence(&self, index: usize) -> Option<&str> {
+        self.sentences.get(index).map(|range| &self.text[range.clone()])
+    }
+
+    pub fn sentence_count(&self) -> usize {
+        self.sentences.len()
+    }
+
+    /// Lowercased words left after stopword removal; only words that had capitals are copied
+    pub fn words(&self) -> impl ExactSizeIterator<Item = Cow<'_, str>> + Clone + '_ {
+        self.words.iter().map(|range| lowercase(&self.text[range.clone()]))
+    }
+
+    /// The text sent to the LLM: the cleaned text, without stopwords when the processor
+    /// strips them from prompts. Borrowed when it does not
+    pub fn cleaned_text(&self) -> Cow<'_, str> {
+        self.cleaned_text_protecting(&[])
+    }
+
+    /// Whether [`cleaned_text`](Self::cleaned_text) leaves out stopwords
+    pub fn strips_prompt_stopwords(&self) -> bool {
+        self.kept_tokens.is_some()
+    }
+
+    /// [`cleaned_text`](Self::cleaned_text), also keeping the stopwords that overlap the
+    /// `protected` byte ranges of [`text`](Self::text), such as known entity names
+    pub fn cleaned_text_protecting(&self, protected: &[Range<usize>]) -> Cow<'_, str> {
+        let Some(kept) = &self.kept_tokens else {
+            return Cow::Borrowed(&self.text);
+        };
+        if protected.is_empty() {
+            return Cow::Owned(kept.iter().map(|range| &self.text[range.clone()]).collect::<Vec<_>>().join(" "));
+        }
+        let tokens: Vec<&str> = token_ranges(&self.text)
+            .filter(|token| {
+                kept.binary_search_by_key(&token.start, |range| range.start).is_ok()
+                    || protected.iter().any(|range| range.start < token.end && token.start < range.end)
+            })
+            .map(|token| &self.text[token])
+            .collect();
+        Cow::Owned(tokens.join(" "))
+    }
+}
+
+/// Byte ranges of the whitespace-separated tokens of `text`
+fn token_ranges(text: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    let mut offset = 0;
+    text.split_whitespace().map(move |token| {
+        let start = offset + text[offset..].find(token).unwrap_or(0);
+        offset = start + token.len();
+        start..offset
+    })
+}
+
+/// `word` in lowercase, borrowed when it already is
+fn lowercase(word: &str) -> Cow<'_, str> {
+    if word.chars().all(|c| c.to_lowercase().eq(std::iter::once(c))) {
+        Cow::Borrowed(word)
+    } else {
+        Cow::Owned(word.to_lowercase())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TextMetadata {
     pub word_count: usize,
@@ -19,6 +110,11 @@ pub struct TextMetadata {
     pub character_count: usize,
     pub language: String,
     pub source_type: SourceType,
+    /// Whether `source_type` was detected from the text rather than given
+    #[serde(default)]
+    pub source_type_detected: bool,
+    #[serde(default)]
+    pub stemmed: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,15 +123,29 @@ pub enum SourceType {
     Document,
     Email,
     Article,
+    Html,
+    Markdown,
     Unknown,
+    /// Detect the type from the text with [`SourceType::detect`]; processed text records
+    /// the detected type instead
+    Auto,
 }
 
 pub struct TextProcessor {
-    sentence_regex: Regex,
+    sentence_splitter: SentenceSplitter,
     word_regex: Regex,
     cleanup_regex: Regex,
     stopwords: HashSet<String>,
     remove_stopwords: bool,
+    strip_prompt_stopwords: bool,
+    stem_words: bool,
+}
+
+/// Reduce a word to its English stem (Snowball), lowercasing it first.
+pub fn stem_word(word: &str) -> String {
+    Stemmer::create(Algorithm::English)
+        .stem(&word.to_lowercase())
+        .into_owned()
 }
 
 impl TextProcessor {
@@ -73,11 +183,13 @@ impl TextProcessor {
 
     pub fn new() -> Result<Self> {
         Ok(Self {
-            sentence_regex: Regex::new(r"[.!?]+\s*")?,
+            sentence_splitter: SentenceSplitter::default(),
             word_regex: Regex::new(r"\b\w+\b")?,
             cleanup_regex: Regex::new(r"[^\w\s.,!?;:\-\(\)\[\]]")?,
             stopwords: Self::default_english_stopwords(),
             remove_stopwords: true, // Default is to remove stopwords
+            strip_prompt_stopwords: false,
+            stem_words: false,
         })
     }
 
@@ -89,17 +201,19 @@ impl TextProcessor {
         };
 
         Ok(Self {
-            sentence_regex: Regex::new(r"[.!?]+\s*")?,
+            sentence_splitter: SentenceSplitter::default(),
             word_regex: Regex::new(r"\b\w+\b")?,
             cleanup_regex: Regex::new(r"[^\w\s.,!?;:\-\(\)\[\]]")?,
             stopwords,
             remove_stopwords,
+            strip_prompt_stopwords: false,
+            stem_words: false,
         })
     }
 
     pub fn load_stopwords_from_file(file_path: &str) -> Result<HashSet<String>> {
         let content = std::fs::read_to_string(file_path)
-            .map_err(|e| crate::error::GraphError::Io(e))?;
+            .map_err(crate::error::GraphError::Io)?;
         
         let stopwords: HashSet<String> = content
             .lines()
@@ -110,6 +224,10 @@ impl TextProcessor {
         Ok(stopwords)
     }
 
+    pub fn stopwords(&self) -> &HashSet<String> {
+        &self.stopwords
+    }
+
     pub fn set_stopwords(&mut self, stopwords: HashSet<String>) {
         self.stopwords = stopwords;
     }
@@ -118,45 +236,73 @@ impl TextProcessor {
         self.remove_stopwords = remove;
     }
 
+    /// Also remove stopwords from the text given to the LLM ([`ProcessedText::cleaned_text`]),
+    /// keeping those inside names such as "Bank of America". Off by default, so stopwords
+    /// only leave the word statistics
+    pub fn set_strip_prompt_stopwords(&mut self, strip: bool) {
+        self.strip_prompt_stopwords = strip;
+    }
+
+    pub fn set_stem_words(&mut self, stem: bool) {
+        self.stem_words = stem;
+    }
+
+    pub fn sentence_splitter(&self) -> &SentenceSplitter {
+        &self.sentence_splitter
+    }
+
+    pub fn set_sentence_splitter(&mut self, splitter: SentenceSplitter) {
+        self.sentence_splitter = splitter;
+    }
+
     pub fn process_text(&self, text: &str, source_type: SourceType) -> Result<ProcessedText> {
         // Print stopword processing status
         if self.remove_stopwords {
-            println!("🔍 Processing text with stopword removal enabled");
+            info!("🔍 Processing text with stopword removal enabled");
         } else {
-            println!("🔍 Processing text with stopword removal disabled");
+            info!("🔍 Processing text with stopword removal disabled");
         }
         
-        let cleaned_text = self.clean_text(text)?;
-        let sentences = self.extract_sentences(&cleaned_text)?;
-        let words = self.extract_words(&cleaned_text)?;
-        
-        // Apply stopword removal if enabled
-        let filtered_words = if self.remove_stopwords {
-            self.remove_stopwords_from_words(&words)
-        } else {
-            words.clone()
+        let (source_type, source_type_detected) = match source_type {
+            SourceType::Auto => (SourceType::detect(text), true),
+            source_type => (source_type, false),
         };
+        let cleaned_text = self.clean_text(&source_type.prepare(text))?;
+        let sentences = self.sentence_splitter.sentence_ranges(&cleaned_text);
+
+        // Apply stopword removal if enabled
+        let words: Vec<Range<usize>> = self
+            .word_regex
+            .find_iter(&cleaned_text)
+            .map(|word| word.range())
+            .filter(|range| !self.remove_stopwords || !self.stopwords.contains(lowercase(&cleaned_text[range.clone()]).as_ref()))
+            .collect();
+
+        // The filtered cleaned text is the tokens that are not stopwords
+        let kept_tokens = (self.remove_stopwords && self.strip_prompt_stopwords).then(|| self.kept_token_ranges(&cleaned_text));
 
-        // Create filtered cleaned text by reconstructing from filtered words
-        let filtered_cleaned_text = if self.remove_stopwords {
-            self.reconstruct_text_without_stopwords(&cleaned_text)?
+        let stems = if self.stem_words {
+            words.iter().map(|range| stem_word(&cleaned_text[range.clone()])).collect()
         } else {
-            cleaned_text.clone()
+            Vec::new()
         };
         
         let metadata = TextMetadata {
-            word_count: filtered_words.len(),
+            word_count: words.len(),
             sentence_count: sentences.len(),
             character_count: text.len(),
-            language: self.detect_language(&cleaned_text),
+            language: detect_language(&cleaned_text),
             source_type,
+            source_type_detected,
+            stemmed: self.stem_words,
         };
 
         Ok(ProcessedText {
-            original_text: text.to_string(),
+            text: cleaned_text,
             sentences,
-            words: filtered_words,
-            cleaned_text: filtered_cleaned_text,
+            words,
+            kept_tokens,
+            stems,
             metadata,
         })
     }
@@ -176,66 +322,43 @@ impl TextProcessor {
         Ok(normalized.to_string())
     }
 
-    fn extract_sentences(&self, text: &str) -> Result<Vec<String>> {
-        let sentences: Vec<String> = self.sentence_regex
-            .split(text)
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-            .collect();
-        
-        Ok(sentences)
-    }
-
-    fn extract_words(&self, text: &str) -> Result<Vec<String>> {
-        let words: Vec<String> = self.word_regex
-            .find_iter(text)
-            .map(|m| m.as_str().to_lowercase())
-            .collect();
-        
-        Ok(words)
-    }
-
-    fn remove_stopwords_from_words(&self, words: &[String]) -> Vec<String> {
-        words.iter()
-            .filter(|word| !self.stopwords.contains(*word))
-            .cloned()
-            .collect()
-    }
-
-    fn reconstruct_text_without_stopwords(&self, text: &str) -> Result<String> {
-        let words: Vec<&str> = text.split_whitespace().collect();
-        let filtered_words: Vec<&str> = words.into_iter()
-            .filter(|word| {
-                let clean_word = word.to_lowercase()
-                    .trim_matches(|c: char| !c.is_alphabetic())
-                    .to_string();
-                !self.stopwords.contains(&clean_word)
-            })
-            .collect();
-        
-        Ok(filtered_words.join(" "))
-    }
-
-    fn detect_language(&self, text: &str) -> String {
-        // Simple language detection - can be enhanced with proper language detection library
-        let common_english_words = ["the", "and", "or", "but", "in", "on", "at", "to", "for", "of", "with", "by"];
-        let word_count = text.split_whitespace().count();
-        
-        if word_count == 0 {
-            return "unknown".to_string();
-        }
-        
-        let english_word_count = text.split_whitespace()
-            .filter(|word| common_english_words.contains(&word.to_lowercase().as_str()))
-            .count();
-        
-        let english_ratio = english_word_count as f64 / word_count as f64;
-        
-        if english_ratio > 0.1 {
-            "english".to_string()
-        } else {
-            "unknown".to_string()
+    /// Ranges of the whitespace-separated tokens of `text` that are not stopwords, plus
+    /// runs of up to two lowercase stopwords joining capitalized words ("Bank of America",
+    /// "University of the West"), which are part of a name
+    fn kept_token_ranges(&self, text: &str) -> Vec<Range<usize>> {
+        let tokens: Vec<Range<usize>> = token_ranges(text).collect();
+        let is_stopword = |token: &Range<usize>| {
+            let clean_word = text[token.clone()].to_lowercase();
+            self.stopwords.contains(clean_word.trim_matches(|c: char| !c.is_alphabetic()))
+        };
+        let capitalized = |token: &Range<usize>| text[token.clone()].starts_with(char::is_uppercase);
+        let ends_clause = |token: &Range<usize>| text[token.clone()].ends_with(['.', '!', '?', ',', ';', ':']);
+
+        let mut ranges = Vec::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            if !is_stopword(&tokens[i]) {
+                ranges.push(tokens[i].clone());
+                i += 1;
+                continue;
+            }
+            let run_end = (i..tokens.len())
+                .find(|&j| !is_stopword(&tokens[j]) || capitalized(&tokens[j]) || ends_clause(&tokens[j]))
+                .unwrap_or(tokens.len());
+            let in_name = i > 0
+                && run_end > i
+                && run_end - i <= 2
+                && capitalized(&tokens[i - 1])
+                && !ends_clause(&tokens[i - 1])
+                && tokens.get(run_end).is_some_and(|next| capitalized(next) && !is_stopword(next));
+            if in_name {
+                ranges.extend(tokens[i..run_end].iter().cloned());
+                i = run_end;
+            } else {
+                i += 1;
+            }
         }
+        ranges
     }
 
     pub fn extract_context_windows(&self, text: &str, window_size: usize) -> Result<Vec<String>> {
@@ -243,7 +366,7 @@ impl TextProcessor {
         let mut windows = Vec::new();
         
         for i in 0..words.len() {
-            let start = if i >= window_size / 2 { i - window_size / 2 } else { 0 };
+            let start = i.saturating_sub(window_size / 2);
             let end = std::cmp::min(i + window_size / 2 + 1, words.len());
             
             let window = words[start..end].join(" ");
@@ -271,7 +394,7 @@ impl TextProcessor {
         }
         
         // Remove duplicates and sort by length (longer phrases first)
-        phrases.sort_by(|a, b| b.len().cmp(&a.len()));
+        phrases.sort_by_key(|p| std::cmp::Reverse(p.len()));
         phrases.dedup();
         
         Ok(phrases)
@@ -290,6 +413,10 @@ mod tests {
     use std::io::Write;
     use tempfile::NamedTempFile;
 
+    fn has_word(processed: &ProcessedText, word: &str) -> bool {
+        processed.words().any(|w| w == word)
+    }
+
     #[test]
     fn test_default_stopwords_removal() {
         let processor = TextProcessor::new().expect("Failed to create processor");
@@ -297,17 +424,17 @@ mod tests {
         let processed = processor.process_text(text, SourceType::Document).expect("Failed to process text");
         
         // With stopwords removed, should have fewer words
-        assert!(processed.words.len() < text.split_whitespace().count());
+        assert!(processed.words().len() < text.split_whitespace().count());
         
         // Should not contain common stopwords
-        assert!(!processed.words.contains(&"the".to_string()));
-        assert!(!processed.words.contains(&"and".to_string()));
-        assert!(!processed.words.contains(&"over".to_string()));
+        assert!(!has_word(&processed, "the"));
+        assert!(!has_word(&processed, "and"));
+        assert!(!has_word(&processed, "over"));
         
         // Should contain content words
-        assert!(processed.words.contains(&"quick".to_string()));
-        assert!(processed.words.contains(&"brown".to_string()));
-        assert!(processed.words.contains(&"fox".to_string()));
+        assert!(has_word(&processed, "quick"));
+        assert!(has_word(&processed, "brown"));
+        assert!(has_word(&processed, "fox"));
     }
 
     #[test]
@@ -318,11 +445,11 @@ mod tests {
         
         // With no stopword removal, should have all words (converted to lowercase)
         let original_word_count = text.split_whitespace().count();
-        assert_eq!(processed.words.len(), original_word_count);
+        assert_eq!(processed.words().len(), original_word_count);
         
         // Should contain stopwords
-        assert!(processed.words.contains(&"the".to_string()));
-        assert!(processed.words.contains(&"over".to_string()));
+        assert!(has_word(&processed, "the"));
+        assert!(has_word(&processed, "over"));
     }
 
     #[test]
@@ -341,14 +468,14 @@ mod tests {
         let processed = processor.process_text(text, SourceType::Document).expect("Failed to process text");
         
         // Should not contain our custom stopwords
-        assert!(!processed.words.contains(&"quick".to_string()));
-        assert!(!processed.words.contains(&"brown".to_string()));
-        assert!(!processed.words.contains(&"jumps".to_string()));
+        assert!(!has_word(&processed, "quick"));
+        assert!(!has_word(&processed, "brown"));
+        assert!(!has_word(&processed, "jumps"));
         
         // Should contain other words
-        assert!(processed.words.contains(&"the".to_string())); // "the" not in our custom list
-        assert!(processed.words.contains(&"fox".to_string()));
-        assert!(processed.words.contains(&"dog".to_string()));
+        assert!(has_word(&processed, "the")); // "the" not in our custom list
+        assert!(has_word(&processed, "fox"));
+        assert!(has_word(&processed, "dog"));
     }
 
     #[test]
@@ -358,20 +485,22 @@ mod tests {
         let processed = processor.process_text(text, SourceType::Document).expect("Failed to process text");
         
         // All words should be lowercase and stopwords removed
-        assert!(!processed.words.contains(&"the".to_string()));
-        assert!(!processed.words.contains(&"over".to_string()));
-        assert!(processed.words.contains(&"quick".to_string()));
-        assert!(processed.words.contains(&"brown".to_string()));
-        assert!(processed.words.contains(&"fox".to_string()));
+        assert!(!has_word(&processed, "the"));
+        assert!(!has_word(&processed, "over"));
+        assert!(has_word(&processed, "quick"));
+        assert!(has_word(&processed, "brown"));
+        assert!(has_word(&processed, "fox"));
     }
 
     #[test]
     fn test_reconstruct_text_without_stopwords() {
-        let processor = TextProcessor::new().expect("Failed to create processor");
+        let mut processor = TextProcessor::new().expect("Failed to create processor");
+        processor.set_strip_prompt_stopwords(true);
         let original_text = "The quick brown fox jumps over the lazy dog.";
         
-        let reconstructed = processor.reconstruct_text_without_stopwords(original_text)
-            .expect("Failed to reconstruct text");
+        let processed = processor.process_text(original_text, SourceType::Document)
+            .expect("Failed to process text");
+        let reconstructed = processed.cleaned_text();
         
         // Should not contain stopwords
         assert!(!reconstructed.contains(" the "));
@@ -383,6 +512,43 @@ mod tests {
         assert!(reconstructed.contains("fox"));
     }
 
+    #[test]
+    fn test_prompt_text_keeps_stopwords_by_default_and_inside_names() {
+        let text = "The loan from Bank of America went to the University of the West. Alice and the team agreed.";
+        let mut processor = TextProcessor::new().expect("Failed to create processor");
+        let processed = processor.process_text(text, SourceType::Document).expect("Failed to process text");
+        assert_eq!(processed.cleaned_text(), text);
+        assert!(!processed.words().any(|word| word == "of"));
+
+        processor.set_strip_prompt_stopwords(true);
+        let processed = processor.process_text(text, SourceType::Document).expect("Failed to process text");
+        assert_eq!(processed.cleaned_text(), "loan Bank of America University of the West. Alice team agreed.");
+        let protected = [text.find("team").unwrap()..text.find("team").unwrap() + 4, 0..3];
+        assert_eq!(
+            processed.cleaned_text_protecting(&protected),
+            "The loan Bank of America University of the West. Alice team agreed."
+        );
+    }
+
+    #[test]
+    fn test_sentences_and_words_are_slices_of_the_cleaned_text() {
+        let processor = TextProcessor::new().expect("Failed to create processor");
+        let processed = processor
+            .process_text("  Alice met Dr. Bob.\tThe server crashed!  ", SourceType::Document)
+            .expect("Failed to process text");
+
+        assert_eq!(processed.text(), "Alice met Dr. Bob. The server crashed!");
+        assert_eq!(processed.sentences().collect::<Vec<_>>(), vec!["Alice met Dr. Bob", "The server crashed"]);
+        assert_eq!(processed.sentence(1), Some("The server crashed"));
+        let text = processed.text().as_bytes().as_ptr_range();
+        assert!(processed.sentences().all(|sentence| text.contains(&sentence.as_ptr())));
+
+        let words: Vec<_> = processed.words().collect();
+        assert_eq!(words, vec!["alice", "met", "dr", "bob", "server", "crashed"]);
+        assert!(matches!(words[1], Cow::Borrowed(_)));
+        assert_eq!(processed.cleaned_text(), "Alice met Dr. Bob. The server crashed!");
+    }
+
     #[test]
     fn test_load_stopwords_from_file() {
         // Create a temporary stopwords file
@@ -391,7 +557,7 @@ mod tests {
         writeln!(temp_file, "word2").expect("Failed to write to temp file");
         writeln!(temp_file, "# comment line").expect("Failed to write to temp file");
         writeln!(temp_file, "word3").expect("Failed to write to temp file");
-        writeln!(temp_file, "").expect("Failed to write empty line");
+        writeln!(temp_file).expect("Failed to write empty line");
         writeln!(temp_file, "word4").expect("Failed to write to temp file");
         
         let temp_path = temp_file.path().to_str().expect("Failed to get temp path");
@@ -429,9 +595,9 @@ mod tests {
         let processor = TextProcessor::new().expect("Failed to create processor");
         let processed = processor.process_text("", SourceType::Document).expect("Failed to process empty text");
         
-        assert_eq!(processed.words.len(), 0);
-        assert_eq!(processed.sentences.len(), 0);
-        assert_eq!(processed.cleaned_text, "");
+        assert_eq!(processed.words().len(), 0);
+        assert_eq!(processed.sentence_count(), 0);
+        assert_eq!(processed.cleaned_text(), "");
     }
 
     #[test]
@@ -441,9 +607,34 @@ mod tests {
         let processed = processor.process_text(text, SourceType::Document).expect("Failed to process text");
         
         // Should handle punctuation correctly while removing stopwords
-        assert!(processed.words.contains(&"quick".to_string()));
-        assert!(processed.words.contains(&"brown".to_string()));
-        assert!(!processed.words.contains(&"the".to_string()));
-        assert!(!processed.words.contains(&"over".to_string()));
+        assert!(has_word(&processed, "quick"));
+        assert!(has_word(&processed, "brown"));
+        assert!(!has_word(&processed, "the"));
+        assert!(!has_word(&processed, "over"));
+    }
+
+    #[test]
+    fn test_stem_word_normalizes_inflections() {
+        assert_eq!(stem_word("manages"), stem_word("managed"));
+        assert_eq!(stem_word("managed"), stem_word("Managing"));
+        assert_ne!(stem_word("manager"), stem_word("database"));
+    }
+
+    #[test]
+    fn test_stems_only_when_enabled() {
+        let mut processor = TextProcessor::new().expect("Failed to create processor");
+        let text = "Alice manages the processes. Bob managed them.";
+
+        let processed = processor.process_text(text, SourceType::Document).expect("Failed to process text");
+        assert!(processed.stems.is_empty());
+        assert!(!processed.metadata.stemmed);
+
+        processor.set_stem_words(true);
+        let processed = processor.process_text(text, SourceType::Document).expect("Failed to process text");
+        assert!(processed.metadata.stemmed);
+        assert_eq!(processed.stems.len(), processed.words().len());
+        // Surface forms are preserved alongside the stems
+        assert!(has_word(&processed, "manages"));
+        assert_eq!(processed.stems.iter().filter(|s| *s == "manag").count(), 2);
     }
 }
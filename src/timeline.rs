@@ -0,0 +1,225 @@
+//! Dates mentioned in the source text, for the viewer's timeline. Each entity node records
+//! the dates that share a sentence with it; the viewer groups them back into events (one
+//! per date and sentence) and draws them with vis-timeline, one row per entity.
+
+use crate::graph_builder::{InteractiveGraph, NodeType};
+use crate::sentence_splitter::SentenceSplitter;
+use chrono::NaiveDate;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::sync::OnceLock;
+
+/// Settings for date detection (`timeline`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimelineConfig {
+    /// Record the dates mentioned alongside each entity
+    pub enabled: bool,
+    /// Longest sentence excerpt kept with a date, in characters
+    pub max_excerpt_chars: usize,
+}
+
+impl Default for TimelineConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_excerpt_chars: 160,
+        }
+    }
+}
+
+/// A date mentioned in the same sentence as an entity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatedMention {
+    /// ISO 8601 day (`2024-03-15`), month (`2024-03`) or year (`2024`), as precise as the text
+    pub date: String,
+    /// The date as written
+    pub text: String,
+    /// Index of the sentence in the source text
+    pub sentence: usize,
+    /// The sentence, shortened to `timeline.max_excerpt_chars`
+    pub excerpt: String,
+}
+
+/// A date found in text by [`find_dates`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextDate {
+    /// Byte range of the date in the text
+    pub range: Range<usize>,
+    /// ISO 8601 day, month or year, see [`DatedMention::date`]
+    pub date: String,
+}
+
+const MONTH: &str = "(january|february|march|april|may|june|july|august|september|october|november|december|jan|feb|mar|apr|jun|jul|aug|sept|sep|oct|nov|dec)";
+
+/// Words that make a lone four-digit number a year ("in 1999", "since 2010")
+const YEAR_CUES: &str = "in|since|by|until|till|from|during|around|before|after|circa|early|late|mid";
+
+/// Date patterns, most precise first, each with the capture groups of its year, month and day
+fn date_patterns() -> &'static [(Regex, [usize; 3])] {
+    static PATTERNS: OnceLock<Vec<(Regex, [usize; 3])>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        let pattern = |source: String| Regex::new(&format!("(?i){}", source)).expect("valid date regex");
+        vec![
+            (pattern(r"\b([0-9]{4})-([0-9]{1,2})-([0-9]{1,2})\b".to_string()), [1, 2, 3]),
+            (pattern(format!(r"\b{}\.?\s+([0-9]{{1,2}})(?:st|nd|rd|th)?,?\s+([0-9]{{4}})\b", MONTH)), [3, 1, 2]),
+            (pattern(format!(r"\b([0-9]{{1,2}})(?:st|nd|rd|th)?\s+(?:of\s+)?{}\.?,?\s+([0-9]{{4}})\b", MONTH)), [3, 2, 1]),
+            (pattern(format!(r"\b{}\.?,?\s+(?:of\s+)?([0-9]{{4}})\b", MONTH)), [2, 1, 0]),
+            (pattern(format!(r"\b(?:{})\s+([0-9]{{4}})\b", YEAR_CUES)), [1, 0, 0]),
+        ]
+    })
+}
+
+fn month_number(name: &str) -> Option<u32> {
+    let months = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+    if let Ok(number) = name.parse::<u32>() {
+        return (1..=12).contains(&number).then_some(number);
+    }
+    let prefix = name.get(..3)?.to_lowercase();
+    months.iter().position(|month| *month == prefix).map(|index| index as u32 + 1)
+}
+
+/// Dates in `text`: ISO dates, "March 15, 2024", "15 March 2024", "March 2024" and years
+/// after a cue word such as "in 1999". Impossible days are skipped.
+pub fn find_dates(text: &str) -> Vec<TextDate> {
+    let mut dates: Vec<TextDate> = Vec::new();
+    // Text already read as a date, or as an impossible one that a less precise pattern
+    // shouldn't pick apart again
+    let mut taken: Vec<Range<usize>> = Vec::new();
+    for (regex, [year, month, day]) in date_patterns() {
+        for captures in regex.captures_iter(text) {
+            let whole = captures.get(0).expect("whole match");
+            if taken.iter().any(|range| range.start < whole.end() && whole.start() < range.end) {
+                continue;
+            }
+            taken.push(whole.range());
+            let year_text = &captures[*year];
+            let Some(year_number) = year_text.parse::<i32>().ok().filter(|year| (1000..=2999).contains(year)) else {
+                continue;
+            };
+            let Some(month_number) = (*month > 0).then(|| month_number(&captures[*month])) else {
+                dates.push(TextDate { range: captures.get(*year).unwrap().range(), date: year_text.to_string() });
+                continue;
+            };
+            let Some(month_number) = month_number else { continue };
+            let date = if *day > 0 {
+                let Ok(day_number) = captures[*day].parse::<u32>() else { continue };
+                match NaiveDate::from_ymd_opt(year_number, month_number, day_number) {
+                    Some(date) => date.format("%Y-%m-%d").to_string(),
+                    None => continue,
+                }
+            } else {
+                format!("{}-{:02}", year_text, month_number)
+            };
+            dates.push(TextDate { range: whole.range(), date });
+        }
+    }
+    dates.sort_by_key(|date| date.range.start);
+    dates
+}
+
+/// Shorten `text` to `max` characters, ending in an ellipsis when cut
+fn excerpt(text: &str, max: usize) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match text.char_indices().nth(max) {
+        Some((cut, _)) => format!("{}…", text[..cut].trim_end()),
+        None => text,
+    }
+}
+
+/// Record on each entity node the dates found in the sentences mentioning it, in its
+/// `dates` metadata. Nodes whose sentences mention no date keep an empty list, and so do
+/// entities only mentioned inside a date, such as "On March" picked up from "On March 4, 2021".
+pub fn apply_timeline(graph: &mut InteractiveGraph, source_text: &str, splitter: &SentenceSplitter, config: &TimelineConfig) {
+    let dated_sentences: Vec<(usize, &str, Vec<TextDate>)> = splitter
+        .split_spans(source_text)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, (start, end))| {
+            let sentence = &source_text[start..end];
+            let dates = find_dates(sentence);
+            (!dates.is_empty()).then_some((index, sentence, dates))
+        })
+        .collect();
+    if dated_sentences.is_empty() {
+        return;
+    }
+
+    for node in graph.nodes.iter_mut().filter(|node| node.node_type == NodeType::Entity) {
+        let Ok(mention) = Regex::new(&format!(r"(?i)\b{}\b", regex::escape(&node.label))) else {
+            continue;
+        };
+        node.metadata.dates.clear();
+        for (index, sentence, dates) in &dated_sentences {
+            let outside_dates = mention.find_iter(sentence).any(|found| {
+                !dates.iter().any(|date| date.range.start < found.end() && found.start() < date.range.end)
+            });
+            if !outside_dates {
+                continue;
+            }
+            for date in dates {
+                node.metadata.dates.push(DatedMention {
+                    date: date.date.clone(),
+                    text: sentence[date.range.clone()].to_string(),
+                    sentence: *index,
+                    excerpt: excerpt(sentence, config.max_excerpt_chars),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_dates_reads_common_forms_with_their_precision() {
+        let dates: Vec<String> = find_dates(
+            "Founded on March 3rd, 2015 and renamed 14 July 2019, it moved in 2021-02-30 (no such day), \
+             opened in Sept. 2020 and grew since 2022. It has 1500 staff.",
+        )
+        .into_iter()
+        .map(|date| date.date)
+        .collect();
+        assert_eq!(dates, vec!["2015-03-03", "2019-07-14", "2020-09", "2022"]);
+
+        let text = "Alice joined on 2024-01-05.";
+        let found = find_dates(text);
+        assert_eq!(&text[found[0].range.clone()], "2024-01-05");
+
+        // Only ASCII digits are read; other scripts' digits are left alone rather than panicking
+        assert!(find_dates("Alice joined Acme Corp in ٢٠٢٤, on ١٥ March ٢٠٢٤ or ٢٠٢٤-٠٣-١٥.").is_empty());
+        assert!(find_dates("Bob left in ２０２３.").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_entities_record_the_dates_of_their_sentences() {
+        let graph = crate::pipeline::Pipeline::new()
+            .with_text(
+                "Alice is connected to Bob since 2019. On March 4, 2021 Bob is connected to Carol. \
+                 Carol works with Dave.",
+            )
+            .run()
+            .await
+            .expect("Pipeline failed");
+
+        let dates = |label: &str| -> Vec<String> {
+            let node = graph.nodes.iter().find(|node| node.label == label).expect(label);
+            node.metadata.dates.iter().map(|mention| mention.date.clone()).collect()
+        };
+        assert_eq!(dates("Alice"), vec!["2019"]);
+        assert_eq!(dates("Bob"), vec!["2019", "2021-03-04"]);
+        assert_eq!(dates("Carol"), vec!["2021-03-04"]);
+        let carol = graph.nodes.iter().find(|node| node.label == "Carol").unwrap();
+        assert_eq!(carol.metadata.dates[0].text, "March 4, 2021");
+        assert_eq!(carol.metadata.dates[0].sentence, 1);
+        // Neither Dave nor a name made of the date's own words is dated
+        assert!(graph
+            .nodes
+            .iter()
+            .filter(|node| node.label == "Dave" || node.label == "On March")
+            .all(|node| node.metadata.dates.is_empty()));
+    }
+}
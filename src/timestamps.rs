@@ -0,0 +1,121 @@
+use crate::error::{GraphError, Result};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, FixedOffset, Local, Locale, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How timestamps in graph metadata, exports and the HTML header are written.
+///
+/// All timestamp formatting goes through [`format_timestamp`] so every exporter
+/// renders the same instant the same way.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimestampConfig {
+    /// `"rfc3339"` for full RFC 3339, or a strftime pattern such as `"%d %B %Y %H:%M"`
+    pub format: String,
+    /// `"UTC"`, `"local"`, or a fixed offset such as `"+02:00"`
+    pub timezone: String,
+    /// Locale for month and day names in strftime patterns (e.g. `"de_DE"`)
+    pub locale: Option<String>,
+}
+
+impl Default for TimestampConfig {
+    fn default() -> Self {
+        Self {
+            format: "rfc3339".to_string(),
+            timezone: "UTC".to_string(),
+            locale: None,
+        }
+    }
+}
+
+fn to_timezone(instant: DateTime<Utc>, timezone: &str) -> Result<DateTime<FixedOffset>> {
+    match timezone.to_lowercase().as_str() {
+        "utc" | "z" => Ok(instant.fixed_offset()),
+        "local" => Ok(instant.with_timezone(&Local).fixed_offset()),
+        _ => {
+            let offset: FixedOffset = timezone.parse().map_err(|_| {
+                GraphError::Configuration(format!(
+                    "Invalid timezone '{}': expected UTC, local or an offset like +02:00",
+                    timezone
+                ))
+            })?;
+            Ok(instant.with_timezone(&offset))
+        }
+    }
+}
+
+/// Format `instant` according to `config`
+pub fn format_timestamp(instant: DateTime<Utc>, config: &TimestampConfig) -> Result<String> {
+    let zoned = to_timezone(instant, &config.timezone)?;
+
+    if config.format.eq_ignore_ascii_case("rfc3339") {
+        return Ok(zoned.to_rfc3339());
+    }
+
+    // chrono panics on Display for malformed patterns, so reject them up front
+    if StrftimeItems::new(&config.format).any(|item| matches!(item, Item::Error)) {
+        return Err(GraphError::Configuration(format!(
+            "Invalid timestamp format '{}'",
+            config.format
+        )));
+    }
+
+    match &config.locale {
+        Some(name) => {
+            let locale = Locale::try_from(name.as_str())
+                .map_err(|_| GraphError::Configuration(format!("Unknown locale '{}'", name)))?;
+            Ok(zoned.format_localized(&config.format, locale).to_string())
+        }
+        None => Ok(zoned.format(&config.format).to_string()),
+    }
+}
+
+/// Format the current time according to `config`
+pub fn timestamp_now(config: &TimestampConfig) -> Result<String> {
+    format_timestamp(Utc::now(), config)
+}
+
+/// Format the time a graph is stamped with: now, or for a deterministic run the
+/// `SOURCE_DATE_EPOCH` environment variable (seconds since 1970) when set, else 1970 itself
+pub fn run_timestamp(config: &TimestampConfig, deterministic: bool) -> Result<String> {
+    if !deterministic {
+        return timestamp_now(config);
+    }
+    let seconds = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|value| value.trim().parse::<i64>().ok())
+        .unwrap_or(0);
+    let instant = DateTime::from_timestamp(seconds, 0).unwrap_or(DateTime::UNIX_EPOCH);
+    format_timestamp(instant, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn instant() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 5, 14, 30, 0).unwrap()
+    }
+
+    #[test]
+    fn test_default_is_utc_rfc3339() {
+        let formatted = format_timestamp(instant(), &TimestampConfig::default()).unwrap();
+        assert_eq!(formatted, "2024-03-05T14:30:00+00:00");
+    }
+
+    #[test]
+    fn test_pattern_offset_and_locale() {
+        let config = TimestampConfig {
+            format: "%d %B %Y %H:%M".to_string(),
+            timezone: "+02:00".to_string(),
+            locale: Some("de_DE".to_string()),
+        };
+        assert_eq!(format_timestamp(instant(), &config).unwrap(), "05 März 2024 16:30");
+
+        let invalid = TimestampConfig {
+            timezone: "Mars/Olympus".to_string(),
+            ..TimestampConfig::default()
+        };
+        assert!(matches!(format_timestamp(instant(), &invalid), Err(GraphError::Configuration(_))));
+    }
+}
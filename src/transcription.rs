@@ -0,0 +1,252 @@
+//! Transcripts of audio recordings, from an external speech recognizer (ASR).
+//!
+//! msg_net does not recognize speech itself. Audio input is handed either to a command
+//! (`openai-whisper`, `whisper.cpp`, WhisperX, ...) or to an OpenAI-compatible
+//! `/v1/audio/transcriptions` endpoint, and the JSON it returns is turned into
+//! timestamped, speaker-labelled chat lines that the chat pipeline understands.
+
+use crate::error::{GraphError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+/// Extensions treated as audio (or video with an audio track) to transcribe
+pub const AUDIO_EXTENSIONS: &[&str] = &["wav", "mp3", "m4a", "flac", "ogg", "opus", "aac", "webm", "mp4"];
+
+/// Where audio goes to be transcribed
+#[derive(Debug, Clone, PartialEq)]
+pub enum AsrBackend {
+    /// A command line with `{input}` and `{output_dir}` placeholders, split on whitespace
+    /// and run without a shell. It prints the transcript JSON, or writes a `.json` file
+    /// into `{output_dir}`, e.g. `whisper {input} --output_format json --output_dir {output_dir}`
+    Command(String),
+    /// An OpenAI-compatible transcription endpoint, sent the file as multipart form data
+    Endpoint { url: String, model: String },
+}
+
+/// One stretch of speech
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptSegment {
+    /// Seconds from the start of the recording
+    pub start: f64,
+    pub end: f64,
+    /// Diarization label, when the recognizer provides one
+    pub speaker: Option<String>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Transcript {
+    pub segments: Vec<TranscriptSegment>,
+    pub language: Option<String>,
+}
+
+impl Transcript {
+    /// Read the JSON of the common recognizers: Whisper's and OpenAI's `verbose_json`
+    /// (`segments` with `start`/`end` seconds), WhisperX (plus `speaker`), whisper.cpp
+    /// (`transcription` with `offsets` in milliseconds), or just `{"text": ...}`
+    pub fn from_json(json: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(json)?;
+        let language = value.get("language").and_then(Value::as_str).map(str::to_string);
+        let text_of = |segment: &Value| segment.get("text").and_then(Value::as_str).unwrap_or("").trim().to_string();
+
+        let segments: Vec<TranscriptSegment> = if let Some(segments) = value.get("segments").and_then(Value::as_array) {
+            segments
+                .iter()
+                .map(|segment| TranscriptSegment {
+                    start: segment.get("start").and_then(Value::as_f64).unwrap_or(0.0),
+                    end: segment.get("end").and_then(Value::as_f64).unwrap_or(0.0),
+                    speaker: segment.get("speaker").and_then(Value::as_str).map(str::to_string),
+                    text: text_of(segment),
+                })
+                .collect()
+        } else if let Some(segments) = value.get("transcription").and_then(Value::as_array) {
+            let millis = |segment: &Value, key: &str| {
+                segment.get("offsets").and_then(|offsets| offsets.get(key)).and_then(Value::as_f64).unwrap_or(0.0) / 1000.0
+            };
+            segments
+                .iter()
+                .map(|segment| TranscriptSegment {
+                    start: millis(segment, "from"),
+                    end: millis(segment, "to"),
+                    speaker: None,
+                    text: text_of(segment),
+                })
+                .collect()
+        } else if let Some(text) = value.get("text").and_then(Value::as_str) {
+            vec![TranscriptSegment { start: 0.0, end: 0.0, speaker: None, text: text.trim().to_string() }]
+        } else {
+            return Err(GraphError::TextProcessing(
+                "Transcript JSON has no segments, transcription or text".to_string(),
+            ));
+        };
+
+        Ok(Self { segments: segments.into_iter().filter(|segment| !segment.text.is_empty()).collect(), language })
+    }
+
+    /// The transcript as chat lines, `[00:01:05] Speaker One: text`, which source type
+    /// detection recognizes as a chat log. Diarization labels like `SPEAKER_00` become
+    /// `Speaker One`; real names are kept.
+    pub fn to_chat_text(&self) -> String {
+        self.segments
+            .iter()
+            .map(|segment| {
+                let seconds = segment.start.max(0.0).round() as u64;
+                let timestamp = format!("[{:02}:{:02}:{:02}]", seconds / 3600, seconds / 60 % 60, seconds % 60);
+                match &segment.speaker {
+                    Some(speaker) => format!("{} {}: {}", timestamp, speaker_name(speaker), segment.text),
+                    None => format!("{} {}", timestamp, segment.text),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Distinct speakers, in order of first appearance
+    pub fn speakers(&self) -> Vec<String> {
+        let mut speakers: Vec<String> = Vec::new();
+        for speaker in self.segments.iter().filter_map(|segment| segment.speaker.as_deref()).map(speaker_name) {
+            if !speakers.contains(&speaker) {
+                speakers.push(speaker);
+            }
+        }
+        speakers
+    }
+}
+
+/// Spelled out so speaker labels are capitalized words the entity patterns pick up
+const SPEAKER_NUMBERS: &[&str] = &[
+    "One", "Two", "Three", "Four", "Five", "Six", "Seven", "Eight", "Nine", "Ten", "Eleven", "Twelve",
+];
+
+/// `SPEAKER_00` -> `Speaker One`
+fn speaker_name(label: &str) -> String {
+    match label.strip_prefix("SPEAKER_").and_then(|number| number.parse::<usize>().ok()) {
+        Some(number) => match SPEAKER_NUMBERS.get(number) {
+            Some(word) => format!("Speaker {}", word),
+            None => format!("Speaker {}", number + 1),
+        },
+        None => label.to_string(),
+    }
+}
+
+/// Whether `path` is a recording to transcribe
+pub fn is_audio_input(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| AUDIO_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Transcribe the recording at `path` with `backend`
+pub async fn transcribe(path: &str, backend: &AsrBackend) -> Result<Transcript> {
+    match backend {
+        AsrBackend::Command(template) => transcribe_with_command(path, template).await,
+        AsrBackend::Endpoint { url, model } => transcribe_with_endpoint(path, url, model).await,
+    }
+}
+
+async fn transcribe_with_command(path: &str, template: &str) -> Result<Transcript> {
+    let output_dir = std::env::temp_dir().join(format!("msg_net_asr_{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&output_dir)?;
+    let args: Vec<String> = template
+        .split_whitespace()
+        .map(|arg| arg.replace("{input}", path).replace("{output_dir}", &output_dir.to_string_lossy()))
+        .collect();
+    let Some((program, args)) = args.split_first() else {
+        return Err(GraphError::Configuration("The ASR command is empty".to_string()));
+    };
+
+    let result = async {
+        let output = tokio::process::Command::new(program)
+            .args(args)
+            .output()
+            .await
+            .map_err(|e| GraphError::Configuration(format!("Cannot run ASR command '{}': {}", program, e)))?;
+        if !output.status.success() {
+            return Err(GraphError::TextProcessing(format!(
+                "ASR command failed ({}): {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if let Ok(transcript) = Transcript::from_json(stdout.trim()) {
+            return Ok(transcript);
+        }
+        let mut written: Vec<_> = std::fs::read_dir(&output_dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        written.sort();
+        match written.first() {
+            Some(json) => Transcript::from_json(&std::fs::read_to_string(json)?),
+            None => Err(GraphError::TextProcessing(
+                "The ASR command printed no transcript JSON and wrote no .json file".to_string(),
+            )),
+        }
+    }
+    .await;
+    let _ = std::fs::remove_dir_all(&output_dir);
+    result
+}
+
+async fn transcribe_with_endpoint(path: &str, url: &str, model: &str) -> Result<Transcript> {
+    let bytes = tokio::fs::read(path).await?;
+    let file_name = Path::new(path).file_name().and_then(|name| name.to_str()).unwrap_or("audio").to_string();
+    let form = reqwest::multipart::Form::new()
+        .part("file", reqwest::multipart::Part::bytes(bytes).file_name(file_name))
+        .text("model", model.to_string())
+        .text("response_format", "verbose_json");
+
+    let response = reqwest::Client::new().post(url).multipart(form).send().await?;
+    if !response.status().is_success() {
+        return Err(GraphError::TextProcessing(format!(
+            "ASR endpoint returned error status: {}",
+            response.status()
+        )));
+    }
+    Transcript::from_json(&response.text().await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text_processor::SourceType;
+
+    #[test]
+    fn test_reads_whisper_whisperx_and_whisper_cpp_json() {
+        let whisperx = r#"{"language": "en", "segments": [
+            {"start": 0.0, "end": 4.2, "speaker": "SPEAKER_00", "text": " Alice will send the budget to Initech."},
+            {"start": 65.4, "end": 70.0, "speaker": "SPEAKER_01", "text": " Bob agreed."},
+            {"start": 3700.0, "end": 3701.0, "speaker": "SPEAKER_00", "text": "  "}]}"#;
+        let transcript = Transcript::from_json(whisperx).unwrap();
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+        assert_eq!(
+            transcript.to_chat_text(),
+            "[00:00:00] Speaker One: Alice will send the budget to Initech.\n[00:01:05] Speaker Two: Bob agreed."
+        );
+        assert_eq!(transcript.speakers(), vec!["Speaker One", "Speaker Two"]);
+        assert!(matches!(SourceType::detect(&transcript.to_chat_text()), SourceType::ChatMessage));
+
+        let whisper_cpp = r#"{"transcription": [{"offsets": {"from": 1500, "to": 3000}, "text": " Hello there."}]}"#;
+        let transcript = Transcript::from_json(whisper_cpp).unwrap();
+        assert_eq!(transcript.segments[0].start, 1.5);
+        assert_eq!(transcript.to_chat_text(), "[00:00:02] Hello there.");
+
+        assert_eq!(Transcript::from_json(r#"{"text": "Just text."}"#).unwrap().segments.len(), 1);
+        assert!(Transcript::from_json(r#"{"words": []}"#).is_err());
+        assert!(is_audio_input("standup.M4A") && !is_audio_input("standup.txt"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_command_backend_reads_printed_json() {
+        let backend = AsrBackend::Command(r#"echo {"text":"From_{input}"}"#.to_string());
+        let transcript = transcribe("call.wav", &backend).await.unwrap();
+        assert_eq!(transcript.segments[0].text, "From_call.wav");
+
+        let failing = AsrBackend::Command("false {input}".to_string());
+        assert!(transcribe("call.wav", &failing).await.is_err());
+    }
+}
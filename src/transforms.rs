@@ -0,0 +1,398 @@
+use crate::config::{GraphConfig, NodeMetric};
+use crate::error::{GraphError, Result};
+use crate::graph_builder::{node_metric, InteractiveGraph, NodeType};
+use crate::history::{GraphHistory, GraphOperation};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+/// One step of a cleanup recipe declared in `GraphConfig::transforms`.
+///
+/// Transforms are written as short strings so recipes can be shared in config files:
+///
+/// | String                     | Effect                                                        |
+/// |----------------------------|---------------------------------------------------------------|
+/// | `dedupe`                   | Merge entities/concepts with the same label, drop duplicate edges |
+/// | `prune_isolated`           | Remove nodes with no edges                                    |
+/// | `min_degree 2`             | Remove nodes with fewer than N edges                          |
+/// | `min_confidence 0.6`       | Remove nodes below the confidence threshold                   |
+/// | `keep_top 200 by degree`   | Keep the N highest-ranked nodes (`confidence`, `degree`, `mention_count`) |
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphTransform {
+    Dedupe,
+    PruneIsolated,
+    MinDegree(usize),
+    MinConfidence(f64),
+    KeepTop { count: usize, metric: NodeMetric },
+}
+
+fn parse_metric(name: &str) -> Option<NodeMetric> {
+    match name {
+        "confidence" => Some(NodeMetric::Confidence),
+        "degree" => Some(NodeMetric::Degree),
+        "mention_count" | "mentions" => Some(NodeMetric::MentionCount),
+        _ => None,
+    }
+}
+
+impl FromStr for GraphTransform {
+    type Err = GraphError;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        let invalid = || GraphError::Configuration(format!("Invalid transform '{}'", spec));
+        let words: Vec<&str> = spec.split_whitespace().collect();
+
+        match words.as_slice() {
+            ["dedupe"] => Ok(GraphTransform::Dedupe),
+            ["prune_isolated"] => Ok(GraphTransform::PruneIsolated),
+            ["min_degree", degree] => Ok(GraphTransform::MinDegree(degree.parse().map_err(|_| invalid())?)),
+            ["min_confidence", threshold] => {
+                let threshold: f64 = threshold.parse().map_err(|_| invalid())?;
+                Ok(GraphTransform::MinConfidence(threshold))
+            }
+            ["keep_top", count] => Ok(GraphTransform::KeepTop {
+                count: count.parse().map_err(|_| invalid())?,
+                metric: NodeMetric::Degree,
+            }),
+            ["keep_top", count, "by", metric] => Ok(GraphTransform::KeepTop {
+                count: count.parse().map_err(|_| invalid())?,
+                metric: parse_metric(metric).ok_or_else(invalid)?,
+            }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for GraphTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphTransform::Dedupe => write!(f, "dedupe"),
+            GraphTransform::PruneIsolated => write!(f, "prune_isolated"),
+            GraphTransform::MinDegree(degree) => write!(f, "min_degree {}", degree),
+            GraphTransform::MinConfidence(threshold) => write!(f, "min_confidence {}", threshold),
+            GraphTransform::KeepTop { count, metric } => {
+                let metric = match metric {
+                    NodeMetric::Confidence => "confidence",
+                    NodeMetric::Degree => "degree",
+                    NodeMetric::MentionCount => "mention_count",
+                };
+                write!(f, "keep_top {} by {}", count, metric)
+            }
+        }
+    }
+}
+
+/// Parse every transform in a recipe, failing on the first invalid entry
+pub fn parse_transforms(specs: &[String]) -> Result<Vec<GraphTransform>> {
+    specs.iter().map(|spec| spec.parse()).collect()
+}
+
+/// The `transforms` recipe followed by the `min_degree` and `prune_isolated` settings,
+/// in that order, so nodes left without edges by the degree cut are pruned too
+pub fn config_transforms(config: &GraphConfig) -> Result<Vec<GraphTransform>> {
+    let mut transforms = parse_transforms(&config.transforms)?;
+    if config.min_degree > 0 {
+        transforms.push(GraphTransform::MinDegree(config.min_degree));
+    }
+    if config.prune_isolated {
+        transforms.push(GraphTransform::PruneIsolated);
+    }
+    Ok(transforms)
+}
+
+impl GraphTransform {
+    /// The history operations that carry out this transform on the current graph
+    fn operations(&self, graph: &InteractiveGraph, source_text: &str) -> Vec<GraphOperation> {
+        match self {
+            GraphTransform::Dedupe => dedupe_operations(graph),
+            GraphTransform::PruneIsolated => vec![GraphOperation::PruneIsolated],
+            GraphTransform::MinDegree(min_degree) => {
+                let degrees = node_metric(graph, NodeMetric::Degree, source_text);
+                let node_ids: Vec<String> = graph
+                    .nodes
+                    .iter()
+                    .zip(degrees)
+                    .filter(|(_, degree)| *degree < *min_degree as f64)
+                    .map(|(node, _)| node.id.clone())
+                    .collect();
+                if node_ids.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![GraphOperation::RemoveNodes { node_ids }]
+                }
+            }
+            GraphTransform::MinConfidence(threshold) => {
+                vec![GraphOperation::FilterMinConfidence { min_confidence: *threshold }]
+            }
+            GraphTransform::KeepTop { count, metric } => {
+                let values = node_metric(graph, *metric, source_text);
+                let mut ranked: Vec<usize> = (0..graph.nodes.len()).collect();
+                // Stable sort keeps extraction order among equally ranked nodes
+                ranked.sort_by(|a, b| values[*b].total_cmp(&values[*a]));
+                let node_ids: Vec<String> =
+                    ranked.into_iter().skip(*count).map(|index| graph.nodes[index].id.clone()).collect();
+                if node_ids.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![GraphOperation::RemoveNodes { node_ids }]
+                }
+            }
+        }
+    }
+}
+
+/// Merge same-labelled entities and concepts into their first occurrence, then drop edges
+/// that now repeat an earlier edge between the same nodes.
+fn dedupe_operations(graph: &InteractiveGraph) -> Vec<GraphOperation> {
+    let mut operations = Vec::new();
+
+    let mut groups: HashMap<(NodeType, String), Vec<String>> = HashMap::new();
+    let mut order = Vec::new();
+    for node in graph.nodes.iter().filter(|n| matches!(n.node_type, NodeType::Entity | NodeType::Concept)) {
+        let key = (node.node_type.clone(), node.label.trim().to_lowercase());
+        let group = groups.entry(key.clone()).or_default();
+        if group.is_empty() {
+            order.push(key);
+        }
+        group.push(node.id.clone());
+    }
+    for key in order {
+        let mut ids = groups.remove(&key).unwrap_or_default();
+        if ids.len() > 1 {
+            let target = ids.remove(0);
+            operations.push(GraphOperation::MergeNodes { target, sources: ids });
+        }
+    }
+
+    // Duplicate edges are resolved against the merged node ids
+    let merged: HashMap<&str, &str> = operations
+        .iter()
+        .filter_map(|op| match op {
+            GraphOperation::MergeNodes { target, sources } => Some((target, sources)),
+            _ => None,
+        })
+        .flat_map(|(target, sources)| sources.iter().map(move |source| (source.as_str(), target.as_str())))
+        .collect();
+    let resolve = |id: &str| merged.get(id).copied().unwrap_or(id).to_string();
+
+    let mut seen = HashSet::new();
+    let edge_ids: Vec<String> = graph
+        .edges
+        .iter()
+        .filter(|edge| {
+            let key = (resolve(&edge.from), resolve(&edge.to), edge.label.to_lowercase(), edge.edge_type.clone());
+            !seen.insert(key)
+        })
+        .map(|edge| edge.id.clone())
+        .collect();
+    if !edge_ids.is_empty() {
+        operations.push(GraphOperation::RemoveEdges { edge_ids });
+    }
+
+    operations
+}
+
+/// Run `transforms` in order over `graph`, returning the history so callers can
+/// report or undo what each step removed
+pub fn apply_transforms(
+    graph: InteractiveGraph,
+    transforms: &[GraphTransform],
+    source_text: &str,
+) -> Result<GraphHistory> {
+    let mut history = GraphHistory::new(graph);
+    for transform in transforms {
+        for operation in transform.operations(history.graph(), source_text) {
+            history.apply(operation)?;
+        }
+    }
+    Ok(history)
+}
+
+/// What `max_nodes` and `max_edges` cut from an oversized graph
+#[derive(Debug, Clone, PartialEq)]
+pub struct Truncation {
+    pub nodes_before: usize,
+    pub nodes_after: usize,
+    pub edges_before: usize,
+    pub edges_after: usize,
+    /// Dropped nodes by node type key (`entity`, `concept`, ...)
+    pub dropped_node_types: BTreeMap<String, usize>,
+}
+
+impl fmt::Display for Truncation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "kept {} of {} nodes and {} of {} edges",
+            self.nodes_after, self.nodes_before, self.edges_after, self.edges_before
+        )?;
+        if !self.dropped_node_types.is_empty() {
+            let dropped: Vec<String> =
+                self.dropped_node_types.iter().map(|(node_type, count)| format!("{} {}", count, node_type)).collect();
+            write!(f, " (dropped {})", dropped.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Cut the graph down to `max_nodes` and `max_edges` from the config. The nodes kept are
+/// the best connected, ties going to the more confident; then, if there are still too
+/// many edges, the least confident are dropped. With `prune_isolated` set, nodes the cut
+/// leaves without edges go too. `None` when the graph was within both limits.
+pub fn limit_graph_size(
+    graph: InteractiveGraph,
+    config: &GraphConfig,
+    source_text: &str,
+) -> Result<(InteractiveGraph, Option<Truncation>)> {
+    let over_nodes = config.max_nodes.filter(|max| graph.nodes.len() > *max);
+    let over_edges = |graph: &InteractiveGraph| config.max_edges.filter(|max| graph.edges.len() > *max);
+    if over_nodes.is_none() && over_edges(&graph).is_none() {
+        return Ok((graph, None));
+    }
+    let (nodes_before, edges_before) = (graph.nodes.len(), graph.edges.len());
+    let mut dropped_node_types: BTreeMap<String, usize> = BTreeMap::new();
+    let mut history = GraphHistory::new(graph);
+
+    if let Some(max_nodes) = over_nodes {
+        let graph = history.graph();
+        let degrees = node_metric(graph, NodeMetric::Degree, source_text);
+        let mut ranked: Vec<usize> = (0..graph.nodes.len()).collect();
+        ranked.sort_by(|a, b| {
+            degrees[*b]
+                .total_cmp(&degrees[*a])
+                .then(graph.nodes[*b].metadata.confidence.total_cmp(&graph.nodes[*a].metadata.confidence))
+        });
+        let node_ids: Vec<String> = ranked[max_nodes..]
+            .iter()
+            .map(|&index| {
+                *dropped_node_types.entry(graph.nodes[index].node_type.metadata_key().to_string()).or_default() += 1;
+                graph.nodes[index].id.clone()
+            })
+            .collect();
+        history.apply(GraphOperation::RemoveNodes { node_ids })?;
+    }
+
+    if let Some(max_edges) = over_edges(history.graph()) {
+        let edges = &history.graph().edges;
+        let mut ranked: Vec<usize> = (0..edges.len()).collect();
+        ranked.sort_by(|a, b| {
+            edges[*b]
+                .metadata
+                .confidence
+                .total_cmp(&edges[*a].metadata.confidence)
+                .then(edges[*b].metadata.weight.total_cmp(&edges[*a].metadata.weight))
+        });
+        let edge_ids: Vec<String> = ranked[max_edges..].iter().map(|&index| edges[index].id.clone()).collect();
+        history.apply(GraphOperation::RemoveEdges { edge_ids })?;
+    }
+
+    if config.prune_isolated {
+        let before: HashMap<String, String> = history
+            .graph()
+            .nodes
+            .iter()
+            .map(|node| (node.id.clone(), node.node_type.metadata_key().to_string()))
+            .collect();
+        history.apply(GraphOperation::PruneIsolated)?;
+        let kept: HashSet<&str> = history.graph().nodes.iter().map(|node| node.id.as_str()).collect();
+        for (id, node_type) in &before {
+            if !kept.contains(id.as_str()) {
+                *dropped_node_types.entry(node_type.clone()).or_default() += 1;
+            }
+        }
+    }
+
+    let graph = history.into_graph();
+    let truncation = Truncation {
+        nodes_before,
+        nodes_after: graph.nodes.len(),
+        edges_before,
+        edges_after: graph.edges.len(),
+        dropped_node_types,
+    };
+    Ok((graph, Some(truncation)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GraphConfig;
+    use crate::pipeline::Pipeline;
+
+    #[test]
+    fn test_parse_round_trip_and_errors() {
+        let specs: Vec<String> = ["dedupe", "prune_isolated", "min_degree 2", "min_confidence 0.6", "keep_top 200 by degree"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let transforms = parse_transforms(&specs).expect("Failed to parse");
+        assert_eq!(transforms[4], GraphTransform::KeepTop { count: 200, metric: NodeMetric::Degree });
+        assert_eq!(transforms.iter().map(|t| t.to_string()).collect::<Vec<_>>(), specs);
+
+        for bad in ["explode", "min_confidence high", "keep_top 5 by size", "min_degree -1"] {
+            assert!(matches!(bad.parse::<GraphTransform>(), Err(GraphError::Configuration(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_config_transforms_run_after_construction() {
+        let config = GraphConfig {
+            transforms: vec!["dedupe".to_string(), "keep_top 3 by degree".to_string(), "prune_isolated".to_string()],
+            ..GraphConfig::default()
+        };
+        let graph = Pipeline::new()
+            .with_config(config)
+            .with_text("Alice works at TechCorp. Alice is connected to Bob. Bob manages the database system.")
+            .run()
+            .await
+            .expect("Pipeline failed");
+
+        assert!(graph.nodes.len() <= 3);
+        assert_eq!(graph.metadata.total_nodes, graph.nodes.len());
+        let alice = graph.nodes.iter().filter(|n| n.label == "Alice").count();
+        assert!(alice <= 1);
+        let ids: HashSet<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert!(graph.edges.iter().all(|e| ids.contains(e.from.as_str()) && ids.contains(e.to.as_str())));
+    }
+
+    #[tokio::test]
+    async fn test_min_degree_and_prune_isolated_settings() {
+        let text = "Alice works at TechCorp. Alice is connected to Bob. Bob manages the database system.";
+        let unpruned = Pipeline::new().with_text(text).run().await.expect("Pipeline failed");
+        let config = GraphConfig { min_degree: 2, prune_isolated: true, ..GraphConfig::default() };
+        let graph = Pipeline::new().with_config(config.clone()).with_text(text).run().await.expect("Pipeline failed");
+
+        assert_eq!(config_transforms(&config).unwrap(), vec![GraphTransform::MinDegree(2), GraphTransform::PruneIsolated]);
+        assert!(graph.nodes.len() < unpruned.nodes.len());
+        let ids: HashSet<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert!(graph.edges.iter().all(|e| ids.contains(e.from.as_str()) && ids.contains(e.to.as_str())));
+        assert!(graph.nodes.iter().all(|n| graph.edges.iter().any(|e| e.from == n.id || e.to == n.id)));
+    }
+
+    #[tokio::test]
+    async fn test_size_limits_keep_the_best_connected_nodes_and_most_confident_edges() {
+        let text = "Alice works at TechCorp. Alice is connected to Bob. Bob manages the database system. Carol has a process.";
+        let full = Pipeline::new().with_text(text).run().await.expect("Pipeline failed");
+        let config = GraphConfig { max_nodes: Some(3), max_edges: Some(1), ..GraphConfig::default() };
+        assert!(full.nodes.len() > 3 && full.edges.len() > 1);
+
+        let (graph, truncation) = limit_graph_size(full.clone(), &config, text).unwrap();
+        let truncation = truncation.expect("Graph was over the limits");
+        assert_eq!((graph.nodes.len(), graph.edges.len()), (3, 1));
+        assert_eq!((truncation.nodes_before, truncation.edges_before), (full.nodes.len(), full.edges.len()));
+        assert_eq!(truncation.dropped_node_types.values().sum::<usize>(), full.nodes.len() - 3);
+        assert!(truncation.to_string().starts_with(&format!("kept 3 of {} nodes and 1 of", full.nodes.len())));
+        // The best connected nodes survive
+        let degrees = node_metric(&full, NodeMetric::Degree, text);
+        let top = degrees.iter().copied().fold(0.0, f64::max);
+        let kept: HashSet<&str> = graph.nodes.iter().map(|node| node.id.as_str()).collect();
+        assert!(full.nodes.iter().zip(&degrees).any(|(node, degree)| *degree == top && kept.contains(node.id.as_str())));
+
+        let built = Pipeline::new().with_config(config).with_text(text).run().await.expect("Pipeline failed");
+        assert_eq!((built.nodes.len(), built.edges.len()), (3, 1));
+        assert_eq!(built.metadata.provenance.config_summary["max_nodes"], "3");
+
+        let roomy = GraphConfig { max_nodes: Some(full.nodes.len()), ..GraphConfig::default() };
+        assert!(limit_graph_size(full, &roomy, text).unwrap().1.is_none());
+    }
+}
@@ -0,0 +1,302 @@
+//! Integrity checks for graphs, mainly for hand-edited JSON exports: the viewer drops
+//! edges whose nodes are missing and stops laying out at a non-finite position without
+//! saying why, so `msg_net validate` and [`InteractiveGraph::validate`] report them.
+
+use crate::graph_builder::InteractiveGraph;
+use crate::schema::{graph_from_json, legacy_schema_version, GRAPH_SCHEMA_VERSION};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Loads and renders, but probably not as intended
+    Warning,
+    /// Breaks loading or rendering
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// The file is not graph JSON msg_net can read
+    Unreadable,
+    /// `schema_version` is missing, older or newer than this release's
+    SchemaVersion,
+    DuplicateNodeId,
+    DuplicateEdgeId,
+    /// An edge's `from` or `to` names no node
+    DanglingEdge,
+    /// A node position or size that is NaN or infinite
+    InvalidNumber,
+    EmptyLabel,
+    /// `metadata` totals that no longer match the node and edge lists
+    StaleMetadata,
+}
+
+/// One problem found in a graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphIssue {
+    pub severity: Severity,
+    pub kind: IssueKind,
+    /// Where in the JSON the problem is, e.g. `edges[3].to`; empty for the whole file
+    pub path: String,
+    pub message: String,
+}
+
+/// Everything [`InteractiveGraph::validate`] or [`validate_graph_json`] found, errors first
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub issues: Vec<GraphIssue>,
+}
+
+impl ValidationReport {
+    fn push(&mut self, severity: Severity, kind: IssueKind, path: impl Into<String>, message: impl Into<String>) {
+        self.issues.push(GraphIssue {
+            severity,
+            kind,
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+
+    /// No errors; warnings are allowed
+    pub fn is_valid(&self) -> bool {
+        self.errors() == 0
+    }
+
+    pub fn errors(&self) -> usize {
+        self.issues.iter().filter(|issue| issue.severity == Severity::Error).count()
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.issues.len() - self.errors()
+    }
+
+    fn sort(&mut self) {
+        self.issues.sort_by_key(|issue| std::cmp::Reverse(issue.severity));
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in &self.issues {
+            let severity = match issue.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            };
+            if issue.path.is_empty() {
+                writeln!(f, "{}: {}", severity, issue.message)?;
+            } else {
+                writeln!(f, "{}: {}: {}", severity, issue.path, issue.message)?;
+            }
+        }
+        write!(f, "{} error(s), {} warning(s)", self.errors(), self.warnings())
+    }
+}
+
+impl InteractiveGraph {
+    /// Check the graph for duplicate ids, edges to missing nodes, non-finite positions
+    /// and sizes, empty labels, a schema version other than this release's and stale
+    /// metadata totals
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        if self.schema_version != GRAPH_SCHEMA_VERSION {
+            report.push(
+                if self.schema_version > GRAPH_SCHEMA_VERSION { Severity::Error } else { Severity::Warning },
+                IssueKind::SchemaVersion,
+                "schema_version",
+                format!("schema version {}, this msg_net writes version {}", self.schema_version, GRAPH_SCHEMA_VERSION),
+            );
+        }
+
+        let mut node_ids: HashMap<&str, usize> = HashMap::new();
+        for (index, node) in self.nodes.iter().enumerate() {
+            match node_ids.entry(&node.id) {
+                Entry::Occupied(first) => report.push(
+                    Severity::Error,
+                    IssueKind::DuplicateNodeId,
+                    format!("nodes[{}].id", index),
+                    format!("id \"{}\" is already used by nodes[{}]", node.id, first.get()),
+                ),
+                Entry::Vacant(slot) => {
+                    slot.insert(index);
+                }
+            }
+            if node.label.trim().is_empty() {
+                report.push(
+                    Severity::Warning,
+                    IssueKind::EmptyLabel,
+                    format!("nodes[{}].label", index),
+                    format!("node \"{}\" has no label", node.id),
+                );
+            }
+            for (field, value) in [("x", node.x), ("y", node.y), ("size", Some(node.size))] {
+                if value.is_some_and(|value| !value.is_finite()) {
+                    report.push(
+                        Severity::Error,
+                        IssueKind::InvalidNumber,
+                        format!("nodes[{}].{}", index, field),
+                        format!("node \"{}\" has {} {}", node.id, field, value.unwrap_or_default()),
+                    );
+                }
+            }
+        }
+
+        let mut edge_ids = HashMap::new();
+        for (index, edge) in self.edges.iter().enumerate() {
+            match edge_ids.entry(edge.id.as_str()) {
+                Entry::Occupied(first) => report.push(
+                    Severity::Error,
+                    IssueKind::DuplicateEdgeId,
+                    format!("edges[{}].id", index),
+                    format!("id \"{}\" is already used by edges[{}]", edge.id, first.get()),
+                ),
+                Entry::Vacant(slot) => {
+                    slot.insert(index);
+                }
+            }
+            for (field, end) in [("from", &edge.from), ("to", &edge.to)] {
+                if !node_ids.contains_key(end.as_str()) {
+                    report.push(
+                        Severity::Error,
+                        IssueKind::DanglingEdge,
+                        format!("edges[{}].{}", index, field),
+                        format!("edge \"{}\" points to missing node \"{}\"", edge.id, end),
+                    );
+                }
+            }
+        }
+
+        let metadata = &self.metadata;
+        if metadata.total_nodes != self.nodes.len() || metadata.total_edges != self.edges.len() {
+            report.push(
+                Severity::Warning,
+                IssueKind::StaleMetadata,
+                "metadata",
+                format!(
+                    "metadata counts {} nodes and {} edges, the graph has {} and {}",
+                    metadata.total_nodes,
+                    metadata.total_edges,
+                    self.nodes.len(),
+                    self.edges.len()
+                ),
+            );
+        }
+
+        report.sort();
+        report
+    }
+}
+
+/// Validate graph JSON as written to disk: the stored `schema_version` (which loading
+/// migrates away), whether it loads at all, then [`InteractiveGraph::validate`]
+pub fn validate_graph_json(json: &str) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    let value: Value = match serde_json::from_str(json) {
+        Ok(value) => value,
+        Err(error) => {
+            report.push(Severity::Error, IssueKind::Unreadable, "", format!("not valid JSON: {}", error));
+            return report;
+        }
+    };
+
+    // Compared as stored, so a version past u32 isn't mistaken for an older one
+    let version = match value.get("schema_version") {
+        None => Some(u64::from(legacy_schema_version())),
+        Some(version) => version.as_u64(),
+    };
+    match version {
+        Some(version) if version > u64::from(GRAPH_SCHEMA_VERSION) => report.push(
+            Severity::Error,
+            IssueKind::SchemaVersion,
+            "schema_version",
+            format!("schema version {} is newer than this msg_net reads ({}); upgrade msg_net", version, GRAPH_SCHEMA_VERSION),
+        ),
+        Some(version) if version < u64::from(GRAPH_SCHEMA_VERSION) => report.push(
+            Severity::Warning,
+            IssueKind::SchemaVersion,
+            "schema_version",
+            format!("schema version {} is migrated to {} on load; export again to update the file", version, GRAPH_SCHEMA_VERSION),
+        ),
+        Some(_) => {}
+        None => report.push(Severity::Error, IssueKind::SchemaVersion, "schema_version", "schema_version is not a number"),
+    }
+    if !report.is_valid() {
+        return report;
+    }
+
+    match graph_from_json(json) {
+        Ok(graph) => {
+            // Loading already brought the version up to date
+            report.issues.extend(graph.validate().issues);
+        }
+        Err(error) => report.push(Severity::Error, IssueKind::Unreadable, "", format!("not a graph export: {}", error)),
+    }
+    report.sort();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::Pipeline;
+
+    async fn sample_graph() -> InteractiveGraph {
+        Pipeline::new()
+            .with_text("Alice is connected to Bob. Bob works with Carol at TechCorp.")
+            .run()
+            .await
+            .expect("Pipeline failed")
+    }
+
+    #[tokio::test]
+    async fn test_built_graph_is_valid_and_edits_are_reported() {
+        let mut graph = sample_graph().await;
+        assert_eq!(graph.validate(), ValidationReport::default());
+
+        let duplicate = graph.nodes[0].id.clone();
+        graph.nodes[1].id = duplicate.clone();
+        graph.nodes[0].label = " ".to_string();
+        graph.nodes[0].x = Some(f64::NAN);
+        graph.edges[0].to = "missing".to_string();
+        let report = graph.validate();
+
+        let kinds: Vec<(IssueKind, &str)> = report.issues.iter().map(|issue| (issue.kind, issue.path.as_str())).collect();
+        assert!(kinds.contains(&(IssueKind::DuplicateNodeId, "nodes[1].id")));
+        assert!(kinds.contains(&(IssueKind::InvalidNumber, "nodes[0].x")));
+        assert!(kinds.contains(&(IssueKind::DanglingEdge, "edges[0].to")));
+        assert!(kinds.contains(&(IssueKind::EmptyLabel, "nodes[0].label")));
+        assert!(!report.is_valid());
+        assert_eq!(report.issues.last().unwrap().severity, Severity::Warning);
+    }
+
+    #[tokio::test]
+    async fn test_json_reports_stored_schema_version_and_unreadable_files() {
+        let graph = sample_graph().await;
+        let mut value = serde_json::to_value(&graph).unwrap();
+        assert!(validate_graph_json(&value.to_string()).issues.is_empty());
+
+        value["schema_version"] = serde_json::json!(GRAPH_SCHEMA_VERSION + 1);
+        let report = validate_graph_json(&value.to_string());
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!((report.issues[0].kind, report.issues[0].severity), (IssueKind::SchemaVersion, Severity::Error));
+        value["schema_version"] = serde_json::json!(u64::from(u32::MAX) + 2);
+        let report = validate_graph_json(&value.to_string());
+        assert_eq!(report.issues.len(), 1);
+        assert!(report.issues[0].message.contains("4294967297 is newer"), "{}", report.issues[0].message);
+
+        value.as_object_mut().unwrap().remove("schema_version");
+        value["edges"][0]["from"] = serde_json::json!("nowhere");
+        let report = validate_graph_json(&value.to_string());
+        assert_eq!(report.errors(), 1);
+        assert_eq!(report.warnings(), 1);
+        assert_eq!(report.issues[0].kind, IssueKind::DanglingEdge);
+
+        let report = validate_graph_json(r#"{"nodes": [{"id": "a"}], "edges": []}"#);
+        assert_eq!(report.issues[0].kind, IssueKind::Unreadable);
+    }
+}
@@ -0,0 +1,71 @@
+//! JavaScript bindings for running extraction in the browser.
+//!
+//! Built with `--no-default-features --features wasm` for `wasm32-unknown-unknown`, e.g.
+//! `wasm-pack build --target web -- --no-default-features --features wasm`. Only the
+//! pattern-based extractor runs: nothing is fetched, cached or read from disk.
+
+use crate::config::GraphConfig;
+use crate::extractors::{ExtractorKind, ExtractorStep};
+use crate::pipeline::Pipeline;
+use wasm_bindgen::prelude::*;
+
+/// The config a browser run uses: `config_json` (a msg_net config file's contents, or
+/// the defaults when empty) restricted to what works without an operating system
+pub fn browser_config(config_json: Option<&str>) -> crate::error::Result<GraphConfig> {
+    let mut config: GraphConfig = match config_json.map(str::trim).filter(|json| !json.is_empty()) {
+        Some(json) => serde_json::from_str(json)?,
+        None => GraphConfig::default(),
+    };
+    config.extraction.extractors = vec![ExtractorStep {
+        kind: ExtractorKind::Patterns,
+        enabled: true,
+    }];
+    config.extraction.llm_cache.enabled = false;
+    config.extraction.gazetteer_files.clear();
+    config.extraction.plugins.clear();
+    config.text_processing.stopwords_file = None;
+    Ok(config)
+}
+
+/// Extract entities and relationships from `text` and resolve to the laid-out graph as
+/// JSON, the same document as a `-f json --include-metadata` export
+#[wasm_bindgen(js_name = buildGraph)]
+pub async fn build_graph(text: String, config_json: Option<String>) -> Result<String, JsError> {
+    let config = browser_config(config_json.as_deref()).map_err(|e| JsError::new(&e.to_string()))?;
+    let graph = Pipeline::new()
+        .with_config(config)
+        .with_text(text)
+        .run()
+        .await
+        .map_err(|e| JsError::new(&e.to_string()))?;
+    serde_json::to_string(&graph).map_err(|e| JsError::new(&e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_browser_config_runs_the_patterns_only() {
+        let config = browser_config(Some(
+            r#"{"extraction": {"use_llm": true, "llm_model": "llama3.2", "plugins": ["x.wasm"]}}"#,
+        ));
+        // A partial config is not a valid config file
+        assert!(config.is_err());
+
+        let mut full = serde_json::to_value(GraphConfig::default()).unwrap();
+        full["extraction"]["use_llm"] = true.into();
+        full["extraction"]["plugins"] = serde_json::json!(["x.wasm"]);
+        let config = browser_config(Some(&full.to_string())).unwrap();
+        assert!(!config.extraction.uses_llm());
+        assert!(config.extraction.plugins.is_empty());
+
+        let graph = Pipeline::new()
+            .with_config(browser_config(None).unwrap())
+            .with_text("Alice works at TechCorp.")
+            .run()
+            .await
+            .unwrap();
+        assert!(graph.nodes.iter().any(|node| node.label == "Alice"));
+    }
+}
@@ -1,8 +1,118 @@
-use crate::config::GraphConfig;
-use crate::graph_builder::InteractiveGraph;
+use crate::config::{GraphConfig, RenderConfig};
+use crate::graph_builder::{detect_communities, InteractiveGraph};
 use crate::error::{GraphError, Result};
+use askama::Template;
 use serde::{Deserialize, Serialize};
 
+const VIEWER_CSS: &str = include_str!("../templates/viewer.css");
+const VIEWER_JS: &str = include_str!("../templates/viewer.js");
+
+/// CDN location of the vis-network bundle the viewer loads by default
+pub const DEFAULT_VIS_NETWORK_URL: &str = "https://unpkg.com/vis-network/standalone/umd/vis-network.min.js";
+
+/// CDN location of the vis-timeline bundle loaded by pages showing a timeline
+pub const DEFAULT_VIS_TIMELINE_URL: &str = "https://unpkg.com/vis-timeline/standalone/umd/vis-timeline-graph2d.min.js";
+
+/// How the viewer page gets the vis-network library
+#[derive(Debug, Clone, PartialEq)]
+pub enum VisNetworkSource {
+    /// `<script src=...>`; the page needs network access to that URL
+    Url(String),
+    /// The bundle's source, inlined so the page works offline
+    Inline(String),
+}
+
+impl Default for VisNetworkSource {
+    fn default() -> Self {
+        VisNetworkSource::Url(DEFAULT_VIS_NETWORK_URL.to_string())
+    }
+}
+
+/// Timeline of a viewer page, drawn from the `dates` of its entity nodes
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageTimeline {
+    /// Where the page gets vis-timeline, given the same way as vis-network
+    pub bundle: VisNetworkSource,
+    /// Show only the timeline, without the network
+    pub only: bool,
+}
+
+/// Palette of a rendered viewer page, resolved from a [`crate::config::Theme`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageTheme {
+    /// `light`, `dark` or `auto`, see [`crate::config::Theme::palette`]
+    pub palette: &'static str,
+    /// Stylesheet of a custom theme, inlined after the built-in one
+    pub css: Option<String>,
+}
+
+impl Default for PageTheme {
+    fn default() -> Self {
+        Self {
+            palette: "light",
+            css: None,
+        }
+    }
+}
+
+/// The chunk files a chunked export's page loads instead of an embedded graph
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageChunks {
+    /// The serialized [`crate::graph_chunks::ChunkManifest`]
+    pub manifest: String,
+    /// URL, relative to the page, of the directory holding the chunk files
+    pub base: String,
+}
+
+/// How [`WebInterface::render_page`] renders a viewer page
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageOptions {
+    pub title: String,
+    /// When the graph was generated, shown in the page header; already formatted (see
+    /// [`crate::timestamps::format_timestamp`])
+    pub generated_at: Option<String>,
+    pub vis_network: VisNetworkSource,
+    pub theme: PageTheme,
+    /// A timeline beside the network, or instead of it when `timeline.only` is set
+    pub timeline: Option<PageTimeline>,
+    pub chunks: Option<PageChunks>,
+}
+
+impl Default for PageOptions {
+    fn default() -> Self {
+        Self {
+            title: "Entity Relationship Graph".to_string(),
+            generated_at: None,
+            vis_network: VisNetworkSource::default(),
+            theme: PageTheme::default(),
+            timeline: None,
+            chunks: None,
+        }
+    }
+}
+
+/// The interactive viewer page. Markup lives in `templates/viewer.html`; the
+/// stylesheet and script are plain asset files inserted verbatim.
+#[derive(Template)]
+#[template(path = "viewer.html")]
+struct ViewerTemplate<'a> {
+    title: &'a str,
+    container_id: &'a str,
+    styles: &'a str,
+    theme: &'a str,
+    theme_css: Option<String>,
+    script: &'a str,
+    vis_network_url: Option<&'a str>,
+    vis_network_inline: Option<String>,
+    vis_timeline_url: Option<&'a str>,
+    vis_timeline_inline: Option<String>,
+    /// `graph`, or `timeline` for a page without the network
+    view: &'a str,
+    graph_data: Option<String>,
+    graph_chunks: Option<String>,
+    generated_at: Option<&'a str>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VisJsOptions {
     pub nodes: VisJsNodeOptions,
@@ -47,6 +157,8 @@ pub struct VisJsHierarchicalOptions {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VisJsPhysicsOptions {
     pub enabled: bool,
+    /// Always `repulsion`, the solver the `repulsion` settings belong to
+    pub solver: String,
     pub stabilization: VisJsStabilizationOptions,
     pub repulsion: VisJsRepulsionOptions,
 }
@@ -133,7 +245,12 @@ impl WebInterface {
                 "x": node.x,
                 "y": node.y,
                 "physics": node.physics,
-                "title": format!("Type: {:?}<br/>Confidence: {:.2}", node.node_type, node.metadata.confidence),
+                "title": format!(
+                    "Type: {:?}<br/>Confidence: {:.2}{}",
+                    node.node_type,
+                    node.metadata.confidence,
+                    sentiment_line(node.metadata.sentiment)
+                ),
                 "group": format!("{:?}", node.node_type).to_lowercase()
             })
         }).collect();
@@ -143,18 +260,35 @@ impl WebInterface {
     }
 
     fn prepare_vis_js_edges(&self, edges: &[crate::graph_builder::GraphEdge]) -> Result<String> {
-        let vis_edges: Vec<serde_json::Value> = edges.iter().map(|edge| {
+        let bends = crate::graph_builder::parallel_edge_bends(edges);
+        let vis_edges: Vec<serde_json::Value> = edges.iter().zip(bends).map(|(edge, bend)| {
             serde_json::json!({
                 "id": edge.id,
                 "from": edge.from,
                 "to": edge.to,
                 "label": edge.label,
-                "color": edge.color,
+                "color": match edge.opacity {
+                    Some(opacity) => serde_json::json!({ "color": edge.color, "opacity": opacity }),
+                    None => serde_json::json!(edge.color),
+                },
                 "width": edge.width,
                 "arrows": edge.arrows,
-                "title": format!("Type: {}<br/>Confidence: {:.2}", edge.metadata.relationship_type, edge.metadata.confidence),
-                "smooth": {
-                    "type": "continuous"
+                "dashes": if edge.dashes && !edge.dash_pattern.is_empty() {
+                    serde_json::json!(edge.dash_pattern)
+                } else {
+                    serde_json::json!(edge.dashes)
+                },
+                "title": format!(
+                    "Type: {}<br/>Confidence: {:.2}{}",
+                    edge.metadata.relationship_type,
+                    edge.metadata.confidence,
+                    sentiment_line(edge.metadata.sentiment)
+                ),
+                // Edges between the same two nodes curve apart
+                "smooth": if bend == 0.0 {
+                    serde_json::json!({ "type": "continuous" })
+                } else {
+                    serde_json::json!({ "type": if bend > 0.0 { "curvedCW" } else { "curvedCCW" }, "roundness": bend.abs() })
                 }
             })
         }).collect();
@@ -163,7 +297,7 @@ impl WebInterface {
             .map_err(|e| GraphError::WebInterface(format!("Failed to serialize edges: {}", e)))
     }
 
-    fn prepare_vis_js_options(&self, config: &GraphConfig) -> Result<String> {
+    fn prepare_vis_js_options(&self, config: &RenderConfig) -> Result<String> {
         let options = VisJsOptions {
             nodes: VisJsNodeOptions {
                 shape: "dot".to_string(),
@@ -206,13 +340,14 @@ impl WebInterface {
             },
             physics: VisJsPhysicsOptions {
                 enabled: config.physics.enabled,
+                solver: "repulsion".to_string(),
                 stabilization: VisJsStabilizationOptions {
                     enabled: config.physics.stabilization,
                     iterations: 1000,
                 },
                 repulsion: VisJsRepulsionOptions {
                     node_distance: config.physics.repulsion,
-                    central_gravity: 0.1,
+                    central_gravity: config.physics.central_gravity,
                     spring_length: config.physics.spring_length,
                     spring_constant: config.physics.spring_constant,
                 },
@@ -231,777 +366,191 @@ impl WebInterface {
     }
 
     pub fn create_html_template(&self, title: &str) -> String {
-        format!(r#"
-<!DOCTYPE html>
-<html lang="en">
-<head>
-    <meta charset="UTF-8">
-    <meta name="viewport" content="width=device-width, initial-scale=1.0">
-    <title>{}</title>
-    <script type="text/javascript" src="https://unpkg.com/vis-network/standalone/umd/vis-network.min.js"></script>
-    <style>
-        body {{
-            font-family: Arial, sans-serif;
-            margin: 0;
-            padding: 0;
-            background-color: #f5f5f5;
-            overflow: hidden;
-        }}
-        
-        .header {{
-            background-color: #2c3e50;
-            color: white;
-            padding: 15px 20px;
-            box-shadow: 0 2px 4px rgba(0,0,0,0.1);
-            z-index: 1000;
-            position: relative;
-        }}
-        
-        .main-container {{
-            display: flex;
-            height: calc(100vh - 70px);
-            position: relative;
-        }}
-        
-        .side-panel {{
-            width: 300px;
-            background-color: white;
-            box-shadow: 2px 0 4px rgba(0,0,0,0.1);
-            overflow-y: auto;
-            transition: transform 0.3s ease;
-            z-index: 100;
-        }}
-        
-        .side-panel.collapsed {{
-            transform: translateX(-100%);
-        }}
-        
-        .panel-toggle {{
-            position: absolute;
-            left: 10px;
-            top: 50%;
-            transform: translateY(-50%);
-            background-color: #34495e;
-            color: white;
-            border: none;
-            padding: 10px;
-            border-radius: 0 5px 5px 0;
-            cursor: pointer;
-            z-index: 200;
-            transition: all 0.3s ease;
-        }}
-        
-        .panel-toggle.active {{
-            background-color: #27ae60;
-            left: 310px;
-        }}
-        
-        .panel-toggle.collapsed {{
-            background-color: #e74c3c;
-            left: 10px;
-        }}
-        
-        .controls {{
-            padding: 20px;
-        }}
-        
-        .control-section {{
-            margin-bottom: 20px;
-            border: 1px solid #e0e0e0;
-            border-radius: 8px;
-            overflow: hidden;
-        }}
-        
-        .section-header {{
-            background-color: #ecf0f1;
-            padding: 12px 15px;
-            font-weight: bold;
-            cursor: pointer;
-            border-bottom: 1px solid #e0e0e0;
-            display: flex;
-            justify-content: space-between;
-            align-items: center;
-        }}
-        
-        .section-header:hover {{
-            background-color: #d5dbdb;
-        }}
-        
-        .section-content {{
-            padding: 15px;
-            display: none;
-        }}
-        
-        .section-content.expanded {{
-            display: block;
-        }}
-        
-        .control-group {{
-            margin-bottom: 15px;
-            padding: 10px;
-            background-color: #f9f9f9;
-            border-radius: 4px;
-        }}
-        
-        .control-group label {{
-            font-weight: bold;
-            margin-bottom: 8px;
-            display: block;
-        }}
-        
-        .graph-container {{
-            flex: 1;
-            background-color: white;
-            position: relative;
-        }}
-        
-        #{} {{
-            width: 100%;
-            height: 100%;
-            border: none;
-        }}
-        
-        .info-panel {{
-            position: absolute;
-            top: 20px;
-            right: 20px;
-            background-color: white;
-            padding: 15px;
-            border-radius: 8px;
-            box-shadow: 0 2px 4px rgba(0,0,0,0.1);
-            max-width: 300px;
-            min-width: 250px;
-            z-index: 50;
-            transition: transform 0.3s ease, opacity 0.3s ease;
-        }}
-        
-        .info-panel.collapsed {{
-            transform: translateX(calc(100% + 20px));
-            opacity: 0;
-        }}
-        
-        .info-panel h3 {{
-            margin-top: 0;
-            margin-bottom: 15px;
-            font-size: 16px;
-            color: #34495e;
-            border-bottom: 1px solid #e0e0e0;
-            padding-bottom: 8px;
-        }}
-        
-        .info-toggle {{
-            position: absolute;
-            top: 20px;
-            right: 20px;
-            background-color: #34495e;
-            color: white;
-            border: none;
-            padding: 12px;
-            border-radius: 5px;
-            cursor: pointer;
-            z-index: 60;
-            transition: all 0.3s ease;
-            font-size: 16px;
-            box-shadow: 0 2px 4px rgba(0,0,0,0.2);
-        }}
-        
-        .info-toggle.panel-open {{
-            right: 290px;
-            background-color: #27ae60;
-        }}
-        
-        .info-toggle:hover {{
-            background-color: #2c3e50;
-            transform: scale(1.05);
-        }}
-        
-        .info-toggle.panel-open:hover {{
-            background-color: #229954;
-            transform: scale(1.05);
-        }}
-        
-        button {{
-            background-color: #3498db;
-            color: white;
-            border: none;
-            padding: 8px 16px;
-            margin: 3px;
-            border-radius: 4px;
-            cursor: pointer;
-            transition: background-color 0.3s ease;
-        }}
-        
-        button:hover {{
-            background-color: #2980b9;
-        }}
-        
-        button.toggle-off {{
-            background-color: #e74c3c;
-        }}
-        
-        button.toggle-off:hover {{
-            background-color: #c0392b;
-        }}
-        
-        button.toggle-on {{
-            background-color: #27ae60;
-        }}
-        
-        button.toggle-on:hover {{
-            background-color: #229954;
-        }}
-        
-        select, input {{
-            padding: 8px;
-            margin: 3px;
-            border: 1px solid #ddd;
-            border-radius: 4px;
-            width: 100%;
-            box-sizing: border-box;
-        }}
-        
-        .node-info, .edge-info {{
-            background-color: #ecf0f1;
-            padding: 10px;
-            border-radius: 4px;
-            margin-top: 10px;
-            display: none;
-        }}
-        
-        .expand-icon {{
-            transition: transform 0.3s ease;
-        }}
-        
-        .expand-icon.rotated {{
-            transform: rotate(180deg);
-        }}
-    </style>
-</head>
-<body>
-    <div class="header">
-        <h1>{}</h1>
-        <p>Interactive Entity Relationship Graph Visualizer</p>
-    </div>
-    
-    <div class="main-container">
-        <button class="panel-toggle active" onclick="toggleSidePanel()">☰</button>
-        
-        <div class="side-panel" id="sidePanel">
-            <div class="controls">
-                <h3>Graph Controls</h3>
-                
-                <!-- Layout Controls -->
-                <div class="control-section">
-                    <div class="section-header" onclick="toggleSection('layout')">
-                        Layout Controls
-                        <span class="expand-icon">▼</span>
-                    </div>
-                    <div class="section-content expanded" id="layout">
-                        <div class="control-group">
-                            <label>Layout Type:</label>
-                            <button onclick="changeLayout('hierarchical')">Hierarchical</button>
-                            <button onclick="changeLayout('force')">Force-Directed</button>
-                            <button onclick="changeLayout('circular')">Circular</button>
-                        </div>
-                    </div>
-                </div>
-                
-                <!-- View Controls -->
-                <div class="control-section">
-                    <div class="section-header" onclick="toggleSection('view')">
-                        View Controls
-                        <span class="expand-icon">▼</span>
-                    </div>
-                    <div class="section-content expanded" id="view">
-                        <div class="control-group">
-                            <label>Zoom & Position:</label>
-                            <button onclick="zoomIn()">Zoom In</button>
-                            <button onclick="zoomOut()">Zoom Out</button>
-                            <button onclick="fitGraph()">Fit to View</button>
-                            <button onclick="centerGraph()">Center Graph</button>
-                        </div>
-                    </div>
-                </div>
-                
-                <!-- Physics Controls -->
-                <div class="control-section">
-                    <div class="section-header" onclick="toggleSection('physics')">
-                        Physics Controls
-                        <span class="expand-icon">▼</span>
-                    </div>
-                    <div class="section-content expanded" id="physics">
-                        <div class="control-group">
-                            <label>Physics Simulation:</label>
-                            <button id="physicsToggle" class="toggle-on" onclick="togglePhysics()">Physics: ON</button>
-                            <button onclick="stabilizeGraph()">Stabilize</button>
-                        </div>
-                    </div>
-                </div>
-                
-                <!-- Label Controls -->
-                <div class="control-section">
-                    <div class="section-header" onclick="toggleSection('labels')">
-                        Label Controls
-                        <span class="expand-icon">▼</span>
-                    </div>
-                    <div class="section-content" id="labels">
-                        <div class="control-group">
-                            <label>Label Visibility:</label>
-                            <button id="nodeLabelsToggle" class="toggle-on" onclick="toggleNodeLabels()">Node Labels: ON</button>
-                            <button id="edgeLabelsToggle" class="toggle-on" onclick="toggleEdgeLabels()">Edge Labels: ON</button>
-                        </div>
-                        <div class="control-group">
-                            <label>Node Uniqueness:</label>
-                            <button id="uniqueNodesToggle" class="toggle-on" onclick="toggleUniqueNodes()">Unique Nodes: ON</button>
-                        </div>
-                    </div>
-                </div>
-                
-                <!-- Filter Controls -->
-                <div class="control-section">
-                    <div class="section-header" onclick="toggleSection('filters')">
-                        Filter Controls
-                        <span class="expand-icon">▼</span>
-                    </div>
-                    <div class="section-content" id="filters">
-                        <div class="control-group">
-                            <label>Node Type Filter:</label>
-                            <select onchange="filterNodes(this.value)">
-                                <option value="">Show All Nodes</option>
-                                <option value="entity">Entities Only</option>
-                                <option value="concept">Concepts Only</option>
-                                <option value="attribute">Attributes Only</option>
-                            </select>
-                        </div>
-                    </div>
-                </div>
-                
-                <!-- Export Controls -->
-                <div class="control-section">
-                    <div class="section-header" onclick="toggleSection('export')">
-                        Export Controls
-                        <span class="expand-icon">▼</span>
-                    </div>
-                    <div class="section-content" id="export">
-                        <div class="control-group">
-                            <label>Export Options:</label>
-                            <button onclick="exportGraph('json')">Export JSON</button>
-                            <button onclick="exportGraph('png')">Export PNG</button>
-                        </div>
-                    </div>
-                </div>
-            </div>
-        </div>
-        
-        <div class="graph-container">
-            <div id="{}"></div>
-            
-            <button class="info-toggle panel-open" id="infoToggle" onclick="toggleInfoPanel()">ℹ️</button>
-            
-            <div class="info-panel" id="infoPanel">
-                <h3>Information Panel</h3>
-                <div id="node-info" class="node-info">
-                    <h4>Node Information</h4>
-                    <div id="node-details"></div>
-                </div>
-                <div id="edge-info" class="edge-info">
-                    <h4>Edge Information</h4>
-                    <div id="edge-details"></div>
-                </div>
-            </div>
-        </div>
-    </div>
-    
-    <script>
-        // Global variables
-        let currentNetwork = null;
-        let originalNodes = null;
-        let originalEdges = null;
-        let showNodeLabels = true;
-        let showEdgeLabels = true;
-        let physicsEnabled = true;
-        let sidePanelOpen = true;
-        let infoPanelOpen = true;
-        let uniqueNodesEnabled = true;
-        
-        // Side panel and section controls
-        function toggleSidePanel() {{
-            const panel = document.getElementById('sidePanel');
-            const toggle = document.querySelector('.panel-toggle');
-            
-            sidePanelOpen = !sidePanelOpen;
-            
-            if (sidePanelOpen) {{
-                panel.classList.remove('collapsed');
-                toggle.classList.add('active');
-                toggle.classList.remove('collapsed');
-                toggle.textContent = '☰';
-            }} else {{
-                panel.classList.add('collapsed');
-                toggle.classList.remove('active');
-                toggle.classList.add('collapsed');
-                toggle.textContent = '►';
-            }}
-        }}
-        
-        function toggleInfoPanel() {{
-            const panel = document.getElementById('infoPanel');
-            const toggle = document.getElementById('infoToggle');
-            
-            infoPanelOpen = !infoPanelOpen;
-            
-            if (infoPanelOpen) {{
-                panel.classList.remove('collapsed');
-                toggle.classList.add('panel-open');
-                toggle.textContent = 'ℹ️';
-            }} else {{
-                panel.classList.add('collapsed');
-                toggle.classList.remove('panel-open');
-                toggle.textContent = '►';
-            }}
-        }}
-        
-        function toggleSection(sectionId) {{
-            const content = document.getElementById(sectionId);
-            const header = content.previousElementSibling;
-            const icon = header.querySelector('.expand-icon');
-            
-            if (content.classList.contains('expanded')) {{
-                content.classList.remove('expanded');
-                icon.classList.remove('rotated');
-            }} else {{
-                content.classList.add('expanded');
-                icon.classList.add('rotated');
-            }}
-        }}
-        
-        function updateToggleButton(buttonId, isOn, onText, offText) {{
-            const button = document.getElementById(buttonId);
-            if (isOn) {{
-                button.className = 'toggle-on';
-                button.textContent = onText;
-            }} else {{
-                button.className = 'toggle-off';
-                button.textContent = offText;
-            }}
-        }}
-        
-        // Layout change function
-        function changeLayout(layoutType) {{
-            if (currentNetwork) {{
-                console.log('Changing layout to:', layoutType);
-                let layoutOptions = {{}};
-                
-                switch(layoutType) {{
-                    case 'hierarchical':
-                        layoutOptions = {{
-                            hierarchical: {{
-                                enabled: true,
-                                direction: 'UD',
-                                sortMethod: 'directed',
-                                nodeSpacing: 200,
-                                levelSeparation: 150
-                            }}
-                        }};
-                        break;
-                    case 'force':
-                        layoutOptions = {{
-                            hierarchical: {{ enabled: false }},
-                            randomSeed: Math.floor(Math.random() * 1000)
-                        }};
-                        break;
-                    case 'circular':
-                        // Implement circular layout
-                        const nodes = currentNetwork.body.data.nodes.get();
-                        const nodePositions = {{}};
-                        const centerX = 0, centerY = 0;
-                        const radius = Math.max(200, nodes.length * 20);
-                        
-                        nodes.forEach((node, index) => {{
-                            const angle = (2 * Math.PI * index) / nodes.length;
-                            nodePositions[node.id] = {{
-                                x: centerX + radius * Math.cos(angle),
-                                y: centerY + radius * Math.sin(angle)
-                            }};
-                        }});
-                        
-                        currentNetwork.setData({{
-                            nodes: nodes.map(n => ({{ ...n, ...nodePositions[n.id] }})),
-                            edges: currentNetwork.body.data.edges.get()
-                        }});
-                        return;
-                }}
-                
-                currentNetwork.setOptions({{ layout: layoutOptions }});
-                currentNetwork.stabilize();
-            }}
-        }}
-        
-        // View control functions
-        function zoomIn() {{
-            if (currentNetwork) {{
-                const scale = currentNetwork.getScale();
-                currentNetwork.moveTo({{ scale: scale * 1.2 }});
-            }}
-        }}
-        
-        function zoomOut() {{
-            if (currentNetwork) {{
-                const scale = currentNetwork.getScale();
-                currentNetwork.moveTo({{ scale: scale * 0.8 }});
-            }}
-        }}
-        
-        function fitGraph() {{
-            if (currentNetwork) {{
-                currentNetwork.fit({{ animation: true }});
-            }}
-        }}
-        
-        function centerGraph() {{
-            if (currentNetwork) {{
-                currentNetwork.moveTo({{ position: {{ x: 0, y: 0 }} }});
-            }}
-        }}
-        
-        // Physics control functions
-        function togglePhysics() {{
-            if (currentNetwork) {{
-                physicsEnabled = !physicsEnabled;
-                const physicsOptions = {{
-                    enabled: physicsEnabled,
-                    stabilization: {{
-                        enabled: window.graphData.config.physics.stabilization,
-                        iterations: 1000
-                    }},
-                    repulsion: {{
-                        nodeDistance: window.graphData.config.physics.repulsion,
-                        centralGravity: 0.1,
-                        springLength: window.graphData.config.physics.spring_length,
-                        springConstant: window.graphData.config.physics.spring_constant
-                    }}
-                }};
-                currentNetwork.setOptions({{ physics: physicsOptions }});
-                updateToggleButton('physicsToggle', physicsEnabled, 'Physics: ON', 'Physics: OFF');
-                console.log('Physics:', physicsEnabled ? 'enabled' : 'disabled');
-            }}
-        }}
-        
-        function stabilizeGraph() {{
-            if (currentNetwork) {{
-                currentNetwork.stabilize();
-                console.log('Graph stabilization initiated');
-                // Optional: Add a timeout to show when stabilization is complete
-                currentNetwork.once('stabilizationIterationsDone', function() {{
-                    console.log('Graph stabilization completed');
-                }});
-            }}
-        }}
-        
-        // Label control functions
-        function toggleNodeLabels() {{
-            if (currentNetwork && originalNodes) {{
-                showNodeLabels = !showNodeLabels;
-                const nodes = originalNodes.map(node => ({{
-                    ...node,
-                    label: showNodeLabels ? node.originalLabel || node.label : ''
-                }}));
-                
-                currentNetwork.setData({{
-                    nodes: nodes,
-                    edges: currentNetwork.body.data.edges.get()
-                }});
-                updateToggleButton('nodeLabelsToggle', showNodeLabels, 'Node Labels: ON', 'Node Labels: OFF');
-                console.log('Node labels:', showNodeLabels ? 'shown' : 'hidden');
-            }}
-        }}
-        
-        function toggleEdgeLabels() {{
-            if (currentNetwork && originalEdges) {{
-                showEdgeLabels = !showEdgeLabels;
-                const edges = originalEdges.map(edge => ({{
-                    ...edge,
-                    label: showEdgeLabels ? edge.originalLabel || edge.label : ''
-                }}));
-                
-                currentNetwork.setData({{
-                    nodes: currentNetwork.body.data.nodes.get(),
-                    edges: edges
-                }});
-                updateToggleButton('edgeLabelsToggle', showEdgeLabels, 'Edge Labels: ON', 'Edge Labels: OFF');
-                console.log('Edge labels:', showEdgeLabels ? 'shown' : 'hidden');
-            }}
-        }}
-        
-        // Node uniqueness control function
-        function toggleUniqueNodes() {{
-            if (currentNetwork && originalNodes && originalEdges) {{
-                uniqueNodesEnabled = !uniqueNodesEnabled;
-                
-                let nodesToDisplay = [];
-                let edgesToDisplay = [];
-                
-                if (uniqueNodesEnabled) {{
-                    // Show all unique nodes (original behavior)
-                    nodesToDisplay = originalNodes.map(node => ({{
-                        ...node,
-                        label: showNodeLabels ? node.originalLabel || node.label : ''
-                    }}));
-                    edgesToDisplay = originalEdges.map(edge => ({{
-                        ...edge,
-                        label: showEdgeLabels ? edge.originalLabel || edge.label : ''
-                    }}));
-                }} else {{
-                    // Consolidate nodes by label/name, but preserve all edges
-                    const nodeMap = new Map();
-                    const consolidatedNodes = [];
-                    
-                    // Group nodes by their original label to identify duplicates
-                    originalNodes.forEach(node => {{
-                        const key = (node.originalLabel || node.label).toLowerCase().trim();
-                        if (!nodeMap.has(key)) {{
-                            nodeMap.set(key, {{
-                                ...node,
-                                label: showNodeLabels ? node.originalLabel || node.label : '',
-                                consolidatedIds: [node.id] // Track all IDs this consolidated node represents
-                            }});
-                            consolidatedNodes.push(nodeMap.get(key));
-                        }} else {{
-                            // Add this node's ID to the consolidated node's ID list
-                            nodeMap.get(key).consolidatedIds.push(node.id);
-                        }}
-                    }});
-                    
-                    nodesToDisplay = consolidatedNodes;
-                    
-                    // Update edges to point to consolidated nodes
-                    edgesToDisplay = originalEdges.map(edge => {{
-                        let fromId = edge.from;
-                        let toId = edge.to;
-                        
-                        // Find consolidated nodes for from and to
-                        for (const [key, consolidatedNode] of nodeMap) {{
-                            if (consolidatedNode.consolidatedIds.includes(edge.from)) {{
-                                fromId = consolidatedNode.id;
-                            }}
-                            if (consolidatedNode.consolidatedIds.includes(edge.to)) {{
-                                toId = consolidatedNode.id;
-                            }}
-                        }}
-                        
-                        return {{
-                            ...edge,
-                            from: fromId,
-                            to: toId,
-                            label: showEdgeLabels ? edge.originalLabel || edge.label : ''
-                        }};
-                    }}).filter(edge => edge.from !== edge.to); // Remove self-loops that might be created
-                }}
-                
-                currentNetwork.setData({{
-                    nodes: nodesToDisplay,
-                    edges: edgesToDisplay
-                }});
-                
-                updateToggleButton('uniqueNodesToggle', uniqueNodesEnabled, 'Unique Nodes: ON', 'Unique Nodes: OFF');
-                console.log('Unique nodes:', uniqueNodesEnabled ? 'enabled' : 'disabled (consolidated)');
-            }}
-        }}
-        
-        // Node filtering function
-        function filterNodes(nodeType) {{
-            if (currentNetwork && originalNodes) {{
-                console.log('Filtering nodes by type:', nodeType);
-                
-                let filteredNodes = originalNodes;
-                let filteredEdges = originalEdges;
-                
-                if (nodeType) {{
-                    filteredNodes = originalNodes.filter(node => 
-                        node.group === nodeType || 
-                        node.node_type === nodeType || 
-                        node.type === nodeType
-                    );
-                    
-                    const nodeIds = new Set(filteredNodes.map(n => n.id));
-                    filteredEdges = originalEdges.filter(edge => 
-                        nodeIds.has(edge.from) && nodeIds.has(edge.to)
-                    );
-                }}
-                
-                currentNetwork.setData({{
-                    nodes: filteredNodes,
-                    edges: filteredEdges
-                }});
-            }}
-        }}
-        
-        // Export functions
-        function exportGraph(format) {{
-            console.log('Exporting graph as:', format);
-            
-            if (format === 'json') {{
-                const graphData = {{
-                    nodes: currentNetwork.body.data.nodes.get(),
-                    edges: currentNetwork.body.data.edges.get(),
-                    config: window.graphData.config
-                }};
-                
-                const dataStr = JSON.stringify(graphData, null, 2);
-                const dataBlob = new Blob([dataStr], {{ type: 'application/json' }});
-                const url = URL.createObjectURL(dataBlob);
-                
-                const link = document.createElement('a');
-                link.href = url;
-                link.download = 'graph_export.json';
-                link.click();
-                
-                URL.revokeObjectURL(url);
-            }} else if (format === 'png') {{
-                // Note: PNG export requires additional vis.js configuration
-                console.log('PNG export not implemented in this version');
-                alert('PNG export requires server-side rendering. Use browser screenshot instead.');
-            }}
-        }}
-        
-        // Node and edge selection handlers
-        function onNodeSelected(nodeId) {{
-            console.log('Node selected:', nodeId);
-            const nodeData = currentNetwork.body.data.nodes.get(nodeId);
-            
-            document.getElementById('node-info').style.display = 'block';
-            document.getElementById('edge-info').style.display = 'none';
-            
-            if (nodeData) {{
-                document.getElementById('node-details').innerHTML = `
-                    <strong>ID:</strong> ${{nodeData.id}}<br/>
-                    <strong>Label:</strong> ${{nodeData.label}}<br/>
-                    <strong>Type:</strong> ${{nodeData.node_type || nodeData.group || 'Unknown'}}<br/>
-                    <strong>Confidence:</strong> ${{nodeData.confidence || 'N/A'}}
-                `;
-            }}
-        }}
-        
-        function onEdgeSelected(edgeId) {{
-            console.log('Edge selected:', edgeId);
-            const edgeData = currentNetwork.body.data.edges.get(edgeId);
-            
-            document.getElementById('edge-info').style.display = 'block';
-            document.getElementById('node-info').style.display = 'none';
-            
-            if (edgeData) {{
-                document.getElementById('edge-details').innerHTML = `
-                    <strong>ID:</strong> ${{edgeData.id}}<br/>
-                    <strong>From:</strong> ${{edgeData.from}}<br/>
-                    <strong>To:</strong> ${{edgeData.to}}<br/>
-                    <strong>Label:</strong> ${{edgeData.label}}<br/>
-                    <strong>Type:</strong> ${{edgeData.relationship_type || 'Unknown'}}
-                `;
-            }}
-        }}
-    </script>
-</body>
-</html>
-        "#, title, self.container_id, title, self.container_id)
+        let options = PageOptions { title: title.to_string(), ..PageOptions::default() };
+        self.render_page(None, &options)
+            .expect("Failed to render HTML template")
+    }
+
+    /// Render the viewer page. `graph` is embedded for the viewer script to load; a page
+    /// without one reads a share link, or loads `options.chunks`.
+    pub fn render_page(&self, graph: Option<&InteractiveGraph>, options: &PageOptions) -> Result<String> {
+        let graph_data = graph.map(|graph| serde_json::to_string(&Self::page_data(graph))).transpose()?;
+        let graph_chunks = match &options.chunks {
+            Some(chunks) => Some(format!(r#"{{"base":{},"manifest":{}}}"#, serde_json::to_string(&chunks.base)?, chunks.manifest)),
+            None => None,
+        };
+        let script_source = |source: &VisNetworkSource| match source {
+            VisNetworkSource::Url(url) => (Some(url.clone()), None),
+            VisNetworkSource::Inline(source) => (None, Some(source.replace("</script", "<\\/script"))),
+        };
+        let (vis_network_url, vis_network_inline) = script_source(&options.vis_network);
+        let (vis_timeline_url, vis_timeline_inline) =
+            options.timeline.as_ref().map(|timeline| script_source(&timeline.bundle)).unwrap_or_default();
+        let template = ViewerTemplate {
+            title: &options.title,
+            container_id: &self.container_id,
+            styles: VIEWER_CSS,
+            theme: options.theme.palette,
+            // A stylesheet could otherwise close the <style> block
+            theme_css: options.theme.css.as_ref().map(|css| css.replace("</", "<\\/")),
+            script: VIEWER_JS,
+            vis_network_url: vis_network_url.as_deref(),
+            vis_network_inline,
+            vis_timeline_url: vis_timeline_url.as_deref(),
+            vis_timeline_inline,
+            view: if options.timeline.as_ref().is_some_and(|timeline| timeline.only) { "timeline" } else { "graph" },
+            // "</" inside a <script> block would terminate it early
+            graph_data: graph_data.map(|data| data.replace("</", "<\\/")),
+            graph_chunks: graph_chunks.map(|data| data.replace("</", "<\\/")),
+            generated_at: options.generated_at.as_deref(),
+        };
+
+        template
+            .render()
+            .map_err(|e| GraphError::WebInterface(format!("Failed to render HTML template: {}", e)))
+    }
+
+    /// What the viewer script loads: the graph and, for large graphs, the community of
+    /// each node (in node order) for the zoomed-out cluster nodes
+    fn page_data(graph: &InteractiveGraph) -> serde_json::Value {
+        let mut data = serde_json::json!({
+            "schema_version": graph.schema_version,
+            "nodes": graph.nodes,
+            "edges": graph.edges,
+            "config": graph.config,
+            "stats": graph.stats(),
+            "metadata": graph.metadata
+        });
+        let lod = &graph.config.lod;
+        if lod.enabled && graph.nodes.len() >= lod.min_nodes {
+            data["lod"] = serde_json::json!({ "clusters": detect_communities(graph) });
+        }
+        data
+    }
+}
+
+/// Tooltip line for a sentiment score, empty when the item wasn't scored
+fn sentiment_line(sentiment: Option<f64>) -> String {
+    sentiment.map(|score| format!("<br/>Sentiment: {:+.2}", score)).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity_extractor::EntityExtractor;
+    use crate::graph_builder::GraphBuilder;
+    use crate::text_processor::{SourceType, TextProcessor};
+
+    async fn sample_graph() -> InteractiveGraph {
+        let text = "Alice Smith works at Initech in Berlin.";
+        let processed = TextProcessor::new().unwrap().process_text(text, SourceType::Document).unwrap();
+        let extraction = EntityExtractor::default().extract_from_text(&processed).await.unwrap();
+        GraphBuilder::default().build_graph(&extraction, text).unwrap()
+    }
+
+    /// The data-less page with default options
+    fn render_default() -> String {
+        WebInterface::new("graph".to_string())
+            .render_page(None, &PageOptions::default())
+            .expect("Failed to render template")
+    }
+
+    #[test]
+    fn test_title_with_braces_is_escaped() {
+        let html = WebInterface::new("graph".to_string()).create_html_template("Notes {draft} <v2>");
+
+        assert!(html.contains("<title>Notes {draft} &lt;v2&gt;</title>"));
+        assert!(!html.contains("window.graphData = "));
+    }
+
+    #[tokio::test]
+    async fn test_embedded_data_cannot_close_script_tag() {
+        let mut graph = sample_graph().await;
+        graph.nodes[0].label = "}} </script><b>x</b>".to_string();
+        let html = WebInterface::new("graph".to_string())
+            .render_page(Some(&graph), &PageOptions::default())
+            .expect("Failed to render template");
+
+        assert!(html.contains(r#"window.graphData = {"#));
+        assert!(html.contains(r#""label":"}} <\/script><b>x<\/b>""#));
+        assert!(!html.contains("</script><b>"));
+        assert!(html.contains(r#"<div id="graph" class="network-canvas"></div>"#));
+    }
+
+    #[test]
+    fn test_generated_at_is_shown_in_header() {
+        let options = PageOptions { generated_at: Some("05 März 2024".to_string()), ..PageOptions::default() };
+        let html = WebInterface::new("graph".to_string())
+            .render_page(None, &options)
+            .expect("Failed to render template");
+        assert!(html.contains("<p class=\"generated-at\">Generated 05 März 2024</p>"));
+
+        assert!(!render_default().contains("<p class=\"generated-at\">"));
+    }
+
+    #[test]
+    fn test_physics_panel_has_sliders_and_copy_button() {
+        let html = render_default();
+        for slider in ["repulsionSlider", "springLengthSlider", "springConstantSlider", "gravitySlider"] {
+            assert!(html.contains(&format!("<input type=\"range\" id=\"{}\"", slider)), "{}", slider);
+        }
+        assert!(html.contains("onclick=\"copyPhysicsSettings()\""));
+        assert!(html.contains("function physicsSnippet()"));
+    }
+
+    #[test]
+    fn test_export_controls_offer_png_and_svg() {
+        let html = render_default();
+        assert!(html.contains("onclick=\"exportGraph('png')\""));
+        assert!(html.contains("onclick=\"exportGraph('svg')\""));
+        assert!(!html.contains("not implemented"));
+    }
+
+    #[test]
+    fn test_legend_lists_node_types_with_checkboxes() {
+        let html = render_default();
+        assert!(html.contains("<div id=\"legend-items\" class=\"legend-items\"></div>"));
+        assert!(html.contains("onclick=\"setAllLegendTypes(false)\""));
+        assert!(html.contains("function buildLegend()"));
+        assert!(html.contains("onchange=\"toggleLegendType(this.dataset.type, this.checked)\""));
+    }
+
+    #[test]
+    fn test_data_less_viewer_reads_share_links() {
+        let html = render_default();
+        assert!(html.contains("location.hash.startsWith('#g=')"));
+        assert!(html.contains("new DecompressionStream('deflate-raw')"));
+    }
+
+    #[test]
+    fn test_chunked_page_embeds_the_manifest_only() {
+        let manifest = r#"{"version":1,"chunks":[{"top_labels":["</script>"]}]}"#;
+        let options = PageOptions {
+            chunks: Some(PageChunks { manifest: manifest.to_string(), base: "big graph_chunks/".to_string() }),
+            ..PageOptions::default()
+        };
+        let html = WebInterface::new("graph".to_string())
+            .render_page(None, &options)
+            .expect("Failed to render template");
+        assert!(html.contains(r#"window.graphChunks = {"base":"big graph_chunks/","manifest":{"version":1,"chunks":[{"top_labels":["<\/script>"]}]}};"#));
+        assert!(!html.contains("window.graphData = "));
+        assert!(html.contains("function initializeChunkedGraph()"));
+    }
+
+    #[test]
+    fn test_inline_vis_network_replaces_cdn_script() {
+        let options = PageOptions {
+            vis_network: VisNetworkSource::Inline("var vis = {}; // </script> inside a string".to_string()),
+            ..PageOptions::default()
+        };
+        let html = WebInterface::new("graph".to_string())
+            .render_page(None, &options)
+            .expect("Failed to render template");
+
+        assert!(!html.contains("<script type=\"text/javascript\" src="));
+        assert!(html.contains("var vis = {}; // <\\/script> inside a string"));
+
+        let html = render_default();
+        assert!(html.contains(&format!("<script type=\"text/javascript\" src=\"{}\"></script>", DEFAULT_VIS_NETWORK_URL)));
     }
 }
@@ -0,0 +1,1857 @@
+// Global variables
+let currentNetwork = null;
+let originalNodes = null;
+let originalEdges = null;
+let showNodeLabels = true;
+let showEdgeLabels = true;
+let physicsEnabled = true;
+let sidePanelOpen = true;
+let infoPanelOpen = true;
+let uniqueNodesEnabled = true;
+// Physics settings as in GraphConfig.physics, changed by the sliders
+let physicsSettings = null;
+
+// Slider element ids for each physics setting
+const PHYSICS_SLIDERS = {
+    repulsion: 'repulsion',
+    spring_length: 'springLength',
+    spring_constant: 'springConstant',
+    central_gravity: 'gravity'
+};
+
+// Side panel and section controls
+function toggleSidePanel() {
+    const panel = document.getElementById('sidePanel');
+    const toggle = document.querySelector('.panel-toggle');
+
+    sidePanelOpen = !sidePanelOpen;
+
+    if (sidePanelOpen) {
+        panel.classList.remove('collapsed');
+        toggle.classList.add('active');
+        toggle.classList.remove('collapsed');
+        toggle.textContent = '☰';
+    } else {
+        panel.classList.add('collapsed');
+        toggle.classList.remove('active');
+        toggle.classList.add('collapsed');
+        toggle.textContent = '►';
+    }
+}
+
+function toggleInfoPanel() {
+    const panel = document.getElementById('infoPanel');
+    const toggle = document.getElementById('infoToggle');
+
+    infoPanelOpen = !infoPanelOpen;
+
+    if (infoPanelOpen) {
+        panel.classList.remove('collapsed');
+        toggle.classList.add('panel-open');
+        toggle.textContent = 'ℹ️';
+    } else {
+        panel.classList.add('collapsed');
+        toggle.classList.remove('panel-open');
+        toggle.textContent = '►';
+    }
+}
+
+function toggleSection(sectionId) {
+    const content = document.getElementById(sectionId);
+    const header = content.previousElementSibling;
+    const icon = header.querySelector('.expand-icon');
+
+    if (content.classList.contains('expanded')) {
+        content.classList.remove('expanded');
+        icon.classList.remove('rotated');
+    } else {
+        content.classList.add('expanded');
+        icon.classList.add('rotated');
+    }
+}
+
+function updateToggleButton(buttonId, isOn, onText, offText) {
+    const button = document.getElementById(buttonId);
+    if (isOn) {
+        button.className = 'toggle-on';
+        button.textContent = onText;
+    } else {
+        button.className = 'toggle-off';
+        button.textContent = offText;
+    }
+}
+
+// Top-down layered layout following edge directions, used by the button and by graphs
+// laid out as a tree (hierarchy mode)
+function hierarchicalLayoutOptions() {
+    return {
+        hierarchical: {
+            enabled: true,
+            direction: 'UD',
+            sortMethod: 'directed',
+            nodeSpacing: 200,
+            levelSeparation: 150
+        }
+    };
+}
+
+// Layout change function
+function changeLayout(layoutType) {
+    if (currentNetwork) {
+        console.log('Changing layout to:', layoutType);
+        let layoutOptions = {};
+
+        switch(layoutType) {
+            case 'hierarchical':
+                layoutOptions = hierarchicalLayoutOptions();
+                break;
+            case 'force':
+                layoutOptions = {
+                    hierarchical: { enabled: false },
+                    randomSeed: Math.floor(Math.random() * 1000)
+                };
+                break;
+            case 'circular':
+                // Implement circular layout
+                const nodes = currentNetwork.body.data.nodes.get();
+                const nodePositions = {};
+                const centerX = 0, centerY = 0;
+                const radius = Math.max(200, nodes.length * 20);
+
+                nodes.forEach((node, index) => {
+                    const angle = (2 * Math.PI * index) / nodes.length;
+                    nodePositions[node.id] = {
+                        x: centerX + radius * Math.cos(angle),
+                        y: centerY + radius * Math.sin(angle)
+                    };
+                });
+
+                currentNetwork.setData({
+                    nodes: nodes.map(n => ({ ...n, ...nodePositions[n.id] })),
+                    edges: currentNetwork.body.data.edges.get()
+                });
+                return;
+        }
+
+        currentNetwork.setOptions({ layout: layoutOptions });
+        currentNetwork.stabilize();
+    }
+}
+
+// View control functions
+function zoomIn() {
+    if (currentNetwork) {
+        const scale = currentNetwork.getScale();
+        currentNetwork.moveTo({ scale: scale * 1.2 });
+    }
+}
+
+function zoomOut() {
+    if (currentNetwork) {
+        const scale = currentNetwork.getScale();
+        currentNetwork.moveTo({ scale: scale * 0.8 });
+    }
+}
+
+function fitGraph() {
+    if (currentNetwork) {
+        currentNetwork.fit({ animation: true });
+    }
+}
+
+function centerGraph() {
+    if (currentNetwork) {
+        currentNetwork.moveTo({ position: { x: 0, y: 0 } });
+    }
+}
+
+// Physics control functions
+function physicsOptions() {
+    return {
+        enabled: physicsEnabled,
+        solver: 'repulsion',
+        stabilization: {
+            enabled: physicsSettings.stabilization,
+            iterations: 1000
+        },
+        repulsion: {
+            nodeDistance: physicsSettings.repulsion,
+            centralGravity: physicsSettings.central_gravity,
+            springLength: physicsSettings.spring_length,
+            springConstant: physicsSettings.spring_constant
+        }
+    };
+}
+
+function togglePhysics() {
+    if (currentNetwork) {
+        physicsEnabled = !physicsEnabled;
+        currentNetwork.setOptions({ physics: physicsOptions() });
+        updateToggleButton('physicsToggle', physicsEnabled, 'Physics: ON', 'Physics: OFF');
+        console.log('Physics:', physicsEnabled ? 'enabled' : 'disabled');
+    }
+}
+
+// Show the current settings on the sliders
+function initPhysicsSliders() {
+    for (const [key, id] of Object.entries(PHYSICS_SLIDERS)) {
+        document.getElementById(id + 'Slider').value = physicsSettings[key];
+        document.getElementById(id + 'Value').textContent = physicsSettings[key];
+    }
+}
+
+function updatePhysics(key, value) {
+    physicsSettings[key] = parseFloat(value);
+    document.getElementById(PHYSICS_SLIDERS[key] + 'Value').textContent = physicsSettings[key];
+    if (currentNetwork) {
+        currentNetwork.setOptions({ physics: physicsOptions() });
+        if (physicsEnabled) {
+            currentNetwork.startSimulation();
+        }
+    }
+}
+
+// `"physics": {...}` to paste into a config file
+function physicsSnippet() {
+    const physics = {
+        enabled: physicsEnabled,
+        stabilization: physicsSettings.stabilization,
+        repulsion: physicsSettings.repulsion,
+        spring_length: physicsSettings.spring_length,
+        spring_constant: physicsSettings.spring_constant,
+        central_gravity: physicsSettings.central_gravity
+    };
+    return '"physics": ' + JSON.stringify(physics, null, 2);
+}
+
+function copyPhysicsSettings() {
+    const snippet = physicsSnippet();
+    const status = document.getElementById('copyPhysicsStatus');
+    const copied = () => { status.textContent = 'Copied!'; };
+    // The clipboard API is missing on some file:// pages; fall back to a selected textarea
+    const fallback = () => {
+        const textarea = document.createElement('textarea');
+        textarea.value = snippet;
+        document.body.appendChild(textarea);
+        textarea.select();
+        const ok = document.execCommand('copy');
+        document.body.removeChild(textarea);
+        if (ok) {
+            copied();
+        } else {
+            window.prompt('Copy the physics settings:', snippet);
+        }
+    };
+    if (navigator.clipboard && navigator.clipboard.writeText) {
+        navigator.clipboard.writeText(snippet).then(copied, fallback);
+    } else {
+        fallback();
+    }
+}
+
+function stabilizeGraph() {
+    if (currentNetwork) {
+        currentNetwork.stabilize();
+        console.log('Graph stabilization initiated');
+        // Optional: Add a timeout to show when stabilization is complete
+        currentNetwork.once('stabilizationIterationsDone', function() {
+            console.log('Graph stabilization completed');
+        });
+    }
+}
+
+// Label control functions
+function toggleNodeLabels() {
+    if (currentNetwork && originalNodes) {
+        showNodeLabels = !showNodeLabels;
+        const nodes = originalNodes.map(node => ({
+            ...node,
+            label: showNodeLabels ? node.originalLabel || node.label : ''
+        }));
+
+        currentNetwork.setData({
+            nodes: nodes,
+            edges: currentNetwork.body.data.edges.get()
+        });
+        updateToggleButton('nodeLabelsToggle', showNodeLabels, 'Node Labels: ON', 'Node Labels: OFF');
+        console.log('Node labels:', showNodeLabels ? 'shown' : 'hidden');
+    }
+}
+
+function toggleEdgeLabels() {
+    if (currentNetwork && originalEdges) {
+        showEdgeLabels = !showEdgeLabels;
+        const edges = originalEdges.map(edge => ({
+            ...edge,
+            label: showEdgeLabels ? edge.originalLabel || edge.label : ''
+        }));
+
+        currentNetwork.setData({
+            nodes: currentNetwork.body.data.nodes.get(),
+            edges: edges
+        });
+        updateToggleButton('edgeLabelsToggle', showEdgeLabels, 'Edge Labels: ON', 'Edge Labels: OFF');
+        console.log('Edge labels:', showEdgeLabels ? 'shown' : 'hidden');
+    }
+}
+
+// Node uniqueness control function
+function toggleUniqueNodes() {
+    if (currentNetwork && originalNodes && originalEdges) {
+        uniqueNodesEnabled = !uniqueNodesEnabled;
+
+        let nodesToDisplay = [];
+        let edgesToDisplay = [];
+
+        if (uniqueNodesEnabled) {
+            // Show all unique nodes (original behavior)
+            nodesToDisplay = originalNodes.map(node => ({
+                ...node,
+                label: showNodeLabels ? node.originalLabel || node.label : ''
+            }));
+            edgesToDisplay = originalEdges.map(edge => ({
+                ...edge,
+                label: showEdgeLabels ? edge.originalLabel || edge.label : ''
+            }));
+        } else {
+            // Consolidate nodes by label/name, but preserve all edges
+            const nodeMap = new Map();
+            const consolidatedNodes = [];
+
+            // Group nodes by their original label to identify duplicates
+            originalNodes.forEach(node => {
+                const key = (node.originalLabel || node.label).toLowerCase().trim();
+                if (!nodeMap.has(key)) {
+                    nodeMap.set(key, {
+                        ...node,
+                        label: showNodeLabels ? node.originalLabel || node.label : '',
+                        consolidatedIds: [node.id] // Track all IDs this consolidated node represents
+                    });
+                    consolidatedNodes.push(nodeMap.get(key));
+                } else {
+                    // Add this node's ID to the consolidated node's ID list
+                    nodeMap.get(key).consolidatedIds.push(node.id);
+                }
+            });
+
+            nodesToDisplay = consolidatedNodes;
+
+            // Update edges to point to consolidated nodes
+            edgesToDisplay = originalEdges.map(edge => {
+                let fromId = edge.from;
+                let toId = edge.to;
+
+                // Find consolidated nodes for from and to
+                for (const [key, consolidatedNode] of nodeMap) {
+                    if (consolidatedNode.consolidatedIds.includes(edge.from)) {
+                        fromId = consolidatedNode.id;
+                    }
+                    if (consolidatedNode.consolidatedIds.includes(edge.to)) {
+                        toId = consolidatedNode.id;
+                    }
+                }
+
+                return {
+                    ...edge,
+                    from: fromId,
+                    to: toId,
+                    label: showEdgeLabels ? edge.originalLabel || edge.label : ''
+                };
+            }).filter(edge => edge.from !== edge.to); // Remove self-loops that might be created
+        }
+
+        currentNetwork.setData({
+            nodes: nodesToDisplay,
+            edges: edgesToDisplay
+        });
+
+        if (lodState) {
+            applyLod(true);
+        }
+        updateLegendCounts();
+        updateToggleButton('uniqueNodesToggle', uniqueNodesEnabled, 'Unique Nodes: ON', 'Unique Nodes: OFF');
+        console.log('Unique nodes:', uniqueNodesEnabled ? 'enabled' : 'disabled (consolidated)');
+    }
+}
+
+// Node type chosen in the filter dropdown ('' for all) and legend types unticked
+let nodeTypeFilter = '';
+const hiddenLegendTypes = new Set();
+
+// Node filtering function
+function filterNodes(nodeType) {
+    nodeTypeFilter = nodeType;
+    applyNodeFilters();
+}
+
+// Show the nodes passing both the dropdown and the legend, and the edges between them
+function applyNodeFilters() {
+    if (currentNetwork && originalNodes) {
+        console.log('Filtering nodes by type:', nodeTypeFilter, 'hiding:', [...hiddenLegendTypes]);
+
+        let filteredNodes = originalNodes;
+        let filteredEdges = originalEdges;
+
+        if (nodeTypeFilter || hiddenLegendTypes.size > 0) {
+            filteredNodes = originalNodes.filter(node =>
+                (!nodeTypeFilter ||
+                    node.group === nodeTypeFilter ||
+                    node.node_type === nodeTypeFilter ||
+                    node.type === nodeTypeFilter) &&
+                !hiddenLegendTypes.has(legendType(node))
+            );
+
+            const nodeIds = new Set(filteredNodes.map(n => n.id));
+            filteredEdges = originalEdges.filter(edge => 
+                nodeIds.has(edge.from) && nodeIds.has(edge.to)
+            );
+        }
+
+        currentNetwork.setData({
+            nodes: filteredNodes,
+            edges: filteredEdges
+        });
+        if (lodState) {
+            applyLod(true);
+        }
+        updateLegendCounts();
+    }
+}
+
+// Legend row a node belongs to: its entity type for entities ("Person", or the name of
+// a custom type), otherwise its node type
+function legendType(node) {
+    if (node.node_type === 'Entity' && node.entity_type) {
+        const custom = /^Other\("(.*)"\)$/.exec(node.entity_type);
+        return custom ? custom[1] : node.entity_type;
+    }
+    return node.node_type;
+}
+
+// The value most nodes of a legend row share, for its swatch
+function mostCommon(values) {
+    const counts = new Map();
+    values.forEach(value => counts.set(value, (counts.get(value) || 0) + 1));
+    return [...counts].sort((a, b) => b[1] - a[1])[0][0];
+}
+
+// One legend row per node type with its usual color and shape, a checkbox to hide the
+// type and a count of its nodes on screen
+function buildLegend() {
+    const container = document.getElementById('legend-items');
+    const groups = new Map();
+    originalNodes.forEach(node => {
+        const type = legendType(node);
+        if (!groups.has(type)) {
+            groups.set(type, []);
+        }
+        groups.get(type).push(node);
+    });
+    if (groups.size === 0) {
+        document.getElementById('legendSection').style.display = 'none';
+        return;
+    }
+    container.innerHTML = [...groups]
+        .sort((a, b) => b[1].length - a[1].length || a[0].localeCompare(b[0]))
+        .map(([type, nodes]) => {
+            const color = mostCommon(nodes.map(node => typeof node.color === 'string' ? node.color : '#97C2FC'));
+            const shape = mostCommon(nodes.map(node => node.shape || 'ellipse'));
+            return `<label class="legend-item" title="${escapeHtml(shape)}">
+                <input type="checkbox" checked data-type="${escapeHtml(type)}" onchange="toggleLegendType(this.dataset.type, this.checked)">
+                <span class="legend-swatch legend-${escapeHtml(shape)}" style="background: ${escapeHtml(color)}"></span>
+                <span class="legend-name">${escapeHtml(type)}</span>
+                <span class="legend-count" data-type="${escapeHtml(type)}"></span>
+            </label>`;
+        })
+        .join('');
+    updateLegendCounts();
+}
+
+function toggleLegendType(type, visible) {
+    if (visible) {
+        hiddenLegendTypes.delete(type);
+    } else {
+        hiddenLegendTypes.add(type);
+    }
+    applyNodeFilters();
+}
+
+// Tick or untick every legend row at once
+function setAllLegendTypes(visible) {
+    document.querySelectorAll('#legend-items input[type="checkbox"]').forEach(box => {
+        box.checked = visible;
+        if (visible) {
+            hiddenLegendTypes.delete(box.dataset.type);
+        } else {
+            hiddenLegendTypes.add(box.dataset.type);
+        }
+    });
+    applyNodeFilters();
+}
+
+// "shown / total" for each legend row
+function updateLegendCounts() {
+    if (!originalNodes) {
+        return;
+    }
+    const totals = new Map();
+    originalNodes.forEach(node => totals.set(legendType(node), (totals.get(legendType(node)) || 0) + 1));
+    const shown = new Map();
+    currentNetwork.body.data.nodes
+        .get({ filter: node => node.lodCluster === undefined })
+        .forEach(node => shown.set(legendType(node), (shown.get(legendType(node)) || 0) + 1));
+    document.querySelectorAll('#legend-items .legend-count').forEach(count => {
+        const type = count.dataset.type;
+        count.textContent = `${shown.get(type) || 0} / ${totals.get(type) || 0}`;
+    });
+}
+
+// Export functions
+function exportGraph(format) {
+    console.log('Exporting graph as:', format);
+
+    if (format === 'json') {
+        // Cluster nodes and links drawn for level of detail are not part of the graph
+        const graphData = {
+            nodes: currentNetwork.body.data.nodes.get({ filter: node => node.lodCluster === undefined }),
+            edges: currentNetwork.body.data.edges.get({ filter: edge => !edge.lodSummary }),
+            config: (window.graphData || window.graphChunks.manifest).config
+        };
+
+        const dataStr = JSON.stringify(graphData, null, 2);
+        downloadBlob(new Blob([dataStr], { type: 'application/json' }), 'graph_export.json');
+    } else if (format === 'png') {
+        exportPng();
+    } else if (format === 'svg') {
+        const svgBlob = new Blob([graphToSvg()], { type: 'image/svg+xml' });
+        downloadBlob(svgBlob, 'graph_export.svg');
+    }
+}
+
+function downloadBlob(blob, filename) {
+    const url = URL.createObjectURL(blob);
+    const link = document.createElement('a');
+    link.href = url;
+    link.download = filename;
+    link.click();
+    URL.revokeObjectURL(url);
+}
+
+// The vis.js canvas is transparent, so draw it over the page background before saving
+function exportPng() {
+    const source = currentNetwork.canvas.frame.canvas;
+    const canvas = document.createElement('canvas');
+    canvas.width = source.width;
+    canvas.height = source.height;
+    const context = canvas.getContext('2d');
+    context.fillStyle = getComputedStyle(source.parentElement).backgroundColor || '#ffffff';
+    if (context.fillStyle === 'rgba(0, 0, 0, 0)') {
+        context.fillStyle = '#ffffff';
+    }
+    context.fillRect(0, 0, canvas.width, canvas.height);
+    context.drawImage(source, 0, 0);
+    canvas.toBlob(blob => downloadBlob(blob, 'graph_export.png'), 'image/png');
+}
+
+// vis.js colors are either a string or { background, border }
+function colorOf(color, fallback) {
+    if (!color) {
+        return fallback;
+    }
+    return typeof color === 'string' ? color : (color.background || color.color || fallback);
+}
+
+// Shapes vis.js draws their label inside of; the rest have it underneath
+const LABEL_INSIDE_SHAPES = ['box', 'ellipse', 'circle', 'database', 'text'];
+
+// Rebuild the visible graph as SVG from the current node positions, so the export is
+// vector and every label stays selectable. Edges are drawn straight.
+function graphToSvg() {
+    const nodes = currentNetwork.body.data.nodes.get().filter(node => !node.hidden);
+    const visible = new Set(nodes.map(node => node.id));
+    const edges = currentNetwork.body.data.edges.get()
+        .filter(edge => !edge.hidden && visible.has(edge.from) && visible.has(edge.to));
+    const positions = currentNetwork.getPositions(nodes.map(node => node.id));
+    const boxes = {};
+    let minX = Infinity, minY = Infinity, maxX = -Infinity, maxY = -Infinity;
+    nodes.forEach(node => {
+        // The bounding box includes a label drawn underneath, so those shapes use their size
+        const box = currentNetwork.getBoundingBox(node.id);
+        const { x, y } = positions[node.id];
+        const size = node.size || 25;
+        boxes[node.id] = LABEL_INSIDE_SHAPES.includes(node.shape)
+            ? box
+            : { left: x - size, right: x + size, top: y - size, bottom: y + size };
+        minX = Math.min(minX, box.left);
+        minY = Math.min(minY, box.top);
+        maxX = Math.max(maxX, box.right);
+        maxY = Math.max(maxY, box.bottom);
+    });
+    if (nodes.length === 0) {
+        minX = minY = 0;
+        maxX = maxY = 100;
+    }
+    const margin = 40;
+    const width = maxX - minX + 2 * margin;
+    const height = maxY - minY + 2 * margin;
+    const parts = [
+        `<svg xmlns="http://www.w3.org/2000/svg" width="${width}" height="${height}" viewBox="${minX - margin} ${minY - margin} ${width} ${height}">`,
+        '<defs><marker id="arrow" viewBox="0 0 10 10" refX="10" refY="5" markerWidth="8" markerHeight="8" orient="auto-start-reverse">' +
+            '<path d="M 0 0 L 10 5 L 0 10 z" fill="context-stroke"/></marker></defs>',
+        `<rect x="${minX - margin}" y="${minY - margin}" width="${width}" height="${height}" fill="#ffffff"/>`
+    ];
+
+    edges.forEach(edge => {
+        const from = positions[edge.from];
+        const to = positions[edge.to];
+        const color = colorOf(edge.color, '#848484');
+        const arrows = String(edge.arrows || '');
+        const markers = (arrows.includes('to') ? ' marker-end="url(#arrow)"' : '') +
+            (arrows.includes('from') ? ' marker-start="url(#arrow)"' : '');
+        // End the line at the node border so the arrowhead stays visible
+        const trim = (point, other, id) => {
+            const box = boxes[id];
+            const radius = Math.min(box.right - box.left, box.bottom - box.top) / 2;
+            const length = Math.hypot(other.x - point.x, other.y - point.y) || 1;
+            return { x: point.x + (other.x - point.x) * radius / length, y: point.y + (other.y - point.y) * radius / length };
+        };
+        const dashArray = edge.dashes
+            ? ` stroke-dasharray="${Array.isArray(edge.dashes) ? edge.dashes.join(' ') : '6 4'}"` : '';
+        const opacity = edge.color && edge.color.opacity != null ? ` stroke-opacity="${edge.color.opacity}"` : '';
+        const style = `stroke="${escapeHtml(color)}" stroke-width="${edge.width || 2}"${dashArray}${opacity}${markers}`;
+        if (edge.bend) {
+            // Parallel edges: a quadratic curve whose control point is pushed sideways
+            const control = {
+                x: (from.x + to.x) / 2 - (to.y - from.y) * edge.bend,
+                y: (from.y + to.y) / 2 + (to.x - from.x) * edge.bend
+            };
+            const start = trim(from, control, edge.from);
+            const end = trim(to, control, edge.to);
+            parts.push(`<path d="M ${start.x} ${start.y} Q ${control.x} ${control.y} ${end.x} ${end.y}" fill="none" ${style}/>`);
+            if (edge.label) {
+                parts.push(`<text x="${(from.x + to.x) / 4 + control.x / 2}" y="${(from.y + to.y) / 4 + control.y / 2}" ` +
+                    `font-family="arial" font-size="12" fill="#343434" text-anchor="middle">${escapeHtml(edge.label)}</text>`);
+            }
+            return;
+        }
+        const start = trim(from, to, edge.from);
+        const end = trim(to, from, edge.to);
+        parts.push(`<line x1="${start.x}" y1="${start.y}" x2="${end.x}" y2="${end.y}" ${style}/>`);
+        if (edge.label) {
+            parts.push(`<text x="${(from.x + to.x) / 2}" y="${(from.y + to.y) / 2}" font-family="arial" font-size="12" ` +
+                `fill="#343434" text-anchor="middle">${escapeHtml(edge.label)}</text>`);
+        }
+    });
+
+    nodes.forEach(node => {
+        const box = boxes[node.id];
+        const { x, y } = positions[node.id];
+        const fill = escapeHtml(colorOf(node.color, '#97c2fc'));
+        const rx = (box.right - box.left) / 2;
+        const ry = (box.bottom - box.top) / 2;
+        parts.push(`<g class="node">`);
+        if (node.shape === 'box' || node.shape === 'square') {
+            parts.push(`<rect x="${box.left}" y="${box.top}" width="${2 * rx}" height="${2 * ry}" rx="4" fill="${fill}" stroke="#2b2b2b"/>`);
+        } else if (node.shape === 'diamond') {
+            parts.push(`<polygon points="${x},${box.top} ${box.right},${y} ${x},${box.bottom} ${box.left},${y}" fill="${fill}" stroke="#2b2b2b"/>`);
+        } else {
+            parts.push(`<ellipse cx="${x}" cy="${y}" rx="${rx}" ry="${ry}" fill="${fill}" stroke="#2b2b2b"/>`);
+        }
+        if (node.label) {
+            const inside = LABEL_INSIDE_SHAPES.includes(node.shape);
+            parts.push(`<text x="${x}" y="${inside ? y + 5 : box.bottom + 16}" font-family="arial" font-size="14" ` +
+                `fill="#343434" text-anchor="middle">${escapeHtml(node.label)}</text>`);
+        }
+        parts.push('</g>');
+    });
+
+    parts.push('</svg>');
+    return parts.join('\n');
+}
+
+// Escape user-provided text before inserting it as HTML
+function escapeHtml(value) {
+    return String(value)
+        .replace(/&/g, '&amp;')
+        .replace(/</g, '&lt;')
+        .replace(/>/g, '&gt;')
+        .replace(/"/g, '&quot;')
+        .replace(/'/g, '&#39;');
+}
+
+// Node and edge selection handlers
+function onNodeSelected(nodeId) {
+    console.log('Node selected:', nodeId);
+    const nodeData = currentNetwork
+        ? currentNetwork.body.data.nodes.get(nodeId)
+        : originalNodes.find(node => node.id === nodeId);
+
+    document.getElementById('node-info').style.display = 'block';
+    document.getElementById('edge-info').style.display = 'none';
+
+    if (nodeData) {
+        document.getElementById('node-details').innerHTML = `
+            <strong>ID:</strong> ${escapeHtml(nodeData.id)}<br/>
+            <strong>Label:</strong> ${escapeHtml(nodeData.label)}<br/>
+            <strong>Type:</strong> ${escapeHtml(nodeData.node_type || nodeData.group || 'Unknown')}<br/>
+            <strong>Confidence:</strong> ${escapeHtml(nodeData.confidence || 'N/A')}
+            ${nodeLinksHtml(nodeData.links)}
+            ${nodeData.sources && nodeData.sources.length ? `<br/><strong>Sources:</strong> ${nodeData.sources.map(escapeHtml).join(', ')}` : ''}
+            ${nodeData.disambiguation ? `<br/><strong>Sense:</strong> ${escapeHtml(nodeData.disambiguation.sense)} (1 of ${escapeHtml(nodeData.disambiguation.senses)}, by ${escapeHtml(nodeData.disambiguation.method)})` : ''}
+        `;
+    }
+}
+
+// Links from the node's attributes; only web and email addresses are made clickable
+function nodeLinksHtml(links) {
+    const safe = (links || []).filter(link => /^(https?:|mailto:)/i.test(link.url));
+    if (safe.length === 0) {
+        return '';
+    }
+    const items = safe.map(link =>
+        `<a href="${escapeHtml(link.url)}" target="_blank" rel="noopener noreferrer">${escapeHtml(link.name)}</a>`);
+    return `<br/><strong>Links:</strong> ${items.join(', ')}`;
+}
+
+function onEdgeSelected(edgeId) {
+    console.log('Edge selected:', edgeId);
+    const edgeData = currentNetwork.body.data.edges.get(edgeId);
+
+    document.getElementById('edge-info').style.display = 'block';
+    document.getElementById('node-info').style.display = 'none';
+
+    if (edgeData) {
+        document.getElementById('edge-details').innerHTML = `
+            <strong>ID:</strong> ${escapeHtml(edgeData.id)}<br/>
+            <strong>From:</strong> ${escapeHtml(edgeData.from)}<br/>
+            <strong>To:</strong> ${escapeHtml(edgeData.to)}<br/>
+            <strong>Label:</strong> ${escapeHtml(edgeData.label)}<br/>
+            <strong>Type:</strong> ${escapeHtml(edgeData.relationship_type || 'Unknown')}
+            ${edgeData.category ? `<br/><strong>Category:</strong> ${escapeHtml(edgeData.category)}` : ''}
+            ${edgeData.context ? `<br/><strong>Context:</strong> ${escapeHtml(edgeData.context)}` : ''}
+        `;
+    }
+}
+
+// Structure summary computed when the graph was exported
+function showGraphStats(stats) {
+    const panel = document.getElementById('graph-stats');
+    if (!stats) {
+        panel.style.display = 'none';
+        return;
+    }
+    document.getElementById('graph-stats-details').innerHTML = `
+        <strong>Nodes:</strong> ${stats.nodes} &nbsp; <strong>Edges:</strong> ${stats.edges}<br/>
+        <strong>Density:</strong> ${stats.density.toFixed(4)}<br/>
+        <strong>Average degree:</strong> ${stats.average_degree.toFixed(2)}<br/>
+        <strong>Components:</strong> ${stats.connected_components}<br/>
+        <strong>Diameter:</strong> ${stats.diameter} (largest component)<br/>
+        <strong>Isolated nodes:</strong> ${stats.isolated_nodes}
+    `;
+}
+
+// "About this graph": how the graph was made, from the exported metadata
+function showGraphAbout(metadata, config) {
+    const panel = document.getElementById('graph-about');
+    const provenance = metadata && metadata.provenance;
+    if (!provenance || !provenance.extraction_method) {
+        panel.style.display = 'none';
+        return;
+    }
+    const counts = types => Object.entries(types || {})
+        .sort(([a], [b]) => a.localeCompare(b))
+        .map(([type, count]) => `${escapeHtml(type)} ${count}`)
+        .join(', ') || 'none';
+    const settings = Object.entries(provenance.config_summary || {})
+        .map(([key, value]) => `<strong>${escapeHtml(key)}:</strong> ${escapeHtml(value)}`)
+        .join('<br/>');
+    document.getElementById('graph-about-details').innerHTML = `
+        <strong>Extraction:</strong> ${escapeHtml(provenance.extraction_method)}<br/>
+        ${provenance.llm_model ? `<strong>Model:</strong> ${escapeHtml(provenance.llm_model)}<br/>` : ''}
+        <strong>Created:</strong> ${escapeHtml(metadata.creation_timestamp)}
+        (${provenance.processing_time_ms} ms extraction, msg_net ${escapeHtml(provenance.version)})<br/>
+        <strong>Input:</strong> ${metadata.source_text_length} bytes,
+        SHA-256 <span class="graph-about-hash" title="${escapeHtml(provenance.input_hash)}">${escapeHtml(provenance.input_hash.slice(0, 16))}…</span><br/>
+        <strong>Nodes by type:</strong> ${counts(metadata.node_types)}<br/>
+        <strong>Edges by type:</strong> ${counts(metadata.edge_types)}
+        <details>
+            <summary>Configuration${config && config.config_id ? ` <code>${escapeHtml(config.config_id)}</code>` : ''}</summary>
+            ${settings}
+        </details>
+    `;
+}
+
+// Palettes: the page's data-theme is `light`, `dark` or `auto` (the browser's setting).
+// Node and edge label colors follow it, and so do the neutral grays of the default
+// styling; configured node colors are kept on both.
+const NETWORK_LABEL_COLORS = {
+    light: { font: '#343434', stroke: '#ffffff' },
+    dark: { font: '#e3e5e8', stroke: '#17181a' }
+};
+const DARK_PALETTE_COLORS = { '#CCCCCC': '#5F6368', '#888888': '#9AA0A6', '#BBBBBB': '#6B6F76' };
+
+function isDarkTheme() {
+    const theme = document.documentElement.dataset.theme;
+    return theme === 'dark' ||
+        (theme === 'auto' && !!window.matchMedia && window.matchMedia('(prefers-color-scheme: dark)').matches);
+}
+
+// `color` as drawn on the current palette
+function themedColor(color) {
+    if (!isDarkTheme() || typeof color !== 'string') {
+        return color;
+    }
+    return DARK_PALETTE_COLORS[color.toUpperCase()] || color;
+}
+
+// vis.js color of an edge; opacity needs the object form
+function visEdgeColor(color, opacity) {
+    return opacity == null ? themedColor(color) : { color: themedColor(color), opacity: opacity };
+}
+
+function labelOptions() {
+    const labels = NETWORK_LABEL_COLORS[isDarkTheme() ? 'dark' : 'light'];
+    return {
+        nodes: { font: { color: labels.font } },
+        edges: { font: { color: labels.font, strokeColor: labels.stroke } }
+    };
+}
+
+// An `auto` page follows the browser when its color scheme changes
+if (window.matchMedia) {
+    window.matchMedia('(prefers-color-scheme: dark)').addEventListener('change', () => {
+        if (document.documentElement.dataset.theme === 'auto') {
+            applyTheme();
+        }
+    });
+}
+
+// Switch between the light and dark palettes, recoloring the labels and default grays
+function toggleTheme() {
+    document.documentElement.dataset.theme = isDarkTheme() ? 'light' : 'dark';
+    applyTheme();
+}
+
+function applyTheme() {
+    if (!currentNetwork) {
+        return;
+    }
+    currentNetwork.setOptions(labelOptions());
+    (originalNodes || []).forEach(node => { node.color = themedColor(node.baseColor); });
+    (originalEdges || []).forEach(edge => { edge.color = visEdgeColor(edge.baseColor, edge.baseOpacity); });
+    const data = currentNetwork.body.data;
+    data.nodes.update(data.nodes.get({ filter: node => node.baseColor !== undefined })
+        .map(node => ({ id: node.id, color: themedColor(node.baseColor) })));
+    data.edges.update(data.edges.get({ filter: edge => edge.baseColor !== undefined })
+        .map(edge => ({ id: edge.id, color: visEdgeColor(edge.baseColor, edge.baseOpacity) })));
+}
+
+// Graph nodes and edges as the viewer's vis.js items
+function toVisNode(node) {
+    const label = node.badge ? `${node.label} ${node.badge}` : node.label;
+    return {
+        id: node.id,
+        label: label,
+        originalLabel: label, // Store original label for toggle functionality
+        color: themedColor(node.color),
+        baseColor: node.color,
+        shape: node.shape,
+        size: node.size,
+        x: node.x,
+        y: node.y,
+        physics: node.physics,
+        title: `Type: ${node.node_type}<br/>Confidence: ${node.metadata.confidence.toFixed(2)}`,
+        group: node.node_type.toLowerCase(),
+        node_type: node.node_type,
+        confidence: node.metadata.confidence,
+        links: node.metadata.links || [],
+        sources: node.metadata.sources || [],
+        disambiguation: node.metadata.disambiguation,
+        entity_type: node.metadata.entity_type
+    };
+}
+
+function toVisEdge(edge) {
+    return {
+        id: edge.id,
+        from: edge.from,
+        to: edge.to,
+        label: edge.label,
+        originalLabel: edge.label, // Store original label for toggle functionality
+        color: visEdgeColor(edge.color, edge.opacity),
+        baseColor: edge.color,
+        baseOpacity: edge.opacity,
+        width: edge.width,
+        arrows: edge.arrows,
+        dashes: edge.dashes && edge.dash_pattern && edge.dash_pattern.length ? edge.dash_pattern : edge.dashes,
+        title: edgeTitle(edge),
+        smooth: { type: "continuous" },
+        relationship_type: edge.metadata.relationship_type,
+        category: edge.metadata.category,
+        context: edge.metadata.context
+    };
+}
+
+// Tooltip of an edge, listing every relationship folded into a merged one
+function edgeTitle(edge) {
+    const merged = edge.metadata.merged || [];
+    if (merged.length === 0) {
+        return `Type: ${edge.metadata.relationship_type}<br/>Confidence: ${edge.metadata.confidence.toFixed(2)}`;
+    }
+    return merged.map(relation => `${relation.reversed ? '← ' : ''}${relation.label} ` +
+        `(${relation.relationship_type}, ${relation.confidence.toFixed(2)})`).join('<br/>');
+}
+
+// Bend of the outermost edges between the same two nodes, as in graph_builder.rs
+const PARALLEL_EDGE_BEND = 0.4;
+
+// Bend edges between the same two nodes apart so each label stays readable; `bend` is
+// kept on the edge for the SVG snapshot
+function curveParallelEdges(visEdges) {
+    const groups = new Map();
+    visEdges.forEach(edge => {
+        const key = edge.from <= edge.to ? `${edge.from}\u0000${edge.to}` : `${edge.to}\u0000${edge.from}`;
+        if (!groups.has(key)) {
+            groups.set(key, []);
+        }
+        groups.get(key).push(edge);
+    });
+    groups.forEach(members => {
+        if (members.length < 2) {
+            return;
+        }
+        const middle = (members.length - 1) / 2;
+        members.forEach((edge, position) => {
+            const bend = (position - middle) * PARALLEL_EDGE_BEND / middle;
+            // Measured from the pair's first node, so a reversed edge flips sign
+            edge.bend = edge.from <= edge.to ? bend : -bend;
+            if (edge.bend !== 0) {
+                edge.smooth = { type: edge.bend > 0 ? 'curvedCW' : 'curvedCCW', roundness: Math.abs(edge.bend) };
+            }
+        });
+    });
+    return visEdges;
+}
+
+// vis.js options other than physics
+function networkOptions() {
+    return {
+        nodes: {
+            shape: 'dot',
+            size: 25,
+            font: {
+                size: 14,
+                color: labelOptions().nodes.font.color,
+                face: 'arial'
+            },
+            borderWidth: 2,
+            shadow: true
+        },
+        edges: {
+            font: labelOptions().edges.font,
+            width: 2,
+            arrows: {
+                to: {
+                    enabled: true,
+                    scaleFactor: 1
+                }
+            },
+            smooth: true,
+            shadow: true
+        },
+        interaction: {
+            dragNodes: true,
+            dragView: true,
+            zoomView: true,
+            selectConnectedEdges: true,
+            multiselect: true,
+            hover: true
+        }
+    };
+}
+
+function initializeGraph() {
+    // Sync physics state with config; graphs saved before gravity was configurable use 0.1
+    physicsEnabled = window.graphData.config.physics.enabled;
+    physicsSettings = { central_gravity: 0.1, ...window.graphData.config.physics };
+
+    const container = document.getElementById(document.body.dataset.containerId);
+    const nodes = new vis.DataSet(window.graphData.nodes.map(toVisNode));
+    const edges = new vis.DataSet(curveParallelEdges(window.graphData.edges.map(toVisEdge)));
+
+    // Store original data globally for filtering and label toggling
+    originalNodes = nodes.get();
+    originalEdges = edges.get();
+
+    const data = { nodes: nodes, edges: edges };
+
+    const options = { ...networkOptions(), physics: physicsOptions() };
+    if (window.graphData.config.layout && window.graphData.config.layout.algorithm === 'tree') {
+        options.layout = hierarchicalLayoutOptions();
+    }
+
+    // Assign to the global variable (not window.currentNetwork)
+    currentNetwork = new vis.Network(container, data, options);
+
+    // Set up event listeners
+    currentNetwork.on('selectNode', function(params) {
+        onNodeSelected(params.nodes[0]);
+    });
+
+    currentNetwork.on('selectEdge', function(params) {
+        onEdgeSelected(params.edges[0]);
+    });
+
+    currentNetwork.on('select', function(params) {
+        selectTimelineNodes(params.nodes);
+    });
+
+    showGraphStats(window.graphData.stats);
+    showGraphAbout(window.graphData.metadata, window.graphData.config);
+    if (window.graphData.lod) {
+        initializeLod(window.graphData.lod);
+    }
+    buildLegend();
+    initializeTimeline();
+    initializeEditing();
+
+    // Initialize toggle button and slider states
+    initPhysicsSliders();
+    updateToggleButton('physicsToggle', physicsEnabled, 'Physics: ON', 'Physics: OFF');
+    updateToggleButton('nodeLabelsToggle', showNodeLabels, 'Node Labels: ON', 'Node Labels: OFF');
+    updateToggleButton('edgeLabelsToggle', showEdgeLabels, 'Edge Labels: ON', 'Edge Labels: OFF');
+    updateToggleButton('uniqueNodesToggle', uniqueNodesEnabled, 'Unique Nodes: ON', 'Unique Nodes: OFF');
+
+    console.log('Graph initialized successfully');
+}
+
+// Level of detail for large graphs: zoomed out, each community is drawn as one cluster
+// node and only the heavier links between clusters are kept; members appear as the view
+// zooms in, attribute nodes last
+let lodState = null;
+
+function lodNodeId(cluster) {
+    return `lod:${cluster}`;
+}
+
+function lodLevel() {
+    if (!lodState.enabled) {
+        return 'all';
+    }
+    const scale = currentNetwork.getScale();
+    if (scale < lodState.config.cluster_scale) {
+        return 'clusters';
+    }
+    return scale < lodState.config.detail_scale ? 'members' : 'all';
+}
+
+// Cluster nodes at their members' centroid, labelled with the best connected member
+function lodSummaries(members, memberEdges) {
+    const degree = new Map();
+    memberEdges.forEach(edge => {
+        degree.set(edge.from, (degree.get(edge.from) || 0) + 1);
+        degree.set(edge.to, (degree.get(edge.to) || 0) + 1);
+    });
+    const groups = new Map();
+    members.forEach(node => {
+        const cluster = lodState.clusters.get(node.id);
+        if (!groups.has(cluster)) {
+            groups.set(cluster, []);
+        }
+        groups.get(cluster).push(node);
+    });
+
+    const positions = currentNetwork.getPositions(members.map(node => node.id));
+    const nodes = [...groups].map(([cluster, group]) => {
+        group.sort((a, b) => (degree.get(b.id) || 0) - (degree.get(a.id) || 0));
+        const top = group.slice(0, 5).map(node => node.originalLabel || node.label);
+        const more = group.length > top.length ? ', ...' : '';
+        const label = group.length > 1 ? `${top[0]} +${group.length - 1}` : top[0];
+        return {
+            id: lodNodeId(cluster),
+            label: showNodeLabels ? label : '',
+            originalLabel: label,
+            title: `${group.length} nodes<br/>${escapeHtml(top.join(', '))}${more}<br/>Zoom in or double-click to expand`,
+            x: group.reduce((sum, node) => sum + positions[node.id].x, 0) / group.length,
+            y: group.reduce((sum, node) => sum + positions[node.id].y, 0) / group.length,
+            size: 15 + 5 * Math.sqrt(group.length),
+            shape: 'dot',
+            color: group[0].color,
+            physics: false,
+            lodCluster: cluster
+        };
+    });
+
+    const links = new Map();
+    memberEdges.forEach(edge => {
+        const from = lodState.clusters.get(edge.from);
+        const to = lodState.clusters.get(edge.to);
+        if (from !== to) {
+            const key = from < to ? `${from}:${to}` : `${to}:${from}`;
+            links.set(key, (links.get(key) || 0) + 1);
+        }
+    });
+    const edges = [...links]
+        .filter(([, count]) => count >= lodState.config.min_link_edges)
+        .map(([key, count]) => {
+            const [from, to] = key.split(':');
+            return {
+                id: `lod-link:${key}`,
+                from: lodNodeId(from),
+                to: lodNodeId(to),
+                width: 1 + Math.log2(count),
+                title: `${count} edges`,
+                color: '#bbbbbb',
+                arrows: '',
+                lodSummary: true
+            };
+        });
+    return { nodes, edges };
+}
+
+// Swap between cluster nodes and members when the zoom crosses a threshold; `force`
+// redraws after the viewer replaced its data (filters, node uniqueness)
+function applyLod(force) {
+    const level = lodLevel();
+    if (level === lodState.level && !force) {
+        return;
+    }
+    lodState.level = level;
+    const nodes = currentNetwork.body.data.nodes;
+    const edges = currentNetwork.body.data.edges;
+    nodes.remove(nodes.getIds({ filter: node => node.lodCluster !== undefined }));
+    edges.remove(edges.getIds({ filter: edge => edge.lodSummary }));
+    const members = nodes.get();
+    const memberEdges = edges.get();
+
+    if (level === 'clusters') {
+        const summaries = lodSummaries(members, memberEdges);
+        nodes.update(members.map(node => ({ id: node.id, hidden: true })));
+        edges.update(memberEdges.map(edge => ({ id: edge.id, hidden: true })));
+        nodes.add(summaries.nodes);
+        edges.add(summaries.edges);
+    } else {
+        const hidden = new Set(level === 'members'
+            ? members.filter(node => node.group === 'attribute').map(node => node.id)
+            : []);
+        nodes.update(members.map(node => ({ id: node.id, hidden: hidden.has(node.id) })));
+        edges.update(memberEdges.map(edge => ({ id: edge.id, hidden: hidden.has(edge.from) || hidden.has(edge.to) })));
+    }
+    showLodStatus();
+}
+
+function showLodStatus() {
+    let status = document.getElementById('lod-status');
+    if (!status) {
+        status = document.createElement('div');
+        status.id = 'lod-status';
+        document.getElementById('graph-stats').appendChild(status);
+    }
+    const levels = {
+        clusters: `${lodState.communities} clusters; zoom in to show their members`,
+        members: 'members; zoom in further to show attribute nodes',
+        all: 'every node'
+    };
+    status.innerHTML = `<strong>Showing:</strong> ${levels[lodState.level]}`;
+}
+
+function toggleLod() {
+    lodState.enabled = !lodState.enabled;
+    applyLod();
+    updateToggleButton('lodToggle', lodState.enabled, 'Level of Detail: ON', 'Level of Detail: OFF');
+}
+
+function initializeLod(lod) {
+    const clusters = new Map(window.graphData.nodes.map((node, index) => [node.id, lod.clusters[index]]));
+    lodState = {
+        clusters,
+        communities: new Set(lod.clusters).size,
+        config: window.graphData.config.lod,
+        enabled: true,
+        level: null
+    };
+
+    currentNetwork.on('doubleClick', params => {
+        const node = params.nodes.length ? currentNetwork.body.data.nodes.get(params.nodes[0]) : null;
+        if (node && node.lodCluster !== undefined) {
+            currentNetwork.moveTo({
+                position: currentNetwork.getPosition(node.id),
+                scale: (lodState.config.cluster_scale + lodState.config.detail_scale) / 2,
+                animation: true
+            });
+        }
+    });
+    let pending = null;
+    const schedule = () => {
+        clearTimeout(pending);
+        pending = setTimeout(() => applyLod(), 150);
+    };
+    ['zoom', 'animationFinished'].forEach(event => currentNetwork.on(event, schedule));
+
+    const toggle = document.getElementById('lodToggle');
+    toggle.hidden = false;
+    updateToggleButton('lodToggle', lodState.enabled, 'Level of Detail: ON', 'Level of Detail: OFF');
+    // Cluster centroids need the members laid out first
+    if (physicsEnabled && physicsSettings.stabilization) {
+        currentNetwork.once('stabilizationIterationsDone', () => applyLod(true));
+    } else {
+        applyLod(true);
+    }
+}
+
+// Explain an empty graph instead of showing a blank canvas
+function showEmptyGraphMessage() {
+    const container = document.getElementById(document.body.dataset.containerId);
+    container.innerHTML = `
+        <div class="empty-graph">
+            <h2>No entities or concepts were found</h2>
+            <p>Things to try when generating the graph:</p>
+            <ul>
+                <li>Keep stopwords with <code>--no-remove-stopwords</code></li>
+                <li>Loosen <code>extraction.entity_patterns</code>; the default only matches Capitalized Words</li>
+                <li>List known names in <code>extraction.gazetteer_files</code></li>
+                <li>Extract with an LLM using <code>--use-llm</code></li>
+            </ul>
+        </div>
+    `;
+}
+
+// Graphs shared as links carry their data deflated and base64url-encoded in the
+// fragment (#g=...); see share_payload in src/share.rs for the layout
+async function loadSharedGraph(encoded) {
+    const base64 = encoded.replace(/-/g, '+').replace(/_/g, '/');
+    const binary = atob(base64 + '='.repeat((4 - base64.length % 4) % 4));
+    const bytes = Uint8Array.from(binary, c => c.charCodeAt(0));
+    const stream = new Blob([bytes]).stream().pipeThrough(new DecompressionStream('deflate-raw'));
+    const payload = await new Response(stream).json();
+    if (payload.v !== 1) {
+        throw new Error(`unsupported share link version ${payload.v}`);
+    }
+    return {
+        nodes: payload.n.map(([label, nodeType, color, shape, size, confidence], index) => ({
+            id: `n${index}`, label, node_type: nodeType, color, shape, size, physics: true,
+            metadata: { confidence }
+        })),
+        edges: payload.e.map(([from, to, label, relationshipType, color, width, confidence, dashes, arrows], index) => ({
+            id: `e${index}`, from: `n${from}`, to: `n${to}`, label, color, width, dashes, arrows,
+            metadata: { confidence, relationship_type: relationshipType }
+        })),
+        config: { physics: payload.p },
+        stats: null
+    };
+}
+
+function showSharedGraphError(error) {
+    const container = document.getElementById(document.body.dataset.containerId);
+    container.innerHTML = `
+        <div class="empty-graph">
+            <h2>This share link could not be opened</h2>
+            <p>${escapeHtml(error.message)}. The link may have been cut off when it was copied.</p>
+        </div>
+    `;
+}
+
+// Chunked exports embed a manifest of chunks (see src/graph_chunks.rs). Each chunk is
+// drawn as one summary node until it is zoomed into view; then its file is fetched and
+// its nodes, laid out in advance, take the summary node's place
+let chunkState = null;
+
+function chunkNodeId(id) {
+    return `chunk:${id}`;
+}
+
+function chunkLinkId(link) {
+    return `link:${link.from}:${link.to}`;
+}
+
+function chunkSummaryNode(chunk) {
+    const first = chunk.top_labels[0] || `Chunk ${chunk.id}`;
+    const more = chunk.nodes > chunk.top_labels.length ? ', ...' : '';
+    return {
+        id: chunkNodeId(chunk.id),
+        label: chunk.nodes > 1 ? `${first} +${chunk.nodes - 1}` : first,
+        title: `${chunk.nodes} nodes, ${chunk.edges} edges<br/>${chunk.top_labels.join(', ')}${more}<br/>Zoom in or double-click to load`,
+        x: chunk.x,
+        y: chunk.y,
+        size: chunk.radius * 0.6,
+        shape: 'dot',
+        color: chunk.color || '#97c2fc',
+        physics: false,
+        chunkId: chunk.id
+    };
+}
+
+function chunkLinkEdge(link) {
+    return {
+        id: chunkLinkId(link),
+        from: chunkNodeId(link.from),
+        to: chunkNodeId(link.to),
+        width: 1 + Math.log2(link.edges),
+        title: `${link.edges} edges`,
+        color: themedColor('#bbbbbb'),
+        baseColor: '#bbbbbb',
+        arrows: ''
+    };
+}
+
+function showChunkStatus() {
+    let status = document.getElementById('chunk-status');
+    if (!status) {
+        status = document.createElement('div');
+        status.id = 'chunk-status';
+        document.getElementById('graph-stats').appendChild(status);
+    }
+    const total = chunkState.manifest.chunks.length;
+    const loading = chunkState.loading.size ? `, loading ${chunkState.loading.size}` : '';
+    status.innerHTML = `<strong>Chunks loaded:</strong> ${chunkState.loaded.size} of ${total}${loading}` +
+        (chunkState.error ? `<p class="chunk-error">${escapeHtml(chunkState.error)}</p>` : '');
+}
+
+async function loadChunk(chunk) {
+    if (chunkState.loaded.has(chunk.id) || chunkState.loading.has(chunk.id)) {
+        return;
+    }
+    chunkState.loading.add(chunk.id);
+    showChunkStatus();
+    try {
+        const response = await fetch(chunkState.base + chunk.file);
+        if (!response.ok) {
+            throw new Error(`${chunk.file}: HTTP ${response.status}`);
+        }
+        const data = await response.json();
+        showChunk(chunk, data);
+        chunkState.error = null;
+    } catch (error) {
+        // Pages opened from disk may not fetch files next to them
+        chunkState.error = `Chunk ${chunk.file} could not be loaded (${error.message}). Browsers block this ` +
+            'on pages opened from disk; serve the folder over HTTP, e.g. python3 -m http.server, and open the page from there.';
+    } finally {
+        chunkState.loading.delete(chunk.id);
+        showChunkStatus();
+    }
+}
+
+function showChunk(chunk, data) {
+    const nodes = currentNetwork.body.data.nodes;
+    const edges = currentNetwork.body.data.edges;
+    chunkState.loaded.set(chunk.id, data);
+    nodes.remove(chunkNodeId(chunk.id));
+    edges.remove(chunkState.manifest.links.filter(link => link.from === chunk.id || link.to === chunk.id).map(chunkLinkId));
+    nodes.add(data.nodes.map(toVisNode));
+    // Edges to chunks that are not loaded wait for the copy in the other chunk's file
+    edges.update(curveParallelEdges(data.edges.filter(edge => nodes.get(edge.from) && nodes.get(edge.to)).map(toVisEdge)));
+}
+
+function unloadChunk(chunk) {
+    const nodes = currentNetwork.body.data.nodes;
+    const edges = currentNetwork.body.data.edges;
+    const data = chunkState.loaded.get(chunk.id);
+    chunkState.loaded.delete(chunk.id);
+    edges.remove(data.edges.map(edge => edge.id));
+    nodes.remove(data.nodes.map(node => node.id));
+    nodes.add(chunkSummaryNode(chunk));
+    edges.update(chunkState.manifest.links
+        .filter(link => link.from === chunk.id || link.to === chunk.id)
+        .filter(link => !chunkState.loaded.has(link.from === chunk.id ? link.to : link.from))
+        .map(chunkLinkEdge));
+    showChunkStatus();
+}
+
+// Load the chunks in view that are drawn large enough, unload those out of view or
+// zoomed out below half that size
+function refreshChunks() {
+    const manifest = chunkState.manifest;
+    if (manifest.chunks.length === 1) {
+        loadChunk(manifest.chunks[0]);
+        return;
+    }
+    const scale = currentNetwork.getScale();
+    const canvas = currentNetwork.canvas.frame.canvas;
+    const topLeft = currentNetwork.DOMtoCanvas({ x: 0, y: 0 });
+    const bottomRight = currentNetwork.DOMtoCanvas({ x: canvas.clientWidth, y: canvas.clientHeight });
+    manifest.chunks.forEach(chunk => {
+        const visible = chunk.x + chunk.radius >= topLeft.x && chunk.x - chunk.radius <= bottomRight.x &&
+            chunk.y + chunk.radius >= topLeft.y && chunk.y - chunk.radius <= bottomRight.y;
+        const onScreen = chunk.radius * scale;
+        if (visible && onScreen >= chunkState.loadRadius) {
+            loadChunk(chunk);
+        } else if (chunkState.loaded.has(chunk.id) && (!visible || onScreen < chunkState.loadRadius / 2)) {
+            unloadChunk(chunk);
+        }
+    });
+}
+
+function zoomToChunk(chunk) {
+    const canvas = currentNetwork.canvas.frame.canvas;
+    const fit = 0.45 * Math.min(canvas.clientWidth, canvas.clientHeight) / chunk.radius;
+    currentNetwork.moveTo({
+        position: { x: chunk.x, y: chunk.y },
+        scale: Math.max(fit, 1.1 * chunkState.loadRadius / chunk.radius),
+        animation: true
+    });
+}
+
+function initializeChunkedGraph() {
+    const { base, manifest } = window.graphChunks;
+    chunkState = {
+        base,
+        manifest,
+        loaded: new Map(),
+        loading: new Set(),
+        error: manifest.version === 1 ? null : `Unsupported chunk manifest version ${manifest.version}`,
+        loadRadius: manifest.config.chunks.load_radius_px
+    };
+    // Positions are precomputed; a simulation over every loaded chunk would undo them
+    physicsEnabled = false;
+    physicsSettings = { central_gravity: 0.1, ...manifest.config.physics };
+
+    const container = document.getElementById(document.body.dataset.containerId);
+    const data = {
+        nodes: new vis.DataSet(manifest.chunks.map(chunkSummaryNode)),
+        edges: new vis.DataSet(manifest.links.map(chunkLinkEdge))
+    };
+    const options = networkOptions();
+    options.physics = physicsOptions();
+    options.interaction.hideEdgesOnDrag = true;
+    options.interaction.hideEdgesOnZoom = true;
+    currentNetwork = new vis.Network(container, data, options);
+
+    currentNetwork.on('selectNode', params => onNodeSelected(params.nodes[0]));
+    currentNetwork.on('selectEdge', params => onEdgeSelected(params.edges[0]));
+    currentNetwork.on('doubleClick', params => {
+        const node = params.nodes.length ? data.nodes.get(params.nodes[0]) : null;
+        if (node && node.chunkId !== undefined) {
+            zoomToChunk(manifest.chunks[node.chunkId]);
+        }
+    });
+    let pending = null;
+    const schedule = () => {
+        clearTimeout(pending);
+        pending = setTimeout(refreshChunks, 150);
+    };
+    ['zoom', 'dragEnd', 'animationFinished'].forEach(event => currentNetwork.on(event, schedule));
+
+    showGraphStats(manifest.stats);
+    showGraphAbout(null);
+    // Chunks come and go while zooming, so there are no stable per-type counts
+    document.getElementById('legendSection').style.display = 'none';
+    showChunkStatus();
+    initPhysicsSliders();
+    updateToggleButton('physicsToggle', physicsEnabled, 'Physics: ON', 'Physics: OFF');
+    updateToggleButton('nodeLabelsToggle', showNodeLabels, 'Node Labels: ON', 'Node Labels: OFF');
+    updateToggleButton('edgeLabelsToggle', showEdgeLabels, 'Edge Labels: ON', 'Edge Labels: OFF');
+    updateToggleButton('uniqueNodesToggle', uniqueNodesEnabled, 'Unique Nodes: ON', 'Unique Nodes: OFF');
+    if (!chunkState.error) {
+        schedule();
+    }
+    console.log('Chunked graph initialized:', manifest.chunks.length, 'chunks');
+}
+
+// Timeline of the dates mentioned with each entity (the `dates` of its metadata): an Events
+// row with one item per dated sentence, then a row per entity. Selecting items selects
+// their entities in the network and the other way round.
+const TIMELINE_EVENTS_GROUP = 'timeline:events';
+let currentTimeline = null;
+let timelineItems = new Map();
+let timelineOpen = true;
+
+// Partial ISO dates ("2024", "2024-03") start at the beginning of their year or month
+function timelineStart(date) {
+    return date.length === 4 ? `${date}-01-01` : date.length === 7 ? `${date}-01` : date;
+}
+
+function shortText(text, max) {
+    return text.length > max ? `${text.slice(0, max - 1).trimEnd()}…` : text;
+}
+
+function timelineData(nodes) {
+    const groups = [{ id: TIMELINE_EVENTS_GROUP, content: 'Events', className: 'timeline-events' }];
+    const items = [];
+    const events = new Map();
+    nodes.filter(node => (node.metadata.dates || []).length > 0).forEach(node => {
+        groups.push({ id: node.id, content: escapeHtml(node.label) });
+        node.metadata.dates.forEach((mention, index) => {
+            items.push({
+                id: `${node.id}#${index}`,
+                group: node.id,
+                type: 'point',
+                start: timelineStart(mention.date),
+                content: escapeHtml(mention.text),
+                title: escapeHtml(mention.excerpt),
+                nodes: [node.id]
+            });
+            const key = `${mention.date}|${mention.sentence}`;
+            if (!events.has(key)) {
+                events.set(key, { mention: mention, nodes: [], labels: [] });
+            }
+            const event = events.get(key);
+            if (!event.nodes.includes(node.id)) {
+                event.nodes.push(node.id);
+                event.labels.push(node.label);
+            }
+        });
+    });
+    events.forEach((event, key) => items.push({
+        id: `event:${key}`,
+        group: TIMELINE_EVENTS_GROUP,
+        start: timelineStart(event.mention.date),
+        content: escapeHtml(shortText(event.mention.excerpt, 48)),
+        title: `${escapeHtml(event.mention.excerpt)}<br/><em>${event.labels.map(escapeHtml).join(', ')}</em>`,
+        nodes: event.nodes
+    }));
+    return { groups: groups, items: items, events: events.size };
+}
+
+function initializeTimeline() {
+    const onlyTimeline = document.body.dataset.view === 'timeline';
+    if (!window.visTimeline) {
+        return;
+    }
+    const data = timelineData(window.graphData.nodes);
+    const pane = document.getElementById('timelinePane');
+    if (data.events === 0) {
+        // Beside the network an empty timeline is just noise
+        if (onlyTimeline) {
+            pane.hidden = false;
+            document.getElementById('timeline').innerHTML =
+                '<div class="empty-graph"><h2>No dates were found</h2><p>The text this graph was built from mentions no dates next to its entities.</p></div>';
+        }
+        return;
+    }
+    pane.hidden = false;
+    timelineItems = new Map(data.items.map(item => [item.id, item]));
+    showTimelineStatus(data);
+    currentTimeline = new window.visTimeline(document.getElementById('timeline'), data.items, data.groups, {
+        stack: false,
+        multiselect: true,
+        orientation: 'top',
+        zoomMin: 24 * 60 * 60 * 1000,
+        tooltip: { followMouse: true }
+    });
+    currentTimeline.on('select', props => onTimelineSelected(props.items));
+}
+
+function showTimelineStatus(data) {
+    const entities = data.groups.length - 1;
+    document.getElementById('timelineStatus').textContent =
+        `${data.events} dated event${data.events === 1 ? '' : 's'}, ${entities} entit${entities === 1 ? 'y' : 'ies'}`;
+}
+
+// Redraw the timeline after the graph was edited
+function refreshTimeline() {
+    if (!currentTimeline) {
+        return;
+    }
+    const data = timelineData(window.graphData.nodes);
+    timelineItems = new Map(data.items.map(item => [item.id, item]));
+    showTimelineStatus(data);
+    currentTimeline.setData({ groups: data.groups, items: data.items });
+}
+
+function onTimelineSelected(itemIds) {
+    const nodeIds = [...new Set(itemIds.flatMap(id => (timelineItems.get(id) || { nodes: [] }).nodes))];
+    if (currentNetwork) {
+        // Nodes folded into a cluster or hidden by a filter can't be selected
+        const shown = nodeIds.filter(id => currentNetwork.body.nodes[id]);
+        currentNetwork.selectNodes(shown);
+        if (shown.length > 0) {
+            currentNetwork.fit({ nodes: shown, animation: true });
+        }
+    }
+    if (nodeIds.length > 0) {
+        onNodeSelected(nodeIds[0]);
+    }
+}
+
+function selectTimelineNodes(nodeIds) {
+    if (!currentTimeline) {
+        return;
+    }
+    const selected = new Set(nodeIds);
+    const itemIds = [...timelineItems.values()]
+        .filter(item => item.group !== TIMELINE_EVENTS_GROUP && selected.has(item.nodes[0]))
+        .map(item => item.id);
+    currentTimeline.setSelection(itemIds, { focus: itemIds.length > 0 });
+}
+
+function fitTimeline() {
+    if (currentTimeline) {
+        currentTimeline.fit();
+    }
+}
+
+function toggleTimeline() {
+    timelineOpen = !timelineOpen;
+    document.getElementById('timelinePane').classList.toggle('collapsed', !timelineOpen);
+    document.getElementById('timelineToggle').textContent = timelineOpen ? 'Hide' : 'Show';
+    if (currentNetwork) {
+        currentNetwork.redraw();
+    }
+}
+
+// A timeline-only page (-f timeline) shows the node details without drawing the network
+function initializeTimelineView() {
+    originalNodes = window.graphData.nodes.map(toVisNode);
+    showGraphStats(window.graphData.stats);
+    showGraphAbout(window.graphData.metadata, window.graphData.config);
+    initializeTimeline();
+}
+
+// Editing: changes are made to window.graphData, in the layout of the graph JSON, and
+// mirrored in the network, so the curated graph can be saved and rendered again with
+// `msg_net render -g`
+let editCount = 0;
+
+// Keys of the edge type counts in the graph metadata, as in EdgeType::metadata_key
+const EDGE_TYPE_KEYS = {
+    EntityRelationship: 'relationship',
+    EntityAttribute: 'entity_attribute',
+    ConceptEntity: 'concept_entity',
+    ConceptConcept: 'concept_concept',
+    Hierarchy: 'hierarchy'
+};
+
+// Graphs from a share link carry too little metadata to be written back as graph JSON
+function initializeEditing() {
+    if (window.graphData.schema_version === undefined) {
+        return;
+    }
+    document.getElementById('editSection').hidden = false;
+    updateEditTypes();
+}
+
+// Entity and relationship types already in the graph, offered by the type field
+function updateEditTypes() {
+    const types = new Set();
+    window.graphData.nodes.forEach(node => node.metadata.entity_type && types.add(node.metadata.entity_type));
+    window.graphData.edges.forEach(edge => types.add(edge.metadata.relationship_type));
+    document.getElementById('editTypes').innerHTML = [...types].sort()
+        .map(type => `<option value="${escapeHtml(type)}"></option>`).join('');
+}
+
+// Selected graph nodes and edges; cluster nodes and their links are not part of the graph
+function editableSelection() {
+    const nodeIds = new Set(window.graphData.nodes.map(node => node.id));
+    const edgeIds = new Set(window.graphData.edges.map(edge => edge.id));
+    return {
+        nodes: currentNetwork.getSelectedNodes().filter(id => nodeIds.has(id)),
+        edges: currentNetwork.getSelectedEdges().filter(id => edgeIds.has(id))
+    };
+}
+
+function showEditStatus(message) {
+    document.getElementById('editStatus').textContent = message;
+}
+
+// Put edited graph items back in the network, where shown, and in the copies kept for the
+// filters and label toggles; positions stay where the items were drawn
+function syncEditedItems(items, originals, dataSet, showLabels) {
+    const shownNodes = currentNetwork.body.data.nodes;
+    items.forEach(item => {
+        const index = originals.findIndex(original => original.id === item.id);
+        if (index >= 0) {
+            originals[index] = item;
+        } else {
+            originals.push(item);
+        }
+        const { x, y, ...shown } = { ...item, label: showLabels ? item.originalLabel : '' };
+        if (dataSet.get(item.id)) {
+            dataSet.update(shown);
+        } else if (index < 0 && shownNodes.get(shown.from) && shownNodes.get(shown.to)) {
+            // A new edge, drawn if both its nodes are
+            dataSet.add(shown);
+        }
+    });
+}
+
+function recordEdit(message, { nodes = [], edges = [] } = {}) {
+    const data = currentNetwork.body.data;
+    syncEditedItems(nodes.map(toVisNode), originalNodes, data.nodes, showNodeLabels);
+    syncEditedItems(edges.map(toVisEdge), originalEdges, data.edges, showEdgeLabels);
+    editCount += 1;
+    showEditStatus(`${message} (${editCount} edit${editCount === 1 ? '' : 's'} to export)`);
+    buildLegend();
+    updateEditTypes();
+    refreshTimeline();
+}
+
+function renameSelected() {
+    const label = document.getElementById('editLabel').value.trim();
+    const selection = editableSelection();
+    if (!label) {
+        showEditStatus('Type the new label first.');
+        return;
+    }
+    if (selection.nodes.length > 0) {
+        const nodes = window.graphData.nodes.filter(node => selection.nodes.includes(node.id));
+        nodes.forEach(node => { node.label = label; });
+        recordEdit(`Renamed ${nodes.length} node${nodes.length === 1 ? '' : 's'} to "${label}"`, { nodes: nodes });
+        onNodeSelected(nodes[0].id);
+    } else if (selection.edges.length > 0) {
+        const edges = window.graphData.edges.filter(edge => selection.edges.includes(edge.id));
+        edges.forEach(edge => { edge.label = label; });
+        recordEdit(`Renamed ${edges.length} edge${edges.length === 1 ? '' : 's'} to "${label}"`, { edges: edges });
+        onEdgeSelected(edges[0].id);
+    } else {
+        showEditStatus('Select a node or an edge to rename.');
+    }
+}
+
+// Change the node type of the selected nodes; the dropdown goes back to its prompt
+function changeSelectedNodeType(nodeType) {
+    document.getElementById('editNodeType').value = '';
+    const selection = editableSelection();
+    if (!nodeType) {
+        return;
+    }
+    if (selection.nodes.length === 0) {
+        showEditStatus('Select the nodes to change.');
+        return;
+    }
+    const nodes = window.graphData.nodes.filter(node => selection.nodes.includes(node.id));
+    nodes.forEach(node => { node.node_type = nodeType; });
+    recordEdit(`Made ${nodes.length} node${nodes.length === 1 ? '' : 's'} ${nodeType}`, { nodes: nodes });
+    onNodeSelected(nodes[0].id);
+}
+
+// Set the entity type of the selected nodes, or else the relationship type of the selected edges
+function changeSelectedType() {
+    const type = document.getElementById('editType').value.trim();
+    const selection = editableSelection();
+    if (!type) {
+        showEditStatus('Type the new entity or relationship type first.');
+        return;
+    }
+    if (selection.nodes.length > 0) {
+        const nodes = window.graphData.nodes.filter(node => selection.nodes.includes(node.id));
+        nodes.forEach(node => { node.metadata.entity_type = type; });
+        recordEdit(`Set the entity type of ${nodes.length} node${nodes.length === 1 ? '' : 's'} to ${type}`, { nodes: nodes });
+        onNodeSelected(nodes[0].id);
+    } else if (selection.edges.length > 0) {
+        const edges = window.graphData.edges.filter(edge => selection.edges.includes(edge.id));
+        edges.forEach(edge => { edge.metadata.relationship_type = type; });
+        recordEdit(`Set the relationship type of ${edges.length} edge${edges.length === 1 ? '' : 's'} to ${type}`, { edges: edges });
+        onEdgeSelected(edges[0].id);
+    } else {
+        showEditStatus('Select a node or an edge to change.');
+    }
+}
+
+// Delete the selected nodes with every edge touching them, or else the selected edges
+function deleteSelected() {
+    const selection = editableSelection();
+    const nodeIds = new Set(selection.nodes);
+    const edgeIds = new Set(nodeIds.size > 0
+        ? window.graphData.edges.filter(edge => nodeIds.has(edge.from) || nodeIds.has(edge.to)).map(edge => edge.id)
+        : selection.edges);
+    if (nodeIds.size === 0 && edgeIds.size === 0) {
+        showEditStatus('Select the nodes or edges to delete.');
+        return;
+    }
+    window.graphData.nodes = window.graphData.nodes.filter(node => !nodeIds.has(node.id));
+    window.graphData.edges = window.graphData.edges.filter(edge => !edgeIds.has(edge.id));
+    originalNodes = originalNodes.filter(node => !nodeIds.has(node.id));
+    originalEdges = originalEdges.filter(edge => !edgeIds.has(edge.id));
+    currentNetwork.body.data.edges.remove([...edgeIds]);
+    currentNetwork.body.data.nodes.remove([...nodeIds]);
+    document.getElementById('node-info').style.display = 'none';
+    document.getElementById('edge-info').style.display = 'none';
+    recordEdit(nodeIds.size > 0
+        ? `Deleted ${nodeIds.size} node${nodeIds.size === 1 ? '' : 's'} and ${edgeIds.size} edge${edgeIds.size === 1 ? '' : 's'}`
+        : `Deleted ${edgeIds.size} edge${edgeIds.size === 1 ? '' : 's'}`);
+}
+
+// Link the two selected nodes, in the order they were selected, with a relationship
+// named by the label field
+function addEdgeBetweenSelected() {
+    const selection = editableSelection();
+    if (selection.nodes.length !== 2) {
+        showEditStatus('Select exactly two nodes (Ctrl+click the second) to link them.');
+        return;
+    }
+    const label = document.getElementById('editLabel').value.trim() || 'related to';
+    // Written like an extracted relationship's RelationshipType
+    const relationshipType = label === 'related to' ? 'RelatedTo' : `Other(${JSON.stringify(label)})`;
+    const [from, to] = selection.nodes;
+    const ids = new Set(window.graphData.edges.map(edge => edge.id));
+    let number = window.graphData.edges.length + 1;
+    while (ids.has(`edge_edited_${number}`)) {
+        number += 1;
+    }
+    // Drawn like the graph's other relationships
+    const like = window.graphData.edges.find(edge => edge.edge_type === 'EntityRelationship');
+    const edge = {
+        id: `edge_edited_${number}`,
+        from: from,
+        to: to,
+        label: label,
+        color: like ? like.color : '#4ECDC4',
+        width: like ? like.width : 2,
+        arrows: 'to',
+        edge_type: 'EntityRelationship',
+        metadata: {
+            confidence: 1,
+            relationship_type: relationshipType,
+            bidirectional: false,
+            weight: 1
+        },
+        dashes: false
+    };
+    window.graphData.edges.push(edge);
+    recordEdit(`Added the edge "${label}"`, { edges: [edge] });
+}
+
+// The edited graph as graph JSON, with the positions the nodes are drawn at and the
+// metadata counts brought up to date
+function editedGraph() {
+    const positions = currentNetwork.getPositions();
+    const count = (items, key) => items.reduce((counts, item) => {
+        counts[key(item)] = (counts[key(item)] || 0) + 1;
+        return counts;
+    }, {});
+    const nodes = window.graphData.nodes.map(node =>
+        positions[node.id] ? { ...node, x: positions[node.id].x, y: positions[node.id].y } : node);
+    return {
+        schema_version: window.graphData.schema_version,
+        nodes: nodes,
+        edges: window.graphData.edges,
+        config: window.graphData.config,
+        metadata: {
+            ...window.graphData.metadata,
+            total_nodes: nodes.length,
+            total_edges: window.graphData.edges.length,
+            node_types: count(nodes, node => node.node_type.toLowerCase()),
+            edge_types: count(window.graphData.edges, edge => EDGE_TYPE_KEYS[edge.edge_type] || edge.edge_type)
+        }
+    };
+}
+
+function exportEditedGraph() {
+    const dataStr = JSON.stringify(editedGraph(), null, 2);
+    downloadBlob(new Blob([dataStr], { type: 'application/json' }), 'graph_edited.json');
+}
+
+// Initialize the graph when page loads: embedded graph data, or a graph shared in the link
+window.addEventListener('load', async function() {
+    if (window.graphChunks) {
+        initializeChunkedGraph();
+        return;
+    }
+    if (!window.graphData && location.hash.startsWith('#g=')) {
+        try {
+            Object.assign(window, { graphData: await loadSharedGraph(location.hash.slice(3)) });
+        } catch (error) {
+            showSharedGraphError(error);
+            return;
+        }
+    }
+    if (window.graphData) {
+        if (document.body.dataset.view === 'timeline') {
+            initializeTimelineView();
+        } else if (window.graphData.nodes.length === 0) {
+            showEmptyGraphMessage();
+        } else {
+            initializeGraph();
+        }
+    }
+});
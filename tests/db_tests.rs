@@ -0,0 +1,55 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+use std::fs;
+
+const TEST_TEXT: &str = "Alice works at TechCorp. Bob is connected to Alice.";
+
+#[test]
+fn test_generate_saves_run_that_can_be_listed_found_and_loaded() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("notes.txt"), TEST_TEXT).expect("Failed to write test file");
+
+    let msg_net = || {
+        let mut cmd = Command::cargo_bin("msg_net").expect("Failed to find binary");
+        cmd.current_dir(temp_dir.path());
+        cmd
+    };
+
+    msg_net()
+        .args(["-q", "generate", "-i", "notes.txt", "-o", "graph.json", "-f", "json", "--db", "graphs.db"])
+        .assert()
+        .success();
+
+    msg_net()
+        .args(["-q", "db", "--db", "graphs.db", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("notes.txt").and(predicate::str::contains("with extraction")));
+
+    msg_net()
+        .args(["-q", "db", "--db", "graphs.db", "find", "alice"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Alice"));
+
+    msg_net()
+        .args(["db", "--db", "graphs.db", "load", "--run", "1", "-o", "restored.dot", "-f", "dot"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Graph exported successfully"));
+    let restored = fs::read_to_string(temp_dir.path().join("0_networks").join("restored.dot"))
+        .expect("Failed to read restored graph");
+    assert!(restored.contains("Alice"));
+}
+
+#[test]
+fn test_db_load_unknown_run_fails() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+
+    let mut cmd = Command::cargo_bin("msg_net").expect("Failed to find binary");
+    cmd.current_dir(temp_dir.path())
+        .args(["-q", "db", "--db", "graphs.db", "load", "--run", "7", "-o", "out.html"]);
+
+    cmd.assert().failure();
+}
@@ -0,0 +1,51 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+use std::fs;
+
+/// Nothing the default capitalized-word pattern can match
+const LOWERCASE_TEXT: &str = "the cat sat on the mat. then it slept in the sun.";
+
+fn generate(temp_dir: &TempDir, extra: &[&str]) -> assert_cmd::assert::Assert {
+    let input_file = temp_dir.path().join("input.txt");
+    fs::write(&input_file, LOWERCASE_TEXT).expect("Failed to write test file");
+
+    let mut cmd = Command::cargo_bin("msg_net").expect("Failed to find binary");
+    cmd.current_dir(temp_dir.path())
+        .arg("generate")
+        .arg("-i")
+        .arg(&input_file)
+        .arg("-o")
+        .arg("graph.html")
+        .args(extra);
+    cmd.assert()
+}
+
+#[test]
+fn test_empty_graph_prints_suggestions_and_still_writes() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+
+    generate(&temp_dir, &[])
+        .success()
+        .stdout(predicate::str::contains("The graph has only 0 nodes"))
+        .stdout(predicate::str::contains("--no-remove-stopwords"))
+        .stdout(predicate::str::contains("--use-llm"));
+
+    let html = fs::read_to_string(temp_dir.path().join("0_networks").join("graph.html")).expect("Graph was not written");
+    assert!(html.contains("showEmptyGraphMessage"));
+}
+
+#[test]
+fn test_min_results_fails_without_writing_output() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+
+    generate(&temp_dir, &["--min-results", "1"])
+        .failure()
+        .stderr(predicate::str::contains("fewer than --min-results 1"));
+    assert!(!temp_dir.path().join("0_networks").join("graph.html").exists());
+
+    // Enough results pass through unchanged
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    generate(&temp_dir, &["--min-results", "0"]).success();
+    assert!(temp_dir.path().join("0_networks").join("graph.html").exists());
+}
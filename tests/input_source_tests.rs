@@ -0,0 +1,102 @@
+//! Generating from a web page served by a local stub, and from several input files
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use serde_json::Value;
+use std::fs;
+use tempfile::TempDir;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const ARTICLE: &str = r#"<html><head><title>Initech opens Berlin office</title></head><body>
+<nav><a href="/">Home</a> <a href="/markets">Globex Markets</a></nav>
+<article>
+  <p>Initech announced on Monday that Peter Gibbons will lead its new office in Berlin, the first in Germany.</p>
+  <p>Bill Lumbergh said the team would serve clients across Europe, starting with Hooli.</p>
+</article>
+<aside><p>Sponsored: Acme Anvils ships worldwide, order today and save, limited offer.</p></aside>
+</body></html>"#;
+
+async fn serve_article() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/news/initech"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(ARTICLE, "text/html; charset=utf-8"))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_from_url_uses_the_main_content_of_the_page() {
+    let server = serve_article().await;
+    let url = format!("{}/news/initech", server.uri());
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let dir_path = dir.path().to_path_buf();
+
+    let output = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("msg_net")
+            .expect("Failed to find binary")
+            .current_dir(dir_path)
+            .args(["-q", "generate", "--from-url", &url, "-o", "graph.json", "-f", "json", "--no-cache"])
+            .output()
+            .expect("Failed to run msg_net")
+    })
+    .await
+    .expect("CLI thread panicked");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let json = fs::read_to_string(dir.path().join("0_networks").join("graph.json")).expect("Graph was not written");
+    let graph: Value = serde_json::from_str(&json).expect("Graph is not valid JSON");
+    let labels: Vec<&str> = graph["nodes"].as_array().unwrap().iter().filter_map(|node| node["label"].as_str()).collect();
+    assert!(labels.contains(&"Peter Gibbons"), "{:?}", labels);
+    assert!(labels.contains(&"Hooli"), "{:?}", labels);
+    assert!(!labels.iter().any(|label| label.contains("Globex") || label.contains("Acme")), "{:?}", labels);
+}
+
+#[test]
+fn test_input_sources_are_exclusive_and_one_is_required() {
+    let mut cmd = Command::cargo_bin("msg_net").expect("Failed to find binary");
+    cmd.args(["generate", "-i", "notes.txt", "--from-url", "https://example.com", "-o", "graph.html"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+
+    let mut cmd = Command::cargo_bin("msg_net").expect("Failed to find binary");
+    cmd.args(["analyze"]).assert().failure().stderr(predicate::str::contains("--input"));
+}
+
+#[test]
+fn test_several_input_files_combine_into_one_graph_with_sources() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(dir.path().join("a.txt"), "Alice Smith works at Initech in Berlin.\n").expect("Failed to write test file");
+    fs::write(dir.path().join("b.txt"), "Bob Jones met Alice Smith in Paris.\n").expect("Failed to write test file");
+    let sources = |output: &str, args: &[&str]| {
+        Command::cargo_bin("msg_net")
+            .expect("Failed to find binary")
+            .current_dir(dir.path())
+            .args(["-q", "generate", "-o", output, "-f", "json"])
+            .args(args)
+            .assert()
+            .success();
+        let json = fs::read_to_string(dir.path().join("0_networks").join(output)).expect("Graph was not written");
+        let graph: Value = serde_json::from_str(&json).expect("Graph is not valid JSON");
+        graph["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|node| {
+                let sources: Vec<String> = serde_json::from_value(node["metadata"]["sources"].clone()).unwrap_or_default();
+                (node["label"].as_str().unwrap().to_string(), sources)
+            })
+            .collect::<std::collections::BTreeMap<_, _>>()
+    };
+
+    let combined = sources("repeated.json", &["-i", "a.txt", "-i", "b.txt"]);
+    assert_eq!(combined["Alice Smith"], ["a.txt", "b.txt"]);
+    assert_eq!(combined["Initech"], ["a.txt"]);
+    assert_eq!(combined["Bob Jones"], ["b.txt"]);
+    assert_eq!(sources("comma.json", &["-i", "a.txt,b.txt"]), combined);
+    // A single file records no sources
+    assert!(sources("single.json", &["-i", "a.txt"]).values().all(Vec::is_empty));
+}
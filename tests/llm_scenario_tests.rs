@@ -0,0 +1,553 @@
+//! End-to-end runs of the LLM and transcription code paths against local stubs of their APIs
+
+use assert_cmd::Command;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+use wiremock::matchers::{body_string_contains, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const TEST_TEXT: &str = "Alice works at TechCorp. TechCorp sells a CRM platform to retailers.";
+
+/// An Ollama `/api/generate` reply carrying `response` as the model output
+fn ollama_reply(response: &str) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(json!({
+        "model": "stub-model",
+        "created_at": "2024-01-01T00:00:00Z",
+        "response": response,
+        "done": true,
+    }))
+}
+
+/// Answer the entity, relationship and concept prompts, each exactly `calls` times
+async fn mount_extraction_stubs(server: &MockServer, calls: u64) {
+    let entities = json!([
+        {"name": "Alice", "type": "Person", "confidence": 0.9},
+        {"name": "TechCorp", "type": "Organization", "confidence": 0.95},
+    ]);
+    let relationships = json!([
+        {"from": "Alice", "to": "TechCorp", "relationship": "works at", "confidence": 0.85},
+        {"from": "Alice", "to": "Nobody", "relationship": "knows", "confidence": 0.5},
+    ]);
+    let concepts = json!([
+        {"name": "CRM platform", "description": "Software TechCorp sells to retailers", "confidence": 0.8},
+    ]);
+
+    for (prompt, reply) in [
+        ("extract entities", entities),
+        ("identify relationships between these entities", relationships),
+        ("extract key concepts", concepts),
+    ] {
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .and(body_string_contains(prompt))
+            // Models often wrap the JSON in prose; the parser has to cope
+            .respond_with(ollama_reply(&format!("Here you go:\n{}\nHope that helps.", reply)))
+            .expect(calls)
+            .mount(server)
+            .await;
+    }
+}
+
+/// Run the CLI in `dir` on a blocking thread, so the stub server keeps serving meanwhile
+async fn run_cli(dir: &Path, args: Vec<String>) -> std::process::Output {
+    let dir = dir.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("msg_net")
+            .expect("Failed to find binary")
+            .current_dir(dir)
+            .args(args)
+            .output()
+            .expect("Failed to run msg_net")
+    })
+    .await
+    .expect("CLI thread panicked")
+}
+
+fn generate_args(server: &MockServer, extra: &[&str]) -> Vec<String> {
+    let mut args: Vec<String> = [
+        "-q", "generate", "-i", "input.txt", "-o", "graph.json", "-f", "json", "--include-metadata", "--use-llm",
+        "--llm-model", "stub-model",
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect();
+    args.push("--llm-endpoint".to_string());
+    args.push(format!("{}/api/generate", server.uri()));
+    args.extend(extra.iter().map(|arg| arg.to_string()));
+    args
+}
+
+fn setup() -> TempDir {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(dir.path().join("input.txt"), TEST_TEXT).expect("Failed to write input");
+    dir
+}
+
+fn read_graph(dir: &Path) -> Value {
+    let json = fs::read_to_string(dir.join("0_networks").join("graph.json")).expect("Graph was not written");
+    serde_json::from_str(&json).expect("Graph is not valid JSON")
+}
+
+fn node<'a>(graph: &'a Value, label: &str) -> &'a Value {
+    graph["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|node| node["label"] == label)
+        .unwrap_or_else(|| panic!("No node labelled {}", label))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_deep_analysis_builds_graph_from_llm_responses() {
+    let server = MockServer::start().await;
+    mount_extraction_stubs(&server, 1).await;
+    let dir = setup();
+
+    let output = run_cli(dir.path(), generate_args(&server, &["--deep-analysis", "--no-cache"])).await;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let graph = read_graph(dir.path());
+    let alice = node(&graph, "Alice");
+    let techcorp = node(&graph, "TechCorp");
+    assert_eq!(alice["metadata"]["entity_type"], "Person");
+    assert_eq!(techcorp["metadata"]["entity_type"], "Organization");
+    assert_eq!(node(&graph, "CRM platform")["node_type"], "Concept");
+
+    let edges = graph["edges"].as_array().unwrap();
+    let works_at = edges
+        .iter()
+        .find(|edge| edge["label"] == "works at")
+        .expect("LLM relationship missing");
+    assert_eq!((&works_at["from"], &works_at["to"]), (&alice["id"], &techcorp["id"]));
+    // The relationship to an entity the LLM never listed is dropped
+    assert!(edges.iter().all(|edge| edge["label"] != "knows"));
+    // Deep analysis links concepts to the entities their description mentions
+    assert!(edges.iter().any(|edge| edge["label"] == "relates to" && edge["to"] == techcorp["id"]));
+
+    assert_eq!(graph["metadata"]["total_nodes"], graph["nodes"].as_array().unwrap().len());
+    assert!(graph["schema_version"].as_u64().is_some());
+}
+
+/// A deep-analysis answer shaped like a chatty model's: fenced JSON, a percentage confidence and
+/// `evidence` instead of `context` on one item
+const RECORDED_DEEP_RESPONSE: &str = r#"Here are the deeper relationships I found:
+
+```json
+{
+  "relationships": [
+    {"from": "Alice", "to": "TechCorp", "relationship": "employed by", "category": "Hierarchical", "confidence": 0.9, "context": "Alice works at TechCorp."},
+    {"from": "TechCorp", "to": "Alice", "relationship": "relies on", "category": "dependency", "confidence": "70%", "evidence": "TechCorp sells a CRM platform"},
+    {"from": "Alice", "to": "Retailers", "relationship": "serves", "category": "functional", "confidence": 0.6, "context": "to retailers"}
+  ]
+}
+```"#;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_deep_analysis_keeps_category_and_context_of_llm_relationships() {
+    let server = MockServer::start().await;
+    mount_extraction_stubs(&server, 1).await;
+    Mock::given(method("POST"))
+        .and(path("/api/generate"))
+        .and(body_string_contains("sophisticated relationships"))
+        .and(body_string_contains("hierarchical"))
+        .respond_with(ollama_reply(RECORDED_DEEP_RESPONSE))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let dir = setup();
+
+    let output = run_cli(dir.path(), generate_args(&server, &["--deep-analysis", "--no-cache"])).await;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let graph = read_graph(dir.path());
+    let edges = graph["edges"].as_array().unwrap();
+    let edge = |label: &str| edges.iter().find(|edge| edge["label"] == label).unwrap_or_else(|| panic!("No {} edge", label));
+    let employed = edge("employed by");
+    assert_eq!(employed["from"], node(&graph, "Alice")["id"]);
+    assert_eq!(employed["metadata"]["category"], "hierarchical");
+    assert_eq!(employed["metadata"]["context"], "Alice works at TechCorp.");
+    let relies = edge("relies on");
+    assert_eq!(relies["metadata"]["category"], "dependency");
+    assert_eq!(relies["metadata"]["context"], "TechCorp sells a CRM platform");
+    assert_eq!(relies["metadata"]["confidence"], 0.7);
+    // Relationships to entities the LLM never listed are dropped, and basic ones carry no category
+    assert!(edges.iter().all(|edge| edge["label"] != "serves"));
+    assert!(edge("works at")["metadata"].get("category").is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_second_run_is_answered_from_the_response_cache() {
+    let server = MockServer::start().await;
+    // Each prompt reaches the stub once across both runs
+    mount_extraction_stubs(&server, 1).await;
+    let dir = setup();
+
+    for _ in 0..2 {
+        let output = run_cli(dir.path(), generate_args(&server, &[])).await;
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        assert_eq!(node(&read_graph(dir.path()), "TechCorp")["metadata"]["entity_type"], "Organization");
+    }
+    assert!(dir.path().join(".msg_net_cache").exists());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_changing_the_output_schema_misses_the_response_cache() {
+    use msg_net::config::LlmOutputFormat;
+    use msg_net::pipeline::Pipeline;
+
+    let server = MockServer::start().await;
+    // Asked once per output format, however often each format runs
+    mount_extraction_stubs(&server, 2).await;
+    let dir = TempDir::new().expect("Failed to create temp dir");
+
+    for format in [LlmOutputFormat::Schema, LlmOutputFormat::Json, LlmOutputFormat::Json] {
+        let mut config = msg_net::config::GraphConfig::default();
+        config.extraction.use_llm = true;
+        config.extraction.llm_model = "stub-model".to_string();
+        config.extraction.llm_endpoint = format!("{}/api/generate", server.uri());
+        config.extraction.llm_cache.directory = dir.path().join("cache").to_string_lossy().to_string();
+        config.extraction.llm_output_format = format;
+        let graph = Pipeline::new().with_config(config).with_text(TEST_TEXT).run().await.expect("Pipeline failed");
+        assert_eq!(node(&serde_json::to_value(&graph).unwrap(), "TechCorp")["metadata"]["entity_type"], "Organization");
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_unusable_llm_output_falls_back_to_patterns() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/generate"))
+        .respond_with(ollama_reply("Sorry, I can't produce JSON today."))
+        .expect(3)
+        .mount(&server)
+        .await;
+    let dir = setup();
+
+    let output = run_cli(dir.path(), generate_args(&server, &["--no-cache"])).await;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    // Pattern extraction still finds the capitalized names
+    let graph = read_graph(dir.path());
+    assert_eq!(node(&graph, "Alice")["node_type"], "Entity");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_explain_answers_with_the_llm() {
+    let server = MockServer::start().await;
+    mount_extraction_stubs(&server, 1).await;
+    Mock::given(method("POST"))
+        .and(path("/api/generate"))
+        .and(body_string_contains("Question: How is Alice connected to TechCorp?"))
+        .respond_with(ollama_reply("Alice works at TechCorp."))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let dir = setup();
+
+    let output = run_cli(dir.path(), generate_args(&server, &["--no-cache"])).await;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let output = run_cli(
+        dir.path(),
+        vec![
+            "-q".to_string(),
+            "explain".to_string(),
+            "-g".to_string(),
+            "0_networks/graph.json".to_string(),
+            "-Q".to_string(),
+            "How is Alice connected to TechCorp?".to_string(),
+            "--llm-endpoint".to_string(),
+            format!("{}/api/generate", server.uri()),
+        ],
+    )
+    .await;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Alice works at TechCorp."));
+    assert!(stdout.contains("works at"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_declining_an_over_budget_run_makes_no_llm_calls() {
+    let server = MockServer::start().await;
+    mount_extraction_stubs(&server, 0).await;
+    let dir = setup();
+    let mut config = msg_net::config::GraphConfig::default();
+    config.extraction.llm_cost.input_cost_per_million = 1000.0;
+    config.extraction.llm_cost.budget = Some(0.001);
+    fs::write(dir.path().join("config.json"), serde_json::to_string(&config).unwrap()).unwrap();
+
+    let args = generate_args(&server, &["--no-cache", "-c", "config.json", "--confirm"]);
+    let cwd = dir.path().to_path_buf();
+    let output = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("msg_net").unwrap().current_dir(cwd).args(args).write_stdin("n\n").output().unwrap()
+    })
+    .await
+    .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("exceeds the budget"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Cancelled"));
+    assert!(!dir.path().join("0_networks").join("graph.json").exists());
+}
+
+/// A streamed Ollama reply: `response` split into one JSON line per piece
+fn ollama_stream_reply(response: &str) -> ResponseTemplate {
+    let mut body = String::new();
+    for piece in response.split_inclusive(' ') {
+        body.push_str(&json!({"model": "stub-model", "response": piece, "done": false}).to_string());
+        body.push('\n');
+    }
+    body.push_str(&json!({"model": "stub-model", "response": "", "done": true, "eval_count": 3}).to_string());
+    body.push('\n');
+    ResponseTemplate::new(200).set_body_raw(body, "application/x-ndjson")
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_streamed_responses_are_accumulated_and_report_progress() {
+    use msg_net::pipeline::{Pipeline, PipelineEvent};
+
+    let server = MockServer::start().await;
+    let entities = json!([{"name": "Alice", "type": "Person", "confidence": 0.9}]).to_string();
+    Mock::given(method("POST"))
+        .and(path("/api/generate"))
+        .and(body_string_contains("\"stream\":true"))
+        .and(body_string_contains("extract entities"))
+        .respond_with(ollama_stream_reply(&entities))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/generate"))
+        .respond_with(ollama_stream_reply("[]"))
+        .mount(&server)
+        .await;
+
+    let mut config = msg_net::config::GraphConfig::default();
+    config.extraction.use_llm = true;
+    config.extraction.llm_cache.enabled = false;
+    config.extraction.llm_endpoint = format!("{}/api/generate", server.uri());
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    let graph = Pipeline::new()
+        .with_config(config)
+        .with_text(TEST_TEXT)
+        .with_event_sender(sender)
+        .run()
+        .await
+        .expect("Pipeline failed");
+
+    assert_eq!(node(&serde_json::to_value(&graph).unwrap(), "Alice")["metadata"]["entity_type"], "Person");
+    let mut progress = Vec::new();
+    while let Ok(event) = receiver.try_recv() {
+        if let PipelineEvent::LlmProgress(update) = event {
+            progress.push(update);
+        }
+    }
+    // The entity prompt goes first: one update per piece, then the final `done`
+    let pieces = entities.split_inclusive(' ').count();
+    let first_done = progress.iter().position(|update| update.done).expect("No final update");
+    assert_eq!(first_done, pieces);
+    assert_eq!((progress[first_done].tokens, progress[first_done].characters), (pieces, entities.len()));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_cut_off_streams_fail_and_are_not_cached() {
+    let server = MockServer::start().await;
+    let entities = json!([{"name": "Alice", "type": "Person", "confidence": 0.9}]).to_string();
+    // Every piece of the answer but the final `done` line
+    let cut_off: String = entities
+        .split_inclusive(' ')
+        .map(|piece| format!("{}\n", json!({"model": "stub-model", "response": piece, "done": false})))
+        .collect();
+    Mock::given(method("POST"))
+        .and(path("/api/generate"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(cut_off, "application/x-ndjson"))
+        .mount(&server)
+        .await;
+
+    let dir = setup();
+    let cache_dir = dir.path().join("cache");
+    let mut config = msg_net::config::GraphConfig::default();
+    config.extraction.use_llm = true;
+    config.extraction.llm_cache.directory = cache_dir.to_string_lossy().to_string();
+    config.extraction.llm_endpoint = format!("{}/api/generate", server.uri());
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    msg_net::pipeline::Pipeline::new()
+        .with_config(config)
+        .with_text(TEST_TEXT)
+        .with_event_sender(sender)
+        .run()
+        .await
+        .expect("Pipeline failed");
+
+    // Each call falls back to patterns instead of using the partial answer
+    let mut warnings = Vec::new();
+    while let Ok(event) = receiver.try_recv() {
+        if let msg_net::pipeline::PipelineEvent::Warning(message) = event {
+            warnings.push(message);
+        }
+    }
+    assert!(
+        warnings.iter().any(|warning| warning.contains("LLM call failed") && warning.contains("without a final chunk")),
+        "{:?}",
+        warnings
+    );
+    let cached = std::fs::read_dir(&cache_dir).map(|entries| entries.count()).unwrap_or(0);
+    assert_eq!(cached, 0, "a cut-off answer was cached");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_schema_constrained_calls_accept_loosely_shaped_json() {
+    use wiremock::matchers::body_partial_json;
+
+    let server = MockServer::start().await;
+    // Each call carries the schema of its answer; the replies still wrap the JSON in a
+    // code fence, rename fields and leave trailing commas
+    for (key, reply) in [
+        ("entities", "```json\n{\"entities\": [{\"entity\": \"Alice\", \"category\": \"Person\"}, {\"name\": \"TechCorp\", \"type\": \"Place\", \"confidence\": \"90%\"},]}\n```"),
+        ("relationships", "{\"relationships\": [{\"source\": \"Alice\", \"target\": \"TechCorp\", \"predicate\": \"maintains\", \"confidence\": 0.7,}]}"),
+        ("concepts", "{\"concepts\": [{\"concept\": \"CRM platform\"}]}"),
+    ] {
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .and(body_partial_json(json!({"format": {"required": [key]}})))
+            .respond_with(ollama_reply(reply))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+    let dir = setup();
+
+    let output = run_cli(dir.path(), generate_args(&server, &["--no-cache"])).await;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let graph = read_graph(dir.path());
+    let techcorp = node(&graph, "TechCorp");
+    assert_eq!(techcorp["metadata"]["entity_type"], "Place");
+    assert_eq!(node(&graph, "CRM platform")["node_type"], "Concept");
+    let edges = graph["edges"].as_array().unwrap();
+    assert!(edges.iter().any(|edge| edge["label"] == "maintains" && edge["to"] == techcorp["id"]));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_recording_is_transcribed_into_a_chat_graph() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/audio/transcriptions"))
+        .and(body_string_contains("verbose_json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "language": "en",
+            "segments": [
+                {"start": 2.0, "end": 6.5, "speaker": "SPEAKER_00", "text": " Alice will meet Bob Smith at Initech on Friday."},
+                {"start": 7.1, "end": 9.0, "speaker": "SPEAKER_01", "text": " Bob Smith agreed to bring the contract."},
+            ],
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(dir.path().join("standup.wav"), b"RIFF fake audio").expect("Failed to write recording");
+
+    let args = [
+        "-q", "generate", "-i", "standup.wav", "-o", "graph.json", "-f", "json", "--no-cache", "--asr-endpoint",
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .chain([format!("{}/v1/audio/transcriptions", server.uri())])
+    .collect();
+    let output = run_cli(dir.path(), args).await;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let graph = read_graph(dir.path());
+    node(&graph, "Bob Smith");
+    node(&graph, "Initech");
+    node(&graph, "Speaker One");
+    node(&graph, "Speaker Two");
+
+    let without_asr = ["-q", "generate", "-i", "standup.wav", "-o", "graph.json"];
+    let without_asr = run_cli(dir.path(), without_asr.iter().map(|arg| arg.to_string()).collect()).await;
+    assert!(!without_asr.status.success());
+    assert!(String::from_utf8_lossy(&without_asr.stderr).contains("--asr-command or --asr-endpoint"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_compare_methods_colors_the_graph_by_agreement() {
+    let server = MockServer::start().await;
+    mount_extraction_stubs(&server, 1).await;
+    let dir = setup();
+
+    let args: Vec<String> = [
+        "compare-methods", "-i", "input.txt", "-o", "graph.json", "-f", "json", "--llm-model", "stub-model",
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .chain(["--llm-endpoint".to_string(), format!("{}/api/generate", server.uri())])
+    .collect();
+    let output = run_cli(dir.path(), args).await;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // The patterns miss TechCorp, so only Alice is found by both
+    assert!(stdout.contains("Entities: 1 both, 1 LLM only, 0 patterns only (50% agreement)"), "{}", stdout);
+    assert!(stdout.contains("Relationships: 0 both, 1 LLM only"), "{}", stdout);
+
+    let graph = read_graph(dir.path());
+    assert_eq!(node(&graph, "Alice")["color"], "#2E7D32");
+    assert_eq!(node(&graph, "TechCorp")["color"], "#1565C0");
+    assert_eq!(node(&graph, "TechCorp")["metadata"]["attributes"]["found_by"], "LLM only");
+    // Concepts are not part of the comparison
+    assert_eq!(graph["metadata"]["node_types"], json!({"entity": 2}));
+    let provenance = &graph["metadata"]["provenance"];
+    assert_eq!(provenance["extraction_method"], "Comparison of Pattern-based and LLM-stub-model");
+    assert!(provenance["config_summary"]["comparison.entities"].as_str().unwrap().contains("50% agreement"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_long_text_is_split_to_fit_the_model_context() {
+    let server = MockServer::start().await;
+    for (prompt, reply) in [
+        ("extract entities", r#"{"entities": [{"name": "Alice", "type": "Person", "confidence": 0.9}, {"name": "TechCorp", "type": "Organization", "confidence": 0.95}]}"#),
+        ("identify relationships", r#"{"relationships": [{"from": "Alice", "to": "TechCorp", "relationship": "works at", "confidence": 0.85}]}"#),
+        ("extract key concepts", r#"{"concepts": [{"name": "CRM platform", "description": "Software", "confidence": 0.8}]}"#),
+    ] {
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .and(body_string_contains(prompt))
+            .respond_with(ollama_reply(reply))
+            .mount(&server)
+            .await;
+    }
+    let dir = setup();
+    fs::write(dir.path().join("input.txt"), format!("{}\n", TEST_TEXT).repeat(40)).unwrap();
+    let mut config = msg_net::config::GraphConfig::default();
+    config.extraction.prompt_budget.response_tokens = 0;
+    config.extraction.prompt_budget.models.insert("stub-model".to_string(), 300);
+    fs::write(dir.path().join("config.json"), serde_json::to_string(&config).unwrap()).unwrap();
+
+    let output = run_cli(dir.path(), generate_args(&server, &["--no-cache", "-c", "config.json"])).await;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let requests: Vec<Value> = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| serde_json::from_slice(&request.body).unwrap())
+        .collect();
+    let prompts: Vec<&str> = requests.iter().map(|request| request["prompt"].as_str().unwrap()).collect();
+    let entity_prompts: Vec<&str> = prompts.iter().copied().filter(|prompt| prompt.contains("extract entities")).collect();
+    assert!(entity_prompts.len() > 1, "{} entity prompts", entity_prompts.len());
+    assert!(!entity_prompts[0].contains("already found"));
+    // Later segments carry the names found so far
+    assert!(entity_prompts[1].contains(r#"(reuse these names): ["Alice", "TechCorp"]"#), "{}", entity_prompts[1]);
+    assert!(prompts.iter().all(|prompt| prompt.chars().count() <= 300 * 4), "a prompt is over budget");
+    assert!(requests.iter().all(|request| request["options"]["num_ctx"] == 300));
+
+    // The segments' answers are merged into one node per entity
+    let graph = read_graph(dir.path());
+    let labels: Vec<&str> = graph["nodes"].as_array().unwrap().iter().filter_map(|node| node["label"].as_str()).collect();
+    assert_eq!(labels.iter().filter(|label| **label == "Alice").count(), 1, "{:?}", labels);
+    let edges = graph["edges"].as_array().unwrap();
+    assert_eq!(edges.iter().filter(|edge| edge["label"] == "works at").count(), 1);
+}
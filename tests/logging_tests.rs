@@ -0,0 +1,60 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+use std::fs;
+
+const TEST_TEXT: &str = "Alice works at TechCorp. Bob manages the database system for the company.";
+
+#[test]
+fn test_quiet_suppresses_progress_output() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let input_file = temp_dir.path().join("input.txt");
+    fs::write(&input_file, TEST_TEXT).expect("Failed to write test file");
+
+    let mut cmd = Command::cargo_bin("msg_net").expect("Failed to find binary");
+    cmd.current_dir(temp_dir.path())
+        .arg("--quiet")
+        .arg("generate")
+        .arg("-i")
+        .arg(&input_file)
+        .arg("-o")
+        .arg("graph.json")
+        .arg("-f")
+        .arg("json");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+
+    assert!(temp_dir.path().join("0_networks").join("graph.json").exists());
+}
+
+#[test]
+fn test_json_log_format_emits_structured_records() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let input_file = temp_dir.path().join("input.txt");
+    fs::write(&input_file, TEST_TEXT).expect("Failed to write test file");
+
+    let mut cmd = Command::cargo_bin("msg_net").expect("Failed to find binary");
+    cmd.current_dir(temp_dir.path())
+        .arg("generate")
+        .arg("--log-format")
+        .arg("json")
+        .arg("-i")
+        .arg(&input_file)
+        .arg("-o")
+        .arg("graph.json")
+        .arg("-f")
+        .arg("json");
+
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let stdout = String::from_utf8(output).expect("stdout is not UTF-8");
+
+    assert!(!stdout.trim().is_empty());
+    for line in stdout.lines() {
+        let record: serde_json::Value =
+            serde_json::from_str(line).expect("Log line is not valid JSON");
+        assert!(record["fields"]["message"].is_string());
+    }
+    assert!(stdout.contains("Graph exported successfully"));
+}
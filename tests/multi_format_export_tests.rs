@@ -0,0 +1,132 @@
+use assert_cmd::Command;
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_several_formats_are_written_side_by_side() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("input.txt"), "Alice works at TechCorp. Alice is connected to Bob.")
+        .expect("Failed to write test file");
+
+    Command::cargo_bin("msg_net")
+        .expect("Failed to find binary")
+        .current_dir(temp_dir.path())
+        .args(["-q", "generate", "-i", "input.txt", "-o", "graph.html", "-f", "html, graphml,json,report,html"])
+        .assert()
+        .success();
+
+    let networks = temp_dir.path().join("0_networks");
+    let mut written: Vec<String> = fs::read_dir(&networks)
+        .expect("No output directory")
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    written.sort();
+    assert_eq!(written, vec!["graph.graphml", "graph.html", "graph.json", "graph_report.html"]);
+
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(networks.join("graph.json")).unwrap()).expect("Invalid JSON");
+    let graphml = fs::read_to_string(networks.join("graph.graphml")).unwrap();
+    assert_eq!(graphml.matches("<node ").count(), json["nodes"].as_array().unwrap().len());
+    assert!(fs::read_to_string(networks.join("graph_report.html")).unwrap().contains("Top Entities"));
+}
+
+#[test]
+fn test_an_unknown_format_in_the_list_fails_before_writing() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("input.txt"), "Alice works at TechCorp.").expect("Failed to write test file");
+
+    Command::cargo_bin("msg_net")
+        .expect("Failed to find binary")
+        .current_dir(temp_dir.path())
+        .args(["-q", "generate", "-i", "input.txt", "-o", "graph.html", "-f", "html,pdf"])
+        .assert()
+        .failure();
+    assert!(!temp_dir.path().join("0_networks").exists());
+}
+
+#[test]
+fn test_share_url_prints_the_link_and_writes_its_viewer() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("input.txt"), "Alice works at TechCorp. Alice is connected to Bob.")
+        .expect("Failed to write test file");
+
+    let output = Command::cargo_bin("msg_net")
+        .expect("Failed to find binary")
+        .current_dir(temp_dir.path())
+        .args(["-q", "generate", "-i", "input.txt", "-o", "graph.html", "-f", "html,share-url"])
+        .output()
+        .expect("Failed to run msg_net");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let networks = temp_dir.path().join("0_networks");
+    let link = fs::read_to_string(networks.join("graph.txt")).expect("Link was not written");
+    assert!(link.starts_with("msg_net_viewer.html#g="));
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), link.trim());
+    let payload = msg_net::decode_share_fragment(&link).expect("Link does not decode");
+    assert!(payload["n"].as_array().unwrap().iter().any(|node| node[0] == "Alice"));
+
+    let viewer = fs::read_to_string(networks.join("msg_net_viewer.html")).expect("Viewer was not written");
+    assert!(!viewer.contains("window.graphData = "));
+}
+
+#[test]
+fn test_deterministic_runs_write_identical_files() {
+    let text = "Alice works at TechCorp. Alice is connected to Bob. Bob manages the database system.";
+    let run = || {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        fs::write(temp_dir.path().join("input.txt"), text).expect("Failed to write test file");
+        Command::cargo_bin("msg_net")
+            .expect("Failed to find binary")
+            .current_dir(temp_dir.path())
+            .args(["-q", "generate", "-i", "input.txt", "-o", "graph.json", "-f", "json,html,dot", "--include-metadata"])
+            .arg("--deterministic")
+            .assert()
+            .success();
+        let networks = temp_dir.path().join("0_networks");
+        ["graph.json", "graph.html", "graph.dot"].map(|name| fs::read_to_string(networks.join(name)).unwrap())
+    };
+
+    let first = run();
+    assert_eq!(first, run());
+    let json: serde_json::Value = serde_json::from_str(&first[0]).expect("Invalid JSON");
+    assert!(json["nodes"].as_array().unwrap().iter().all(|node| node["id"].as_str().unwrap().contains('-')));
+    assert!(json["nodes"][0]["id"].as_str().unwrap().starts_with("entity-"));
+    assert_eq!(json["metadata"]["creation_timestamp"], "1970-01-01T00:00:00+00:00");
+    assert_eq!(json["metadata"]["provenance"]["config_summary"]["deterministic"], "true");
+}
+
+#[test]
+fn test_deterministic_runs_with_styling_maps_write_identical_files() {
+    let text = "Alice works at TechCorp. Alice is connected to Bob. Bob manages the database system.";
+    let mut config = msg_net::config::GraphConfig::default();
+    for (name, color) in [("Person", "#E74C3C"), ("Organization", "#3498DB"), ("Product", "#2ECC71"), ("Place", "#F39C12")] {
+        config.styling.entity_type_colors.insert(name.to_string(), color.to_string());
+        config.styling.attribute_edge_colors.insert(format!("{}Attribute", name), color.to_string());
+        config.styling.edge_styles.insert(
+            name.to_string(),
+            msg_net::config::EdgeStyle { color: Some(color.to_string()), ..Default::default() },
+        );
+    }
+    let config = serde_json::to_string(&config).unwrap();
+    // Each run hashes its maps with a new seed, so any key order leaking into the output
+    // or the config_id shows up as a difference
+    let run = || {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        fs::write(temp_dir.path().join("input.txt"), text).expect("Failed to write test file");
+        fs::write(temp_dir.path().join("config.json"), &config).expect("Failed to write config file");
+        Command::cargo_bin("msg_net")
+            .expect("Failed to find binary")
+            .current_dir(temp_dir.path())
+            .args(["-q", "generate", "-i", "input.txt", "-o", "graph.json", "-f", "json,html,dot", "--include-metadata"])
+            .args(["-c", "config.json", "--deterministic"])
+            .assert()
+            .success();
+        let networks = temp_dir.path().join("0_networks");
+        ["graph.json", "graph.html", "graph.dot"].map(|name| fs::read(networks.join(name)).unwrap())
+    };
+
+    let first = run();
+    for _ in 0..3 {
+        assert!(first == run(), "deterministic runs with the same config differ");
+    }
+}
@@ -0,0 +1,37 @@
+use assert_cmd::Command;
+use tempfile::TempDir;
+use std::fs;
+
+#[test]
+fn test_obsidian_command_builds_graph_and_writes_entity_notes() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let vault = temp_dir.path().join("vault");
+    fs::create_dir_all(vault.join("projects")).expect("Failed to create vault");
+    fs::write(vault.join("projects").join("Apollo.md"), "Alice is connected to Bob on [[Roadmap]].").expect("Failed to write note");
+    fs::write(vault.join("Bob.md"), "---\ntags: [person]\n---\nBob reviews [[projects/Apollo|the project]].").expect("Failed to write note");
+
+    let mut cmd = Command::cargo_bin("msg_net").expect("Failed to find binary");
+    cmd.current_dir(temp_dir.path())
+        .arg("-q")
+        .arg("obsidian")
+        .arg("-i")
+        .arg(&vault)
+        .arg("-o")
+        .arg("vault.json")
+        .arg("-f")
+        .arg("json")
+        .arg("--write-notes");
+    cmd.assert().success();
+
+    let json = fs::read_to_string(temp_dir.path().join("0_networks").join("vault.json")).expect("Graph was not written");
+    let graph: serde_json::Value = serde_json::from_str(&json).expect("Graph is not valid JSON");
+    let labels: Vec<&str> = graph["nodes"].as_array().unwrap().iter().filter_map(|node| node["label"].as_str()).collect();
+    for label in ["Apollo", "Bob", "Roadmap", "Alice"] {
+        assert!(labels.contains(&label), "missing {}", label);
+    }
+    assert!(graph["edges"].as_array().unwrap().iter().any(|edge| edge["label"] == "links to"));
+
+    let alice = fs::read_to_string(vault.join("msg_net").join("Alice.md")).expect("Entity note was not written");
+    assert!(alice.contains("- [[Apollo]]"));
+    assert!(!vault.join("msg_net").join("Bob.md").exists());
+}
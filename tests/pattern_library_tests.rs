@@ -0,0 +1,119 @@
+//! Precision of the built-in relationship pattern library on a small labelled corpus
+
+use msg_net::{
+    EntityExtractor, ExtractionConfig, PatternCategory, SourceType, TextProcessor, RULE_CONFIDENCE,
+};
+use std::collections::HashMap;
+
+/// Minimum share of library relationships that must be right, per category
+const MIN_PRECISION: f64 = 0.9;
+/// Minimum share of the expected relationships the library must find, per category
+const MIN_RECALL: f64 = 0.8;
+
+/// Expected (subject, label, object); `None` marks sentences the library must not turn
+/// into a relationship
+type Expected = Option<(&'static str, &'static str, &'static str)>;
+
+const CORPUS: &[(PatternCategory, &str, Expected)] = &[
+    (PatternCategory::Employment, "Alice works at Initech.", Some(("Alice", "works at", "Initech"))),
+    (PatternCategory::Employment, "Bob Jones worked for Globex before the merger.", Some(("Bob Jones", "works at", "Globex"))),
+    (PatternCategory::Employment, "Initech hired Carol last spring.", Some(("Carol", "works at", "Initech"))),
+    (PatternCategory::Employment, "Dana was recruited by Umbrella in March.", Some(("Dana", "works at", "Umbrella"))),
+    (PatternCategory::Employment, "Erin, the CEO of Hooli, resigned.", Some(("Erin", "works at", "Hooli"))),
+    (PatternCategory::Employment, "Frank founded Vandelay in his garage.", Some(("Frank", "founded", "Vandelay"))),
+    (PatternCategory::Employment, "Grace joined Stark Industries as an intern.", Some(("Grace", "joined", "Stark Industries"))),
+    (PatternCategory::Employment, "Heidi left Wayne Enterprises after a decade.", Some(("Heidi", "left", "Wayne Enterprises"))),
+    (PatternCategory::Employment, "Ivan works hard every day.", None),
+    (PatternCategory::Employment, "Judy thinks Initech is a fine place.", None),
+    (PatternCategory::Ownership, "Globex acquired Initech for two billion.", Some(("Globex", "acquired", "Initech"))),
+    (PatternCategory::Ownership, "Instagram was bought by Facebook in the deal.", Some(("Facebook", "acquired", "Instagram"))),
+    (PatternCategory::Ownership, "Karl owns the Bluebird Cafe.", Some(("Karl", "owns", "Bluebird Cafe"))),
+    (PatternCategory::Ownership, "The Red Barn is owned by Laura.", Some(("Laura", "owns", "The Red Barn"))),
+    (PatternCategory::Ownership, "The Silver Yacht belongs to Mallory.", Some(("Mallory", "owns", "The Silver Yacht"))),
+    (PatternCategory::Ownership, "Niaj bought groceries near Oscar.", None),
+    (PatternCategory::Location, "Peggy lives in Lisbon.", Some(("Peggy", "lives in", "Lisbon"))),
+    (PatternCategory::Location, "Initech is headquartered in Austin.", Some(("Initech", "located in", "Austin"))),
+    (PatternCategory::Location, "Rupert moved to Berlin in 2019.", Some(("Rupert", "moved to", "Berlin"))),
+    (PatternCategory::Location, "Sybil was born in Dublin.", Some(("Sybil", "born in", "Dublin"))),
+    (PatternCategory::Location, "Trent visited Kyoto with friends.", Some(("Trent", "visited", "Kyoto"))),
+    (PatternCategory::Location, "Uma lives happily with her cat.", None),
+    (PatternCategory::Family, "Victor is married to Wendy.", Some(("Victor", "married to", "Wendy"))),
+    (PatternCategory::Family, "Xavier is the father of Yolanda.", Some(("Xavier", "parent of", "Yolanda"))),
+    (PatternCategory::Family, "Zoe is the daughter of Arthur.", Some(("Zoe", "child of", "Arthur"))),
+    (PatternCategory::Family, "Beth is the sister of Caleb.", Some(("Beth", "sibling of", "Caleb"))),
+    (PatternCategory::Family, "Diana's mother, Eleanor, arrived late.", Some(("Eleanor", "parent of", "Diana"))),
+    (PatternCategory::Family, "Fiona is the manager of Gordon.", None),
+    (PatternCategory::Communication, "Hank emailed Irene about the budget.", Some(("Hank", "wrote to", "Irene"))),
+    (PatternCategory::Communication, "Jack called Kate twice yesterday.", Some(("Jack", "called", "Kate"))),
+    (PatternCategory::Communication, "Liam spoke with Mia after lunch.", Some(("Liam", "spoke with", "Mia"))),
+    (PatternCategory::Communication, "Noah met Olivia at the conference.", Some(("Noah", "met", "Olivia"))),
+    (PatternCategory::Communication, "Paul warned Quinn about the outage.", Some(("Paul", "informed", "Quinn"))),
+    (PatternCategory::Communication, "Rosa called it a day.", None),
+    (PatternCategory::Causation, "The Storm caused the Blackout.", Some(("The Storm", "caused", "Blackout"))),
+    (PatternCategory::Causation, "The Outage was triggered by Hermes.", Some(("Hermes", "caused", "The Outage"))),
+    (PatternCategory::Causation, "Kubernetes depends on Etcd.", Some(("Kubernetes", "depends on", "Etcd"))),
+    (PatternCategory::Causation, "Cloudflare prevented the Attack.", Some(("Cloudflare", "prevented", "Attack"))),
+    (PatternCategory::Causation, "Sam and Tina discussed the weather.", None),
+];
+
+/// Library relationships found in `sentence` as (subject, label, object)
+async fn library_relationships(extractor: &EntityExtractor, sentence: &str) -> Vec<(String, String, String)> {
+    let processed = TextProcessor::new()
+        .expect("Failed to create processor")
+        .process_text(sentence, SourceType::Document)
+        .expect("Failed to process text");
+    let result = extractor.extract_from_text(&processed).await.expect("Extraction failed");
+    let names: HashMap<&str, &str> = result.entities.iter().map(|e| (e.id.as_str(), e.name.as_str())).collect();
+    result
+        .relationships
+        .iter()
+        .filter(|r| r.confidence == RULE_CONFIDENCE)
+        .map(|r| {
+            (
+                names[r.source_entity_id.as_str()].to_string(),
+                r.label.clone(),
+                names[r.target_entity_id.as_str()].to_string(),
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_pattern_library_precision_and_recall_per_category() {
+    let extractor = EntityExtractor::new(ExtractionConfig::default()).expect("Failed to create extractor");
+
+    for category in PatternCategory::ALL {
+        let (mut found, mut correct, mut expected) = (0, 0, 0);
+        for (_, sentence, truth) in CORPUS.iter().filter(|(c, _, _)| *c == category) {
+            let relationships = library_relationships(&extractor, sentence).await;
+            found += relationships.len();
+            if let Some((subject, label, object)) = truth {
+                expected += 1;
+                let hit = relationships.iter().any(|(s, l, o)| s == subject && l == label && o == object);
+                correct += usize::from(hit);
+                if !hit {
+                    println!("{:?} missed in {:?}: {:?}", category, sentence, relationships);
+                }
+            } else if !relationships.is_empty() {
+                println!("{:?} false positive in {:?}: {:?}", category, sentence, relationships);
+            }
+        }
+
+        let precision = correct as f64 / found.max(1) as f64;
+        let recall = correct as f64 / expected as f64;
+        println!("{:?}: precision {:.2}, recall {:.2}", category, precision, recall);
+        assert!(precision >= MIN_PRECISION, "{:?} precision {:.2}", category, precision);
+        assert!(recall >= MIN_RECALL, "{:?} recall {:.2}", category, recall);
+    }
+}
+
+#[tokio::test]
+async fn test_disabled_categories_add_nothing() {
+    let config = ExtractionConfig {
+        pattern_library: vec![PatternCategory::Family],
+        ..ExtractionConfig::default()
+    };
+    let extractor = EntityExtractor::new(config).expect("Failed to create extractor");
+    assert!(library_relationships(&extractor, "Alice works at Initech.").await.is_empty());
+    assert_eq!(library_relationships(&extractor, "Victor is married to Wendy.").await.len(), 1);
+}
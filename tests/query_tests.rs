@@ -0,0 +1,98 @@
+use assert_cmd::Command;
+use std::fs;
+use tempfile::TempDir;
+
+const EXPRESSION: &str = "type:Person AND (label~/^(alice|bob)/ OR label:berlin) AND NOT label~smith";
+
+fn labels(path: &std::path::Path) -> Vec<String> {
+    let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).expect("Invalid JSON");
+    let mut labels: Vec<String> =
+        json["nodes"].as_array().unwrap().iter().map(|node| node["label"].as_str().unwrap().to_string()).collect();
+    labels.sort();
+    labels
+}
+
+#[test]
+fn test_query_and_generate_filter_select_the_same_subgraph() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(
+        temp_dir.path().join("input.txt"),
+        "Alice Smith works at Initech in Berlin. Alice Smith met Bob Jones in Berlin. Bob Jones called Carol White.",
+    )
+    .expect("Failed to write test file");
+    let msg_net = || {
+        let mut command = Command::cargo_bin("msg_net").expect("Failed to find binary");
+        command.current_dir(temp_dir.path());
+        command
+    };
+
+    msg_net()
+        .args(["-q", "generate", "-i", "input.txt", "-o", "graph.json", "-f", "json", "--include-metadata"])
+        .assert()
+        .success();
+    let networks = temp_dir.path().join("0_networks");
+
+    msg_net()
+        .args(["-q", "query", "-g", "0_networks/graph.json", "-e", EXPRESSION, "-o", "selected.json"])
+        .assert()
+        .success();
+    let selected = labels(&networks.join("selected.json"));
+    assert_eq!(selected, vec!["Berlin", "Bob Jones"]);
+
+    msg_net()
+        .args(["-q", "generate", "-i", "input.txt", "-o", "filtered.json", "-f", "json"])
+        .args(["--filter", EXPRESSION])
+        .assert()
+        .success();
+    assert_eq!(labels(&networks.join("filtered.json")), selected);
+
+    msg_net()
+        .args(["-q", "query", "-g", "0_networks/graph.json", "-e", "type:Person AND", "-o", "broken.json"])
+        .assert()
+        .failure();
+    assert!(!networks.join("broken.json").exists());
+}
+
+#[test]
+fn test_path_prints_the_shortest_route_and_highlights_it() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("input.txt"), "Alice is connected to Bob. Dave has Erin.").expect("Failed to write test file");
+    let msg_net = || {
+        let mut command = Command::cargo_bin("msg_net").expect("Failed to find binary");
+        command.current_dir(temp_dir.path());
+        command
+    };
+    msg_net()
+        .args(["-q", "generate", "-i", "input.txt", "-o", "graph.json", "-f", "json", "--include-metadata"])
+        .assert()
+        .success();
+
+    // Join the two sentences' pairs with a "Bob knows Dave" edge
+    let networks = temp_dir.path().join("0_networks");
+    let mut graph: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(networks.join("graph.json")).unwrap()).expect("Invalid JSON");
+    let id = |label: &str| {
+        graph["nodes"].as_array().unwrap().iter().find(|node| node["label"] == label).expect(label)["id"].clone()
+    };
+    let mut knows = graph["edges"][0].clone();
+    knows["id"] = "knows".into();
+    (knows["from"], knows["to"], knows["label"]) = (id("Bob"), id("Dave"), "knows".into());
+    knows["metadata"]["bidirectional"] = false.into();
+    graph["edges"].as_array_mut().unwrap().push(knows);
+    fs::write(networks.join("joined.json"), graph.to_string()).unwrap();
+
+    let output = msg_net()
+        .args(["-q", "path", "-g", "0_networks/joined.json", "--from", "alice", "--to", "Erin", "--highlight", "path.html"])
+        .output()
+        .expect("Failed to run msg_net");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("1 shortest path of 3 steps:"), "{}", stdout);
+    assert!(stdout.contains("Alice <--is connected to--> Bob --knows--> Dave --has--> Erin"), "{}", stdout);
+    assert!(fs::read_to_string(networks.join("path.html")).unwrap().contains("#E74C3C"));
+
+    msg_net()
+        .args(["-q", "path", "-g", "0_networks/joined.json", "--from", "Alice", "--to", "Nobody"])
+        .assert()
+        .failure();
+}
@@ -0,0 +1,59 @@
+use assert_cmd::Command;
+use std::fs;
+use tempfile::TempDir;
+
+fn graph_json(path: &std::path::Path) -> serde_json::Value {
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).expect("Invalid JSON")
+}
+
+fn labels(graph: &serde_json::Value) -> Vec<String> {
+    let mut labels: Vec<String> =
+        graph["nodes"].as_array().unwrap().iter().map(|node| node["label"].as_str().unwrap().to_string()).collect();
+    labels.sort();
+    labels
+}
+
+#[test]
+fn test_render_converts_and_relays_out_a_json_export() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(
+        temp_dir.path().join("input.txt"),
+        "Alice Smith works at Initech in Berlin. Alice Smith met Bob Jones in Berlin. Bob Jones called Carol White.",
+    )
+    .expect("Failed to write test file");
+    let msg_net = || {
+        let mut command = Command::cargo_bin("msg_net").expect("Failed to find binary");
+        command.current_dir(temp_dir.path());
+        command
+    };
+    let networks = temp_dir.path().join("0_networks");
+
+    // A plain export, without metadata, is enough to render from
+    msg_net().args(["-q", "generate", "-i", "input.txt", "-o", "graph.json", "-f", "json"]).assert().success();
+    let original = graph_json(&networks.join("graph.json"));
+
+    msg_net()
+        .args(["-q", "render", "-g", "0_networks/graph.json", "-o", "rendered.html", "-f", "html,graphml"])
+        .assert()
+        .success();
+    let html = fs::read_to_string(networks.join("rendered.html")).expect("HTML not written");
+    assert!(html.contains("Bob Jones"));
+    assert!(networks.join("rendered.graphml").exists());
+
+    msg_net()
+        .args(["-q", "render", "-g", "0_networks/graph.json", "-o", "circular.json", "-f", "json"])
+        .args(["--layout", "circular", "--include-metadata"])
+        .assert()
+        .success();
+    let circular = graph_json(&networks.join("circular.json"));
+    assert_eq!(labels(&circular), labels(&original));
+    assert_eq!(circular["edges"].as_array().unwrap().len(), original["edges"].as_array().unwrap().len());
+    assert_eq!(circular["config"]["layout"]["algorithm"], "circular");
+    assert!(circular["nodes"].as_array().unwrap().iter().all(|node| node["x"].is_number() && node["y"].is_number()));
+
+    msg_net()
+        .args(["-q", "render", "-g", "input.txt", "-o", "broken.html"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("is not a graph JSON export"));
+}
@@ -0,0 +1,44 @@
+use assert_cmd::Command;
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_report_writes_markdown_and_html_dossiers_from_a_json_export() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(
+        temp_dir.path().join("input.txt"),
+        "Alice Smith works at Initech in Berlin. Alice Smith met Bob Jones in Berlin. Bob Jones called Carol White.",
+    )
+    .expect("Failed to write test file");
+    let msg_net = || {
+        let mut command = Command::cargo_bin("msg_net").expect("Failed to find binary");
+        command.current_dir(temp_dir.path());
+        command
+    };
+    let networks = temp_dir.path().join("0_networks");
+
+    msg_net().args(["-q", "generate", "-i", "input.txt", "-o", "graph.json", "-f", "json"]).assert().success();
+
+    msg_net()
+        .args(["-q", "report", "-g", "0_networks/graph.json", "-o", "report.md", "-i", "input.txt"])
+        .assert()
+        .success();
+    let markdown = fs::read_to_string(networks.join("report.md")).expect("Markdown report not written");
+    assert!(markdown.contains("## Top Entities"));
+    assert!(markdown.contains("### Bob Jones"));
+    assert!(markdown.contains("**Supporting sentences**"));
+    assert!(markdown.contains("> Bob Jones called Carol White"));
+
+    // Without the source text there is nothing to quote
+    msg_net().args(["-q", "report", "-g", "0_networks/graph.json", "-o", "report.html"]).assert().success();
+    let html = fs::read_to_string(networks.join("report.html")).expect("HTML report not written");
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<th>PageRank</th>"));
+    assert!(!html.contains("Supporting sentences"));
+
+    msg_net()
+        .args(["-q", "report", "-g", "input.txt", "-o", "broken.md"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("is not a graph JSON export"));
+}
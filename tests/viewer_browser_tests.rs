@@ -0,0 +1,334 @@
+//! Headless-browser regression tests for the generated HTML viewer.
+//!
+//! These drive a real browser through WebDriver, so they are ignored by default.
+//! To run them, start a driver and point the tests at it:
+//!
+//!   chromedriver --port=4444 &
+//!   WEBDRIVER_URL=http://localhost:4444 cargo test --test viewer_browser_tests -- --ignored
+//!
+//! The viewer loads vis-network from unpkg, so the browser needs network access.
+
+use fantoccini::{Client, ClientBuilder};
+use msg_net::{
+    encode_share_fragment, EntityExtractor, GraphBuilder, InteractiveGraph, NodeType, PageOptions, SourceType,
+    TextProcessor, WebInterface,
+};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tempfile::TempDir;
+
+const TEST_TEXT: &str = "Alice is connected to Bob. Bob has Carol. The system uses a process.";
+
+async fn build_graph() -> InteractiveGraph {
+    let processor = TextProcessor::new().expect("Failed to create processor");
+    let processed = processor
+        .process_text(TEST_TEXT, SourceType::Document)
+        .expect("Failed to process text");
+    let extraction = EntityExtractor::default()
+        .extract_from_text(&processed)
+        .await
+        .expect("Failed to extract entities");
+    GraphBuilder::default()
+        .build_graph(&extraction, TEST_TEXT)
+        .expect("Failed to build graph")
+}
+
+/// Render the graph into an HTML file and open it in a WebDriver session
+async fn open_viewer(graph: &InteractiveGraph, dir: &TempDir) -> Client {
+    let options = PageOptions { title: "Viewer Test".to_string(), ..PageOptions::default() };
+    let html = WebInterface::new("graph-container".to_string())
+        .render_page(Some(graph), &options)
+        .expect("Failed to render HTML");
+    let html_path = dir.path().join("viewer.html");
+    std::fs::write(&html_path, html).expect("Failed to write HTML");
+
+    let client = connect().await;
+    client
+        .goto(&format!("file://{}", html_path.display()))
+        .await
+        .expect("Failed to open viewer");
+    wait_for_network(&client).await;
+    client
+}
+
+async fn connect() -> Client {
+    let webdriver_url =
+        std::env::var("WEBDRIVER_URL").unwrap_or_else(|_| "http://localhost:4444".to_string());
+    let mut capabilities = serde_json::Map::new();
+    capabilities.insert(
+        "goog:chromeOptions".to_string(),
+        json!({ "args": ["--headless", "--no-sandbox", "--disable-gpu"] }),
+    );
+    ClientBuilder::native()
+        .capabilities(capabilities)
+        .connect(&webdriver_url)
+        .await
+        .expect("Failed to connect to WebDriver")
+}
+
+async fn wait_for_network(client: &Client) {
+    for _ in 0..50 {
+        let ready = client
+            .execute("return typeof currentNetwork !== 'undefined' && currentNetwork !== null;", vec![])
+            .await
+            .expect("Failed to query viewer state");
+        if ready == Value::Bool(true) {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    panic!("Viewer never initialized the network");
+}
+
+async fn rendered_node_count(client: &Client) -> u64 {
+    client
+        .execute("return currentNetwork.body.data.nodes.length;", vec![])
+        .await
+        .expect("Failed to count nodes")
+        .as_u64()
+        .expect("Node count is not a number")
+}
+
+#[tokio::test]
+#[ignore = "requires a WebDriver server (see module docs)"]
+async fn test_viewer_renders_all_nodes() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let graph = build_graph().await;
+    let client = open_viewer(&graph, &dir).await;
+
+    assert!(!graph.nodes.is_empty());
+    assert_eq!(rendered_node_count(&client).await, graph.nodes.len() as u64);
+
+    let edge_count = client
+        .execute("return currentNetwork.body.data.edges.length;", vec![])
+        .await
+        .expect("Failed to count edges");
+    assert_eq!(edge_count.as_u64(), Some(graph.edges.len() as u64));
+
+    client.close().await.expect("Failed to close session");
+}
+
+#[tokio::test]
+#[ignore = "requires a WebDriver server (see module docs)"]
+async fn test_viewer_node_type_filter() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let graph = build_graph().await;
+    let client = open_viewer(&graph, &dir).await;
+
+    let entity_count = graph
+        .nodes
+        .iter()
+        .filter(|node| matches!(node.node_type, NodeType::Entity))
+        .count() as u64;
+
+    client
+        .execute("filterNodes('entity');", vec![])
+        .await
+        .expect("Failed to filter nodes");
+    assert_eq!(rendered_node_count(&client).await, entity_count);
+
+    client
+        .execute("filterNodes('');", vec![])
+        .await
+        .expect("Failed to reset filter");
+    assert_eq!(rendered_node_count(&client).await, graph.nodes.len() as u64);
+
+    client.close().await.expect("Failed to close session");
+}
+
+#[tokio::test]
+#[ignore = "requires a WebDriver server (see module docs)"]
+async fn test_viewer_legend_hides_several_types_at_once() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let graph = build_graph().await;
+    let client = open_viewer(&graph, &dir).await;
+
+    let rows = client
+        .execute(
+            "return [...document.querySelectorAll('#legend-items input')].map(box => box.dataset.type);",
+            vec![],
+        )
+        .await
+        .expect("Failed to read legend");
+    let rows: Vec<String> = serde_json::from_value(rows).expect("Legend rows are not strings");
+    assert!(rows.contains(&"Concept".to_string()), "{:?}", rows);
+    let count = client
+        .execute("return document.querySelector('#legend-items .legend-count[data-type=\"Concept\"]').textContent;", vec![])
+        .await
+        .expect("Failed to read legend count");
+    let concepts = graph.nodes.iter().filter(|node| matches!(node.node_type, NodeType::Concept)).count();
+    assert_eq!(count, json!(format!("{} / {}", concepts, concepts)));
+
+    client
+        .execute("toggleLegendType('Concept', false); toggleLegendType('Attribute', false);", vec![])
+        .await
+        .expect("Failed to untick legend rows");
+    let kept = graph
+        .nodes
+        .iter()
+        .filter(|node| !matches!(node.node_type, NodeType::Concept | NodeType::Attribute))
+        .count() as u64;
+    assert_eq!(rendered_node_count(&client).await, kept);
+    let count = client
+        .execute("return document.querySelector('#legend-items .legend-count[data-type=\"Concept\"]').textContent;", vec![])
+        .await
+        .expect("Failed to read legend count");
+    assert_eq!(count, json!(format!("0 / {}", concepts)));
+
+    client
+        .execute("setAllLegendTypes(true);", vec![])
+        .await
+        .expect("Failed to tick legend rows");
+    assert_eq!(rendered_node_count(&client).await, graph.nodes.len() as u64);
+
+    client.close().await.expect("Failed to close session");
+}
+
+#[tokio::test]
+#[ignore = "requires a WebDriver server (see module docs)"]
+async fn test_viewer_node_selection_shows_details() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let graph = build_graph().await;
+    let client = open_viewer(&graph, &dir).await;
+
+    let node = &graph.nodes[0];
+    let details = client
+        .execute(
+            "onNodeSelected(arguments[0]); return document.getElementById('node-details').textContent;",
+            vec![json!(node.id)],
+        )
+        .await
+        .expect("Failed to select node");
+    let details = details.as_str().expect("Details are not text");
+    assert!(details.contains(&node.id));
+    assert!(details.contains(&node.label));
+
+    let display = client
+        .execute("return document.getElementById('node-info').style.display;", vec![])
+        .await
+        .expect("Failed to read panel state");
+    assert_eq!(display, json!("block"));
+
+    client.close().await.expect("Failed to close session");
+}
+
+#[tokio::test]
+#[ignore = "requires a WebDriver server (see module docs)"]
+async fn test_viewer_physics_sliders_update_network_and_snippet() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let graph = build_graph().await;
+    let client = open_viewer(&graph, &dir).await;
+
+    let node_distance = client
+        .execute(
+            "const slider = document.getElementById('repulsionSlider');
+             slider.value = 420;
+             slider.dispatchEvent(new Event('input'));
+             return currentNetwork.physics.options.repulsion.nodeDistance;",
+            vec![],
+        )
+        .await
+        .expect("Failed to move slider");
+    assert_eq!(node_distance.as_f64(), Some(420.0));
+
+    let snippet = client
+        .execute("return physicsSnippet();", vec![])
+        .await
+        .expect("Failed to build snippet");
+    let snippet = snippet.as_str().expect("Snippet is not text");
+    let physics: Value = serde_json::from_str(snippet.trim_start_matches("\"physics\": ")).expect("Snippet is not JSON");
+    assert_eq!(physics["repulsion"].as_f64(), Some(420.0));
+    assert_eq!(physics["spring_length"].as_f64(), Some(graph.config.physics.spring_length));
+
+    client.close().await.expect("Failed to close session");
+}
+
+#[tokio::test]
+#[ignore = "requires a WebDriver server (see module docs)"]
+async fn test_viewer_exports_svg_and_png() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let graph = build_graph().await;
+    let client = open_viewer(&graph, &dir).await;
+
+    let svg = client.execute("return graphToSvg();", vec![]).await.expect("Failed to build SVG");
+    let svg = svg.as_str().expect("SVG is not text");
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert_eq!(svg.matches("<g class=\"node\">").count() as u64, rendered_node_count(&client).await);
+    let parsed = client
+        .execute(
+            "const doc = new DOMParser().parseFromString(arguments[0], 'image/svg+xml');
+             return doc.getElementsByTagName('parsererror').length;",
+            vec![json!(svg)],
+        )
+        .await
+        .expect("Failed to parse SVG");
+    assert_eq!(parsed.as_u64(), Some(0));
+
+    // Capture the download instead of saving it
+    let png = client
+        .execute_async(
+            "const done = arguments[arguments.length - 1];
+             window.downloadBlob = (blob, name) => done([name, blob.type, blob.size]);
+             exportGraph('png');",
+            vec![],
+        )
+        .await
+        .expect("Failed to export PNG");
+    assert_eq!(png[0], "graph_export.png");
+    assert_eq!(png[1], "image/png");
+    assert!(png[2].as_u64().unwrap_or(0) > 0);
+
+    client.close().await.expect("Failed to close session");
+}
+
+#[tokio::test]
+#[ignore = "requires a WebDriver server (see module docs)"]
+async fn test_viewer_opens_share_links() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let graph = build_graph().await;
+    let options = PageOptions { title: "Shared".to_string(), ..PageOptions::default() };
+    let html = WebInterface::new("graph-container".to_string())
+        .render_page(None, &options)
+        .expect("Failed to render HTML");
+    let html_path = dir.path().join("msg_net_viewer.html");
+    std::fs::write(&html_path, html).expect("Failed to write HTML");
+    let fragment = encode_share_fragment(&graph).expect("Failed to encode graph");
+
+    let client = connect().await;
+    client
+        .goto(&format!("file://{}#g={}", html_path.display(), fragment))
+        .await
+        .expect("Failed to open share link");
+    wait_for_network(&client).await;
+    assert_eq!(rendered_node_count(&client).await, graph.nodes.len() as u64);
+    let labels = client
+        .execute("return currentNetwork.body.data.nodes.get().map(node => node.label);", vec![])
+        .await
+        .expect("Failed to read labels");
+    assert!(labels.as_array().unwrap().contains(&json!(graph.nodes[0].label)));
+
+    client.close().await.expect("Failed to close session");
+}
+
+#[tokio::test]
+#[ignore = "requires a WebDriver server (see module docs)"]
+async fn test_viewer_theme_toggle_switches_the_palette() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let graph = build_graph().await;
+    let client = open_viewer(&graph, &dir).await;
+    let palette = "return [document.documentElement.dataset.theme, \
+        getComputedStyle(document.querySelector('.graph-container')).backgroundColor, \
+        currentNetwork.body.data.edges.get().find(edge => edge.baseColor === '#CCCCCC')?.color];";
+
+    let light = client.execute(palette, vec![]).await.expect("Failed to read palette");
+    client.execute("toggleTheme();", vec![]).await.expect("Failed to toggle theme");
+    let dark = client.execute(palette, vec![]).await.expect("Failed to read palette");
+    assert_eq!(light[0], "light");
+    assert_eq!(dark[0], "dark");
+    assert_ne!(light[1], dark[1]);
+    if !light[2].is_null() {
+        assert_eq!(dark[2], "#5F6368");
+    }
+
+    client.close().await.expect("Failed to close session");
+}